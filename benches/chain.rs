@@ -0,0 +1,80 @@
+//! Benchmarks of the hot paths of the chain: mining, adding and verifying blocks, and
+//! serializing chains and transactions. Run with `cargo bench`.
+
+use blockchain::{
+    bench::bench::bench,
+    chain::chain::chain::Chain,
+    store::codec::codec::Codec,
+    transaction::transaction::transaction::Transaction,
+};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+/// Lengths of the chains verified and serialized.
+const CHAIN_LENGTHS: [usize; 3] = [10, 100, 1000];
+
+fn mining(c: &mut Criterion) {
+    let chain = Chain::new();
+    let mut group = c.benchmark_group("mine");
+    group.sample_size(10);
+    for difficulty in 1..=4 {
+        let mut miner = bench::miner_for(&chain);
+        miner.set_chain_meta(chain.get_len(), difficulty, chain.get_blocks());
+        group.throughput(Throughput::Elements(1));
+        group.bench_with_input(BenchmarkId::from_parameter(difficulty), &difficulty, |b, _| {
+            b.iter(|| miner.mine(chain.get_last_block()).expect("the chain meta is set"))
+        });
+    }
+    group.finish();
+}
+
+fn add_block(c: &mut Criterion) {
+    let chain = bench::synthetic_chain(10);
+    let digest = bench::mine_next(&chain, &mut bench::miner_for(&chain));
+    c.bench_function("add_block", |b| {
+        b.iter_batched(
+            || (chain.clone(), digest.clone()),
+            |(mut chain, digest)| chain.add_block(digest).expect("the block was mined on this chain"),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn verify_chain(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_chain");
+    for len in CHAIN_LENGTHS {
+        let chain = bench::synthetic_chain(len);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &chain, |b, chain| {
+            b.iter(|| chain.verify_chain().expect("synthetic chains are valid"))
+        });
+    }
+    group.finish();
+}
+
+fn serialization(c: &mut Criterion) {
+    let mut group = c.benchmark_group("round_trip");
+    for len in CHAIN_LENGTHS {
+        let chain = bench::synthetic_chain(len);
+        for codec in [Codec::Json, Codec::Bincode] {
+            let name = format!("{:?}", codec).to_lowercase();
+            group.bench_with_input(BenchmarkId::new(name, len), &chain, |b, chain| {
+                b.iter(|| {
+                    let encoded = codec.encode(chain).expect("chains always encode");
+                    codec.decode::<Chain>(&encoded).expect("encoded chains decode")
+                })
+            });
+        }
+    }
+    let transaction = bench::signed_transaction();
+    group.bench_function("transaction_wire", |b| {
+        b.iter(|| {
+            let wire: String = transaction.clone().into();
+            Transaction::try_from(wire).expect("wire transactions decode")
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, mining, add_block, verify_chain, serialization);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+//! Runs the scenario tests of `src/test` end to end.
+//!
+//! `cargo run --example scenarios --features scenarios -- [NAME...]` runs the named
+//! scenarios in order, or all of them when no name is given.
+use blockchain::test::{
+    test_batch, test_checkpoint, test_clock, test_core, test_crash, test_decode, test_filter,
+    test_fork, test_gossip, test_identity, test_keys, test_mint, test_peer_log, test_pool,
+    test_protocol, test_range, test_record, test_sim, test_state, test_upgrade, test_verifier,
+};
+
+use tracing_subscriber::EnvFilter;
+
+const SCENARIOS: &[&str] = &[
+    "gossip",
+    "record_gossip",
+    "record_rules",
+    "sim",
+    "core",
+    "fork",
+    "clock",
+    "decode",
+    "verifier",
+    "batch",
+    "identity",
+    "filter",
+    "keys",
+    "checkpoint",
+    "pool",
+    "peer_log",
+    "crash",
+    "protocol",
+    "upgrade",
+    "range",
+    "state",
+    "mint",
+];
+
+async fn run(name: &str) {
+    match name {
+        "gossip" => test_gossip::test_gossip::test_gossip().await,
+        "record_gossip" => test_record::test_record::test_record_gossip().await,
+        "record_rules" => test_record::test_record::test_record_rules(),
+        "sim" => test_sim::test_sim::test_sim_gossip(100, 42).await,
+        "core" => test_core::test_core::test_core(),
+        "fork" => test_fork::test_fork::test_fork(),
+        "clock" => test_clock::test_clock::test_clock().await,
+        "decode" => test_decode::test_decode::test_decode(1000, 42),
+        "verifier" => test_verifier::test_verifier::test_verifier().await,
+        "batch" => test_batch::test_batch::test_batch(),
+        "identity" => test_identity::test_identity::test_identity(),
+        "filter" => test_filter::test_filter::test_filter().await,
+        "keys" => test_keys::test_keys::test_keys(),
+        "checkpoint" => test_checkpoint::test_checkpoint::test_checkpoint().await,
+        "pool" => test_pool::test_pool::test_pool().await,
+        "peer_log" => test_peer_log::test_peer_log::test_peer_log(),
+        "crash" => test_crash::test_crash::test_crash().await,
+        "protocol" => test_protocol::test_protocol::test_protocol().await,
+        "upgrade" => test_upgrade::test_upgrade::test_upgrade(),
+        "range" => test_range::test_range::test_range().await,
+        "state" => test_state::test_state::test_state(),
+        "mint" => test_mint::test_mint::test_mint(),
+        _ => {
+            eprintln!("Unknown scenario {}. Known scenarios: {}", name, SCENARIOS.join(", "));
+            std::process::exit(2);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let names: Vec<String> = std::env::args().skip(1).collect();
+    let names: Vec<&str> = if names.is_empty() {
+        SCENARIOS.to_vec()
+    } else {
+        names.iter().map(String::as_str).collect()
+    };
+    for name in names {
+        println!("Running scenario {}", name);
+        run(name).await;
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * C bindings of humble_blockchain wallets, transactions and records.
+ *
+ * Strings are NUL terminated UTF-8. Strings handed over through `out_*` arguments belong
+ * to the caller, who frees them with humble_string_free.
+ */
+#ifndef HUMBLE_H
+#define HUMBLE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Outcome of every function. Values never change meaning. */
+typedef enum HumbleStatus {
+    HUMBLE_OK = 0,
+    /* A pointer argument was null. */
+    HUMBLE_NULL_POINTER = 1,
+    /* A string argument was not valid UTF-8. */
+    HUMBLE_INVALID_UTF8 = 2,
+    /* A wallet key was not a base64 encoded PKCS#8 key pair, as written by `wallet create`. */
+    HUMBLE_INVALID_KEY = 3,
+    /* An address was not a base64 encoded public key. */
+    HUMBLE_INVALID_ADDRESS = 4,
+    /* A coin was not a token, 64 hex digits. */
+    HUMBLE_INVALID_TOKEN = 5,
+    /* A network id was empty, too long or held characters other than [A-Za-z0-9._-]. */
+    HUMBLE_INVALID_NETWORK = 6,
+} HumbleStatus;
+
+/* Creates a wallet and hands over its base64 encoded key. */
+HumbleStatus humble_wallet_create(char **out_key);
+
+/* Hands over the address of the wallet whose key is `key`. */
+HumbleStatus humble_wallet_address(const char *key, char **out_address);
+
+/*
+ * Signs a transaction of `coin` from the wallet whose key is `key` to the address
+ * `receiver`, for the network `network` (e.g. "mainnet"), and hands it over in the format
+ * nodes take it in, along with its id unless `out_id` is null. Nodes of other networks
+ * reject it. `nonce` must exceed the nonce of the wallet's last transaction.
+ */
+HumbleStatus humble_transaction_sign(const char *key, const char *receiver, const char *coin,
+                                     const char *network, uint64_t nonce, char **out_transaction,
+                                     char **out_id);
+
+/*
+ * Signs a record writing `value` under `record_key`, owned by the wallet whose key is
+ * `key`, for the network `network` (e.g. "mainnet"), and hands it over in the format nodes
+ * take it in. Nodes of other networks reject it. An empty value deletes the key.
+ */
+HumbleStatus humble_record_sign(const char *key, const char *record_key, const char *value,
+                                const char *network, char **out_record);
+
+/* Describes a status. The string is static and must not be freed. */
+const char *humble_status_message(int32_t status);
+
+/* Frees a string handed over by this library, wiping it first. Does nothing on null. */
+void humble_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
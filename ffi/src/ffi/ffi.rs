@@ -0,0 +1,152 @@
+pub mod ffi {
+
+    use crate::status::status::status::{self, HumbleStatus};
+
+    use blockchain::{
+        chain::network::network::NetworkId,
+        record::record::record::Record,
+        token::token::token::Token,
+        transaction::transaction::transaction::Transaction,
+        wallet::wallet::wallet::Wallet,
+    };
+
+    use std::{
+        ffi::{c_char, CString},
+        mem,
+    };
+
+    use base64::{Engine as _, engine::general_purpose};
+    use zeroize::Zeroize;
+
+    /// Reads a wallet key, as written by `wallet create`.
+    ///
+    /// # Safety
+    /// See `status::read_str`.
+    unsafe fn read_wallet(key: *const c_char) -> Result<Wallet, HumbleStatus> {
+        Wallet::from_base64(status::read_str(key)?).ok_or(HumbleStatus::InvalidKey)
+    }
+
+    /// Reads a base64 encoded public key.
+    ///
+    /// # Safety
+    /// See `status::read_str`.
+    unsafe fn read_address(address: *const c_char) -> Result<Vec<u8>, HumbleStatus> {
+        general_purpose::STANDARD
+            .decode(status::read_str(address)?)
+            .map_err(|_| HumbleStatus::InvalidAddress)
+    }
+
+    /// Creates a wallet and hands over its key, base64 encoded like `wallet create` writes it.
+    ///
+    /// # Safety
+    /// `out_key` must be valid for writes.
+    #[no_mangle]
+    pub unsafe extern "C" fn humble_wallet_create(out_key: *mut *mut c_char) -> HumbleStatus {
+        let mut key = Wallet::new().to_base64();
+        status::status(status::write_string(out_key, mem::take(&mut key)))
+    }
+
+    /// Hands over the address of the wallet whose key is `key`: its base64 encoded public key.
+    ///
+    /// # Safety
+    /// `key` must be a NUL terminated string and `out_address` valid for writes.
+    #[no_mangle]
+    pub unsafe extern "C" fn humble_wallet_address(key: *const c_char, out_address: *mut *mut c_char) -> HumbleStatus {
+        status::status(read_wallet(key).and_then(|wallet| {
+            status::write_string(out_address, general_purpose::STANDARD.encode(wallet.get_pub_key()))
+        }))
+    }
+
+    /// Signs a transaction of `coin` from the wallet whose key is `key` to `receiver`, dated
+    /// now, and hands it over in the format nodes take it in.
+    ///
+    /// # Arguments
+    /// * `network` - The network the transaction is meant for, such as `mainnet`. Nodes of
+    ///   any other network reject it.
+    /// * `nonce` - Must exceed the nonce of the wallet's last transaction in the chain.
+    /// * `out_id` - Receives the id of the transaction, unless null.
+    ///
+    /// # Safety
+    /// `key`, `receiver`, `coin` and `network` must be NUL terminated strings, `out_transaction` valid
+    /// for writes and `out_id` null or valid for writes.
+    #[no_mangle]
+    pub unsafe extern "C" fn humble_transaction_sign(
+        key: *const c_char,
+        receiver: *const c_char,
+        coin: *const c_char,
+        network: *const c_char,
+        nonce: u64,
+        out_transaction: *mut *mut c_char,
+        out_id: *mut *mut c_char,
+    ) -> HumbleStatus {
+        let sign = || -> Result<(), HumbleStatus> {
+            let wallet = read_wallet(key)?;
+            let receiver = read_address(receiver)?;
+            let coin = Token::parse_legacy(status::read_str(coin)?).map_err(|_| HumbleStatus::InvalidToken)?;
+            let transaction = Transaction::new(wallet.get_pub_key(), receiver, vec![coin]).with_nonce(nonce);
+            let network = status::read_str(network)?
+                .parse::<NetworkId>()
+                .map_err(|_| HumbleStatus::InvalidNetwork)?;
+            let transaction = wallet.sign(transaction, &network);
+            let id = transaction.id();
+            status::write_string(out_transaction, transaction.into())?;
+            if !out_id.is_null() {
+                status::write_string(out_id, id)?;
+            }
+            Ok(())
+        };
+        status::status(sign())
+    }
+
+    /// Signs a record writing `value` under `record_key`, owned by the wallet whose key is
+    /// `key`, and hands it over in the format nodes take it in. An empty value deletes the
+    /// key.
+    ///
+    /// # Arguments
+    /// * `network` - The network the record is meant for, such as `mainnet`. Nodes of any
+    ///   other network reject it.
+    ///
+    /// # Safety
+    /// `key`, `record_key`, `value` and `network` must be NUL terminated strings and
+    /// `out_record` valid for writes.
+    #[no_mangle]
+    pub unsafe extern "C" fn humble_record_sign(
+        key: *const c_char,
+        record_key: *const c_char,
+        value: *const c_char,
+        network: *const c_char,
+        out_record: *mut *mut c_char,
+    ) -> HumbleStatus {
+        let sign = || -> Result<(), HumbleStatus> {
+            let wallet = read_wallet(key)?;
+            let record_key = status::read_str(record_key)?.to_string();
+            let value = status::read_str(value)?.to_string();
+            let network = status::read_str(network)?
+                .parse::<NetworkId>()
+                .map_err(|_| HumbleStatus::InvalidNetwork)?;
+            let record = wallet.sign_record(Record::new(record_key, value, wallet.get_pub_key()), &network);
+            status::write_string(out_record, record.into())
+        };
+        status::status(sign())
+    }
+
+    /// Describes the status with value `status`. The string is static and must not be freed.
+    #[no_mangle]
+    pub extern "C" fn humble_status_message(status: i32) -> *const c_char {
+        HumbleStatus::from_code(status)
+            .map_or(c"unknown status", HumbleStatus::message)
+            .as_ptr()
+    }
+
+    /// Frees a string handed over by one of the functions above, wiping it first as it
+    /// may hold a wallet key. Does nothing on null.
+    ///
+    /// # Safety
+    /// `string` must be null or a string handed over by this library, not freed yet.
+    #[no_mangle]
+    pub unsafe extern "C" fn humble_string_free(string: *mut c_char) {
+        if !string.is_null() {
+            CString::from_raw(string).into_bytes_with_nul().zeroize();
+        }
+    }
+}
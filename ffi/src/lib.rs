@@ -0,0 +1,10 @@
+//! C bindings for wallets, transactions and records, for mobile apps and other languages.
+//! The functions are declared in `include/humble.h`.
+
+pub mod status {
+    pub mod status;
+}
+
+pub mod ffi {
+    pub mod ffi;
+}
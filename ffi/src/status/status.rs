@@ -0,0 +1,98 @@
+pub mod status {
+
+    use std::{
+        ffi::{c_char, CStr, CString},
+        ptr,
+    };
+
+    use zeroize::Zeroizing;
+
+    /// Outcome of every function of the bindings. The values are part of the C interface
+    /// and never change meaning; new outcomes get new values.
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum HumbleStatus {
+        Ok = 0,
+        /// A pointer argument was null.
+        NullPointer = 1,
+        /// A string argument was not valid UTF-8.
+        InvalidUtf8 = 2,
+        /// A wallet key was not the base64 encoded PKCS#8 document of an ECDSA P-256 key
+        /// pair, as written by `wallet create`.
+        InvalidKey = 3,
+        /// An address was not a base64 encoded public key.
+        InvalidAddress = 4,
+        /// A coin was not a token, 64 hex digits.
+        InvalidToken = 5,
+        /// A network id was empty, too long or held characters other than letters,
+        /// digits, `-`, `_` and `.`.
+        InvalidNetwork = 6,
+    }
+
+    impl HumbleStatus {
+        /// Returns the status with value `code`, `None` if there is none.
+        pub fn from_code(code: i32) -> Option<Self> {
+            [
+                HumbleStatus::Ok,
+                HumbleStatus::NullPointer,
+                HumbleStatus::InvalidUtf8,
+                HumbleStatus::InvalidKey,
+                HumbleStatus::InvalidAddress,
+                HumbleStatus::InvalidToken,
+                HumbleStatus::InvalidNetwork,
+            ]
+            .into_iter()
+            .find(|status| *status as i32 == code)
+        }
+
+        /// Describes the status, as a static NUL terminated string.
+        pub fn message(self) -> &'static CStr {
+            match self {
+                HumbleStatus::Ok => c"ok",
+                HumbleStatus::NullPointer => c"a pointer argument is null",
+                HumbleStatus::InvalidUtf8 => c"a string argument is not valid UTF-8",
+                HumbleStatus::InvalidKey => c"the wallet key is not a base64 encoded PKCS#8 key pair",
+                HumbleStatus::InvalidAddress => c"the address is not a base64 encoded public key",
+                HumbleStatus::InvalidToken => c"the coin is not a token of 64 hex digits",
+                HumbleStatus::InvalidNetwork => c"the network id is not a valid one",
+            }
+        }
+    }
+
+    /// Reads the NUL terminated string at `string`.
+    ///
+    /// # Safety
+    /// `string` must be null or point to a NUL terminated string that stays valid and
+    /// unchanged for `'a`.
+    pub unsafe fn read_str<'a>(string: *const c_char) -> Result<&'a str, HumbleStatus> {
+        if string.is_null() {
+            return Err(HumbleStatus::NullPointer);
+        }
+        CStr::from_ptr(string).to_str().map_err(|_| HumbleStatus::InvalidUtf8)
+    }
+
+    /// Hands `string` over to the caller through `out`, who frees it with
+    /// `humble_string_free`.
+    ///
+    /// # Safety
+    /// `out` must be null or valid for writes.
+    pub unsafe fn write_string(out: *mut *mut c_char, string: String) -> Result<(), HumbleStatus> {
+        if out.is_null() {
+            return Err(HumbleStatus::NullPointer);
+        }
+        // Copied into a buffer with room for the NUL, so `CString::new` does not reallocate
+        // and leave a copy of a wallet key behind. The original is wiped.
+        let bytes = Zeroizing::new(string.into_bytes());
+        let mut terminated = Vec::with_capacity(bytes.len() + 1);
+        terminated.extend_from_slice(&bytes);
+        // Everything handed over is base64, hex or decimal, so never holds a NUL.
+        let string = CString::new(terminated).expect("strings handed over hold no NUL");
+        ptr::write(out, string.into_raw());
+        Ok(())
+    }
+
+    /// Turns the outcome of a binding into the status it returns.
+    pub fn status(result: Result<(), HumbleStatus>) -> HumbleStatus {
+        result.err().unwrap_or(HumbleStatus::Ok)
+    }
+}
@@ -0,0 +1,1122 @@
+pub mod cli {
+
+    use crate::{
+        app::{
+            client::client::{self, ClientError},
+            devnet::devnet::{Devnet, DEFAULT_BASE_PORT, DEFAULT_HOST, DEFAULT_MINERS, DEFAULT_NODES},
+            logging::logging::LogOptions,
+            metrics_server::metrics_server,
+            operator::operator::{self, OperatorError},
+            rest::rest::{self, ApiState},
+            signer::signer::{self, SignerError, DEFAULT_SIGNER_ADDRESS},
+        },
+        chain::{
+            block::block::block::{self, Block, InvalidRecordErr},
+            chain::chain::{BlockCheckError, Chain},
+            genesis::genesis::{Allocation, Genesis, GenesisError},
+        },
+        explorer::explorer::explorer::{Explorer, IndexKind},
+        miner::miner::miner::{Miner, MiningDigest, MiningError, ZERO_WALLET_PK},
+        node::{
+            config::config::{NodeBuilder, NodeConfig, NodeConfigError, DEFAULT_CHANNEL_CAPACITY},
+            metrics::metrics::DEFAULT_MAX_TIP_AGE,
+            mining::mining::MiningCommand,
+            neighbour::neighbour::Role,
+            node::node::{NodeLoopError, PeerStoreError},
+            receiver::receiver::Receiver,
+        },
+        store::{
+            encrypted_engine::encrypted_engine,
+            snapshot::snapshot::SnapshotError,
+            store::store::{Store, StoreError},
+        },
+        record::record::record::{Record, RecordRules},
+        token::token::token::Token,
+        transaction::{transaction::transaction::Transaction, validation::validation::ValidationReport},
+        wallet::wallet::wallet::Wallet,
+    };
+
+    use std::{
+        fs::{self, OpenOptions},
+        future::Future,
+        io::{Error as IOError, ErrorKind, Write},
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+    #[cfg(unix)]
+    use std::os::unix::fs::OpenOptionsExt;
+
+    use base64::{Engine as _, engine::general_purpose};
+    use clap::{Args, Parser, Subcommand};
+    use thiserror::Error;
+    use tokio::{
+        sync::mpsc,
+        task::JoinHandle,
+    };
+    #[cfg(unix)]
+    use tokio::signal::unix::{signal, SignalKind};
+    use tracing::{info, warn};
+    use zeroize::Zeroizing;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum CliError {
+        #[error("Failed to read {}: {source}", path.display())]
+        #[from(ignore)]
+        ReadError { path: PathBuf, source: IOError },
+        #[error("Failed to write {}: {source}", path.display())]
+        #[from(ignore)]
+        WriteError { path: PathBuf, source: IOError },
+        #[error("{} already exists, refusing to overwrite it.", .0.display())]
+        #[from(ignore)]
+        WalletExists(PathBuf),
+        #[error("{} does not hold a wallet key.", .0.display())]
+        #[from(ignore)]
+        InvalidWallet(PathBuf),
+        #[error("{0} is not a base64 encoded public key.")]
+        #[from(ignore)]
+        InvalidAddress(String),
+        #[error("The wallet holds {available} coins, {needed} are needed.")]
+        InsufficientBalance { needed: usize, available: usize },
+        #[error("No record was written under {0}.")]
+        #[from(ignore)]
+        RecordNotFound(String),
+        #[error("The node would reject the record: {0}")]
+        InvalidRecordErr(InvalidRecordErr),
+        #[error("Failed to listen for signals: {0}")]
+        #[from(ignore)]
+        SignalError(IOError),
+        #[error(transparent)]
+        ClientError(ClientError),
+        #[error("Mining failed: {0}")]
+        MiningError(MiningError),
+        #[error("Invalid chain: {0}")]
+        BlockCheckError(BlockCheckError),
+        #[error(transparent)]
+        NodeConfigError(NodeConfigError),
+        #[error(transparent)]
+        NodeLoopError(NodeLoopError),
+        #[error(transparent)]
+        PeerStoreError(PeerStoreError),
+        #[error(transparent)]
+        StoreError(StoreError),
+        #[error(transparent)]
+        SnapshotError(SnapshotError),
+        #[error(transparent)]
+        SignerError(SignerError),
+        #[error(transparent)]
+        OperatorError(OperatorError),
+        #[error(transparent)]
+        GenesisError(GenesisError),
+        #[error("The node would reject {0} of the transactions.")]
+        #[from(ignore)]
+        RejectedTransactions(usize),
+        #[error("The blocks pulled end at {pulled}, dropping the stored blocks up to {tip}.")]
+        #[from(ignore)]
+        Truncated { pulled: usize, tip: usize },
+    }
+
+    /// Runs and manages humble_blockchain nodes, wallets and chains.
+    ///
+    /// Commands taking a `--store` work on file stores written with the default codec,
+    /// like the chain store of a node configured without a key file or write-ahead log.
+    #[derive(Parser)]
+    #[command(version, about)]
+    pub struct Cli {
+        #[command(subcommand)]
+        pub command: Command,
+        #[command(flatten)]
+        pub log: LogOptions,
+    }
+
+    #[derive(Subcommand)]
+    pub enum Command {
+        /// Runs a node, or inspects the chain it stores.
+        #[command(subcommand)]
+        Node(NodeCommand),
+        /// Creates wallets and spends their coins.
+        #[command(subcommand)]
+        Wallet(WalletCommand),
+        /// Moves stored chains around as snapshots.
+        #[command(subcommand)]
+        Chain(ChainCommand),
+        /// Creates and checks the genesis of a private network.
+        #[command(subcommand)]
+        Genesis(GenesisCommand),
+        /// Mines blocks on top of a stored chain, which is created if missing.
+        Mine {
+            /// The chain store.
+            #[arg(long)]
+            store: PathBuf,
+            /// Wallet receiving the mining rewards. Without one, they go to a throwaway wallet.
+            #[arg(long)]
+            wallet: Option<PathBuf>,
+            /// Number of blocks to mine.
+            #[arg(long, default_value_t = 1)]
+            blocks: usize,
+            /// Threads searching for a nonce in parallel.
+            #[arg(long, default_value_t = 1)]
+            threads: usize,
+        },
+        /// Runs a local network of nodes in this process, sharing a genesis block, until
+        /// it receives SIGINT (Ctrl-C) or SIGTERM. The tracker binds `--base-port` and the
+        /// miners, then the regular nodes, the ports following it.
+        Devnet {
+            /// Number of miners.
+            #[arg(long, default_value_t = DEFAULT_MINERS)]
+            miners: usize,
+            /// Number of regular nodes.
+            #[arg(long, default_value_t = DEFAULT_NODES)]
+            nodes: usize,
+            /// The IP address the nodes bind.
+            #[arg(long, default_value = DEFAULT_HOST)]
+            host: String,
+            /// The tracker's port.
+            #[arg(long, default_value_t = DEFAULT_BASE_PORT)]
+            base_port: u16,
+        },
+        /// Runs a signing daemon until it receives SIGINT (Ctrl-C) or SIGTERM. It holds a
+        /// wallet encrypted by `wallet encrypt` and serves, on localhost only, an API to
+        /// sign transactions and send them to a node, so the key never lives in the
+        /// process of a networked node.
+        Signer {
+            /// The encrypted wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+            /// The key the wallet was encrypted with.
+            #[arg(long)]
+            key_file: PathBuf,
+            /// Address of the node the coins of the wallet are looked up on, and the
+            /// transactions sent to.
+            #[arg(long)]
+            node: String,
+            /// Loopback address to serve the API on.
+            #[arg(long, default_value = DEFAULT_SIGNER_ADDRESS)]
+            listen: String,
+        },
+    }
+
+    #[derive(Subcommand)]
+    pub enum NodeCommand {
+        /// Runs a node until it receives SIGINT (Ctrl-C) or SIGTERM.
+        Run {
+            /// The node's TOML configuration. See `NodeConfig`.
+            #[arg(long)]
+            config: PathBuf,
+            /// Address to serve Prometheus metrics on, e.g. `127.0.0.1:9100`.
+            #[arg(long)]
+            metrics: Option<String>,
+            /// Age (in seconds) of the last block past which `/readyz`, served along the
+            /// metrics, reports the node as not synced.
+            #[arg(long, requires = "metrics", default_value_t = DEFAULT_MAX_TIP_AGE)]
+            max_tip_age: u64,
+            /// Address to serve the REST API on, e.g. `127.0.0.1:3000`. Its OpenAPI
+            /// documentation is served at `/openapi.json`, and events are streamed over a
+            /// WebSocket at `/events`.
+            #[arg(long)]
+            api: Option<String>,
+            /// Indexes the chain to serve paginated queries through the REST API:
+            /// transactions by address, record history and blocks by hash. Either `memory`
+            /// or `sqlite:PATH`, which needs the `sqlite` feature.
+            #[arg(long, requires = "api", value_parser = IndexKind::parse)]
+            explorer: Option<IndexKind>,
+            /// Address to serve the operator API on, apart from the public REST API:
+            /// node and peer status, the mempool and its evictions, pausing mining and
+            /// shutdown. Only a loopback address is served without `--operator-token-file`.
+            #[arg(long)]
+            operator_api: Option<String>,
+            /// File holding the token operators present to the operator API as
+            /// `Authorization: Bearer <token>`, e.g. through `node evict`, `node pause-mining`
+            /// and `node resume-mining`.
+            #[arg(long)]
+            operator_token_file: Option<PathBuf>,
+        },
+        /// Describes a stored chain. The store is only read, even while a node writes it.
+        Status {
+            /// The chain store.
+            #[arg(long)]
+            store: PathBuf,
+        },
+        /// Asks a running node about itself: id, role, uptime, version and chain tip.
+        Info {
+            /// The address of the node.
+            #[arg(long)]
+            node: String,
+        },
+        /// Asks a running node about its neighbours and the traffic exchanged with them.
+        Peers {
+            /// The address of the node.
+            #[arg(long)]
+            node: String,
+        },
+        /// Lists the transactions a running miner has yet to mine.
+        Mempool {
+            /// The address of the miner.
+            #[arg(long)]
+            node: String,
+            /// Only shows the transaction with this id.
+            #[arg(long)]
+            id: Option<String>,
+        },
+        /// Drops a transaction from the mempool of a miner.
+        Evict {
+            /// The address of the miner's operator API. See `node run --operator-api`.
+            #[arg(long)]
+            operator_api: String,
+            /// The id of the transaction.
+            #[arg(long)]
+            id: String,
+            /// File holding the token of the operator API. See `node run --operator-token-file`.
+            #[arg(long)]
+            operator_token_file: Option<PathBuf>,
+        },
+        /// Pauses mining on a miner, without stopping it.
+        PauseMining {
+            /// The address of the miner's operator API. See `node run --operator-api`.
+            #[arg(long)]
+            operator_api: String,
+            /// File holding the token of the operator API. See `node run --operator-token-file`.
+            #[arg(long)]
+            operator_token_file: Option<PathBuf>,
+        },
+        /// Resumes mining on a miner.
+        ResumeMining {
+            /// The address of the miner's operator API. See `node run --operator-api`.
+            #[arg(long)]
+            operator_api: String,
+            /// File holding the token of the operator API. See `node run --operator-token-file`.
+            #[arg(long)]
+            operator_token_file: Option<PathBuf>,
+        },
+    }
+
+    #[derive(Subcommand)]
+    pub enum WalletCommand {
+        /// Creates a wallet and prints its address, the public key coins are sent to.
+        #[command(alias = "new")]
+        Create {
+            /// Where to write the wallet key. Never overwritten.
+            #[arg(long)]
+            out: PathBuf,
+        },
+        /// Prints the address of a wallet.
+        Address {
+            /// The wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+        },
+        /// Counts the coins a wallet owns.
+        Balance {
+            /// The wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+            #[command(flatten)]
+            chain: ChainSource,
+        },
+        /// Sends coins of a wallet through a node, one transaction per coin.
+        Send {
+            /// The wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+            /// Address of the receiver.
+            #[arg(long)]
+            to: String,
+            /// Number of coins to send.
+            #[arg(long, default_value_t = 1)]
+            amount: usize,
+            /// Address of the node the coins of the wallet are looked up on, and the
+            /// transactions sent to.
+            #[arg(long)]
+            node: String,
+            /// Has the node check the transactions and prints what it finds, without
+            /// sending them.
+            #[arg(long)]
+            dry_run: bool,
+        },
+        /// Creates coins as a mint authority of the chain and sends them through a node, one
+        /// transaction per coin.
+        Mint {
+            /// The wallet key of the mint authority.
+            #[arg(long)]
+            wallet: PathBuf,
+            /// Address of the receiver.
+            #[arg(long)]
+            to: String,
+            /// Number of coins to create.
+            #[arg(long, default_value_t = 1)]
+            amount: usize,
+            /// Address of the node the transactions are sent to.
+            #[arg(long)]
+            node: String,
+            /// Has the node check the transactions and prints what it finds, without
+            /// sending them.
+            #[arg(long)]
+            dry_run: bool,
+        },
+        /// Encrypts a wallet key for the `signer`. The plain key can be deleted afterwards.
+        Encrypt {
+            /// The wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+            /// The key to encrypt it with, created if the file does not exist.
+            #[arg(long)]
+            key_file: PathBuf,
+            /// Where to write the encrypted wallet key. Never overwritten.
+            #[arg(long)]
+            out: PathBuf,
+        },
+        /// Writes, reads and deletes key-value records.
+        #[command(subcommand)]
+        Record(RecordCommand),
+    }
+
+    #[derive(Subcommand)]
+    pub enum RecordCommand {
+        /// Writes a record signed by a wallet. The first wallet writing a key is the only
+        /// one allowed to overwrite or delete it.
+        Put {
+            /// The wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+            #[arg(long)]
+            key: String,
+            #[arg(long)]
+            value: String,
+            /// Address of a miner to send the record to.
+            #[arg(long)]
+            node: String,
+        },
+        /// Prints the value last written under a key.
+        Get {
+            #[arg(long)]
+            key: String,
+            #[command(flatten)]
+            chain: ChainSource,
+        },
+        /// Deletes a key, by writing an empty value over it.
+        Delete {
+            /// The wallet key.
+            #[arg(long)]
+            wallet: PathBuf,
+            #[arg(long)]
+            key: String,
+            /// Address of a miner to send the deletion to.
+            #[arg(long)]
+            node: String,
+        },
+    }
+
+    /// Where a command reads the chain from: a running node, or a chain store.
+    #[derive(Args)]
+    #[group(required = true, multiple = false)]
+    pub struct ChainSource {
+        /// Address of a node to ask for its chain.
+        #[arg(long)]
+        node: Option<String>,
+        /// A chain store.
+        #[arg(long)]
+        store: Option<PathBuf>,
+    }
+
+    /// Where `chain import` reads the chain from: a snapshot, or a running node.
+    #[derive(Args)]
+    #[group(required = true, multiple = false)]
+    pub struct ImportSource {
+        /// The snapshot to import.
+        #[arg(long)]
+        snapshot: Option<PathBuf>,
+        /// Address of a healthy node to pull blocks from.
+        #[arg(long)]
+        node: Option<String>,
+    }
+
+    #[derive(Subcommand)]
+    pub enum ChainCommand {
+        /// Writes a stored chain to a snapshot.
+        Export {
+            /// The chain store.
+            #[arg(long)]
+            store: PathBuf,
+            /// Where to write the snapshot.
+            #[arg(long)]
+            out: PathBuf,
+        },
+        /// Replaces a stored chain with the one in a snapshot, or with the blocks of a range
+        /// pulled from a running node.
+        Import {
+            /// The chain store.
+            #[arg(long)]
+            store: PathBuf,
+            #[command(flatten)]
+            source: ImportSource,
+            /// The node's TOML configuration, whose network, genesis and chain rules the
+            /// blocks pulled must follow.
+            #[arg(long, conflicts_with = "snapshot", required_unless_present = "snapshot")]
+            config: Option<PathBuf>,
+            /// Index of the first block to pull. Past the last stored block by default.
+            #[arg(long, conflicts_with = "snapshot")]
+            from: Option<usize>,
+            /// Index of the block to stop pulling at, excluded. The end of the node's chain
+            /// by default.
+            #[arg(long, conflicts_with = "snapshot")]
+            to: Option<usize>,
+        },
+        /// Rebuilds the indices of a node's chain store, and of its explorer, from the
+        /// stored blocks. Run it while the node is stopped.
+        Reindex {
+            /// The node's TOML configuration, telling where its chain is stored.
+            #[arg(long)]
+            config: PathBuf,
+            /// The explorer index to rebuild too, as given to `node run --explorer`.
+            #[arg(long, value_parser = IndexKind::parse)]
+            explorer: Option<IndexKind>,
+        },
+    }
+
+    #[derive(Subcommand)]
+    pub enum GenesisCommand {
+        /// Writes a genesis file to share with the other operators, and prints its hash.
+        Create {
+            /// Where to write the genesis. Never overwritten.
+            #[arg(long)]
+            out: PathBuf,
+            /// Name of the network.
+            #[arg(long)]
+            network: String,
+            /// Difficulty the chain starts at.
+            #[arg(long, default_value_t = 1)]
+            difficulty: usize,
+            /// Timestamp of the genesis block, in seconds since the UNIX epoch. Defaults to now.
+            #[arg(long)]
+            timestamp: Option<u64>,
+            /// Coins given to an address, as `ADDRESS:COINS`. Repeat it for every address.
+            #[arg(long = "allocate", value_parser = Allocation::parse)]
+            allocations: Vec<Allocation>,
+        },
+        /// Prints the hash of a genesis file, for operators to compare.
+        Hash {
+            /// The genesis file.
+            #[arg(long)]
+            genesis: PathBuf,
+        },
+        /// Checks a running node's chain starts at a genesis.
+        Verify {
+            /// The genesis file.
+            #[arg(long)]
+            genesis: PathBuf,
+            /// Address of the node.
+            #[arg(long)]
+            node: String,
+        },
+    }
+
+    /// Runs the command given on the command line.
+    pub async fn run(cli: Cli) -> Result<(), CliError> {
+        match cli.command {
+            Command::Node(NodeCommand::Run { config, metrics, max_tip_age, api, explorer, operator_api, operator_token_file }) => {
+                let operator_token = operator_token_file.as_deref().map(operator::load_token).transpose()?;
+                run_node(&config, metrics.map(|address| (address, max_tip_age)), api, explorer, operator_api, operator_token).await
+            },
+            Command::Node(NodeCommand::Status { store }) => status(&store),
+            Command::Node(NodeCommand::Info { node }) => {
+                let info = client::node_info(&node).await?;
+                println!("id: {}", info.id);
+                println!("role: {}", info.role);
+                println!("uptime: {}s", info.uptime);
+                println!("version: {}", info.version);
+                println!("network: {}", info.network);
+                println!("height: {}", info.height);
+                println!("tip: {}", info.tip_hash);
+                println!("state root: {}", info.state_root);
+                if info.role == Role::Miner.to_string() {
+                    println!("mining: {}", if info.mining_paused { "paused" } else { "running" });
+                }
+                Ok(())
+            },
+            Command::Node(NodeCommand::Peers { node }) => {
+                for peer in client::peer_info(&node).await? {
+                    println!(
+                        "{} {} {} last_seen={} score={} version={} in={}B out={}B",
+                        peer.id,
+                        peer.address,
+                        peer.role,
+                        peer.last_seen,
+                        peer.score,
+                        peer.version.as_deref().unwrap_or("unknown"),
+                        peer.bytes_in,
+                        peer.bytes_out,
+                    );
+                }
+                Ok(())
+            },
+            Command::Node(NodeCommand::Mempool { node, id }) => {
+                for transaction in client::mempool(&node, id.as_deref()).await? {
+                    println!(
+                        "{} from={} to={} coins={} timestamp={} nonce={}",
+                        transaction.id(),
+                        general_purpose::STANDARD.encode(&transaction.sender),
+                        general_purpose::STANDARD.encode(&transaction.receiver),
+                        transaction.coins.iter().map(Token::to_string).collect::<Vec<_>>().join(","),
+                        transaction.timestamp,
+                        transaction.nonce,
+                    );
+                }
+                Ok(())
+            },
+            Command::Node(NodeCommand::Evict { operator_api, id, operator_token_file }) => {
+                let token = operator_token_file.as_deref().map(operator::load_token).transpose()?;
+                operator::evict(&operator_api, token.as_deref(), &id).await?;
+                println!("Evicted {}", id);
+                Ok(())
+            },
+            Command::Node(NodeCommand::PauseMining { operator_api, operator_token_file }) => {
+                switch_mining(&operator_api, operator_token_file.as_deref(), MiningCommand::Pause).await
+            },
+            Command::Node(NodeCommand::ResumeMining { operator_api, operator_token_file }) => {
+                switch_mining(&operator_api, operator_token_file.as_deref(), MiningCommand::Resume).await
+            },
+            Command::Wallet(WalletCommand::Create { out }) => create_wallet(&out),
+            Command::Wallet(WalletCommand::Address { wallet }) => {
+                println!("{}", general_purpose::STANDARD.encode(read_wallet(&wallet)?.get_pub_key()));
+                Ok(())
+            },
+            Command::Wallet(WalletCommand::Balance { wallet, chain }) => {
+                let wallet = read_wallet(&wallet)?;
+                println!("{}", chain.read().await?.coins_of(&wallet.get_pub_key()).len());
+                Ok(())
+            },
+            Command::Wallet(WalletCommand::Send { wallet, to, amount, node, dry_run }) => {
+                send(&wallet, &to, amount, &node, dry_run).await
+            },
+            Command::Wallet(WalletCommand::Mint { wallet, to, amount, node, dry_run }) => {
+                mint(&wallet, &to, amount, &node, dry_run).await
+            },
+            Command::Wallet(WalletCommand::Encrypt { wallet, key_file, out }) => encrypt_wallet(&wallet, &key_file, &out),
+            Command::Wallet(WalletCommand::Record(RecordCommand::Put { wallet, key, value, node })) => {
+                put_record(&wallet, key, value, &node).await
+            },
+            Command::Wallet(WalletCommand::Record(RecordCommand::Get { key, chain })) => {
+                let chain = chain.read().await?;
+                let (record, _) = chain
+                    .find_record(&key)
+                    .filter(|(record, _)| !record.is_deletion())
+                    .ok_or(CliError::RecordNotFound(key))?;
+                println!("{}", record.value);
+                Ok(())
+            },
+            Command::Wallet(WalletCommand::Record(RecordCommand::Delete { wallet, key, node })) => {
+                put_record(&wallet, key, String::new(), &node).await
+            },
+            Command::Chain(ChainCommand::Export { store, out }) => {
+                Store::open_read_only(&store)?.export_snapshot(&out)?;
+                println!("Exported {} to {}", store.display(), out.display());
+                Ok(())
+            },
+            Command::Chain(ChainCommand::Import { store, source: ImportSource { node: Some(node), .. }, config, from, to }) => {
+                let config = read_config(&config.expect("clap requires --config along with --node"))?;
+                let pulled = pull(&store, &config, &node, from, to).await?;
+                println!("Pulled {} blocks from {} into {}", pulled, node, store.display());
+                Ok(())
+            },
+            Command::Chain(ChainCommand::Import { store, source: ImportSource { snapshot, .. }, .. }) => {
+                let snapshot = snapshot.expect("clap requires --snapshot or --node");
+                let imported = Store::new(&store)?.import_snapshot(&snapshot)?;
+                let height = imported.height.map_or("unknown".to_string(), |height| height.to_string());
+                println!("Imported a chain of height {} into {}", height, store.display());
+                Ok(())
+            },
+            Command::Chain(ChainCommand::Reindex { config, explorer }) => reindex(&config, explorer),
+            Command::Genesis(GenesisCommand::Create { out, network, difficulty, timestamp, allocations }) => {
+                let timestamp = timestamp.unwrap_or_else(|| {
+                    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+                });
+                let genesis = Genesis { network, timestamp, difficulty, allocations };
+                genesis.validate()?;
+                genesis.write(&out)?;
+                println!("{}", genesis.hash());
+                Ok(())
+            },
+            Command::Genesis(GenesisCommand::Hash { genesis }) => {
+                println!("{}", Genesis::read(&genesis)?.hash());
+                Ok(())
+            },
+            Command::Genesis(GenesisCommand::Verify { genesis, node }) => {
+                let genesis = Genesis::read(&genesis)?;
+                genesis.verify(&client::poll_chain(&node).await?)?;
+                println!("{} starts at genesis {}", node, genesis.hash());
+                Ok(())
+            },
+            Command::Mine { store, wallet, blocks, threads } => mine(&store, wallet.as_deref(), blocks, threads),
+            Command::Devnet { miners, nodes, host, base_port } => run_devnet(&host, base_port, miners, nodes).await,
+            Command::Signer { wallet, key_file, node, listen } => run_signer(&wallet, &key_file, node, &listen).await,
+        }
+    }
+
+    /// Starts listening for SIGINT and SIGTERM.
+    ///
+    /// # Returns
+    /// * `Result<impl Future<Output = ()>, CliError>` - A future resolving once the process
+    ///   receives either, or an error if the signals cannot be listened for.
+    #[cfg(unix)]
+    fn on_signal() -> Result<impl Future<Output = ()>, CliError> {
+        let mut terminate = signal(SignalKind::terminate()).map_err(CliError::SignalError)?;
+        Ok(async move {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => info!("Received SIGINT, stopping"),
+                _ = terminate.recv() => info!("Received SIGTERM, stopping"),
+            }
+        })
+    }
+
+    /// Starts listening for Ctrl-C, the only signal there is to stop on outside Unix.
+    ///
+    /// # Returns
+    /// * `Result<impl Future<Output = ()>, CliError>` - A future resolving once Ctrl-C is
+    ///   pressed.
+    #[cfg(not(unix))]
+    fn on_signal() -> Result<impl Future<Output = ()>, CliError> {
+        Ok(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                info!("Received Ctrl-C, stopping");
+            }
+        })
+    }
+
+    /// Runs the node configured in the TOML file at `path` until it receives SIGINT or
+    /// SIGTERM, then shuts it down: it says farewell to its peers, its stores are flushed
+    /// and the servers stop. The saved chain and peers are loaded first.
+    ///
+    /// # Arguments
+    /// * `path` - The node's configuration.
+    /// * `metrics` - Where to serve the node's metrics and health checks, if anywhere, and
+    ///   the tip age past which the node is not synced.
+    /// * `api` - Where to serve the REST API, if anywhere.
+    /// * `explorer` - Where to index the chain for the REST API, if at all.
+    /// * `operator_api` - Where to serve the operator API, if anywhere.
+    /// * `operator_token` - The token operators present to the operator API.
+    async fn run_node(
+        path: &Path,
+        metrics: Option<(String, u64)>,
+        api: Option<String>,
+        explorer: Option<IndexKind>,
+        operator_api: Option<String>,
+        operator_token: Option<String>,
+    ) -> Result<(), CliError> {
+        let config = read_config(path)?;
+        let has_trackers = !config.trackers.is_empty();
+        let has_peer_store = config.store.is_some();
+        let mint = config.chain.mint.clone();
+        let (transactions, receiver) = mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
+        let mut node = NodeBuilder::from_config(config)
+            .with_receiver(Receiver::new(receiver))
+            .build()?;
+        let peers = match has_peer_store {
+            true => node.load_peers()?,
+            false => 0,
+        };
+
+        let mut servers: Vec<JoinHandle<()>> = vec![];
+        if let Some((address, max_tip_age)) = metrics {
+            let handle = node.metrics_handle();
+            servers.push(tokio::spawn(async move {
+                if let Err(e) = metrics_server::serve(&address, handle, max_tip_age).await {
+                    warn!("Stopped serving metrics: {}", e);
+                }
+            }));
+        }
+        if let Some(address) = api {
+            let mut state = ApiState::new(
+                node.watch_chain(),
+                node.watch_peers(),
+                transactions,
+                node.subscribe(),
+                node.get_network().clone(),
+            ).with_mint_policy(mint);
+            if let Some(kind) = explorer {
+                state = state.with_explorer(Explorer::new(node.watch_chain(), kind.open()?));
+            }
+            servers.push(tokio::spawn(async move {
+                if let Err(e) = rest::serve(&address, state).await {
+                    warn!("Stopped serving the REST API: {}", e);
+                }
+            }));
+        }
+        if let Some(address) = operator_api {
+            let listener = operator::listen(&address, operator_token.as_deref()).await?;
+            let (node_address, stop, control) = (node.get_address(), node.stop_handle(), node.operator_handle());
+            servers.push(tokio::spawn(async move {
+                if let Err(e) = operator::serve(listener, &node_address, stop, control, operator_token).await {
+                    warn!("Stopped serving the operator API: {}", e);
+                }
+            }));
+        }
+        let signalled = on_signal()?;
+        let stop = node.stop_handle();
+        tokio::spawn(async move {
+            signalled.await;
+            stop.stop();
+        });
+
+        // Without trackers or saved peers there is no network to enter: the node waits to be found.
+        let result = match has_trackers || peers > 0 {
+            true => node.enter_and_node_loop().await,
+            false => node.node_loop().await.map_err(NodeLoopError::from),
+        };
+        for server in servers {
+            server.abort();
+        }
+        node.shutdown().await?;
+        result?;
+        info!("Node stopped");
+        Ok(())
+    }
+
+    /// Runs a devnet until SIGINT or SIGTERM, printing the role and address of its nodes.
+    ///
+    /// # Arguments
+    /// * `host` - The IP address the nodes bind.
+    /// * `base_port` - The tracker's port.
+    /// * `miners` - Number of miners.
+    /// * `nodes` - Number of regular nodes.
+    async fn run_devnet(host: &str, base_port: u16, miners: usize, nodes: usize) -> Result<(), CliError> {
+        let signalled = on_signal()?;
+        let devnet = Devnet::launch(host, base_port, miners, nodes)?;
+        for (role, address) in devnet.addresses() {
+            let role = match role {
+                Role::Tracker => "tracker",
+                Role::Miner => "miner",
+                Role::Node => "node",
+            };
+            println!("{} {}", role, address);
+        }
+        signalled.await;
+        devnet.shutdown().await?;
+        info!("Devnet stopped");
+        Ok(())
+    }
+
+    /// Runs the signer until SIGINT or SIGTERM.
+    ///
+    /// # Arguments
+    /// * `wallet` - The encrypted wallet key.
+    /// * `key_file` - The key it was encrypted with.
+    /// * `node` - The node the transactions are sent to.
+    /// * `listen` - Where to serve the signer's API.
+    async fn run_signer(wallet: &Path, key_file: &Path, node: String, listen: &str) -> Result<(), CliError> {
+        let key = encrypted_engine::load_key(key_file)?;
+        let wallet = signer::open_wallet(wallet, &key)?;
+        let signalled = on_signal()?;
+        tokio::select! {
+            served = signer::serve(listen, wallet, node) => served?,
+            _ = signalled => info!("Signer stopped"),
+        }
+        Ok(())
+    }
+
+    fn status(store: &Path) -> Result<(), CliError> {
+        let chain = read_chain(store)?;
+        let tip = chain.digest();
+        println!("height: {}", tip.height);
+        println!("tip: {}", tip.tip);
+        println!("state root: {}", chain.state_root());
+        println!("difficulty: {}", chain.difficulty);
+        Ok(())
+    }
+
+    /// Rebuilds the indices of the chain store configured at `path`, and clears the ones of
+    /// `explorer` for the node to fill again.
+    fn reindex(path: &Path, explorer: Option<IndexKind>) -> Result<(), CliError> {
+        let config = read_config(path)?;
+        match &config.chain_store {
+            Some(engine) => {
+                let reindexed = engine.open()?.reindex()?;
+                println!("Reindexed {} blocks of the chain store", reindexed);
+            },
+            None => println!("No chain store configured, nothing to reindex"),
+        }
+        if let Some(explorer) = explorer {
+            let dropped = explorer.open()?.reindex()?;
+            println!("Cleared {} blocks of the explorer index, indexed again on the next query", dropped);
+        }
+        Ok(())
+    }
+
+    fn create_wallet(out: &Path) -> Result<(), CliError> {
+        let wallet = Wallet::new();
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        // Readable by its owner only, like the keys of encrypted stores.
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(out)
+            .map_err(|source| match source.kind() {
+                ErrorKind::AlreadyExists => CliError::WalletExists(out.to_path_buf()),
+                _ => CliError::WriteError { path: out.to_path_buf(), source },
+            })?;
+        file.write_all(wallet.to_base64().as_bytes())
+            .map_err(|source| CliError::WriteError { path: out.to_path_buf(), source })?;
+        println!("{}", general_purpose::STANDARD.encode(wallet.get_pub_key()));
+        Ok(())
+    }
+
+    /// Encrypts the wallet key at `wallet` into `out`, with the key at `key_file`, which is
+    /// created first if it does not exist.
+    fn encrypt_wallet(wallet: &Path, key_file: &Path, out: &Path) -> Result<(), CliError> {
+        if out.exists() {
+            return Err(CliError::WalletExists(out.to_path_buf()));
+        }
+        let wallet = read_wallet(wallet)?;
+        let key = match key_file.exists() {
+            true => encrypted_engine::load_key(key_file)?,
+            false => encrypted_engine::create_key(key_file)?,
+        };
+        signer::seal_wallet(&wallet, out, &key)?;
+        println!("{}", general_purpose::STANDARD.encode(wallet.get_pub_key()));
+        Ok(())
+    }
+
+    /// Signs a transaction for each of `amount` coins of a wallet and sends them to a node,
+    /// which relays them to the miners.
+    ///
+    /// # Arguments
+    /// * `wallet` - The key of the wallet spending the coins.
+    /// * `to` - The base64 encoded public key of the receiver.
+    /// * `amount` - The number of coins to send.
+    /// * `node` - The address of the node telling which coins the wallet owns, and
+    ///   receiving the transactions.
+    /// * `dry_run` - Whether to only have the node check the transactions instead.
+    async fn send(wallet: &Path, to: &str, amount: usize, node: &str, dry_run: bool) -> Result<(), CliError> {
+        let wallet = read_wallet(wallet)?;
+        let receiver = general_purpose::STANDARD
+            .decode(to)
+            .map_err(|_| CliError::InvalidAddress(to.to_string()))?;
+        let chain = client::poll_chain(node).await?;
+        // Signed for the node's network, which would reject them otherwise.
+        let network = client::node_info(node).await?.network;
+        let mut coins = chain.coins_of(&wallet.get_pub_key());
+        if coins.len() < amount {
+            return Err(CliError::InsufficientBalance { needed: amount, available: coins.len() });
+        }
+        let nonce = chain.next_nonce(&wallet.get_pub_key());
+        // Transactions carry a single coin on the wire, so each coin is sent on its own.
+        let transactions = (nonce..).zip(coins.drain(..amount)).map(|(nonce, coin)| {
+            let transaction = Transaction::new(wallet.get_pub_key(), receiver.clone(), vec![coin]).with_nonce(nonce);
+            wallet.sign(transaction, &network)
+        });
+        submit(transactions.collect(), node, dry_run).await
+    }
+
+    /// Creates `amount` coins with the wallet at `wallet`, a mint authority of the chain of
+    /// the node at `node`, and sends them to `to` through it.
+    async fn mint(wallet: &Path, to: &str, amount: usize, node: &str, dry_run: bool) -> Result<(), CliError> {
+        let wallet = read_wallet(wallet)?;
+        let receiver = general_purpose::STANDARD
+            .decode(to)
+            .map_err(|_| CliError::InvalidAddress(to.to_string()))?;
+        let chain = client::poll_chain(node).await?;
+        let network = client::node_info(node).await?.network;
+        let nonce = chain.next_nonce(&wallet.get_pub_key());
+        let transactions = (nonce..nonce + amount as u64).map(|nonce| wallet.mint(receiver.clone(), nonce, &network));
+        submit(transactions.collect(), node, dry_run).await
+    }
+
+    /// Sends `transactions` to the node at `node` and prints their ids, or only prints how
+    /// the node checks them if `dry_run`.
+    async fn submit(transactions: Vec<Transaction>, node: &str, dry_run: bool) -> Result<(), CliError> {
+        let mut rejected = 0;
+        for transaction in transactions {
+            let id = transaction.id();
+            if dry_run {
+                rejected += print_report(&client::validate_transaction(node, &transaction).await?);
+                continue;
+            }
+            client::send_transaction(node, transaction).await?;
+            println!("{}", id);
+        }
+        match rejected {
+            0 => Ok(()),
+            rejected => Err(CliError::RejectedTransactions(rejected)),
+        }
+    }
+
+    /// Asks the operator API at `address`, with the token in `token_file` if any, to
+    /// pause or resume mining, and prints what it did.
+    async fn switch_mining(address: &str, token_file: Option<&Path>, command: MiningCommand) -> Result<(), CliError> {
+        let token = token_file.map(operator::load_token).transpose()?;
+        operator::switch_mining(address, token.as_deref(), command).await?;
+        match command {
+            MiningCommand::Pause => println!("Mining paused."),
+            MiningCommand::Resume => println!("Mining resumed."),
+        }
+        Ok(())
+    }
+
+    /// Prints the outcome of every check of a transaction.
+    ///
+    /// # Returns
+    /// * `usize` - 1 if the transaction failed a check, 0 otherwise.
+    fn print_report(report: &ValidationReport) -> usize {
+        println!("{}", report.id);
+        for outcome in &report.checks {
+            println!("  {}: {}", outcome.check, outcome.error.as_deref().unwrap_or("ok"));
+        }
+        usize::from(!report.is_valid())
+    }
+
+    /// Signs a record writing `value` under `key` and sends it to a miner, once checked
+    /// against the chain of the miner under the default `RecordRules`: records over the
+    /// size limit are not sent. A record costing coins is followed by the signed
+    /// transactions burning them, the miner holding the record back until they land in
+    /// the same block.
+    async fn put_record(wallet: &Path, key: String, value: String, node: &str) -> Result<(), CliError> {
+        let wallet = read_wallet(wallet)?;
+        let network = client::node_info(node).await?.network;
+        let record = wallet.sign_record(Record::new(key, value, wallet.get_pub_key()), &network);
+        let chain = client::poll_chain(node).await?;
+        let rules = RecordRules::default();
+        let record = block::check_record(record, &network, &rules, chain.blocks())?;
+        let cost = rules.cost(&record);
+        let mut coins = chain.coins_of(&wallet.get_pub_key());
+        if coins.len() < cost {
+            return Err(CliError::InsufficientBalance { needed: cost, available: coins.len() });
+        }
+        let id = record.id();
+        client::send_record(node, record).await?;
+        if cost > 0 {
+            let nonce = chain.next_nonce(&wallet.get_pub_key());
+            for (nonce, coin) in (nonce..).zip(coins.drain(..cost)) {
+                let transaction = Transaction::new(wallet.get_pub_key(), ZERO_WALLET_PK.to_vec(), vec![coin]).with_nonce(nonce);
+                client::send_transaction(node, wallet.sign(transaction, &network)).await?;
+            }
+        }
+        println!("{}", id);
+        Ok(())
+    }
+
+    impl ChainSource {
+        async fn read(&self) -> Result<Chain, CliError> {
+            match &self.node {
+                Some(node) => Ok(client::poll_chain(node).await?),
+                None => read_chain(self.store.as_deref().expect("clap requires --node or --store")),
+            }
+        }
+    }
+
+    /// Pulls the blocks of index `from` up to `to` from the node at `node` into the chain at
+    /// `store`, in place of the stored blocks from `from` on. See `client::fetch_blocks`.
+    /// The blocks are added one by one, replaying the difficulty rather than taking the
+    /// node's word for it, and the chain is held to the network, genesis and chain rules
+    /// of `config` before it is saved.
+    ///
+    /// # Arguments
+    /// * `from` - Index of the first block pulled, past the last stored block if `None`.
+    /// * `to` - Index of the block to stop at, excluded, the end of the node's chain if
+    ///   `None`.
+    ///
+    /// # Returns
+    /// * `Result<usize, CliError>` - The number of blocks pulled, or an error if they do
+    ///   not extend the stored blocks into a valid chain or end below the stored ones.
+    async fn pull(store: &Path, config: &NodeConfig, node: &str, from: Option<usize>, to: Option<usize>) -> Result<usize, CliError> {
+        let genesis = config.genesis.as_ref().map(Genesis::read).transpose()?;
+        let network = config.network(genesis.as_ref())?;
+        let mut store = Store::new(store)?;
+        let stored = match store.load::<Chain>() {
+            Ok(chain) => Some(chain),
+            Err(StoreError::EmptyFile) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let from = from.unwrap_or_else(|| stored.as_ref().map_or(0, |chain| chain.get_last_block().index + 1));
+        let pulled = client::fetch_blocks(node, from..to.unwrap_or(usize::MAX)).await?;
+        if pulled.blocks.is_empty() {
+            return Ok(0);
+        }
+        let n_pulled = pulled.blocks.len();
+        let mut pulled = pulled.blocks.into_iter();
+        let kept: Vec<Block> = stored
+            .as_ref()
+            .map(|chain| chain.blocks_from(0).take_while(|block| block.index < from).cloned().collect())
+            .unwrap_or_default();
+        let mut chain = match (&stored, kept.is_empty()) {
+            (Some(stored), false) => Chain::from_blocks_unverified(kept, stored.difficulty_at(from)),
+            _ => {
+                let difficulty = genesis.as_ref().map_or(1, |genesis| genesis.difficulty);
+                Chain::from_genesis(pulled.next().expect("at least one block was pulled"), difficulty)
+            },
+        };
+        for block in pulled {
+            let nonce = block.nonce;
+            chain.add_block(MiningDigest::new(block, nonce))?;
+        }
+        chain.verify_chain()?;
+        if let Some(genesis) = &genesis {
+            genesis.verify(&chain)?;
+        }
+        if *chain.network() != network {
+            return Err(BlockCheckError::WrongNetwork { index: 0, expected: network, got: chain.network().clone() }.into());
+        }
+        config.chain.verify(&chain)?;
+        if let Some(tip) = stored.map(|stored| stored.get_last_block().index) {
+            let pulled = chain.get_last_block().index;
+            if pulled < tip {
+                return Err(CliError::Truncated { pulled, tip });
+            }
+        }
+        store.save(&chain)?;
+        Ok(n_pulled)
+    }
+
+    /// Mines `blocks` blocks on top of the chain at `store`, starting a new chain if the
+    /// store is empty, then saves it.
+    ///
+    /// # Arguments
+    /// * `store` - The chain store.
+    /// * `wallet` - The key of the wallet receiving the rewards, a new one if `None`.
+    /// * `blocks` - The number of blocks to mine.
+    /// * `threads` - The number of threads searching for each nonce.
+    fn mine(store: &Path, wallet: Option<&Path>, blocks: usize, threads: usize) -> Result<(), CliError> {
+        let mut store = Store::new(store)?;
+        let mut chain = match store.load::<Chain>() {
+            Ok(chain) => chain,
+            Err(StoreError::EmptyFile) => Chain::new(),
+            Err(e) => return Err(e.into()),
+        };
+        chain.verify_chain()?;
+        let mut miner = Miner::new(0, "cli".to_string());
+        if let Some(wallet) = wallet {
+            miner.wallet = read_wallet(wallet)?;
+        }
+        miner.set_threads(threads);
+        miner.set_network(chain.network().clone());
+        for _ in 0..blocks {
+            miner.set_chain_meta(chain.get_len(), chain.difficulty, chain.shared_blocks());
+            let mining_digest = miner.mine(chain.get_last_block())?;
+            info!("Mined block {}", mining_digest.get_block().hash);
+            chain.add_block(mining_digest)?;
+        }
+        store.save(&chain)?;
+        println!("height: {}", chain.len());
+        Ok(())
+    }
+
+    /// Reads the wallet whose key is in the file at `path`, holding the base64 encoding of a
+    /// PKCS#8 document. See `Wallet::pkcs8`.
+    fn read_wallet(path: &Path) -> Result<Wallet, CliError> {
+        let encoded = fs::read_to_string(path)
+            .map(Zeroizing::new)
+            .map_err(|source| CliError::ReadError { path: path.to_path_buf(), source })?;
+        Wallet::from_base64(&encoded).ok_or_else(|| CliError::InvalidWallet(path.to_path_buf()))
+    }
+
+    /// Reads the node configuration at `path`. See `NodeConfig::from_toml`.
+    fn read_config(path: &Path) -> Result<NodeConfig, CliError> {
+        let toml = fs::read_to_string(path).map_err(|source| CliError::ReadError { path: path.to_path_buf(), source })?;
+        Ok(NodeConfig::from_toml(&toml)?)
+    }
+
+        /// Reads and verifies the chain at `path` without writing to the store.
+    fn read_chain(path: &Path) -> Result<Chain, CliError> {
+        let chain: Chain = Store::open_read_only(path)?.load()?;
+        chain.verify_chain()?;
+        Ok(chain)
+    }
+}
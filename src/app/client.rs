@@ -0,0 +1,138 @@
+pub mod client {
+
+    use crate::{
+        chain::chain::chain::{BlockCheckError, Chain},
+        node::{
+            gossip::gossip::{self, LISTEN_TIMEOUT, MAX_GOSSIP_INTERVAL},
+            listener::listener::Listener,
+            metrics::metrics::Metrics,
+            neighbour::neighbour::{Neighbour, Role},
+            outbound::outbound::PEER_SEND_RATE,
+            rpc::rpc::{PendingRequests, RpcError},
+            status::status::{NodeStatus, PeerStatus},
+            sync::sync::{self, RangeResponse, SyncError},
+        },
+        record::record::record::Record,
+        transaction::{transaction::transaction::Transaction, validation::validation::ValidationReport},
+    };
+
+    use std::{
+        future::Future,
+        io::Error as IOError,
+        ops::Range,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use thiserror::Error;
+    use uuid::Uuid;
+
+    /// Address messages to nodes are sent from. Nodes answer to wherever they came from.
+    pub const CLIENT_ADDRESS: &str = "0.0.0.0:0";
+    /// How long to keep asking a node for its chain. Nodes only read their messages once
+    /// per gossip round, and idle ones wait up to `MAX_GOSSIP_INTERVAL` between rounds.
+    pub const POLL_DEADLINE: Duration = Duration::from_secs(MAX_GOSSIP_INTERVAL + LISTEN_TIMEOUT);
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum ClientError {
+        #[error("Failed to reach the node: {0}")]
+        NetworkError(IOError),
+        #[error(transparent)]
+        RpcError(RpcError),
+        #[error("Invalid chain: {0}")]
+        BlockCheckError(BlockCheckError),
+        #[error(transparent)]
+        SyncError(SyncError),
+    }
+
+    /// A node this client talks to, without a session: messages to it go in the clear.
+    pub fn as_neighbour(address: &str) -> Neighbour {
+        Neighbour::new(Uuid::nil(), address.to_string(), Role::Node)
+    }
+
+    /// Sends a request to the node at `address` until it answers or `POLL_DEADLINE` passes.
+    ///
+    /// # Arguments
+    /// * `request` - Sends the request from `CLIENT_ADDRESS` to the given node, and waits
+    ///   for the answer among the given pending requests.
+    async fn ask<T, F, Fut>(address: &str, request: F) -> Result<T, ClientError>
+    where
+        F: Fn(Neighbour, PendingRequests) -> Fut,
+        Fut: Future<Output = Result<T, RpcError>>,
+    {
+        // The answer arrives on the socket the request left from, read by this listener.
+        let (listener, _incoming) = Listener::spawn(CLIENT_ADDRESS.into(), Arc::new(Metrics::new()), PEER_SEND_RATE).await?;
+        let started = Instant::now();
+        loop {
+            match request(as_neighbour(address), listener.pending()).await {
+                Ok(answer) => return Ok(answer),
+                Err(RpcError::Timeout(_)) if started.elapsed() < POLL_DEADLINE => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Asks the node at `address` for its chain, and verifies it.
+    pub async fn poll_chain(address: &str) -> Result<Chain, ClientError> {
+        let chain = ask(address, |node, pending| async move {
+            gossip::poll_chain(CLIENT_ADDRESS.into(), &node, &pending).await
+        }).await?;
+        chain.verify_chain()?;
+        Ok(chain)
+    }
+
+    /// Asks the node at `address` for the blocks of index within `range`, a batch at a
+    /// time, as `Node::fetch_blocks` does.
+    ///
+    /// # Returns
+    /// * `Result<RangeResponse, ClientError>` - The blocks, oldest first, fewer than asked
+    ///   for if the chain of the node ends first, and the difficulty it reached.
+    pub async fn fetch_blocks(address: &str, range: Range<usize>) -> Result<RangeResponse, ClientError> {
+        sync::fetch_range(range, |range| ask(address, move |node, pending| {
+            let range = range.clone();
+            async move { gossip::get_range(CLIENT_ADDRESS.into(), &node, &pending, range).await }
+        })).await
+    }
+
+    /// Asks the node at `address` to check a transaction without relaying it.
+    pub async fn validate_transaction(address: &str, transaction: &Transaction) -> Result<ValidationReport, ClientError> {
+        ask(address, |node, pending| {
+            let transaction = transaction.clone();
+            async move { gossip::validate_transaction(CLIENT_ADDRESS.into(), &node, &pending, transaction).await }
+        }).await
+    }
+
+    /// Asks the node at `address` what it tells about itself.
+    pub async fn node_info(address: &str) -> Result<NodeStatus, ClientError> {
+        ask(address, |node, pending| async move {
+            gossip::get_node_info(CLIENT_ADDRESS.into(), &node, &pending).await
+        }).await
+    }
+
+    /// Asks the node at `address` what it knows about each of its neighbours.
+    pub async fn peer_info(address: &str) -> Result<Vec<PeerStatus>, ClientError> {
+        ask(address, |node, pending| async move {
+            gossip::get_peer_info(CLIENT_ADDRESS.into(), &node, &pending).await
+        }).await
+    }
+
+    /// Asks the miner at `address` for the transactions waiting to be mined, or only the
+    /// one with id `transaction_id`.
+    pub async fn mempool(address: &str, transaction_id: Option<&str>) -> Result<Vec<Transaction>, ClientError> {
+        ask(address, |node, pending| async move {
+            gossip::get_mempool(CLIENT_ADDRESS.into(), &node, &pending, transaction_id).await
+        }).await
+    }
+
+    /// Sends a signed transaction to the node at `address`, which relays it to the miners.
+    pub async fn send_transaction(address: &str, transaction: Transaction) -> Result<(), ClientError> {
+        gossip::send_transaction(CLIENT_ADDRESS.into(), &as_neighbour(address), transaction).await?;
+        Ok(())
+    }
+
+    /// Sends a signed record to the miner at `address`.
+    pub async fn send_record(address: &str, record: Record) -> Result<(), ClientError> {
+        gossip::send_record(CLIENT_ADDRESS.into(), &as_neighbour(address), record).await?;
+        Ok(())
+    }
+}
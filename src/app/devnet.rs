@@ -0,0 +1,130 @@
+pub mod devnet {
+
+    use crate::{
+        chain::{chain::chain::Chain, network::network::NetworkId},
+        node::{
+            bootstrap::bootstrap::INITIAL_BACKOFF,
+            gossip::gossip::GOSSIP_INTERVAL,
+            config::config::{NodeBuilder, NodeConfigError},
+            neighbour::neighbour::Role,
+            node::node::{Node, NodeLoopError, StopHandle},
+        },
+        store::store::store::StoreError,
+    };
+
+    use std::sync::Arc;
+
+    use tokio::task::JoinHandle;
+    use tracing::{info, warn};
+
+    pub const DEFAULT_HOST: &str = "127.0.0.1";
+    pub const DEFAULT_BASE_PORT: u16 = 8081;
+    pub const DEFAULT_MINERS: usize = 1;
+    pub const DEFAULT_NODES: usize = 2;
+    /// Longest wait (in milliseconds) between two rounds of greetings to the tracker, which
+    /// nodes keep greeting until they get in.
+    const MAX_DIAL_BACKOFF: u64 = 2000;
+
+    /// A node of the devnet, running its loop in a task that hands it back once stopped.
+    struct Member {
+        role: Role,
+        address: Arc<str>,
+        stop: StopHandle,
+        task: JoinHandle<(Node, Result<(), NodeLoopError>)>,
+    }
+
+    /// A local network of nodes running in this process, for development: one tracker,
+    /// miners and regular nodes on consecutive ports, all sharing the same genesis block.
+    pub struct Devnet {
+        members: Vec<Member>,
+    }
+
+    impl Devnet {
+        /// Starts a tracker on `base_port`, then miners and regular nodes on the ports
+        /// following it. Every node enters the network through the tracker.
+        ///
+        /// # Arguments
+        /// * `host` - The IP address the nodes bind.
+        /// * `base_port` - The tracker's port.
+        /// * `miners` - Number of miners.
+        /// * `nodes` - Number of regular nodes.
+        ///
+        /// # Returns
+        /// * `Result<Devnet, NodeConfigError>` - The running devnet, or an error if a node
+        ///   cannot be configured, e.g. because the ports run past 65535.
+        pub fn launch(host: &str, base_port: u16, miners: usize, nodes: usize) -> Result<Self, NodeConfigError> {
+            let genesis = Chain::for_network(&NetworkId::devnet());
+            let tracker = format!("{}:{}", host, base_port);
+            let roles = std::iter::once(Role::Tracker)
+                .chain(std::iter::repeat_n(Role::Miner, miners))
+                .chain(std::iter::repeat_n(Role::Node, nodes));
+            let mut members = vec![];
+            for (offset, role) in roles.enumerate() {
+                let port = u16::try_from(offset)
+                    .ok()
+                    .and_then(|offset| base_port.checked_add(offset))
+                    .ok_or_else(|| NodeConfigError::InvalidAddress(format!("{}:{}", host, base_port as usize + offset)))?;
+                // Every node is trusted, so the tracker hands out no admission puzzles, and
+                // gossip does not slow down when idle, so greetings are read promptly.
+                let mut builder = NodeBuilder::new()
+                    .with_address(format!("{}:{}", host, port))
+                    .with_role(role)
+                    .with_admission_difficulty(0)
+                    .with_max_gossip_interval(GOSSIP_INTERVAL)
+                    .with_dial_policy(None, INITIAL_BACKOFF, MAX_DIAL_BACKOFF)
+                    .with_network(NetworkId::devnet())
+                    .with_chain(genesis.clone());
+                if role != Role::Tracker {
+                    builder = builder.with_trackers(vec![tracker.clone()]);
+                }
+                let mut node = builder.build()?;
+                let address = node.get_address();
+                let stop = node.stop_handle();
+                let task = tokio::spawn(async move {
+                    let result = match role {
+                        Role::Tracker => node.node_loop().await.map_err(NodeLoopError::from),
+                        _ => node.enter_and_node_loop().await,
+                    };
+                    (node, result)
+                });
+                members.push(Member { role, address, stop, task });
+            }
+            info!("Started a devnet of {} nodes", members.len());
+            Ok(Devnet { members })
+        }
+
+        /// Returns the role and address of every node, the tracker first.
+        pub fn addresses(&self) -> Vec<(Role, Arc<str>)> {
+            self.members.iter().map(|member| (member.role, member.address.clone())).collect()
+        }
+
+        /// Stops every node, then shuts them down one by one. See `Node::shutdown`.
+        ///
+        /// # Returns
+        /// * `Result<(), StoreError>` - The first error met flushing a node's stores. Every
+        ///   node is shut down regardless.
+        pub async fn shutdown(self) -> Result<(), StoreError> {
+            for member in &self.members {
+                member.stop.stop();
+            }
+            let mut result = Ok(());
+            for member in self.members {
+                let (mut node, looped) = match member.task.await {
+                    Ok(stopped) => stopped,
+                    Err(e) => {
+                        warn!("The node at {} did not stop: {}", member.address, e);
+                        continue;
+                    },
+                };
+                if let Err(e) = looped {
+                    warn!("The node at {} stopped on an error: {}", member.address, e);
+                }
+                if let Err(e) = node.shutdown().await {
+                    warn!("The node at {} failed to shut down: {}", member.address, e);
+                    result = result.and(Err(e));
+                }
+            }
+            result
+        }
+    }
+}
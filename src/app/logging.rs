@@ -0,0 +1,211 @@
+pub mod logging {
+
+    use std::{
+        fs::{self, File, OpenOptions},
+        io::{Result as IOResult, Write},
+        path::{Path, PathBuf},
+        sync::Mutex,
+    };
+
+    use chrono::{NaiveDate, Utc};
+    use clap::{Args, ValueEnum};
+    use tracing::level_filters::LevelFilter;
+    use tracing_subscriber::{
+        fmt::writer::BoxMakeWriter,
+        prelude::*,
+        EnvFilter, Layer, Registry,
+    };
+
+    /// Rotated log files kept next to the current one. Older ones are deleted.
+    pub const KEPT_LOGS: usize = 7;
+
+    /// How log lines are written.
+    #[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+    pub enum LogFormat {
+        /// One human readable line per event.
+        Text,
+        /// One JSON object per event, for log collectors.
+        Json,
+    }
+
+    /// When the log file is set aside for a new one.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Rotation {
+        Never,
+        /// On the first event of every day (UTC).
+        Daily,
+        /// Before the file grows past this many bytes.
+        Size(u64),
+    }
+
+    impl Rotation {
+        /// Reads `never`, `daily` or `size:BYTES`.
+        ///
+        /// # Returns
+        /// * `Result<Rotation, String>` - The rotation, or why `spec` names none.
+        pub fn parse(spec: &str) -> Result<Self, String> {
+            match spec.split_once(':') {
+                None if spec == "never" => Ok(Rotation::Never),
+                None if spec == "daily" => Ok(Rotation::Daily),
+                Some(("size", bytes)) => match bytes.parse() {
+                    Ok(bytes) if bytes > 0 => Ok(Rotation::Size(bytes)),
+                    _ => Err(format!("expected a positive number of bytes, got `{}`", bytes)),
+                },
+                _ => Err(format!("expected `never`, `daily` or `size:BYTES`, got `{}`", spec)),
+            }
+        }
+    }
+
+    /// Where and how the process logs. Logs go to stdout unless a file is given.
+    #[derive(Args, Debug, Clone)]
+    pub struct LogOptions {
+        /// Format of the log lines.
+        #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+        pub log_format: LogFormat,
+        /// File to write the logs to instead of stdout. Its directory is created if needed.
+        #[arg(long, global = true)]
+        pub log_file: Option<PathBuf>,
+        /// When to rotate the log file: `never`, `daily` or `size:BYTES`. Rotated files
+        /// are renamed after the time they were set aside.
+        #[arg(long, global = true, requires = "log_file", default_value = "daily", value_parser = Rotation::parse)]
+        pub log_rotation: Rotation,
+    }
+
+    /// A log file set aside for a new one according to its `Rotation`.
+    pub struct RotatingFile {
+        path: PathBuf,
+        rotation: Rotation,
+        file: File,
+        /// Bytes in the current file.
+        size: u64,
+        /// Day the current file was opened on.
+        day: NaiveDate,
+    }
+
+    impl RotatingFile {
+        /// Opens the log file at `path` for appending, creating it and its directory if
+        /// they do not exist.
+        pub fn open(path: &Path, rotation: Rotation) -> IOResult<Self> {
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            let metadata = file.metadata()?;
+            let day = metadata
+                .modified()
+                .map(|modified| chrono::DateTime::<Utc>::from(modified).date_naive())
+                .unwrap_or_else(|_| Utc::now().date_naive());
+            Ok(RotatingFile {
+                path: path.to_path_buf(),
+                rotation,
+                file,
+                size: metadata.len(),
+                day,
+            })
+        }
+
+        fn is_due(&self, incoming: usize) -> bool {
+            match self.rotation {
+                Rotation::Never => false,
+                Rotation::Daily => Utc::now().date_naive() != self.day,
+                Rotation::Size(max) => self.size > 0 && self.size + incoming as u64 > max,
+            }
+        }
+
+        /// Renames the current file after the time it is set aside, opens a new one and
+        /// deletes the rotated files past `KEPT_LOGS`.
+        fn rotate(&mut self) -> IOResult<()> {
+            self.file.flush()?;
+            let stamp = match self.rotation {
+                Rotation::Daily => self.day.format("%Y-%m-%d").to_string(),
+                _ => Utc::now().format("%Y-%m-%dT%H-%M-%S%.6f").to_string(),
+            };
+            let mut rotated = self.path.clone().into_os_string();
+            rotated.push(".");
+            rotated.push(stamp);
+            // Files rotated within the same microsecond are told apart by a counter.
+            let mut target = PathBuf::from(&rotated);
+            let mut n = 0;
+            while target.exists() {
+                n += 1;
+                target = PathBuf::from(format!("{}.{}", rotated.to_string_lossy(), n));
+            }
+            fs::rename(&self.path, &target)?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.size = 0;
+            self.day = Utc::now().date_naive();
+            self.prune()
+        }
+
+        /// Deletes the oldest rotated files, keeping `KEPT_LOGS`. Their names sort by age.
+        fn prune(&self) -> IOResult<()> {
+            let Some(name) = self.path.file_name().and_then(|name| name.to_str()) else {
+                return Ok(());
+            };
+            let prefix = format!("{}.", name);
+            let directory = match self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                Some(parent) => parent.to_path_buf(),
+                None => PathBuf::from("."),
+            };
+            let mut rotated: Vec<PathBuf> = fs::read_dir(&directory)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name().to_str().is_some_and(|file| file.starts_with(&prefix)))
+                .map(|entry| entry.path())
+                .collect();
+            rotated.sort();
+            let excess = rotated.len().saturating_sub(KEPT_LOGS);
+            for path in &rotated[..excess] {
+                fs::remove_file(path)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Write for RotatingFile {
+        fn write(&mut self, buf: &[u8]) -> IOResult<usize> {
+            if self.is_due(buf.len()) {
+                self.rotate()?;
+            }
+            let written = self.file.write(buf)?;
+            self.size += written as u64;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> IOResult<()> {
+            self.file.flush()
+        }
+    }
+
+    /// Sets up logging for the process. The level defaults to DEBUG and is overridden by
+    /// `RUST_LOG`.
+    ///
+    /// # Returns
+    /// * `IOResult<()>` - An error if the log file cannot be opened.
+    pub fn init(options: &LogOptions) -> IOResult<()> {
+        let env = EnvFilter::builder()
+            .with_default_directive(LevelFilter::DEBUG.into())
+            .with_env_var("RUST_LOG")
+            .from_env_lossy();
+
+        let writer = match &options.log_file {
+            Some(path) => BoxMakeWriter::new(Mutex::new(RotatingFile::open(path, options.log_rotation)?)),
+            None => BoxMakeWriter::new(std::io::stdout),
+        };
+        let fmt_layer = tracing_subscriber::fmt::layer()
+            .with_file(true)
+            .with_line_number(true)
+            .with_thread_ids(false)
+            .with_target(false)
+            .with_ansi(options.log_file.is_none())
+            .with_writer(writer);
+        let fmt_layer: Box<dyn Layer<Registry> + Send + Sync> = match options.log_format {
+            LogFormat::Text => fmt_layer.compact().boxed(),
+            LogFormat::Json => fmt_layer.json().boxed(),
+        };
+        tracing_subscriber::registry()
+            .with(fmt_layer)
+            .with(env)
+            .init();
+        Ok(())
+    }
+}
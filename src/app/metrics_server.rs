@@ -0,0 +1,94 @@
+pub mod metrics_server {
+
+    use crate::node::metrics::metrics::{Health, Metrics};
+    use crate::clock::clock::clock;
+
+    use std::{
+        io::Result as IOResult,
+        sync::Arc,
+    };
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tracing::{debug, info};
+
+    /// Largest request read before answering. Only the request line matters.
+    const MAX_REQUEST_SIZE: usize = 4096;
+
+    /// Serves `metrics` over HTTP at `/metrics`, in the Prometheus text format, until
+    /// the task running it is dropped. The health checks of the node are served for
+    /// container orchestrators: `/healthz` answers 200 while the node can save to its
+    /// stores, and `/readyz` while it also is synced and has a neighbour. Both answer 503
+    /// otherwise, with the outcome of every check as JSON. See `Health`.
+    ///
+    /// # Arguments
+    /// * `address` - The TCP address to listen on, e.g. `127.0.0.1:9100`.
+    /// * `metrics` - The live metrics of a node. See `Node::metrics_handle`.
+    /// * `max_tip_age` - Age (in seconds) of the last block past which the node is not synced.
+    pub async fn serve(address: &str, metrics: Arc<Metrics>, max_tip_age: u64) -> IOResult<()> {
+        let listener = TcpListener::bind(address).await?;
+        info!("Serving metrics on http://{}/metrics", address);
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let metrics = metrics.clone();
+            tokio::spawn(async move {
+                if let Err(e) = answer(stream, &metrics, max_tip_age).await {
+                    debug!("Failed to answer metrics request from {}: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    fn health(metrics: &Metrics, max_tip_age: u64) -> Health {
+        metrics.snapshot().health(max_tip_age, clock::now())
+    }
+
+    fn health_response(health: &Health, passed: bool) -> String {
+        let status = match passed {
+            true => "200 OK",
+            false => "503 Service Unavailable",
+        };
+        let body = serde_json::to_string(health).expect("health always serializes");
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body,
+        )
+    }
+
+    async fn answer(mut stream: TcpStream, metrics: &Metrics, max_tip_age: u64) -> IOResult<()> {
+        let mut request = vec![0u8; MAX_REQUEST_SIZE];
+        let n_bytes = stream.read(&mut request).await?;
+        let request = String::from_utf8_lossy(&request[..n_bytes]);
+        let path = request
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("GET "))
+            .and_then(|rest| rest.split_whitespace().next());
+
+        let response = match path {
+            Some("/metrics") => {
+                let body = metrics.snapshot().to_prometheus();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body,
+                )
+            },
+            Some("/healthz") => {
+                let health = health(metrics, max_tip_age);
+                health_response(&health, health.is_live())
+            },
+            Some("/readyz") => {
+                let health = health(metrics, max_tip_age);
+                health_response(&health, health.is_ready())
+            },
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        };
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
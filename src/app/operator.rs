@@ -0,0 +1,314 @@
+pub mod operator {
+
+    use crate::{
+        app::client::client::{self, ClientError},
+        node::{
+            mining::mining::MiningCommand,
+            node::node::{OperatorHandle, StopHandle, WrongRoleError},
+            status::status::{NodeStatus, PeerStatus},
+        },
+        token::token::token::Token,
+        transaction::transaction::transaction::Transaction,
+    };
+
+    use std::{
+        io::Error as IOError,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+        path::{Path as FilePath, PathBuf},
+        sync::Arc,
+    };
+
+    use axum::{
+        extract::{Path, Request, State},
+        http::{header, StatusCode},
+        middleware::{self, Next},
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use base64::{Engine as _, engine::general_purpose};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use thiserror::Error;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use tracing::info;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum OperatorError {
+        #[error("{0} is not a loopback address - the operator API needs a token to listen on it.")]
+        #[from(ignore)]
+        NoToken(String),
+        #[error("Failed to read the operator token from {}: {source}", path.display())]
+        #[from(ignore)]
+        TokenError { path: PathBuf, source: IOError },
+        #[error("{} holds no token.", .0.display())]
+        #[from(ignore)]
+        EmptyToken(PathBuf),
+        #[error("Failed to serve the operator API: {0}")]
+        ServeError(IOError),
+        #[error("Failed to reach the operator API: {0}")]
+        #[from(ignore)]
+        RequestError(IOError),
+        #[error("{0}")]
+        #[from(ignore)]
+        Refused(String),
+    }
+
+    /// What the operator API answers for a transaction the mempool does not hold.
+    const NOT_PENDING: &str = "No such transaction waits to be mined.";
+
+    /// Reads the token operators present as `Authorization: Bearer <token>`, the first
+    /// line of the file at `path`.
+    pub fn load_token(path: &FilePath) -> Result<String, OperatorError> {
+        let token = std::fs::read_to_string(path)
+            .map_err(|source| OperatorError::TokenError { path: path.to_path_buf(), source })?;
+        match token.lines().next().map(str::trim) {
+            Some(token) if !token.is_empty() => Ok(token.to_string()),
+            _ => Err(OperatorError::EmptyToken(path.to_path_buf())),
+        }
+    }
+
+    /// What the operator API acts on.
+    struct Operator {
+        /// Address the node's RPCs are sent to.
+        node: String,
+        stop: StopHandle,
+        /// Evicts and pauses mining on the node, which takes neither from the network.
+        control: OperatorHandle,
+        /// Token requests to the operator API must carry.
+        token: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct ErrorBody {
+        error: String,
+    }
+
+    struct ApiError(StatusCode, String);
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            (self.0, Json(ErrorBody { error: self.1 })).into_response()
+        }
+    }
+
+    impl From<ClientError> for ApiError {
+        fn from(e: ClientError) -> Self {
+            ApiError(StatusCode::BAD_GATEWAY, e.to_string())
+        }
+    }
+
+    /// A transaction waiting to be mined. Keys are base64 encoded.
+    #[derive(Serialize)]
+    struct PendingView {
+        id: String,
+        sender: String,
+        receiver: String,
+        coins: Vec<String>,
+        timestamp: u64,
+        nonce: u64,
+    }
+
+    /// Rejects requests without the token, when one is configured.
+    async fn authorize(State(operator): State<Arc<Operator>>, request: Request, next: Next) -> Response {
+        let Some(token) = &operator.token else {
+            return next.run(request).await;
+        };
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        // Digests are compared rather than tokens, so the time it takes tells nothing of
+        // how much of the token was guessed right.
+        match presented {
+            Some(presented) if Sha256::digest(presented) == Sha256::digest(token) => next.run(request).await,
+            _ => ApiError(StatusCode::UNAUTHORIZED, "Missing or wrong operator token.".to_string()).into_response(),
+        }
+    }
+
+    async fn get_node(State(operator): State<Arc<Operator>>) -> Result<Json<NodeStatus>, ApiError> {
+        Ok(Json(client::node_info(&operator.node).await?))
+    }
+
+    async fn get_peers(State(operator): State<Arc<Operator>>) -> Result<Json<Vec<PeerStatus>>, ApiError> {
+        Ok(Json(client::peer_info(&operator.node).await?))
+    }
+
+    async fn get_mempool(State(operator): State<Arc<Operator>>) -> Result<Json<Vec<PendingView>>, ApiError> {
+        let pending = client::mempool(&operator.node, None).await?;
+        Ok(Json(pending.iter().map(pending_view).collect()))
+    }
+
+    async fn get_pending(
+        State(operator): State<Arc<Operator>>,
+        Path(id): Path<String>,
+    ) -> Result<Json<PendingView>, ApiError> {
+        let pending = client::mempool(&operator.node, Some(&id)).await?;
+        pending
+            .first()
+            .map(|transaction| Json(pending_view(transaction)))
+            .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, NOT_PENDING.to_string()))
+    }
+
+    async fn delete_pending(
+        State(operator): State<Arc<Operator>>,
+        Path(id): Path<String>,
+    ) -> Result<StatusCode, ApiError> {
+        match operator.control.evict(&id).await {
+            Ok(true) => Ok(StatusCode::NO_CONTENT),
+            Ok(false) => Err(ApiError(StatusCode::NOT_FOUND, NOT_PENDING.to_string())),
+            Err(e) => Err(ApiError(StatusCode::CONFLICT, e.to_string())),
+        }
+    }
+
+    /// Answers 204 once mining is paused or resumed, whether it already was or not.
+    fn mining_switched(switched: Result<bool, WrongRoleError>) -> Result<StatusCode, ApiError> {
+        switched
+            .map(|_| StatusCode::NO_CONTENT)
+            .map_err(|e| ApiError(StatusCode::CONFLICT, e.to_string()))
+    }
+
+    async fn post_pause_mining(State(operator): State<Arc<Operator>>) -> Result<StatusCode, ApiError> {
+        mining_switched(operator.control.pause_mining())
+    }
+
+    async fn post_resume_mining(State(operator): State<Arc<Operator>>) -> Result<StatusCode, ApiError> {
+        mining_switched(operator.control.resume_mining())
+    }
+
+    /// Stops the node the way SIGTERM does.
+    async fn post_shutdown(State(operator): State<Arc<Operator>>) -> StatusCode {
+        info!("Stopping on request of the operator API");
+        operator.stop.stop();
+        StatusCode::ACCEPTED
+    }
+
+    fn pending_view(transaction: &Transaction) -> PendingView {
+        PendingView {
+            id: transaction.id(),
+            sender: general_purpose::STANDARD.encode(&transaction.sender),
+            receiver: general_purpose::STANDARD.encode(&transaction.receiver),
+            coins: transaction.coins.iter().map(Token::to_string).collect(),
+            timestamp: transaction.timestamp,
+            nonce: transaction.nonce,
+        }
+    }
+
+    /// Returns the address this machine reaches the node bound to `address` at.
+    fn local_address(address: &str) -> String {
+        match address.parse::<SocketAddr>() {
+            Ok(mut address) if address.ip().is_unspecified() => {
+                address.set_ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+                address.to_string()
+            },
+            _ => address.to_string(),
+        }
+    }
+
+    /// Binds the operator API to `address`, so a node misconfigured to expose it to
+    /// everyone fails before it starts.
+    ///
+    /// # Returns
+    /// * `Result<TcpListener, OperatorError>` - The listener to `serve` on, or
+    ///   `OperatorError::NoToken` if `address` is not a loopback one and no token is given.
+    pub async fn listen(address: &str, token: Option<&str>) -> Result<TcpListener, OperatorError> {
+        let local = address
+            .parse::<SocketAddr>()
+            .is_ok_and(|address| address.ip().is_loopback());
+        if !local && token.is_none() {
+            return Err(OperatorError::NoToken(address.to_string()));
+        }
+        Ok(TcpListener::bind(address).await?)
+    }
+
+    /// Serves the endpoints reserved to operators over HTTP, apart from the public REST
+    /// API, until the task running it is dropped:
+    ///
+    /// * `GET /node` - The `NodeStatus` of the node.
+    /// * `GET /peers` - The `PeerStatus` of each neighbour.
+    /// * `GET /mempool` and `GET /mempool/{id}` - Transactions waiting to be mined.
+    /// * `DELETE /mempool/{id}` - Evicts a transaction from the mempool.
+    /// * `POST /mining/pause` and `POST /mining/resume` - Pauses and resumes mining.
+    /// * `POST /shutdown` - Stops the node.
+    ///
+    /// # Arguments
+    /// * `listener` - Where to accept requests. See `listen`.
+    /// * `node` - The address the node is bound to.
+    /// * `stop` - Stops the node. See `Node::stop_handle`.
+    /// * `control` - Evicts and pauses mining on the node. See `Node::operator_handle`.
+    /// * `token` - The token requests must carry as `Authorization: Bearer <token>`. See
+    ///   `load_token`.
+    pub async fn serve(
+        listener: TcpListener,
+        node: &str,
+        stop: StopHandle,
+        control: OperatorHandle,
+        token: Option<String>,
+    ) -> Result<(), OperatorError> {
+        let address = listener.local_addr()?;
+        let operator = Arc::new(Operator { node: local_address(node), stop, control, token });
+        let router = Router::new()
+            .route("/node", get(get_node))
+            .route("/peers", get(get_peers))
+            .route("/mempool", get(get_mempool))
+            .route("/mempool/:id", get(get_pending).delete(delete_pending))
+            .route("/mining/pause", post(post_pause_mining))
+            .route("/mining/resume", post(post_resume_mining))
+            .route("/shutdown", post(post_shutdown))
+            .route_layer(middleware::from_fn_with_state(operator.clone(), authorize))
+            .with_state(operator);
+        info!("Serving the operator API on http://{}", address);
+        axum::serve(listener, router).await?;
+        Ok(())
+    }
+
+    /// Asks the operator API at `address` to evict the transaction with id `id` from the
+    /// mempool of its node. See `serve`.
+    pub async fn evict(address: &str, token: Option<&str>, id: &str) -> Result<(), OperatorError> {
+        request(address, token, "DELETE", &format!("/mempool/{}", id)).await
+    }
+
+    /// Asks the operator API at `address` to pause or resume the mining of its node.
+    pub async fn switch_mining(address: &str, token: Option<&str>, command: MiningCommand) -> Result<(), OperatorError> {
+        let path = match command {
+            MiningCommand::Pause => "/mining/pause",
+            MiningCommand::Resume => "/mining/resume",
+        };
+        request(address, token, "POST", path).await
+    }
+
+    /// Sends a request without a body to the operator API at `address`.
+    ///
+    /// # Returns
+    /// * `Result<(), OperatorError>` - `OperatorError::Refused` with the error the API
+    ///   answered, unless it answered with a 2xx status.
+    async fn request(address: &str, token: Option<&str>, method: &str, path: &str) -> Result<(), OperatorError> {
+        let authorization = token.map(|token| format!("Authorization: Bearer {}\r\n", token)).unwrap_or_default();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+            method,
+            path,
+            address,
+            authorization,
+        );
+        let mut response = vec![];
+        let mut stream = TcpStream::connect(address).await.map_err(OperatorError::RequestError)?;
+        stream.write_all(request.as_bytes()).await.map_err(OperatorError::RequestError)?;
+        stream.read_to_end(&mut response).await.map_err(OperatorError::RequestError)?;
+
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        if status_line.split_whitespace().nth(1).is_some_and(|status| status.starts_with('2')) {
+            return Ok(());
+        }
+        let body = response.split_once("\r\n\r\n").map_or("", |(_, body)| body);
+        Err(OperatorError::Refused(match serde_json::from_str::<ErrorBody>(body) {
+            Ok(body) => body.error,
+            Err(_) => status_line.to_string(),
+        }))
+    }
+}
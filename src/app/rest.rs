@@ -0,0 +1,767 @@
+pub mod rest {
+
+    use crate::{
+        chain::{
+            block::block::block::Block,
+            chain::chain::{Chain, KeyPage},
+            config::config::MintPolicy,
+            network::network::NetworkId,
+            state::state::{self as chain_state, StateProof},
+        },
+        clock::clock::clock,
+        explorer::explorer::explorer::{Explorer, Page, Paged},
+        node::{
+            event::event::NodeEvent,
+            neighbour::neighbour::{PeerRecord, Role},
+        },
+        record::record::record::Record,
+        store::store::store::StoreError,
+        token::token::token::Token,
+        transaction::{transaction::transaction::Transaction, validation::validation},
+    };
+
+    use std::{
+        io::Result as IOResult,
+        sync::Arc,
+    };
+
+    use axum::{
+        extract::{
+            ws::{Message, WebSocket, WebSocketUpgrade},
+            Path, Query, State,
+        },
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use base64::{Engine as _, engine::general_purpose};
+    use serde::{Deserialize, Serialize};
+    use tokio::{
+        net::TcpListener,
+        sync::{
+            broadcast::{self, error::RecvError},
+            mpsc, watch,
+        },
+    };
+    use tracing::{debug, info};
+    use utoipa::{IntoParams, OpenApi, ToSchema};
+    use uuid::Uuid;
+
+    /// Results per page of the explorer's queries when the client does not ask for a size.
+    pub const DEFAULT_PAGE_SIZE: usize = 20;
+    /// Most results per page of the explorer's queries.
+    pub const MAX_PAGE_SIZE: usize = 100;
+
+    /// Documentation of the API, served at `/openapi.json`.
+    #[derive(OpenApi)]
+    #[openapi(
+        info(
+            title = "humble_blockchain",
+            description = "Read the chain and peers of a node, and submit transactions to it.",
+        ),
+        paths(
+            get_block,
+            get_block_by_hash,
+            get_transaction,
+            post_transaction,
+            get_address_transactions,
+            get_record,
+            get_record_history,
+            get_record_keys,
+            get_coin_proof,
+            get_record_proof,
+            get_peers,
+            get_events,
+        ),
+        components(schemas(
+            ApiEvent,
+            BlockView,
+            TransactionView,
+            TransactionPage,
+            RecordView,
+            RecordPage,
+            KeyList,
+            StateProofView,
+            StateLeafView,
+            PeerView,
+            TransactionSubmission,
+            SubmittedTransaction,
+            ErrorBody,
+        )),
+    )]
+    pub struct ApiDoc;
+
+    /// What the API reads from and writes to a running node.
+    #[derive(Clone)]
+    pub struct ApiState {
+        chain: watch::Receiver<Chain>,
+        peers: watch::Receiver<Vec<PeerRecord>>,
+        transactions: mpsc::Sender<String>,
+        /// Only resubscribed from, once per `/events` subscriber.
+        events: Arc<broadcast::Receiver<NodeEvent>>,
+        /// Answers the paginated queries, which are not served without it.
+        explorer: Option<Arc<Explorer>>,
+        /// The node's network, which transactions must be signed for.
+        network: NetworkId,
+        /// Who may create coins on the node's chain.
+        mint: MintPolicy,
+    }
+
+    impl ApiState {
+        /// # Arguments
+        /// * `chain` - The node's chain. See `Node::watch_chain`.
+        /// * `peers` - The node's neighbours. See `Node::watch_peers`.
+        /// * `transactions` - The sending half of the channel given to the node through
+        ///   `NodeBuilder::with_receiver`.
+        /// * `events` - The node's events. See `Node::subscribe`.
+        /// * `network` - The node's network. See `Node::get_network`.
+        pub fn new(
+            chain: watch::Receiver<Chain>,
+            peers: watch::Receiver<Vec<PeerRecord>>,
+            transactions: mpsc::Sender<String>,
+            events: broadcast::Receiver<NodeEvent>,
+            network: NetworkId,
+        ) -> Self {
+            ApiState {
+                chain,
+                peers,
+                transactions,
+                events: Arc::new(events),
+                explorer: None,
+                network,
+                mint: MintPolicy::default(),
+            }
+        }
+
+        /// Accepts transactions creating coins from the mint authorities of `mint`, the
+        /// policy of the node's chain. See `ChainConfig::mint`.
+        pub fn with_mint_policy(mut self, mint: MintPolicy) -> Self {
+            self.mint = mint;
+            self
+        }
+
+        /// Serves the explorer's queries: transactions by address, record history and
+        /// blocks by hash.
+        pub fn with_explorer(mut self, explorer: Explorer) -> Self {
+            self.explorer = Some(Arc::new(explorer));
+            self
+        }
+    }
+
+    /// A block, with its transactions decoded.
+    #[derive(Serialize, ToSchema)]
+    pub struct BlockView {
+        pub height: usize,
+        pub hash: String,
+        pub previous_hash: String,
+        pub timestamp: u64,
+        pub nonce: u64,
+        /// Base64 encoded public key of the authority that sealed the block, on
+        /// proof-of-authority chains.
+        pub sealed_by: Option<String>,
+        /// Root of the state of the chain once the block is applied, empty on blocks
+        /// older than state roots.
+        pub state_root: String,
+        pub transactions: Vec<TransactionView>,
+        pub records: Vec<RecordView>,
+    }
+
+    /// A transaction. Keys and signatures are base64 encoded.
+    #[derive(Serialize, ToSchema)]
+    pub struct TransactionView {
+        pub id: String,
+        pub sender: String,
+        pub receiver: String,
+        pub coins: Vec<String>,
+        pub timestamp: u64,
+        pub nonce: u64,
+        pub signature: Option<String>,
+        /// Height of the block holding the transaction.
+        pub height: usize,
+    }
+
+    /// A key-value record. The owner and signature are base64 encoded.
+    #[derive(Serialize, ToSchema)]
+    pub struct RecordView {
+        pub id: String,
+        pub key: String,
+        pub value: String,
+        pub owner: String,
+        pub timestamp: u64,
+        pub signature: Option<String>,
+        /// Height of the block holding the record.
+        pub height: usize,
+    }
+
+    /// A page of transactions, newest first.
+    #[derive(Serialize, ToSchema)]
+    pub struct TransactionPage {
+        pub transactions: Vec<TransactionView>,
+        pub offset: usize,
+        /// Transactions across every page.
+        pub total: usize,
+    }
+
+    /// A page of records, newest first.
+    #[derive(Serialize, ToSchema)]
+    pub struct RecordPage {
+        pub records: Vec<RecordView>,
+        pub offset: usize,
+        /// Records across every page.
+        pub total: usize,
+    }
+
+    /// A page of the keys records are written under, in lexicographic order.
+    #[derive(Serialize, ToSchema)]
+    pub struct KeyList {
+        pub keys: Vec<String>,
+        /// Cursor of the next page, absent on the last one.
+        pub next: Option<String>,
+    }
+
+    /// Proof that a leaf of the state of the chain holds a value, or none. Light clients
+    /// check it against the `state_root` of the block at `height`.
+    #[derive(Serialize, ToSchema)]
+    pub struct StateProofView {
+        /// State root the proof holds under.
+        pub root: String,
+        /// Height of the last block, which commits to `root`.
+        pub height: usize,
+        /// `coin:<coin>` or `record:<key>`.
+        pub key: String,
+        /// Hex encoded public key of the owner of the coin, or id of the last record
+        /// written under the key. Absent if the state holds none.
+        pub value: Option<String>,
+        /// Every leaf of the bucket of `key`, in key order.
+        pub bucket: Vec<StateLeafView>,
+        /// Hashes of the siblings of the bucket and its ancestors, bottom up.
+        pub path: Vec<String>,
+    }
+
+    /// A leaf of the state of the chain.
+    #[derive(Serialize, ToSchema)]
+    pub struct StateLeafView {
+        pub key: String,
+        pub value: String,
+    }
+
+    /// Which record keys to list.
+    #[derive(Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    pub struct KeyQuery {
+        /// What the keys start with, every key by default.
+        pub prefix: Option<String>,
+        /// Keys to return, `DEFAULT_PAGE_SIZE` by default and at most `MAX_PAGE_SIZE`.
+        pub limit: Option<usize>,
+        /// The `next` cursor of the previous page, to list the keys past it.
+        pub cursor: Option<String>,
+    }
+
+    /// Which page of results to return.
+    #[derive(Deserialize, IntoParams)]
+    #[into_params(parameter_in = Query)]
+    pub struct PageQuery {
+        /// Results to skip, 0 by default.
+        pub offset: Option<usize>,
+        /// Results to return, `DEFAULT_PAGE_SIZE` by default and at most `MAX_PAGE_SIZE`.
+        pub limit: Option<usize>,
+    }
+
+    impl From<PageQuery> for Page {
+        fn from(query: PageQuery) -> Self {
+            Page {
+                offset: query.offset.unwrap_or(0),
+                limit: query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE),
+            }
+        }
+    }
+
+    /// A neighbour of the node.
+    #[derive(Serialize, ToSchema)]
+    pub struct PeerView {
+        pub id: Uuid,
+        pub address: String,
+        /// `tracker`, `node` or `miner`.
+        pub role: String,
+        /// Unix timestamp (in seconds) of the last message received from the peer.
+        pub last_seen: u64,
+        pub score: i32,
+        pub version: Option<String>,
+        pub services: Option<u32>,
+    }
+
+    /// A signed transaction spending a single coin. Keys and the signature are base64 encoded.
+    #[derive(Deserialize, ToSchema)]
+    pub struct TransactionSubmission {
+        pub sender: String,
+        pub receiver: String,
+        pub coin: String,
+        pub timestamp: u64,
+        /// Must exceed the nonce of the sender's last transaction in the chain.
+        #[serde(default)]
+        pub nonce: u64,
+        pub signature: String,
+    }
+
+    /// Answer to an accepted `TransactionSubmission`.
+    #[derive(Serialize, ToSchema)]
+    pub struct SubmittedTransaction {
+        pub id: String,
+    }
+
+    /// An event streamed to `/events` subscribers, as a JSON text message tagged by `type`.
+    #[derive(Serialize, ToSchema)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    pub enum ApiEvent {
+        /// The node mined the block at `height`.
+        Block { height: usize },
+        /// A transaction reached the mempool of the node.
+        Transaction { id: String },
+        /// The node replaced its chain with a longer one, now `height` blocks long.
+        Reorg { height: usize },
+        /// A neighbour was added.
+        PeerConnected { id: Uuid, address: String },
+        /// A neighbour left, was evicted or was banned.
+        PeerDisconnected { id: Uuid, address: String },
+        /// The subscriber fell behind and `missed` events were dropped. Whatever it
+        /// follows should be fetched again.
+        Lagged { missed: u64 },
+    }
+
+    impl ApiEvent {
+        /// Returns the event subscribers see for `event`, `None` for events not streamed.
+        pub fn from_node_event(event: NodeEvent) -> Option<Self> {
+            match event {
+                NodeEvent::BlockMined { height } => Some(ApiEvent::Block { height }),
+                NodeEvent::TransactionReceived(transaction) => Some(ApiEvent::Transaction { id: transaction.id() }),
+                NodeEvent::ChainAdopted { len } => Some(ApiEvent::Reorg { height: len }),
+                NodeEvent::NeighbourAdded { id, address } => Some(ApiEvent::PeerConnected { id, address }),
+                NodeEvent::NeighbourRemoved { id, address } => Some(ApiEvent::PeerDisconnected { id, address }),
+                _ => None,
+            }
+        }
+    }
+
+    /// Body of every error answer.
+    #[derive(Serialize, ToSchema)]
+    pub struct ErrorBody {
+        pub error: String,
+    }
+
+    struct ApiError(StatusCode, String);
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            (self.0, Json(ErrorBody { error: self.1 })).into_response()
+        }
+    }
+
+    fn not_found(what: String) -> ApiError {
+        ApiError(StatusCode::NOT_FOUND, format!("{} not found.", what))
+    }
+
+    /// Runs `query` on the explorer, away from the tasks serving requests since it may have
+    /// to index the chain first.
+    async fn explore<T: Send + 'static>(
+        state: &ApiState,
+        query: impl FnOnce(&Explorer) -> Result<T, StoreError> + Send + 'static,
+    ) -> Result<T, ApiError> {
+        let explorer = state
+            .explorer
+            .clone()
+            .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "The explorer is not enabled on this node.".to_string()))?;
+        tokio::task::spawn_blocking(move || query(&explorer))
+            .await
+            .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+    }
+
+    fn encode(bytes: &[u8]) -> String {
+        general_purpose::STANDARD.encode(bytes)
+    }
+
+    fn transaction_view(transaction: Transaction, height: usize) -> TransactionView {
+        TransactionView {
+            id: transaction.id(),
+            sender: encode(&transaction.sender),
+            receiver: encode(&transaction.receiver),
+            coins: transaction.coins.iter().map(Token::to_string).collect(),
+            timestamp: transaction.timestamp,
+            nonce: transaction.nonce,
+            signature: transaction.signature.as_deref().map(encode),
+            height,
+        }
+    }
+
+    fn record_view(record: &Record, height: usize) -> RecordView {
+        RecordView {
+            id: record.id(),
+            key: record.key.clone(),
+            value: record.value.clone(),
+            owner: encode(&record.owner),
+            timestamp: record.timestamp,
+            signature: record.signature.as_deref().map(encode),
+            height,
+        }
+    }
+
+    fn block_view(block: &Block) -> BlockView {
+        BlockView {
+            height: block.index,
+            hash: block.hash.clone(),
+            previous_hash: block.previous_hash.clone(),
+            timestamp: block.timestamp,
+            nonce: block.nonce,
+            sealed_by: block.seal.as_ref().map(|seal| encode(&seal.authority)),
+            state_root: block.state_root.clone(),
+            transactions: block
+                .get_transactions()
+                .into_iter()
+                .map(|transaction| transaction_view(transaction, block.index))
+                .collect(),
+            records: block.records.iter().map(|record| record_view(record, block.index)).collect(),
+        }
+    }
+
+    /// Proves the leaf `key` of the state of `chain`, under the root its last block
+    /// commits to.
+    fn state_proof_view(chain: &Chain, key: &str) -> StateProofView {
+        let StateProof { key, value, bucket, path } = chain.state().prove(key);
+        StateProofView {
+            root: chain.state_root(),
+            height: chain.get_last_block().index,
+            key,
+            value,
+            bucket: bucket.into_iter().map(|leaf| StateLeafView { key: leaf.key, value: leaf.value }).collect(),
+            path,
+        }
+    }
+
+    fn peer_view(peer: &PeerRecord) -> PeerView {
+        let role = match Role::from_protocol(peer.role) {
+            Ok(Role::Tracker) => "tracker",
+            Ok(Role::Node) => "node",
+            Ok(Role::Miner) => "miner",
+            Err(_) => "unknown",
+        };
+        PeerView {
+            id: peer.id,
+            address: peer.address.clone(),
+            role: role.to_string(),
+            last_seen: peer.last_seen,
+            score: peer.score,
+            version: peer.version.clone(),
+            services: peer.services,
+        }
+    }
+
+    /// Returns the block at a height. Heights are block indices: the genesis block is at 0
+    /// and the block after it at 2.
+    #[utoipa::path(
+        get,
+        path = "/blocks/{height}",
+        params(("height" = usize, Path, description = "Height of the block.")),
+        responses(
+            (status = 200, description = "The block.", body = BlockView),
+            (status = 404, description = "The chain does not reach this height.", body = ErrorBody),
+        ),
+    )]
+    async fn get_block(State(state): State<ApiState>, Path(height): Path<usize>) -> Result<Json<BlockView>, ApiError> {
+        let chain = state.chain.borrow();
+        let block = chain.get_block(height).ok_or_else(|| not_found(format!("Block {}", height)))?;
+        Ok(Json(block_view(block)))
+    }
+
+    /// Returns a block by its hash. Needs the explorer.
+    #[utoipa::path(
+        get,
+        path = "/blocks/by-hash/{hash}",
+        params(("hash" = String, Path, description = "Hex encoded hash of the block.")),
+        responses(
+            (status = 200, description = "The block.", body = BlockView),
+            (status = 404, description = "No block of the chain has this hash, or the explorer is not enabled.", body = ErrorBody),
+        ),
+    )]
+    async fn get_block_by_hash(State(state): State<ApiState>, Path(hash): Path<String>) -> Result<Json<BlockView>, ApiError> {
+        let what = format!("Block {}", hash);
+        let block = explore(&state, move |explorer| explorer.block_by_hash(&hash)).await?;
+        block.map(|block| Json(block_view(&block))).ok_or_else(|| not_found(what))
+    }
+
+    /// Returns a transaction of the chain by its id.
+    #[utoipa::path(
+        get,
+        path = "/transactions/{id}",
+        params(("id" = String, Path, description = "Hex encoded SHA-256 of the transaction.")),
+        responses(
+            (status = 200, description = "The transaction.", body = TransactionView),
+            (status = 404, description = "No block holds the transaction.", body = ErrorBody),
+        ),
+    )]
+    async fn get_transaction(State(state): State<ApiState>, Path(id): Path<String>) -> Result<Json<TransactionView>, ApiError> {
+        let chain = state.chain.borrow();
+        chain
+            .find_entry(&id)
+            .and_then(|height| chain.get_block(height))
+            .and_then(|block| {
+                block
+                    .get_transactions()
+                    .into_iter()
+                    .find(|transaction| transaction.id() == id)
+                    .map(|transaction| transaction_view(transaction, block.index))
+            })
+            .map(Json)
+            .ok_or_else(|| not_found(format!("Transaction {}", id)))
+    }
+
+    /// Hands a transaction to the node, which relays it to the miners.
+    #[utoipa::path(
+        post,
+        path = "/transactions",
+        request_body = TransactionSubmission,
+        responses(
+            (status = 202, description = "The node took the transaction.", body = SubmittedTransaction),
+            (status = 400, description = "A field is not valid base64.", body = ErrorBody),
+            (status = 422, description = "The transaction is not signed by the sender, spends a coin the sender does not own, reuses a nonce or expired.", body = ErrorBody),
+            (status = 503, description = "The node is not taking transactions.", body = ErrorBody),
+        ),
+    )]
+    async fn post_transaction(
+        State(state): State<ApiState>,
+        Json(submission): Json<TransactionSubmission>,
+    ) -> Result<(StatusCode, Json<SubmittedTransaction>), ApiError> {
+        let decode = |field: &str, value: &str| {
+            general_purpose::STANDARD
+                .decode(value)
+                .map_err(|_| ApiError(StatusCode::BAD_REQUEST, format!("{} is not valid base64.", field)))
+        };
+        let coin = submission
+            .coin
+            .parse::<Token>()
+            .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("coin is not a token: {}", e)))?;
+        let transaction = Transaction {
+            sender: decode("sender", &submission.sender)?,
+            receiver: decode("receiver", &submission.receiver)?,
+            timestamp: submission.timestamp,
+            coins: vec![coin],
+            nonce: submission.nonce,
+            signature: Some(decode("signature", &submission.signature)?),
+        };
+        let report = validation::validate(&transaction, &state.chain.borrow(), [], clock::now(), &state.network, &state.mint);
+        if let Some((_, error)) = report.failures().next() {
+            return Err(ApiError(StatusCode::UNPROCESSABLE_ENTITY, error.to_string()));
+        }
+        let id = report.id;
+        state
+            .transactions
+            .try_send(transaction.into())
+            .map_err(|_| ApiError(StatusCode::SERVICE_UNAVAILABLE, "The node is not taking transactions.".to_string()))?;
+        Ok((StatusCode::ACCEPTED, Json(SubmittedTransaction { id })))
+    }
+
+    /// Returns the transactions sent or received by an address, newest first. Needs the explorer.
+    #[utoipa::path(
+        get,
+        path = "/addresses/{address}/transactions",
+        params(
+            ("address" = String, Path, description = "Base64 encoded public key, standard or URL-safe."),
+            PageQuery,
+        ),
+        responses(
+            (status = 200, description = "A page of transactions.", body = TransactionPage),
+            (status = 400, description = "The address is not valid base64.", body = ErrorBody),
+            (status = 404, description = "The explorer is not enabled.", body = ErrorBody),
+        ),
+    )]
+    async fn get_address_transactions(
+        State(state): State<ApiState>,
+        Path(address): Path<String>,
+        Query(query): Query<PageQuery>,
+    ) -> Result<Json<TransactionPage>, ApiError> {
+        let address = general_purpose::STANDARD
+            .decode(&address)
+            .or_else(|_| general_purpose::URL_SAFE.decode(&address))
+            .map_err(|_| ApiError(StatusCode::BAD_REQUEST, "address is not valid base64.".to_string()))?;
+        let page = Page::from(query);
+        let Paged { items, total } = explore(&state, move |explorer| explorer.transactions_of(&address, page)).await?;
+        Ok(Json(TransactionPage {
+            transactions: items.into_iter().map(|(transaction, height)| transaction_view(transaction, height)).collect(),
+            offset: page.offset,
+            total,
+        }))
+    }
+
+    /// Returns the last record written under a key.
+    #[utoipa::path(
+        get,
+        path = "/records/{key}",
+        params(("key" = String, Path, description = "Key of the record.")),
+        responses(
+            (status = 200, description = "The record.", body = RecordView),
+            (status = 404, description = "No record was written under the key, or it was deleted.", body = ErrorBody),
+        ),
+    )]
+    async fn get_record(State(state): State<ApiState>, Path(key): Path<String>) -> Result<Json<RecordView>, ApiError> {
+        let chain = state.chain.borrow();
+        let (record, height) = chain
+            .find_record(&key)
+            .filter(|(record, _)| !record.is_deletion())
+            .ok_or_else(|| not_found(format!("Record {}", key)))?;
+        Ok(Json(record_view(record, height)))
+    }
+
+    /// Returns every record written under a key, newest first, deletions included. Needs
+    /// the explorer.
+    #[utoipa::path(
+        get,
+        path = "/records/{key}/history",
+        params(("key" = String, Path, description = "Key of the records."), PageQuery),
+        responses(
+            (status = 200, description = "A page of records.", body = RecordPage),
+            (status = 404, description = "The explorer is not enabled.", body = ErrorBody),
+        ),
+    )]
+    async fn get_record_history(
+        State(state): State<ApiState>,
+        Path(key): Path<String>,
+        Query(query): Query<PageQuery>,
+    ) -> Result<Json<RecordPage>, ApiError> {
+        let page = Page::from(query);
+        let Paged { items, total } = explore(&state, move |explorer| explorer.records_under(&key, page)).await?;
+        Ok(Json(RecordPage {
+            records: items.iter().map(|(record, height)| record_view(record, *height)).collect(),
+            offset: page.offset,
+            total,
+        }))
+    }
+
+    /// Lists the keys records are written under, deleted ones aside. Needs the explorer.
+    #[utoipa::path(
+        get,
+        path = "/records",
+        params(KeyQuery),
+        responses(
+            (status = 200, description = "A page of keys.", body = KeyList),
+            (status = 404, description = "The explorer is not enabled.", body = ErrorBody),
+        ),
+    )]
+    async fn get_record_keys(State(state): State<ApiState>, Query(query): Query<KeyQuery>) -> Result<Json<KeyList>, ApiError> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+        let prefix = query.prefix.unwrap_or_default();
+        let KeyPage { keys, next } = explore(&state, move |explorer| {
+            explorer.list_keys(&prefix, limit, query.cursor.as_deref())
+        }).await?;
+        Ok(Json(KeyList { keys, next }))
+    }
+
+    /// Proves who owns a coin, or that it was never minted, for light clients holding
+    /// only block headers.
+    #[utoipa::path(
+        get,
+        path = "/coins/{coin}/proof",
+        params(("coin" = String, Path, description = "Hex encoded coin.")),
+        responses(
+            (status = 200, description = "The proof.", body = StateProofView),
+            (status = 400, description = "The coin is not a token.", body = ErrorBody),
+        ),
+    )]
+    async fn get_coin_proof(State(state): State<ApiState>, Path(coin): Path<String>) -> Result<Json<StateProofView>, ApiError> {
+        let coin = coin
+            .parse::<Token>()
+            .map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("coin is not a token: {}", e)))?;
+        Ok(Json(state_proof_view(&state.chain.borrow(), &chain_state::coin_key(&coin))))
+    }
+
+    /// Proves the id of the last record written under a key, or that none was, for
+    /// light clients holding only block headers.
+    #[utoipa::path(
+        get,
+        path = "/records/{key}/proof",
+        params(("key" = String, Path, description = "Key of the record.")),
+        responses((status = 200, description = "The proof.", body = StateProofView)),
+    )]
+    async fn get_record_proof(State(state): State<ApiState>, Path(key): Path<String>) -> Json<StateProofView> {
+        Json(state_proof_view(&state.chain.borrow(), &chain_state::record_key(&key)))
+    }
+
+    /// Lists the neighbours of the node.
+    #[utoipa::path(
+        get,
+        path = "/peers",
+        responses((status = 200, description = "The neighbours.", body = Vec<PeerView>)),
+    )]
+    async fn get_peers(State(state): State<ApiState>) -> Json<Vec<PeerView>> {
+        Json(state.peers.borrow().iter().map(peer_view).collect())
+    }
+
+    /// Upgrades to a WebSocket streaming every `ApiEvent` of the node from now on.
+    #[utoipa::path(
+        get,
+        path = "/events",
+        responses((status = 101, description = "Switching to a WebSocket carrying `ApiEvent` messages.")),
+    )]
+    async fn get_events(State(state): State<ApiState>, upgrade: WebSocketUpgrade) -> Response {
+        let events = state.events.resubscribe();
+        upgrade.on_upgrade(move |socket| stream_events(socket, events))
+    }
+
+    /// Sends events to a subscriber until it goes away or the node is dropped.
+    async fn stream_events(mut socket: WebSocket, mut events: broadcast::Receiver<NodeEvent>) {
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => ApiEvent::from_node_event(event),
+                    Err(RecvError::Lagged(missed)) => Some(ApiEvent::Lagged { missed }),
+                    Err(RecvError::Closed) => break,
+                },
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => None, // Subscribers have nothing to say.
+                },
+            };
+            let Some(event) = event else {
+                continue;
+            };
+            let text = serde_json::to_string(&event).expect("events always serialize");
+            if let Err(e) = socket.send(Message::Text(text)).await {
+                debug!("Dropped an events subscriber: {}", e);
+                break;
+            }
+        }
+    }
+
+    async fn get_openapi() -> Json<utoipa::openapi::OpenApi> {
+        Json(ApiDoc::openapi())
+    }
+
+    /// Routes of the API, including `/openapi.json`.
+    pub fn router(state: ApiState) -> Router {
+        Router::new()
+            .route("/blocks/:height", get(get_block))
+            .route("/blocks/by-hash/:hash", get(get_block_by_hash))
+            .route("/transactions", post(post_transaction))
+            .route("/transactions/:id", get(get_transaction))
+            .route("/addresses/:address/transactions", get(get_address_transactions))
+            .route("/records", get(get_record_keys))
+            .route("/records/:key", get(get_record))
+            .route("/records/:key/history", get(get_record_history))
+            .route("/records/:key/proof", get(get_record_proof))
+            .route("/coins/:coin/proof", get(get_coin_proof))
+            .route("/peers", get(get_peers))
+            .route("/events", get(get_events))
+            .route("/openapi.json", get(get_openapi))
+            .with_state(state)
+    }
+
+    /// Serves the API over HTTP until the task running it is dropped.
+    ///
+    /// # Arguments
+    /// * `address` - The TCP address to listen on, e.g. `127.0.0.1:8080`.
+    /// * `state` - What the API reads from and writes to the node.
+    pub async fn serve(address: &str, state: ApiState) -> IOResult<()> {
+        let listener = TcpListener::bind(address).await?;
+        info!("Serving the REST API on http://{}", address);
+        axum::serve(listener, router(state)).await
+    }
+}
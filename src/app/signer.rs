@@ -0,0 +1,247 @@
+pub mod signer {
+
+    use crate::{
+        app::client::client::{self, ClientError},
+        store::{
+            encrypted_engine::encrypted_engine::{EncryptedEngine, KEY_LEN},
+            engine::engine::Engine,
+            file_engine::file_engine::FileEngine,
+            store::store::StoreError,
+        },
+        token::token::token::Token,
+        transaction::transaction::transaction::Transaction,
+        wallet::wallet::wallet::Wallet,
+    };
+
+    use std::{
+        collections::HashSet,
+        io::Error as IOError,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use base64::{Engine as _, engine::general_purpose};
+    use serde::{Deserialize, Serialize};
+    use thiserror::Error;
+    use tokio::net::TcpListener;
+    use tracing::info;
+    use zeroize::Zeroizing;
+
+    /// Where the signer listens unless told otherwise.
+    pub const DEFAULT_SIGNER_ADDRESS: &str = "127.0.0.1:7070";
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum SignerError {
+        #[error("{0} is not a loopback address - the signer only listens locally.")]
+        #[from(ignore)]
+        NotLocal(String),
+        #[error("{} does not hold a wallet key.", .0.display())]
+        #[from(ignore)]
+        InvalidWallet(PathBuf),
+        #[error(transparent)]
+        StoreError(StoreError),
+        #[error("Failed to serve the signer: {0}")]
+        ServeError(IOError),
+    }
+
+    /// Encrypts the key of `wallet` into the file at `path`, for `open_wallet`.
+    ///
+    /// # Arguments
+    /// * `wallet` - The wallet to seal.
+    /// * `path` - Where to write the encrypted key.
+    /// * `key` - The secret key it is encrypted with. See `encrypted_engine::load_key`.
+    pub fn seal_wallet(wallet: &Wallet, path: &Path, key: &[u8; KEY_LEN]) -> Result<(), StoreError> {
+        let mut engine = EncryptedEngine::new(Box::new(FileEngine::open(path)?), key);
+        engine.store(&wallet.to_base64())?;
+        engine.flush()
+    }
+
+    /// Decrypts the wallet sealed in the file at `path` by `seal_wallet`.
+    ///
+    /// # Returns
+    /// * `Result<Wallet, SignerError>` - The wallet, `StoreError::DecryptionError` if `key`
+    ///   is not the one it was sealed with or `SignerError::InvalidWallet` if the file
+    ///   holds something else.
+    pub fn open_wallet(path: &Path, key: &[u8; KEY_LEN]) -> Result<Wallet, SignerError> {
+        let mut engine = EncryptedEngine::new(Box::new(FileEngine::open_read_only(path)?), key);
+        let encoded = Zeroizing::new(engine.load()?);
+        Wallet::from_base64(&encoded).ok_or_else(|| SignerError::InvalidWallet(path.to_path_buf()))
+    }
+
+    /// Holds the wallet, and the coins it spent that the node may not have mined yet.
+    struct Signer {
+        wallet: Wallet,
+        node: String,
+        pending: Mutex<HashSet<Token>>,
+        /// Nonce of the next transaction, unless the chain already holds a later one.
+        next_nonce: Mutex<u64>,
+    }
+
+    /// Coins to send to a wallet.
+    #[derive(Deserialize)]
+    pub struct Payment {
+        /// Base64 encoded public key of the receiver.
+        pub to: String,
+        /// Number of coins, one transaction each.
+        pub amount: usize,
+    }
+
+    /// A signed transaction, in the format the REST API of a node takes. Keys and the
+    /// signature are base64 encoded.
+    #[derive(Serialize)]
+    pub struct SignedTransaction {
+        pub id: String,
+        pub sender: String,
+        pub receiver: String,
+        pub coin: String,
+        pub timestamp: u64,
+        pub nonce: u64,
+        pub signature: String,
+    }
+
+    #[derive(Serialize)]
+    struct AddressBody {
+        address: String,
+    }
+
+    #[derive(Serialize)]
+    struct ErrorBody {
+        error: String,
+    }
+
+    struct ApiError(StatusCode, String);
+
+    impl IntoResponse for ApiError {
+        fn into_response(self) -> Response {
+            (self.0, Json(ErrorBody { error: self.1 })).into_response()
+        }
+    }
+
+    impl From<ClientError> for ApiError {
+        fn from(e: ClientError) -> Self {
+            ApiError(StatusCode::BAD_GATEWAY, e.to_string())
+        }
+    }
+
+    fn encode(bytes: &[u8]) -> String {
+        general_purpose::STANDARD.encode(bytes)
+    }
+
+    impl Signer {
+        /// Signs a transaction for each of `payment.amount` coins of the wallet, picked
+        /// among the ones the node says it owns and this signer did not spend already.
+        async fn sign(&self, payment: &Payment) -> Result<Vec<Transaction>, ApiError> {
+            let receiver = general_purpose::STANDARD
+                .decode(&payment.to)
+                .map_err(|_| ApiError(StatusCode::BAD_REQUEST, format!("{} is not a base64 encoded public key.", payment.to)))?;
+            let chain = client::poll_chain(&self.node).await?;
+            let network = client::node_info(&self.node).await?.network;
+            let owned = chain.coins_of(&self.wallet.get_pub_key());
+            let mut pending = self.pending.lock().unwrap();
+            let mut next_nonce = self.next_nonce.lock().unwrap();
+            *next_nonce = (*next_nonce).max(chain.next_nonce(&self.wallet.get_pub_key()));
+            // Coins no longer owned were spent on chain, so they stop being pending.
+            pending.retain(|coin| owned.contains(coin));
+            let available: Vec<Token> = owned.into_iter().filter(|coin| !pending.contains(coin)).collect();
+            if available.len() < payment.amount {
+                return Err(ApiError(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("The wallet holds {} spendable coins, {} are needed.", available.len(), payment.amount),
+                ));
+            }
+            let transactions = available
+                .into_iter()
+                .take(payment.amount)
+                .map(|coin| {
+                    pending.insert(coin);
+                    let nonce = *next_nonce;
+                    *next_nonce += 1;
+                    let transaction = Transaction::new(self.wallet.get_pub_key(), receiver.clone(), vec![coin]);
+                    self.wallet.sign(transaction.with_nonce(nonce), &network)
+                })
+                .collect();
+            Ok(transactions)
+        }
+    }
+
+    fn signed_view(transaction: &Transaction) -> SignedTransaction {
+        SignedTransaction {
+            id: transaction.id(),
+            sender: encode(&transaction.sender),
+            receiver: encode(&transaction.receiver),
+            coin: transaction.coins.iter().map(Token::to_string).collect(),
+            timestamp: transaction.timestamp,
+            nonce: transaction.nonce,
+            signature: transaction.signature.as_deref().map(encode).unwrap_or_default(),
+        }
+    }
+
+    async fn get_address(State(signer): State<Arc<Signer>>) -> Json<AddressBody> {
+        Json(AddressBody { address: encode(&signer.wallet.get_pub_key()) })
+    }
+
+    /// Signs a payment without sending it, for the caller to submit wherever it wants.
+    async fn post_sign(
+        State(signer): State<Arc<Signer>>,
+        Json(payment): Json<Payment>,
+    ) -> Result<Json<Vec<SignedTransaction>>, ApiError> {
+        let transactions = signer.sign(&payment).await?;
+        Ok(Json(transactions.iter().map(signed_view).collect()))
+    }
+
+    /// Signs a payment and sends it to the node.
+    async fn post_send(
+        State(signer): State<Arc<Signer>>,
+        Json(payment): Json<Payment>,
+    ) -> Result<(StatusCode, Json<Vec<SignedTransaction>>), ApiError> {
+        let transactions = signer.sign(&payment).await?;
+        let signed = transactions.iter().map(signed_view).collect();
+        for transaction in transactions {
+            client::send_transaction(&signer.node, transaction).await?;
+        }
+        Ok((StatusCode::ACCEPTED, Json(signed)))
+    }
+
+    /// Serves the signer over HTTP until the task running it is dropped:
+    ///
+    /// * `GET /address` - The address of the wallet.
+    /// * `POST /sign` - Signs a `Payment` and answers the `SignedTransaction`s.
+    /// * `POST /send` - Signs a `Payment` and sends it to the node.
+    ///
+    /// # Arguments
+    /// * `address` - The TCP address to listen on, which must be a loopback one.
+    /// * `wallet` - The wallet signing the transactions. See `open_wallet`.
+    /// * `node` - The address of the node telling which coins the wallet owns, and
+    ///   receiving the transactions.
+    pub async fn serve(address: &str, wallet: Wallet, node: String) -> Result<(), SignerError> {
+        let local = address
+            .parse::<SocketAddr>()
+            .is_ok_and(|address| address.ip().is_loopback());
+        if !local {
+            return Err(SignerError::NotLocal(address.to_string()));
+        }
+        let signer = Arc::new(Signer {
+            wallet,
+            node,
+            pending: Mutex::new(HashSet::new()),
+            next_nonce: Mutex::new(0),
+        });
+        let router = Router::new()
+            .route("/address", get(get_address))
+            .route("/sign", post(post_sign))
+            .route("/send", post(post_send))
+            .with_state(signer);
+        let listener = TcpListener::bind(address).await?;
+        info!("Serving the signer on http://{}", address);
+        axum::serve(listener, router).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+pub mod bench {
+
+    use crate::{
+        chain::{chain::chain::{Chain, INTERVAL}, network::network::NetworkId},
+        miner::miner::miner::{Miner, MiningDigest},
+        token::token::token::Token,
+        transaction::transaction::transaction::Transaction,
+        wallet::wallet::wallet::Wallet,
+    };
+
+    /// Returns a miner ready to mine on top of `chain`.
+    pub fn miner_for(chain: &Chain) -> Miner {
+        let mut miner = Miner::new(0, "bench".to_string());
+        miner.set_network(chain.network().clone());
+        miner.set_chain_meta(chain.get_len(), chain.difficulty, chain.shared_blocks());
+        miner
+    }
+
+    /// Mines the block following the last one of `chain`, holding the prize of `miner`
+    /// and whatever transactions it queued. The block is dated `INTERVAL` seconds after
+    /// the last one, so adding it leaves the difficulty of the chain unchanged.
+    pub fn mine_next(chain: &Chain, miner: &mut Miner) -> MiningDigest {
+        miner.set_chain_meta(chain.get_len(), chain.difficulty, chain.shared_blocks());
+        miner.set_chain_state(chain.state().clone());
+        let last = chain.get_last_block();
+        let digest = miner.mine(last.clone()).expect("the chain meta was just set");
+        let mut block = digest.get_block();
+        block.timestamp = last.timestamp + INTERVAL;
+        MiningDigest::new(block, digest.get_nonce())
+    }
+
+    /// Mines the block following the last one of `chain` with `miner`, as `mine_next`
+    /// does, and adds it.
+    pub fn add_next(chain: &mut Chain, miner: &mut Miner) {
+        let digest = mine_next(chain, miner);
+        chain.add_block(digest).expect("mined blocks extend the chain");
+    }
+
+    /// Mines `blocks` more blocks on top of `chain` with a fresh miner, so copies of a
+    /// chain extended apart fork off it.
+    pub fn extend(chain: &mut Chain, blocks: usize) {
+        let mut miner = miner_for(chain);
+        for _ in 0..blocks {
+            add_next(chain, &mut miner);
+        }
+    }
+
+    /// Builds a valid chain of `blocks` mined blocks after the genesis block, each holding
+    /// the prize of its miner, at the lowest difficulty.
+    pub fn synthetic_chain(blocks: usize) -> Chain {
+        let mut chain = Chain::new();
+        extend(&mut chain, blocks);
+        chain
+    }
+
+    /// Returns a transaction of a single coin between two fresh wallets, signed for `mainnet`.
+    pub fn signed_transaction() -> Transaction {
+        let sender = Wallet::new();
+        let transaction = Transaction::new(sender.get_pub_key(), Wallet::new().get_pub_key(), vec![Token::default()]);
+        sender.sign(transaction, &NetworkId::default())
+    }
+}
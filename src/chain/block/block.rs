@@ -1,18 +1,32 @@
 pub mod block {
     use crate::Transaction;
+    use crate::transaction::transaction::transaction::TransactionFromBase64Error;
+    use crate::miner::miner::miner::ZERO_WALLET_PK;
+    use crate::record::record::record::{Record, RecordRules};
+    use crate::token::token::token::Token;
+    use crate::transaction::batch::batch::{BatchTransaction, InvalidBatchErr};
+    use crate::chain::network::network::NetworkId;
+    use crate::clock::clock::clock;
 
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::collections::HashMap;
     use std::fmt;
 
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
     use sha2::{Digest, Sha256};
     use serde::{Deserialize, Serialize};
     use thiserror::Error;
 
 
     pub const MAX_TRANSACTIONS: usize = 8;
+    pub const MAX_RECORDS: usize = 8;
+    pub const MAX_BATCHES: usize = 4;
+    /// Fields of a transaction in the data of a block, each ended by `FIELD_END`.
     pub const N_TRANSACTION_PARAMS: usize = 6;
 
     pub const FIELD_END: char = ';';
+    /// Version of the block format new chains start at. Later versions are activated by
+    /// height, see `ChainConfig::upgrades`.
+    pub const BLOCK_VERSION: u32 = 1;
 
     #[derive(Default, Debug, Clone, Serialize, Deserialize)]
     pub struct Block {
@@ -22,12 +36,118 @@ pub mod block {
         pub data: String,
         pub timestamp: u64,
         pub nonce: u64,
+        /// Key-value records carried by the block, next to the transactions in `data`.
+        #[serde(default)]
+        pub records: Vec<Record>,
+        /// Batches of transfers carried by the block, after the transactions in `data`.
+        #[serde(default)]
+        pub batches: Vec<BatchTransaction>,
+        /// Signature of the authority that produced the block, on proof-of-authority
+        /// chains. `None` for mined blocks.
+        #[serde(default)]
+        pub seal: Option<Seal>,
+        /// Network the block belongs to, the one of the genesis block. Mined and sealed
+        /// hashes cover it, so a block is only valid on its network.
+        #[serde(default)]
+        pub network: NetworkId,
+        /// Version of the block format, covered by hashes like `network`.
+        #[serde(default = "first_version")]
+        pub version: u32,
+        /// Root of the state of the chain once the block is applied, see `ChainState`.
+        /// Empty on blocks stored before blocks committed to it.
+        #[serde(default)]
+        pub state_root: String,
+        /// Whether `prune` dropped what the block held. The hash of the block mined on it
+        /// covers what it held, so it can no longer be re-derived. See `Chain::verify_from`.
+        #[serde(default)]
+        pub pruned: bool,
+    }
+
+    /// A block without its data, enough to check how blocks link together.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct BlockHeader {
+        pub index: usize,
+        pub previous_hash: String,
+        pub hash: String,
+        pub timestamp: u64,
+        #[serde(default)]
+        pub seal: Option<Seal>,
+        #[serde(default)]
+        pub network: NetworkId,
+        #[serde(default = "first_version")]
+        pub version: u32,
+        #[serde(default)]
+        pub state_root: String,
+    }
+
+    /// Version of blocks stored before the block format had versions.
+    fn first_version() -> u32 {
+        BLOCK_VERSION
+    }
+
+    /// A version of the block format, required of every block from `height` on. See
+    /// `ChainConfig::upgrades`.
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub struct Upgrade {
+        pub height: usize,
+        pub version: u32,
+    }
+
+    /// Returns the version of the block at `index`: the one of the last of `upgrades`
+    /// reached, in increasing order of height, `BLOCK_VERSION` before the first one.
+    pub fn version_at(upgrades: &[Upgrade], index: usize) -> u32 {
+        upgrades
+            .iter()
+            .take_while(|upgrade| upgrade.height <= index)
+            .last()
+            .map_or(BLOCK_VERSION, |upgrade| upgrade.version)
+    }
+
+    /// What a state root adds to the hashes of a block. Blocks without one hash as they
+    /// did before blocks had state roots.
+    fn state_suffix(state_root: &str) -> String {
+        match state_root {
+            "" => String::new(),
+            root => format!(":{}", root),
+        }
+    }
+
+    /// An authority's signature of the hash of a block it produced. See `Wallet::seal`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Seal {
+        /// Public key of the authority.
+        pub authority: Vec<u8>,
+        pub signature: Vec<u8>,
+    }
+
+    impl Seal {
+        /// Whether the signature is the authority's signature of `hash`.
+        pub fn verify(&self, hash: &str) -> bool {
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &self.authority)
+                .verify(hash.as_bytes(), &self.signature)
+                .is_ok()
+        }
+    }
+
+    #[derive(Error, Debug)]
+    pub enum InvalidRecordErr {
+        #[error("A record needs a non-empty key.")]
+        EmptyKey,
+        #[error("The key is owned by someone other than this record's owner.")]
+        NotOwner,
+        #[error("The record is not signed by its owner.")]
+        InvalidSignature,
+        #[error("The value takes {size} bytes, over the limit of {limit}.")]
+        ValueTooLarge { size: usize, limit: usize },
+        #[error("The record costs {cost} coins, but its owner burned {paid} in its block.")]
+        Unpaid { cost: usize, paid: usize },
     }
 
     #[derive(Error, Debug)]    
     pub enum InvalidTransactionErr {
         IncompleteChain,
         UnknownCoin,
+        StaleNonce { nonce: u64, last: u64 },
     }
     
     impl fmt::Display for InvalidTransactionErr {
@@ -35,18 +155,20 @@ pub mod block {
             match self {
                 InvalidTransactionErr::IncompleteChain => write!(f, "The last owner of this coin is not this transaction's spender."),
                 InvalidTransactionErr::UnknownCoin => write!(f, "The coin spent in this transaction is not valid."),
+                InvalidTransactionErr::StaleNonce { nonce, last } => write!(
+                    f, "The nonce {} does not exceed {}, the nonce of the sender's last transaction.", nonce, last
+                ),
             }
         }
     }
 
-    pub fn check_transaction(transaction: Transaction, blocks: &Vec<Block>) ->  Result<Transaction, InvalidTransactionErr> {
+    pub fn check_transaction(transaction: Transaction, blocks: &[Block]) ->  Result<Transaction, InvalidTransactionErr> {
         let coins = &transaction.coins;
         for coin in coins { //verify each coin is valid:
             let mut coin_found = false;
             for block in blocks.iter().rev().collect::<Vec<&Block>>() { //check each block
                 for t in block.get_transactions() { //check each transaction in the block
-                    println!("coin in transaction: {}", t.coins[0]);
-                    if t.coins[0] == *coin { 
+                    if t.coins.contains(coin) { 
                         coin_found = true; //if the coin gets found, check if the spender is
                                            //the last owner of the coin
                         if t.receiver != transaction.sender { // fail if sender doesnt own the
@@ -64,12 +186,126 @@ pub mod block {
         Ok(transaction)
     }
 
+    /// Checks that a batch may be added on top of `blocks`: it must be well formed and its
+    /// sender must own every coin it sends. See `check_transaction`.
+    pub fn check_batch(batch: BatchTransaction, blocks: &[Block]) -> Result<BatchTransaction, InvalidBatchErr> {
+        batch.check()?;
+        for transaction in batch.transactions() {
+            check_transaction(transaction, blocks).map_err(InvalidBatchErr::Transfer)?;
+        }
+        Ok(batch)
+    }
+
+    /// Returns the nonce of the last transaction of each sender in `blocks`.
+    pub fn last_nonces<'a>(blocks: impl IntoIterator<Item = &'a Block>) -> HashMap<Vec<u8>, u64> {
+        let mut last = HashMap::new();
+        for transaction in blocks.into_iter().flat_map(Block::get_transactions) {
+            last.insert(transaction.sender, transaction.nonce);
+        }
+        last
+    }
+
+    /// Checks that `transaction` may follow the transactions whose last nonces by sender
+    /// are `last` (see `last_nonces`): its nonce must exceed the one of its sender's last
+    /// transaction, so it cannot be replayed. Coins created by the zero wallet carry no
+    /// nonce to check.
+    pub fn check_nonce(transaction: &Transaction, last: &HashMap<Vec<u8>, u64>) -> Result<(), InvalidTransactionErr> {
+        check_sender_nonce(&transaction.sender, transaction.nonce, last)
+    }
+
+    /// Same as `check_nonce`, for any entry of `sender` taking `nonce`, such as a batch.
+    pub fn check_sender_nonce(sender: &[u8], nonce: u64, last: &HashMap<Vec<u8>, u64>) -> Result<(), InvalidTransactionErr> {
+        if sender == ZERO_WALLET_PK {
+            return Ok(());
+        }
+        match last.get(sender) {
+            Some(&last) if nonce <= last => Err(InvalidTransactionErr::StaleNonce { nonce, last }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the coins whose last transaction in `blocks` pays them to `owner`.
+    pub fn coins_of<'a>(owner: &[u8], blocks: impl IntoIterator<Item = &'a Block>) -> Vec<Token> {
+        let mut owners: HashMap<Token, Vec<u8>> = HashMap::new();
+        for transaction in blocks.into_iter().flat_map(Block::get_transactions) {
+            for coin in transaction.coins {
+                owners.insert(coin, transaction.receiver.clone());
+            }
+        }
+        owners
+            .into_iter()
+            .filter(|(_, receiver)| receiver == owner)
+            .map(|(coin, _)| coin)
+            .collect()
+    }
+
+    /// Checks that a record may be added on top of `blocks`: its key must not be empty,
+    /// it must be signed by its owner for `network`, within the size limit of `rules` (see
+    /// `check_record_size`) and, if the key was already written, by the same owner. The
+    /// coins it costs are paid in the block it lands in, see `pay_record`.
+    pub fn check_record(record: Record, network: &NetworkId, rules: &RecordRules, blocks: &[Block]) -> Result<Record, InvalidRecordErr> {
+        if record.key.is_empty() {
+            return Err(InvalidRecordErr::EmptyKey);
+        }
+        if !record.verify_signature(network) {
+            return Err(InvalidRecordErr::InvalidSignature);
+        }
+        check_record_size(&record, rules)?;
+        let first_owner = blocks
+            .iter()
+            .flat_map(|block| block.records.iter())
+            .find(|written| written.key == record.key)
+            .map(|written| &written.owner);
+        if first_owner.is_some_and(|owner| *owner != record.owner) {
+            return Err(InvalidRecordErr::NotOwner);
+        }
+        Ok(record)
+    }
+
+    /// Checks that the value of a record is at most `rules.max_value_size` bytes.
+    pub fn check_record_size(record: &Record, rules: &RecordRules) -> Result<(), InvalidRecordErr> {
+        if record.value.len() > rules.max_value_size {
+            return Err(InvalidRecordErr::ValueTooLarge { size: record.value.len(), limit: rules.max_value_size });
+        }
+        Ok(())
+    }
+
+    /// Returns the coins each sender burns in `block`, sending them to the zero wallet,
+    /// batched or not. They pay for the records of their sender in the same block, see
+    /// `pay_record`.
+    pub fn burned(block: &Block) -> HashMap<Vec<u8>, usize> {
+        let mut burned = HashMap::new();
+        for transaction in block.get_transactions() {
+            if transaction.receiver == ZERO_WALLET_PK && transaction.sender != ZERO_WALLET_PK {
+                *burned.entry(transaction.sender).or_insert(0) += transaction.coins.len();
+            }
+        }
+        burned
+    }
+
+    /// Takes the coins `record` costs under `rules` out of the ones its owner has left of
+    /// `burned`, so the records of an owner in a block are paid for in order. See
+    /// `RecordRules::cost`.
+    ///
+    /// # Returns
+    /// * `Result<(), InvalidRecordErr>` - `InvalidRecordErr::Unpaid` if the owner has less
+    ///   left than the record costs, in which case nothing is taken.
+    pub fn pay_record(record: &Record, rules: &RecordRules, burned: &mut HashMap<Vec<u8>, usize>) -> Result<(), InvalidRecordErr> {
+        let cost = rules.cost(record);
+        if cost == 0 {
+            return Ok(());
+        }
+        let paid = burned.get(&record.owner).copied().unwrap_or(0);
+        if paid < cost {
+            return Err(InvalidRecordErr::Unpaid { cost, paid });
+        }
+        burned.insert(record.owner.clone(), paid - cost);
+        Ok(())
+    }
+
     impl Block {
         pub fn new(index: usize, previous_hash: String, data: String, hash: Option<String>) -> Block { 
-            let timestamp = SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs();
+            let timestamp = clock::now();
             let private_hash = match hash {
                 Some(h) => h,
                 None => String::new(),
@@ -81,44 +317,124 @@ pub mod block {
                 timestamp,
                 hash: private_hash, 
                 nonce: 0,
+                records: vec![],
+                batches: vec![],
+                seal: None,
+                network: NetworkId::default(),
+                version: BLOCK_VERSION,
+                state_root: String::new(),
+                pruned: false,
             }
         }
 
-        pub fn get_transactions(&self) -> Vec<Transaction> { 
-            let mut transactions = vec![];
-            let mut separator_counter = 1;
+        /// Splits the data of the block into the wire format of each of its transactions.
+        fn transaction_wires(&self) -> Vec<String> {
+            let mut wires = vec![];
+            let mut separator_counter = 0;
             let mut last_tx = 0;
-            for i in 0..self.data.len() {
-                if self.data[i..].chars().next().unwrap() == FIELD_END { //this is the 'byte' way of indexing
-                    separator_counter += 1;
+            for (i, c) in self.data.char_indices() {
+                if c != FIELD_END {
+                    continue;
                 }
+                separator_counter += 1;
                 if separator_counter % N_TRANSACTION_PARAMS == 0 {
-                    let str_transaction = String::from(&self.data[last_tx..i + 1]);
-                    transactions.push(Transaction::try_from(str_transaction).unwrap());
+                    wires.push(String::from(&self.data[last_tx..i + 1]));
                     last_tx = i + 1;
                 }
             }
+            wires
+        }
+
+        /// Returns the transactions of the block, skipping malformed ones, followed by the
+        /// transfers of its batches (see `BatchTransaction::transactions`). Blocks taken
+        /// from the network hold no malformed transaction, see `decode::check_block`.
+        pub fn get_transactions(&self) -> Vec<Transaction> {
+            let mut transactions = self.get_data_transactions();
+            transactions.extend(self.batches.iter().flat_map(BatchTransaction::transactions));
             transactions
         }
 
+        /// Returns the transactions of the block's data, skipping malformed ones, without
+        /// the transfers of its batches.
+        pub fn get_data_transactions(&self) -> Vec<Transaction> {
+            self.transaction_wires()
+                .into_iter()
+                .filter_map(|wire| Transaction::try_from(wire).ok())
+                .collect()
+        }
+
+        /// Returns the transactions of the block's data, failing on the first malformed one.
+        pub fn try_get_transactions(&self) -> Result<Vec<Transaction>, TransactionFromBase64Error> {
+            self.transaction_wires().into_iter().map(Transaction::try_from).collect()
+        }
+
+        /// Drops the transactions, records and batches of the block, keeping what `header`
+        /// returns.
+        ///
+        /// # Returns
+        /// Whether the block held anything to drop.
+        pub fn prune(&mut self) -> bool {
+            let pruned = !self.data.is_empty() || !self.records.is_empty() || !self.batches.is_empty();
+            self.data.clear();
+            self.records.clear();
+            self.batches.clear();
+            self.pruned |= pruned;
+            pruned
+        }
+
+        pub fn header(&self) -> BlockHeader {
+            BlockHeader {
+                index: self.index,
+                previous_hash: self.previous_hash.clone(),
+                hash: self.hash.clone(),
+                timestamp: self.timestamp,
+                seal: self.seal.clone(),
+                network: self.network.clone(),
+                version: self.version,
+                state_root: self.state_root.clone(),
+            }
+        }
+
         pub fn get_hash(&self) -> String {
             self.hash.clone()
         }
 
-        pub fn calculate_hash(&mut self) -> String {
-            let str_block = format!("{}{}{}{}{}{}",
+        /// Hash the block mined on this one gets with `nonce`, which must meet the
+        /// difficulty. The network and version of the mined block are part of it, so a
+        /// nonce found for one network or version is worthless on any other, and so is
+        /// the state root of this block, once it has one.
+        ///
+        /// # Arguments
+        /// * `nonce` - The nonce tried.
+        /// * `network` - The network of the block mined.
+        /// * `version` - The version of the block mined.
+        pub fn mining_hash(&self, nonce: u64, network: &NetworkId, version: u32) -> String {
+            let str_block = format!("{}{}{}{}{}{}:{}:{}",
                              self.hash,
                              self.previous_hash,
                              self.data,
                              self.timestamp,
                              self.index,
-                             self.nonce,
-            );
+                             nonce,
+                             network,
+                             version,
+            ) + &state_suffix(&self.state_root);
             let mut hasher = Sha256::new();
             hasher.update(str_block);
             let digest = hasher.finalize();
             format!("{:x}", digest)
         }
+
+        /// Hashes what the block holds, rather than searching a nonce on the previous
+        /// block like mined blocks do. Sealed blocks are identified by it, so their seal
+        /// covers their transactions, records, batches, network, version and state root.
+        pub fn content_hash(&self) -> String {
+            let records: String = self.records.iter().map(Record::id).collect();
+            let batches: String = self.batches.iter().map(BatchTransaction::id).collect();
+            let str_block = format!("{}{}{}{}{}{}:{}:{}", self.index, self.previous_hash, self.data, records, batches, self.timestamp, self.network, self.version)
+                + &state_suffix(&self.state_root);
+            format!("{:x}", Sha256::digest(str_block))
+        }
     }
 
     impl fmt::Display for Block {
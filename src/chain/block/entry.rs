@@ -0,0 +1,88 @@
+pub mod entry {
+
+    use crate::chain::block::block::block::{self, Block, InvalidRecordErr, InvalidTransactionErr};
+    use crate::chain::config::config::ChainConfig;
+    use crate::chain::network::network::NetworkId;
+    use crate::record::record::record::Record;
+    use crate::transaction::batch::batch::{BatchTransaction, InvalidBatchErr};
+    use crate::Transaction;
+
+    use thiserror::Error;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum InvalidEntryErr {
+        #[error(transparent)]
+        InvalidTransactionErr(InvalidTransactionErr),
+        #[error(transparent)]
+        InvalidRecordErr(InvalidRecordErr),
+        #[error(transparent)]
+        InvalidBatchErr(InvalidBatchErr),
+    }
+
+    /// Anything a block can carry.
+    pub trait BlockEntry: Send + Sync {
+        /// Identifies the entry by the SHA-256 of its wire format.
+        fn id(&self) -> String;
+
+        /// Checks whether the entry may be added on top of `blocks`, on a chain of `network`
+        /// following `rules`.
+        fn validate(&self, blocks: &[Block], network: &NetworkId, rules: &ChainConfig) -> Result<(), InvalidEntryErr>;
+
+        fn as_transaction(&self) -> Option<&Transaction> {
+            None
+        }
+
+        fn as_record(&self) -> Option<&Record> {
+            None
+        }
+
+        fn as_batch(&self) -> Option<&BatchTransaction> {
+            None
+        }
+    }
+
+    impl BlockEntry for Transaction {
+        fn id(&self) -> String {
+            Transaction::id(self)
+        }
+
+        fn validate(&self, blocks: &[Block], _network: &NetworkId, rules: &ChainConfig) -> Result<(), InvalidEntryErr> {
+            rules.mint.check_transaction(self.clone(), blocks)?;
+            Ok(())
+        }
+
+        fn as_transaction(&self) -> Option<&Transaction> {
+            Some(self)
+        }
+    }
+
+    impl BlockEntry for Record {
+        fn id(&self) -> String {
+            Record::id(self)
+        }
+
+        fn validate(&self, blocks: &[Block], network: &NetworkId, rules: &ChainConfig) -> Result<(), InvalidEntryErr> {
+            block::check_record(self.clone(), network, &rules.records, blocks)?;
+            Ok(())
+        }
+
+        fn as_record(&self) -> Option<&Record> {
+            Some(self)
+        }
+    }
+
+    impl BlockEntry for BatchTransaction {
+        fn id(&self) -> String {
+            BatchTransaction::id(self)
+        }
+
+        fn validate(&self, blocks: &[Block], _network: &NetworkId, _rules: &ChainConfig) -> Result<(), InvalidEntryErr> {
+            block::check_batch(self.clone(), blocks)?;
+            Ok(())
+        }
+
+        fn as_batch(&self) -> Option<&BatchTransaction> {
+            Some(self)
+        }
+    }
+}
@@ -1,24 +1,96 @@
 pub mod chain {
 
-    use crate::chain::block::block::block::Block;
+    use crate::chain::block::block::block::{self, Block, BlockHeader, InvalidRecordErr, InvalidTransactionErr};
+    use crate::chain::network::network::NetworkId;
+    use crate::chain::state::state::{ChainState, StateSnapshot};
     use crate::miner::miner::miner::MiningDigest;
-    use crate::node::reply::reply::Reply;
-    use crate::Transaction;
+    use crate::record::record::record::Record;
+    use crate::transaction::batch::batch::InvalidBatchErr;
+    use crate::token::token::token::Token;
 
-    use std::fmt;
+    use std::{collections::{BTreeMap, HashMap}, fmt, sync::Arc};
     use serde::{Deserialize, Serialize};
-    use sha2::{Digest, Sha256};
     use tracing::debug;
 
     /// The interval (in seconds) to check for increasing difficulty. Difficulty increases if mining a block takes more than this interval.
-    const INTERVAL: u64 = 60;
+    pub const INTERVAL: u64 = 60;
+
+    /// Nonces a block replaced: its senders with the nonce they had before it, oldest first.
+    pub type NonceUndo = Vec<(Vec<u8>, Option<u64>)>;
 
     /// Struct representing a blockchain with a vector of blocks, length, and mining difficulty.
     #[derive(Clone, Serialize, Deserialize)]
+    #[serde(from = "StoredChain")]
     pub struct Chain {
-        blocks: Vec<Block>,    // List of blocks in the chain
+        blocks: Arc<Vec<Block>>, // List of blocks in the chain, shared with miners until it changes
         len: usize,            // Current length of the chain
         pub difficulty: usize, // Current mining difficulty (number of leading zeros required)
+        #[serde(skip)]
+        state: ChainState,     // What the blocks add up to, kept up to date by add_block
+    }
+
+    /// A `Chain` as it is serialized, without its state, which is rebuilt from its blocks.
+    #[derive(Deserialize)]
+    struct StoredChain {
+        blocks: Vec<Block>,
+        len: usize,
+        difficulty: usize,
+    }
+
+    impl From<StoredChain> for Chain {
+        fn from(stored: StoredChain) -> Self {
+            Chain {
+                state: ChainState::of(&stored.blocks),
+                blocks: Arc::new(stored.blocks),
+                len: stored.len,
+                difficulty: stored.difficulty,
+            }
+        }
+    }
+
+    /// A summary of a chain, small enough to be gossiped instead of the chain itself.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ChainDigest {
+        /// Number of blocks in the chain.
+        pub height: usize,
+        /// Hash of the last block.
+        pub tip: String,
+    }
+
+    /// A page of record keys, in lexicographic order. See `Chain::list_keys`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct KeyPage {
+        pub keys: Vec<String>,
+        /// The cursor to ask for the next page with, `None` on the last one.
+        pub next: Option<String>,
+    }
+
+    impl KeyPage {
+        /// Pages through `keys`, sorted and without duplicates: up to `limit` of those
+        /// starting with `prefix`, past `cursor`.
+        ///
+        /// # Arguments
+        /// * `keys` - Every candidate key, in lexicographic order.
+        /// * `prefix` - What the keys listed start with. Empty to list every key.
+        /// * `limit` - Most keys listed.
+        /// * `cursor` - The last key of the previous page, `None` for the first page.
+        pub fn of<'a>(keys: impl IntoIterator<Item = &'a str>, prefix: &str, limit: usize, cursor: Option<&str>) -> Self {
+            let mut keys: Vec<String> = keys
+                .into_iter()
+                .skip_while(|key| cursor.is_some_and(|cursor| *key <= cursor) || *key < prefix)
+                .take_while(|key| key.starts_with(prefix))
+                .take(limit.saturating_add(1))
+                .map(str::to_string)
+                .collect();
+            let next = match keys.len() > limit {
+                true => {
+                    keys.truncate(limit);
+                    keys.last().cloned()
+                },
+                false => None,
+            };
+            KeyPage { keys, next }
+        }
     }
 
     /// Enum representing possible errors when validating a block in the chain.
@@ -32,6 +104,41 @@ pub mod chain {
         NotInChain { expected: String, got: String },
         /// Error for when the block's hash does not match the expected hash.
         WrongHash { expected: String, got: String },
+        /// Error for when a transaction does not raise the nonce of its sender, e.g. replays an older one.
+        StaleNonce { transaction: String, nonce: u64 },
+        /// Error for when a block other than the one pinned by a checkpoint sits at its index.
+        CheckpointMismatch { index: usize, expected: String, got: String },
+        /// Error for when a block of a proof-of-authority chain carries no seal.
+        Unsealed(usize),
+        /// Error for when a block is sealed by an authority other than the one whose turn it was.
+        WrongAuthority { index: usize, expected: String, got: String },
+        /// Error for when the seal of a block is not its authority's signature of its hash.
+        InvalidSeal(usize),
+        /// Error for when a block of a proof-of-work chain is sealed rather than mined.
+        UnexpectedSeal(usize),
+        /// Error for when a batch of transfers is malformed, e.g. sends the same coin twice.
+        InvalidBatch { batch: String, error: InvalidBatchErr },
+        /// Error for when a record breaks the record rules of the chain, e.g. is too large.
+        InvalidRecord { record: String, error: InvalidRecordErr },
+        /// Error for when a block belongs to another network than the chain.
+        WrongNetwork { index: usize, expected: NetworkId, got: NetworkId },
+        /// Error for when a block is of another version than the one active at its index.
+        WrongVersion { index: usize, expected: u32, got: u32 },
+        /// Error for when a block commits to another state than the one it leads to.
+        WrongStateRoot { index: usize, expected: String, got: String },
+        /// Error for when a block commits to no state from the height the chain requires one.
+        MissingStateRoot(usize),
+        /// Error for when a transaction creates coins the mint policy of the chain does not allow.
+        InvalidMint { index: usize, transaction: String },
+        /// Error for when a transaction or batch is not signed by its sender.
+        InvalidSignature { index: usize, transaction: String },
+        /// Error for when a transaction sends coins its sender does not own.
+        InvalidTransaction { index: usize, transaction: String, error: InvalidTransactionErr },
+        /// Error for when a chain received holds a pruned block, so the hash of the block
+        /// mined on it cannot be re-derived.
+        Pruned(usize),
+        /// Error for when a chain claims another difficulty than the one its blocks raised it to.
+        WrongDifficulty { expected: usize, got: usize },
     }
 
     impl fmt::Display for BlockCheckError {
@@ -50,27 +157,106 @@ pub mod chain {
                 BlockCheckError::WrongHash { expected, got } => write!(
                     f, "Wrong hash. Expected: {}, but got: {}", expected, got
                 ),
+                BlockCheckError::StaleNonce { transaction, nonce } => write!(
+                    f, "Transaction {} reuses the nonce {} of its sender", transaction, nonce
+                ),
+                BlockCheckError::CheckpointMismatch { index, expected, got } => write!(
+                    f, "Block {} does not match its checkpoint. Expected: {}, but got: {}", index, expected, got
+                ),
+                BlockCheckError::Unsealed(index) => write!(
+                    f, "Block {} is not sealed by an authority", index
+                ),
+                BlockCheckError::WrongAuthority { index, expected, got } => write!(
+                    f, "Block {} is sealed by the wrong authority. Expected: {}, but got: {}", index, expected, got
+                ),
+                BlockCheckError::InvalidSeal(index) => write!(
+                    f, "The seal of block {} does not match its hash", index
+                ),
+                BlockCheckError::UnexpectedSeal(index) => write!(
+                    f, "Block {} is sealed, but the chain is mined", index
+                ),
+                BlockCheckError::InvalidBatch { batch, error } => write!(
+                    f, "Batch {} is invalid - {}", batch, error
+                ),
+                BlockCheckError::InvalidRecord { record, error } => write!(
+                    f, "Record {} is invalid - {}", record, error
+                ),
+                BlockCheckError::WrongNetwork { index, expected, got } => write!(
+                    f, "Block {} belongs to another network. Expected: {}, but got: {}", index, expected, got
+                ),
+                BlockCheckError::WrongVersion { index, expected, got } => write!(
+                    f, "Block {} is of the wrong version. Expected: {}, but got: {}", index, expected, got
+                ),
+                BlockCheckError::WrongStateRoot { index, expected, got } => write!(
+                    f, "Block {} commits to the wrong state. Expected: {}, but got: {}", index, expected, got
+                ),
+                BlockCheckError::MissingStateRoot(index) => write!(
+                    f, "Block {} commits to no state", index
+                ),
+                BlockCheckError::InvalidMint { index, transaction } => write!(
+                    f, "Transaction {} of block {} creates coins against the mint policy", transaction, index
+                ),
+                BlockCheckError::InvalidSignature { index, transaction } => write!(
+                    f, "Transaction {} of block {} is not signed by its sender", transaction, index
+                ),
+                BlockCheckError::InvalidTransaction { index, transaction, error } => write!(
+                    f, "Transaction {} of block {} is invalid - {}", transaction, index, error
+                ),
+                BlockCheckError::Pruned(index) => write!(
+                    f, "Block {} is pruned, so the hash of the next block cannot be checked", index
+                ),
+                BlockCheckError::WrongDifficulty { expected, got } => write!(
+                    f, "Wrong difficulty. Expected: {}, but the chain claims {}", expected, got
+                ),
             }
         }
     }
 
+    impl std::error::Error for BlockCheckError {}
+
+    impl Default for Chain {
+        fn default() -> Self {
+            Chain::new()
+        }
+    }
+
     impl Chain {
-        /// Creates a new blockchain with a single genesis block.
+        /// Creates a new blockchain with a single genesis block, on `mainnet`.
         ///
         /// # Returns
         /// A new instance of `Chain`.
         pub fn new() -> Self {
-            let genesis_block = Block::new(0, "0".repeat(64), String::from(""), Some("0".repeat(64)));
+            Chain::for_network(&NetworkId::default())
+        }
+
+        /// Creates a new blockchain with a single genesis block, on `network`. Blocks
+        /// mined on chains of other networks cannot be added to it.
+        pub fn for_network(network: &NetworkId) -> Self {
+            let mut genesis_block = Block::new(0, "0".repeat(64), String::from(""), Some("0".repeat(64)));
+            genesis_block.network = network.clone();
+            genesis_block.state_root = ChainState::new().root_after(&genesis_block);
             let mut chain = Chain {
-                blocks: vec![],
+                blocks: Arc::new(vec![]),
                 len: 0,
                 difficulty: 1,
+                state: ChainState::new(),
             };
             let genesis_mining_digest = MiningDigest::new(genesis_block, 0);
             chain.add_block(genesis_mining_digest).unwrap();
             chain
         }
 
+        /// Creates a chain out of a genesis block of its own, such as the one of a
+        /// `Genesis`, starting at `difficulty`.
+        pub fn from_genesis(genesis_block: Block, difficulty: usize) -> Self {
+            Chain {
+                state: ChainState::of([&genesis_block]),
+                blocks: Arc::new(vec![genesis_block]),
+                len: 1,
+                difficulty,
+            }
+        }
+
         /// Returns the current length of the chain.
         ///
         /// # Returns
@@ -79,10 +265,20 @@ pub mod chain {
             self.len
         }
 
-        /// Verifies the validity of a block based on its data, previous hash, and current difficulty.
+        /// Network of the chain, the one of its genesis block.
+        pub fn network(&self) -> &NetworkId {
+            &self.blocks[0].network // It is impossible to have a chain with 0 blocks.
+        }
+
+        /// Always false: a chain holds at least its genesis block.
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+
+        /// Verifies the validity of a block based on its mined hash, previous hash, and current difficulty.
         ///
         /// # Arguments
-        /// * `data` - The block data.
+        /// * `digest_str` - The hash the nonce gives the previous block. See `Block::mining_hash`.
         /// * `previous_hash` - Hash of the previous block.
         /// * `block_hash` - Hash of the current block.
         /// * `block_index` - Index of the current block.
@@ -91,16 +287,11 @@ pub mod chain {
         /// A `Result` which is `Ok` if the block is valid or contains a `BlockCheckError` if invalid.
         fn check_block_data(
             &self,
-            data: String,
+            digest_str: String,
             previous_hash: &String,
             block_hash: &String,
             block_index: usize
         ) -> Result<(), BlockCheckError> {
-            let mut hasher = Sha256::new();
-            hasher.update(data);
-            let digest = hasher.finalize();
-            let digest_str = format!("{:x}", digest);
-
             if block_index != self.len + 1 {
                 return Err(BlockCheckError::WrongIndex(self.len + 1, block_index));
             }
@@ -118,6 +309,23 @@ pub mod chain {
             Ok(())
         }
 
+        /// Verifies a block sealed by an authority: it follows the last block and its hash
+        /// is its `Block::content_hash`. Who sealed it is up to `ChainConfig::verify_headers`.
+        fn check_sealed_block(&self, block: &Block) -> Result<(), BlockCheckError> {
+            if block.index != self.len + 1 {
+                return Err(BlockCheckError::WrongIndex(self.len + 1, block.index));
+            }
+            let last_chain_hash = self.get_last_block().hash;
+            if block.previous_hash != last_chain_hash {
+                return Err(BlockCheckError::NotInChain { expected: block.previous_hash.clone(), got: last_chain_hash });
+            }
+            let content_hash = block.content_hash();
+            if block.hash != content_hash {
+                return Err(BlockCheckError::WrongHash { expected: content_hash, got: block.hash.clone() });
+            }
+            Ok(())
+        }
+
         /// Adjusts the difficulty level based on the block's timestamp. If the time taken is less than the interval, difficulty is increased.
         ///
         /// # Arguments
@@ -129,6 +337,24 @@ pub mod chain {
             }
         }
 
+        /// Returns the difficulty the chain started at, the one of its genesis block: its
+        /// difficulty, less every rise since, and at least 1. A chain claiming a lower
+        /// difficulty than its blocks raised it to is refused by `verify_chain`.
+        pub fn start_difficulty(&self) -> usize {
+            let raised = self.blocks.windows(2).filter(|pair| raised_difficulty(&pair[0].header(), &pair[1].header())).count();
+            self.difficulty.saturating_sub(raised).max(1)
+        }
+
+        /// Returns the difficulty the chain had once its blocks below `index` were added:
+        /// its difficulty, less the rises of the blocks from `index` on.
+        pub fn difficulty_at(&self, index: usize) -> usize {
+            let raised = self.blocks
+                .windows(2)
+                .filter(|pair| pair[1].index >= index && raised_difficulty(&pair[0].header(), &pair[1].header()))
+                .count();
+            self.difficulty.saturating_sub(raised).max(1)
+        }
+
         /// Retrieves the last block in the chain.
         ///
         /// # Returns
@@ -137,7 +363,10 @@ pub mod chain {
             self.blocks.iter().last().unwrap().clone() // It is impossible to have a chain with 0 blocks.
         }
 
-        /// Adds a new block to the chain after validating its data, hash, and index.
+        /// Adds a new block to the chain after validating its network, data, hash, index
+        /// and state root, and applies it to the state of the chain. Blocks sealed by an
+        /// authority are checked by `check_sealed_block` instead, and leave the difficulty
+        /// unchanged.
         ///
         /// # Arguments
         /// * `block` - The new `Block` to be added.
@@ -146,30 +375,311 @@ pub mod chain {
         /// # Returns
         /// A `Result` which is `Ok` if the block is added successfully or contains a `BlockCheckError` if the block is invalid.
         pub fn add_block(&mut self, mining_digest: MiningDigest) -> Result<(), BlockCheckError> {
-            let block = mining_digest.get_block();
+            let mut block = mining_digest.get_block();
             let nonce = mining_digest.get_nonce();
-            if block.index != 0 {
+            // Kept with the block, so chains received from elsewhere can re-derive its hash.
+            block.nonce = nonce;
+            if block.index != 0 && block.network != *self.network() {
+                return Err(BlockCheckError::WrongNetwork { index: block.index, expected: self.network().clone(), got: block.network });
+            }
+            if block.seal.is_some() {
+                self.check_sealed_block(&block)?;
+                verify_nonces(&mut block::last_nonces(self.blocks.iter()), &block)?;
+            } else if block.index != 0 {
                 let last_block = self.blocks.iter().last().clone().unwrap();
-                let str_block = format!("{}{}{}{}{}{}",  
-                    last_block.hash,
-                    last_block.previous_hash,
-                    last_block.data,
-                    last_block.timestamp,
-                    last_block.index,
-                    nonce, // Include the mined nonce
-                );
-                let data = str_block.clone();
+                let digest_str = last_block.mining_hash(nonce, &block.network, block.version);
                 let previous_hash = &block.previous_hash;
                 let block_hash = &block.hash;
                 let block_index = block.index;
-                self.check_block_data(data, previous_hash, block_hash, block_index)?;
+                self.check_block_data(digest_str, previous_hash, block_hash, block_index)?;
+                verify_nonces(&mut block::last_nonces(self.blocks.iter()), &block)?;
+            }
+            // Blocks stored before blocks had state roots commit to none.
+            let undo = self.state.apply(&block);
+            if !block.state_root.is_empty() && block.state_root != self.state.root() {
+                let expected = self.state.root();
+                self.state.undo(undo);
+                return Err(BlockCheckError::WrongStateRoot { index: block.index, expected, got: block.state_root });
+            }
+            if block.seal.is_none() && block.index != 0 {
                 self.check_difficulty(block.timestamp);
             }
-            self.blocks.push(block);
+            Arc::make_mut(&mut self.blocks).push(block);
             self.len += 1;
             Ok(())
         }
 
+        /// Verifies the structure of a chain received from elsewhere: block indices are
+        /// consecutive, every block points to the hash of the previous one, the hash of
+        /// every mined block is the one its nonce gives the previous block and satisfies
+        /// the difficulty the chain had then, and the hash of every sealed block is its
+        /// `Block::content_hash`, which its seal signs.
+        ///
+        /// # Returns
+        /// A `Result` which is `Ok` if the chain is consistent or contains the first `BlockCheckError` found.
+        pub fn verify_chain(&self) -> Result<(), BlockCheckError> {
+            self.verify_from(0)?;
+            let mut last = HashMap::new();
+            for block in self.blocks.iter() {
+                verify_nonces(&mut last, block)?;
+            }
+            Ok(())
+        }
+
+        /// Verifies the structure of the blocks from `height` on, as `verify_chain` does,
+        /// trusting the ones before it. The nonces of their transactions are left to
+        /// `apply_nonces`. The hash of a block mined on a pruned one, or of a pruned sealed
+        /// block, can no longer be re-derived, and is only held to the difficulty or seal.
+        /// The difficulty is replayed from the one the chain started at, see
+        /// `start_difficulty`, which whoever knows the genesis of the chain checks.
+        ///
+        /// # Returns
+        /// A `Result` which is `Ok` if the blocks are consistent or contains the first `BlockCheckError` found.
+        pub fn verify_from(&self, height: usize) -> Result<(), BlockCheckError> {
+            if self.len != self.blocks.len() {
+                return Err(BlockCheckError::WrongIndex(self.blocks.len(), self.len));
+            }
+            // The block before `height` is taken along, to check the first one links to it.
+            let first = height.saturating_sub(1).min(self.blocks.len());
+            let raised = self.blocks.windows(2).take(first).filter(|pair| raised_difficulty(&pair[0].header(), &pair[1].header())).count();
+            let headers: Vec<BlockHeader> = self.blocks[first..].iter().map(Block::header).collect();
+            verify_headers_from(&headers, first, self.start_difficulty() + raised, self.difficulty)?;
+            self.blocks[first..].windows(2).try_for_each(|pair| verify_hash(&pair[0], &pair[1]))
+        }
+
+        /// Builds a chain out of blocks received from elsewhere.
+        ///
+        /// # Arguments
+        /// * `blocks` - Every block of the chain, genesis first.
+        /// * `difficulty` - The difficulty the chain had reached.
+        ///
+        /// # Returns
+        /// A `Result` containing the chain, or the first `BlockCheckError` found by `verify_chain`.
+        pub fn from_blocks(blocks: Vec<Block>, difficulty: usize) -> Result<Chain, BlockCheckError> {
+            let chain = Chain {
+                len: blocks.len(),
+                state: ChainState::of(&blocks),
+                blocks: Arc::new(blocks),
+                difficulty,
+            };
+            chain.verify_chain()?;
+            Ok(chain)
+        }
+
+        /// Builds a chain out of blocks received from elsewhere, without verifying them.
+        /// Meant for callers verifying them otherwise, such as a node's `Verifier`.
+        pub fn from_blocks_unverified(blocks: Vec<Block>, difficulty: usize) -> Chain {
+            Chain {
+                len: blocks.len(),
+                state: ChainState::of(&blocks),
+                blocks: Arc::new(blocks),
+                difficulty,
+            }
+        }
+
+        /// Returns the headers of every block in the chain, genesis first.
+        pub fn headers(&self) -> Vec<BlockHeader> {
+            self.blocks.iter().map(Block::header).collect()
+        }
+
+        /// Retrieves the blocks with the given hashes, skipping the ones not in the chain.
+        pub fn get_blocks_by_hash(&self, hashes: &[String]) -> Vec<Block> {
+            hashes
+                .iter()
+                .filter_map(|hash| self.blocks.iter().find(|block| block.hash == *hash))
+                .cloned()
+                .collect()
+        }
+
+        /// Returns the block at `index`, `None` if the chain does not reach it.
+        pub fn get_block(&self, index: usize) -> Option<&Block> {
+            self.blocks.iter().find(|block| block.index == index)
+        }
+
+        /// Iterates over the blocks at `height` and above, oldest first.
+        pub fn blocks_from(&self, height: usize) -> impl Iterator<Item = &Block> {
+            self.blocks.iter().filter(move |block| block.index >= height)
+        }
+
+        /// Looks for the last record written under `key`.
+        ///
+        /// # Returns
+        /// The record and the index of the block holding it, `None` if no block does.
+        pub fn find_record(&self, key: &str) -> Option<(&Record, usize)> {
+            self.blocks
+                .iter()
+                .rev()
+                .find_map(|block| {
+                    block.records.iter().rev().find(|record| record.key == key).map(|record| (record, block.index))
+                })
+        }
+
+        /// Lists the keys records are written under, deleted ones aside, so applications
+        /// using the chain as a key-value store can enumerate their data. Every block is
+        /// read: nodes with an explorer answer the same from its index, see
+        /// `Explorer::list_keys`.
+        ///
+        /// # Arguments
+        /// * `prefix` - What the keys listed start with. Empty to list every key.
+        /// * `limit` - Most keys listed.
+        /// * `cursor` - The `next` cursor of the previous page, `None` for the first page.
+        ///
+        /// # Returns
+        /// * `KeyPage` - The keys, in lexicographic order, and the cursor of the next page.
+        pub fn list_keys(&self, prefix: &str, limit: usize, cursor: Option<&str>) -> KeyPage {
+            let mut live: BTreeMap<&str, bool> = BTreeMap::new();
+            for record in self.blocks.iter().flat_map(|block| &block.records) {
+                live.insert(&record.key, !record.is_deletion());
+            }
+            let keys = live.into_iter().filter(|(_, live)| *live).map(|(key, _)| key);
+            KeyPage::of(keys, prefix, limit, cursor)
+        }
+
+        /// Returns the state of the chain: who owns each coin, and the last record written
+        /// under each key. Light clients are sent its proofs, see `ChainState::prove`.
+        pub fn state(&self) -> &ChainState {
+            &self.state
+        }
+
+        /// Returns the root of the state of the chain. Nodes holding the same coins and
+        /// records have the same one, whatever blocks they pruned since.
+        pub fn state_root(&self) -> String {
+            self.state.root()
+        }
+
+        /// Checks every block commits to the state it leads to, as replayed when the chain
+        /// was built. Chains stored pruned cannot replay the blocks they dropped, and fail
+        /// until their state is restored, see `restore_state`.
+        ///
+        /// # Returns
+        /// A `Result` which is `Ok` if every root matched or contains `BlockCheckError::WrongStateRoot` for the first one that did not.
+        pub fn verify_state(&self) -> Result<(), BlockCheckError> {
+            match self.state.divergence() {
+                Some(divergence) => Err(BlockCheckError::WrongStateRoot {
+                    index: divergence.index,
+                    expected: divergence.expected.clone(),
+                    got: divergence.got.clone(),
+                }),
+                None => Ok(()),
+            }
+        }
+
+        /// Takes a snapshot of the state, to restore it once the chain is stored pruned.
+        pub fn snapshot(&self) -> StateSnapshot {
+            StateSnapshot { index: self.get_last_block().index, leaves: self.state.leaves() }
+        }
+
+        /// Restores the state of the chain from `snapshot`, which must match the root the
+        /// block it was taken at committed to, and replays the blocks past it.
+        ///
+        /// # Returns
+        /// A `Result` which is `Ok` if the state was restored or contains the first `BlockCheckError` found.
+        pub fn restore_state(&mut self, snapshot: StateSnapshot) -> Result<(), BlockCheckError> {
+            let index = snapshot.index;
+            let block = self.get_block(index).ok_or(BlockCheckError::WrongIndex(self.get_last_block().index, index))?;
+            if block.state_root.is_empty() {
+                return Err(BlockCheckError::MissingStateRoot(index));
+            }
+            let mut state = ChainState::from_leaves(snapshot.leaves);
+            if state.root() != block.state_root {
+                return Err(BlockCheckError::WrongStateRoot { index, expected: state.root(), got: block.state_root.clone() });
+            }
+            for block in self.blocks_from(index + 1) {
+                state.apply(block);
+                if !block.state_root.is_empty() && block.state_root != state.root() {
+                    return Err(BlockCheckError::WrongStateRoot { index: block.index, expected: state.root(), got: block.state_root.clone() });
+                }
+            }
+            self.state = state;
+            Ok(())
+        }
+
+        /// Replaces the state rebuilt from the blocks of the chain with `state`, the one a
+        /// `Verifier` replayed them into. Chains sharing pruned blocks with a node's own
+        /// cannot rebuild it themselves.
+        pub fn with_state(mut self, state: ChainState) -> Chain {
+            self.state = state;
+            self
+        }
+
+        /// Returns the nonce the next transaction of `sender` must carry at least: one more
+        /// than the nonce of its last transaction in the chain, 0 if it made none.
+        pub fn next_nonce(&self, sender: &[u8]) -> u64 {
+            block::last_nonces(self.blocks.iter()).get(sender).map_or(0, |last| last + 1)
+        }
+
+        /// Lists the coins whose last transaction in the chain sends them to `owner`. Coins
+        /// only moved in pruned blocks are not found.
+        ///
+        /// # Arguments
+        /// * `owner` - The public key of a wallet.
+        ///
+        /// # Returns
+        /// * `Vec<Token>` - The coins `owner` can spend.
+        pub fn coins_of(&self, owner: &[u8]) -> Vec<Token> {
+            block::coins_of(owner, self.blocks.iter())
+        }
+
+        /// Drops the transactions and records of every block but the last `keep_full` ones,
+        /// which are enough to check the next blocks. Pruned blocks still link up, so the
+        /// chain keeps passing `verify_chain`.
+        ///
+        /// # Arguments
+        /// * `keep_full` - Blocks kept whole, at least one.
+        ///
+        /// # Returns
+        /// The number of blocks pruned by this call.
+        pub fn prune(&mut self, keep_full: usize) -> usize {
+            let end = self.blocks.len().saturating_sub(keep_full.max(1));
+            Arc::make_mut(&mut self.blocks)[..end].iter_mut().map(Block::prune).filter(|pruned| *pruned).count()
+        }
+
+        /// Finds the last block this chain shares with `other`, where the two forked.
+        ///
+        /// # Returns
+        /// * `Option<(usize, String)>` - The index and hash of that block, `None` if the
+        ///   chains do not even share their genesis block.
+        pub fn common_ancestor(&self, other: &Chain) -> Option<(usize, String)> {
+            let shared = common_prefix(&self.headers(), &other.headers());
+            shared.checked_sub(1).map(|position| {
+                let ancestor = &self.blocks[position];
+                (ancestor.index, ancestor.hash.clone())
+            })
+        }
+
+        /// Finds the height where this chain and `other` stop holding the same blocks.
+        ///
+        /// # Returns
+        /// * `Option<usize>` - The number of blocks both chains share, `None` if one of
+        ///   them holds every block of the other, so they do not diverge.
+        pub fn diverges_at(&self, other: &Chain) -> Option<usize> {
+            let shared = common_prefix(&self.headers(), &other.headers());
+            (shared < self.len() && shared < other.len()).then_some(shared)
+        }
+
+        /// Summarizes the chain by its height and the hash of its last block.
+        pub fn digest(&self) -> ChainDigest {
+            ChainDigest {
+                height: self.len,
+                tip: self.get_last_block().hash,
+            }
+        }
+
+        /// Looks for a transaction or record in the chain by its id.
+        ///
+        /// # Returns
+        /// The index of the block holding the entry, `None` if no block does.
+        pub fn find_entry(&self, id: &str) -> Option<usize> {
+            self.blocks
+                .iter()
+                .find(|block| {
+                    block.records.iter().any(|record| record.id() == id)
+                        || block.batches.iter().any(|batch| batch.id() == id)
+                        || block.get_transactions().iter().any(|transaction| transaction.id() == id)
+                })
+                .map(|block| block.index)
+        }
+
         /// Returns the length of the chain (number of blocks).
         pub fn get_len(&self) -> usize {
             self.len
@@ -187,25 +697,137 @@ pub mod chain {
         pub fn get_blocks(&self) -> Vec<Block> {
             self.blocks.iter().cloned().collect()
         }
+
+        /// Borrows all the blocks in the chain, genesis first, to check entries against
+        /// them without cloning the chain as `get_blocks` does.
+        pub fn blocks(&self) -> &[Block] {
+            &self.blocks
+        }
+
+        /// Shares all the blocks in the chain, genesis first, with whoever keeps them past
+        /// a borrow, such as a miner. Blocks are copied only if the chain changes while
+        /// they are shared.
+        pub fn shared_blocks(&self) -> Arc<Vec<Block>> {
+            self.blocks.clone()
+        }
     }
 
-    /// Implementation of the `Reply` trait for the `Chain` struct, allowing it to be used in message replies.
-    impl Reply for Chain {
-        /// Converts the chain to a transaction, which is not applicable here.
-        ///
-        /// # Returns
-        /// None, as a chain is not a transaction.
-        fn as_transaction(&mut self) -> Option<&mut Transaction> {
-            None
+    /// Checks every transaction and batch of `block` raises the nonce of its sender, and
+    /// every batch is well formed.
+    ///
+    /// # Arguments
+    /// * `last` - The nonce of the last transaction of each sender before `block`, updated
+    ///   with the ones of `block`. See `block::last_nonces`.
+    fn verify_nonces(last: &mut HashMap<Vec<u8>, u64>, block: &Block) -> Result<(), BlockCheckError> {
+        apply_nonces(last, block).map(|_| ())
+    }
+
+    /// Checks every transaction and batch of `block` raises the nonce of its sender, and
+    /// every batch is well formed, like `verify_nonces`, and returns what it replaced in
+    /// `last`. The transfers of a batch share its nonce, which is checked once.
+    ///
+    /// # Returns
+    /// * `Result<NonceUndo, BlockCheckError>` - What `block` replaced in `last`, to undo
+    ///   the block. `last` may be left partly updated on error.
+    pub fn apply_nonces(last: &mut HashMap<Vec<u8>, u64>, block: &Block) -> Result<NonceUndo, BlockCheckError> {
+        let mut replaced = vec![];
+        for transaction in block.get_data_transactions() {
+            if block::check_nonce(&transaction, last).is_err() {
+                return Err(BlockCheckError::StaleNonce { transaction: transaction.id(), nonce: transaction.nonce });
+            }
+            let previous = last.insert(transaction.sender.clone(), transaction.nonce);
+            replaced.push((transaction.sender, previous));
+        }
+        for batch in &block.batches {
+            if let Err(error) = batch.check() {
+                return Err(BlockCheckError::InvalidBatch { batch: batch.id(), error });
+            }
+            if block::check_sender_nonce(&batch.sender, batch.nonce, last).is_err() {
+                return Err(BlockCheckError::StaleNonce { transaction: batch.id(), nonce: batch.nonce });
+            }
+            let previous = last.insert(batch.sender.clone(), batch.nonce);
+            replaced.push((batch.sender.clone(), previous));
         }
+        Ok(replaced)
+    }
 
-        /// Converts the chain into a mutable reference to itself.
-        ///
-        /// # Returns
-        /// A mutable reference to the chain.
-        fn as_chain(&mut self) -> Option<&mut Chain> {
-            Some(self)
+    /// Counts the headers two chains share before they fork, genesis first.
+    ///
+    /// # Arguments
+    /// * `ours` - The headers of one chain.
+    /// * `theirs` - The headers of the other.
+    ///
+    /// # Returns
+    /// * `usize` - The height up to which both chains hold the same blocks.
+    pub fn common_prefix(ours: &[BlockHeader], theirs: &[BlockHeader]) -> usize {
+        ours.iter().zip(theirs).take_while(|(ours, theirs)| ours == theirs).count()
+    }
+
+    /// Checks the hash of `block` is the one the nonce it was mined with gives `previous`,
+    /// or its content hash if it is sealed, as `Chain::add_block` does. Hashes covering
+    /// what a pruned block held are left alone.
+    fn verify_hash(previous: &Block, block: &Block) -> Result<(), BlockCheckError> {
+        let expected = match block.seal {
+            Some(_) if block.pruned => return Ok(()),
+            Some(_) => block.content_hash(),
+            None if previous.pruned => return Ok(()),
+            None => previous.mining_hash(block.nonce, &block.network, block.version),
+        };
+        if block.hash != expected {
+            return Err(BlockCheckError::WrongHash { expected, got: block.hash.clone() });
+        }
+        Ok(())
+    }
+
+    /// Whether adding the block of `header` after the one of `previous` raised the
+    /// difficulty of the chain. See `Chain::check_difficulty`.
+    fn raised_difficulty(previous: &BlockHeader, header: &BlockHeader) -> bool {
+        header.seal.is_none() && header.timestamp < previous.timestamp + INTERVAL
+    }
+
+    /// Verifies that headers link up into a chain: indices are consecutive, every header
+    /// points to the hash of the previous one and belongs to its network, the hash of
+    /// every mined block satisfies the difficulty the chain had when it was added, and the
+    /// chain reached the difficulty claimed. Headers carry too little to re-derive the
+    /// hashes, see `Chain::verify_from`.
+    ///
+    /// # Arguments
+    /// * `headers` - The headers to check, genesis first.
+    /// * `start` - The difficulty of the genesis block, raised block by block from there.
+    /// * `difficulty` - The difficulty the chain claims to have reached with the last of them.
+    ///
+    /// # Returns
+    /// A `Result` which is `Ok` if the headers are consistent or contains the first `BlockCheckError` found.
+    pub fn verify_headers(headers: &[BlockHeader], start: usize, difficulty: usize) -> Result<(), BlockCheckError> {
+        verify_headers_from(headers, 0, start, difficulty)
+    }
+
+    /// Same as `verify_headers`, for headers starting with the one of the block at position
+    /// `first` in the chain, once the chain reached difficulty `required` with it.
+    fn verify_headers_from(headers: &[BlockHeader], first: usize, mut required: usize, difficulty: usize) -> Result<(), BlockCheckError> {
+        for (position, pair) in headers.windows(2).enumerate() {
+            let (previous, header) = (&pair[0], &pair[1]);
+            let expected_index = first + position + 2; // The first mined block follows the genesis with index 2.
+            if header.index != expected_index {
+                return Err(BlockCheckError::WrongIndex(expected_index, header.index));
+            }
+            if header.previous_hash != previous.hash {
+                return Err(BlockCheckError::NotInChain { expected: previous.hash.clone(), got: header.previous_hash.clone() });
+            }
+            if header.network != previous.network {
+                return Err(BlockCheckError::WrongNetwork { index: header.index, expected: previous.network.clone(), got: header.network.clone() });
+            }
+            if header.seal.is_none() && !header.hash.starts_with(&"0".repeat(required)) {
+                return Err(BlockCheckError::InvalidPrefix(required));
+            }
+            if raised_difficulty(previous, header) {
+                required += 1;
+            }
+        }
+        if required != difficulty {
+            return Err(BlockCheckError::WrongDifficulty { expected: required, got: difficulty });
         }
+        Ok(())
     }
 }
 
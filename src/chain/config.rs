@@ -0,0 +1,457 @@
+pub mod config {
+
+    use crate::chain::{
+        block::block::block::{self, Block, BlockHeader, InvalidRecordErr, InvalidTransactionErr, Upgrade},
+        chain::chain::{BlockCheckError, Chain, INTERVAL},
+        network::network::NetworkId,
+    };
+    use crate::miner::miner::miner::ZERO_WALLET_PK;
+    use crate::record::record::record::RecordRules;
+    use crate::token::token::token::Token;
+    use crate::Transaction;
+
+    use std::collections::{HashMap, HashSet};
+
+    use base64::{Engine as _, engine::general_purpose};
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+    use serde::{Deserialize, Serialize};
+
+    pub const DEFAULT_CHECKPOINT_INTERVAL: usize = 100;
+    pub const DEFAULT_CHECKPOINT_DEPTH: usize = 6;
+    /// Namespace of the chain store the signed checkpoints a node pinned are saved to.
+    pub const CHECKPOINTS_NAMESPACE: &str = "checkpoints";
+
+    /// Tag setting checkpoint signatures apart from the other signatures of a key.
+    const CHECKPOINT_TAG: &[u8] = b"checkpoint\0";
+
+    /// A block operators trust to be part of the chain, e.g. taken from a node they run.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Checkpoint {
+        /// Index of the block. See `Block::index`.
+        pub index: usize,
+        /// Hex encoded hash of the block.
+        pub hash: String,
+    }
+
+    impl Checkpoint {
+        /// Whether `hash` looks like the hash of a block: 64 hex digits.
+        pub fn is_well_formed(&self) -> bool {
+            self.hash.len() == 64 && self.hash.chars().all(|c| c.is_ascii_hexdigit())
+        }
+    }
+
+    /// A checkpoint signed by a publisher, gossiped with `ProtocolMsg::Checkpoint`. Nodes
+    /// trusting the publisher pin it. See `ChainConfig::checkpoint_publishers`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct SignedCheckpoint {
+        pub checkpoint: Checkpoint,
+        /// Public key of the publisher.
+        pub publisher: Vec<u8>,
+        pub signature: Vec<u8>,
+    }
+
+    impl SignedCheckpoint {
+        /// The bytes a publisher signs: the NUL terminated id of the network,
+        /// `CHECKPOINT_TAG`, then the index and hash of the checkpoint.
+        pub fn signed_bytes(checkpoint: &Checkpoint, network: &NetworkId) -> Vec<u8> {
+            [
+                network.as_str().as_bytes(),
+                &[0],
+                CHECKPOINT_TAG,
+                &(checkpoint.index as u64).to_be_bytes(),
+                checkpoint.hash.as_bytes(),
+            ].concat()
+        }
+
+        /// Whether the signature is the publisher's signature of the checkpoint, for `network`.
+        pub fn verify(&self, network: &NetworkId) -> bool {
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &self.publisher)
+                .verify(&SignedCheckpoint::signed_bytes(&self.checkpoint, network), &self.signature)
+                .is_ok()
+        }
+    }
+
+    /// How the blocks of a chain are produced.
+    #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Consensus {
+        /// Miners search a nonce giving the block a hash that meets the difficulty.
+        #[default]
+        Pow,
+        /// Authorities take turns sealing blocks with their key, without any mining. See
+        /// `ChainConfig::authorities`.
+        PoA,
+    }
+
+    /// Who may create coins on a chain: the genesis block, giving away the allocations of
+    /// a `Genesis`, miners, paid the coin named after the hash of every block they mine,
+    /// and mint authorities, creating coins with transactions they sign, e.g. to issue a
+    /// stablecoin on a private network.
+    ///
+    /// ```toml
+    /// [chain.mint]
+    /// coinbase = false
+    /// premine = true
+    /// authorities = ["BHx2..."]
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(default)]
+    pub struct MintPolicy {
+        /// Whether mined blocks may pay their miner a coin.
+        pub coinbase: bool,
+        /// Whether the genesis block may give coins away. Nodes still refuse chains whose
+        /// genesis block gives away other coins than their own, see `Genesis`.
+        pub premine: bool,
+        /// Base64 encoded public keys of the wallets allowed to create coins by sending
+        /// coins no block holds yet. See `Wallet::mint`.
+        pub authorities: Vec<String>,
+    }
+
+    impl Default for MintPolicy {
+        fn default() -> Self {
+            MintPolicy {
+                coinbase: true,
+                premine: true,
+                authorities: vec![],
+            }
+        }
+    }
+
+    impl MintPolicy {
+        /// Whether `key` is the public key of one of `authorities`.
+        pub fn is_authority(&self, key: &[u8]) -> bool {
+            self.authorities
+                .iter()
+                .any(|authority| general_purpose::STANDARD.decode(authority).is_ok_and(|authority| authority == key))
+        }
+
+        /// Checks that `transaction` may be added on top of `blocks`, as
+        /// `block::check_transaction` does. Mint authorities may also send coins no block
+        /// holds yet, which creates them.
+        pub fn check_transaction(&self, transaction: Transaction, blocks: &[Block]) -> Result<Transaction, InvalidTransactionErr> {
+            if self.is_authority(&transaction.sender) {
+                let created: HashSet<Token> = blocks.iter().flat_map(Block::get_transactions).flat_map(|held| held.coins).collect();
+                if transaction.coins.iter().all(|coin| !created.contains(coin)) {
+                    return Ok(transaction);
+                }
+            }
+            block::check_transaction(transaction, blocks)
+        }
+
+        /// Checks the sender of `transaction` owns every coin it sends, as
+        /// `check_transaction` does, given the last receiver of every coin in `owners`.
+        /// Mint authorities may also send coins no one owns yet.
+        pub fn check_owner(&self, transaction: &Transaction, owners: &HashMap<Token, Vec<u8>>) -> Result<(), InvalidTransactionErr> {
+            if self.is_authority(&transaction.sender) && transaction.coins.iter().all(|coin| !owners.contains_key(coin)) {
+                return Ok(());
+            }
+            for coin in &transaction.coins {
+                match owners.get(coin) {
+                    None => return Err(InvalidTransactionErr::UnknownCoin),
+                    Some(owner) if *owner != transaction.sender => return Err(InvalidTransactionErr::IncompleteChain),
+                    Some(_) => (),
+                }
+            }
+            Ok(())
+        }
+
+        /// Checks the coins `block` creates out of the zero wallet follow the policy: the
+        /// genesis block only gives coins away if `premine` allows it, and mined blocks only
+        /// pay their miner the coin named after their hash, once, if `coinbase` allows it.
+        pub fn check_block(&self, block: &Block) -> Result<(), BlockCheckError> {
+            let prize = Token::parse_legacy(&block.hash).ok().filter(|_| self.coinbase && block.seal.is_none());
+            let mut paid = false;
+            for transaction in block.get_transactions().into_iter().filter(|transaction| transaction.sender == ZERO_WALLET_PK) {
+                let allowed = match block.index {
+                    0 => self.premine,
+                    _ => !paid && prize.is_some_and(|prize| transaction.coins == [prize]),
+                };
+                if !allowed {
+                    return Err(BlockCheckError::InvalidMint { index: block.index, transaction: transaction.id() });
+                }
+                paid = block.index != 0;
+            }
+            Ok(())
+        }
+    }
+
+    /// Rules a node holds every chain to, on top of the ones of `Chain::verify_chain`.
+    ///
+    /// ```toml
+    /// [chain]
+    /// consensus = "poa"
+    /// authorities = ["BHx2...", "BNq0..."]
+    /// block_interval = 10
+    /// checkpoint_publishers = ["BKp7..."]
+    /// state_roots_from = 0
+    ///
+    /// [chain.records]
+    /// max_value_size = 1024
+    ///
+    /// [[chain.upgrades]]
+    /// height = 50000
+    /// version = 2
+    ///
+    /// [chain.mint]
+    /// coinbase = false
+    ///
+    /// [[chain.checkpoints]]
+    /// index = 1200
+    /// hash = "0000a3f1..."
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(default)]
+    pub struct ChainConfig {
+        /// Blocks every chain reaching their index must hold. Chains branching off below a
+        /// checkpoint are refused, however long, so a node restored from scratch cannot be
+        /// fed a fabricated history.
+        pub checkpoints: Vec<Checkpoint>,
+        pub consensus: Consensus,
+        /// Base64 encoded public keys of the wallets allowed to seal blocks under
+        /// `Consensus::PoA`. The block at index `i` is sealed by the authority at
+        /// `i % authorities.len()`, so the chain stalls while one of them is offline.
+        pub authorities: Vec<String>,
+        /// Seconds an authority waits after the last block before sealing the next one.
+        pub block_interval: u64,
+        /// Base64 encoded public keys of the wallets whose signed checkpoints are pinned
+        /// when gossiped, as if they were part of `checkpoints`.
+        pub checkpoint_publishers: Vec<String>,
+        /// Blocks between two checkpoints signed by a publisher: it signs the blocks whose
+        /// index is a multiple of it.
+        pub checkpoint_interval: usize,
+        /// Blocks a block must lie below the tip of a publisher's chain to be signed, so
+        /// forks settle first.
+        pub checkpoint_depth: usize,
+        /// Limits on the size and cost of records.
+        pub records: RecordRules,
+        /// Versions of the block format, in increasing order of height, each required of
+        /// the blocks from its height on. Blocks below the first one are of
+        /// `BLOCK_VERSION`. Nodes agree on when a new format takes effect by upgrading
+        /// before its height.
+        pub upgrades: Vec<Upgrade>,
+        /// Who may create coins.
+        pub mint: MintPolicy,
+        /// Height from which every block must commit to a state root. `None` for chains
+        /// started before blocks had state roots, whose blocks are then only held to the
+        /// root they commit to, if any.
+        pub state_roots_from: Option<usize>,
+    }
+
+    impl Default for ChainConfig {
+        fn default() -> Self {
+            ChainConfig {
+                checkpoints: vec![],
+                consensus: Consensus::Pow,
+                authorities: vec![],
+                block_interval: INTERVAL,
+                checkpoint_publishers: vec![],
+                checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+                checkpoint_depth: DEFAULT_CHECKPOINT_DEPTH,
+                records: RecordRules::default(),
+                upgrades: vec![],
+                mint: MintPolicy::default(),
+                state_roots_from: None,
+            }
+        }
+    }
+
+    impl ChainConfig {
+        /// Version of the block at `index`. See `upgrades`.
+        pub fn version_at(&self, index: usize) -> u32 {
+            block::version_at(&self.upgrades, index)
+        }
+
+        /// Checks `blocks` hold every checkpoint they reach. Their hashes must have been
+        /// re-derived from what they hold, see `Chain::verify_from`, or any block could
+        /// claim the hash of a checkpoint.
+        ///
+        /// # Returns
+        /// * `Result<(), BlockCheckError>` - `BlockCheckError::CheckpointMismatch` for the
+        ///   first checkpoint another block takes the place of.
+        pub fn verify_checkpoints(&self, blocks: &[Block]) -> Result<(), BlockCheckError> {
+            for checkpoint in &self.checkpoints {
+                let Some(block) = blocks.iter().find(|block| block.index == checkpoint.index) else {
+                    continue;
+                };
+                if block.hash != checkpoint.hash {
+                    return Err(BlockCheckError::CheckpointMismatch {
+                        index: checkpoint.index,
+                        expected: checkpoint.hash.clone(),
+                        got: block.hash.clone(),
+                    });
+                }
+            }
+            Ok(())
+        }
+
+        /// Checks `headers` were produced the way `consensus` says, are of the version
+        /// active at their index and commit to a state root from `state_roots_from` on.
+        ///
+        /// # Returns
+        /// * `Result<(), BlockCheckError>` - The first block produced otherwise, of
+        ///   another version or committing to no state.
+        pub fn verify_headers(&self, headers: &[BlockHeader]) -> Result<(), BlockCheckError> {
+            for header in headers.iter().filter(|header| header.index != 0) {
+                self.verify_seal(header)?;
+                let expected = self.version_at(header.index);
+                if header.version != expected {
+                    return Err(BlockCheckError::WrongVersion { index: header.index, expected, got: header.version });
+                }
+                if header.state_root.is_empty() && self.state_roots_from.is_some_and(|height| header.index >= height) {
+                    return Err(BlockCheckError::MissingStateRoot(header.index));
+                }
+            }
+            Ok(())
+        }
+
+        /// Whether `key` is the public key of one of `checkpoint_publishers`.
+        pub fn is_publisher(&self, key: &[u8]) -> bool {
+            self.checkpoint_publishers
+                .iter()
+                .any(|publisher| general_purpose::STANDARD.decode(publisher).is_ok_and(|publisher| publisher == key))
+        }
+
+        /// Pins `checkpoint`, so chains reaching its index must hold it from now on.
+        ///
+        /// # Returns
+        /// * `Result<bool, BlockCheckError>` - Whether it was not pinned yet, or
+        ///   `BlockCheckError::CheckpointMismatch` if another block is pinned at its index.
+        pub fn pin(&mut self, checkpoint: Checkpoint) -> Result<bool, BlockCheckError> {
+            match self.checkpoints.iter().find(|pinned| pinned.index == checkpoint.index) {
+                Some(pinned) if pinned.hash == checkpoint.hash => Ok(false),
+                Some(pinned) => Err(BlockCheckError::CheckpointMismatch {
+                    index: checkpoint.index,
+                    expected: pinned.hash.clone(),
+                    got: checkpoint.hash,
+                }),
+                None => {
+                    self.checkpoints.push(checkpoint);
+                    Ok(true)
+                },
+            }
+        }
+
+        /// Returns the base64 encoded public key of the authority whose turn it is to seal
+        /// the block at `index`, `None` if there are no authorities.
+        pub fn authority_for(&self, index: usize) -> Option<&str> {
+            match self.authorities.len() {
+                0 => None,
+                n => Some(&self.authorities[index % n]),
+            }
+        }
+
+        /// Checks the block of `header` was produced the way `consensus` says: mined blocks
+        /// carry no seal, and sealed blocks are signed by the authority whose turn it was.
+        fn verify_seal(&self, header: &BlockHeader) -> Result<(), BlockCheckError> {
+            let seal = match (self.consensus, &header.seal) {
+                (Consensus::Pow, None) => return Ok(()),
+                (Consensus::Pow, Some(_)) => return Err(BlockCheckError::UnexpectedSeal(header.index)),
+                (Consensus::PoA, None) => return Err(BlockCheckError::Unsealed(header.index)),
+                (Consensus::PoA, Some(seal)) => seal,
+            };
+            let expected = self.authority_for(header.index).unwrap_or_default();
+            if general_purpose::STANDARD.decode(expected).ok().as_ref() != Some(&seal.authority) {
+                return Err(BlockCheckError::WrongAuthority {
+                    index: header.index,
+                    expected: expected.to_string(),
+                    got: general_purpose::STANDARD.encode(&seal.authority),
+                });
+            }
+            if !seal.verify(&header.hash) {
+                return Err(BlockCheckError::InvalidSeal(header.index));
+            }
+            Ok(())
+        }
+
+        /// Checks the records of `blocks`, from the one at position `from` on, are signed by
+        /// their owner and, in blocks where `records` is enforced, within its size limit and
+        /// paid for by coins their owner burned in the same block. Each block is checked on
+        /// its own. See `RecordRules::enforced_at` and `block::pay_record`.
+        ///
+        /// # Returns
+        /// * `Result<(), BlockCheckError>` - `BlockCheckError::InvalidRecord` for the first
+        ///   record not signed by its owner, too large, or not paid for.
+        pub fn verify_records(&self, blocks: &[Block], from: usize) -> Result<(), BlockCheckError> {
+            for block in blocks.iter().skip(from) {
+                let enforced = self.records.enforced_at(block.index);
+                let mut burned = if enforced { block::burned(block) } else { HashMap::new() };
+                for record in &block.records {
+                    let checked = if !record.verify_signature(&block.network) {
+                        Err(InvalidRecordErr::InvalidSignature)
+                    } else if enforced {
+                        block::check_record_size(record, &self.records)
+                            .and_then(|()| block::pay_record(record, &self.records, &mut burned))
+                    } else {
+                        Ok(())
+                    };
+                    checked.map_err(|error| BlockCheckError::InvalidRecord { record: record.id(), error })?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Checks the blocks of `blocks`, from the one at position `from` on, create coins
+        /// the way `mint` allows, and that every other transaction, batched or not, is
+        /// signed by its sender and only sends coins it owns. See `MintPolicy::check_block`
+        /// and `MintPolicy::check_owner`. Pruned blocks no longer tell who owns what, so
+        /// past one only signatures are checked.
+        ///
+        /// # Returns
+        /// * `Result<(), BlockCheckError>` - `BlockCheckError::InvalidMint` for the first
+        ///   transaction creating coins against the policy, `BlockCheckError::InvalidSignature`
+        ///   or `BlockCheckError::InvalidTransaction` for the first one its sender did not
+        ///   sign or may not send.
+        pub fn verify_mints(&self, blocks: &[Block], from: usize) -> Result<(), BlockCheckError> {
+            let mut owners = HashMap::new();
+            let mut pruned = false;
+            for block in blocks.iter().take(from) {
+                pruned |= block.pruned;
+                for transaction in block.get_transactions() {
+                    owners.extend(transaction.coins.into_iter().map(|coin| (coin, transaction.receiver.clone())));
+                }
+            }
+            for block in blocks.iter().skip(from) {
+                self.mint.check_block(block)?;
+                let data = block.get_data_transactions().into_iter().map(|transaction| (transaction.verify_signature(&block.network), transaction));
+                let batched = block.batches.iter().flat_map(|batch| {
+                    let signed = batch.verify_signature(&block.network);
+                    batch.transactions().into_iter().map(move |transaction| (signed, transaction))
+                });
+                for (signed, transaction) in data.chain(batched) {
+                    // Coins the zero wallet sends are held to `MintPolicy::check_block`.
+                    if transaction.sender != ZERO_WALLET_PK {
+                        let index = block.index;
+                        if !signed {
+                            return Err(BlockCheckError::InvalidSignature { index, transaction: transaction.id() });
+                        }
+                        match self.mint.check_owner(&transaction, &owners) {
+                            Ok(()) => (),
+                            Err(_) if pruned => (),
+                            Err(InvalidTransactionErr::UnknownCoin) => {
+                                return Err(BlockCheckError::InvalidMint { index, transaction: transaction.id() });
+                            },
+                            Err(error) => return Err(BlockCheckError::InvalidTransaction { index, transaction: transaction.id(), error }),
+                        }
+                    }
+                    owners.extend(transaction.coins.into_iter().map(|coin| (coin, transaction.receiver.clone())));
+                }
+                pruned |= block.pruned;
+            }
+            Ok(())
+        }
+
+        /// Checks `chain` holds every checkpoint it reaches, once its hashes are re-derived
+        /// by `Chain::verify_from`, follows `consensus`, holds no record breaking `records`
+        /// nor coins created against `mint`, and commits to the state it leads to. See
+        /// `verify_checkpoints`, `verify_headers`, `verify_records`, `verify_mints` and
+        /// `Chain::verify_state`.
+        pub fn verify(&self, chain: &Chain) -> Result<(), BlockCheckError> {
+            chain.verify_from(0)?;
+            self.verify_checkpoints(chain.blocks())?;
+            self.verify_headers(&chain.headers())?;
+            self.verify_records(chain.blocks(), 0)?;
+            self.verify_mints(chain.blocks(), 0)?;
+            chain.verify_state()
+        }
+    }
+}
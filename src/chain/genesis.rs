@@ -0,0 +1,209 @@
+pub mod genesis {
+
+    use crate::{
+        chain::{block::block::block::Block, chain::chain::Chain, network::network::{NetworkId, NetworkIdError}, state::state::ChainState},
+        miner::miner::miner::ZERO_WALLET_PK,
+        token::token::token::Token,
+        transaction::transaction::transaction::Transaction,
+    };
+
+    use std::{
+        collections::HashSet,
+        fs::{self, OpenOptions},
+        io::{Error as IOError, ErrorKind, Write},
+        path::{Path, PathBuf},
+    };
+
+    use base64::{Engine as _, engine::general_purpose};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use thiserror::Error;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum GenesisError {
+        #[error("A genesis needs a network name.")]
+        NoNetwork,
+        #[error("{0} is not a valid network name: {1}")]
+        #[from(ignore)]
+        InvalidNetwork(String, NetworkIdError),
+        #[error("The difficulty of a genesis must be greater than zero.")]
+        ZeroDifficulty,
+        #[error("{0} is not a base64 encoded public key.")]
+        #[from(ignore)]
+        InvalidAddress(String),
+        #[error("The allocation to {0} holds no coins.")]
+        #[from(ignore)]
+        EmptyAllocation(String),
+        #[error("{0} is allocated coins more than once.")]
+        #[from(ignore)]
+        DuplicateAllocation(String),
+        #[error("{} already exists, refusing to overwrite it.", .0.display())]
+        #[from(ignore)]
+        Exists(PathBuf),
+        #[error("Expected the genesis block {expected}, got {got}.")]
+        #[from(ignore)]
+        Mismatch { expected: String, got: String },
+        #[error(transparent)]
+        IOError(IOError),
+        #[error(transparent)]
+        SerdeError(serde_json::Error),
+    }
+
+    /// Coins the genesis block gives to an address.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Allocation {
+        /// Base64 encoded public key of the wallet receiving the coins.
+        pub address: String,
+        pub coins: usize,
+    }
+
+    impl Allocation {
+        /// Reads `ADDRESS:COINS`.
+        ///
+        /// # Returns
+        /// * `Result<Allocation, String>` - The allocation, or why `spec` describes none.
+        pub fn parse(spec: &str) -> Result<Self, String> {
+            let Some((address, coins)) = spec.rsplit_once(':') else {
+                return Err(format!("expected `ADDRESS:COINS`, got `{}`", spec));
+            };
+            match coins.parse() {
+                Ok(coins) => Ok(Allocation { address: address.to_string(), coins }),
+                Err(_) => Err(format!("expected a number of coins, got `{}`", coins)),
+            }
+        }
+    }
+
+    /// What the operators of a private network agree on before starting it. Every node
+    /// given the same genesis starts from the same block, whose hash commits to all of it,
+    /// and nodes given different ones never accept each other's chains.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct Genesis {
+        /// Name of the network, so two networks with the same allocations still differ.
+        pub network: String,
+        /// Timestamp of the genesis block, in seconds since the UNIX epoch.
+        pub timestamp: u64,
+        /// Difficulty the chain starts at.
+        pub difficulty: usize,
+        #[serde(default)]
+        pub allocations: Vec<Allocation>,
+    }
+
+    impl Genesis {
+        pub fn new(network: impl Into<String>, timestamp: u64, difficulty: usize) -> Self {
+            Genesis {
+                network: network.into(),
+                timestamp,
+                difficulty,
+                allocations: vec![],
+            }
+        }
+
+        /// Gives `coins` coins to the wallet whose base64 encoded public key is `address`.
+        pub fn allocate(mut self, address: impl Into<String>, coins: usize) -> Self {
+            self.allocations.push(Allocation { address: address.into(), coins });
+            self
+        }
+
+        /// Checks the network is named by a valid network id, the difficulty is positive
+        /// and every allocation gives coins to a valid address, a different one each.
+        pub fn validate(&self) -> Result<(), GenesisError> {
+            if self.network.is_empty() {
+                return Err(GenesisError::NoNetwork);
+            }
+            if let Err(e) = self.network.parse::<NetworkId>() {
+                return Err(GenesisError::InvalidNetwork(self.network.clone(), e));
+            }
+            if self.difficulty == 0 {
+                return Err(GenesisError::ZeroDifficulty);
+            }
+            let mut allocated = HashSet::new();
+            for allocation in &self.allocations {
+                let Ok(address) = general_purpose::STANDARD.decode(&allocation.address) else {
+                    return Err(GenesisError::InvalidAddress(allocation.address.clone()));
+                };
+                if allocation.coins == 0 {
+                    return Err(GenesisError::EmptyAllocation(allocation.address.clone()));
+                }
+                if !allocated.insert(address) {
+                    return Err(GenesisError::DuplicateAllocation(allocation.address.clone()));
+                }
+            }
+            Ok(())
+        }
+
+        /// Reads and validates the genesis file at `path`.
+        pub fn read(path: impl AsRef<Path>) -> Result<Self, GenesisError> {
+            let genesis: Genesis = serde_json::from_slice(&fs::read(path)?)?;
+            genesis.validate()?;
+            Ok(genesis)
+        }
+
+        /// Writes the genesis to a new file at `path`, never overwriting an existing one.
+        pub fn write(&self, path: impl AsRef<Path>) -> Result<(), GenesisError> {
+            let mut file = OpenOptions::new().write(true).create_new(true).open(&path).map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => GenesisError::Exists(path.as_ref().to_path_buf()),
+                _ => e.into(),
+            })?;
+            file.write_all(&serde_json::to_vec_pretty(self)?)?;
+            Ok(())
+        }
+
+        /// Hex encoded SHA-256 of the genesis, which is the hash of its block. Operators
+        /// compare it to make sure they share the same genesis.
+        pub fn hash(&self) -> String {
+            let json = serde_json::to_string(self).expect("a genesis always serializes");
+            format!("{:x}", Sha256::digest(json.as_bytes()))
+        }
+
+        /// Builds the genesis block, holding a transaction from the zero wallet for every
+        /// allocated coin. Coins are named after the network, the address and their rank,
+        /// so they are the same on every node. The block belongs to the network, so the
+        /// chain does too.
+        pub fn block(&self) -> Block {
+            let data: String = self
+                .allocations
+                .iter()
+                .flat_map(|allocation| {
+                    let receiver = general_purpose::STANDARD.decode(&allocation.address).unwrap_or_default();
+                    (0..allocation.coins).map(move |rank| {
+                        let coin = Token::from(Sha256::digest(format!("{}:{}:{}", self.network, allocation.address, rank)));
+                        let mut transaction = Transaction::new(ZERO_WALLET_PK.to_vec(), receiver.clone(), vec![coin]);
+                        transaction.timestamp = self.timestamp;
+                        let wire: String = transaction.into();
+                        wire
+                    })
+                })
+                .collect();
+            let mut block = Block::new(0, "0".repeat(64), data, Some(self.hash()));
+            block.timestamp = self.timestamp;
+            block.network = self.network.parse().unwrap_or_default(); // Validated genesis always name valid networks.
+            block.state_root = ChainState::new().root_after(&block);
+            block
+        }
+
+        /// Returns a chain holding only the genesis block.
+        pub fn chain(&self) -> Chain {
+            Chain::from_genesis(self.block(), self.difficulty)
+        }
+
+        /// Checks `chain` starts at this genesis.
+        ///
+        /// # Returns
+        /// * `Result<(), GenesisError>` - `GenesisError::Mismatch` if its first block is
+        ///   another one, or claims the hash of this genesis without its allocations.
+        pub fn verify(&self, chain: &Chain) -> Result<(), GenesisError> {
+            let expected = self.block();
+            let matches = chain.get_block(0).filter(|first| {
+                first.hash == expected.hash
+                    && first.data == expected.data
+                    && first.timestamp == expected.timestamp
+                    && first.network == expected.network
+            });
+            if matches.is_none() {
+                let got = chain.get_block(0).map(|first| first.hash.clone()).unwrap_or_default();
+                return Err(GenesisError::Mismatch { expected: expected.hash, got });
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,91 @@
+pub mod network {
+
+    use std::{fmt, str::FromStr};
+
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use thiserror::Error;
+
+    pub const MAINNET: &str = "mainnet";
+    pub const TESTNET: &str = "testnet";
+    pub const DEVNET: &str = "devnet";
+    /// Longest network id, in bytes.
+    pub const MAX_NETWORK_ID_LEN: usize = 64;
+
+    #[derive(Error, Debug, PartialEq)]
+    pub enum NetworkIdError {
+        #[error("A network id cannot be empty.")]
+        Empty,
+        #[error("A network id is at most {MAX_NETWORK_ID_LEN} bytes long, got {0}.")]
+        TooLong(usize),
+        #[error("A network id is made of letters, digits, `-`, `_` and `.`, got `{0}`.")]
+        InvalidChar(char),
+    }
+
+    /// Names the network a node belongs to, such as `mainnet`, `testnet` or `devnet`.
+    ///
+    /// Nodes exchange it when they greet and handshake, and refuse to peer with nodes of
+    /// another network. Wallets sign it along with every transaction, so a transaction
+    /// signed for one network fails verification on any other. Nodes and wallets not told
+    /// otherwise are on `mainnet`.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct NetworkId(String);
+
+    impl NetworkId {
+        pub fn mainnet() -> Self {
+            NetworkId(MAINNET.to_string())
+        }
+
+        pub fn testnet() -> Self {
+            NetworkId(TESTNET.to_string())
+        }
+
+        pub fn devnet() -> Self {
+            NetworkId(DEVNET.to_string())
+        }
+
+        pub fn as_str(&self) -> &str {
+            &self.0
+        }
+    }
+
+    impl Default for NetworkId {
+        fn default() -> Self {
+            NetworkId::mainnet()
+        }
+    }
+
+    impl FromStr for NetworkId {
+        type Err = NetworkIdError;
+        fn from_str(id: &str) -> Result<Self, Self::Err> {
+            if id.is_empty() {
+                return Err(NetworkIdError::Empty);
+            }
+            if id.len() > MAX_NETWORK_ID_LEN {
+                return Err(NetworkIdError::TooLong(id.len()));
+            }
+            if let Some(c) = id.chars().find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_' | '.')) {
+                return Err(NetworkIdError::InvalidChar(c));
+            }
+            Ok(NetworkId(id.to_string()))
+        }
+    }
+
+    impl fmt::Display for NetworkId {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl Serialize for NetworkId {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.0)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for NetworkId {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let string = String::deserialize(deserializer)?;
+            string.parse().map_err(de::Error::custom)
+        }
+    }
+}
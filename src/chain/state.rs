@@ -0,0 +1,283 @@
+pub mod state {
+
+    use crate::chain::block::block::block::Block;
+    use crate::token::token::token::Token;
+
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    /// Buckets the leaves of the state are spread over, by the first byte of the hash of
+    /// their key. The root is the Merkle root of the hashes of the buckets.
+    pub const STATE_BUCKETS: usize = 256;
+    /// Sibling hashes from a bucket up to the root.
+    pub const STATE_DEPTH: usize = STATE_BUCKETS.ilog2() as usize;
+
+    /// Namespace of the chain store the `StateSnapshot` of a pruned chain is saved to.
+    pub const STATE_NAMESPACE: &str = "state";
+
+    /// Values a block replaced in the state: its leaf keys with the value they had before
+    /// it, oldest first.
+    pub type StateUndo = Vec<(String, Option<String>)>;
+
+    /// Leaf key of the owner of `coin`, whose value is the hex encoded public key of the
+    /// receiver of its last transaction.
+    pub fn coin_key(coin: &Token) -> String {
+        format!("coin:{}", coin)
+    }
+
+    /// Leaf key of the records written under `key`, whose value is the id of the last of
+    /// them, deletions included. See `Record::id`.
+    pub fn record_key(key: &str) -> String {
+        format!("record:{}", key)
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn bucket_of(key: &str) -> usize {
+        Sha256::digest(key.as_bytes())[0] as usize
+    }
+
+    fn leaf_hash(key: &str, value: &str) -> String {
+        format!("{:x}", Sha256::digest(format!("{}:{}:{}", key.len(), key, value)))
+    }
+
+    fn bucket_hash<'a>(leaves: impl IntoIterator<Item = (&'a String, &'a String)>) -> String {
+        let mut hasher = Sha256::new();
+        for (key, value) in leaves {
+            hasher.update(leaf_hash(key, value));
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn node_hash(left: &str, right: &str) -> String {
+        format!("{:x}", Sha256::digest(format!("{}{}", left, right)))
+    }
+
+    /// What the blocks of a chain add up to: who owns each coin, and the last record
+    /// written under each key. Kept up to date block by block, see `Chain::add_block`,
+    /// and committed to by the `state_root` of every block.
+    #[derive(Debug, Clone)]
+    pub struct ChainState {
+        buckets: Vec<BTreeMap<String, String>>,
+        /// Hash of each bucket, rehashed as its leaves change.
+        hashes: Vec<String>,
+        /// First root the blocks committed to that the state did not match. See
+        /// `ChainState::of`.
+        diverged: Option<Divergence>,
+    }
+
+    /// A block committing to another root than the one of the state it led to.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Divergence {
+        pub index: usize,
+        /// Root of the state the block led to.
+        pub expected: String,
+        /// Root the block committed to.
+        pub got: String,
+    }
+
+    /// The state of a chain once the block at `index` is applied, stored along with
+    /// chains pruned past it, which can no longer rebuild it. See `Chain::restore_state`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct StateSnapshot {
+        pub index: usize,
+        /// Every leaf of the state, in key order within their buckets.
+        pub leaves: Vec<StateLeaf>,
+    }
+
+    /// A leaf of the state.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct StateLeaf {
+        pub key: String,
+        pub value: String,
+    }
+
+    /// Proof that a leaf of the state holds a value, or none, under a state root. See
+    /// `ChainState::prove`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct StateProof {
+        pub key: String,
+        /// The value proved, `None` to prove the key is not in the state.
+        pub value: Option<String>,
+        /// Every leaf of the bucket of `key`, in key order.
+        pub bucket: Vec<StateLeaf>,
+        /// Hashes of the siblings of the bucket and its ancestors, bottom up.
+        pub path: Vec<String>,
+    }
+
+    impl StateProof {
+        /// Whether the proof holds under `root`, the `state_root` of a block.
+        pub fn verify(&self, root: &str) -> bool {
+            let position = bucket_of(&self.key);
+            let found = self.bucket.iter().find(|leaf| leaf.key == self.key).map(|leaf| &leaf.value);
+            if found != self.value.as_ref()
+                || self.path.len() != STATE_DEPTH
+                || self.bucket.iter().any(|leaf| bucket_of(&leaf.key) != position)
+            {
+                return false;
+            }
+            let mut hash = bucket_hash(self.bucket.iter().map(|leaf| (&leaf.key, &leaf.value)));
+            for (level, sibling) in self.path.iter().enumerate() {
+                hash = match (position >> level) % 2 {
+                    0 => node_hash(&hash, sibling),
+                    _ => node_hash(sibling, &hash),
+                };
+            }
+            hash == root
+        }
+    }
+
+    impl Default for ChainState {
+        fn default() -> Self {
+            ChainState::new()
+        }
+    }
+
+    impl ChainState {
+        /// The state before any block.
+        pub fn new() -> Self {
+            let empty = bucket_hash([]);
+            ChainState {
+                buckets: vec![BTreeMap::new(); STATE_BUCKETS],
+                hashes: vec![empty; STATE_BUCKETS],
+                diverged: None,
+            }
+        }
+
+        /// The state holding `leaves`, as taken by `leaves`.
+        pub fn from_leaves(leaves: Vec<StateLeaf>) -> Self {
+            let mut state = ChainState::new();
+            for leaf in leaves {
+                state.buckets[bucket_of(&leaf.key)].insert(leaf.key, leaf.value);
+            }
+            state.hashes = state.buckets.iter().map(|bucket| bucket_hash(bucket.iter())).collect();
+            state
+        }
+
+        /// Replays `blocks`, genesis first. Blocks stored pruned no longer hold what they
+        /// changed, so the state diverges from the chain at the first root it does not
+        /// match, and is only made whole again by a `StateSnapshot`.
+        pub fn of<'a>(blocks: impl IntoIterator<Item = &'a Block>) -> Self {
+            let mut state = ChainState::new();
+            for block in blocks {
+                state.apply(block);
+                if state.diverged.is_none() && !block.state_root.is_empty() && block.state_root != state.root() {
+                    state.diverged = Some(Divergence { index: block.index, expected: state.root(), got: block.state_root.clone() });
+                }
+            }
+            state
+        }
+
+        /// Whether the state holds every change of the chain, so the roots of new blocks
+        /// can be checked against it.
+        pub fn is_complete(&self) -> bool {
+            self.diverged.is_none()
+        }
+
+        /// Returns the first block whose root the state did not match, if any.
+        pub fn divergence(&self) -> Option<&Divergence> {
+            self.diverged.as_ref()
+        }
+
+        /// Returns every leaf of the state, to store it as a `StateSnapshot`.
+        pub fn leaves(&self) -> Vec<StateLeaf> {
+            self.buckets
+                .iter()
+                .flatten()
+                .map(|(key, value)| StateLeaf { key: key.clone(), value: value.clone() })
+                .collect()
+        }
+
+        /// Returns the value of the leaf `key`, see `coin_key` and `record_key`.
+        pub fn get(&self, key: &str) -> Option<&str> {
+            self.buckets[bucket_of(key)].get(key).map(String::as_str)
+        }
+
+        /// Applies the transactions and records of `block`.
+        ///
+        /// # Returns
+        /// * `StateUndo` - What `block` replaced, to undo it. See `undo`.
+        pub fn apply(&mut self, block: &Block) -> StateUndo {
+            let mut changes = vec![];
+            for transaction in block.get_transactions() {
+                let owner = hex(&transaction.receiver);
+                changes.extend(transaction.coins.iter().map(|coin| (coin_key(coin), owner.clone())));
+            }
+            changes.extend(block.records.iter().map(|record| (record_key(&record.key), record.id())));
+            changes
+                .into_iter()
+                .map(|(key, value)| {
+                    let previous = self.set(key.clone(), Some(value));
+                    (key, previous)
+                })
+                .collect()
+        }
+
+        /// Reverts what a block replaced, as returned by `apply`.
+        pub fn undo(&mut self, undo: StateUndo) {
+            for (key, previous) in undo.into_iter().rev() {
+                self.set(key, previous);
+            }
+        }
+
+        fn set(&mut self, key: String, value: Option<String>) -> Option<String> {
+            let position = bucket_of(&key);
+            let bucket = &mut self.buckets[position];
+            let previous = match value {
+                Some(value) => bucket.insert(key, value),
+                None => bucket.remove(&key),
+            };
+            self.hashes[position] = bucket_hash(bucket.iter());
+            previous
+        }
+
+        /// The hashes of every level of the tree over the buckets, the buckets first and
+        /// the root last.
+        fn levels(&self) -> Vec<Vec<String>> {
+            let mut levels = vec![self.hashes.clone()];
+            while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+                let parents = level.chunks(2).map(|pair| node_hash(&pair[0], &pair[1])).collect();
+                levels.push(parents);
+            }
+            levels
+        }
+
+        /// Commitment to the whole state: nodes holding the same coins and records have
+        /// the same root.
+        pub fn root(&self) -> String {
+            self.levels().pop().and_then(|mut root| root.pop()).unwrap_or_default()
+        }
+
+        /// Root of the state once `block` is applied, which `block` commits to.
+        pub fn root_after(&self, block: &Block) -> String {
+            let mut next = self.clone();
+            next.apply(block);
+            next.root()
+        }
+
+        /// Proves the value of the leaf `key`, or that there is none, under `root`.
+        pub fn prove(&self, key: &str) -> StateProof {
+            let position = bucket_of(key);
+            let bucket = self.buckets[position]
+                .iter()
+                .map(|(key, value)| StateLeaf { key: key.clone(), value: value.clone() })
+                .collect();
+            let levels = self.levels();
+            let path = levels[..levels.len() - 1]
+                .iter()
+                .enumerate()
+                .map(|(level, hashes)| hashes[(position >> level) ^ 1].clone())
+                .collect();
+            StateProof {
+                key: key.to_string(),
+                value: self.get(key).map(str::to_string),
+                bucket,
+                path,
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+pub mod clock {
+
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex, OnceLock,
+        },
+        time::Duration,
+    };
+
+    use thiserror::Error;
+
+    /// Future returned by `Clock::sleep`.
+    pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    /// Returned by `timeout` when the future did not complete in time.
+    #[derive(Error, Debug, Clone, Copy, PartialEq)]
+    #[error("Deadline elapsed.")]
+    pub struct Elapsed;
+
+    /// Tells the time. Blocks, transactions, records and nodes only read the time and wait
+    /// through the installed clock, so a `MockClock` can stand in for the system one and
+    /// time-dependent logic can be checked without waiting for real.
+    pub trait Clock: Send + Sync {
+        /// The current time, in seconds since the UNIX epoch.
+        fn now(&self) -> u64;
+
+        /// The current time, in milliseconds since the UNIX epoch, for deadlines shorter
+        /// than a second. Only as precise as `now` unless overridden.
+        fn now_millis(&self) -> u64 {
+            self.now() * 1000
+        }
+
+        /// Waits for `duration`.
+        fn sleep(&self, duration: Duration) -> SleepFuture;
+    }
+
+    /// The time of the system, and real waits. Read through `chrono`, which asks the
+    /// browser for it on wasm32-unknown-unknown, where `SystemTime` is not available.
+    pub struct SystemClock;
+
+    impl Clock for SystemClock {
+        fn now(&self) -> u64 {
+            chrono::Utc::now().timestamp() as u64
+        }
+
+        fn now_millis(&self) -> u64 {
+            chrono::Utc::now().timestamp_millis() as u64
+        }
+
+        fn sleep(&self, duration: Duration) -> SleepFuture {
+            Box::pin(tokio::time::sleep(duration))
+        }
+    }
+
+    /// A clock that only moves when told to. Sleeping on it moves it forward by the
+    /// duration slept, right away.
+    #[derive(Clone, Default)]
+    pub struct MockClock {
+        /// Milliseconds since the UNIX epoch.
+        millis: Arc<AtomicU64>,
+    }
+
+    impl MockClock {
+        /// Returns a clock reading `now`, in seconds since the UNIX epoch.
+        pub fn new(now: u64) -> Self {
+            let clock = MockClock::default();
+            clock.set(now);
+            clock
+        }
+
+        /// Sets the time to `now`, in seconds since the UNIX epoch.
+        pub fn set(&self, now: u64) {
+            self.millis.store(now * 1000, Ordering::Relaxed);
+        }
+
+        /// Moves the time forward by `duration`.
+        pub fn advance(&self, duration: Duration) {
+            self.millis.fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> u64 {
+            self.millis.load(Ordering::Relaxed) / 1000
+        }
+
+        fn now_millis(&self) -> u64 {
+            self.millis.load(Ordering::Relaxed)
+        }
+
+        fn sleep(&self, duration: Duration) -> SleepFuture {
+            self.advance(duration);
+            Box::pin(tokio::task::yield_now())
+        }
+    }
+
+    /// The clock time is read from, `None` for the system clock.
+    static CLOCK: OnceLock<Mutex<Option<Arc<dyn Clock>>>> = OnceLock::new();
+
+    fn clock() -> &'static Mutex<Option<Arc<dyn Clock>>> {
+        CLOCK.get_or_init(|| Mutex::new(None))
+    }
+
+    /// Makes `now` and `sleep` use `clock` from now on, or the system clock again if `None`.
+    pub fn install(clock_: Option<Arc<dyn Clock>>) {
+        *clock().lock().unwrap() = clock_;
+    }
+
+    /// Puts the clock installed before it back once dropped. See `install_scoped`.
+    pub struct ClockGuard {
+        previous: Option<Arc<dyn Clock>>,
+    }
+
+    impl Drop for ClockGuard {
+        fn drop(&mut self) {
+            install(self.previous.take());
+        }
+    }
+
+    /// Installs `clock_` until the returned guard is dropped, even if the caller panics
+    /// in between, so a failed test does not leave its clock to the ones after it.
+    pub fn install_scoped(clock_: Arc<dyn Clock>) -> ClockGuard {
+        let previous = clock().lock().unwrap().replace(clock_);
+        ClockGuard { previous }
+    }
+
+    /// The current time on the installed clock, in seconds since the UNIX epoch.
+    pub fn now() -> u64 {
+        let installed = clock().lock().unwrap().clone();
+        match installed {
+            Some(clock) => clock.now(),
+            None => SystemClock.now(),
+        }
+    }
+
+    /// The current time on the installed clock, in milliseconds since the UNIX epoch.
+    pub fn now_millis() -> u64 {
+        let installed = clock().lock().unwrap().clone();
+        match installed {
+            Some(clock) => clock.now_millis(),
+            None => SystemClock.now_millis(),
+        }
+    }
+
+    /// Time left on the installed clock until `deadline`, in milliseconds since the UNIX
+    /// epoch, zero once it passed.
+    pub fn until(deadline: u64) -> Duration {
+        Duration::from_millis(deadline.saturating_sub(now_millis()))
+    }
+
+    /// Waits for `duration` on the installed clock.
+    pub async fn sleep(duration: Duration) {
+        let installed = clock().lock().unwrap().clone();
+        match installed {
+            Some(clock) => clock.sleep(duration).await,
+            None => SystemClock.sleep(duration).await,
+        }
+    }
+
+    /// Waits for `future` to complete, for up to `duration` on the installed clock.
+    ///
+    /// # Returns
+    /// * `Result<F::Output, Elapsed>` - The output of `future`, `Elapsed` if `duration`
+    ///   passed first.
+    pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+        tokio::select! {
+            biased;
+            output = future => Ok(output),
+            _ = sleep(duration) => Err(Elapsed),
+        }
+    }
+}
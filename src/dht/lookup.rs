@@ -0,0 +1,192 @@
+pub mod lookup {
+
+    use crate::dht::routing::routing::{Contact, DhtKey, BUCKET_SIZE};
+    use crate::clock::clock::clock;
+    use crate::node::decode::decode;
+    use crate::node::protocol::protocol::ProtocolMsg;
+    use crate::node::transport::transport::Transport;
+
+    use std::{
+        collections::{HashMap, HashSet},
+        io::Error as IOError,
+    };
+
+    use serde::{Deserialize, Serialize};
+    use thiserror::Error;
+    use uuid::Uuid;
+    use tracing::debug;
+
+    /// Queries sent in parallel per lookup round (Kademlia's `alpha`).
+    pub const ALPHA: usize = 3;
+    /// Time (in milliseconds) to wait for the answers of one lookup round.
+    pub const QUERY_TIMEOUT: u64 = 1000;
+    /// Rounds after which a lookup gives up.
+    pub const MAX_ROUNDS: usize = 8;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum DhtError {
+        #[error("No known contacts to start the lookup from.")]
+        NoContacts,
+        #[error("No peer holds {0}.")]
+        NotFound(DhtKey),
+        #[error(transparent)]
+        IOError(IOError),
+    }
+
+    /// Payload of a `ProtocolMsg::DhtFind` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct FindRequest {
+        pub lookup_id: Uuid,
+        pub target: DhtKey,
+    }
+
+    /// Payload of a `ProtocolMsg::DhtFound` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct FindResponse {
+        pub lookup_id: Uuid,
+        /// Peers known to hold the target.
+        pub providers: Vec<Contact>,
+        /// Contacts closer to the target than the one answering, as far as it knows.
+        pub closer: Vec<Contact>,
+    }
+
+    /// Payload of a `ProtocolMsg::DhtProvide` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct ProvideRequest {
+        pub target: DhtKey,
+        pub provider: Contact,
+    }
+
+    /// Remembers which peers announced holding which keys.
+    #[derive(Default)]
+    pub struct ProviderStore {
+        providers: HashMap<DhtKey, Vec<Contact>>,
+    }
+
+    impl ProviderStore {
+        pub fn new() -> Self {
+            ProviderStore::default()
+        }
+
+        /// Records a provider for `target`, keeping at most `BUCKET_SIZE` of the most recent.
+        pub fn add(&mut self, target: DhtKey, provider: Contact) {
+            let providers = self.providers.entry(target).or_default();
+            providers.retain(|known| known.id != provider.id);
+            providers.push(provider);
+            if providers.len() > BUCKET_SIZE {
+                providers.remove(0);
+            }
+        }
+
+        pub fn get(&self, target: &DhtKey) -> Vec<Contact> {
+            self.providers.get(target).cloned().unwrap_or_default()
+        }
+
+        /// Forgets every key provided by `id`.
+        pub fn remove_provider(&mut self, id: &Uuid) {
+            self.providers.values_mut().for_each(|providers| providers.retain(|provider| provider.id != *id));
+            self.providers.retain(|_, providers| !providers.is_empty());
+        }
+    }
+
+    /// What an iterative lookup learned.
+    #[derive(Default)]
+    pub struct LookupOutcome {
+        /// Peers holding the target.
+        pub providers: Vec<Contact>,
+        /// Contacts that answered, closest to the target first.
+        pub responsive: Vec<Contact>,
+        /// Contacts that did not answer in time and should be dropped from the routing table.
+        pub unresponsive: Vec<Uuid>,
+    }
+
+    /// Walks the DHT towards `target`, querying the `ALPHA` closest unqueried contacts each
+    /// round, until a provider is found or no closer contacts turn up.
+    ///
+    /// # Arguments
+    /// * `socket` - A socket dedicated to this lookup, so answers are not mixed with gossip.
+    /// * `seeds` - The closest contacts from the local routing table.
+    /// * `target` - The key being looked up.
+    /// * `stop_at_provider` - Whether to stop at the first round that finds providers.
+    pub async fn iterative_find(
+        socket: &dyn Transport,
+        seeds: Vec<Contact>,
+        target: DhtKey,
+        stop_at_provider: bool,
+    ) -> Result<LookupOutcome, DhtError> {
+        if seeds.is_empty() {
+            return Err(DhtError::NoContacts);
+        }
+        let lookup_id = Uuid::new_v4();
+        let mut shortlist = seeds;
+        let mut queried: HashSet<Uuid> = HashSet::new();
+        let mut outcome = LookupOutcome::default();
+
+        for _ in 0..MAX_ROUNDS {
+            shortlist.sort_by_key(|contact| contact.key().distance(&target));
+            shortlist.truncate(BUCKET_SIZE);
+            let round: Vec<Contact> = shortlist
+                .iter()
+                .filter(|contact| !queried.contains(&contact.id))
+                .take(ALPHA)
+                .cloned()
+                .collect();
+            if round.is_empty() {
+                break;
+            }
+
+            let mut buffer = vec![ProtocolMsg::DhtFind as u8];
+            buffer.extend_from_slice(serde_json::to_string(&FindRequest { lookup_id, target }).unwrap().as_bytes());
+            for contact in &round {
+                queried.insert(contact.id);
+                socket.send_to(&buffer, &contact.address).await?;
+            }
+
+            let mut pending: HashMap<String, Contact> = round
+                .into_iter()
+                .map(|contact| (contact.address.clone(), contact))
+                .collect();
+            let deadline = clock::now_millis() + QUERY_TIMEOUT;
+            let mut recv_buffer = vec![0u8; crate::node::gossip::gossip::MAX_DATAGRAM_SIZE];
+            while !pending.is_empty() {
+                let Ok(res) = clock::timeout(clock::until(deadline), socket.recv_from(&mut recv_buffer)).await else {
+                    break;
+                };
+                let (n_bytes, sender) = res?;
+                if n_bytes == 0 || recv_buffer[0] != ProtocolMsg::DhtFound as u8 {
+                    continue;
+                }
+                let Ok(response) = decode::message::<FindResponse>(&recv_buffer[1..n_bytes]) else {
+                    continue;
+                };
+                if response.lookup_id != lookup_id {
+                    continue;
+                }
+                let Some(contact) = pending.remove(&sender.to_string()) else {
+                    continue;
+                };
+                outcome.responsive.push(contact);
+                for provider in response.providers {
+                    if !outcome.providers.contains(&provider) {
+                        outcome.providers.push(provider);
+                    }
+                }
+                for closer in response.closer {
+                    if !queried.contains(&closer.id) && !shortlist.contains(&closer) {
+                        shortlist.push(closer);
+                    }
+                }
+            }
+            for (_, silent) in pending {
+                debug!("DHT contact {} did not answer", silent.address);
+                shortlist.retain(|contact| contact.id != silent.id);
+                outcome.unresponsive.push(silent.id);
+            }
+            if stop_at_provider && !outcome.providers.is_empty() {
+                break;
+            }
+        }
+        outcome.responsive.sort_by_key(|contact| contact.key().distance(&target));
+        Ok(outcome)
+    }
+}
@@ -58,7 +58,7 @@ pub mod peer {
                 .as_ref()) {
                     index += 1;
             }
-            let address = self.peers[index].node.get_address();
+            let address = self.peers[index].node.get_advertise_address();
             
             self.transport_object(object, address)?;
             Ok(())
@@ -0,0 +1,163 @@
+pub mod routing {
+
+    use std::fmt;
+
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    /// Bits in a `DhtKey`, and therefore buckets in a `RoutingTable`.
+    pub const KEY_BITS: usize = 256;
+    /// Contacts kept per bucket, and returned per lookup answer (Kademlia's `k`).
+    pub const BUCKET_SIZE: usize = 8;
+
+    /// A position in the DHT key space. Node ids, record keys and block hashes are all
+    /// mapped into it, and closeness is measured by XOR distance.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+    pub struct DhtKey(pub [u8; 32]);
+
+    impl DhtKey {
+        /// Hashes arbitrary bytes into the key space.
+        pub fn of(bytes: &[u8]) -> Self {
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&Sha256::digest(bytes));
+            DhtKey(key)
+        }
+
+        /// Key of a node, derived from its id.
+        pub fn of_node(id: &Uuid) -> Self {
+            DhtKey::of(id.as_bytes())
+        }
+
+        /// Key of a record, derived from its key.
+        pub fn of_record(key: &str) -> Self {
+            DhtKey::of(key.as_bytes())
+        }
+
+        /// Key of a block. Block hashes already are SHA-256 digests, so they are used as is
+        /// when they parse; anything else is hashed.
+        pub fn of_block(hash: &str) -> Self {
+            DhtKey::from_hex(hash).unwrap_or_else(|| DhtKey::of(hash.as_bytes()))
+        }
+
+        pub fn from_hex(hex: &str) -> Option<Self> {
+            if hex.len() != 64 || !hex.is_ascii() {
+                return None;
+            }
+            let mut key = [0u8; 32];
+            for (i, byte) in key.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+            }
+            Some(DhtKey(key))
+        }
+
+        pub fn distance(&self, other: &DhtKey) -> DhtKey {
+            let mut distance = [0u8; 32];
+            for (i, byte) in distance.iter_mut().enumerate() {
+                *byte = self.0[i] ^ other.0[i];
+            }
+            DhtKey(distance)
+        }
+
+        /// Index of the bucket `other` falls in, seen from `self`: the number of leading
+        /// bits both keys share. `None` if the keys are equal.
+        pub fn bucket_index(&self, other: &DhtKey) -> Option<usize> {
+            let distance = self.distance(other);
+            let leading_zeros = distance.0
+                .iter()
+                .position(|byte| *byte != 0)
+                .map(|i| i * 8 + distance.0[i].leading_zeros() as usize)?;
+            Some(leading_zeros)
+        }
+    }
+
+    impl fmt::Display for DhtKey {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for byte in self.0 {
+                write!(f, "{:02x}", byte)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Debug for DhtKey {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "DhtKey({})", self)
+        }
+    }
+
+    /// A node reachable through the DHT.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Contact {
+        pub id: Uuid,
+        pub address: String,
+    }
+
+    impl Contact {
+        pub fn key(&self) -> DhtKey {
+            DhtKey::of_node(&self.id)
+        }
+    }
+
+    /// Kademlia routing table: contacts grouped in buckets by the length of the prefix
+    /// they share with this node's key. Each bucket is ordered from least to most
+    /// recently seen.
+    pub struct RoutingTable {
+        own_key: DhtKey,
+        buckets: Vec<Vec<Contact>>,
+    }
+
+    impl RoutingTable {
+        pub fn new(own_id: &Uuid) -> Self {
+            RoutingTable {
+                own_key: DhtKey::of_node(own_id),
+                buckets: vec![vec![]; KEY_BITS],
+            }
+        }
+
+        /// Adds a contact, or marks it as the most recently seen of its bucket. When the
+        /// bucket is full the new contact is ignored: long-lived contacts are the most
+        /// likely to stay online, and unresponsive ones get removed instead.
+        ///
+        /// # Returns
+        /// `true` if the contact is in the table afterwards.
+        pub fn insert(&mut self, contact: Contact) -> bool {
+            let Some(index) = self.own_key.bucket_index(&contact.key()) else {
+                return false; // That is us.
+            };
+            let bucket = &mut self.buckets[index];
+            if let Some(position) = bucket.iter().position(|known| known.id == contact.id) {
+                bucket.remove(position);
+                bucket.push(contact);
+                return true;
+            }
+            if bucket.len() >= BUCKET_SIZE {
+                return false;
+            }
+            bucket.push(contact);
+            true
+        }
+
+        pub fn remove(&mut self, id: &Uuid) {
+            if let Some(index) = self.own_key.bucket_index(&DhtKey::of_node(id)) {
+                self.buckets[index].retain(|contact| contact.id != *id);
+            }
+        }
+
+        /// Returns up to `n` known contacts, closest to `target` first.
+        pub fn closest(&self, target: &DhtKey, n: usize) -> Vec<Contact> {
+            let mut contacts: Vec<Contact> = self.buckets.iter().flatten().cloned().collect();
+            contacts.sort_by_key(|contact| contact.key().distance(target));
+            contacts.truncate(n);
+            contacts
+        }
+
+        pub fn len(&self) -> usize {
+            self.buckets.iter().map(|bucket| bucket.len()).sum()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+}
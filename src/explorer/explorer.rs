@@ -0,0 +1,166 @@
+pub mod explorer {
+
+    use crate::{
+        chain::{
+            block::block::block::Block,
+            chain::chain::{Chain, KeyPage},
+        },
+        explorer::memory_index::memory_index::MemoryIndex,
+        record::record::record::Record,
+        store::store::store::StoreError,
+        transaction::transaction::transaction::Transaction,
+    };
+    #[cfg(feature = "sqlite")]
+    use crate::explorer::sqlite_index::sqlite_index::SqliteIndex;
+
+    #[cfg(feature = "sqlite")]
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use tokio::sync::watch;
+
+    /// A slice of the results of a query.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Page {
+        /// Results skipped.
+        pub offset: usize,
+        /// Most results returned.
+        pub limit: usize,
+    }
+
+    /// Results of a query for a `Page`, newest block first, each with the height of the
+    /// block holding it.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Paged<T> {
+        pub items: Vec<(T, usize)>,
+        /// Results of the query across every page.
+        pub total: usize,
+    }
+
+    /// Secondary indices over a chain: the transactions sent or received by an address,
+    /// the records written under a key and the height of a block by its hash.
+    pub trait Index: Send {
+        /// Hash of the last block indexed, `None` before the first update.
+        fn tip(&self) -> Result<Option<String>, StoreError>;
+
+        /// Indexes the blocks of `chain` not indexed yet. If the chain no longer holds the
+        /// last block indexed, e.g. after a reorganization, what it does not share with
+        /// the index is indexed again, or everything.
+        fn update(&mut self, chain: &Chain) -> Result<(), StoreError>;
+
+        /// Returns the transactions sent or received by the public key `address`.
+        fn transactions_of(&self, address: &[u8], page: Page) -> Result<Paged<Transaction>, StoreError>;
+
+        /// Returns the records written under `key`, deletions included.
+        fn records_under(&self, key: &str, page: Page) -> Result<Paged<Record>, StoreError>;
+
+        /// Returns the keys records are written under, deleted ones aside, as
+        /// `Chain::list_keys` does.
+        fn keys(&self, prefix: &str, limit: usize, cursor: Option<&str>) -> Result<KeyPage, StoreError>;
+
+        /// Returns the height of the block whose hash is `hash`, `None` if none is indexed.
+        fn block_height(&self, hash: &str) -> Result<Option<usize>, StoreError>;
+
+        /// Rebuilds the index. Indices kept in memory are built from scratch anyway and do
+        /// nothing; the others may drop what they hold, to index it again on the next update.
+        ///
+        /// # Returns
+        /// The number of blocks reindexed.
+        fn reindex(&mut self) -> Result<usize, StoreError> {
+            Ok(0)
+        }
+    }
+
+    /// Where an `Explorer` keeps its indices.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum IndexKind {
+        /// In memory, built again every time the node starts.
+        Memory,
+        /// In an SQLite database file at `path`. Needs the `sqlite` feature.
+        #[cfg(feature = "sqlite")]
+        Sqlite { path: PathBuf },
+    }
+
+    impl IndexKind {
+        /// Reads `memory` or `sqlite:PATH`.
+        ///
+        /// # Returns
+        /// * `Result<IndexKind, String>` - The kind of index, or why `spec` names none.
+        pub fn parse(spec: &str) -> Result<Self, String> {
+            match spec.split_once(':') {
+                None if spec == "memory" => Ok(IndexKind::Memory),
+                #[cfg(feature = "sqlite")]
+                Some(("sqlite", path)) if !path.is_empty() => Ok(IndexKind::Sqlite { path: PathBuf::from(path) }),
+                #[cfg(not(feature = "sqlite"))]
+                Some(("sqlite", _)) => Err("SQLite indices need the `sqlite` feature".to_string()),
+                _ => Err(format!("expected `memory` or `sqlite:PATH`, got `{}`", spec)),
+            }
+        }
+
+        /// Opens an empty index, or the one saved at the path of an SQLite index.
+        pub fn open(&self) -> Result<Box<dyn Index>, StoreError> {
+            match self {
+                IndexKind::Memory => Ok(Box::new(MemoryIndex::new())),
+                #[cfg(feature = "sqlite")]
+                IndexKind::Sqlite { path } => Ok(Box::new(SqliteIndex::open(path)?)),
+            }
+        }
+    }
+
+    /// Answers queries on a node's chain from an `Index`, kept up to date with the chain
+    /// as queries come.
+    pub struct Explorer {
+        chain: watch::Receiver<Chain>,
+        index: Mutex<Box<dyn Index>>,
+    }
+
+    impl Explorer {
+        /// # Arguments
+        /// * `chain` - The node's chain. See `Node::watch_chain`.
+        /// * `index` - Where to keep the indices. See `IndexKind::open`.
+        pub fn new(chain: watch::Receiver<Chain>, index: Box<dyn Index>) -> Self {
+            Explorer {
+                chain,
+                index: Mutex::new(index),
+            }
+        }
+
+        /// Runs `query` on the index once it has caught up with the chain.
+        fn query<T>(&self, query: impl FnOnce(&dyn Index) -> Result<T, StoreError>) -> Result<T, StoreError> {
+            let mut index = self.index.lock().unwrap();
+            let tip = self.chain.borrow().get_last_block().hash;
+            if index.tip()?.as_deref() != Some(tip.as_str()) {
+                // Cloned so the node is not kept from updating its chain while indexing.
+                let chain = self.chain.borrow().clone();
+                index.update(&chain)?;
+            }
+            query(index.as_ref())
+        }
+
+        /// Returns the transactions sent or received by the public key `address`, newest first.
+        pub fn transactions_of(&self, address: &[u8], page: Page) -> Result<Paged<Transaction>, StoreError> {
+            self.query(|index| index.transactions_of(address, page))
+        }
+
+        /// Returns the records written under `key`, newest first, deletions included.
+        pub fn records_under(&self, key: &str, page: Page) -> Result<Paged<Record>, StoreError> {
+            self.query(|index| index.records_under(key, page))
+        }
+
+        /// Returns up to `limit` keys records are written under, deleted ones aside, starting
+        /// with `prefix` and past `cursor`. See `Chain::list_keys`.
+        pub fn list_keys(&self, prefix: &str, limit: usize, cursor: Option<&str>) -> Result<KeyPage, StoreError> {
+            self.query(|index| index.keys(prefix, limit, cursor))
+        }
+
+        /// Returns the block whose hash is `hash`, `None` if the chain holds none.
+        pub fn block_by_hash(&self, hash: &str) -> Result<Option<Block>, StoreError> {
+            let height = self.query(|index| index.block_height(hash))?;
+            let chain = self.chain.borrow();
+            Ok(height
+                .and_then(|height| chain.get_block(height))
+                .filter(|block| block.hash == hash)
+                .cloned())
+        }
+    }
+}
@@ -0,0 +1,98 @@
+pub mod memory_index {
+
+    use crate::{
+        chain::chain::chain::{Chain, KeyPage},
+        explorer::explorer::explorer::{Index, Page, Paged},
+        record::record::record::Record,
+        store::store::store::StoreError,
+        transaction::transaction::transaction::Transaction,
+    };
+
+    use std::{
+        collections::{BTreeSet, HashMap},
+        ops::Bound,
+    };
+
+    /// Index kept in memory. Entries are appended as blocks are indexed, so every list is
+    /// ordered oldest block first.
+    #[derive(Default)]
+    pub struct MemoryIndex {
+        by_address: HashMap<Vec<u8>, Vec<(Transaction, usize)>>,
+        by_key: HashMap<String, Vec<(Record, usize)>>,
+        /// Keys whose last record is not a deletion, sorted to page through them.
+        live_keys: BTreeSet<String>,
+        by_hash: HashMap<String, usize>,
+        /// Height and hash of the last block indexed.
+        tip: Option<(usize, String)>,
+    }
+
+    impl MemoryIndex {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    /// Returns a page of `entries`, read from the newest.
+    fn page_of<T: Clone>(entries: Option<&Vec<(T, usize)>>, page: Page) -> Paged<T> {
+        let entries = entries.map(Vec::as_slice).unwrap_or_default();
+        Paged {
+            items: entries.iter().rev().skip(page.offset).take(page.limit).cloned().collect(),
+            total: entries.len(),
+        }
+    }
+
+    impl Index for MemoryIndex {
+        fn tip(&self) -> Result<Option<String>, StoreError> {
+            Ok(self.tip.as_ref().map(|(_, hash)| hash.clone()))
+        }
+
+        fn update(&mut self, chain: &Chain) -> Result<(), StoreError> {
+            let next = match &self.tip {
+                Some((height, hash)) if chain.get_block(*height).is_some_and(|block| block.hash == *hash) => height + 1,
+                _ => {
+                    *self = MemoryIndex::new();
+                    0
+                },
+            };
+            for block in chain.blocks_from(next) {
+                for transaction in block.get_transactions() {
+                    let mut parties = vec![transaction.sender.clone()];
+                    if transaction.receiver != transaction.sender {
+                        parties.push(transaction.receiver.clone());
+                    }
+                    for party in parties {
+                        self.by_address.entry(party).or_default().push((transaction.clone(), block.index));
+                    }
+                }
+                for record in &block.records {
+                    self.by_key.entry(record.key.clone()).or_default().push((record.clone(), block.index));
+                    match record.is_deletion() {
+                        true => self.live_keys.remove(&record.key),
+                        false => self.live_keys.insert(record.key.clone()),
+                    };
+                }
+                self.by_hash.insert(block.hash.clone(), block.index);
+                self.tip = Some((block.index, block.hash.clone()));
+            }
+            Ok(())
+        }
+
+        fn transactions_of(&self, address: &[u8], page: Page) -> Result<Paged<Transaction>, StoreError> {
+            Ok(page_of(self.by_address.get(address), page))
+        }
+
+        fn records_under(&self, key: &str, page: Page) -> Result<Paged<Record>, StoreError> {
+            Ok(page_of(self.by_key.get(key), page))
+        }
+
+        fn keys(&self, prefix: &str, limit: usize, cursor: Option<&str>) -> Result<KeyPage, StoreError> {
+            let start = cursor.map_or(prefix, |cursor| cursor.max(prefix));
+            let keys = self.live_keys.range::<str, _>((Bound::Included(start), Bound::Unbounded)).map(String::as_str);
+            Ok(KeyPage::of(keys, prefix, limit, cursor))
+        }
+
+        fn block_height(&self, hash: &str) -> Result<Option<usize>, StoreError> {
+            Ok(self.by_hash.get(hash).copied())
+        }
+    }
+}
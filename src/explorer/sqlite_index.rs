@@ -0,0 +1,85 @@
+pub mod sqlite_index {
+
+    use crate::{
+        chain::{
+            block::block::block::Block,
+            chain::chain::{Chain, KeyPage},
+        },
+        explorer::explorer::explorer::{Index, Page, Paged},
+        record::record::record::Record,
+        store::{
+            engine::engine::Engine,
+            sqlite_engine::sqlite_engine::SqliteEngine,
+            store::store::StoreError,
+        },
+        transaction::transaction::transaction::Transaction,
+    };
+
+    use std::path::Path;
+
+    /// Index kept in the tables of an `SqliteEngine`, so it survives restarts. Only the
+    /// blocks the tables do not hold yet are written when the chain grows, and the ones
+    /// past the fork when it reorganizes. The chain itself is not kept.
+    pub struct SqliteIndex {
+        engine: SqliteEngine,
+    }
+
+    impl SqliteIndex {
+        /// Opens the database at `path`, creating it if it does not exist.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            Ok(SqliteIndex { engine: SqliteEngine::open(path)? })
+        }
+    }
+
+    impl Index for SqliteIndex {
+        fn tip(&self) -> Result<Option<String>, StoreError> {
+            self.engine.tip_hash()
+        }
+
+        fn update(&mut self, chain: &Chain) -> Result<(), StoreError> {
+            // Walks back to the last block of the chain the tables hold: its previous tip,
+            // unless the chain reorganized.
+            let mut next = None;
+            if self.engine.tip_hash()?.is_some() {
+                for block in chain.blocks().iter().rev() {
+                    if self.engine.block_height(&block.hash)? == Some(block.index) {
+                        next = Some(block.index + 1);
+                        break;
+                    }
+                }
+            }
+            if next.is_none() {
+                self.engine.delete()?;
+            }
+            let blocks: Vec<Block> = chain.blocks_from(next.unwrap_or(0)).cloned().collect();
+            self.engine.index_blocks(&blocks)
+        }
+
+        fn transactions_of(&self, address: &[u8], page: Page) -> Result<Paged<Transaction>, StoreError> {
+            let (items, total) = self.engine.transactions_page(address, page.offset, page.limit)?;
+            Ok(Paged { items, total })
+        }
+
+        fn records_under(&self, key: &str, page: Page) -> Result<Paged<Record>, StoreError> {
+            let (items, total) = self.engine.records_page(key, page.offset, page.limit)?;
+            Ok(Paged { items, total })
+        }
+
+        fn keys(&self, prefix: &str, limit: usize, cursor: Option<&str>) -> Result<KeyPage, StoreError> {
+            let keys = self.engine.live_keys(prefix, limit.saturating_add(1), cursor)?;
+            Ok(KeyPage::of(keys.iter().map(String::as_str), prefix, limit, cursor))
+        }
+
+        fn block_height(&self, hash: &str) -> Result<Option<usize>, StoreError> {
+            self.engine.block_height(hash)
+        }
+
+        /// Drops every block indexed, since the chain they came from is not kept: the next
+        /// update indexes the whole chain again.
+        fn reindex(&mut self) -> Result<usize, StoreError> {
+            let dropped = self.engine.block_count()?;
+            self.engine.delete()?;
+            Ok(dropped)
+        }
+    }
+}
@@ -0,0 +1,163 @@
+pub mod chain {
+    pub mod chain;
+    pub mod genesis;
+    pub mod network;
+    pub mod config;
+    pub mod state;
+    pub mod block {
+        pub mod block;
+        pub mod entry;
+    }
+}
+
+pub mod clock {
+    pub mod clock;
+}
+
+pub mod miner {
+    pub mod miner;
+}
+
+pub mod wallet {
+    pub mod wallet;
+}
+
+pub mod transaction {
+    pub mod transaction;
+    pub mod batch;
+    pub mod validation;
+}
+
+pub mod record {
+    pub mod record;
+}
+
+pub mod token {
+    pub mod token;
+}
+
+#[cfg(feature = "node")]
+pub mod node {
+    pub mod node;
+    pub mod gossip;
+    pub mod fragment;
+    pub mod frame;
+    pub mod decode;
+    pub mod listener;
+    pub mod outbound;
+    pub mod rpc;
+    pub mod transport;
+    pub mod simnet;
+    pub mod secure;
+    pub mod identity;
+    pub mod reputation;
+    pub mod ratelimit;
+    pub mod bandwidth;
+    pub mod admission;
+    pub mod metrics;
+    pub mod event;
+    pub mod config;
+    pub mod registry;
+    pub mod bootstrap;
+    pub mod sync;
+    pub mod verifier;
+    pub mod relay;
+    pub mod submit;
+    pub mod neighbour;
+    pub mod protocol;
+    pub mod status;
+    pub mod mempool;
+    pub mod mining;
+    pub mod filter;
+    pub mod pool;
+    pub mod peer_log;
+    pub mod receiver;
+    pub mod reply;
+    pub mod theme;
+}
+
+#[cfg(feature = "node")]
+pub mod store {
+    pub mod store;
+    pub mod engine;
+    pub mod file_engine;
+    pub mod memory_engine;
+    pub mod encrypted_engine;
+    pub mod wal;
+    pub mod snapshot;
+    pub mod codec;
+    pub mod persistence;
+    #[cfg(feature = "sled")]
+    pub mod sled_engine;
+    #[cfg(feature = "sqlite")]
+    pub mod sqlite_engine;
+}
+
+#[cfg(feature = "node")]
+pub mod explorer {
+    pub mod explorer;
+    pub mod memory_index;
+    #[cfg(feature = "sqlite")]
+    pub mod sqlite_index;
+}
+
+#[cfg(feature = "node")]
+pub mod dht {
+    pub mod routing;
+    pub mod lookup;
+    //pub mod peer;
+}
+
+#[cfg(feature = "node")]
+pub mod app {
+    pub mod metrics_server;
+    pub mod rest;
+    pub mod cli;
+    pub mod client;
+    pub mod devnet;
+    pub mod logging;
+    pub mod signer;
+    pub mod operator;
+}
+
+//mod object {
+//    pub mod object;
+//}
+
+pub mod bench {
+    pub mod bench;
+}
+
+#[cfg(feature = "scenarios")]
+pub mod test {
+    pub mod test_batch;
+    pub mod test_checkpoint;
+    pub mod test_clock;
+    pub mod test_core;
+    pub mod test_crash;
+    pub mod test_decode;
+    pub mod test_filter;
+    pub mod test_fork;
+    pub mod test_gossip;
+    pub mod test_identity;
+    pub mod test_keys;
+    pub mod test_mint;
+    pub mod test_pool;
+    pub mod test_protocol;
+    pub mod test_range;
+    pub mod test_peer_log;
+    pub mod test_record;
+    pub mod test_sim;
+    pub mod test_state;
+    pub mod test_upgrade;
+    pub mod test_verifier;
+    //pub mod test_peer;
+}
+
+
+#[cfg(feature = "node")]
+use crate::miner::miner::miner::Miner as Miner;
+#[cfg(feature = "node")]
+use crate::chain::chain::chain::Chain as Chain;
+use crate::wallet::wallet::wallet::Wallet as Wallet;
+use crate::transaction::transaction::transaction::Transaction as Transaction;
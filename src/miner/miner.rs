@@ -3,13 +3,23 @@ pub mod miner {
     use crate::chain::block::block::block::{
         self, 
         Block, 
-        InvalidTransactionErr
+        InvalidTransactionErr,
+        Upgrade
     };
     use crate::transaction::transaction::transaction::Transaction;
+    use crate::transaction::batch::batch::BatchTransaction;
+    use crate::record::record::record::{Record, RecordRules};
+    use crate::token::token::token::Token;
+    use crate::chain::network::network::NetworkId;
+    use crate::chain::state::state::ChainState;
+    use crate::chain::config::config::MintPolicy;
     use crate::Wallet;
 
     use std::fmt;
     use std::cmp;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
     use rand::{self, Rng};
     
     use thiserror::Error;
@@ -21,9 +31,13 @@ pub mod miner {
     pub struct ChainMeta {
         pub len: usize,
         pub difficulty: usize,
-        pub blocks: Vec<Block>,
+        /// Blocks of the chain mined on, shared with it. See `Chain::shared_blocks`.
+        pub blocks: Arc<Vec<Block>>,
+        /// State of the chain, which the blocks mined commit to the next root of.
+        pub state: ChainState,
     }
 
+    #[derive(Clone)]
     pub struct MiningDigest {
         block: Block,
         nonce: u64,
@@ -67,7 +81,38 @@ pub mod miner {
         name: String,
         pub wallet: Wallet,
         pub transactions: Vec<Transaction>,
+        pub records: Vec<Record>,
+        pub batches: Vec<BatchTransaction>,
         pub chain_meta: Option<ChainMeta>,
+        threads: usize,
+        /// Network the prize transactions are signed for.
+        network: NetworkId,
+        /// Limits the queued records are held to.
+        record_rules: RecordRules,
+        /// Versions of the block format of the chain mined on, by height.
+        upgrades: Vec<Upgrade>,
+        /// Who may create coins on the chain mined on.
+        mint: MintPolicy,
+    }
+
+    /// Tries random nonces on `block` until the hash of the block of `network` and
+    /// `version` mined on it meets `difficulty`, or `stop` or any of `cancel` is raised.
+    ///
+    /// # Returns
+    /// The winning nonce and hash, or `None` if another thread found one first or mining
+    /// was cancelled.
+    fn search_nonce(block: &Block, network: &NetworkId, version: u32, difficulty: usize, stop: &AtomicBool, cancel: &[&AtomicBool]) -> Option<(u64, String)> {
+        let prefix = "0".repeat(difficulty);
+        let mut rng = rand::thread_rng();
+        while !stop.load(Ordering::Relaxed) && !cancel.iter().any(|cancel| cancel.load(Ordering::Relaxed)) {
+            let nonce = rng.gen_range(0..=u64::MAX);
+            let str_digest = block.mining_hash(nonce, network, version);
+            if str_digest.starts_with(&prefix) {
+                stop.store(true, Ordering::Relaxed);
+                return Some((nonce, str_digest));
+            }
+        }
+        None
     }
 
     
@@ -78,52 +123,164 @@ pub mod miner {
                 name,
                 wallet: Wallet::new(),
                 transactions: vec![],
+                records: vec![],
+                batches: vec![],
                 chain_meta: None,
+                threads: 1,
+                network: NetworkId::default(),
+                record_rules: RecordRules::default(),
+                upgrades: vec![],
+                mint: MintPolicy::default(),
             }
         }
 
+        /// Sets how many threads search for a nonce in parallel.
+        pub fn set_threads(&mut self, threads: usize) {
+            self.threads = threads.max(1);
+        }
+
+        /// Sets the network the prize transactions are signed for.
+        pub fn set_network(&mut self, network: NetworkId) {
+            self.network = network;
+        }
+
+        /// Sets the limits the queued records are held to, the ones of the chain mined on.
+        pub fn set_record_rules(&mut self, rules: RecordRules) {
+            self.record_rules = rules;
+        }
+
+        /// Sets the versions of the block format of the chain mined on, by height.
+        pub fn set_upgrades(&mut self, upgrades: Vec<Upgrade>) {
+            self.upgrades = upgrades;
+        }
+
+        /// Sets who may create coins on the chain mined on: whether blocks pay the miner a
+        /// prize, and whose transactions may create coins.
+        pub fn set_mint_policy(&mut self, mint: MintPolicy) {
+            self.mint = mint;
+        }
+
+        /// Version of the block following the last one of the chain mined on.
+        fn next_version(&self, chain_meta: &ChainMeta) -> u32 {
+            block::version_at(&self.upgrades, chain_meta.len + 1)
+        }
+
         pub fn get_name(&self) -> String {
             self.name.clone()
         }
 
-        pub fn mine(&mut self, mut block: Block) 
+        pub fn mine(&mut self, block: Block)
                 -> Result<MiningDigest, MiningError> {
+            let digest = self.mine_until(block, &[])?;
+            Ok(digest.expect("mining that is never cancelled only ends with a nonce"))
+        }
+
+        /// Mines like `mine`, giving up as soon as any of `cancel` is raised.
+        ///
+        /// # Returns
+        /// * `Result<Option<MiningDigest>, MiningError>` - The mined block, `None` if mining
+        ///   was cancelled first.
+        pub fn mine_until(&mut self, block: Block, cancel: &[&AtomicBool])
+                -> Result<Option<MiningDigest>, MiningError> {
             self.transactions = self.check_transactions();
+            self.records = self.check_records();
+            self.batches = self.check_batches();
+            let chain_meta = self.chain_meta.as_ref().ok_or(
+                MiningError::UninitializedChainMetaErr(UninitializedChainMetaErr)
+            )?;
+            let difficulty = chain_meta.difficulty;
+            let version = self.next_version(chain_meta);
+            let stop = AtomicBool::new(false);
+            let Some((nonce, str_digest)) = thread::scope(|scope| {
+                let searchers: Vec<_> = (0..self.threads)
+                    .map(|_| {
+                        let (block, network, stop) = (&block, &self.network, &stop);
+                        scope.spawn(move || search_nonce(block, network, version, difficulty, stop, cancel))
+                    })
+                    .collect();
+                searchers
+                    .into_iter()
+                    .filter_map(|searcher| searcher.join().unwrap())
+                    .next()
+            }) else {
+                return Ok(None); // Searchers only all give up when cancelled.
+            };
+            Ok(Some(self.reward(&block, nonce, str_digest)))
+        }
+
+        /// Completes the block following `previous` with a nonce found elsewhere, e.g. by
+        /// the workers of a pool, as `mine` does once it found one.
+        ///
+        /// # Arguments
+        /// * `previous` - The last block of the chain the nonce was searched on.
+        /// * `nonce` - The nonce, giving `previous` a hash meeting the difficulty.
+        pub fn solved(&mut self, previous: &Block, nonce: u64) -> Result<MiningDigest, MiningError> {
             let chain_meta = self.chain_meta.as_ref().ok_or(
                 MiningError::UninitializedChainMetaErr(UninitializedChainMetaErr)
             )?;
-            loop {
-                let mut rng = rand::thread_rng();
-                block.nonce  = rng.gen_range(0..=u64::MAX);
-                let str_digest = block.calculate_hash();
-                if str_digest.starts_with(&"0".repeat(chain_meta.difficulty)) {
-                    let prize_transaction = Transaction::new(
-                        ZERO_WALLET_PK.to_vec(), 
-                        self.wallet.get_pub_key(), 
-                        vec![str_digest.clone()],
-                    );
-                    let signed_prize = self.wallet.sign(prize_transaction);
-                    self.transactions.push(signed_prize); //TODO: this should be the 1st tx
-                    return Ok(
-                        MiningDigest::new(
-                            self.create_new_block(str_digest, block.hash.clone()), 
-                            block.nonce,
-                        )
-                    );
-                } else {
-                    continue;
-                }
+            let version = self.next_version(chain_meta);
+            self.transactions = self.check_transactions();
+            self.records = self.check_records();
+            self.batches = self.check_batches();
+            let str_digest = previous.mining_hash(nonce, &self.network, version);
+            Ok(self.reward(previous, nonce, str_digest))
+        }
+
+        /// Pays the prize of the block whose hash is `str_digest` to the miner's wallet, if
+        /// the mint policy allows it, and returns the block following `previous` with the
+        /// queued entries.
+        fn reward(&mut self, previous: &Block, nonce: u64, str_digest: String) -> MiningDigest {
+            if self.mint.coinbase {
+                let prize = Token::parse_legacy(&str_digest).expect("block hashes are hex SHA-256 digests");
+                let prize_transaction = Transaction::new(
+                    ZERO_WALLET_PK.to_vec(), 
+                    self.wallet.get_pub_key(), 
+                    vec![prize],
+                );
+                let signed_prize = self.wallet.sign(prize_transaction, &self.network);
+                self.transactions.push(signed_prize); //TODO: this should be the 1st tx
+                self.wallet.add_coin(prize);
             }
+            MiningDigest::new(
+                self.create_new_block(str_digest, previous.hash.clone()), 
+                nonce,
+            )
         }
 
-        pub fn set_chain_meta(&mut self, len: usize, difficulty: usize, blocks: Vec<Block>) {
+        /// Produces the block following `previous` as an authority of a proof-of-authority
+        /// chain: the queued transactions and records are sealed with the miner's wallet,
+        /// without searching a nonce or paying a prize.
+        pub fn seal(&mut self, previous: &Block) -> Result<MiningDigest, MiningError> {
+            if self.chain_meta.is_none() {
+                return Err(MiningError::UninitializedChainMetaErr(UninitializedChainMetaErr));
+            }
+            self.transactions = self.check_transactions();
+            self.records = self.check_records();
+            self.batches = self.check_batches();
+            let block = self.create_new_block(String::new(), previous.hash.clone());
+            Ok(MiningDigest::new(self.wallet.seal(block), 0))
+        }
+
+        /// Sets the chain to mine on. Pass `Chain::shared_blocks` rather than a copy of the
+        /// blocks when mining round after round on the same chain.
+        pub fn set_chain_meta(&mut self, len: usize, difficulty: usize, blocks: impl Into<Arc<Vec<Block>>>) {
+            let blocks = blocks.into();
             self.chain_meta = Some(ChainMeta {
                 len,
                 difficulty,
+                state: ChainState::of(blocks.iter()),
                 blocks,
             })
         }
 
+        /// Replaces the state `set_chain_meta` replayed out of the blocks with the one of the
+        /// chain, complete even if the chain pruned its blocks. See `Chain::state`.
+        pub fn set_chain_state(&mut self, state: ChainState) {
+            if let Some(chain_meta) = self.chain_meta.as_mut() {
+                chain_meta.state = state;
+            }
+        }
+
         pub fn set_transactions(&mut self, new_transactions: Vec<Transaction>) {
             self.transactions = new_transactions;
         }
@@ -134,36 +291,117 @@ pub mod miner {
         }
 
 
+        pub fn push_record(&mut self, record: Record) {
+            self.records.push(record);
+        }
+
+
+        pub fn push_batch(&mut self, batch: BatchTransaction) {
+            self.batches.push(batch);
+        }
+
+        /// Drops the queued records that can no longer be added to the chain, including the
+        /// ones over the record rules. See `set_record_rules`.
+        pub fn check_records(&self) -> Vec<Record> {
+            let chain_meta = self.chain_meta
+                .as_ref()
+                .ok_or(MiningError::UninitializedChainMetaErr(UninitializedChainMetaErr))
+                .unwrap();
+            self.records
+                .iter()
+                .filter_map(|record| block::check_record(record.clone(), &self.network, &self.record_rules, &chain_meta.blocks).ok())
+                .collect()
+        }
+
+        /// Drops the queued transactions that can no longer be added to the chain: the ones
+        /// spending coins their sender does not own, unless it is a mint authority creating
+        /// them, and the ones not raising the nonce of their sender, e.g. replays. The
+        /// others are ordered by nonce.
         pub fn check_transactions(&self) -> 
                 Vec<Transaction>  {
             let chain_meta = self.chain_meta
                 .as_ref()
                 .ok_or(MiningError::UninitializedChainMetaErr(UninitializedChainMetaErr))
                 .unwrap();
-            let filtered: Vec<Transaction> = self.transactions
-                .iter()
+            let mut last = block::last_nonces(chain_meta.blocks.iter());
+            let mut queued = self.transactions.clone();
+            queued.sort_by_key(|transaction| transaction.nonce);
+            let filtered: Vec<Transaction> = queued
+                .into_iter()
                 .filter_map(|transaction| { 
-                    block::check_transaction(transaction.clone(), &chain_meta.blocks).ok() 
-                }).collect();
+                    self.mint.check_transaction(transaction, &chain_meta.blocks).ok() 
+                })
+                .filter(|transaction| {
+                    let fresh = block::check_nonce(transaction, &last).is_ok();
+                    if fresh {
+                        last.insert(transaction.sender.clone(), transaction.nonce);
+                    }
+                    fresh
+                })
+                .collect();
             filtered
         }
 
+        /// Drops the queued batches that can no longer be added to the chain, like
+        /// `check_transactions` does: the malformed ones, the ones sending coins their
+        /// sender does not own, and the ones not raising the nonce of their sender past
+        /// the chain and the queued transactions. The others are ordered by nonce.
+        pub fn check_batches(&self) -> Vec<BatchTransaction> {
+            let chain_meta = self.chain_meta
+                .as_ref()
+                .ok_or(MiningError::UninitializedChainMetaErr(UninitializedChainMetaErr))
+                .unwrap();
+            let mut last = block::last_nonces(chain_meta.blocks.iter());
+            for transaction in &self.transactions {
+                last.insert(transaction.sender.clone(), transaction.nonce);
+            }
+            let mut queued = self.batches.clone();
+            queued.sort_by_key(|batch| batch.nonce);
+            queued
+                .into_iter()
+                .filter_map(|batch| block::check_batch(batch, &chain_meta.blocks).ok())
+                .filter(|batch| {
+                    let fresh = block::check_sender_nonce(&batch.sender, batch.nonce, &last).is_ok();
+                    if fresh {
+                        last.insert(batch.sender.clone(), batch.nonce);
+                    }
+                    fresh
+                })
+                .collect()
+        }
+
         pub fn create_new_block(&mut self, hash: String, previous_hash: String) -> Block { 
-            let index = self.chain_meta.clone().unwrap().len + 1; 
+            let chain_meta = self.chain_meta.as_ref().unwrap();
+            let index = chain_meta.len + 1; 
+            let state = chain_meta.state.clone();
             let cap = cmp::min(self.transactions.len(), block::MAX_TRANSACTIONS);
             let capped_transactions: Vec<Transaction> = self.transactions.drain(0..cap).collect();
             let encoded_transactions: Vec<String> = capped_transactions.iter().map(|transaction| {
                 transaction.clone().into()
             }).collect();
             let data = encoded_transactions.join("");
-            self.wallet.add_coin(hash.clone());
-            Block::new(index, previous_hash, data, Some(hash)) 
+            let cap = cmp::min(self.batches.len(), block::MAX_BATCHES);
+            let batches: Vec<BatchTransaction> = self.batches.drain(0..cap).collect();
+            let mut block = Block::new(index, previous_hash, data, Some(hash));
+            block.batches = batches;
+            // Records whose owner burned too little in this block wait for a later one.
+            let mut burned = block::burned(&block);
+            let (records, unpaid) = self.records.drain(..).partition(|record| {
+                block.records.len() < block::MAX_RECORDS
+                    && block::pay_record(record, &self.record_rules, &mut burned).is_ok()
+            });
+            block.records = records;
+            self.records = unpaid;
+            block.network = self.network.clone();
+            block.version = block::version_at(&self.upgrades, index);
+            block.state_root = state.root_after(&block);
+            block
         }
     }
 
     impl fmt::Display for Miner {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            let joint_coins = self.wallet.coins.join(",");
+            let joint_coins = self.wallet.coins.iter().map(Token::to_string).collect::<Vec<_>>().join(",");
             write!(f, "id: {}, name: {}, wallet: {}", self.id, self.name, joint_coins)
         }
     }
@@ -0,0 +1,113 @@
+pub mod admission {
+
+    use crate::clock::clock::clock;
+
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    /// Leading zero bits a ticket's hash needs by default. Takes tens of milliseconds to solve.
+    pub const ADMISSION_DIFFICULTY: u32 = 16;
+    /// Hardest challenge a node agrees to solve, so a tracker cannot keep it busy forever.
+    pub const MAX_ADMISSION_DIFFICULTY: u32 = 32;
+    /// Time (in seconds) a challenge stays valid. Tickets for the previous epoch are still accepted.
+    pub const CHALLENGE_EPOCH: u64 = 60;
+
+    /// Payload of a `ProtocolMsg::Challenge` message, sent by a tracker to a greeter without a valid ticket.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Challenge {
+        pub challenge: [u8; 32],
+        /// Leading zero bits the ticket's hash needs.
+        pub difficulty: u32,
+    }
+
+    /// Proof of work over a challenge and the id of the node greeting.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Ticket {
+        pub challenge: [u8; 32],
+        pub nonce: u64,
+    }
+
+    /// Hands out challenges and checks tickets. Challenges are derived from a secret, the
+    /// greeter's id and the current epoch, so a tracker keeps no state per greeter.
+    pub struct Gatekeeper {
+        secret: [u8; 32],
+        difficulty: u32,
+    }
+
+    impl Gatekeeper {
+        /// # Arguments
+        /// * `difficulty` - Leading zero bits a ticket's hash needs. Zero admits everyone.
+        pub fn new(difficulty: u32) -> Self {
+            Gatekeeper {
+                secret: rand::random(),
+                difficulty,
+            }
+        }
+
+        /// Whether greeters need a ticket at all.
+        pub fn enabled(&self) -> bool {
+            self.difficulty > 0
+        }
+
+        /// The challenge a greeter with id `id` has to solve in the current epoch.
+        pub fn challenge(&self, id: &Uuid) -> Challenge {
+            Challenge {
+                challenge: self.derive(id, epoch()),
+                difficulty: self.difficulty,
+            }
+        }
+
+        /// Checks that `ticket` solves a challenge handed to `id` in this epoch or the previous one.
+        pub fn admits(&self, id: &Uuid, ticket: &Ticket) -> bool {
+            let epoch = epoch();
+            let issued = ticket.challenge == self.derive(id, epoch)
+                || ticket.challenge == self.derive(id, epoch.saturating_sub(1));
+            issued && leading_zeros(&stamp(&ticket.challenge, id, ticket.nonce)) >= self.difficulty
+        }
+
+        fn derive(&self, id: &Uuid, epoch: u64) -> [u8; 32] {
+            let mut hasher = Sha256::new();
+            hasher.update(self.secret);
+            hasher.update(id.as_bytes());
+            hasher.update(epoch.to_be_bytes());
+            hasher.finalize().into()
+        }
+    }
+
+    /// Searches the nonce solving `challenge` for the node with id `id`.
+    ///
+    /// # Returns
+    /// * `Option<Ticket>` - The ticket, `None` if the challenge is over `MAX_ADMISSION_DIFFICULTY`.
+    pub fn solve(challenge: &Challenge, id: &Uuid) -> Option<Ticket> {
+        if challenge.difficulty > MAX_ADMISSION_DIFFICULTY {
+            return None;
+        }
+        (0..)
+            .find(|nonce| leading_zeros(&stamp(&challenge.challenge, id, *nonce)) >= challenge.difficulty)
+            .map(|nonce| Ticket { challenge: challenge.challenge, nonce })
+    }
+
+    fn stamp(challenge: &[u8; 32], id: &Uuid, nonce: u64) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(challenge);
+        hasher.update(id.as_bytes());
+        hasher.update(nonce.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    fn leading_zeros(hash: &[u8; 32]) -> u32 {
+        let mut zeros = 0;
+        for byte in hash {
+            zeros += byte.leading_zeros();
+            if *byte != 0 {
+                break;
+            }
+        }
+        zeros
+    }
+
+    fn epoch() -> u64 {
+        clock::now() / CHALLENGE_EPOCH
+    }
+}
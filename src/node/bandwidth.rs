@@ -0,0 +1,66 @@
+pub mod bandwidth {
+
+    use std::time::{Duration, Instant};
+
+    /// Length (in seconds) of the window an hourly cap applies to.
+    pub const HOUR: u64 = 60 * 60;
+    /// Length (in seconds) of the window a daily cap applies to.
+    pub const DAY: u64 = 24 * HOUR;
+
+    /// Bytes allowed within a fixed window, counted from the traffic total at its start.
+    struct Window {
+        cap: u64,
+        length: Duration,
+        started: Instant,
+        base: u64,
+    }
+
+    impl Window {
+        fn new(cap: u64, length: u64) -> Self {
+            Window {
+                cap,
+                length: Duration::new(length, 0),
+                started: Instant::now(),
+                base: 0,
+            }
+        }
+
+        fn exceeded(&mut self, total: u64) -> bool {
+            if self.started.elapsed() >= self.length {
+                self.started = Instant::now();
+                self.base = total;
+            }
+            total.saturating_sub(self.base) >= self.cap
+        }
+    }
+
+    /// Optional hourly and daily caps on the bytes a node sends and receives. Once one
+    /// is reached, low-priority gossip is suppressed until its window starts over.
+    pub struct BandwidthCaps {
+        hourly: Option<Window>,
+        daily: Option<Window>,
+    }
+
+    impl BandwidthCaps {
+        /// # Arguments
+        /// * `hourly` - Bytes allowed per hour, unlimited if `None`.
+        /// * `daily` - Bytes allowed per day, unlimited if `None`.
+        pub fn new(hourly: Option<u64>, daily: Option<u64>) -> Self {
+            BandwidthCaps {
+                hourly: hourly.map(|cap| Window::new(cap, HOUR)),
+                daily: daily.map(|cap| Window::new(cap, DAY)),
+            }
+        }
+
+        /// Whether a cap was reached in its current window.
+        ///
+        /// # Arguments
+        /// * `total` - Bytes sent and received since the node started.
+        pub fn exceeded(&mut self, total: u64) -> bool {
+            // Both windows are checked, so each rolls over on time.
+            let hourly = self.hourly.as_mut().is_some_and(|window| window.exceeded(total));
+            let daily = self.daily.as_mut().is_some_and(|window| window.exceeded(total));
+            hourly || daily
+        }
+    }
+}
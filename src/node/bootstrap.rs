@@ -0,0 +1,92 @@
+pub mod bootstrap {
+
+    use std::{collections::HashSet, time::Duration};
+
+    use rand::{seq::SliceRandom, Rng};
+    use tokio::net::lookup_host;
+    use tracing::debug;
+
+    /// Rounds of greeting every tracker and known peer before a node gives up entering
+    /// the network, unless configured otherwise.
+    pub const BOOTSTRAP_ATTEMPTS: u32 = 5;
+    /// Wait (in milliseconds) after the first failed round. Doubles every round.
+    pub const INITIAL_BACKOFF: u64 = 1000;
+    /// Longest wait (in milliseconds) between two rounds.
+    pub const MAX_BACKOFF: u64 = 30_000;
+
+    /// Resolves tracker entries into socket addresses, in random order. Entries may be
+    /// literal `ip:port` addresses or DNS seeds (`name:port`) resolving to several A/AAAA
+    /// records, each of which is a tracker. Entries that fail to resolve are skipped.
+    ///
+    /// # Arguments
+    /// * `trackers` - The configured tracker entries.
+    ///
+    /// # Returns
+    /// * `Vec<String>` - Every distinct tracker address found, shuffled.
+    pub async fn resolve_trackers(trackers: &[String]) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut resolved = vec![];
+        for tracker in trackers {
+            match lookup_host(tracker.as_str()).await {
+                Ok(addresses) => {
+                    for address in addresses {
+                        if seen.insert(address) {
+                            resolved.push(address.to_string());
+                        }
+                    }
+                },
+                Err(e) => debug!("Could not resolve tracker {}: {}", tracker, e),
+            }
+        }
+        resolved.shuffle(&mut rand::thread_rng());
+        resolved
+    }
+
+    /// Time to wait after the given failed round, doubling from `initial` up to `max` milliseconds.
+    pub fn backoff(attempt: u32, initial: u64, max: u64) -> Duration {
+        let millis = initial.saturating_mul(1u64 << attempt.min(16));
+        Duration::from_millis(millis.min(max))
+    }
+
+    /// Retry policy for the rounds of greetings a node sends while entering the network.
+    /// Waits between rounds back off exponentially, with jitter so nodes started together
+    /// do not hit the trackers in lockstep.
+    pub struct Dialer {
+        /// Rounds allowed, `None` to keep trying until connected.
+        attempts: Option<u32>,
+        initial_backoff: u64,
+        max_backoff: u64,
+        attempt: u32,
+    }
+
+    impl Dialer {
+        /// # Arguments
+        /// * `attempts` - Rounds allowed, `None` to keep trying until connected.
+        /// * `initial_backoff` - Wait (in milliseconds) after the first failed round.
+        /// * `max_backoff` - Longest wait (in milliseconds) between two rounds.
+        pub fn new(attempts: Option<u32>, initial_backoff: u64, max_backoff: u64) -> Self {
+            Dialer {
+                attempts,
+                initial_backoff,
+                max_backoff,
+                attempt: 0,
+            }
+        }
+
+        /// Counts a failed round.
+        ///
+        /// # Returns
+        /// * `Option<Duration>` - How long to wait before the next round, somewhere between
+        ///   half and all of the backoff. `None` once every round was used.
+        pub fn next_delay(&mut self) -> Option<Duration> {
+            let attempt = self.attempt;
+            self.attempt = self.attempt.saturating_add(1);
+            if self.attempts.is_some_and(|attempts| self.attempt >= attempts) {
+                return None;
+            }
+            let backoff = backoff(attempt, self.initial_backoff, self.max_backoff).as_millis() as u64;
+            let jittered = rand::thread_rng().gen_range(backoff / 2..=backoff);
+            Some(Duration::from_millis(jittered))
+        }
+    }
+}
@@ -0,0 +1,882 @@
+pub mod config {
+
+    use crate::chain::{
+        block::block::block::{Upgrade, BLOCK_VERSION},
+        chain::chain::{BlockCheckError, Chain},
+        config::config::{ChainConfig, Checkpoint, Consensus},
+        genesis::genesis::{Genesis, GenesisError},
+        network::network::{NetworkId, NetworkIdError},
+    };
+    use crate::node::{
+        gossip::gossip::{GOSSIP_INTERVAL, LISTEN_TIMEOUT, MAX_GOSSIP_INTERVAL},
+        ratelimit::ratelimit::{GLOBAL_RATE_LIMIT, PEER_RATE_LIMIT},
+        bootstrap::bootstrap::{BOOTSTRAP_ATTEMPTS, INITIAL_BACKOFF, MAX_BACKOFF},
+        admission::admission::{ADMISSION_DIFFICULTY, MAX_ADMISSION_DIFFICULTY},
+        outbound::outbound::PEER_SEND_RATE,
+        relay::relay::DEFAULT_MAX_HOPS,
+        identity::identity::IdentityError,
+        neighbour::neighbour::Role,
+        node::node::Node,
+        receiver::receiver::Receiver,
+        verifier::verifier::DEFAULT_AUDIT_INTERVAL,
+    };
+    use crate::record::record::record::RecordRules;
+    use crate::wallet::wallet::wallet::Wallet;
+    use crate::store::store::store::{Codec, FileEngine, Retention, Store, StoreError};
+    #[cfg(feature = "sled")]
+    use crate::store::store::store::SledEngine;
+    #[cfg(feature = "sqlite")]
+    use crate::store::store::store::SqliteEngine;
+
+    use std::{
+        fs,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+    };
+
+    use base64::{Engine as _, engine::general_purpose};
+    use serde::{Deserialize, Deserializer};
+    use thiserror::Error;
+    use tokio::sync::mpsc;
+    use zeroize::Zeroizing;
+
+    pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+    pub const DEFAULT_MINING_THREADS: usize = 1;
+    pub const DEFAULT_MAX_PEERS: usize = 64;
+    /// Capacity of the transaction channel created when no `Receiver` is supplied.
+    pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+    #[derive(Error, Debug)]
+    pub enum NodeConfigError {
+        #[error("Invalid address {0} - expected ip:port.")]
+        InvalidAddress(String),
+        #[error("Bound to the unspecified address {0} - set advertise_address to the address peers reach this node at.")]
+        UnspecifiedAdvertise(String),
+        #[error("Invalid tracker address {0} - expected ip:port or name:port.")]
+        InvalidTracker(String),
+        #[error("{0} must be greater than zero.")]
+        Zero(&'static str),
+        #[error("max_gossip_interval ({max}) must not be shorter than gossip_interval ({min}).")]
+        InvalidBackoff { min: u64, max: u64 },
+        #[error("max_dial_backoff ({max}) must not be shorter than dial_backoff ({min}).")]
+        InvalidDialBackoff { min: u64, max: u64 },
+        #[error("admission_difficulty ({0}) must not be over {max}.", max = MAX_ADMISSION_DIFFICULTY)]
+        InvalidDifficulty(u32),
+        #[error("The chain and the address book cannot share the store {0:?}.")]
+        SharedStore(StoreEngine),
+        #[error("Invalid compression level {0} - not supported by zstd.")]
+        InvalidCompression(i32),
+        #[error("wal_checkpoint_interval cannot be set along with store_key_file - the log is kept in clear.")]
+        EncryptedWal,
+        #[error("{0} cannot be set along with store_codec = \"bincode\" - it reads the chain store as JSON.")]
+        OpaqueCodec(&'static str),
+        #[error("Invalid checkpoint at block {0} - expected a single hash of 64 hex digits.")]
+        InvalidCheckpoint(usize),
+        #[error("A proof-of-authority chain needs at least one authority.")]
+        NoAuthorities,
+        #[error("Invalid authority {0} - expected a base64 encoded public key.")]
+        InvalidAuthority(String),
+        #[error("Miners of a proof-of-authority chain need authority_key, the wallet key they seal blocks with.")]
+        NoAuthorityKey,
+        #[error("{} does not hold a wallet key.", .0.display())]
+        InvalidAuthorityKey(PathBuf),
+        #[error("The authority key {0} is not one of the chain's authorities.")]
+        NotAnAuthority(String),
+        #[error("Invalid checkpoint publisher {0} - expected a base64 encoded public key.")]
+        InvalidPublisher(String),
+        #[error("The checkpoint key {0} is not one of the chain's checkpoint publishers.")]
+        NotAPublisher(String),
+        #[error("Invalid upgrade to version {0} - upgrades must raise the version, in increasing order of height.")]
+        InvalidUpgrade(u32),
+        #[error("Invalid mint authority {0} - expected a base64 encoded public key.")]
+        InvalidMintAuthority(String),
+        #[error("The genesis gives coins away, but the mint policy of the chain allows no premine.")]
+        Premine,
+        #[error("Pool coordinators must be miners of a proof-of-work chain.")]
+        InvalidCoordinator,
+        #[error("Invalid pool payout address {0} - expected a base64 encoded public key.")]
+        InvalidPayout(String),
+        #[error(transparent)]
+        StoreError(#[from] StoreError),
+        #[error("Invalid genesis: {0}")]
+        GenesisError(#[from] GenesisError),
+        #[error(transparent)]
+        IdentityError(#[from] IdentityError),
+        #[error("The genesis network {0} cannot be told to peers as a network id - {1}")]
+        InvalidNetwork(String, NetworkIdError),
+        #[error("network is {network} but the genesis belongs to {genesis}.")]
+        NetworkMismatch { network: NetworkId, genesis: String },
+        #[error("The chain store holds a chain ruled out by the chain rules - {0}. Restore it from a trusted snapshot.")]
+        RuledOut(BlockCheckError),
+        #[error(transparent)]
+        TomlError(#[from] toml::de::Error),
+    }
+
+    /// How a node talks to its neighbours.
+    #[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Transport {
+        #[default]
+        Udp,
+    }
+
+    /// Where a node persists its state.
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(tag = "engine", rename_all = "lowercase")]
+    pub enum StoreEngine {
+        /// A file, its directory being created if needed. Relative paths are relative to the
+        /// current working directory.
+        File {
+            #[serde(alias = "filename")]
+            path: PathBuf,
+            /// zstd compression level of the payloads written, uncompressed if unset.
+            #[serde(default)]
+            compression: Option<i32>,
+        },
+        /// An embedded sled database in the directory at `path`. Needs the `sled` feature.
+        #[cfg(feature = "sled")]
+        Sled { path: PathBuf },
+        /// An SQLite database file at `path`, with tables for blocks, transactions and
+        /// records. Needs the `sqlite` feature.
+        #[cfg(feature = "sqlite")]
+        Sqlite { path: PathBuf },
+        /// Kept in memory and lost when the node stops. Meant for tests and ephemeral nodes.
+        Memory,
+    }
+
+    impl StoreEngine {
+        /// Where the store keeps its data, `None` if it is not kept on disk.
+        pub fn path(&self) -> Option<&Path> {
+            match self {
+                StoreEngine::File { path, .. } => Some(path),
+                #[cfg(feature = "sled")]
+                StoreEngine::Sled { path } => Some(path),
+                #[cfg(feature = "sqlite")]
+                StoreEngine::Sqlite { path } => Some(path),
+                StoreEngine::Memory => None,
+            }
+        }
+
+        pub fn open(&self) -> Result<Store, StoreError> {
+            match self {
+                StoreEngine::File { path, compression } => {
+                    let engine = FileEngine::open(path)?.with_compression(*compression);
+                    Ok(Store::with_engine(Box::new(engine)))
+                },
+                #[cfg(feature = "sled")]
+                StoreEngine::Sled { path } => Ok(Store::with_engine(Box::new(SledEngine::open(path)?))),
+                #[cfg(feature = "sqlite")]
+                StoreEngine::Sqlite { path } => Ok(Store::with_engine(Box::new(SqliteEngine::open(path)?))),
+                StoreEngine::Memory => Ok(Store::in_memory()),
+            }
+        }
+    }
+
+    /// The part a node takes in a mining pool. See `Pool`.
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(tag = "role", rename_all = "lowercase")]
+    pub enum PoolConfig {
+        /// Hands out work to the workers that join rather than mining itself, and pays
+        /// them the prizes of the blocks they find. Only for miners of a proof-of-work chain.
+        Coordinator,
+        /// Searches nonces for a coordinator, which must be one of the node's neighbours.
+        Worker {
+            /// Address (ip:port) of the coordinator.
+            coordinator: String,
+            /// Base64 encoded public key of the wallet payouts go to.
+            payout: String,
+        },
+    }
+
+    /// Everything needed to start a `Node`. Every field has a default, so a TOML file
+    /// only needs to mention what it changes:
+    ///
+    /// ```toml
+    /// address = "0.0.0.0:8082"
+    /// advertise_address = "203.0.113.7:8082"
+    /// role = "miner"
+    /// trackers = ["127.0.0.1:8081"]
+    /// gossip_interval = 5
+    /// max_gossip_interval = 40
+    /// store_key_file = "/etc/humble/store.key"
+    /// genesis = "/etc/humble/genesis.json"
+    /// network = "testnet"
+    /// authority_key = "/etc/humble/authority.key"
+    /// checkpoint_key = "/etc/humble/checkpoint.key"
+    ///
+    /// [store]
+    /// engine = "file"
+    /// path = "data/peers.dat"
+    ///
+    /// [chain_store]
+    /// engine = "file"
+    /// path = "data/chain.dat"
+    /// compression = 3
+    ///
+    /// [chain_retention]
+    /// keep_full = 1000
+    /// interval = 100
+    ///
+    /// [pool]
+    /// role = "coordinator"
+    ///
+    /// [chain]
+    /// consensus = "poa"
+    /// authorities = ["BHx2...", "BNq0..."]
+    /// checkpoint_publishers = ["BKp7..."]
+    ///
+    /// [[chain.checkpoints]]
+    /// index = 1200
+    /// hash = "0000a3f1..."
+    /// ```
+    #[derive(Debug, Clone, Deserialize)]
+    #[serde(default)]
+    pub struct NodeConfig {
+        /// Address (ip:port) the node listens on.
+        pub address: String,
+        /// Address (ip:port) given out to neighbours, when they cannot reach the node at
+        /// `address`, such as behind NAT or in a container. Defaults to `address`.
+        pub advertise_address: Option<String>,
+        #[serde(deserialize_with = "deserialize_role")]
+        pub role: Role,
+        /// Trackers contacted when entering the network, as `ip:port` or as `name:port` DNS seeds.
+        pub trackers: Vec<String>,
+        /// Seconds between gossip rounds while the chain keeps changing.
+        pub gossip_interval: u64,
+        /// Seconds between gossip rounds once fully backed off. The interval doubles every
+        /// round the chain does not change, up to this value.
+        pub max_gossip_interval: u64,
+        /// Seconds spent listening for gossip per loop iteration.
+        pub listen_timeout: u64,
+        /// Neighbours gossiped to per round. Defaults to the square root of the neighbour count.
+        pub fanout: Option<usize>,
+        /// Maximum number of neighbours kept, `DEFAULT_MAX_PEERS` by default. Once reached,
+        /// new neighbours replace the worst scored ones.
+        pub max_peers: Option<usize>,
+        /// Times transactions and neighbours are forwarded past the node that first
+        /// relayed them, `DEFAULT_MAX_HOPS` by default. Each forward lowers what is left
+        /// by one, so they stop circulating after that many hops.
+        pub max_hops: u8,
+        pub transport: Transport,
+        /// Rounds of greetings sent to trackers and known peers when entering the network.
+        /// `None` keeps trying until connected.
+        pub dial_attempts: Option<u32>,
+        /// Milliseconds to wait after the first round that reached nobody. Doubles every round.
+        pub dial_backoff: u64,
+        /// Longest wait (in milliseconds) between two rounds of greetings.
+        pub max_dial_backoff: u64,
+        /// Messages per second accepted from each peer. Peers going over it get penalized.
+        pub peer_rate_limit: u32,
+        /// Messages per second accepted from all peers together.
+        pub global_rate_limit: u32,
+        /// Messages per second sent to each peer. Messages over it wait in the peer's queue.
+        pub peer_send_rate: u32,
+        /// Bytes sent and received per hour after which full chains are no longer pushed
+        /// to neighbours. Unlimited by default.
+        pub hourly_bandwidth_cap: Option<u64>,
+        /// Same as `hourly_bandwidth_cap`, per day.
+        pub daily_bandwidth_cap: Option<u64>,
+        /// Leading zero bits of the proof of work trackers ask of greeters. Zero admits everyone.
+        pub admission_difficulty: u32,
+        /// Threads searching for a nonce when the node is a miner.
+        pub mining_threads: usize,
+        /// Backend for the node's address book. Nothing is persisted by default.
+        pub store: Option<StoreEngine>,
+        /// Backend for the node's chain. Nothing is persisted by default.
+        pub chain_store: Option<StoreEngine>,
+        /// File holding the base64 encoded key both stores are encrypted with. Stores are
+        /// kept in clear if unset.
+        pub store_key_file: Option<PathBuf>,
+        /// How both stores serialize what they save. `Codec::Bincode` rules out
+        /// `wal_checkpoint_interval` and `chain_retention`.
+        pub store_codec: Codec,
+        /// Blocks appended to a write-ahead log next to the chain store between two
+        /// checkpoints into it. Without it, every new block rewrites the whole chain.
+        /// Not available for encrypted stores.
+        pub wal_checkpoint_interval: Option<usize>,
+        /// Prunes old blocks in the chain store. A node pruning its chain loads it pruned at
+        /// startup, so it stops offering its chain to neighbours.
+        pub chain_retention: Option<Retention>,
+        /// Genesis file of the private network the node belongs to. See `Genesis`. The node
+        /// starts from its block, refuses to start from a stored chain starting elsewhere
+        /// and ignores such chains from its neighbours. Without it, the node makes a
+        /// genesis block of its own.
+        pub genesis: Option<PathBuf>,
+        /// Network the node belongs to. It refuses to peer with nodes of other networks and
+        /// takes in transactions signed for its own only. Defaults to the network of the
+        /// genesis, if any, and to `mainnet` otherwise.
+        pub network: Option<NetworkId>,
+        /// Seconds between two audits of the whole chain, run in the background since
+        /// chains received are only verified past the blocks the node already verified.
+        /// `None` never audits it.
+        pub audit_interval: Option<u64>,
+        /// Rules chains must follow to be adopted, such as checkpoints.
+        pub chain: ChainConfig,
+        /// Wallet key, as written by `wallet create`, a miner seals blocks with when the
+        /// chain runs `Consensus::PoA`. Its address must be one of the authorities.
+        pub authority_key: Option<PathBuf>,
+        /// Wallet key, as written by `wallet create`, the node signs checkpoints with and
+        /// gossips them. Its address must be one of `ChainConfig::checkpoint_publishers`.
+        pub checkpoint_key: Option<PathBuf>,
+        /// The part the node takes in a mining pool, if any.
+        pub pool: Option<PoolConfig>,
+    }
+
+    impl Default for NodeConfig {
+        fn default() -> Self {
+            NodeConfig {
+                address: DEFAULT_ADDRESS.to_string(),
+                advertise_address: None,
+                role: Role::Node,
+                trackers: vec![],
+                gossip_interval: GOSSIP_INTERVAL,
+                max_gossip_interval: MAX_GOSSIP_INTERVAL,
+                listen_timeout: LISTEN_TIMEOUT,
+                fanout: None,
+                max_peers: Some(DEFAULT_MAX_PEERS),
+                max_hops: DEFAULT_MAX_HOPS,
+                transport: Transport::default(),
+                dial_attempts: Some(BOOTSTRAP_ATTEMPTS),
+                dial_backoff: INITIAL_BACKOFF,
+                max_dial_backoff: MAX_BACKOFF,
+                peer_rate_limit: PEER_RATE_LIMIT,
+                global_rate_limit: GLOBAL_RATE_LIMIT,
+                peer_send_rate: PEER_SEND_RATE,
+                hourly_bandwidth_cap: None,
+                daily_bandwidth_cap: None,
+                admission_difficulty: ADMISSION_DIFFICULTY,
+                mining_threads: DEFAULT_MINING_THREADS,
+                store: None,
+                chain_store: None,
+                store_key_file: None,
+                store_codec: Codec::default(),
+                wal_checkpoint_interval: None,
+                chain_retention: None,
+                genesis: None,
+                network: None,
+                audit_interval: Some(DEFAULT_AUDIT_INTERVAL),
+                chain: ChainConfig::default(),
+                authority_key: None,
+                checkpoint_key: None,
+                pool: None,
+            }
+        }
+    }
+
+    impl NodeConfig {
+        /// Parses and validates a TOML configuration.
+        pub fn from_toml(toml: &str) -> Result<Self, NodeConfigError> {
+            let config: NodeConfig = toml::from_str(toml)?;
+            config.validate()?;
+            Ok(config)
+        }
+
+        /// Checks that every address parses and every count is positive.
+        pub fn validate(&self) -> Result<(), NodeConfigError> {
+            let Ok(address) = self.address.parse::<SocketAddr>() else {
+                return Err(NodeConfigError::InvalidAddress(self.address.clone()));
+            };
+            match &self.advertise_address {
+                Some(advertised) => match advertised.parse::<SocketAddr>() {
+                    Ok(advertised) if advertised.ip().is_unspecified() => {
+                        return Err(NodeConfigError::UnspecifiedAdvertise(advertised.to_string()));
+                    },
+                    Ok(_) => (),
+                    Err(_) => return Err(NodeConfigError::InvalidAddress(advertised.clone())),
+                },
+                None if address.ip().is_unspecified() => {
+                    return Err(NodeConfigError::UnspecifiedAdvertise(self.address.clone()));
+                },
+                None => (),
+            }
+            if let Some(tracker) = self.trackers.iter().find(|tracker| !is_tracker_address(tracker)) {
+                return Err(NodeConfigError::InvalidTracker(tracker.clone()));
+            }
+            if self.gossip_interval == 0 {
+                return Err(NodeConfigError::Zero("gossip_interval"));
+            }
+            if self.max_gossip_interval < self.gossip_interval {
+                return Err(NodeConfigError::InvalidBackoff { min: self.gossip_interval, max: self.max_gossip_interval });
+            }
+            if self.listen_timeout == 0 {
+                return Err(NodeConfigError::Zero("listen_timeout"));
+            }
+            if self.fanout == Some(0) {
+                return Err(NodeConfigError::Zero("fanout"));
+            }
+            if self.max_peers == Some(0) {
+                return Err(NodeConfigError::Zero("max_peers"));
+            }
+            if self.max_hops == 0 {
+                return Err(NodeConfigError::Zero("max_hops"));
+            }
+            if self.dial_attempts == Some(0) {
+                return Err(NodeConfigError::Zero("dial_attempts"));
+            }
+            if self.dial_backoff == 0 {
+                return Err(NodeConfigError::Zero("dial_backoff"));
+            }
+            if self.max_dial_backoff < self.dial_backoff {
+                return Err(NodeConfigError::InvalidDialBackoff { min: self.dial_backoff, max: self.max_dial_backoff });
+            }
+            if self.peer_rate_limit == 0 {
+                return Err(NodeConfigError::Zero("peer_rate_limit"));
+            }
+            if self.global_rate_limit == 0 {
+                return Err(NodeConfigError::Zero("global_rate_limit"));
+            }
+            if self.peer_send_rate == 0 {
+                return Err(NodeConfigError::Zero("peer_send_rate"));
+            }
+            if self.hourly_bandwidth_cap == Some(0) {
+                return Err(NodeConfigError::Zero("hourly_bandwidth_cap"));
+            }
+            if self.daily_bandwidth_cap == Some(0) {
+                return Err(NodeConfigError::Zero("daily_bandwidth_cap"));
+            }
+            if self.admission_difficulty > MAX_ADMISSION_DIFFICULTY {
+                return Err(NodeConfigError::InvalidDifficulty(self.admission_difficulty));
+            }
+            if self.audit_interval == Some(0) {
+                return Err(NodeConfigError::Zero("audit_interval"));
+            }
+            if self.mining_threads == 0 {
+                return Err(NodeConfigError::Zero("mining_threads"));
+            }
+            if let Some(retention) = &self.chain_retention {
+                if retention.keep_full == 0 {
+                    return Err(NodeConfigError::Zero("chain_retention.keep_full"));
+                }
+                if retention.interval == 0 {
+                    return Err(NodeConfigError::Zero("chain_retention.interval"));
+                }
+            }
+            if self.wal_checkpoint_interval == Some(0) {
+                return Err(NodeConfigError::Zero("wal_checkpoint_interval"));
+            }
+            if self.wal_checkpoint_interval.is_some() && self.store_key_file.is_some() {
+                return Err(NodeConfigError::EncryptedWal);
+            }
+            if self.store_codec != Codec::Json {
+                if self.wal_checkpoint_interval.is_some() {
+                    return Err(NodeConfigError::OpaqueCodec("wal_checkpoint_interval"));
+                }
+                if self.chain_retention.is_some() {
+                    return Err(NodeConfigError::OpaqueCodec("chain_retention"));
+                }
+            }
+            if let Some(chain_store) = &self.chain_store {
+                let path = chain_store.path();
+                if path.is_some() && self.store.as_ref().and_then(StoreEngine::path) == path {
+                    return Err(NodeConfigError::SharedStore(chain_store.clone()));
+                }
+            }
+            for store in [&self.store, &self.chain_store].into_iter().flatten() {
+                if let StoreEngine::File { compression: Some(level), .. } = store {
+                    if !zstd::compression_level_range().contains(level) {
+                        return Err(NodeConfigError::InvalidCompression(*level));
+                    }
+                }
+            }
+            let checkpoints = &self.chain.checkpoints;
+            for checkpoint in checkpoints {
+                let conflicting = checkpoints.iter().any(|other| other.index == checkpoint.index && other.hash != checkpoint.hash);
+                if !checkpoint.is_well_formed() || conflicting {
+                    return Err(NodeConfigError::InvalidCheckpoint(checkpoint.index));
+                }
+            }
+            if let Some(publisher) = self.chain.checkpoint_publishers.iter().find(|publisher| general_purpose::STANDARD.decode(publisher).is_err()) {
+                return Err(NodeConfigError::InvalidPublisher(publisher.clone()));
+            }
+            if self.chain.checkpoint_interval == 0 {
+                return Err(NodeConfigError::Zero("chain.checkpoint_interval"));
+            }
+            if self.chain.records.bytes_per_coin == 0 {
+                return Err(NodeConfigError::Zero("chain.records.bytes_per_coin"));
+            }
+            if let Some(authority) = self.chain.mint.authorities.iter().find(|authority| general_purpose::STANDARD.decode(authority).is_err()) {
+                return Err(NodeConfigError::InvalidMintAuthority(authority.clone()));
+            }
+            let mut last = Upgrade { height: 0, version: BLOCK_VERSION };
+            for upgrade in &self.chain.upgrades {
+                if upgrade.height <= last.height || upgrade.version <= last.version {
+                    return Err(NodeConfigError::InvalidUpgrade(upgrade.version));
+                }
+                last = *upgrade;
+            }
+            match &self.pool {
+                Some(PoolConfig::Coordinator) if self.role != Role::Miner || self.chain.consensus != Consensus::Pow => {
+                    return Err(NodeConfigError::InvalidCoordinator);
+                },
+                Some(PoolConfig::Worker { coordinator, payout }) => {
+                    if coordinator.parse::<SocketAddr>().is_err() {
+                        return Err(NodeConfigError::InvalidAddress(coordinator.clone()));
+                    }
+                    if general_purpose::STANDARD.decode(payout).is_err() {
+                        return Err(NodeConfigError::InvalidPayout(payout.clone()));
+                    }
+                },
+                _ => (),
+            }
+            if self.chain.consensus == Consensus::PoA {
+                if self.chain.authorities.is_empty() {
+                    return Err(NodeConfigError::NoAuthorities);
+                }
+                if let Some(authority) = self.chain.authorities.iter().find(|authority| general_purpose::STANDARD.decode(authority).is_err()) {
+                    return Err(NodeConfigError::InvalidAuthority(authority.clone()));
+                }
+                if self.chain.block_interval == 0 {
+                    return Err(NodeConfigError::Zero("chain.block_interval"));
+                }
+                if self.role == Role::Miner && self.authority_key.is_none() {
+                    return Err(NodeConfigError::NoAuthorityKey);
+                }
+            }
+            Ok(())
+        }
+
+        /// Works out the network the node belongs to: `network` if set, which must then
+        /// match the one of `genesis`, the network of `genesis` otherwise, and `mainnet`
+        /// without either.
+        ///
+        /// # Arguments
+        /// * `genesis` - The genesis read from `genesis`, if set.
+        pub fn network(&self, genesis: Option<&Genesis>) -> Result<NetworkId, NodeConfigError> {
+            match (&self.network, genesis) {
+                (Some(network), Some(genesis)) if network.as_str() != genesis.network => Err(NodeConfigError::NetworkMismatch {
+                    network: network.clone(),
+                    genesis: genesis.network.clone(),
+                }),
+                (Some(network), _) => Ok(network.clone()),
+                (None, Some(genesis)) => genesis
+                    .network
+                    .parse()
+                    .map_err(|e| NodeConfigError::InvalidNetwork(genesis.network.clone(), e)),
+                (None, None) => Ok(NetworkId::default()),
+            }
+        }
+
+        /// Reads the wallet a miner seals blocks with, under `Consensus::PoA`.
+        ///
+        /// # Returns
+        /// * `Result<Option<Wallet>, NodeConfigError>` - The wallet of `authority_key`,
+        ///   `None` if the chain is mined or no key is set, or an error if the file holds
+        ///   no wallet key or one of no authority.
+        pub fn authority(&self) -> Result<Option<Wallet>, NodeConfigError> {
+            let Some(path) = self.authority_key.as_ref().filter(|_| self.chain.consensus == Consensus::PoA) else {
+                return Ok(None);
+            };
+            let wallet = fs::read_to_string(path)
+                .ok()
+                .map(Zeroizing::new)
+                .and_then(|encoded| Wallet::from_base64(&encoded))
+                .ok_or_else(|| NodeConfigError::InvalidAuthorityKey(path.clone()))?;
+            let address = general_purpose::STANDARD.encode(wallet.get_pub_key());
+            let authorized = self
+                .chain
+                .authorities
+                .iter()
+                .any(|authority| general_purpose::STANDARD.decode(authority).is_ok_and(|key| key == wallet.get_pub_key()));
+            if !authorized {
+                return Err(NodeConfigError::NotAnAuthority(address));
+            }
+            Ok(Some(wallet))
+        }
+
+        /// Reads the wallet the node signs checkpoints with.
+        ///
+        /// # Returns
+        /// * `Result<Option<Wallet>, NodeConfigError>` - The wallet of `checkpoint_key`,
+        ///   `None` if no key is set, or an error if the file holds no wallet key or one
+        ///   of no checkpoint publisher.
+        pub fn publisher(&self) -> Result<Option<Wallet>, NodeConfigError> {
+            let Some(path) = self.checkpoint_key.as_ref() else {
+                return Ok(None);
+            };
+            let wallet = fs::read_to_string(path)
+                .ok()
+                .map(Zeroizing::new)
+                .and_then(|encoded| Wallet::from_base64(&encoded))
+                .ok_or_else(|| NodeConfigError::InvalidAuthorityKey(path.clone()))?;
+            if !self.chain.is_publisher(&wallet.get_pub_key()) {
+                return Err(NodeConfigError::NotAPublisher(general_purpose::STANDARD.encode(wallet.get_pub_key())));
+            }
+            Ok(Some(wallet))
+        }
+    }
+
+    /// Trackers are given as `ip:port`, or as `name:port` for DNS seeds resolving to several trackers.
+    fn is_tracker_address(tracker: &str) -> bool {
+        if tracker.parse::<SocketAddr>().is_ok() {
+            return true;
+        }
+        match tracker.rsplit_once(':') {
+            Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+            None => false,
+        }
+    }
+
+    /// Roles are written by name in configuration files, e.g. `role = "miner"`.
+    fn deserialize_role<'de, D>(deserializer: D) -> Result<Role, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        match name.to_lowercase().as_str() {
+            "tracker" => Ok(Role::Tracker),
+            "node" => Ok(Role::Node),
+            "miner" => Ok(Role::Miner),
+            _ => Err(serde::de::Error::unknown_variant(&name, &["tracker", "node", "miner"])),
+        }
+    }
+
+    #[derive(Default)]
+    pub struct NodeBuilder {
+        config: NodeConfig,
+        receiver: Option<Receiver>,
+        chain: Option<Chain>,
+    }
+
+    impl NodeBuilder {
+        pub fn new() -> Self {
+            NodeBuilder::default()
+        }
+
+        pub fn from_config(config: NodeConfig) -> Self {
+            NodeBuilder {
+                config,
+                receiver: None,
+                chain: None,
+            }
+        }
+
+        pub fn with_address(mut self, address: impl Into<String>) -> Self {
+            self.config.address = address.into();
+            self
+        }
+
+        pub fn with_advertise_address(mut self, address: impl Into<String>) -> Self {
+            self.config.advertise_address = Some(address.into());
+            self
+        }
+
+        pub fn with_role(mut self, role: Role) -> Self {
+            self.config.role = role;
+            self
+        }
+
+        pub fn with_trackers(mut self, trackers: Vec<String>) -> Self {
+            self.config.trackers = trackers;
+            self
+        }
+
+        pub fn with_gossip_interval(mut self, seconds: u64) -> Self {
+            self.config.gossip_interval = seconds;
+            self
+        }
+
+        pub fn with_max_gossip_interval(mut self, seconds: u64) -> Self {
+            self.config.max_gossip_interval = seconds;
+            self
+        }
+
+        pub fn with_listen_timeout(mut self, seconds: u64) -> Self {
+            self.config.listen_timeout = seconds;
+            self
+        }
+
+        pub fn with_fanout(mut self, fanout: usize) -> Self {
+            self.config.fanout = Some(fanout);
+            self
+        }
+
+        pub fn with_max_peers(mut self, max_peers: usize) -> Self {
+            self.config.max_peers = Some(max_peers);
+            self
+        }
+
+        pub fn with_max_hops(mut self, max_hops: u8) -> Self {
+            self.config.max_hops = max_hops;
+            self
+        }
+
+        pub fn with_transport(mut self, transport: Transport) -> Self {
+            self.config.transport = transport;
+            self
+        }
+
+        /// Sets how entering the network retries.
+        ///
+        /// # Arguments
+        /// * `attempts` - Rounds of greetings, `None` to keep trying until connected.
+        /// * `backoff` - Milliseconds to wait after the first failed round.
+        /// * `max_backoff` - Longest wait (in milliseconds) between two rounds.
+        pub fn with_dial_policy(mut self, attempts: Option<u32>, backoff: u64, max_backoff: u64) -> Self {
+            self.config.dial_attempts = attempts;
+            self.config.dial_backoff = backoff;
+            self.config.max_dial_backoff = max_backoff;
+            self
+        }
+
+        pub fn with_rate_limits(mut self, peer_rate_limit: u32, global_rate_limit: u32) -> Self {
+            self.config.peer_rate_limit = peer_rate_limit;
+            self.config.global_rate_limit = global_rate_limit;
+            self
+        }
+
+        pub fn with_send_rate(mut self, peer_send_rate: u32) -> Self {
+            self.config.peer_send_rate = peer_send_rate;
+            self
+        }
+
+        pub fn with_bandwidth_caps(mut self, hourly: Option<u64>, daily: Option<u64>) -> Self {
+            self.config.hourly_bandwidth_cap = hourly;
+            self.config.daily_bandwidth_cap = daily;
+            self
+        }
+
+        pub fn with_admission_difficulty(mut self, difficulty: u32) -> Self {
+            self.config.admission_difficulty = difficulty;
+            self
+        }
+
+        pub fn with_mining_threads(mut self, threads: usize) -> Self {
+            self.config.mining_threads = threads;
+            self
+        }
+
+        pub fn with_store(mut self, store: StoreEngine) -> Self {
+            self.config.store = Some(store);
+            self
+        }
+
+        pub fn with_chain_store(mut self, chain_store: StoreEngine) -> Self {
+            self.config.chain_store = Some(chain_store);
+            self
+        }
+
+        /// Logs new blocks to a write-ahead log, checkpointed into the chain store every
+        /// `interval` blocks.
+        pub fn with_wal_checkpoint_interval(mut self, interval: usize) -> Self {
+            self.config.wal_checkpoint_interval = Some(interval);
+            self
+        }
+
+        pub fn with_store_codec(mut self, codec: Codec) -> Self {
+            self.config.store_codec = codec;
+            self
+        }
+
+        pub fn with_chain_retention(mut self, retention: Retention) -> Self {
+            self.config.chain_retention = Some(retention);
+            self
+        }
+
+        /// Joins the private network described by the genesis file at `path`.
+        pub fn with_genesis(mut self, path: impl Into<PathBuf>) -> Self {
+            self.config.genesis = Some(path.into());
+            self
+        }
+
+        /// Joins the network `network` rather than `mainnet`, or the network of the genesis.
+        pub fn with_network(mut self, network: NetworkId) -> Self {
+            self.config.network = Some(network);
+            self
+        }
+
+        /// Sets how often the whole chain is audited, in seconds, `None` to never audit it.
+        pub fn with_audit_interval(mut self, interval: Option<u64>) -> Self {
+            self.config.audit_interval = interval;
+            self
+        }
+
+        /// Refuses chains holding a block other than the one with hash `hash` at `index`.
+        pub fn with_checkpoint(mut self, index: usize, hash: impl Into<String>) -> Self {
+            self.config.chain.checkpoints.push(Checkpoint { index, hash: hash.into() });
+            self
+        }
+
+        /// Has authorities take turns sealing blocks instead of mining them. See
+        /// `Consensus::PoA`.
+        ///
+        /// # Arguments
+        /// * `authorities` - Base64 encoded public keys of the authorities.
+        /// * `block_interval` - Seconds an authority waits after the last block before
+        ///   sealing the next one.
+        pub fn with_authorities(mut self, authorities: Vec<String>, block_interval: u64) -> Self {
+            self.config.chain.consensus = Consensus::PoA;
+            self.config.chain.authorities = authorities;
+            self.config.chain.block_interval = block_interval;
+            self
+        }
+
+        /// Seals blocks with the wallet key in the file at `path`, for a miner of a
+        /// proof-of-authority chain.
+        pub fn with_authority_key(mut self, path: impl Into<PathBuf>) -> Self {
+            self.config.authority_key = Some(path.into());
+            self
+        }
+
+        /// Pins the checkpoints gossiped signed by one of `publishers`, base64 encoded
+        /// public keys. See `ChainConfig::checkpoint_publishers`.
+        pub fn with_checkpoint_publishers(mut self, publishers: Vec<String>) -> Self {
+            self.config.chain.checkpoint_publishers = publishers;
+            self
+        }
+
+        /// Holds records to `rules`: their size and the coins their owners must hold. See
+        /// `ChainConfig::records`.
+        pub fn with_record_rules(mut self, rules: RecordRules) -> Self {
+            self.config.chain.records = rules;
+            self
+        }
+
+        /// Signs checkpoints of the chain with the wallet key in the file at `path`, every
+        /// `interval` blocks, once they lie `depth` blocks below the tip, and gossips them.
+        pub fn with_checkpoint_key(mut self, path: impl Into<PathBuf>, interval: usize, depth: usize) -> Self {
+            self.config.checkpoint_key = Some(path.into());
+            self.config.chain.checkpoint_interval = interval;
+            self.config.chain.checkpoint_depth = depth;
+            self
+        }
+
+        /// Has the node take part in a mining pool. See `PoolConfig`.
+        pub fn with_pool(mut self, pool: PoolConfig) -> Self {
+            self.config.pool = Some(pool);
+            self
+        }
+
+        /// Encrypts both stores with the key held in the file at `path`.
+        pub fn with_store_key_file(mut self, path: impl Into<PathBuf>) -> Self {
+            self.config.store_key_file = Some(path.into());
+            self
+        }
+
+        /// Sets the channel transactions are fed to the node through. Without one, the
+        /// node gets a channel nobody sends on.
+        pub fn with_receiver(mut self, receiver: Receiver) -> Self {
+            self.receiver = Some(receiver);
+            self
+        }
+
+        /// Starts the node from `chain` rather than from a genesis block of its own. Genesis
+        /// blocks carry the time they were created at, so nodes created apart only agree on
+        /// their genesis if they are given the same one.
+        pub fn with_chain(mut self, chain: Chain) -> Self {
+            self.chain = Some(chain);
+            self
+        }
+
+        pub fn build(self) -> Result<Node, NodeConfigError> {
+            self.config.validate()?;
+            let receiver = self.receiver.unwrap_or_else(|| {
+                Receiver::new(mpsc::channel(DEFAULT_CHANNEL_CAPACITY).1)
+            });
+            let mut node = Node::from_config(self.config, receiver)?;
+            if let Some(chain) = self.chain {
+                node.start_from(chain);
+            }
+            Ok(node)
+        }
+    }
+}
@@ -0,0 +1,267 @@
+pub mod decode {
+
+    use crate::{
+        chain::{block::block::block::Block, chain::chain::Chain, config::config::SignedCheckpoint},
+        node::{
+            filter::filter::{FilteredBlock, NotificationFilter, MAX_FILTER_ENTRIES},
+            pool::pool::{Job, PoolJoin},
+            neighbour::neighbour::Neighbour,
+            relay::relay::RelayedNeighbour,
+            sync::sync::RangeResponse,
+        },
+        record::record::record::{Record, RecordFromBase64Error},
+        transaction::batch::batch::{self, BatchTransaction},
+        transaction::transaction::transaction::{Transaction, TransactionFromBase64Error},
+    };
+
+    use std::str::{self, Utf8Error};
+
+    use serde::de::DeserializeOwned;
+    use thiserror::Error;
+
+    /// Longest transaction or record taken in, in bytes of wire format.
+    pub const MAX_ENTRY_SIZE: usize = 8 * 1024;
+    /// Longest JSON message taken in, in bytes, but for chains and batches of blocks.
+    pub const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+    /// Longest chain, or batch of blocks or headers, taken in, in bytes.
+    pub const MAX_CHAIN_SIZE: usize = 64 * 1024 * 1024;
+    /// Longest public key, in bytes. P-256 ones take 65.
+    pub const MAX_KEY_SIZE: usize = 128;
+    /// Longest signature, in bytes. ASN.1 encoded P-256 ones take at most 72.
+    pub const MAX_SIGNATURE_SIZE: usize = 128;
+    /// Longest record key, in bytes.
+    pub const MAX_RECORD_KEY_SIZE: usize = 256;
+    /// Longest neighbour address, in bytes.
+    pub const MAX_ADDRESS_SIZE: usize = 256;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum DecodeError {
+        #[error("Input of {len} bytes is over the limit of {limit}.")]
+        #[from(ignore)]
+        TooLong { len: usize, limit: usize },
+        #[error("The {field} field holds {len} bytes, over the limit of {limit}.")]
+        #[from(ignore)]
+        FieldTooLong { field: &'static str, len: usize, limit: usize },
+        #[error("Input is not valid UTF-8 - {0}")]
+        Utf8(Utf8Error),
+        #[error("Malformed transaction - {0}")]
+        Transaction(TransactionFromBase64Error),
+        #[error("Malformed record - {0}")]
+        Record(RecordFromBase64Error),
+        #[error("Malformed JSON - {0}")]
+        Json(serde_json::Error),
+        #[error("Block {0} holds a malformed transaction.")]
+        #[from(ignore)]
+        MalformedBlock(usize),
+        #[error("The checkpoint at block {0} does not hold a block hash.")]
+        #[from(ignore)]
+        MalformedCheckpoint(usize),
+    }
+
+    /// Fails if `len` is over `limit`.
+    fn within(len: usize, limit: usize) -> Result<(), DecodeError> {
+        if len > limit {
+            return Err(DecodeError::TooLong { len, limit });
+        }
+        Ok(())
+    }
+
+    /// Fails if the field `field`, of `len` bytes, is over `limit`.
+    fn field(field: &'static str, len: usize, limit: usize) -> Result<(), DecodeError> {
+        if len > limit {
+            return Err(DecodeError::FieldTooLong { field, len, limit });
+        }
+        Ok(())
+    }
+
+    /// Checks every field of a transaction is within its limit.
+    fn check_transaction(transaction: &Transaction) -> Result<(), DecodeError> {
+        field("sender", transaction.sender.len(), MAX_KEY_SIZE)?;
+        field("receiver", transaction.receiver.len(), MAX_KEY_SIZE)?;
+        field("signature", transaction.signature.as_ref().map_or(0, Vec::len), MAX_SIGNATURE_SIZE)
+    }
+
+    /// Checks every field of a record is within its limit. Values are only bounded by
+    /// `MAX_ENTRY_SIZE`.
+    fn check_record(record: &Record) -> Result<(), DecodeError> {
+        field("key", record.key.len(), MAX_RECORD_KEY_SIZE)?;
+        field("owner", record.owner.len(), MAX_KEY_SIZE)?;
+        field("value", record.value.len(), MAX_ENTRY_SIZE)?;
+        field("signature", record.signature.as_ref().map_or(0, Vec::len), MAX_SIGNATURE_SIZE)
+    }
+
+    /// Checks every field of a batch is within its limit, and it carries at most
+    /// `batch::MAX_TRANSFERS` transfers. Whether it is well formed otherwise is left to
+    /// `BatchTransaction::check`.
+    fn check_batch(batch: &BatchTransaction) -> Result<(), DecodeError> {
+        field("sender", batch.sender.len(), MAX_KEY_SIZE)?;
+        field("transfers", batch.transfers.len(), batch::MAX_TRANSFERS)?;
+        for transfer in &batch.transfers {
+            field("receiver", transfer.receiver.len(), MAX_KEY_SIZE)?;
+        }
+        field("signature", batch.signature.as_ref().map_or(0, Vec::len), MAX_SIGNATURE_SIZE)
+    }
+
+    /// Checks the transactions, records and batches a block holds are well formed and
+    /// within their limits, so nothing taken from the network fails to decode later on.
+    pub fn check_block(block: &Block) -> Result<(), DecodeError> {
+        let transactions = block.try_get_transactions().map_err(|_| DecodeError::MalformedBlock(block.index))?;
+        transactions.iter().try_for_each(check_transaction)?;
+        block.records.iter().try_for_each(check_record)?;
+        block.batches.iter().try_for_each(check_batch)
+    }
+
+    /// Decodes a transaction in wire format, as `Transaction::try_from` does, rejecting
+    /// oversized input and fields.
+    ///
+    /// # Arguments
+    /// * `bytes` - The wire format, as received.
+    ///
+    /// # Returns
+    /// * `Result<Transaction, DecodeError>` - The transaction, or why it was rejected.
+    pub fn transaction(bytes: &[u8]) -> Result<Transaction, DecodeError> {
+        within(bytes.len(), MAX_ENTRY_SIZE)?;
+        let transaction = Transaction::try_from(str::from_utf8(bytes)?.to_string())?;
+        check_transaction(&transaction)?;
+        Ok(transaction)
+    }
+
+    /// Decodes a record in wire format, as `Record::try_from` does, rejecting oversized
+    /// input and fields.
+    ///
+    /// # Arguments
+    /// * `bytes` - The wire format, as received.
+    ///
+    /// # Returns
+    /// * `Result<Record, DecodeError>` - The record, or why it was rejected.
+    pub fn record(bytes: &[u8]) -> Result<Record, DecodeError> {
+        within(bytes.len(), MAX_ENTRY_SIZE)?;
+        let record = Record::try_from(str::from_utf8(bytes)?.to_string())?;
+        check_record(&record)?;
+        Ok(record)
+    }
+
+    /// Decodes a batch of transfers, JSON of at most `MAX_MESSAGE_SIZE` bytes, rejecting
+    /// oversized fields.
+    ///
+    /// # Arguments
+    /// * `bytes` - The JSON, as received.
+    ///
+    /// # Returns
+    /// * `Result<BatchTransaction, DecodeError>` - The batch, or why it was rejected.
+    pub fn batch(bytes: &[u8]) -> Result<BatchTransaction, DecodeError> {
+        let batch: BatchTransaction = message(bytes)?;
+        check_batch(&batch)?;
+        Ok(batch)
+    }
+
+    /// Decodes a notification filter, JSON of at most `MAX_MESSAGE_SIZE` bytes, rejecting
+    /// filters of over `MAX_FILTER_ENTRIES` entries and oversized ones.
+    pub fn filter(bytes: &[u8]) -> Result<NotificationFilter, DecodeError> {
+        let filter: NotificationFilter = message(bytes)?;
+        field("entries", filter.len(), MAX_FILTER_ENTRIES)?;
+        for address in &filter.addresses {
+            field("address", address.len(), MAX_KEY_SIZE)?;
+        }
+        for key in &filter.keys {
+            field("key", key.len(), MAX_RECORD_KEY_SIZE)?;
+        }
+        Ok(filter)
+    }
+
+    /// Decodes a filtered block of at most `MAX_CHAIN_SIZE` bytes, checking its entries
+    /// are well formed and within their limits, as `check_block` does.
+    pub fn filtered_block(bytes: &[u8]) -> Result<FilteredBlock, DecodeError> {
+        let block: FilteredBlock = json(bytes, MAX_CHAIN_SIZE)?;
+        for wire in &block.transactions {
+            within(wire.len(), MAX_ENTRY_SIZE)?;
+            let transaction = Transaction::try_from(wire.clone())?;
+            check_transaction(&transaction)?;
+        }
+        block.records.iter().try_for_each(check_record)?;
+        block.batches.iter().try_for_each(check_batch)?;
+        Ok(block)
+    }
+
+    /// Decodes a signed checkpoint, JSON of at most `MAX_MESSAGE_SIZE` bytes, rejecting
+    /// oversized keys and signatures and hashes that are not 64 hex digits.
+    pub fn checkpoint(bytes: &[u8]) -> Result<SignedCheckpoint, DecodeError> {
+        let signed: SignedCheckpoint = message(bytes)?;
+        field("publisher", signed.publisher.len(), MAX_KEY_SIZE)?;
+        field("signature", signed.signature.len(), MAX_SIGNATURE_SIZE)?;
+        if !signed.checkpoint.is_well_formed() {
+            return Err(DecodeError::MalformedCheckpoint(signed.checkpoint.index));
+        }
+        Ok(signed)
+    }
+
+    /// Decodes a request to join a pool, JSON of at most `MAX_MESSAGE_SIZE` bytes,
+    /// rejecting oversized payout addresses.
+    pub fn pool_join(bytes: &[u8]) -> Result<PoolJoin, DecodeError> {
+        let join: PoolJoin = message(bytes)?;
+        field("payout", join.payout.len(), MAX_KEY_SIZE)?;
+        Ok(join)
+    }
+
+    /// Decodes a pool job of at most `MAX_CHAIN_SIZE` bytes, checking the block it mines
+    /// on as `check_block` does.
+    pub fn job(bytes: &[u8]) -> Result<Job, DecodeError> {
+        let job: Job = json(bytes, MAX_CHAIN_SIZE)?;
+        check_block(&job.previous)?;
+        Ok(job)
+    }
+
+    /// Decodes a JSON message of at most `limit` bytes.
+    ///
+    /// # Arguments
+    /// * `bytes` - The JSON, as received.
+    /// * `limit` - The most bytes taken in.
+    pub fn json<T: DeserializeOwned>(bytes: &[u8], limit: usize) -> Result<T, DecodeError> {
+        within(bytes.len(), limit)?;
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    /// Decodes a JSON message of at most `MAX_MESSAGE_SIZE` bytes.
+    pub fn message<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, DecodeError> {
+        json(bytes, MAX_MESSAGE_SIZE)
+    }
+
+    /// Decodes a neighbour, rejecting oversized addresses.
+    pub fn neighbour(bytes: &[u8]) -> Result<Neighbour, DecodeError> {
+        let neighbour: Neighbour = message(bytes)?;
+        field("address", neighbour.address.len(), MAX_ADDRESS_SIZE)?;
+        Ok(neighbour)
+    }
+
+    /// Decodes a neighbour relayed by neighbour gossip, rejecting oversized addresses.
+    pub fn relayed_neighbour(bytes: &[u8]) -> Result<RelayedNeighbour, DecodeError> {
+        let relayed: RelayedNeighbour = message(bytes)?;
+        field("address", relayed.neighbour.address.len(), MAX_ADDRESS_SIZE)?;
+        Ok(relayed)
+    }
+
+    /// Decodes a list of neighbours, rejecting oversized addresses.
+    pub fn neighbours(bytes: &[u8]) -> Result<Vec<Neighbour>, DecodeError> {
+        let neighbours: Vec<Neighbour> = message(bytes)?;
+        for neighbour in &neighbours {
+            field("address", neighbour.address.len(), MAX_ADDRESS_SIZE)?;
+        }
+        Ok(neighbours)
+    }
+
+    /// Decodes a chain of at most `MAX_CHAIN_SIZE` bytes, checking its blocks with
+    /// `check_block`. Whether the chain is valid is left to `Chain::verify_chain`.
+    pub fn chain(bytes: &[u8]) -> Result<Chain, DecodeError> {
+        let chain: Chain = json(bytes, MAX_CHAIN_SIZE)?;
+        chain.blocks_from(0).try_for_each(check_block)?;
+        Ok(chain)
+    }
+
+    /// Decodes the answer to a `ProtocolMsg::GetRange` request, of at most
+    /// `MAX_CHAIN_SIZE` bytes, checking its blocks with `check_block`.
+    pub fn range(bytes: &[u8]) -> Result<RangeResponse, DecodeError> {
+        let response: RangeResponse = json(bytes, MAX_CHAIN_SIZE)?;
+        response.blocks.iter().try_for_each(check_block)?;
+        Ok(response)
+    }
+}
@@ -0,0 +1,56 @@
+pub mod event {
+
+    use crate::Transaction;
+    use crate::record::record::record::Record;
+    use crate::transaction::batch::batch::BatchTransaction;
+    use crate::node::reputation::reputation::BannedPeer;
+    use crate::node::filter::filter::FilteredBlock;
+    use crate::chain::config::config::Checkpoint;
+
+    use uuid::Uuid;
+
+    /// Capacity of the broadcast channel events are published on. Subscribers that fall
+    /// further behind miss the oldest events.
+    pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+    /// Something observable that happened in a `Node`. See `Node::subscribe`.
+    #[derive(Clone)]
+    pub enum NodeEvent {
+        /// A new neighbour was added.
+        NeighbourAdded { id: Uuid, address: String },
+        /// A neighbour left, was evicted for not answering pings or was banned.
+        NeighbourRemoved { id: Uuid, address: String },
+        /// A transaction reached this node and was accepted for mining.
+        TransactionReceived(Transaction),
+        /// A record reached this node and was accepted for mining.
+        RecordReceived(Record),
+        /// A batch of transfers reached this node and was accepted for mining.
+        BatchReceived(BatchTransaction),
+        /// This node mined a block at the given height.
+        BlockMined { height: usize },
+        /// This node replaced its chain with a longer one received from a neighbour.
+        ChainAdopted { len: usize },
+        /// A peer was banned for misbehaving.
+        PeerBanned(BannedPeer),
+        /// A peer claimed the id of a known neighbour, or of this node, from another address.
+        IdConflict { id: Uuid, known: String, claimed: String },
+        /// An audit of the whole chain found it invalid, though every block passed
+        /// verification when adopted. See `Verifier`.
+        AuditFailed { height: usize, tip: String, error: String },
+        /// A neighbour this node loaded a filter on relayed a new block of its chain,
+        /// filtered. See `Node::load_filter`.
+        FilteredBlock { from: String, block: FilteredBlock },
+        /// A checkpoint signed by a trusted publisher was pinned: chains holding another
+        /// block at its index are refused from now on. `from` is the neighbour that sent
+        /// it, `None` if this node signed it.
+        CheckpointPinned { from: Option<String>, checkpoint: Checkpoint },
+        /// The node panicked, and is being dropped. `saved` tells whether its chain of
+        /// `height` blocks and the `pending` entries its miner had yet to mine were saved
+        /// to the chain store, to be loaded on restart.
+        Crashed { height: usize, pending: usize, saved: bool },
+        /// A neighbour sent a message of a protocol this node does not know. `extension`
+        /// tells whether it falls within `protocol::EXTENSIONS`, so was likely meant for
+        /// an extension this node does not run.
+        UnknownProtocol { from: String, protocol: u8, extension: bool },
+    }
+}
@@ -0,0 +1,122 @@
+pub mod filter {
+
+    use crate::{
+        Transaction,
+        chain::block::block::block::{Block, BlockHeader},
+        record::record::record::Record,
+        transaction::batch::batch::BatchTransaction,
+    };
+
+    use std::collections::HashSet;
+
+    use serde::{Deserialize, Serialize};
+
+    /// Most addresses and record keys, together, a filter holds.
+    pub const MAX_FILTER_ENTRIES: usize = 256;
+    /// Most neighbours a node relays filtered blocks to.
+    pub const MAX_FILTERS: usize = 64;
+
+    /// What a light node wants to hear about: transactions and batches sent or received
+    /// by one of `addresses`, and records whose key is one of `keys` or whose owner is
+    /// one of `addresses`. Loaded on a neighbour with `ProtocolMsg::FilterLoad`; an empty
+    /// filter clears the one loaded.
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    pub struct NotificationFilter {
+        #[serde(default)]
+        pub addresses: HashSet<Vec<u8>>,
+        #[serde(default)]
+        pub keys: HashSet<String>,
+    }
+
+    impl NotificationFilter {
+        pub fn new() -> Self {
+            NotificationFilter::default()
+        }
+
+        /// Adds the public key `address` to the filter.
+        pub fn with_address(mut self, address: Vec<u8>) -> Self {
+            self.addresses.insert(address);
+            self
+        }
+
+        /// Adds the record key `key` to the filter.
+        pub fn with_key(mut self, key: impl Into<String>) -> Self {
+            self.keys.insert(key.into());
+            self
+        }
+
+        /// Number of addresses and keys in the filter.
+        pub fn len(&self) -> usize {
+            self.addresses.len() + self.keys.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        pub fn matches_transaction(&self, transaction: &Transaction) -> bool {
+            self.addresses.contains(&transaction.sender) || self.addresses.contains(&transaction.receiver)
+        }
+
+        pub fn matches_record(&self, record: &Record) -> bool {
+            self.keys.contains(&record.key) || self.addresses.contains(&record.owner)
+        }
+
+        pub fn matches_batch(&self, batch: &BatchTransaction) -> bool {
+            self.addresses.contains(&batch.sender)
+                || batch.transfers.iter().any(|transfer| self.addresses.contains(&transfer.receiver))
+        }
+
+        /// Returns the header of `block` with the entries matching the filter.
+        pub fn filter_block(&self, block: &Block) -> FilteredBlock {
+            let transactions = block.get_data_transactions()
+                .into_iter()
+                .filter(|transaction| self.matches_transaction(transaction))
+                .map(Into::into)
+                .collect();
+            FilteredBlock {
+                header: block.header(),
+                transactions,
+                records: block.records.iter().filter(|record| self.matches_record(record)).cloned().collect(),
+                batches: block.batches.iter().filter(|batch| self.matches_batch(batch)).cloned().collect(),
+            }
+        }
+    }
+
+    /// Payload of a `ProtocolMsg::FilteredBlock` message: a new block of the sender's chain,
+    /// with only the entries matching the filter loaded on it. Every new block is sent,
+    /// matching entries or not, so the light node can follow the chain by its headers.
+    ///
+    /// The entries are signed by their senders, but nothing proves the block holds them
+    /// or that it holds no other matching one: light nodes trust the neighbour they load
+    /// a filter on.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct FilteredBlock {
+        pub header: BlockHeader,
+        /// Matching transactions, in wire format.
+        pub transactions: Vec<String>,
+        pub records: Vec<Record>,
+        pub batches: Vec<BatchTransaction>,
+    }
+
+    impl FilteredBlock {
+        /// Decodes the matching transactions, skipping malformed ones.
+        pub fn get_transactions(&self) -> Vec<Transaction> {
+            self.transactions.iter().cloned().filter_map(|wire| Transaction::try_from(wire).ok()).collect()
+        }
+
+        /// Whether the block holds no matching entry.
+        pub fn is_empty(&self) -> bool {
+            self.transactions.is_empty() && self.records.is_empty() && self.batches.is_empty()
+        }
+    }
+
+    /// A filter loaded by a neighbour, with where in the chain relaying stopped.
+    #[derive(Clone, Debug)]
+    pub struct LoadedFilter {
+        pub filter: NotificationFilter,
+        /// Position of the next block to relay. Brought back to the block after the
+        /// common ancestor when the chain is replaced. See `Node::rewind_filters`.
+        pub next: usize,
+    }
+}
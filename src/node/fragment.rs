@@ -0,0 +1,251 @@
+pub mod fragment {
+
+    use crate::{
+        clock::clock::clock,
+        node::{
+            decode::decode::MAX_CHAIN_SIZE,
+            gossip::gossip::MAX_DATAGRAM_SIZE,
+            protocol::protocol::ProtocolMsg,
+        },
+    };
+
+    use std::{
+        collections::HashMap,
+        net::SocketAddr,
+        time::Duration,
+    };
+
+    use thiserror::Error;
+    use uuid::Uuid;
+
+    /// Fragment header: protocol byte, message id, fragment index and fragment count.
+    pub const FRAGMENT_HEADER_SIZE: usize = 1 + 16 + 2 + 2;
+    /// Bytes of the original message carried by each fragment.
+    pub const FRAGMENT_PAYLOAD_SIZE: usize = MAX_DATAGRAM_SIZE - FRAGMENT_HEADER_SIZE;
+    /// Time (in seconds) a partially received message is kept before being dropped.
+    pub const REASSEMBLY_TIMEOUT: u64 = 10;
+    /// Messages partially received at once from a sender. Past it, the oldest message of
+    /// the sender is dropped to make room.
+    pub const MAX_PARTIAL_MESSAGES: usize = 16;
+    /// Bytes of partially received messages held at once. Past it, the oldest messages of
+    /// the sender holding the most bytes are dropped to make room.
+    pub const MAX_REASSEMBLY_BYTES: usize = 2 * MAX_CHAIN_SIZE;
+
+    #[derive(Error, Debug)]
+    pub enum FragmentError {
+        #[error("Malformed fragment - {0} bytes is shorter than the fragment header.")]
+        TooShort(usize),
+        #[error("Malformed fragment - index {index} out of {total} fragments.")]
+        BadIndex { index: u16, total: u16 },
+        #[error("Message too large - {0} bytes do not fit in {} fragments.", u16::MAX)]
+        TooLarge(usize),
+        #[error("Incomplete message {id} - received {received} out of {total} fragments.")]
+        Incomplete { id: Uuid, received: usize, total: usize },
+        #[error("Message {id} too large - {total} fragments exceed {} bytes.", MAX_CHAIN_SIZE)]
+        Oversized { id: Uuid, total: u16 },
+    }
+
+    /// Splits a message into datagrams no larger than `MAX_DATAGRAM_SIZE`.
+    ///
+    /// Messages that already fit in a single datagram are returned untouched, so
+    /// small messages keep their plain `[protocol][payload]` layout.
+    ///
+    /// # Arguments
+    /// * `message` - The full message, starting with its protocol byte.
+    ///
+    /// # Returns
+    /// * `Result<Vec<Vec<u8>>, FragmentError>` - The datagrams to be sent, in order.
+    pub fn fragment(message: &[u8]) -> Result<Vec<Vec<u8>>, FragmentError> {
+        if message.len() <= MAX_DATAGRAM_SIZE {
+            return Ok(vec![message.to_vec()]);
+        }
+        let chunks: Vec<&[u8]> = message.chunks(FRAGMENT_PAYLOAD_SIZE).collect();
+        if chunks.len() > u16::MAX as usize {
+            return Err(FragmentError::TooLarge(message.len()));
+        }
+        let id = Uuid::new_v4();
+        let total = chunks.len() as u16;
+        Ok(chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut datagram = Vec::with_capacity(FRAGMENT_HEADER_SIZE + chunk.len());
+                datagram.push(ProtocolMsg::Fragment as u8);
+                datagram.extend_from_slice(id.as_bytes());
+                datagram.extend_from_slice(&(index as u16).to_be_bytes());
+                datagram.extend_from_slice(&total.to_be_bytes());
+                datagram.extend_from_slice(chunk);
+                datagram
+            })
+            .collect())
+    }
+
+    /// Identifies a partial message: its sender and the id it was fragmented under, so
+    /// senders cannot complete or drop the messages of others.
+    type MessageKey = (SocketAddr, Uuid);
+
+    struct PartialMessage {
+        fragments: Vec<Option<Vec<u8>>>,
+        received: usize,
+        /// Bytes of the fragments received.
+        bytes: usize,
+        /// When the first fragment arrived, in milliseconds since the UNIX epoch on the
+        /// installed clock.
+        first_seen: u64,
+        /// How many messages were begun up to this one, so the first of several begun
+        /// within the same millisecond is known.
+        arrival: u64,
+    }
+
+    impl PartialMessage {
+        fn incomplete(&self, id: Uuid) -> FragmentError {
+            FragmentError::Incomplete { id, received: self.received, total: self.fragments.len() }
+        }
+    }
+
+    /// Collects fragments until every piece of a message has arrived. At most
+    /// `MAX_PARTIAL_MESSAGES` messages per sender, of `MAX_REASSEMBLY_BYTES` bytes in all,
+    /// are collected at once.
+    pub struct Reassembler {
+        pending: HashMap<MessageKey, PartialMessage>,
+        timeout: Duration,
+        /// Bytes of the fragments held in `pending`.
+        buffered: usize,
+        /// Messages dropped to make room, reported by the next `expire`.
+        dropped: Vec<FragmentError>,
+        /// Messages begun so far. See `PartialMessage::arrival`.
+        arrivals: u64,
+    }
+
+    impl Reassembler {
+        pub fn new() -> Self {
+            Reassembler {
+                pending: HashMap::new(),
+                timeout: Duration::new(REASSEMBLY_TIMEOUT, 0),
+                buffered: 0,
+                dropped: Vec::new(),
+                arrivals: 0,
+            }
+        }
+
+        /// Drops the message `sender` sent first but `key`, to make room for the fragments
+        /// of `key`.
+        ///
+        /// # Returns
+        /// `false` if `sender` has no other message to drop.
+        fn drop_oldest(&mut self, sender: SocketAddr, key: MessageKey) -> bool {
+            let oldest = self.pending
+                .iter()
+                .filter(|(pending, _)| pending.0 == sender && **pending != key)
+                .min_by_key(|(_, partial)| partial.arrival)
+                .map(|(pending, _)| *pending);
+            let Some(oldest) = oldest else {
+                return false;
+            };
+            let partial = self.pending.remove(&oldest).unwrap();
+            self.buffered -= partial.bytes;
+            self.dropped.push(partial.incomplete(oldest.1));
+            true
+        }
+
+        /// Drops the oldest message but `key` of the sender holding the most bytes, so a
+        /// sender filling the buffer makes room from its own messages first.
+        ///
+        /// # Returns
+        /// `false` if there is no other message to drop.
+        fn drop_heaviest(&mut self, key: MessageKey) -> bool {
+            let mut held: HashMap<SocketAddr, usize> = HashMap::new();
+            for ((sender, _), partial) in &self.pending {
+                *held.entry(*sender).or_default() += partial.bytes;
+            }
+            let mut senders: Vec<(SocketAddr, usize)> = held.into_iter().collect();
+            senders.sort_by_key(|s| std::cmp::Reverse(s.1));
+            senders.into_iter().any(|(sender, _)| self.drop_oldest(sender, key))
+        }
+
+        /// Feeds a `ProtocolMsg::Fragment` datagram into the reassembler.
+        ///
+        /// # Arguments
+        /// * `sender` - Where the datagram came from.
+        /// * `datagram` - The received datagram, including the fragment header.
+        ///
+        /// # Returns
+        /// * `Result<Option<Vec<u8>>, FragmentError>` - The original message once its last fragment arrives.
+        pub fn push(&mut self, sender: SocketAddr, datagram: &[u8]) -> Result<Option<Vec<u8>>, FragmentError> {
+            if datagram.len() < FRAGMENT_HEADER_SIZE {
+                return Err(FragmentError::TooShort(datagram.len()));
+            }
+            let id = Uuid::from_slice(&datagram[1..17]).unwrap(); // Length checked above.
+            let index = u16::from_be_bytes([datagram[17], datagram[18]]);
+            let total = u16::from_be_bytes([datagram[19], datagram[20]]);
+            if index >= total {
+                return Err(FragmentError::BadIndex { index, total });
+            }
+            if (total as usize - 1) * FRAGMENT_PAYLOAD_SIZE >= MAX_CHAIN_SIZE {
+                return Err(FragmentError::Oversized { id, total });
+            }
+
+            let key = (sender, id);
+            let payload = &datagram[FRAGMENT_HEADER_SIZE..];
+            if !self.pending.contains_key(&key) {
+                let held = |pending: &HashMap<MessageKey, PartialMessage>| {
+                    pending.keys().filter(|(from, _)| *from == sender).count()
+                };
+                while held(&self.pending) >= MAX_PARTIAL_MESSAGES && self.drop_oldest(sender, key) {}
+                self.arrivals += 1;
+            }
+            while self.buffered + payload.len() > MAX_REASSEMBLY_BYTES && self.drop_heaviest(key) {}
+            let partial = self.pending.entry(key).or_insert_with(|| PartialMessage {
+                fragments: vec![None; total as usize],
+                received: 0,
+                bytes: 0,
+                first_seen: clock::now_millis(),
+                arrival: self.arrivals,
+            });
+            if partial.fragments.len() != total as usize {
+                return Err(FragmentError::BadIndex { index, total });
+            }
+            let slot = &mut partial.fragments[index as usize];
+            if slot.is_none() {
+                *slot = Some(payload.to_vec());
+                partial.received += 1;
+                partial.bytes += payload.len();
+                self.buffered += payload.len();
+            }
+            if partial.received < partial.fragments.len() {
+                return Ok(None);
+            }
+
+            let partial = self.pending.remove(&key).unwrap();
+            self.buffered -= partial.bytes;
+            Ok(Some(partial.fragments.into_iter().flatten().flatten().collect()))
+        }
+
+        /// Drops messages whose fragments did not all arrive within the reassembly timeout.
+        ///
+        /// # Returns
+        /// * `Vec<FragmentError>` - One `FragmentError::Incomplete` per dropped message,
+        ///   including the ones dropped to make room since the last call.
+        pub fn expire(&mut self) -> Vec<FragmentError> {
+            let deadline = clock::now_millis().saturating_sub(self.timeout.as_millis() as u64);
+            let expired: Vec<MessageKey> = self.pending
+                .iter()
+                .filter(|(_, partial)| partial.first_seen < deadline)
+                .map(|(key, _)| *key)
+                .collect();
+            let mut dropped = std::mem::take(&mut self.dropped);
+            for key in expired {
+                let partial = self.pending.remove(&key).unwrap();
+                self.buffered -= partial.bytes;
+                dropped.push(partial.incomplete(key.1));
+            }
+            dropped
+        }
+    }
+
+    impl Default for Reassembler {
+        fn default() -> Self {
+            Reassembler::new()
+        }
+    }
+}
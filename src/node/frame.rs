@@ -0,0 +1,54 @@
+pub mod frame {
+
+    use crate::node::protocol::protocol::ProtocolMsg;
+
+    use thiserror::Error;
+
+    /// Frame header: protocol byte and payload length.
+    pub const FRAME_HEADER_SIZE: usize = 1 + 4;
+
+    #[derive(Error, Debug)]
+    pub enum FrameError {
+        #[error("Malformed frame - {0} bytes is shorter than the frame header.")]
+        TooShort(usize),
+        #[error("Malformed frame - header announces {declared} bytes but {actual} were received.")]
+        LengthMismatch { declared: usize, actual: usize },
+    }
+
+    /// Builds a `[protocol][length][payload]` message, the length being that of the
+    /// payload as a big endian `u32`.
+    ///
+    /// # Arguments
+    /// * `protocol` - The kind of the message.
+    /// * `payload` - The payload, sent as is.
+    ///
+    /// # Returns
+    /// * `Vec<u8>` - The framed message.
+    pub fn encode(protocol: ProtocolMsg, payload: &[u8]) -> Vec<u8> {
+        let mut message = Vec::with_capacity(FRAME_HEADER_SIZE + payload.len());
+        message.push(protocol as u8);
+        message.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        message.extend_from_slice(payload);
+        message
+    }
+
+    /// Extracts the payload of a framed message, checking it is exactly as long as
+    /// its header says, so a truncated or padded message is never deserialized.
+    ///
+    /// # Arguments
+    /// * `message` - The received message, including the protocol byte.
+    ///
+    /// # Returns
+    /// * `Result<&[u8], FrameError>` - The payload, exactly as it was sent.
+    pub fn decode(message: &[u8]) -> Result<&[u8], FrameError> {
+        if message.len() < FRAME_HEADER_SIZE {
+            return Err(FrameError::TooShort(message.len()));
+        }
+        let declared = u32::from_be_bytes(message[1..FRAME_HEADER_SIZE].try_into().unwrap()) as usize;
+        let payload = &message[FRAME_HEADER_SIZE..];
+        if payload.len() != declared {
+            return Err(FrameError::LengthMismatch { declared, actual: payload.len() });
+        }
+        Ok(payload)
+    }
+}
@@ -1,28 +1,56 @@
 pub mod gossip {
     use crate::{Chain, Transaction};
-    use crate::node::neighbour::neighbour::{Neighbour, Role};
-    use crate::node::protocol::protocol;
+    use crate::record::record::record::Record;
+    use crate::transaction::batch::batch::BatchTransaction;
+    use crate::transaction::validation::validation::ValidationReport;
+    use crate::chain::chain::chain::ChainDigest;
+    use crate::chain::config::config::SignedCheckpoint;
+    use crate::chain::network::network::NetworkId;
+    use crate::node::neighbour::neighbour::{Neighbour, PeerInfo, Role};
+    use crate::node::protocol::protocol::ProtocolMsg;
+    use crate::node::status::status::{NodeStatus, PeerStatus};
+    use crate::node::filter::filter::{FilteredBlock, NotificationFilter};
+    use crate::node::pool::pool::{Job, PoolJoin, Share};
+    use crate::node::fragment::fragment::{self, FragmentError, Reassembler};
+    use crate::node::frame::frame;
+    use crate::node::decode::decode;
+    use crate::node::admission::admission::{self, Challenge, Ticket};
+    use crate::node::secure::secure::{Session, PUBLIC_KEY_LEN};
+    use crate::dht::lookup::lookup::FindResponse;
+    use crate::node::sync::sync::{BlocksResponse, HeadersResponse, RangeRequest, RangeResponse};
+    use crate::node::rpc::rpc::{self, PendingRequests, RpcError};
+    use crate::node::metrics::metrics;
+    use crate::node::registry::registry::Registration;
+    use crate::node::relay::relay::{Inventory, RelayedNeighbour};
+    use crate::node::outbound::outbound::{self, BATCHABLE_SIZE};
+    use crate::node::transport::transport::{self, Transport};
+    use crate::clock::clock::clock;
 
     use std::{
+        collections::HashMap,
         io::{Result as IOResult, Error as IOError},
-        sync::Arc,
+        net::SocketAddr,
+        ops::Range,
+        sync::{Arc, Mutex, OnceLock},
         time::Duration,
         str,
     };
 
-    use tokio::{
-        net::UdpSocket,
-        time::timeout,
-        io::ErrorKind,
-    };
+    use serde::{Deserialize, Serialize};
+    use tokio::io::ErrorKind;
     use uuid::Uuid;
     use thiserror::Error;
     use tracing::debug;
 
     // Constants
     pub const GOSSIP_INTERVAL: u64 = 3;
-    pub const UUID_LENGTH: usize = 36;
+    /// Longest interval (in seconds) gossip backs off to while the chain does not change.
+    pub const MAX_GOSSIP_INTERVAL: u64 = 24;
+    /// Time (in seconds) a node listens for gossip before moving on with its loop.
+    pub const LISTEN_TIMEOUT: u64 = 3;
     pub const MAX_DATAGRAM_SIZE: usize = 65507;
+    /// Greetings sent to a tracker, one per second, before giving up on it.
+    pub const GREET_RETRIES: usize = 3;
 
     /// Enum to represent potential errors in the gossip protocol.
     #[derive(Error, Debug, derive_more::From)]
@@ -31,51 +59,238 @@ pub mod gossip {
         IOError(IOError),
         #[error("Attempted to read and got would block.")]
         WouldBlock(ErrorKind),
+        #[error(transparent)]
+        FragmentError(FragmentError),
+    }
+
+    /// Payload of a `ProtocolMsg::Greet` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct Greeting {
+        /// The node greeting, with the address it advertises.
+        pub neighbour: Neighbour,
+        /// Proof of work over the challenge the tracker sent, once it sent one.
+        pub ticket: Option<Ticket>,
+        /// The network of the node greeting. Nodes that do not say are on `mainnet`.
+        #[serde(default)]
+        pub network: NetworkId,
+    }
+
+    /// Payload of a `ProtocolMsg::Welcome` message, answering a `ProtocolMsg::Greet`.
+    #[derive(Serialize, Deserialize)]
+    pub struct Welcome {
+        /// The id of the node greeted.
+        pub id: Uuid,
+        /// A random sample of the peers it knows, to connect to right away. Empty if
+        /// the greeter belongs to another network.
+        pub peers: Vec<Neighbour>,
+        /// The network of the node greeted. Nodes that do not say are on `mainnet`.
+        #[serde(default)]
+        pub network: NetworkId,
+    }
+
+    /// Sockets owned by running listeners, by address. See `listener::Listener`.
+    static SHARED_SOCKETS: OnceLock<Mutex<HashMap<String, Arc<dyn Transport>>>> = OnceLock::new();
+
+    fn shared_sockets() -> &'static Mutex<HashMap<String, Arc<dyn Transport>>> {
+        SHARED_SOCKETS.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    /// Makes messages sent from `address` go out through `socket`.
+    pub fn share_socket(address: &str, socket: Arc<dyn Transport>) {
+        shared_sockets().lock().unwrap().insert(address.to_string(), socket);
+    }
+
+    pub fn unshare_socket(address: &str) {
+        shared_sockets().lock().unwrap().remove(address);
+    }
+
+    /// Returns the socket to send from. While a listener owns `address` its socket is
+    /// reused, since the address cannot be bound twice; otherwise a new one is bound.
+    async fn bind(address: &str) -> IOResult<Arc<dyn Transport>> {
+        let shared = shared_sockets().lock().unwrap().get(address).cloned();
+        match shared {
+            Some(socket) => Ok(socket),
+            None => transport::bind(address).await,
+        }
     }
 
-    /// Sends a greeting message to a tracker to introduce a new neighbour.
+    /// Sends a message, splitting it into fragments if it does not fit in one datagram.
+    /// Messages up to `BATCHABLE_SIZE` bytes are queued in the outbox of the node sending
+    /// from `socket`, if it has one.
     ///
     /// # Arguments
-    /// * `address` - The address to bind the local UDP socket.
-    /// * `id` - The UUID of the new neighbour.
-    /// * `role` - The role of the neighbour (e.g., Tracker, Node).
-    /// * `tracker` - The address of the tracker to send the greeting to.
+    /// * `socket` - The local UDP socket to send from.
+    /// * `message` - The full message, starting with its protocol byte.
+    /// * `target` - The address to send the message to.
     ///
     /// # Returns
-    /// * `IOResult<Neighbour>` - The tracker as a `Neighbour` instance.
-    pub async fn greet(address: Arc<str>, id: Uuid, role: Role, tracker: &str) -> IOResult<Neighbour> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
-        let greeter = Neighbour { 
-            id, 
-            address: (*address.clone()).to_owned(), 
-            role 
-        };
-        let neighbour_str: String = serde_json::to_string(&greeter).unwrap();
-        let mut buffer = vec![protocol::GREET];
-        buffer.extend_from_slice(&neighbour_str.as_bytes());
+    /// * `IOResult<usize>` - The total number of bytes sent.
+    pub async fn send_message(socket: &dyn Transport, message: &[u8], target: &str) -> IOResult<usize> {
+        send(socket, message, target, true).await
+    }
 
-        let mut buffer_recv: [u8; UUID_LENGTH] = [0; UUID_LENGTH];
-        let mut retry = true;
+    /// Sends a message right away, ahead of the small messages queued for `target` in the
+    /// outbox. For handshakes, whose answer must not wait behind gossip sealed under the
+    /// session they replace.
+    ///
+    /// # Arguments
+    /// * `socket` - The local UDP socket to send from.
+    /// * `message` - The full message, starting with its protocol byte.
+    /// * `target` - The address to send the message to.
+    ///
+    /// # Returns
+    /// * `IOResult<usize>` - The total number of bytes sent.
+    pub async fn send_unqueued(socket: &dyn Transport, message: &[u8], target: &str) -> IOResult<usize> {
+        send(socket, message, target, false).await
+    }
 
-        while retry {
-            socket.send_to(&buffer, tracker).await?;
-            retry = match timeout(Duration::new(1, 0), socket.recv_from(&mut buffer_recv)).await {
-                Ok(_) => false,
-                Err(_) => {
-                    debug!("Retrying recv_from");
-                    true
+    async fn send(socket: &dyn Transport, message: &[u8], target: &str, queue: bool) -> IOResult<usize> {
+        let local = socket.local_addr().ok().map(|local| local.to_string());
+        let outbox = local.as_deref().and_then(outbound::lookup).filter(|_| queue);
+        let bytes_sent = match outbox {
+            Some(outbox) if message.len() <= BATCHABLE_SIZE => {
+                if !outbox.enqueue(target, message.to_vec()) {
+                    return Err(IOError::new(ErrorKind::WouldBlock, format!("send queue to {} is full", target)));
                 }
+                message.len()
+            },
+            _ => {
+                let datagrams = fragment::fragment(message)
+                    .map_err(|e| IOError::new(ErrorKind::InvalidInput, e))?;
+                let mut bytes_sent = 0;
+                for datagram in datagrams {
+                    bytes_sent += socket.send_to(&datagram, target).await?;
+                }
+                bytes_sent
+            },
+        };
+        if let Some(metrics) = local.as_deref().and_then(metrics::lookup) {
+            metrics.bytes_sent(target, message.first().copied().unwrap_or_default(), bytes_sent);
+        }
+        Ok(bytes_sent)
+    }
+
+    /// Receives the next complete message, reassembling fragmented ones.
+    ///
+    /// # Arguments
+    /// * `socket` - The local UDP socket to read from.
+    /// * `reassembler` - Holds fragments of messages that are still incomplete.
+    /// * `wait` - How long to wait for a complete message.
+    ///
+    /// # Returns
+    /// * `Result<Option<(Vec<u8>, SocketAddr)>, GossipError>` - The message and its sender, `None` on timeout,
+    ///   or `FragmentError::Incomplete` if a fragmented message expired, or was dropped to make
+    ///   room for others, before all its pieces arrived.
+    pub async fn recv_message(
+        socket: &dyn Transport,
+        reassembler: &mut Reassembler,
+        wait: Duration,
+    ) -> Result<Option<(Vec<u8>, SocketAddr)>, GossipError> {
+        let deadline = clock::now_millis() + wait.as_millis() as u64;
+        let mut buffer = vec![0u8; MAX_DATAGRAM_SIZE];
+        loop {
+            let (n_bytes, sender) = match clock::timeout(clock::until(deadline), socket.recv_from(&mut buffer)).await {
+                Ok(res) => res?,
+                Err(_) => {
+                    return match reassembler.expire().into_iter().next() {
+                        Some(e) => Err(e.into()),
+                        None => Ok(None),
+                    };
+                },
             };
+            if n_bytes == 0 {
+                continue;
+            }
+            if buffer[0] != ProtocolMsg::Fragment as u8 {
+                return Ok(Some((buffer[..n_bytes].to_vec(), sender)));
+            }
+            if let Some(message) = reassembler.push(sender, &buffer[..n_bytes])? {
+                return Ok(Some((message, sender)));
+            }
+            if let Some(e) = reassembler.expire().into_iter().next() {
+                return Err(e.into());
+            }
+        }
+    }
+
+    /// Sends a greeting message to a tracker to introduce a new neighbour. Trackers answer
+    /// the first greeting with a challenge, solved and sent back in the next one.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `greeter` - The new neighbour, with the address it advertises.
+    /// * `network` - The network of the new neighbour.
+    /// * `tracker` - The address of the tracker to send the greeting to.
+    ///
+    /// # Returns
+    /// * `IOResult<(Neighbour, Vec<Neighbour>)>` - The tracker as a `Neighbour` instance and the
+    ///   peers it suggested, `ErrorKind::TimedOut` if it did not answer any of `GREET_RETRIES` greetings
+    ///   or `ErrorKind::InvalidData` if it belongs to another network.
+    pub async fn greet(
+        address: Arc<str>,
+        greeter: &Neighbour,
+        network: &NetworkId,
+        tracker: &str,
+    ) -> IOResult<(Neighbour, Vec<Neighbour>)> {
+        let socket = bind(&address).await?;
+        let mut reassembler = Reassembler::new();
+        let mut ticket = None;
+        let mut answer = None;
+
+        for _ in 0..GREET_RETRIES {
+            let greeting = Greeting { neighbour: greeter.clone(), ticket: ticket.clone(), network: network.clone() };
+            let buffer = frame::encode(ProtocolMsg::Greet, &serde_json::to_vec(&greeting).unwrap());
+            send_message(&socket, &buffer, tracker).await?;
+            // Greetings the tracker read together are answered together, possibly batched.
+            let messages = match recv_message(&socket, &mut reassembler, Duration::new(1, 0)).await {
+                Ok(Some((message, _))) => outbound::unbatch(message),
+                Ok(None) => vec![],
+                Err(GossipError::IOError(e)) => return Err(e),
+                Err(e) => {
+                    debug!("Retrying greeting: {}", e);
+                    continue;
+                },
+            };
+            if let Some(welcome) = messages.iter().find(|message| message.first() == Some(&(ProtocolMsg::Welcome as u8))) {
+                answer = Some(welcome.clone());
+                break;
+            }
+            let Some(message) = messages.iter().find(|message| message.first() == Some(&(ProtocolMsg::Challenge as u8))) else {
+                debug!("Retrying greeting");
+                continue;
+            };
+            let challenge = frame::decode(message)
+                .ok()
+                .and_then(|payload| decode::message::<Challenge>(payload).ok());
+            let Some(challenge) = challenge else {
+                debug!("{} sent a malformed challenge", tracker);
+                continue;
+            };
+            let id = greeter.id;
+            ticket = tokio::task::spawn_blocking(move || admission::solve(&challenge, &id))
+                .await
+                .map_err(IOError::other)?;
+            if ticket.is_none() {
+                return Err(IOError::new(ErrorKind::InvalidData, format!("{} sent a challenge too hard to solve", tracker)));
+            }
         }
+        let Some(answer) = answer else {
+            return Err(IOError::new(ErrorKind::TimedOut, format!("{} did not answer the greeting", tracker)));
+        };
 
-        let str_id = str::from_utf8(&buffer_recv).unwrap();
+        let welcome = frame::decode(&answer)
+            .ok()
+            .and_then(|payload| decode::message::<Welcome>(payload).ok())
+            .ok_or_else(|| IOError::new(ErrorKind::InvalidData, format!("{} answered with a malformed welcome", tracker)))?;
+        if welcome.network != *network {
+            return Err(IOError::new(
+                ErrorKind::InvalidData,
+                format!("{} belongs to network {}, not {}", tracker, welcome.network, network),
+            ));
+        }
         debug!("New neighbour connected");
 
-        Ok(Neighbour {
-            id: Uuid::parse_str(str_id).unwrap(),
-            address: tracker.to_string(),
-            role: Role::Tracker,
-        })
+        Ok((Neighbour::new(welcome.id, tracker.to_string(), Role::Tracker), welcome.peers))
     }
 
     /// Sends a farewell message to a neighbour, indicating that it is leaving the network.
@@ -84,9 +299,202 @@ pub mod gossip {
     /// * `address` - The address to bind the local UDP socket.
     /// * `neighbour` - The address of the neighbour to send the farewell to.
     pub async fn farewell(address: Arc<str>, neighbour: String) -> IOResult<()> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
-        let buffer = [protocol::FAREWELL];
-        socket.send_to(&buffer, &neighbour).await?;
+        let socket = bind(&address).await?;
+        let buffer = [ProtocolMsg::Farewell as u8];
+        send_message(&socket, &buffer, &neighbour).await?;
+        Ok(())
+    }
+
+    /// Sends one side of the key exchange to a neighbour.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The address of the neighbour to send the public key to.
+    /// * `public_key` - This node's ephemeral public key.
+    /// * `initiator` - Whether this message starts the handshake or answers one.
+    /// * `info` - What this node tells about itself, sent after the key.
+    pub async fn send_handshake(
+        address: Arc<str>,
+        neighbour: String,
+        public_key: [u8; PUBLIC_KEY_LEN],
+        initiator: bool,
+        info: &PeerInfo,
+    ) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::Handshake as u8, initiator as u8];
+        buffer.extend_from_slice(&public_key);
+        buffer.extend_from_slice(&serde_json::to_vec(info).unwrap());
+        send_unqueued(&socket, &buffer, &neighbour).await?;
+        Ok(())
+    }
+
+    /// Sends a liveness probe to a neighbour, which is expected to answer with a `ProtocolMsg::Pong`.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to ping.
+    pub async fn ping(address: Arc<str>, neighbour: &Neighbour) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        send_message(&socket, &neighbour.seal(vec![ProtocolMsg::Ping as u8]), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Answers a ping from a neighbour.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour that sent the ping.
+    pub async fn pong(address: Arc<str>, neighbour: &Neighbour) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        send_message(&socket, &neighbour.seal(vec![ProtocolMsg::Pong as u8]), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Tells a tracker this node is still active.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `announcer` - This node, as it should be registered.
+    /// * `tracker` - The tracker to announce to.
+    pub async fn announce(address: Arc<str>, announcer: &Neighbour, tracker: &Neighbour) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_announcer = serde_json::to_string(announcer).unwrap();
+        let mut buffer = vec![ProtocolMsg::Announce as u8];
+        buffer.extend_from_slice(str_announcer.as_bytes());
+        send_message(&socket, &tracker.seal(buffer), &tracker.address).await?;
+        Ok(())
+    }
+
+    /// Replicates a tracker's registrations to another tracker.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `tracker` - The tracker to replicate to.
+    /// * `registrations` - Every registration of this tracker.
+    pub async fn send_registry(address: Arc<str>, tracker: &Neighbour, registrations: &[Registration]) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::Registry as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(registrations).unwrap());
+        send_message(&socket, &tracker.seal(buffer), &tracker.address).await?;
+        Ok(())
+    }
+
+    /// Tells a neighbour this node changed its role.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to tell.
+    /// * `role` - The new role of this node.
+    pub async fn send_role(address: Arc<str>, neighbour: &Neighbour, role: Role) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let buffer = vec![ProtocolMsg::Role as u8, role.to_protocol() as u8];
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Asks a tracker for a sample of its registered peers. The answer arrives as a
+    /// `ProtocolMsg::Peers` message.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `tracker` - The tracker to ask.
+    pub async fn request_peers(address: Arc<str>, tracker: &Neighbour) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        send_message(&socket, &tracker.seal(vec![ProtocolMsg::PeerList as u8]), &tracker.address).await?;
+        Ok(())
+    }
+
+    /// Sends a list of peers to a neighbour.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to send the peers to.
+    /// * `peers` - The peers to be sent.
+    pub async fn send_peers(address: Arc<str>, neighbour: &Neighbour, peers: Vec<Neighbour>) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_peers = serde_json::to_string(&peers).unwrap();
+        let mut buffer = vec![ProtocolMsg::Peers as u8];
+        buffer.extend_from_slice(str_peers.as_bytes());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Answers a `ProtocolMsg::DhtFind` query. Lookups run on their own socket with no
+    /// session attached, so the answer is sent in the clear.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `requester` - The address the query came from.
+    /// * `response` - The providers and closer contacts known for the queried key.
+    pub async fn send_dht_found(address: Arc<str>, requester: &str, response: &FindResponse) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_response = serde_json::to_string(response).unwrap();
+        let mut buffer = vec![ProtocolMsg::DhtFound as u8];
+        buffer.extend_from_slice(str_response.as_bytes());
+        send_message(&socket, &buffer, requester).await?;
+        Ok(())
+    }
+
+    /// Answers a `ProtocolMsg::GetHeaders` request. Like DHT lookups, syncs run on their own
+    /// socket, so the answer is sent in the clear.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `requester` - The address the request came from.
+    /// * `response` - The headers of this node's chain.
+    pub async fn send_headers(address: Arc<str>, requester: &str, response: &HeadersResponse) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_response = serde_json::to_string(response).unwrap();
+        let mut buffer = vec![ProtocolMsg::Headers as u8];
+        buffer.extend_from_slice(str_response.as_bytes());
+        send_message(&socket, &buffer, requester).await?;
+        Ok(())
+    }
+
+    /// Answers a `ProtocolMsg::GetBlocks` request.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `requester` - The address the request came from.
+    /// * `response` - The blocks requested.
+    pub async fn send_blocks(address: Arc<str>, requester: &str, response: &BlocksResponse) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_response = serde_json::to_string(response).unwrap();
+        let mut buffer = vec![ProtocolMsg::Blocks as u8];
+        buffer.extend_from_slice(str_response.as_bytes());
+        send_message(&socket, &buffer, requester).await?;
+        Ok(())
+    }
+
+    /// Announces transactions to a neighbour by id. The neighbour asks for the ones it has
+    /// not seen with a `ProtocolMsg::GetData` message.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to announce the transactions to.
+    /// * `ids` - The ids of the transactions.
+    /// * `ttl` - Times the neighbour may still forward them.
+    pub async fn send_inventory(address: Arc<str>, neighbour: &Neighbour, ids: Vec<String>, ttl: u8) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let inventory = serde_json::to_string(&Inventory { ids, ttl }).unwrap();
+        let mut buffer = vec![ProtocolMsg::Inv as u8];
+        buffer.extend_from_slice(inventory.as_bytes());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Asks a neighbour for the transactions it announced and this node has not seen.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour that announced the transactions.
+    /// * `ids` - The ids of the transactions wanted.
+    pub async fn request_data(address: Arc<str>, neighbour: &Neighbour, ids: Vec<String>) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_ids = serde_json::to_string(&ids).unwrap();
+        let mut buffer = vec![ProtocolMsg::GetData as u8];
+        buffer.extend_from_slice(str_ids.as_bytes());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
         Ok(())
     }
 
@@ -94,125 +502,367 @@ pub mod gossip {
     ///
     /// # Arguments
     /// * `address` - The address to bind the local UDP socket.
-    /// * `miner` - The address of the miner to send the transaction to.
+    /// * `miner` - The miner to send the transaction to.
     /// * `transaction` - The transaction to be sent.
-    pub async fn send_transaction(address: Arc<str>, miner: String, transaction: Transaction) -> IOResult<()> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
+    pub async fn send_transaction(address: Arc<str>, miner: &Neighbour, transaction: Transaction) -> IOResult<()> {
+        let socket = bind(&address).await?;
         let str_transaction: String = transaction.into();
-        let mut buffer = vec![protocol::TRANSACTION];
+        let mut buffer = vec![ProtocolMsg::Transaction as u8];
         buffer.extend_from_slice(&str_transaction.as_bytes());
-        socket.send_to(&buffer, &miner).await?;
+        send_message(&socket, &miner.seal(buffer), &miner.address).await?;
+        Ok(())
+    }
+
+    /// Sends a record to a miner so it gets included in a block.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `miner` - The miner to send the record to.
+    /// * `record` - The record to be sent.
+    pub async fn send_record(address: Arc<str>, miner: &Neighbour, record: Record) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_record: String = record.into();
+        let mut buffer = vec![ProtocolMsg::Record as u8];
+        buffer.extend_from_slice(str_record.as_bytes());
+        send_message(&socket, &miner.seal(buffer), &miner.address).await?;
+        Ok(())
+    }
+
+    /// Sends a batch of transfers to a miner so it gets included in a block.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `miner` - The miner to send the batch to.
+    /// * `batch` - The batch to be sent.
+    pub async fn send_batch(address: Arc<str>, miner: &Neighbour, batch: &BatchTransaction) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::BatchTransaction as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(batch).unwrap());
+        send_message(&socket, &miner.seal(buffer), &miner.address).await?;
+        Ok(())
+    }
+
+    /// Loads a notification filter on a neighbour, which then relays filtered blocks
+    /// rather than whole chains. An empty filter clears the one loaded.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to load the filter on.
+    /// * `filter` - The addresses and record keys to hear about.
+    pub async fn load_filter(address: Arc<str>, neighbour: &Neighbour, filter: &NotificationFilter) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::FilterLoad as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(filter).unwrap());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Sends a new block, filtered, to a neighbour that loaded a filter.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour that loaded the filter.
+    /// * `block` - The block, with only the entries matching the filter.
+    pub async fn send_filtered_block(address: Arc<str>, neighbour: &Neighbour, block: &FilteredBlock) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::FilteredBlock as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(block).unwrap());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Sends a signed checkpoint to a neighbour, which pins it if it trusts the publisher.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to send the checkpoint to.
+    /// * `checkpoint` - The checkpoint, signed by its publisher.
+    pub async fn send_checkpoint(address: Arc<str>, neighbour: &Neighbour, checkpoint: &SignedCheckpoint) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::Checkpoint as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(checkpoint).unwrap());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Asks a pool coordinator for work.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `coordinator` - The coordinator, a neighbour.
+    /// * `join` - Where the worker's payouts go.
+    pub async fn join_pool(address: Arc<str>, coordinator: &Neighbour, join: &PoolJoin) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::PoolJoin as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(join).unwrap());
+        send_message(&socket, &coordinator.seal(buffer), &coordinator.address).await?;
+        Ok(())
+    }
+
+    /// Hands out a job to a worker of the pool.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `worker` - The worker, a neighbour.
+    /// * `job` - The block to mine on, and the nonces to search.
+    pub async fn send_job(address: Arc<str>, worker: &Neighbour, job: &Job) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::PoolJob as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(job).unwrap());
+        send_message(&socket, &worker.seal(buffer), &worker.address).await?;
         Ok(())
     }
 
-    /// Requests a copy of the blockchain from a neighbour.
+    /// Reports a share to the pool coordinator.
     ///
     /// # Arguments
     /// * `address` - The address to bind the local UDP socket.
-    /// * `neighbour` - The neighbour to request the chain from.
+    /// * `coordinator` - The coordinator, a neighbour.
+    /// * `share` - The job and the nonce found for it.
+    pub async fn send_share(address: Arc<str>, coordinator: &Neighbour, share: &Share) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let mut buffer = vec![ProtocolMsg::PoolShare as u8];
+        buffer.extend_from_slice(&serde_json::to_vec(share).unwrap());
+        send_message(&socket, &coordinator.seal(buffer), &coordinator.address).await?;
+        Ok(())
+    }
+
+    /// Asks a neighbour for its chain and waits for the answer to that request.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to ask.
+    /// * `pending` - The pending requests of the listener running on `address`.
     ///
     /// # Returns
-    /// * `IOResult<Chain>` - The chain received from the neighbour.
-    pub async fn poll_chain(address: Arc<str>, neighbour: &Neighbour) -> IOResult<Chain> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
-        let buffer = [protocol::POLLCHAIN];
-        socket.send_to(&buffer, &neighbour.address).await?;
+    /// * `Result<Chain, RpcError>` - The neighbour's chain, `RpcError::Timeout` if it did not
+    ///   answer, or `ErrorKind::InvalidData` if it answered with something else.
+    pub async fn poll_chain(address: Arc<str>, neighbour: &Neighbour, pending: &PendingRequests) -> Result<Chain, RpcError> {
+        let socket = bind(&address).await?;
+        let answer = rpc::request(&socket, pending, neighbour, |id| {
+            rpc::encode(ProtocolMsg::PollChain, id, &[])
+        }).await?;
+        decode::chain(&answer)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e).into())
+    }
+
+    /// Asks a node for the blocks of index within `range`, of which it answers with the
+    /// first `BLOCKS_PER_REQUEST` it holds. See `sync::fetch_range`.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The node to ask.
+    /// * `pending` - The pending requests of the listener running on `address`.
+    /// * `range` - The indices of the blocks wanted.
+    pub async fn get_range(
+        address: Arc<str>,
+        neighbour: &Neighbour,
+        pending: &PendingRequests,
+        range: Range<usize>,
+    ) -> Result<RangeResponse, RpcError> {
+        let socket = bind(&address).await?;
+        let request = serde_json::to_vec(&RangeRequest { start: range.start, end: range.end }).unwrap();
+        let answer = rpc::request(&socket, pending, neighbour, |id| {
+            rpc::encode(ProtocolMsg::GetRange, id, &request)
+        }).await?;
+        decode::range(&answer)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e).into())
+    }
 
-        let mut recv_buffer: [u8; MAX_DATAGRAM_SIZE] = [0; MAX_DATAGRAM_SIZE];
-        socket.recv_from(&mut recv_buffer).await?;
+    /// Asks a node to run every check on a transaction without relaying it.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The node to ask.
+    /// * `pending` - The pending requests of the listener running on `address`.
+    /// * `transaction` - The transaction to check.
+    pub async fn validate_transaction(
+        address: Arc<str>,
+        neighbour: &Neighbour,
+        pending: &PendingRequests,
+        transaction: Transaction,
+    ) -> Result<ValidationReport, RpcError> {
+        let socket = bind(&address).await?;
+        let str_transaction: String = transaction.into();
+        let answer = rpc::request(&socket, pending, neighbour, |id| {
+            rpc::encode(ProtocolMsg::Validate, id, str_transaction.as_bytes())
+        }).await?;
+        decode::message(&answer)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e).into())
+    }
+
+    /// Asks a node what it tells about itself. See `Node::status`.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The node to ask.
+    /// * `pending` - The pending requests of the listener running on `address`.
+    pub async fn get_node_info(address: Arc<str>, neighbour: &Neighbour, pending: &PendingRequests) -> Result<NodeStatus, RpcError> {
+        let socket = bind(&address).await?;
+        let answer = rpc::request(&socket, pending, neighbour, |id| {
+            rpc::encode(ProtocolMsg::NodeInfo, id, &[])
+        }).await?;
+        decode::message(&answer)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e).into())
+    }
+
+    /// Asks a node what it knows about each of its neighbours. See `Node::peer_statuses`.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The node to ask.
+    /// * `pending` - The pending requests of the listener running on `address`.
+    pub async fn get_peer_info(address: Arc<str>, neighbour: &Neighbour, pending: &PendingRequests) -> Result<Vec<PeerStatus>, RpcError> {
+        let socket = bind(&address).await?;
+        let answer = rpc::request(&socket, pending, neighbour, |id| {
+            rpc::encode(ProtocolMsg::PeerInfo, id, &[])
+        }).await?;
+        decode::message(&answer)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e).into())
+    }
+
+    /// Asks a miner for the transactions waiting to be mined.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The miner to ask.
+    /// * `pending` - The pending requests of the listener running on `address`.
+    /// * `transaction_id` - The id of the only transaction to return, all of them if `None`.
+    pub async fn get_mempool(
+        address: Arc<str>,
+        neighbour: &Neighbour,
+        pending: &PendingRequests,
+        transaction_id: Option<&str>,
+    ) -> Result<Vec<Transaction>, RpcError> {
+        let socket = bind(&address).await?;
+        let filter = transaction_id.unwrap_or_default().as_bytes();
+        let answer = rpc::request(&socket, pending, neighbour, |id| {
+            rpc::encode(ProtocolMsg::Mempool, id, filter)
+        }).await?;
+        let wire: Vec<String> = decode::json(&answer, decode::MAX_CHAIN_SIZE)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e))?;
+        wire.into_iter()
+            .map(|transaction| decode::transaction(transaction.as_bytes()).map_err(|e| IOError::new(ErrorKind::InvalidData, e).into()))
+            .collect()
+    }
 
-        let chain_str = str::from_utf8(&recv_buffer).unwrap();
-        Ok(serde_json::from_str(&chain_str).unwrap())
+    /// Answers a request, sealed if it arrived sealed. The listener of the requester opens
+    /// it with the session of the peer it asked.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `requester` - The address the request came from.
+    /// * `session` - The session shared with the requester, if the request was sealed.
+    /// * `id` - The id of the request.
+    /// * `payload` - The answer.
+    pub async fn respond(address: Arc<str>, requester: &str, session: Option<&Session>, id: &Uuid, payload: &[u8]) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let buffer = rpc::encode(ProtocolMsg::Response, id, payload);
+        let buffer = match session {
+            Some(session) => session.seal(&buffer),
+            None => buffer,
+        };
+        send_message(&socket, &buffer, requester).await?;
+        Ok(())
     }
 
     /// Sends a copy of the blockchain to a specified neighbour.
     ///
     /// # Arguments
     /// * `address` - The address to bind the local UDP socket.
-    /// * `neighbour` - The address of the neighbour to send the chain to.
+    /// * `neighbour` - The neighbour to send the chain to.
     /// * `chain` - The blockchain to be sent.
-    pub async fn send_chain(address: Arc<str>, neighbour: String, chain: Chain) -> IOResult<()> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
-        let str_chain = serde_json::to_string(&chain).unwrap();
-        let mut buffer = vec![protocol::CHAIN];
-        buffer.extend_from_slice(&str_chain.as_bytes());
-        socket.send_to(&buffer, &neighbour).await?;
+    pub async fn send_chain(address: Arc<str>, neighbour: &Neighbour, chain: Chain) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let buffer = frame::encode(ProtocolMsg::Chain, &serde_json::to_vec(&chain).unwrap());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Tells a neighbour the height and tip of this node's chain. The neighbour answers
+    /// with a `ProtocolMsg::Request` if it wants the whole chain.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour to send the digest to.
+    /// * `digest` - The digest of this node's chain.
+    pub async fn summarize(address: Arc<str>, neighbour: &Neighbour, digest: &ChainDigest) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let str_digest = serde_json::to_string(digest).unwrap();
+        let mut buffer = vec![ProtocolMsg::Summarize as u8];
+        buffer.extend_from_slice(str_digest.as_bytes());
+        send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+        Ok(())
+    }
+
+    /// Asks a neighbour whose digest announced a longer chain for the whole chain.
+    ///
+    /// # Arguments
+    /// * `address` - The address to bind the local UDP socket.
+    /// * `neighbour` - The neighbour that sent the digest.
+    pub async fn request_chain(address: Arc<str>, neighbour: &Neighbour) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        send_message(&socket, &neighbour.seal(vec![ProtocolMsg::Request as u8]), &neighbour.address).await?;
         Ok(())
     }
 
     /// Sends new neighbours information to a specific neighbour.
     ///
     /// # Arguments
-    /// * `neighbour_id` - The UUID of the neighbour to send to.
-    /// * `neighbour_address` - The address of the neighbour.
+    /// * `neighbour` - The neighbour to send to.
     /// * `address` - The local address to bind the socket.
-    /// * `new_neighbours` - The list of new neighbours to be sent.
+    /// * `new_neighbours` - The list of new neighbours to be sent, with their forwards left.
     pub async fn send_new_neighbours(
-        neighbour_id: Uuid,
-        neighbour_address: String,
+        neighbour: &Neighbour,
         address: Arc<str>,
-        new_neighbours: Vec<Neighbour>,
+        new_neighbours: Vec<RelayedNeighbour>,
     ) -> IOResult<()> {
         for new_neighbour in new_neighbours {
-            if new_neighbour.id == neighbour_id {
+            if new_neighbour.neighbour.id == neighbour.id {
                 continue;
             }
 
-            debug!("Sending neighbour {} to {}", new_neighbour.id, neighbour_id);
+            debug!("Sending neighbour {} to {}", new_neighbour.neighbour.id, neighbour.id);
 
-            let socket = UdpSocket::bind(address.as_ref()).await?;
-            let str_neighbour = serde_json::to_string(&new_neighbour).unwrap();
-            let mut buffer = vec![protocol::NEIGHBOUR];
-            buffer.extend_from_slice(&str_neighbour.as_bytes());
+            let socket = bind(&address).await?;
+            let buffer = frame::encode(ProtocolMsg::Neighbour, &serde_json::to_vec(&new_neighbour).unwrap());
 
-            let bytes_sent = socket.send_to(&buffer, &neighbour_address).await?;
-            debug!("Sent {} bytes to {}", bytes_sent, neighbour_address);
+            let bytes_sent = send_message(&socket, &neighbour.seal(buffer), &neighbour.address).await?;
+            debug!("Sent {} bytes to {}", bytes_sent, neighbour.address);
         }
         Ok(())
     }
 
     /// Pauses the execution for the duration of the gossip interval.
-    pub async fn wait_gossip_interval() {
-        tokio::time::sleep(Duration::new(GOSSIP_INTERVAL, 0)).await;
+    pub async fn wait_gossip_interval(interval: Duration) {
+        clock::sleep(interval).await;
     }
 
-    /// Listens for incoming gossip messages on the specified address.
+    /// Asks a greeter to prove some work before being admitted.
     ///
     /// # Arguments
     /// * `address` - The address to bind the UDP socket.
-    ///
-    /// # Returns
-    /// * `Result<Option<(u8, String, Vec<u8>)>, GossipError>` - The gossip message protocol, sender, and data.
-    pub async fn listen_to_gossip(address: Arc<str>) -> Result<Option<(u8, String, Vec<u8>)>, GossipError> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
-        let mut buffer: [u8; MAX_DATAGRAM_SIZE] = [0; MAX_DATAGRAM_SIZE];
-
-        debug!("Listening for gossip...");
-
-        let (n_bytes, sender) = match timeout(Duration::new(3, 0), socket.recv_from(&mut buffer)).await {
-            Ok(Ok((n_bytes, sender))) => (n_bytes, sender),
-            _ => {
-                debug!("Got nothing here");
-                return Ok(None);
-            },
-        };
-
-        let protocol_type = buffer[0];
-        debug!("Received protocol: {}", protocol_type);
-
-        Ok(Some((protocol_type, sender.to_string(), buffer[..n_bytes].to_vec())))
+    /// * `challenge` - The challenge to solve.
+    /// * `sender` - The address of the greeter.
+    pub async fn challenge(address: Arc<str>, challenge: &Challenge, sender: &str) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let challenge = serde_json::to_vec(challenge).unwrap();
+        send_message(&socket, &frame::encode(ProtocolMsg::Challenge, &challenge), sender).await?;
+        Ok(())
     }
 
-    /// Sends the UUID of the current node to the sender of a message.
+    /// Answers a greeting with the UUID of the current node and a sample of its peers.
     ///
     /// # Arguments
     /// * `address` - The address to bind the UDP socket.
     /// * `id` - The UUID to be sent.
-    /// * `sender` - The address of the sender to send the UUID to.
-    pub async fn send_id(address: Arc<str>, id: Uuid, sender: String) -> IOResult<()> {
-        let socket = UdpSocket::bind(address.as_ref()).await?;
-        let id_str = id.to_string();
-        socket.send_to(id_str.as_bytes(), &sender).await?;
+    /// * `peers` - The peers suggested to the greeter.
+    /// * `network` - The network of the current node.
+    /// * `sender` - The address of the greeter.
+    pub async fn welcome(address: Arc<str>, id: Uuid, peers: Vec<Neighbour>, network: NetworkId, sender: String) -> IOResult<()> {
+        let socket = bind(&address).await?;
+        let welcome = serde_json::to_vec(&Welcome { id, peers, network }).unwrap();
+        send_message(&socket, &frame::encode(ProtocolMsg::Welcome, &welcome), &sender).await?;
         Ok(())
     }
 }
@@ -0,0 +1,134 @@
+pub mod identity {
+
+    use crate::{
+        chain::network::network::NetworkId,
+        node::secure::secure::PUBLIC_KEY_LEN,
+        store::store::store::{Store, StoreError},
+        wallet::wallet::wallet::Wallet,
+    };
+
+    use ring::{
+        rand::SystemRandom,
+        signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1},
+    };
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use thiserror::Error;
+    use uuid::Uuid;
+    use zeroize::Zeroizing;
+
+    /// Namespace of the peer store the identity key pair is saved to.
+    pub const IDENTITY_NAMESPACE: &str = "identity";
+    /// Starts the bytes an identity signs during a handshake, so they never match the
+    /// ones of a transaction or a batch.
+    const HANDSHAKE_TAG: &[u8] = b"handshake\0";
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum IdentityError {
+        #[error("Failed to read or save the node identity: {0}")]
+        StoreError(StoreError),
+        #[from(ignore)]
+        #[error("The saved node identity does not hold a key pair.")]
+        Corrupted,
+    }
+
+    /// The key pair a node is known by. Its id is derived from the public key, so a node
+    /// saving its identity keeps its id across restarts, and peers recognize it.
+    pub struct Identity {
+        key: Wallet,
+        rng: SystemRandom,
+    }
+
+    impl Default for Identity {
+        fn default() -> Self {
+            Identity::new()
+        }
+    }
+
+    impl Identity {
+        /// Creates an identity with a fresh key pair, lost when the node stops.
+        pub fn new() -> Self {
+            Identity::with_key(Wallet::new())
+        }
+
+        fn with_key(key: Wallet) -> Self {
+            Identity { key, rng: SystemRandom::new() }
+        }
+
+        /// Loads the identity saved to the `IDENTITY_NAMESPACE` namespace of `store`,
+        /// creating and saving one the first time.
+        ///
+        /// # Returns
+        /// * `Result<Identity, IdentityError>` - The identity, or an error if the saved
+        ///   one is corrupted or the store fails.
+        pub fn load_or_create(store: &mut Store) -> Result<Self, IdentityError> {
+            let store = store.namespace(IDENTITY_NAMESPACE)?;
+            if store.exists()? {
+                let encoded: Zeroizing<String> = Zeroizing::new(store.load()?);
+                let key = Wallet::from_base64(&encoded).ok_or(IdentityError::Corrupted)?;
+                return Ok(Identity::with_key(key));
+            }
+            let identity = Identity::new();
+            store.save(&*identity.key.to_base64())?;
+            store.flush()?;
+            Ok(identity)
+        }
+
+        pub fn public_key(&self) -> Vec<u8> {
+            self.key.get_pub_key()
+        }
+
+        /// The id of the node. See `id_of`.
+        pub fn id(&self) -> Uuid {
+            id_of(&self.public_key())
+        }
+
+        /// Proves this identity owns `ephemeral_key`, the key it sends in a handshake
+        /// for `network`.
+        pub fn prove(&self, ephemeral_key: &[u8; PUBLIC_KEY_LEN], network: &NetworkId) -> IdentityProof {
+            let bytes = handshake_bytes(ephemeral_key, network);
+            let signature = self.key.key_pair.sign(&self.rng, &bytes).unwrap().as_ref().to_vec();
+            IdentityProof {
+                public_key: self.public_key(),
+                signature,
+            }
+        }
+    }
+
+    /// Derives a node id from the public key of its identity: the first 16 bytes of its
+    /// SHA-256, so ids stay spread evenly for routing.
+    pub fn id_of(public_key: &[u8]) -> Uuid {
+        let digest = Sha256::digest(public_key);
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+        Uuid::from_bytes(bytes)
+    }
+
+    /// The bytes signed to prove ownership of a handshake key: the NUL terminated id of
+    /// the network, `HANDSHAKE_TAG`, then the key.
+    fn handshake_bytes(ephemeral_key: &[u8], network: &NetworkId) -> Vec<u8> {
+        [network.as_str().as_bytes(), &[0], HANDSHAKE_TAG, ephemeral_key].concat()
+    }
+
+    /// Sent along a handshake key: the identity of the sender, and its signature of the
+    /// key. See `Identity::prove`.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct IdentityProof {
+        pub public_key: Vec<u8>,
+        pub signature: Vec<u8>,
+    }
+
+    impl IdentityProof {
+        /// The id of the node proving its identity.
+        pub fn id(&self) -> Uuid {
+            id_of(&self.public_key)
+        }
+
+        /// Whether the proof carries a signature of `ephemeral_key` for `network`.
+        pub fn verify(&self, ephemeral_key: &[u8], network: &NetworkId) -> bool {
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &self.public_key)
+                .verify(&handshake_bytes(ephemeral_key, network), &self.signature)
+                .is_ok()
+        }
+    }
+}
@@ -0,0 +1,153 @@
+pub mod listener {
+
+    use crate::node::{
+        fragment::fragment::{Reassembler, REASSEMBLY_TIMEOUT},
+        gossip::gossip::{self, GossipError},
+        protocol::protocol::ProtocolMsg,
+        rpc::rpc::PendingRequests,
+        outbound::outbound::{self, Outbox},
+        transport::transport::{self, Transport},
+        metrics::metrics::{self, Metrics},
+    };
+
+    use std::{
+        io::Result as IOResult,
+        sync::Arc,
+        time::Duration,
+    };
+
+    use tokio::{
+        sync::mpsc::{self, error::TrySendError},
+        task::JoinHandle,
+    };
+    use tracing::{debug, warn};
+
+    /// Messages the listener queues for the node before dropping new ones.
+    pub const LISTENER_CHANNEL_CAPACITY: usize = 1024;
+
+    /// A complete message, reassembled if it arrived in fragments.
+    pub struct Incoming {
+        pub protocol: u8,
+        pub sender: String,
+        pub buffer: Vec<u8>,
+    }
+
+    /// A task owning the node's socket for as long as the node runs. It reads
+    /// continuously, so datagrams are no longer lost between two listening rounds, and
+    /// hands every complete message to the node over a channel. Answers to pending
+    /// requests, sealed or not, are handed straight to whoever is waiting for them.
+    pub struct Listener {
+        address: Arc<str>,
+        pending: PendingRequests,
+        task: JoinHandle<()>,
+    }
+
+    impl Listener {
+        /// Binds `address` and starts reading from it. Until the listener is dropped,
+        /// messages sent through `gossip` from this address go out through its socket,
+        /// small ones through an `Outbox`, and the traffic on it is counted in `metrics`.
+        ///
+        /// # Arguments
+        /// * `address` - The address to listen on.
+        /// * `metrics` - The metrics of the node listening.
+        /// * `peer_send_rate` - Messages per second the outbox sends to each peer.
+        ///
+        /// # Returns
+        /// * `(Listener, mpsc::Receiver<Incoming>)` - The listener and the messages it receives.
+        pub async fn spawn(address: Arc<str>, metrics: Arc<Metrics>, peer_send_rate: u32) -> IOResult<(Listener, mpsc::Receiver<Incoming>)> {
+            let socket = transport::bind(&address).await?;
+            gossip::share_socket(&address, socket.clone());
+            metrics::register(&address, metrics.clone());
+            outbound::register(&address, Arc::new(Outbox::spawn(socket.clone(), peer_send_rate)));
+            let (sender, receiver) = mpsc::channel(LISTENER_CHANNEL_CAPACITY);
+            let pending = PendingRequests::new();
+            let task = tokio::spawn(listen(socket, sender, pending.clone(), metrics));
+            Ok((Listener { address, pending, task }, receiver))
+        }
+
+        /// Returns the requests waiting for an answer on this listener's address.
+        pub fn pending(&self) -> PendingRequests {
+            self.pending.clone()
+        }
+
+        /// Stops listening and closes the socket: once this returns, the address can be
+        /// bound again. Dropping the listener stops it too, but the socket may outlive it
+        /// until the runtime drops the tasks using it.
+        pub async fn close(mut self) {
+            self.task.abort();
+            let _ = (&mut self.task).await;
+            gossip::unshare_socket(&self.address);
+            if let Some(outbox) = outbound::unregister(&self.address).and_then(Arc::into_inner) {
+                outbox.close().await;
+            }
+        }
+    }
+
+    impl Drop for Listener {
+        fn drop(&mut self) {
+            self.task.abort();
+            gossip::unshare_socket(&self.address);
+            metrics::unregister(&self.address);
+            outbound::unregister(&self.address);
+        }
+    }
+
+    async fn listen(
+        socket: Arc<dyn Transport>,
+        messages: mpsc::Sender<Incoming>,
+        pending: PendingRequests,
+        metrics: Arc<Metrics>,
+    ) {
+        let mut reassembler = Reassembler::new();
+        let wait = Duration::new(REASSEMBLY_TIMEOUT, 0);
+        loop {
+            let (buffer, sender) = match gossip::recv_message(&socket, &mut reassembler, wait).await {
+                Ok(Some(message)) => message,
+                Ok(None) => continue,
+                Err(GossipError::FragmentError(e)) => {
+                    warn!("Listener dropped a message: {}", e);
+                    continue;
+                },
+                Err(e) => {
+                    debug!("Listener failed to read: {}", e);
+                    continue;
+                },
+            };
+            let unbatched = match ProtocolMsg::try_from(buffer[0]) {
+                Ok(ProtocolMsg::Batch) => match outbound::unpack(&buffer) {
+                    Some(unbatched) => unbatched,
+                    None => {
+                        debug!("Listener dropped a malformed batch from {}", sender);
+                        continue;
+                    },
+                },
+                _ => vec![buffer],
+            };
+            for buffer in unbatched {
+                metrics.bytes_received(&sender.to_string(), buffer[0], buffer.len());
+                let buffer = match ProtocolMsg::try_from(buffer[0]) {
+                    Ok(ProtocolMsg::Encrypted) => pending.open(&sender.to_string(), &buffer).unwrap_or(buffer),
+                    _ => buffer,
+                };
+                if buffer[0] == ProtocolMsg::Response as u8 {
+                    if !pending.resolve(&buffer) {
+                        debug!("Listener dropped an unexpected response from {}", sender);
+                    }
+                    continue;
+                }
+                let incoming = Incoming {
+                    protocol: buffer[0],
+                    sender: sender.to_string(),
+                    buffer,
+                };
+                match messages.try_send(incoming) {
+                    Ok(()) => (),
+                    Err(TrySendError::Full(incoming)) => {
+                        warn!("Listener queue is full, dropping a message from {}", incoming.sender);
+                    },
+                    Err(TrySendError::Closed(_)) => return,
+                }
+            }
+        }
+    }
+}
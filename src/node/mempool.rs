@@ -0,0 +1,60 @@
+pub mod mempool {
+
+    use crate::{
+        Transaction,
+        miner::miner::miner::Miner,
+        record::record::record::Record,
+        transaction::batch::batch::BatchTransaction,
+    };
+
+    use serde::{Deserialize, Serialize};
+
+    /// Namespace of the chain store a miner's mempool is saved to when its node panics.
+    pub const MEMPOOL_NAMESPACE: &str = "mempool";
+
+    /// What a miner had yet to mine, saved when its node panics so it is mined after a
+    /// restart. See `Node::load_mempool`.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct PendingEntries {
+        /// The transactions, in wire format.
+        pub transactions: Vec<String>,
+        pub records: Vec<Record>,
+        pub batches: Vec<BatchTransaction>,
+    }
+
+    impl PendingEntries {
+        /// Takes a copy of what `miner` queued.
+        pub fn of(miner: &Miner) -> Self {
+            PendingEntries {
+                transactions: miner.transactions.iter().cloned().map(Into::into).collect(),
+                records: miner.records.clone(),
+                batches: miner.batches.clone(),
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.transactions.len() + self.records.len() + self.batches.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Queues the entries on `miner`, skipping the transactions that fail to decode.
+        /// The ones mined meanwhile are dropped by the miner's checks.
+        ///
+        /// # Returns
+        /// * `usize` - The number of entries queued.
+        pub fn restore(self, miner: &mut Miner) -> usize {
+            let mut restored = 0;
+            for transaction in self.transactions.into_iter().filter_map(|wire| Transaction::try_from(wire).ok()) {
+                miner.push_transaction(transaction);
+                restored += 1;
+            }
+            restored += self.records.len() + self.batches.len();
+            self.records.into_iter().for_each(|record| miner.push_record(record));
+            self.batches.into_iter().for_each(|batch| miner.push_batch(batch));
+            restored
+        }
+    }
+}
@@ -0,0 +1,296 @@
+pub mod metrics {
+
+    use crate::{
+        node::protocol::protocol,
+        store::persistence::persistence::PersistenceStats,
+    };
+
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fmt::Write,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, OnceLock,
+        },
+    };
+
+    use serde::Serialize;
+
+    /// Peers whose traffic is counted separately. Traffic with any other peer is counted under `OTHER_PEERS`.
+    pub const MAX_TRACKED_PEERS: usize = 1024;
+    /// Label of the traffic with peers over `MAX_TRACKED_PEERS`.
+    pub const OTHER_PEERS: &str = "other";
+    /// Age (in seconds) of the last block past which a node is no longer considered synced.
+    pub const DEFAULT_MAX_TIP_AGE: u64 = 600;
+
+    /// Bytes exchanged in each direction.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Traffic {
+        pub bytes_in: u64,
+        pub bytes_out: u64,
+    }
+
+    #[derive(Default)]
+    struct TrafficTable {
+        by_peer: BTreeMap<String, Traffic>,
+        by_protocol: BTreeMap<u8, Traffic>,
+    }
+
+    impl TrafficTable {
+        fn entry(&mut self, peer: &str, protocol: u8) -> (&mut Traffic, &mut Traffic) {
+            let peer = match self.by_peer.contains_key(peer) || self.by_peer.len() < MAX_TRACKED_PEERS {
+                true => peer,
+                false => OTHER_PEERS,
+            };
+            (
+                self.by_peer.entry(peer.to_string()).or_default(),
+                self.by_protocol.entry(protocol).or_default(),
+            )
+        }
+    }
+
+    /// Counters and gauges describing a running `Node`. Counters only grow; gauges are
+    /// refreshed by the node once per loop iteration. See `Node::metrics`.
+    #[derive(Default)]
+    pub struct Metrics {
+        peers: AtomicU64,
+        chain_height: AtomicU64,
+        mempool_size: AtomicU64,
+        blocks_mined: AtomicU64,
+        reorgs: AtomicU64,
+        tip_timestamp: AtomicU64,
+        store_failing: AtomicBool,
+        persistence: Arc<PersistenceStats>,
+        bytes_in: AtomicU64,
+        bytes_out: AtomicU64,
+        messages: Mutex<BTreeMap<u8, u64>>,
+        traffic: Mutex<TrafficTable>,
+    }
+
+    /// A point-in-time copy of `Metrics`.
+    #[derive(Clone, Debug, Default)]
+    pub struct MetricsSnapshot {
+        pub peers: u64,
+        pub chain_height: u64,
+        pub mempool_size: u64,
+        pub blocks_mined: u64,
+        pub reorgs: u64,
+        /// Unix timestamp (in seconds) of the last block of the chain.
+        pub tip_timestamp: u64,
+        /// Whether the last attempt to save the chain or the peers failed.
+        pub store_failing: bool,
+        /// Times the chain was saved.
+        pub chain_saves: u64,
+        /// Times saving the chain failed.
+        pub chain_save_failures: u64,
+        /// Unix timestamp (in seconds) of the last time the chain was saved, 0 if never.
+        pub last_chain_save: u64,
+        pub bytes_in: u64,
+        pub bytes_out: u64,
+        /// Messages handled, by protocol.
+        pub messages: BTreeMap<u8, u64>,
+        /// Bytes exchanged, by peer address.
+        pub traffic_by_peer: BTreeMap<String, Traffic>,
+        /// Bytes exchanged, by protocol.
+        pub traffic_by_protocol: BTreeMap<u8, Traffic>,
+    }
+
+    impl Metrics {
+        pub fn new() -> Self {
+            Metrics::default()
+        }
+
+        pub fn set_peers(&self, peers: usize) {
+            self.peers.store(peers as u64, Ordering::Relaxed);
+        }
+
+        pub fn set_chain_height(&self, height: usize) {
+            self.chain_height.store(height as u64, Ordering::Relaxed);
+        }
+
+        pub fn set_mempool_size(&self, size: usize) {
+            self.mempool_size.store(size as u64, Ordering::Relaxed);
+        }
+
+        pub fn block_mined(&self) {
+            self.blocks_mined.fetch_add(1, Ordering::Relaxed);
+        }
+
+        /// Counts the replacement of the chain by a different one.
+        pub fn reorg(&self) {
+            self.reorgs.fetch_add(1, Ordering::Relaxed);
+        }
+
+        pub fn set_tip_timestamp(&self, timestamp: u64) {
+            self.tip_timestamp.store(timestamp, Ordering::Relaxed);
+        }
+
+        /// Records whether the last attempt to save the peers failed.
+        pub fn set_store_failing(&self, failing: bool) {
+            self.store_failing.store(failing, Ordering::Relaxed);
+        }
+
+        /// Counters of the saves of the chain, shared with the `PersistenceService`.
+        pub fn persistence(&self) -> Arc<PersistenceStats> {
+            self.persistence.clone()
+        }
+
+        /// Counts a message received from `peer`.
+        pub fn bytes_received(&self, peer: &str, protocol: u8, n_bytes: usize) {
+            self.bytes_in.fetch_add(n_bytes as u64, Ordering::Relaxed);
+            let mut traffic = self.traffic.lock().unwrap();
+            let (by_peer, by_protocol) = traffic.entry(peer, protocol);
+            by_peer.bytes_in += n_bytes as u64;
+            by_protocol.bytes_in += n_bytes as u64;
+        }
+
+        /// Counts a message sent to `peer`.
+        pub fn bytes_sent(&self, peer: &str, protocol: u8, n_bytes: usize) {
+            self.bytes_out.fetch_add(n_bytes as u64, Ordering::Relaxed);
+            let mut traffic = self.traffic.lock().unwrap();
+            let (by_peer, by_protocol) = traffic.entry(peer, protocol);
+            by_peer.bytes_out += n_bytes as u64;
+            by_protocol.bytes_out += n_bytes as u64;
+        }
+
+        /// Bytes sent and received since the node started.
+        pub fn total_bytes(&self) -> u64 {
+            self.bytes_in.load(Ordering::Relaxed) + self.bytes_out.load(Ordering::Relaxed)
+        }
+
+        pub fn message_handled(&self, protocol: u8) {
+            *self.messages.lock().unwrap().entry(protocol).or_insert(0) += 1;
+        }
+
+        pub fn snapshot(&self) -> MetricsSnapshot {
+            let traffic = self.traffic.lock().unwrap();
+            MetricsSnapshot {
+                peers: self.peers.load(Ordering::Relaxed),
+                chain_height: self.chain_height.load(Ordering::Relaxed),
+                mempool_size: self.mempool_size.load(Ordering::Relaxed),
+                blocks_mined: self.blocks_mined.load(Ordering::Relaxed),
+                reorgs: self.reorgs.load(Ordering::Relaxed),
+                tip_timestamp: self.tip_timestamp.load(Ordering::Relaxed),
+                store_failing: self.store_failing.load(Ordering::Relaxed) || self.persistence.is_failing(),
+                chain_saves: self.persistence.saves(),
+                chain_save_failures: self.persistence.failures(),
+                last_chain_save: self.persistence.last_save().unwrap_or(0),
+                bytes_in: self.bytes_in.load(Ordering::Relaxed),
+                bytes_out: self.bytes_out.load(Ordering::Relaxed),
+                messages: self.messages.lock().unwrap().clone(),
+                traffic_by_peer: traffic.by_peer.clone(),
+                traffic_by_protocol: traffic.by_protocol.clone(),
+            }
+        }
+    }
+
+    impl MetricsSnapshot {
+        /// Checks the health of the node the snapshot was taken from.
+        ///
+        /// # Arguments
+        /// * `max_tip_age` - Age (in seconds) of the last block past which the node is not synced.
+        /// * `now` - The current Unix timestamp, in seconds.
+        pub fn health(&self, max_tip_age: u64, now: u64) -> Health {
+            let tip_age = now.saturating_sub(self.tip_timestamp);
+            Health {
+                synced: tip_age <= max_tip_age,
+                tip_age,
+                peers: self.peers,
+                store: !self.store_failing,
+            }
+        }
+
+        /// Renders the snapshot in the Prometheus text exposition format.
+        pub fn to_prometheus(&self) -> String {
+            let mut out = String::new();
+            let mut metric = |name: &str, kind: &str, help: &str, value: u64| {
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+                let _ = writeln!(out, "# TYPE {} {}", name, kind);
+                let _ = writeln!(out, "{} {}", name, value);
+            };
+            metric("node_peers", "gauge", "Current number of neighbours.", self.peers);
+            metric("node_chain_height", "gauge", "Number of blocks in the chain.", self.chain_height);
+            metric("node_mempool_size", "gauge", "Transactions and records waiting to be mined.", self.mempool_size);
+            metric("node_blocks_mined_total", "counter", "Blocks mined by this node.", self.blocks_mined);
+            metric("node_reorgs_total", "counter", "Times the chain was replaced by a neighbour's.", self.reorgs);
+            metric("node_tip_timestamp_seconds", "gauge", "Unix timestamp of the last block.", self.tip_timestamp);
+            metric("node_store_failing", "gauge", "1 if the last save to a store failed.", self.store_failing as u64);
+            metric("node_chain_saves_total", "counter", "Times the chain was saved.", self.chain_saves);
+            metric("node_chain_save_failures_total", "counter", "Times saving the chain failed.", self.chain_save_failures);
+            metric("node_last_chain_save_seconds", "gauge", "Unix timestamp of the last save of the chain.", self.last_chain_save);
+            metric("node_bytes_in_total", "counter", "Bytes received.", self.bytes_in);
+            metric("node_bytes_out_total", "counter", "Bytes sent.", self.bytes_out);
+
+            let _ = writeln!(out, "# HELP node_messages_total Messages handled, by protocol.");
+            let _ = writeln!(out, "# TYPE node_messages_total counter");
+            for (protocol, count) in &self.messages {
+                let _ = writeln!(out, "node_messages_total{{protocol=\"{}\"}} {}", protocol::name(*protocol), count);
+            }
+
+            let mut labelled = |name: &str, help: &str, label: &str, values: Vec<(String, u64)>| {
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                for (value, count) in values {
+                    let _ = writeln!(out, "{}{{{}=\"{}\"}} {}", name, label, value, count);
+                }
+            };
+            let by_peer = |bytes: fn(&Traffic) -> u64| -> Vec<(String, u64)> {
+                self.traffic_by_peer.iter().map(|(peer, traffic)| (peer.clone(), bytes(traffic))).collect()
+            };
+            let by_protocol = |bytes: fn(&Traffic) -> u64| -> Vec<(String, u64)> {
+                self.traffic_by_protocol.iter().map(|(protocol, traffic)| (protocol::name(*protocol).to_string(), bytes(traffic))).collect()
+            };
+            labelled("node_peer_bytes_in_total", "Bytes received, by peer.", "peer", by_peer(|traffic| traffic.bytes_in));
+            labelled("node_peer_bytes_out_total", "Bytes sent, by peer.", "peer", by_peer(|traffic| traffic.bytes_out));
+            labelled("node_protocol_bytes_in_total", "Bytes received, by protocol.", "protocol", by_protocol(|traffic| traffic.bytes_in));
+            labelled("node_protocol_bytes_out_total", "Bytes sent, by protocol.", "protocol", by_protocol(|traffic| traffic.bytes_out));
+            out
+        }
+    }
+
+    /// Outcome of the health checks of a node.
+    #[derive(Clone, Debug, PartialEq, Serialize)]
+    pub struct Health {
+        /// Whether the last block is recent enough.
+        pub synced: bool,
+        /// Age of the last block, in seconds.
+        pub tip_age: u64,
+        pub peers: u64,
+        /// Whether the node can save to its stores.
+        pub store: bool,
+    }
+
+    impl Health {
+        /// Whether the node works, i.e. can save to its stores. Being out of sync or alone
+        /// is not something restarting the node fixes.
+        pub fn is_live(&self) -> bool {
+            self.store
+        }
+
+        /// Whether the node can serve: it is live, synced and has at least one neighbour.
+        pub fn is_ready(&self) -> bool {
+            self.is_live() && self.synced && self.peers > 0
+        }
+    }
+
+    /// Metrics of the nodes running in this process, by address, so messages sent through
+    /// `gossip` and read by the `Listener` are counted for the right node.
+    static REGISTERED: OnceLock<Mutex<HashMap<String, Arc<Metrics>>>> = OnceLock::new();
+
+    fn registered() -> &'static Mutex<HashMap<String, Arc<Metrics>>> {
+        REGISTERED.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    pub fn register(address: &str, metrics: Arc<Metrics>) {
+        registered().lock().unwrap().insert(address.to_string(), metrics);
+    }
+
+    pub fn unregister(address: &str) {
+        registered().lock().unwrap().remove(address);
+    }
+
+    /// Returns the metrics of the node running on `address`, if any.
+    pub fn lookup(address: &str) -> Option<Arc<Metrics>> {
+        registered().lock().unwrap().get(address).cloned()
+    }
+}
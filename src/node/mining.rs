@@ -0,0 +1,9 @@
+pub mod mining {
+
+    /// What an operator asks a miner to do with its mining. See `Node::pause_mining`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum MiningCommand {
+        Pause,
+        Resume,
+    }
+}
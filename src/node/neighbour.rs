@@ -1,12 +1,28 @@
 pub mod neighbour {
     
+    use crate::node::secure::secure::Session;
+    use crate::node::reputation::reputation::INITIAL_SCORE;
+    use crate::clock::clock::clock::now;
+    use crate::chain::network::network::NetworkId;
+    use crate::node::identity::identity::IdentityProof;
+
     use uuid::Uuid;
     use serde::de::{self, Deserialize, Deserializer, Visitor, MapAccess};
     use serde::ser::{Serialize, SerializeStruct, Serializer};
     use thiserror::Error;
     use std::fmt;
+    use std::sync::Arc;
+
+    /// Software name and version this node announces to its neighbours.
+    pub const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+    /// Service bit: serves headers and blocks to syncing peers.
+    pub const SERVICE_CHAIN: u32 = 1 << 0;
+    /// Service bit: answers DHT lookups.
+    pub const SERVICE_DHT: u32 = 1 << 1;
+    /// Service bit: mines the records and transactions sent to it.
+    pub const SERVICE_MINING: u32 = 1 << 2;
 
-    #[derive(Clone, PartialEq, Copy)]
+    #[derive(Clone, PartialEq, Copy, Debug)]
     pub enum Role {
         Tracker,
         Node,
@@ -43,11 +59,147 @@ pub mod neighbour {
         }
     }
 
+    impl fmt::Display for Role {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Role::Tracker => write!(f, "tracker"),
+                Role::Node => write!(f, "node"),
+                Role::Miner => write!(f, "miner"),
+            }
+        }
+    }
+
     #[derive(Clone)]
     pub struct Neighbour {
         pub id: Uuid,
         pub address: String,
         pub role: Role,
+        /// Keys negotiated with this neighbour. Never serialized.
+        pub session: Option<Arc<Session>>,
+        /// Unix timestamp (in seconds) of the last message received from this neighbour.
+        pub last_seen: u64,
+        /// Pings sent since this neighbour was last heard from.
+        pub missed_pings: u32,
+        /// Reputation earned from this neighbour's behaviour. See `reputation::Behaviour`.
+        pub score: i32,
+        /// Software the neighbour runs, see `USER_AGENT`.
+        pub version: Option<String>,
+        /// Length of the neighbour's chain, as last announced.
+        pub chain_height: Option<usize>,
+        /// `SERVICE_*` bits of what the neighbour offers.
+        pub services: Option<u32>,
+    }
+
+    /// What a neighbour tells about itself during the handshake.
+    #[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct PeerInfo {
+        pub version: Option<String>,
+        pub chain_height: Option<usize>,
+        pub services: Option<u32>,
+        /// Network the neighbour belongs to. Neighbours that do not say are on `mainnet`.
+        pub network: Option<NetworkId>,
+        /// Proof the neighbour owns the identity its id derives from. Sent along handshake
+        /// keys only.
+        pub identity: Option<IdentityProof>,
+    }
+
+    impl Neighbour {
+        pub fn new(id: Uuid, address: String, role: Role) -> Self {
+            Neighbour {
+                id,
+                address,
+                role,
+                session: None,
+                last_seen: now(),
+                missed_pings: 0,
+                score: INITIAL_SCORE,
+                version: None,
+                chain_height: None,
+                services: None,
+            }
+        }
+
+        /// Refreshes the metadata of this neighbour, keeping what `info` does not mention.
+        pub fn set_info(&mut self, info: PeerInfo) {
+            self.version = info.version.or(self.version.take());
+            self.chain_height = info.chain_height.or(self.chain_height);
+            self.services = info.services.or(self.services);
+        }
+
+        pub fn info(&self) -> PeerInfo {
+            PeerInfo {
+                version: self.version.clone(),
+                chain_height: self.chain_height,
+                services: self.services,
+                network: None,
+                identity: None,
+            }
+        }
+
+        /// Whether the neighbour announced every service in `services`.
+        pub fn offers(&self, services: u32) -> bool {
+            self.services.is_some_and(|offered| offered & services == services)
+        }
+
+        /// Records that a message was just received from this neighbour.
+        pub fn mark_seen(&mut self) {
+            self.last_seen = now();
+            self.missed_pings = 0;
+        }
+
+        /// Encrypts a message for this neighbour, if a session has been established.
+        ///
+        /// # Arguments
+        /// * `message` - The plain message, starting with its protocol byte.
+        ///
+        /// # Returns
+        /// * `Vec<u8>` - The message to put on the wire.
+        pub fn seal(&self, message: Vec<u8>) -> Vec<u8> {
+            match &self.session {
+                Some(session) => session.seal(&message),
+                None => message,
+            }
+        }
+    }
+
+    /// What the address book remembers about a neighbour between restarts.
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub struct PeerRecord {
+        pub id: Uuid,
+        pub address: String,
+        pub role: u32,
+        pub last_seen: u64,
+        pub score: i32,
+        #[serde(default)]
+        pub version: Option<String>,
+        #[serde(default)]
+        pub services: Option<u32>,
+    }
+
+    impl From<&Neighbour> for PeerRecord {
+        fn from(neighbour: &Neighbour) -> Self {
+            PeerRecord {
+                id: neighbour.id,
+                address: neighbour.address.clone(),
+                role: neighbour.role.to_protocol(),
+                last_seen: neighbour.last_seen,
+                score: neighbour.score,
+                version: neighbour.version.clone(),
+                services: neighbour.services,
+            }
+        }
+    }
+
+    impl TryFrom<PeerRecord> for Neighbour {
+        type Error = WrongProtocolError;
+        fn try_from(record: PeerRecord) -> Result<Self, Self::Error> {
+            let mut neighbour = Neighbour::new(record.id, record.address, Role::from_protocol(record.role)?);
+            neighbour.last_seen = record.last_seen;
+            neighbour.score = record.score;
+            neighbour.version = record.version;
+            neighbour.services = record.services;
+            Ok(neighbour)
+        }
     }
 
     impl PartialEq for Neighbour {
@@ -70,10 +222,22 @@ pub mod neighbour {
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             where S: Serializer,
         {
-            let mut s = serializer.serialize_struct("Neighbour", 3)?;
+            let mut s = serializer.serialize_struct("Neighbour", 6)?;
             s.serialize_field("id", &self.id.to_string())?;
             s.serialize_field("address", &self.address)?;
             s.serialize_field("role", &self.role.to_protocol())?;
+            match &self.version {
+                Some(version) => s.serialize_field("version", version)?,
+                None => s.skip_field("version")?,
+            }
+            match &self.chain_height {
+                Some(chain_height) => s.serialize_field("chain_height", chain_height)?,
+                None => s.skip_field("chain_height")?,
+            }
+            match &self.services {
+                Some(services) => s.serialize_field("services", services)?,
+                None => s.skip_field("services")?,
+            }
             s.end()
         }
     }
@@ -91,10 +255,7 @@ pub mod neighbour {
         where
             E: de::Error,
         {
-            match Role::from_protocol(value) {
-                Ok(v) => Ok(v),
-                Err(e) => Err(E::custom(format!("e"))), 
-            }
+            Role::from_protocol(value).map_err(E::custom)
         }
     }
 
@@ -114,7 +275,7 @@ pub mod neighbour {
             D: Deserializer<'de>,
         {
 
-            enum Field { Id, Address, Role }
+            enum Field { Id, Address, Role, Version, ChainHeight, Services }
 
             impl<'de> Deserialize<'de> for Field {
                 fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
@@ -127,7 +288,7 @@ pub mod neighbour {
                         type Value = Field;
 
                         fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                            formatter.write_str("`id`, `address` or `role`")
+                            formatter.write_str("`id`, `address`, `role`, `version`, `chain_height` or `services`")
                         }
 
                         fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -138,6 +299,9 @@ pub mod neighbour {
                                 "id" => Ok(Field::Id),
                                 "address" => Ok(Field::Address),
                                 "role" => Ok(Field::Role),
+                                "version" => Ok(Field::Version),
+                                "chain_height" => Ok(Field::ChainHeight),
+                                "services" => Ok(Field::Services),
                                 _ => Err(de::Error::unknown_field(value, FIELDS)),
                             }
                         }
@@ -164,6 +328,7 @@ pub mod neighbour {
                     let mut id = None;
                     let mut address = None;
                     let mut role = None;
+                    let mut info = PeerInfo::default();
 
                     while let Some(key) = map.next_key()? {
                         match key {
@@ -184,23 +349,38 @@ pub mod neighbour {
                                      return Err(de::Error::duplicate_field("role"));
                                 }
                                 let raw = map.next_value()?;
-                                role = Some(Role::from_protocol(raw).unwrap());
+                                role = Some(Role::from_protocol(raw).map_err(de::Error::custom)?);
+                            },
+                            Field::Version => {
+                                if info.version.is_some() {
+                                     return Err(de::Error::duplicate_field("version"));
+                                }
+                                info.version = Some(map.next_value()?);
+                            },
+                            Field::ChainHeight => {
+                                if info.chain_height.is_some() {
+                                     return Err(de::Error::duplicate_field("chain_height"));
+                                }
+                                info.chain_height = Some(map.next_value()?);
+                            },
+                            Field::Services => {
+                                if info.services.is_some() {
+                                     return Err(de::Error::duplicate_field("services"));
+                                }
+                                info.services = Some(map.next_value()?);
                             },
                         }
                     }
                     let id = id.ok_or_else(|| de::Error::missing_field("id"))?;
                     let address = address.ok_or_else(|| de::Error::missing_field("address"))?;
                     let role = role.ok_or_else(|| de::Error::missing_field("role"))?;
-                    let n = Neighbour {
-                        id,
-                        address,
-                        role,
-                    };
+                    let mut n = Neighbour::new(id, address, role);
+                    n.set_info(info);
                     Ok(n)
                 }
             }
 
-            const FIELDS: &[&str] = &["id", "address", "role"];
+            const FIELDS: &[&str] = &["id", "address", "role", "version", "chain_height", "services"];
             d.deserialize_struct("Neighbour", FIELDS, NeighbourVisitor)
         }
     }
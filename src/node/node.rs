@@ -1,42 +1,121 @@
 pub mod node {
 
     use crate::{
-        Wallet,
         Chain,
         Transaction,
         Miner,
-        chain::block::block::block::Block,
+        Wallet,
+        miner::miner::miner::MiningDigest,
+        chain::block::entry::entry::BlockEntry,
+        chain::block::block::block::{Block, BlockHeader},
         node::{
-            neighbour::neighbour::{Neighbour, Role},
+            neighbour::neighbour::{Neighbour, PeerInfo, PeerRecord, Role, SERVICE_CHAIN, SERVICE_DHT, SERVICE_MINING, USER_AGENT},
             gossip::gossip,
-            gossip::gossip::GossipError,
-            protocol::protocol,
+            gossip::gossip::{GossipError, Greeting},
+            listener::listener::{Incoming, Listener, LISTENER_CHANNEL_CAPACITY},
+            outbound::outbound,
+            secure::secure::{Handshake, SecureError, Session, PUBLIC_KEY_LEN},
+            reputation::reputation::{self, Behaviour, BannedPeer},
+            ratelimit::ratelimit::{RateDecision, RateLimiter},
+            bandwidth::bandwidth::BandwidthCaps,
+            admission::admission::Gatekeeper,
+            metrics::metrics::{Metrics, MetricsSnapshot},
+            event::event::{NodeEvent, EVENT_CHANNEL_CAPACITY},
+            config::config::{NodeBuilder, NodeConfig, NodeConfigError, PoolConfig, StoreEngine},
+            registry::registry::{self, Registration, Registry, REGISTRY_NAMESPACE},
+            bootstrap::bootstrap,
+            relay::relay::{Inventory, RelayedNeighbour, SeenCache},
+            submit::submit::{EntryStatus, SubmitError, SubmitReceipt},
+            sync::sync::{self, BlocksRequest, BlocksResponse, HeadersRequest, HeadersResponse, RangeRequest, RangeResponse, SyncError, BLOCKS_PER_REQUEST},
+            verifier::verifier::Verifier,
+            protocol::protocol::ProtocolMsg,
+            status::status::{NodeStatus, PeerStatus},
+            mempool::mempool::{PendingEntries, MEMPOOL_NAMESPACE},
+            identity::identity::{Identity, IdentityProof},
+            filter::filter::{FilteredBlock, LoadedFilter, NotificationFilter, MAX_FILTERS, MAX_FILTER_ENTRIES},
+            pool::pool::{Job, Pool, PoolJoin, Share, ShareOutcome, SEARCH_CHUNK},
+            peer_log::peer_log::PeerLog,
+            rpc::rpc::{self, PendingRequests},
+            decode::decode,
+            frame::frame,
+            transport::transport::{self, Transport},
             receiver::receiver::Receiver,
-            reply::reply::Reply,
+            reply::reply::GossipPayload,
             theme::theme::{self, Theme},
         },
         transaction::transaction::transaction::TransactionFromBase64Error,
+        token::token::token::Token,
+        chain::block::block::block as block,
+        chain::chain::chain::{self as chain, BlockCheckError, ChainDigest},
+        chain::genesis::genesis::{Genesis, GenesisError},
+        chain::network::network::NetworkId,
+        chain::config::config::{ChainConfig, Checkpoint, Consensus, SignedCheckpoint, CHECKPOINTS_NAMESPACE},
+        chain::state::state::{ChainState, STATE_NAMESPACE},
+        clock::clock::clock::{self, now},
+        transaction::validation::validation::{self, Check, ValidationReport},
+        store::{
+            store::store::{Store, StoreError},
+            encrypted_engine::encrypted_engine,
+            persistence::persistence::{self, PersistenceService, SharedStore, PERSIST_INTERVAL},
+        },
+        dht::{
+            routing::routing::{Contact, DhtKey, RoutingTable, BUCKET_SIZE},
+            lookup::lookup::{self, DhtError, FindRequest, FindResponse, ProvideRequest, ProviderStore},
+        },
     };
     use tokio::sync::{
-        mpsc::error::TryRecvError,
+        broadcast,
+        mpsc::{self, error::TryRecvError},
+        watch,
         Mutex,
     };
+    use tokio::task::{JoinHandle, JoinSet};
+
 
 
 
     use std::{
-        sync::{Arc},
-        collections::HashMap,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        net::SocketAddr,
+        ops::Range,
+        collections::{HashMap, HashSet, VecDeque},
+        time::{Duration, Instant},
         io::{Result as IOResult, Error as IOError},
         str,
     };
 
+    use base64::{Engine as _, engine::general_purpose};
     use thiserror::Error;
     use rand::prelude::*;
     use uuid::{self, Uuid};
-    use tracing::{debug, info};
-
-    const DEFAULT_ADDRESS: &str = "127.0.0.1";
+    use tracing::{debug, error, info, warn};
+
+    /// Number of consecutive unanswered pings after which a neighbour is evicted.
+    pub const MAX_MISSED_PINGS: u32 = 3;
+    /// Number of consecutive unanswered pings after which a neighbour is considered stale,
+    /// probably on the other side of a partition. See `Node::exchange_digests`.
+    pub const STALE_PINGS: u32 = 2;
+    /// Time (in seconds) a neighbour evicted for missing pings is not taken back from the
+    /// neighbours gossiped by others, as it may have no room for this node. It may still
+    /// greet this node, or start a handshake with it.
+    pub const STALE_QUARANTINE: u64 = 30;
+    /// Time (in seconds) between the attempts of a node left without neighbours, as after
+    /// a partition, to enter the network again through its trackers.
+    pub const REJOIN_INTERVAL: u64 = 10;
+    /// Interval (in seconds) between automatic flushes of the address book.
+    pub const PEER_FLUSH_INTERVAL: u64 = 30;
+    /// Time (in seconds) `Node::shutdown` waits for farewells to leave the send queues.
+    pub const FAREWELL_TIMEOUT: u64 = 2;
+    /// Signed checkpoints kept, newest last, to share with new neighbours.
+    pub const SHARED_CHECKPOINTS: usize = 16;
+    /// Time (in seconds) after which a handshake left unanswered may be started again.
+    pub const HANDSHAKE_RETRY: u64 = 5;
+    /// Messages in a row the session with a neighbour may fail to open before it is
+    /// dropped and a new handshake started, as both sides may end up with different ones.
+    pub const MAX_DECRYPT_FAILURES: u32 = 5;
 
     // -------------------------------
     // Error Definitions
@@ -50,10 +129,12 @@ pub mod node {
         NoTrackers,
     }
 
-    #[derive(Error, Debug)]
+    #[derive(Error, Debug, derive_more::From)]
     pub enum UpdateChainError {
         #[error("Failed to update chain - No neighbours listening.")]
         NoListeners,
+        #[error(transparent)]
+        IOError(IOError),
     }
 
     #[derive(Error, Debug, derive_more::From)]
@@ -62,6 +143,8 @@ pub mod node {
         NotMiner,
         #[error("That operation requires a Node with Role Tracker.")]
         NotTracker,
+        #[error("That operation is not available to a Node with Role Tracker.")]
+        Tracker,
     }
 
     //#[derive(Error, Debug, derive_more::From, derive_more::Display)]
@@ -81,6 +164,41 @@ pub mod node {
         TransactionFromBase64Error(TransactionFromBase64Error),
     }
     
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum PeerStoreError {
+        #[error("No peer store configured. Consider set_peer_store(store).")]
+        NoStore,
+        #[error(transparent)]
+        StoreError(StoreError),
+    }
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum ChainStoreError {
+        #[error("No chain store configured. Consider set_chain_store(store).")]
+        NoStore,
+        #[error("Stored chain is invalid: {0}")]
+        InvalidChain(BlockCheckError),
+        #[error("Stored chain belongs to another network: {0}")]
+        GenesisError(GenesisError),
+        #[error("Stored chain breaks the chain rules: {0}")]
+        #[from(ignore)]
+        RuledOut(BlockCheckError),
+        #[error(transparent)]
+        StoreError(StoreError),
+    }
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum FilterError {
+        #[error("{0} is not a neighbour of this node.")]
+        #[from(ignore)]
+        NotNeighbour(String),
+        #[error("A filter holds at most {MAX_FILTER_ENTRIES} addresses and keys, got {0}.")]
+        #[from(ignore)]
+        TooLarge(usize),
+        #[error(transparent)]
+        IOError(IOError),
+    }
+
     #[derive(Error, Debug, derive_more::From)]
     pub enum NodeLoopError {
         #[error(transparent)]
@@ -93,20 +211,163 @@ pub mod node {
     // -------------------------------
     // Node Structure Definition
     // -------------------------------
+
+    /// Asks a running `Node::node_loop` to return. See `Node::stop_handle`.
+    #[derive(Clone)]
+    pub struct StopHandle {
+        stop: Arc<AtomicBool>,
+    }
+
+    impl StopHandle {
+        /// Makes the loop return at the end of its current iteration, or right away if it
+        /// has not started yet, e.g. while the node is still entering the network.
+        pub fn stop(&self) {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Evicts transactions from the mempool of a running node and pauses or resumes its
+    /// mining, from another task. Only the operator API is given one: nothing received
+    /// from the network may do either. See `Node::operator_handle`.
+    #[derive(Clone)]
+    pub struct OperatorHandle {
+        id: Uuid,
+        miner: Option<Arc<Mutex<Miner>>>,
+        mining_paused: Arc<AtomicBool>,
+        metrics: Arc<Metrics>,
+    }
+
+    impl OperatorHandle {
+        /// See `Node::evict`.
+        pub async fn evict(&self, id: &str) -> Result<bool, WrongRoleError> {
+            let miner = self.miner.as_ref().ok_or(WrongRoleError::NotMiner)?;
+            let mut miner = miner.lock().await;
+            let before = miner.transactions.len();
+            miner.transactions.retain(|transaction| transaction.id() != id);
+            let evicted = miner.transactions.len() < before;
+            if evicted {
+                info!("{} evicted transaction {} from its mempool", self.id, id);
+                self.metrics.set_mempool_size(miner.transactions.len() + miner.records.len() + miner.batches.len());
+            }
+            Ok(evicted)
+        }
+
+        /// See `Node::pause_mining`.
+        pub fn pause_mining(&self) -> Result<bool, WrongRoleError> {
+            if self.miner.is_none() {
+                return Err(WrongRoleError::NotMiner);
+            }
+            let running = !self.mining_paused.swap(true, Ordering::Relaxed);
+            if running {
+                info!("{} paused mining", self.id);
+            }
+            Ok(running)
+        }
+
+        /// See `Node::resume_mining`.
+        pub fn resume_mining(&self) -> Result<bool, WrongRoleError> {
+            if self.miner.is_none() {
+                return Err(WrongRoleError::NotMiner);
+            }
+            let paused = self.mining_paused.swap(false, Ordering::Relaxed);
+            if paused {
+                info!("{} resumed mining", self.id);
+            }
+            Ok(paused)
+        }
+    }
     
     pub struct Node {
         id: Uuid,
         role: Role,
-        address: Arc<str>,
+        /// Address the node's socket is bound to.
+        bind_addr: Arc<str>,
+        /// Address the node gives out to be reached at. Differs from `bind_addr` behind NAT.
+        advertise_addr: Arc<str>,
         transaction_buffer: Option<Vec<Transaction>>,
-        wallet: Wallet,
+        /// The key pair the node's id derives from. See `Identity`.
+        identity: Identity,
         chain: Chain,
         neighbours: HashMap<Uuid, Neighbour>,
-        new_neighbours: Vec<Neighbour>,
+        /// Changes to `neighbours`, and how far each neighbour was told about them.
+        peer_log: PeerLog,
         initialized: bool,
         trackers: Option<Vec<String>>,
         receiver: Arc<Mutex<Receiver>>,
         miner: Option<Arc<Mutex<Miner>>>,
+        listener: Option<Listener>,
+        incoming: Option<mpsc::Receiver<Incoming>>,
+        pending_handshakes: HashMap<String, (Handshake, u64)>,
+        /// Messages in a row the session with each neighbour failed to open.
+        decrypt_failures: HashMap<String, u32>,
+        /// Session each neighbour shared before its last handshake, still opening the
+        /// messages it sealed before, until the new session opens one.
+        retired_sessions: HashMap<String, Arc<Session>>,
+        /// The session that opened the message being handled, if it arrived sealed. Requests
+        /// are answered the same way, so requesters that do not share a session yet, or
+        /// sealed the request before their last handshake, can read the answer.
+        sealed: Option<Arc<Session>>,
+        banned: HashMap<String, BannedPeer>,
+        /// When each neighbour evicted for missing pings was, by address. See `STALE_QUARANTINE`.
+        evicted: HashMap<String, u64>,
+        peer_store: Option<Store>,
+        last_peer_flush: Instant,
+        chain_store: Option<SharedStore>,
+        /// Sends the chain to the `PersistenceService` saving it while the loop runs.
+        persistence: Option<(watch::Sender<Chain>, JoinHandle<()>)>,
+        /// The genesis of the private network the node belongs to. Chains starting
+        /// elsewhere are refused.
+        genesis: Option<Genesis>,
+        /// The network the node belongs to. See `NodeConfig::network`.
+        network: NetworkId,
+        /// Verifies chains received past what it verified already, and audits the chain.
+        verifier: Verifier,
+        events: broadcast::Sender<NodeEvent>,
+        /// The chain as last published to watchers. See `Node::watch_chain`.
+        chain_updates: watch::Sender<Chain>,
+        /// The neighbours as last published to watchers. See `Node::watch_peers`.
+        peer_updates: watch::Sender<Vec<PeerRecord>>,
+        config: NodeConfig,
+        registry: Registry,
+        last_announce: Instant,
+        /// When the node last tried to enter the network again. See `REJOIN_INTERVAL`.
+        last_rejoin: Instant,
+        routing: RoutingTable,
+        providers: ProviderStore,
+        seen: SeenCache,
+        gossip_interval: u64,
+        last_digest: ChainDigest,
+        submitted: HashMap<String, u64>,
+        rate_limiter: RateLimiter,
+        bandwidth: BandwidthCaps,
+        gatekeeper: Gatekeeper,
+        metrics: Arc<Metrics>,
+        /// Stale neighbours that came back, whose digest has not been compared yet.
+        reconnected: HashSet<String>,
+        /// Reconnected neighbours with a longer, diverging chain to resynchronize with.
+        resync: HashSet<String>,
+        /// The resynchronization fetching blocks in the background, if one runs. See
+        /// `Node::resync_diverged`.
+        resyncing: Option<Resync>,
+        /// Filters loaded by neighbours, by address, which get filtered blocks rather than chains.
+        filters: HashMap<String, LoadedFilter>,
+        /// Addresses of the neighbours this node loaded a filter on. See `Node::load_filter`.
+        filtering: HashSet<String>,
+        /// The wallet this node signs checkpoints with. See `NodeConfig::checkpoint_key`.
+        publisher: Option<Wallet>,
+        /// The last `SHARED_CHECKPOINTS` signed checkpoints pinned, newest last.
+        signed_checkpoints: VecDeque<SignedCheckpoint>,
+        /// The workers and shares of the pool this node coordinates. See `PoolConfig`.
+        pool: Option<Pool>,
+        /// Whether this node, a pool worker, asked its coordinator for work.
+        pool_joined: bool,
+        /// Raised to stop searching nonces for the last job of the pool coordinator.
+        pool_search: Option<Arc<AtomicBool>>,
+        stop: Arc<AtomicBool>,
+        /// Whether mining is paused. See `Node::pause_mining`.
+        mining_paused: Arc<AtomicBool>,
+        /// When the node was created. See `Node::status`.
+        started: Instant,
     }
 
     // -------------------------------
@@ -116,34 +377,282 @@ pub mod node {
     impl Node {
         /// Creates a new `Node` instance.
         pub fn new(role: Role, address: String, trackers: Option<Vec<String>>, receiver: Receiver) -> Self {
+            let config = NodeConfig {
+                role,
+                address,
+                trackers: trackers.unwrap_or_default(),
+                ..NodeConfig::default()
+            };
+            Node::from_config(config, receiver).expect("a node without stores opens no file")
+        }
+
+        /// Returns a `NodeBuilder` with the default configuration.
+        pub fn builder() -> NodeBuilder {
+            NodeBuilder::new()
+        }
+
+        /// Creates a `Node` from an already validated configuration. See `NodeBuilder::build`.
+        ///
+        /// # Returns
+        /// * `Result<Node, NodeConfigError>` - The node, or an error if one of its stores cannot be opened.
+        pub fn from_config(config: NodeConfig, receiver: Receiver) -> Result<Self, NodeConfigError> {
             let mut transaction_buffer = None;
             let mut miner = None;
+            let role = config.role;
+            let genesis = config.genesis.as_ref().map(Genesis::read).transpose()?;
+            let network = config.network(genesis.as_ref())?;
+            if genesis.as_ref().is_some_and(|genesis| !genesis.allocations.is_empty() && !config.chain.mint.premine) {
+                return Err(NodeConfigError::Premine);
+            }
 
             if role == Role::Miner {
                 transaction_buffer = Some(vec![]);
 
-                miner = Some(Arc::new(Mutex::new(Miner::new(1, "miner".to_string())))); //TODO: generate id and name
+                let mut inner_miner = Miner::new(1, "miner".to_string()); //TODO: generate id and name
+                inner_miner.set_threads(config.mining_threads);
+                inner_miner.set_network(network.clone());
+                inner_miner.set_record_rules(config.chain.records);
+                inner_miner.set_upgrades(config.chain.upgrades.clone());
+                inner_miner.set_mint_policy(config.chain.mint.clone());
+                if let Some(authority) = config.authority()? {
+                    inner_miner.wallet = authority;
+                }
+                miner = Some(Arc::new(Mutex::new(inner_miner)));
+            }
+            let publisher = config.publisher()?;
+            let trackers = match config.trackers.is_empty() {
+                true => None,
+                false => Some(config.trackers.clone()),
+            };
+            let chain = genesis.as_ref().map_or_else(|| Chain::for_network(&network), Genesis::chain);
+            let chain_updates = watch::channel(chain.clone()).0;
+            let gossip_interval = config.gossip_interval;
+            let rate_limiter = RateLimiter::new(config.peer_rate_limit, config.global_rate_limit);
+            let bandwidth = BandwidthCaps::new(config.hourly_bandwidth_cap, config.daily_bandwidth_cap);
+            let gatekeeper = Gatekeeper::new(config.admission_difficulty);
+            let key = config.store_key_file.as_ref().map(encrypted_engine::load_key).transpose()?;
+            let open = |engine: &StoreEngine| -> Result<Store, StoreError> {
+                let store = engine.open()?.with_codec(config.store_codec);
+                Ok(match &key {
+                    Some(key) => store.encrypted(key),
+                    None => store,
+                })
+            };
+            let mut peer_store = config.store.as_ref().map(open).transpose()?;
+            let identity = match peer_store.as_mut() {
+                Some(store) => Identity::load_or_create(store)?,
+                None => Identity::new(),
+            };
+            let id = identity.id();
+            let mut chain_store = config.chain_store.as_ref().map(open).transpose()?;
+            let chain_path = config.chain_store.as_ref().and_then(StoreEngine::path);
+            if let (Some(interval), Some(path)) = (config.wal_checkpoint_interval, chain_path) {
+                let mut wal_path = path.as_os_str().to_owned();
+                wal_path.push(".wal");
+                chain_store = chain_store.map(|store| store.with_wal(wal_path, interval)).transpose()?;
+            }
+            if let Some(retention) = config.chain_retention {
+                chain_store = chain_store.map(|store| store.with_retention(retention));
             }
-            Node {
-                id: Uuid::new_v4(),
+            let chain_store = chain_store.map(|store| Arc::new(std::sync::Mutex::new(store)));
+            let mut node = Node {
+                id,
                 role,
-                address: address.into(),
+                bind_addr: config.address.as_str().into(),
+                advertise_addr: config.advertise_address.as_deref().unwrap_or(&config.address).into(),
                 transaction_buffer,
-                wallet: Wallet::new(),
-                chain: Chain::new(),
+                identity,
+                last_digest: chain.digest(),
+                chain,
                 neighbours: HashMap::new(),
-                new_neighbours: vec![],
+                peer_log: PeerLog::new(),
                 initialized: false,
                 trackers,
                 receiver: Arc::new(Mutex::new(receiver)),
                 miner,
+                listener: None,
+                incoming: None,
+                pending_handshakes: HashMap::new(),
+                decrypt_failures: HashMap::new(),
+                retired_sessions: HashMap::new(),
+                sealed: None,
+                banned: HashMap::new(),
+                evicted: HashMap::new(),
+                peer_store,
+                last_peer_flush: Instant::now(),
+                chain_store,
+                persistence: None,
+                genesis,
+                network,
+                verifier: Verifier::new(config.chain.clone(), config.audit_interval),
+                events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+                chain_updates,
+                peer_updates: watch::channel(vec![]).0,
+                config,
+                registry: Registry::new(),
+                last_announce: Instant::now(),
+                last_rejoin: Instant::now(),
+                routing: RoutingTable::new(&id),
+                providers: ProviderStore::new(),
+                seen: SeenCache::default(),
+                gossip_interval,
+                submitted: HashMap::new(),
+                rate_limiter,
+                bandwidth,
+                gatekeeper,
+                metrics: Arc::new(Metrics::new()),
+                reconnected: HashSet::new(),
+                resync: HashSet::new(),
+                resyncing: None,
+                filters: HashMap::new(),
+                filtering: HashSet::new(),
+                publisher,
+                signed_checkpoints: VecDeque::new(),
+                pool: None,
+                pool_joined: false,
+                pool_search: None,
+                stop: Arc::new(AtomicBool::new(false)),
+                mining_paused: Arc::new(AtomicBool::new(false)),
+                started: Instant::now(),
+            };
+            if node.config.pool == Some(PoolConfig::Coordinator) {
+                node.pool = Some(Pool::new());
+            }
+            if node.chain_store.is_some() {
+                match node.load_checkpoints() {
+                    Ok(0) => (),
+                    Ok(pinned) => info!("{} pinned {} saved checkpoints", node.id, pinned),
+                    Err(e) => warn!("{} failed to load its saved checkpoints: {}", node.id, e),
+                }
+                match node.load_chain() {
+                    Err(ChainStoreError::GenesisError(e)) => return Err(e.into()),
+                    Err(ChainStoreError::RuledOut(e)) => return Err(NodeConfigError::RuledOut(e)),
+                    Err(e) => warn!("{} failed to load its chain: {}", node.id, e),
+                    Ok(_) => (),
+                }
+                match node.load_mempool() {
+                    Ok(0) => (),
+                    Ok(restored) => info!("{} queued {} entries saved when it crashed", node.id, restored),
+                    Err(e) => warn!("{} failed to load its saved mempool: {}", node.id, e),
+                }
             }
+            Ok(node)
         }
 
         pub fn get_address(&self) -> Arc<str> {
-            self.address.clone()
+            self.bind_addr.clone()
+        }
+
+        /// Returns the address neighbours reach this node at.
+        pub fn get_advertise_address(&self) -> Arc<str> {
+            self.advertise_addr.clone()
+        }
+
+        /// Returns the network the node belongs to.
+        pub fn get_network(&self) -> &NetworkId {
+            &self.network
+        }
+
+
+        /// Sets the store the address book is saved to and loaded from.
+        pub fn set_peer_store(&mut self, store: Store) {
+            self.peer_store = Some(store);
+        }
+
+        /// Sets the store the chain is saved to and loaded from.
+        pub fn set_chain_store(&mut self, store: Store) {
+            self.chain_store = Some(Arc::new(std::sync::Mutex::new(store)));
+        }
+
+        /// Returns the current metrics of this node.
+        pub fn metrics(&self) -> MetricsSnapshot {
+            self.metrics.set_peers(self.neighbours.len());
+            self.metrics.set_chain_height(self.chain.len());
+            self.metrics.set_tip_timestamp(self.chain.get_last_block().timestamp);
+            self.metrics.snapshot()
+        }
+
+        /// Returns a handle to stop the node loop from another task. Once stopped, the
+        /// loop can be started again on the same node.
+        pub fn stop_handle(&self) -> StopHandle {
+            StopHandle { stop: self.stop.clone() }
+        }
+
+        /// Returns a handle to evict transactions and pause mining from the operator API.
+        pub fn operator_handle(&self) -> OperatorHandle {
+            OperatorHandle {
+                id: self.id,
+                miner: self.miner.clone(),
+                mining_paused: self.mining_paused.clone(),
+                metrics: self.metrics.clone(),
+            }
+        }
+
+        /// Returns the live metrics of this node, for exporting them while it runs.
+        pub fn metrics_handle(&self) -> Arc<Metrics> {
+            self.metrics.clone()
+        }
+
+        /// Replaces the chain of a node that has not started yet, unless the chain loaded
+        /// from its chain store is longer or `chain` misses a checkpoint. See
+        /// `NodeBuilder::with_chain`.
+        pub fn start_from(&mut self, chain: Chain) {
+            if chain.len() < self.chain.len() {
+                return;
+            }
+            if let Err(e) = self.check_network(&chain).and_then(|_| self.config.chain.verify(&chain)) {
+                warn!("{} refused to start from the given chain: {}", self.id, e);
+                return;
+            }
+            self.chain = chain;
+            self.last_digest = self.chain.digest();
+            self.chain_updates.send_replace(self.chain.clone());
+        }
+
+        /// Returns a receiver of the node's chain, updated whenever it changes while the
+        /// loop runs, for reading it from another task.
+        pub fn watch_chain(&self) -> watch::Receiver<Chain> {
+            self.chain_updates.subscribe()
+        }
+
+        /// Returns a receiver of the node's neighbours, updated on every iteration of the loop.
+        pub fn watch_peers(&self) -> watch::Receiver<Vec<PeerRecord>> {
+            self.peer_updates.subscribe()
+        }
+
+        /// Publishes the chain, if it changed, and the neighbours to their watchers.
+        fn publish_state(&self) {
+            let digest = self.chain.digest();
+            self.chain_updates.send_if_modified(|published| {
+                if published.digest() == digest {
+                    return false;
+                }
+                *published = self.chain.clone();
+                true
+            });
+            self.peer_updates.send_replace(self.neighbours.values().map(PeerRecord::from).collect());
+        }
+
+        /// Updates the gauges of the node's metrics.
+        async fn refresh_metrics(&self) {
+            self.metrics.set_peers(self.neighbours.len());
+            self.metrics.set_chain_height(self.chain.len());
+            self.metrics.set_tip_timestamp(self.chain.get_last_block().timestamp);
+            if let Some(miner) = &self.miner {
+                let miner = miner.lock().await;
+                self.metrics.set_mempool_size(miner.transactions.len() + miner.records.len() + miner.batches.len());
+            }
+        }
+
+        /// Returns a receiver for the events this node emits from now on.
+        pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+            self.events.subscribe()
         }
 
+        /// Publishes an event to every subscriber. Events are dropped if nobody listens.
+        fn emit(&self, event: NodeEvent) {
+            let _ = self.events.send(event);
+        }
 
         /// Queues a transaction into the node's transaction buffer.
         pub fn queue_transaction(&mut self, transaction: Transaction) {
@@ -157,346 +666,2962 @@ pub mod node {
             self.neighbours.len()
         }
 
+        /// Returns the transactions waiting to be mined, oldest first.
+        pub async fn list_pending(&self) -> Result<Vec<Transaction>, WrongRoleError> {
+            let miner = self.miner.as_ref().ok_or(WrongRoleError::NotMiner)?;
+            Ok(miner.lock().await.transactions.clone())
+        }
+
+        /// Returns the transaction with id `id` if it waits to be mined.
+        pub async fn get_pending(&self, id: &str) -> Result<Option<Transaction>, WrongRoleError> {
+            let miner = self.miner.as_ref().ok_or(WrongRoleError::NotMiner)?;
+            let miner = miner.lock().await;
+            Ok(miner.transactions.iter().find(|transaction| transaction.id() == id).cloned())
+        }
+
+        /// Drops the transaction with id `id` from the mempool, so it is not mined. It
+        /// stays seen, so neighbours relaying it again do not bring it back.
+        ///
+        /// # Returns
+        /// * `Result<bool, WrongRoleError>` - Whether the transaction waited to be mined.
+        pub async fn evict(&mut self, id: &str) -> Result<bool, WrongRoleError> {
+            self.operator_handle().evict(id).await
+        }
+
+        /// Pauses mining without stopping the node, e.g. to shed CPU load during
+        /// maintenance. The block being mined is given up; the transactions, records and
+        /// batches waiting to be mined are kept for when mining resumes.
+        ///
+        /// # Returns
+        /// * `Result<bool, WrongRoleError>` - Whether mining was running.
+        pub fn pause_mining(&self) -> Result<bool, WrongRoleError> {
+            self.operator_handle().pause_mining()
+        }
+
+        /// Resumes mining paused by `pause_mining`, from the next loop iteration.
+        ///
+        /// # Returns
+        /// * `Result<bool, WrongRoleError>` - Whether mining was paused.
+        pub fn resume_mining(&self) -> Result<bool, WrongRoleError> {
+            self.operator_handle().resume_mining()
+        }
+
+        /// Whether mining is paused. See `pause_mining`.
+        pub fn is_mining_paused(&self) -> bool {
+            self.mining_paused.load(Ordering::Relaxed)
+        }
+
+        /// Returns what the node tells about itself to operators.
+        pub fn status(&self) -> NodeStatus {
+            let tip = self.chain.digest();
+            NodeStatus {
+                id: self.id,
+                role: self.role.to_string(),
+                uptime: self.started.elapsed().as_secs(),
+                version: USER_AGENT.to_string(),
+                network: self.network.clone(),
+                tip_hash: tip.tip,
+                height: tip.height,
+                state_root: self.chain.state_root(),
+                mining_paused: self.is_mining_paused(),
+            }
+        }
+
+        /// Returns what the node knows about each of its neighbours, with the bytes
+        /// exchanged with them.
+        pub fn peer_statuses(&self) -> Vec<PeerStatus> {
+            let traffic = self.metrics.snapshot().traffic_by_peer;
+            self.neighbours
+                .values()
+                .map(|neighbour| {
+                    let exchanged = traffic.get(&neighbour.address).copied().unwrap_or_default();
+                    PeerStatus {
+                        id: neighbour.id,
+                        address: neighbour.address.clone(),
+                        role: neighbour.role.to_string(),
+                        last_seen: neighbour.last_seen,
+                        score: neighbour.score,
+                        version: neighbour.version.clone(),
+                        bytes_in: exchanged.bytes_in,
+                        bytes_out: exchanged.bytes_out,
+                    }
+                })
+                .collect()
+        }
+
+        /// Turns this node into a miner: allocates a miner and tells neighbours and
+        /// trackers about the new role. Mining starts on the next loop iteration. Does
+        /// nothing if this node already is a miner.
+        pub async fn promote_to_miner(&mut self) -> Result<(), WrongRoleError> {
+            match self.role {
+                Role::Tracker => return Err(WrongRoleError::Tracker),
+                Role::Miner => return Ok(()),
+                Role::Node => (),
+            }
+            let mut miner = Miner::new(1, "miner".to_string()); //TODO: generate id and name
+            miner.set_threads(self.config.mining_threads);
+            miner.set_network(self.network.clone());
+            miner.set_record_rules(self.config.chain.records);
+            miner.set_upgrades(self.config.chain.upgrades.clone());
+            miner.set_mint_policy(self.config.chain.mint.clone());
+            match self.config.authority() {
+                Ok(Some(authority)) => miner.wallet = authority,
+                Ok(None) => (),
+                Err(e) => warn!("{} will not seal blocks: {}", self.id, e),
+            }
+            self.miner = Some(Arc::new(Mutex::new(miner)));
+            self.transaction_buffer = Some(vec![]);
+            self.change_role(Role::Miner).await;
+            Ok(())
+        }
+
+        /// Turns a miner back into a regular node, dropping its miner along with the
+        /// transactions and records it had not mined yet, and whether its mining was
+        /// paused. Does nothing if this node already is a regular node.
+        pub async fn demote_to_node(&mut self) -> Result<(), WrongRoleError> {
+            match self.role {
+                Role::Tracker => return Err(WrongRoleError::Tracker),
+                Role::Node => return Ok(()),
+                Role::Miner => (),
+            }
+            self.miner = None;
+            self.transaction_buffer = None;
+            self.mining_paused.store(false, Ordering::Relaxed);
+            self.change_role(Role::Node).await;
+            Ok(())
+        }
+
+        /// Switches to `role` and announces it to every neighbour. Trackers also get a new
+        /// announcement right away, so their registries do not wait for `ANNOUNCE_INTERVAL`.
+        async fn change_role(&mut self, role: Role) {
+            info!("{} changing role from {:?} to {:?}", self.id, self.role, role);
+            self.role = role;
+            self.config.role = role;
+            let announcer = self.as_neighbour();
+            for neighbour in self.neighbours.values() {
+                let _ = gossip::send_role(self.bind_addr.clone(), neighbour, role).await;
+                if neighbour.role == Role::Tracker {
+                    let _ = gossip::announce(self.bind_addr.clone(), &announcer, neighbour).await;
+                }
+            }
+        }
+
 
         // -------------------------------
         // Network Operations
         // -------------------------------
 
         /// Main node loop that listens and processes various activities in the network.
+        ///
+        /// Runs until stopped through a `StopHandle`, then saves the address book and the
+        /// chain. The node's socket stays bound, so the loop can be started again; see
+        /// `Node::shutdown` to release it.
         pub async fn node_loop(&mut self) -> Result<(), GossipError> {
             debug!("{} starting node loop.", self.id);
+            self.start_listener().await?;
+            self.start_persistence();
             let mut theme = Theme::Chain;
-            loop {
+            while !self.stop.load(Ordering::Relaxed) {
                 let theme_protocol = (theme.to_protocol() + 1) % theme::N_THEMES; //TODO: Fix this.
                                                                                   //Jesus Christ.
                 theme = Theme::from_protocol(theme_protocol).unwrap();
                 self.initialized = true;
                 let chain = self.chain.clone();
-                let chain_gossip = self.chain.clone();
+                self.refresh_metrics().await;
+                self.publish_state();
+                let digest = self.chain.digest();
                 let role = self.role.clone();
-                let miner_clone = self.miner.as_mut().unwrap().clone();
+                // Pool coordinators leave the search to their workers.
+                let miner_clone = self.miner.clone().filter(|_| self.pool.is_none());
+                let chain_config = self.config.chain.clone();
+                let stop_mining = self.stop.clone();
+                let mining_paused = self.mining_paused.clone();
                 let receiver_clone = self.receiver.clone();
-                let neighbours = self.neighbours.clone();
-                let address = self.address.clone();
-                let address_gossip = self.address.clone();
+                let address_gossip = self.bind_addr.clone();
                 let random_neighbours = self.get_random_neighbours();
-                let new_neighbours = self.new_neighbours.clone();
-                tokio::join!(
+                let new_neighbours = match theme {
+                    Theme::NewNeighbours => self.neighbour_updates(&random_neighbours),
+                    Theme::Chain => HashMap::new(),
+                };
+                self.flush_peers_if_due();
+                self.audit_if_due();
+                self.prune_submitted();
+                let address_ping = self.bind_addr.clone();
+                let neighbours_ping = self.ping_round();
+                let announcements = self.announce_round();
+                let address_announce = self.bind_addr.clone();
+                let gossip_interval = self.next_gossip_interval();
+                let (_, _, _, _, submitted, mined) = tokio::join!(
                     self.listen_to_peers(),
-                    gossip(address_gossip, chain_gossip, random_neighbours, new_neighbours, theme.clone()),
-                    listen_to_transactions(receiver_clone, neighbours, address),
-                    mine(role, miner_clone, chain), //TODO: Should have to unwrap
+                    gossip(address_gossip, digest, random_neighbours, new_neighbours, theme.clone(), gossip_interval),
+                    ping_neighbours(address_ping, neighbours_ping),
+                    announce_to_trackers(address_announce, announcements),
+                    listen_to_transactions(receiver_clone),
+                    mine(role, miner_clone, chain, chain_config, stop_mining, mining_paused),
                 );
+                if let Some(mining_digest) = mined {
+                    if self.chain.add_block(mining_digest).is_ok() {
+                        self.metrics.block_mined();
+                        self.emit(NodeEvent::BlockMined { height: self.chain.len() });
+                        self.persist_chain();
+                    }
+                }
+                if let Some(transaction) = submitted {
+                    self.relay_transaction(transaction, None, self.config.max_hops).await;
+                }
+                self.resync_diverged().await;
+                self.relay_filtered().await;
+                self.publish_checkpoint().await;
+                self.dispatch_jobs().await;
+                self.join_pool().await;
+                self.rejoin_if_alone().await?;
+            }
+            debug!("{} stopping node loop.", self.id);
+            if let Some(resyncing) = self.resyncing.take() {
+                resyncing.abort();
             }
+            if let Some(search) = self.pool_search.take() {
+                search.store(true, Ordering::Relaxed);
+            }
+            self.stop.store(false, Ordering::Relaxed);
+            if self.peer_store.is_some() {
+                if let Err(e) = self.save_peers() {
+                    warn!("{} failed to save peers: {}", self.id, e);
+                }
+            }
+            self.persist_chain();
+            self.stop_persistence().await;
+            Ok(())
         }
 
 
         /// Enters the network by contacting trackers and starts the node loop.
         pub async fn enter_and_node_loop(&mut self) -> Result<(), NodeLoopError> {
             self.enter_network().await?;
+            if let Err(e) = self.sync_chain().await {
+                warn!("{} could not synchronize its chain: {}", self.id, e);
+            }
             self.node_loop().await?;
             Ok(())
         }
 
-        /// Contacts trackers and attempts to join the network.
+        /// Contacts trackers, and peers remembered in the address book, and attempts to join the network.
+        /// Stops retrying once the node is asked to stop.
         pub async fn enter_network(&mut self) -> Result<(), EnterAttemptError> {
-            if let Some(trackers) = &self.trackers {
-                for tracker in trackers {
-                    match gossip::greet(self.address.clone(), self.id.clone(), self.role, tracker).await {
-                        Ok(neighbour) => {
-                            self.neighbours.insert(neighbour.id.clone(), neighbour.clone());
-                            self.new_neighbours.push(neighbour);
+            let known_peers: Vec<Neighbour> = self.neighbours.drain().map(|(_, neighbour)| neighbour).collect();
+            if self.trackers.is_none() && known_peers.is_empty() {
+                return Err(EnterAttemptError::NoTrackers);
+            }
+            let trackers = self.trackers.clone().unwrap_or_default();
+            let mut unreached = known_peers;
+            let mut dialer = bootstrap::Dialer::new(
+                self.config.dial_attempts,
+                self.config.dial_backoff,
+                self.config.max_dial_backoff,
+            );
+            loop {
+                self.greet_trackers(&trackers).await;
+                for known_peer in std::mem::take(&mut unreached) {
+                    if self.neighbours.values().any(|neighbour| neighbour.address == known_peer.address) {
+                        continue;
+                    }
+                    match gossip::greet(self.bind_addr.clone(), &self.as_neighbour(), &self.network, &known_peer.address).await {
+                        Ok((greeted, _)) => {
+                            // The peer may have restarted with a new id; keep what we know about it otherwise.
+                            let mut neighbour = known_peer;
+                            neighbour.id = greeted.id;
+                            neighbour.mark_seen();
+                            self.start_handshake(neighbour.address.clone()).await;
+                            self.insert_neighbour(neighbour, self.config.max_hops);
                             self.initialized = true;
                         }
                         Err(_) => {
-                            debug!("Node {} failed to greet tracker", self.id);
-                            continue;
+                            debug!("Node {} failed to greet known peer {}", self.id, known_peer.address);
+                            unreached.push(known_peer);
                         }
                     }
                 }
-                if !self.initialized {
-                    return Err(EnterAttemptError::NoListeners);
+                if self.initialized {
+                    break;
                 }
-                Ok(())
-            } else {
-                Err(EnterAttemptError::NoTrackers)
+                if self.stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some(wait) = dialer.next_delay() else {
+                    break;
+                };
+                debug!("Node {} reached nobody, retrying in {:?}", self.id, wait);
+                clock::sleep(wait).await;
+            }
+            if !self.initialized {
+                return Err(EnterAttemptError::NoListeners);
+            }
+            Ok(())
+        }
+
+        /// Greets every tracker once, taking each that answers as a neighbour along with the
+        /// peers it suggests. The node's socket must not be read by a listener meanwhile.
+        async fn greet_trackers(&mut self, trackers: &[String]) {
+            for tracker in bootstrap::resolve_trackers(trackers).await {
+                match gossip::greet(self.bind_addr.clone(), &self.as_neighbour(), &self.network, &tracker).await {
+                    Ok((neighbour, seeds)) => {
+                        self.start_handshake(neighbour.address.clone()).await;
+                        self.insert_neighbour(neighbour, self.config.max_hops);
+                        self.adopt_peers(seeds).await;
+                        self.initialized = true;
+                    }
+                    Err(e) => debug!("Node {} failed to greet tracker {}: {}", self.id, tracker, e),
+                }
+            }
+        }
+
+        /// Greets the trackers again once every neighbour is gone, at most every
+        /// `REJOIN_INTERVAL` seconds, as nobody would tell the node about peers otherwise.
+        /// The listener is stopped meanwhile, so the answers are read where they arrive.
+        /// Trackers wait to be greeted instead.
+        async fn rejoin_if_alone(&mut self) -> IOResult<()> {
+            let Some(trackers) = self.trackers.clone() else {
+                return Ok(());
+            };
+            let alone = self.role != Role::Tracker && self.neighbours.is_empty();
+            if !alone || self.last_rejoin.elapsed() < Duration::new(REJOIN_INTERVAL, 0) {
+                return Ok(());
             }
+            self.last_rejoin = Instant::now();
+            info!("{} has no neighbours left, greeting its trackers again", self.id);
+            self.stop_listener().await;
+            self.greet_trackers(&trackers).await;
+            self.start_listener().await
         }
 
         /// Leaves the network by sending farewell messages to all neighbours.
         pub async fn leave_network(&self) {
             for neighbour in &self.neighbours {
-                let _ = gossip::farewell(self.address.clone(), neighbour.1.address.clone()).await;
+                let _ = gossip::farewell(self.bind_addr.clone(), neighbour.1.address.clone()).await;
             }
         }
 
-        // -------------------------------
-        // Transaction and Chain Operations
-        // -------------------------------
-
-        pub async fn update_chain(&self) -> Result<Chain, UpdateChainError> {
-            let mut cursor = self.neighbours.iter();
-            while let Some((_id, neighbour)) = cursor.next() {
-                match gossip::poll_chain(self.address.clone(), neighbour).await {
-                    Ok(chain) => return Ok(chain),
-                    Err(_) => continue,
+        /// Takes the node out of the network once its loop returned: farewells are sent to
+        /// every neighbour, the socket is closed and the stores are flushed to disk.
+        pub async fn shutdown(&mut self) -> Result<(), StoreError> {
+            info!("{} shutting down", self.id);
+            self.leave_network().await;
+            if let Some(outbox) = outbound::lookup(&self.bind_addr) {
+                let wait = Duration::new(FAREWELL_TIMEOUT, 0);
+                if clock::timeout(wait, outbox.flush()).await.is_err() {
+                    debug!("{} gave up on farewells still queued", self.id);
                 }
             }
-            Err(UpdateChainError::NoListeners)
+            self.stop_listener().await;
+            self.stop_persistence().await;
+            if let Some(store) = self.peer_store.as_mut() {
+                store.flush()?;
+            }
+            if let Some(store) = &self.chain_store {
+                persistence::lock(store).flush()?;
+            }
+            Ok(())
         }
 
         // -------------------------------
-        // Gossip and Neighbor Management
+        // Address Book
         // -------------------------------
 
-        fn get_random_neighbours(&self) -> Vec<Neighbour> {
-            let mut neighbours = vec![];
-            let mut rng = rand::thread_rng();
-            let n = (self.neighbours.len() as f64).sqrt().floor() as usize;
-            for _ in 0..n {
-                let random_index = rng.gen_range(0..self.neighbours.len());
-                let random_key = self.neighbours.keys().nth(random_index).unwrap();
-                neighbours.push(self.neighbours.get(random_key).unwrap().clone());
+        /// Saves the current neighbours to the peer store, and the registry of a tracker to
+        /// its `REGISTRY_NAMESPACE` namespace.
+        pub fn save_peers(&mut self) -> Result<(), PeerStoreError> {
+            let records: Vec<PeerRecord> = self.neighbours.values().map(PeerRecord::from).collect();
+            let store = self.peer_store.as_mut().ok_or(PeerStoreError::NoStore)?;
+            store.save(&records)?;
+            if self.role == Role::Tracker {
+                store.namespace(REGISTRY_NAMESPACE)?.save(&self.registry.registrations())?;
             }
-            neighbours
+            self.last_peer_flush = Instant::now();
+            Ok(())
         }
 
-        // -------------------------------
-        // Listening and Chain Validation
-        // -------------------------------
-
-
-
-        /// Listens for incoming messages and processes them based on the protocol.
-        pub async fn listen_to_peers(&mut self) -> Result<(), GossipError> {
-            debug!("{} listening", self.id);
-            let (protocol, sender, buffer) = 
-                match gossip::listen_to_gossip(self.address.clone()).await {
-                Ok(res) => match res {
-                    Some((protocol, sender, buffer)) => (protocol, sender, buffer),
-                    None => return Ok(()),
-                }
-                Err(_) => return Ok(()),
+        /// Adds the neighbours saved in the peer store, so the node can rejoin without a tracker.
+        /// Trackers also take back the registrations saved that have not expired since.
+        ///
+        /// # Returns
+        /// The number of peers loaded.
+        pub fn load_peers(&mut self) -> Result<usize, PeerStoreError> {
+            if self.role == Role::Tracker {
+                self.load_registry()?;
+            }
+            let store = self.peer_store.as_mut().ok_or(PeerStoreError::NoStore)?;
+            let records: Vec<PeerRecord> = match store.load() {
+                Ok(records) => records,
+                Err(StoreError::EmptyFile) => return Ok(0),
+                Err(e) => return Err(e.into()),
             };
-            debug!("Received protocol: {}", &protocol);
+            let mut loaded = 0;
+            for record in records {
+                if record.id == self.id || self.is_banned(&record.address) {
+                    continue;
+                }
+                match Neighbour::try_from(record) {
+                    Ok(neighbour) => {
+                        self.neighbours.entry(neighbour.id).or_insert(neighbour);
+                        loaded += 1;
+                    },
+                    Err(e) => debug!("{} skipped a saved peer: {}", self.id, e),
+                }
+            }
+            Ok(loaded)
+        }
 
-            let mut outter_transaction: Option<Transaction> = None;
-            {
-                let res = match protocol {
-                    protocol::GREET => self.present_id(sender, buffer).await?,
-                    protocol::FAREWELL => self.remove_neighbour(sender).await?,
-                    protocol::NEIGHBOUR => self.add_neighbour(buffer).await?,
-                    protocol::TRANSACTION => self.add_transaction(buffer).await?,
-                    protocol::CHAIN => self.get_chain(buffer).await?,
-                    protocol::POLLCHAIN => self.share_chain().await?,
-                    _ => None, // Ignore unrecognized protocol with no error
-                };
+        /// Merges the registry saved with `save_peers` into the one of this tracker.
+        fn load_registry(&mut self) -> Result<(), PeerStoreError> {
+            let store = self.peer_store.as_mut().ok_or(PeerStoreError::NoStore)?.namespace(REGISTRY_NAMESPACE)?;
+            if !store.exists()? {
+                return Ok(());
+            }
+            let registrations: Vec<Registration> = store.load()?;
+            let merged = self.registry.merge(registrations, self.id);
+            info!("{} took back {} registrations of its registry", self.id, merged);
+            Ok(())
+        }
 
-                if let Some(mut ptr) = res {
-                    if let Some(chain) = ptr.as_chain() {
-                        self.check_chain(chain.clone());
-                    } else if let Some(transaction) = ptr.as_transaction() {
-                        if let Some(_) = &mut self.miner {
-                            outter_transaction = Some(transaction.clone());
-                        }
-                    }
+        /// Reports the audit of the chain that finished, if any, and starts the next one once due.
+        fn audit_if_due(&mut self) {
+            if let Some(report) = self.verifier.poll_audit() {
+                match report.result {
+                    Ok(()) => debug!("{} audited its chain up to height {}", self.id, report.height),
+                    Err(e) => {
+                        warn!("{} found its chain invalid up to height {} ({}): {}", self.id, report.height, report.tip, e);
+                        // Blocks verified once are not trusted anymore.
+                        self.verifier.forget();
+                        self.emit(NodeEvent::AuditFailed { height: report.height, tip: report.tip, error: e.to_string() });
+                    },
                 }
             }
-            match outter_transaction {
-                Some(t) => push_transaction(self.miner.as_mut().unwrap(), t.clone()).await,
-                None => (),
+            if self.verifier.audit_due() {
+                self.verifier.start_audit(self.chain.clone(), self.genesis.clone());
             }
-            Ok(())
         }
 
-        /// Updates the node's chain if the received chain is longer.
-        fn check_chain(&mut self, chain: Chain) {
-            if chain.len() > self.chain.len() {
-                self.chain = chain;
+        /// Saves the address book if a peer store is configured and the flush interval elapsed.
+        fn flush_peers_if_due(&mut self) {
+            if self.peer_store.is_none()
+                || self.last_peer_flush.elapsed() < Duration::new(PEER_FLUSH_INTERVAL, 0) {
+                return;
+            }
+            let saved = self.save_peers();
+            self.metrics.set_store_failing(saved.is_err());
+            if let Err(e) = saved {
+                warn!("{} failed to save peers: {}", self.id, e);
             }
         }
 
         // -------------------------------
-        // Neighbor Management
+        // Chain Persistence
         // -------------------------------
 
-        /// Handles the presentation of this node's ID when contacted by a neighbour.
-        pub async fn present_id(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<Option<Box<dyn Reply>>> {
-            buffer.remove(0);
-            let str_buffer = str::from_utf8(&buffer)
-                .expect("Malformed request to enter network -- Unable to parse")
-                .trim();
-            let cleared = Node::sanitize(str_buffer.to_string());
-            let neighbour: Neighbour = serde_json::from_str(&cleared)
-                .expect("Malformed neighbour string -- Unable to create neighbour from enter network request");
-
-            let hash_neighbour = neighbour.clone();
-            self.neighbours.entry(hash_neighbour.id).or_insert(hash_neighbour);
-            self.new_neighbours.push(neighbour);
-
-            // Sending ID back to the sender
-            gossip::send_id(self.address.clone(), self.id.clone(), sender).await;
+        /// Saves the current chain to the chain store right away, along with its state if
+        /// the store prunes it. See `persistence::save_chain`.
+        pub fn save_chain(&mut self) -> Result<(), ChainStoreError> {
+            let store = self.chain_store.as_ref().ok_or(ChainStoreError::NoStore)?;
+            let saved = persistence::save_chain(&mut persistence::lock(store), &self.chain);
+            self.metrics.persistence().record(&saved);
+            Ok(saved?)
+        }
 
-            Ok(None)
+        /// Replaces the chain with the one saved in the chain store, if it is valid and
+        /// longer than the current one. A chain stored pruned takes the state saved along
+        /// with it, and is refused without one.
+        ///
+        /// # Returns
+        /// The length of the chain afterwards.
+        pub fn load_chain(&mut self) -> Result<usize, ChainStoreError> {
+            let mut store = persistence::lock(self.chain_store.as_ref().ok_or(ChainStoreError::NoStore)?);
+            let mut chain: Chain = match store.load() {
+                Ok(chain) => chain,
+                Err(StoreError::EmptyFile) => return Ok(self.chain.len()),
+                Err(e) => return Err(e.into()),
+            };
+            if !chain.state().is_complete() {
+                let snapshots = store.namespace(STATE_NAMESPACE)?;
+                if snapshots.exists()? {
+                    chain.restore_state(snapshots.load()?)?;
+                }
+            }
+            chain.verify_chain()?;
+            if let Some(genesis) = &self.genesis {
+                genesis.verify(&chain)?;
+            }
+            self.check_network(&chain).map_err(ChainStoreError::RuledOut)?;
+            self.config.chain.verify(&chain).map_err(ChainStoreError::RuledOut)?;
+            drop(store);
+            if chain.len() > self.chain.len() {
+                info!("{} loaded a chain of {} blocks", self.id, chain.len());
+                self.chain = chain;
+                self.last_digest = self.chain.digest();
+            }
+            Ok(self.chain.len())
         }
 
-        /// Removes a neighbour from the list based on the provided sender address.
-        pub async fn remove_neighbour(&mut self, sender: String) -> IOResult<Option<Box<dyn Reply>>> {
-            self.neighbours.retain(|_, v| v.address != sender);
-            Ok(None)
+        /// Hands the chain to the `PersistenceService` while the loop runs, or saves it right
+        /// away otherwise, if a chain store is configured. Failures are logged.
+        fn persist_chain(&mut self) {
+            if let Some((updates, _)) = &self.persistence {
+                updates.send_replace(self.chain.clone());
+                return;
+            }
+            if self.chain_store.is_none() {
+                return;
+            }
+            if let Err(e) = self.save_chain() {
+                warn!("{} failed to save its chain: {}", self.id, e);
+            }
         }
 
-        /// Adds a neighbour to this node's network from the provided buffer.
-        pub async fn add_neighbour(&mut self, mut buffer: Vec<u8>) -> IOResult<Option<Box<dyn Reply>>> {
-            buffer.remove(0);
+        /// Starts the `PersistenceService` saving the chain to the chain store, if one is
+        /// configured, at most every `PERSIST_INTERVAL` seconds.
+        fn start_persistence(&mut self) {
+            let Some(store) = self.chain_store.clone().filter(|_| self.persistence.is_none()) else {
+                return;
+            };
+            let (updates, receiver) = watch::channel(self.chain.clone());
+            let service = PersistenceService::new(store, receiver, Duration::new(PERSIST_INTERVAL, 0), self.metrics.persistence());
+            self.persistence = Some((updates, service.spawn()));
+        }
 
-            let str_buffer = str::from_utf8(&buffer)
-                .expect("Malformed request to add neighbour -- Unable to parse");
-            debug!("Received neighbour: {}", str_buffer);
+        /// Stops the `PersistenceService` once it saved the last chain handed to it.
+        async fn stop_persistence(&mut self) {
+            let Some((updates, service)) = self.persistence.take() else {
+                return;
+            };
+            drop(updates);
+            if let Err(e) = service.await {
+                warn!("{} lost the task saving its chain: {}", self.id, e);
+            }
+        }
 
-            let cleared = Node::sanitize(str_buffer.to_string());
-            let neighbour: Neighbour = serde_json::from_str(&cleared)
-                .expect("Malformed neighbour string -- Unable to create neighbour from request");
+        /// Saves what the miner has yet to mine to the `MEMPOOL_NAMESPACE` namespace of the
+        /// chain store. Regular nodes save nothing.
+        pub fn save_mempool(&mut self) -> Result<(), ChainStoreError> {
+            let mut store = persistence::lock(self.chain_store.as_ref().ok_or(ChainStoreError::NoStore)?);
+            // The panicking task may hold the lock, so it is not waited for.
+            let Some(pending) = self.miner.as_ref().and_then(|miner| miner.try_lock().ok()).map(|miner| PendingEntries::of(&miner)) else {
+                return Ok(());
+            };
+            store.namespace(MEMPOOL_NAMESPACE)?.save(&pending)?;
+            Ok(())
+        }
 
-            let hash_neighbour = neighbour.clone();
-            self.neighbours.entry(hash_neighbour.id).or_insert(hash_neighbour);
-            self.new_neighbours.push(neighbour);
+        /// Queues on the miner what the node saved with `save_mempool`, and drops it from
+        /// the chain store so it is queued once.
+        ///
+        /// # Returns
+        /// The number of entries queued, 0 on regular nodes.
+        pub fn load_mempool(&mut self) -> Result<usize, ChainStoreError> {
+            let mut store = persistence::lock(self.chain_store.as_ref().ok_or(ChainStoreError::NoStore)?);
+            let store = store.namespace(MEMPOOL_NAMESPACE)?;
+            if !store.exists()? {
+                return Ok(0);
+            }
+            let pending: PendingEntries = store.load()?;
+            store.delete()?;
+            let Some(miner) = self.miner.as_ref() else {
+                return Ok(0);
+            };
+            let mut miner = miner.try_lock().expect("nothing else holds the miner of a node being created");
+            Ok(pending.restore(&mut miner))
+        }
 
-            Ok(None)
+        /// Saves the chain and the mempool and flushes the stores, as well as a node that
+        /// panicked can, then emits `NodeEvent::Crashed`. See `Drop for Node`.
+        fn save_on_panic(&mut self) {
+            let pending = self.miner.as_ref().and_then(|miner| miner.try_lock().ok()).map_or(0, |miner| PendingEntries::of(&miner).len());
+            let saved = match self.chain_store.is_some() {
+                true => self.save_chain().and_then(|_| self.save_mempool()),
+                false => Err(ChainStoreError::NoStore),
+            };
+            let flushed = [
+                self.peer_store.as_mut().map(Store::flush),
+                self.chain_store.as_ref().map(|store| persistence::lock(store).flush()),
+            ];
+            for e in flushed.into_iter().flatten().filter_map(Result::err) {
+                error!("{} failed to flush a store while panicking: {}", self.id, e);
+            }
+            match &saved {
+                Ok(()) => error!("{} panicked, saved its chain of {} blocks and {} pending entries", self.id, self.chain.len(), pending),
+                Err(e) => error!("{} panicked and could not save its chain and mempool: {}", self.id, e),
+            }
+            self.emit(NodeEvent::Crashed { height: self.chain.len(), pending, saved: saved.is_ok() });
         }
 
         // -------------------------------
-        // Transaction Handling
+        // Transaction and Chain Operations
         // -------------------------------
 
-        /// Adds a transaction from the buffer, if this node is a miner.
-        pub async fn add_transaction(&self, mut buffer: Vec<u8>) -> IOResult<Option<Box<dyn Reply>>> {
-            if self.role != Role::Miner {
-                return Ok(None); // We can enhance this later to return an error
+        /// Asks the neighbours for their chain, one at a time, until one answers.
+        ///
+        /// # Returns
+        /// * `Chain` - The first chain received.
+        pub async fn update_chain(&mut self) -> Result<Chain, UpdateChainError> {
+            self.start_listener().await?;
+            let pending = self.listener.as_ref().unwrap().pending();
+            for neighbour in self.neighbours.values() {
+                match gossip::poll_chain(self.bind_addr.clone(), neighbour, &pending).await {
+                    Ok(chain) => return Ok(chain),
+                    Err(e) => debug!("{} could not poll {}: {}", self.id, neighbour.address, e),
+                }
             }
+            Err(UpdateChainError::NoListeners)
+        }
 
-            buffer.remove(0);
-            let str_buffer = str::from_utf8(&buffer)
-                .expect("Malformed request to add transaction -- Unable to parse");
+        /// Downloads the chain headers-first: the headers come from the neighbour announcing
+        /// the longest chain and are validated before any block body is requested, then the
+        /// missing bodies are downloaded in parallel from every neighbour.
+        ///
+        /// # Returns
+        /// * `usize` - The number of blocks downloaded. Zero if no neighbour has a longer chain.
+        pub async fn sync_chain(&mut self) -> Result<usize, SyncError> {
+            let mut peers: Vec<&Neighbour> = self.neighbours.values().collect();
+            // Prefer peers serving the chain, then the longest announced chains, then the best scored.
+            peers.sort_by_key(|neighbour| std::cmp::Reverse((
+                neighbour.services.is_none() || neighbour.offers(SERVICE_CHAIN),
+                neighbour.chain_height.unwrap_or(0),
+                neighbour.score,
+            )));
+            let peers: Vec<String> = peers.into_iter().map(|neighbour| neighbour.address.clone()).collect();
+            if peers.is_empty() {
+                return Err(SyncError::NoPeers);
+            }
+            let socket = self.ephemeral_socket().await?;
+
+            let mut best = None;
+            for peer in &peers {
+                match sync::fetch_headers(&socket, peer).await {
+                    Ok(response) => {
+                        best = Some(response);
+                        break;
+                    },
+                    Err(e) => debug!("{} could not get headers from {}: {}", self.id, peer, e),
+                }
+            }
+            let best = best.ok_or(SyncError::Timeout)?;
+            self.adopt_headers(&peers, best).await
+        }
 
-            let transaction = Transaction::try_from(str_buffer.to_string())
-                .expect("Malformed transaction string -- Unable to create transaction from request");
+        /// Downloads the blocks of index within `range` from `peer`, `BLOCKS_PER_REQUEST`
+        /// per request, as requests answered with `ProtocolMsg::Response` messages.
+        /// `peer` need not be a neighbour.
+        ///
+        /// # Returns
+        /// * `Result<Vec<Block>, SyncError>` - The blocks, oldest first, each pointing to the
+        ///   hash of the one before it. Fewer than asked for if the chain of `peer` ends
+        ///   first.
+        pub async fn fetch_blocks(&mut self, peer: &str, range: Range<usize>) -> Result<Vec<Block>, SyncError> {
+            self.start_listener().await?;
+            let pending = self.listener.as_ref().unwrap().pending();
+            download(self.bind_addr.clone(), &self.neighbour_at(peer), &pending, range).await
+        }
 
-            Ok(Some(Box::new(transaction)))
+        /// Returns the neighbour at `peer`, or a stranger if it is none.
+        fn neighbour_at(&self, peer: &str) -> Neighbour {
+            self.neighbours
+                .values()
+                .find(|neighbour| neighbour.address == peer)
+                .cloned()
+                .unwrap_or_else(|| Neighbour::new(Uuid::nil(), peer.to_string(), Role::Node))
         }
 
-        // -------------------------------
-        // Chain Management
-        // -------------------------------
+        /// Validates a peer's headers and, if they describe a longer chain, downloads the
+        /// blocks from where it diverges from ours and switches to it. See `download_headers`.
+        ///
+        /// # Arguments
+        /// * `peers` - The addresses of the peers to download the blocks from.
+        /// * `response` - The peer's headers.
+        ///
+        /// # Returns
+        /// * `usize` - The number of blocks downloaded. Zero if the chain is not longer.
+        async fn adopt_headers(&mut self, peers: &[String], response: HeadersResponse) -> Result<usize, SyncError> {
+            let Some(common) = diverging_headers(&self.chain.headers(), self.chain.start_difficulty(), &self.config.chain, &response)? else {
+                return Ok(0);
+            };
+            if peers.is_empty() {
+                return Err(SyncError::NoPeers);
+            }
+            self.start_listener().await?;
+            let pending = self.listener.as_ref().unwrap().pending();
+            let neighbours: Vec<Neighbour> = peers.iter().map(|peer| self.neighbour_at(peer)).collect();
+            let blocks = download_headers(self.id, self.bind_addr.clone(), &neighbours, &pending, &response.headers[common..]).await?;
+            self.adopt_blocks(Download { common, difficulty: response.difficulty, blocks })
+        }
 
-        /// Receives a chain from the buffer and returns it.
-        pub async fn get_chain(&mut self, mut buffer: Vec<u8>) -> IOResult<Option<Box<dyn Reply>>> {
+        /// Switches to the chain made of our blocks up to where a peer's chain diverges, and
+        /// the blocks downloaded past it, unless ours grew as long meanwhile.
+        ///
+        /// # Returns
+        /// * `usize` - The number of blocks downloaded. Zero if the chain is not longer.
+        fn adopt_blocks(&mut self, download: Download) -> Result<usize, SyncError> {
+            let Download { common, difficulty, blocks: downloaded } = download;
+            let n_downloaded = downloaded.len();
+            if common + n_downloaded <= self.chain.len() {
+                return Ok(0);
+            }
+            let mut blocks = self.chain.get_blocks();
+            blocks.truncate(common);
+            blocks.extend(downloaded);
+            let chain = Chain::from_blocks_unverified(blocks, difficulty);
+            self.verifier.commit(self.verifier.verify(&chain)?);
+            self.rewind_filters(&chain);
+            self.chain = chain.with_state(self.verifier.state().clone());
+            info!("{} synchronized {} blocks", self.id, n_downloaded);
+            self.metrics.reorg();
+            self.emit(NodeEvent::ChainAdopted { len: self.chain.len() });
+            self.persist_chain();
+            Ok(n_downloaded)
+        }
+
+        /// Resynchronizes with a reconnected neighbour found to have a longer chain diverging
+        /// from ours, fetching only the blocks after the fork. The blocks are fetched in the
+        /// background, from one neighbour at a time, and adopted the round they arrive: a
+        /// neighbour that stopped answering would otherwise hold the node up long enough for
+        /// its own neighbours to think it gone.
+        async fn resync_diverged(&mut self) {
+            if self.resyncing.as_ref().is_some_and(|resyncing| !resyncing.is_finished()) {
+                return;
+            }
+            // Finished already, so awaiting it does not hold the node up.
+            if let Some(resyncing) = self.resyncing.take() {
+                if let Ok((peer, download)) = resyncing.await {
+                    match download.and_then(|download| download.map_or(Ok(0), |download| self.adopt_blocks(download))) {
+                        Ok(n_downloaded) => info!("{} resynchronized {} blocks from {}", self.id, n_downloaded, peer),
+                        Err(e) => warn!("{} could not resynchronize with {}: {}", self.id, peer, e),
+                    }
+                }
+            }
+            let Some(peer) = self.resync.iter().next().cloned() else {
+                return;
+            };
+            self.resync.remove(&peer);
+            let Some(pending) = self.listener.as_ref().map(Listener::pending) else {
+                return;
+            };
+            let socket = match self.ephemeral_socket().await {
+                Ok(socket) => socket,
+                Err(e) => {
+                    warn!("{} could not resynchronize with {}: {}", self.id, peer, e);
+                    return;
+                },
+            };
+            let (id, address, neighbour) = (self.id, self.bind_addr.clone(), self.neighbour_at(&peer));
+            let (own_headers, start, config) = (self.chain.headers(), self.chain.start_difficulty(), self.config.chain.clone());
+            self.resyncing = Some(tokio::spawn(async move {
+                let download = async {
+                    let response = sync::fetch_headers(socket.as_ref(), &peer).await?;
+                    let Some(common) = diverging_headers(&own_headers, start, &config, &response)? else {
+                        return Ok(None);
+                    };
+                    let blocks = download_headers(id, address, &[neighbour], &pending, &response.headers[common..]).await?;
+                    Ok(Some(Download { common, difficulty: response.difficulty, blocks }))
+                }.await;
+                (peer, download)
+            }));
+        }
+
+        /// Answers a `ProtocolMsg::GetHeaders` request with the headers of the whole chain.
+        pub async fn share_headers(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(request) = decode::message::<HeadersRequest>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let response = HeadersResponse {
+                request_id: request.request_id,
+                difficulty: self.chain.difficulty,
+                headers: self.chain.headers(),
+            };
+            let _ = gossip::send_headers(self.bind_addr.clone(), &sender, &response).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::GetBlocks` request with the blocks it asks for.
+        pub async fn share_blocks(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(request) = decode::message::<BlocksRequest>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let response = BlocksResponse {
+                request_id: request.request_id,
+                blocks: self.chain.get_blocks_by_hash(&request.hashes),
+            };
+            let _ = gossip::send_blocks(self.bind_addr.clone(), &sender, &response).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::GetRange` request with the first `BLOCKS_PER_REQUEST`
+        /// blocks of the range it asks for.
+        pub async fn share_range(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let request = rpc::parse(&buffer).and_then(|(id, payload)| Some((id, decode::message::<RangeRequest>(payload).ok()?)));
+            let Some((id, request)) = request else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let response = RangeResponse {
+                difficulty: self.chain.difficulty,
+                blocks: self.chain
+                    .blocks_from(request.start)
+                    .take_while(|block| block.index < request.end)
+                    .take(BLOCKS_PER_REQUEST)
+                    .cloned()
+                    .collect(),
+            };
+            gossip::respond(self.bind_addr.clone(), &sender, self.reply_session().as_deref(), &id, &serde_json::to_vec(&response).unwrap()).await?;
+            Ok(GossipPayload::None)
+        }
+
+        // -------------------------------
+        // Gossip and Neighbor Management
+        // -------------------------------
+
+        /// Backs gossip off while the chain does not change: the interval doubles every round,
+        /// up to `max_gossip_interval`, and drops back to `gossip_interval` as soon as a block
+        /// is mined or a new chain adopted.
+        fn next_gossip_interval(&mut self) -> Duration {
+            let digest = self.chain.digest();
+            if digest != self.last_digest {
+                self.last_digest = digest;
+                self.gossip_interval = self.config.gossip_interval;
+            } else {
+                self.gossip_interval = (self.gossip_interval * 2).min(self.config.max_gossip_interval);
+            }
+            Duration::new(self.gossip_interval, 0)
+        }
+
+        fn get_random_neighbours(&self) -> Vec<Neighbour> {
+            let mut neighbours = vec![];
+            let mut rng = rand::thread_rng();
+            let n = match self.config.fanout {
+                Some(fanout) => fanout.min(self.neighbours.len()),
+                None => (self.neighbours.len() as f64).sqrt().floor() as usize,
+            };
+            for _ in 0..n {
+                let random_index = rng.gen_range(0..self.neighbours.len());
+                let random_key = self.neighbours.keys().nth(random_index).unwrap();
+                neighbours.push(self.neighbours.get(random_key).unwrap().clone());
+            }
+            neighbours
+        }
+
+        /// Picks the neighbours to send each of `peers` this round: the ones added or changed
+        /// since it was last sent some, or every neighbour now and then. See `PeerLog`.
+        fn neighbour_updates(&mut self, peers: &[Neighbour]) -> HashMap<Uuid, Vec<RelayedNeighbour>> {
+            let neighbours: Vec<Neighbour> = self.neighbours.values().cloned().collect();
+            let mut updates = HashMap::new();
+            for peer in peers {
+                updates.entry(peer.id).or_insert_with(|| self.peer_log.updates_for(peer, &neighbours));
+            }
+            updates
+        }
+
+        /// Evicts neighbours that missed too many pings and returns the ones to ping next.
+        ///
+        /// Every call counts as a new ping for each neighbour; receiving any message from
+        /// a neighbour resets its count.
+        fn ping_round(&mut self) -> Vec<Neighbour> {
+            let stale: Vec<String> = self.neighbours
+                .values()
+                .filter(|neighbour| neighbour.missed_pings >= MAX_MISSED_PINGS)
+                .map(|neighbour| neighbour.address.clone())
+                .collect();
+            for address in stale {
+                info!("{} evicting stale neighbour {}", self.id, address);
+                self.drop_neighbour(&address);
+                self.evicted.insert(address, now());
+            }
+            self.neighbours
+                .values_mut()
+                .for_each(|neighbour| neighbour.missed_pings += 1);
+            self.neighbours.values().cloned().collect()
+        }
+
+        // -------------------------------
+        // Tracker Registry
+        // -------------------------------
+
+        /// Describes this node the way neighbours see it.
+        fn as_neighbour(&self) -> Neighbour {
+            let mut neighbour = Neighbour::new(self.id, self.advertise_addr.to_string(), self.role);
+            neighbour.set_info(self.peer_info());
+            neighbour
+        }
+
+        /// What this node tells neighbours about itself.
+        fn peer_info(&self) -> PeerInfo {
+            let mut services = match self.role {
+                Role::Miner => SERVICE_CHAIN | SERVICE_DHT | SERVICE_MINING,
+                Role::Node | Role::Tracker => SERVICE_CHAIN | SERVICE_DHT,
+            };
+            if self.config.chain_retention.is_some() {
+                services &= !SERVICE_CHAIN;
+            }
+            PeerInfo {
+                version: Some(USER_AGENT.to_string()),
+                chain_height: Some(self.chain.len()),
+                services: Some(services),
+                network: Some(self.network.clone()),
+                identity: None,
+            }
+        }
+
+        /// Expires stale registrations on trackers and, once every `ANNOUNCE_INTERVAL`,
+        /// returns this node along with the trackers it should announce itself to. Trackers
+        /// also replicate their registry to the other trackers.
+        fn announce_round(&mut self) -> Option<Announcements> {
+            if self.role == Role::Tracker {
+                for expired in self.registry.expire() {
+                    debug!("{} unregistered {} ({})", self.id, expired.id, expired.address);
+                }
+            }
+            if self.last_announce.elapsed() < Duration::new(registry::ANNOUNCE_INTERVAL, 0) {
+                return None;
+            }
+            self.last_announce = Instant::now();
+            let trackers: Vec<Neighbour> = self.neighbours
+                .values()
+                .filter(|neighbour| neighbour.role == Role::Tracker)
+                .cloned()
+                .collect();
+            let registrations = match self.role {
+                Role::Tracker => self.registry.registrations(),
+                Role::Node | Role::Miner => vec![],
+            };
+            Some(Announcements { announcer: self.as_neighbour(), trackers, registrations })
+        }
+
+        /// Merges the registry another tracker replicated, and connects to the trackers
+        /// it knows about that this one does not.
+        pub async fn merge_registry(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if self.role != Role::Tracker {
+                return Ok(GossipPayload::None);
+            }
+            let from_tracker = self.neighbours
+                .values()
+                .any(|neighbour| neighbour.address == sender && neighbour.role == Role::Tracker);
+            if !from_tracker {
+                debug!("{} ignoring a registry from {}, not a known tracker", self.id, sender);
+                return Ok(GossipPayload::None);
+            }
+            buffer.remove(0);
+            let Ok(registrations) = decode::message::<Vec<Registration>>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let trackers: Vec<Neighbour> = registrations
+                .iter()
+                .map(|registration| registration.neighbour.clone())
+                .filter(|neighbour| neighbour.role == Role::Tracker)
+                .collect();
+            let merged = self.registry.merge(registrations, self.id);
+            debug!("{} merged {} registrations from {}", self.id, merged, sender);
+            self.adopt_peers(trackers).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Updates the role of a neighbour that announced it changed it.
+        pub async fn update_role(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Some(Ok(role)) = buffer.get(1).map(|role| Role::from_protocol(*role as u32)) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let Some(neighbour) = self.neighbours.values_mut().find(|neighbour| neighbour.address == sender) else {
+                return Ok(GossipPayload::None);
+            };
+            debug!("{} now sees {} as {:?}", self.id, sender, role);
+            neighbour.role = role;
+            let id = neighbour.id;
+            if self.role == Role::Tracker {
+                self.registry.set_role(&id, role);
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Registers, or refreshes, a peer announcing itself to this tracker.
+        pub async fn register_peer(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if self.role != Role::Tracker {
+                return Ok(GossipPayload::None);
+            }
+            buffer.remove(0);
+            let Ok(neighbour) = decode::neighbour(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            self.registry.announce(neighbour.clone());
+            self.insert_neighbour(neighbour, self.config.max_hops);
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::PeerList` request with a random sample of registered peers.
+        pub async fn share_peers(&self, sender: String) -> IOResult<GossipPayload> {
+            if self.role != Role::Tracker {
+                return Ok(GossipPayload::None);
+            }
+            let Some(requester) = self.neighbours.values().find(|neighbour| neighbour.address == sender) else {
+                return Ok(GossipPayload::None);
+            };
+            let peers = self.registry.sample(registry::PEERLIST_SIZE, requester.id);
+            let _ = gossip::send_peers(self.bind_addr.clone(), requester, peers).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Adds the peers a tracker sent in answer to a `ProtocolMsg::PeerList` request.
+        pub async fn add_peers(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(peers) = decode::neighbours(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            self.adopt_peers(peers).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Adds the peers suggested by a tracker, skipping banned, inadmissible and already known ones.
+        async fn adopt_peers(&mut self, peers: Vec<Neighbour>) {
+            for peer in peers {
+                if self.is_banned(&peer.address) || !self.admissible(&peer) || self.neighbours.contains_key(&peer.id) {
+                    continue;
+                }
+                if self.id < peer.id {
+                    self.start_handshake(peer.address.clone()).await;
+                }
+                self.insert_neighbour(peer, self.config.max_hops);
+            }
+        }
+
+        // -------------------------------
+        // Distributed Hash Table
+        // -------------------------------
+
+        /// Finds the peers holding the record stored under `key`.
+        pub async fn lookup_record(&mut self, key: &str) -> Result<Vec<Contact>, DhtError> {
+            self.lookup(DhtKey::of_record(key)).await
+        }
+
+        /// Finds the peers holding the block with the given hash.
+        pub async fn lookup_block(&mut self, hash: &str) -> Result<Vec<Contact>, DhtError> {
+            self.lookup(DhtKey::of_block(hash)).await
+        }
+
+        /// Announces to the DHT that this node holds the record stored under `key`.
+        pub async fn provide_record(&mut self, key: &str) -> Result<(), DhtError> {
+            self.provide(DhtKey::of_record(key)).await
+        }
+
+        /// Announces to the DHT that this node holds the block with the given hash.
+        pub async fn provide_block(&mut self, hash: &str) -> Result<(), DhtError> {
+            self.provide(DhtKey::of_block(hash)).await
+        }
+
+        /// Iteratively looks `target` up, starting from the closest contacts in the routing table.
+        ///
+        /// # Returns
+        /// * `Vec<Contact>` - The peers holding `target`. Never empty.
+        async fn lookup(&mut self, target: DhtKey) -> Result<Vec<Contact>, DhtError> {
+            let known = self.providers.get(&target);
+            if !known.is_empty() {
+                return Ok(known);
+            }
+            let socket = self.ephemeral_socket().await?;
+            let outcome = self.walk(&socket, target, true).await?;
+            if outcome.providers.is_empty() {
+                return Err(DhtError::NotFound(target));
+            }
+            Ok(outcome.providers)
+        }
+
+        /// Stores this node as a provider of `target` on the `BUCKET_SIZE` contacts closest to it.
+        async fn provide(&mut self, target: DhtKey) -> Result<(), DhtError> {
+            let provider = Contact { id: self.id, address: self.advertise_addr.to_string() };
+            self.providers.add(target, provider.clone());
+            let socket = self.ephemeral_socket().await?;
+            let outcome = self.walk(&socket, target, false).await?;
+            let str_request = serde_json::to_string(&ProvideRequest { target, provider }).unwrap();
+            let mut buffer = vec![ProtocolMsg::DhtProvide as u8];
+            buffer.extend_from_slice(str_request.as_bytes());
+            for contact in outcome.responsive.iter().take(BUCKET_SIZE) {
+                socket.send_to(&buffer, &contact.address).await?;
+            }
+            Ok(())
+        }
+
+        /// Runs an iterative lookup and keeps the routing table up to date with what it learned.
+        async fn walk(&mut self, socket: &dyn Transport, target: DhtKey, stop_at_provider: bool) -> Result<lookup::LookupOutcome, DhtError> {
+            let seeds = self.routing.closest(&target, BUCKET_SIZE);
+            let outcome = lookup::iterative_find(socket, seeds, target, stop_at_provider).await?;
+            for id in &outcome.unresponsive {
+                debug!("{} dropping unresponsive DHT contact {}", self.id, id);
+                self.routing.remove(id);
+            }
+            for contact in &outcome.responsive {
+                self.routing.insert(contact.clone());
+            }
+            Ok(outcome)
+        }
+
+        /// Binds a socket on this node's ip, with a port of its own, for the answers of one
+        /// lookup or sync.
+        async fn ephemeral_socket(&self) -> IOResult<Arc<dyn Transport>> {
+            let mut address: SocketAddr = self.bind_addr
+                .parse()
+                .unwrap_or_else(|_| SocketAddr::from(([127, 0, 0, 1], 0)));
+            address.set_port(0);
+            transport::bind(&address.to_string()).await
+        }
+
+        /// Answers a `ProtocolMsg::DhtFind` query with the providers and closest contacts known for its key.
+        pub async fn answer_find(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(request) = decode::message::<FindRequest>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let response = FindResponse {
+                lookup_id: request.lookup_id,
+                providers: self.providers.get(&request.target),
+                closer: self.routing.closest(&request.target, BUCKET_SIZE),
+            };
+            let _ = gossip::send_dht_found(self.bind_addr.clone(), &sender, &response).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Remembers a peer announcing, through `ProtocolMsg::DhtProvide`, that it holds a key.
+        pub async fn add_provider(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(request) = decode::message::<ProvideRequest>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            if self.is_banned(&request.provider.address) {
+                return Ok(GossipPayload::None);
+            }
+            self.routing.insert(request.provider.clone());
+            self.providers.add(request.target, request.provider);
+            Ok(GossipPayload::None)
+        }
+
+        // -------------------------------
+        // Reputation
+        // -------------------------------
+
+        /// Returns the peers currently banned by this node.
+        pub fn banned_peers(&self) -> Vec<BannedPeer> {
+            let now = now();
+            self.banned
+                .values()
+                .filter(|banned| banned.until > now)
+                .cloned()
+                .collect()
+        }
+
+        /// Whether messages from `sender` should be dropped. Lifts expired bans.
+        fn is_banned(&mut self, sender: &str) -> bool {
+            let now = now();
+            self.banned.retain(|_, banned| banned.until > now);
+            self.banned.contains_key(sender)
+        }
+
+        /// Whether the neighbour at `address` was evicted for missing pings less than
+        /// `STALE_QUARANTINE` seconds ago.
+        fn quarantined(&mut self, address: &str) -> bool {
+            let now = now();
+            self.evicted.retain(|_, evicted| *evicted + STALE_QUARANTINE > now);
+            self.evicted.contains_key(address)
+        }
+
+        /// Updates the score of the neighbour listening on `sender`, banning it if it drops too low.
+        fn report(&mut self, sender: &str, behaviour: Behaviour) {
+            let Some(neighbour) = self.neighbours.values_mut().find(|neighbour| neighbour.address == sender) else {
+                return;
+            };
+            neighbour.score = reputation::apply(neighbour.score, behaviour);
+            debug!("{} scored {:?} from {}: {}", self.id, behaviour, sender, neighbour.score);
+            if neighbour.score > reputation::BAN_THRESHOLD {
+                return;
+            }
+            let banned = BannedPeer {
+                id: neighbour.id,
+                address: sender.to_string(),
+                until: now() + reputation::BAN_DURATION,
+            };
+            warn!("{} banning {} ({}) until {}", self.id, banned.id, banned.address, banned.until);
+            self.drop_neighbour(sender);
+            self.banned.insert(sender.to_string(), banned.clone());
+            self.emit(NodeEvent::PeerBanned(banned));
+        }
+
+        /// Refreshes the liveness of the neighbour listening on `sender`.
+        ///
+        /// # Returns
+        /// `true` if the neighbour was stale, i.e. it just came back from a partition.
+        fn mark_seen(&mut self, sender: &str) -> bool {
+            let mut was_stale = false;
+            self.neighbours
+                .values_mut()
+                .filter(|neighbour| neighbour.address == sender)
+                .for_each(|neighbour| {
+                    was_stale |= neighbour.missed_pings >= STALE_PINGS;
+                    neighbour.mark_seen();
+                });
+            was_stale
+        }
+
+        /// Sends our chain digest to a neighbour that came back after being stale. It does
+        /// the same, and whichever side has the shorter chain resynchronizes when the other
+        /// digest arrives. See `compare_digest`.
+        async fn exchange_digests(&mut self, sender: &str) {
+            let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == sender) else {
+                return;
+            };
+            info!("{} reconnected with {}, comparing chains", self.id, sender);
+            let _ = gossip::summarize(self.bind_addr.clone(), neighbour, &self.chain.digest()).await;
+            self.reconnected.insert(sender.to_string());
+        }
+
+        // -------------------------------
+        // Listening and Chain Validation
+        // -------------------------------
+
+
+
+        /// Starts the task reading from the node's socket, unless it already runs.
+        async fn start_listener(&mut self) -> IOResult<()> {
+            if self.listener.is_none() {
+                let (listener, incoming) = Listener::spawn(self.bind_addr.clone(), self.metrics.clone(), self.config.peer_send_rate).await?;
+                self.listener = Some(listener);
+                self.incoming = Some(incoming);
+            }
+            Ok(())
+        }
+
+        /// Stops reading from the node's socket and closes it. Messages still queued are dropped.
+        async fn stop_listener(&mut self) {
+            self.incoming = None;
+            if let Some(listener) = self.listener.take() {
+                listener.close().await;
+            }
+        }
+
+        /// Waits up to `listen_timeout` for incoming messages, then processes every message
+        /// queued by the listener.
+        pub async fn listen_to_peers(&mut self) -> Result<(), GossipError> {
+            debug!("{} listening", self.id);
+            let Some(incoming) = self.incoming.as_mut() else {
+                return Ok(());
+            };
+            let wait = Duration::new(self.config.listen_timeout, 0);
+            let Ok(Some(first)) = clock::timeout(wait, incoming.recv()).await else {
+                return Ok(());
+            };
+            let mut messages = vec![first];
+            while messages.len() < LISTENER_CHANNEL_CAPACITY {
+                match incoming.try_recv() {
+                    Ok(message) => messages.push(message),
+                    Err(_) => break,
+                }
+            }
+            for message in messages {
+                self.handle_message(message).await?;
+            }
+            Ok(())
+        }
+
+        /// Processes a single message based on its protocol.
+        async fn handle_message(&mut self, message: Incoming) -> Result<(), GossipError> {
+            let Incoming { protocol, sender, buffer } = message;
+            debug!("Received protocol: {}", &protocol);
+            if self.is_banned(&sender) {
+                debug!("{} ignoring banned peer {}", self.id, sender);
+                return Ok(());
+            }
+            // Even a message dropped below shows the neighbour is alive, so it is not evicted
+            // as stale while this node is too busy to read it.
+            if self.mark_seen(&sender) {
+                self.exchange_digests(&sender).await;
+            }
+            match self.rate_limiter.check(&sender, buffer.len()) {
+                RateDecision::Allowed => (),
+                RateDecision::PeerLimited => {
+                    debug!("{} dropping message from {}: over its rate limit", self.id, sender);
+                    self.report(&sender, Behaviour::Flooding);
+                    return Ok(());
+                },
+                // A dropped handshake leaves both sides sealing with sessions the other cannot
+                // open, so handshakes only answer to the sender's own limit.
+                RateDecision::GlobalLimited if protocol != ProtocolMsg::Handshake as u8 => {
+                    debug!("{} dropping message from {}: over the global rate limit", self.id, sender);
+                    return Ok(());
+                },
+                RateDecision::GlobalLimited => (),
+            }
+
+            self.sealed = None;
+            let (protocol, buffer) = if protocol == ProtocolMsg::Encrypted as u8 {
+                match self.open_message(&sender, &buffer) {
+                    Some((plain, session)) if !plain.is_empty() => {
+                        self.sealed = Some(session);
+                        (plain[0], plain)
+                    },
+                    Some(_) => return Ok(()),
+                    None => {
+                        self.restart_handshake(&sender).await;
+                        return Ok(());
+                    },
+                }
+            } else {
+                (protocol, buffer)
+            };
+            self.metrics.message_handled(protocol);
+            let protocol = match ProtocolMsg::try_from(protocol) {
+                Ok(protocol) => protocol,
+                Err(unknown) => {
+                    debug!("{} ignoring message from {}: {}", self.id, sender, unknown);
+                    self.emit(NodeEvent::UnknownProtocol { from: sender, protocol: unknown.0, extension: unknown.is_extension() });
+                    return Ok(());
+                },
+            };
+
+            let payload = match protocol {
+                ProtocolMsg::Greet => self.present_id(sender.clone(), buffer).await?,
+                ProtocolMsg::Farewell => self.remove_neighbour(sender.clone()).await?,
+                ProtocolMsg::Neighbour => self.add_neighbour(sender.clone(), buffer).await?,
+                ProtocolMsg::Transaction => self.add_transaction(sender.clone(), buffer).await?,
+                ProtocolMsg::Chain => self.get_chain(sender.clone(), buffer).await?,
+                ProtocolMsg::PollChain => self.share_chain(sender.clone(), buffer).await?,
+                ProtocolMsg::Handshake => self.handshake(sender.clone(), buffer).await?,
+                ProtocolMsg::Ping => self.answer_ping(sender.clone()).await?,
+                ProtocolMsg::Pong => GossipPayload::None, // Liveness was already refreshed on receipt.
+                ProtocolMsg::Announce => self.register_peer(sender.clone(), buffer).await?,
+                ProtocolMsg::PeerList => self.share_peers(sender.clone()).await?,
+                ProtocolMsg::Peers => self.add_peers(sender.clone(), buffer).await?,
+                ProtocolMsg::DhtFind => self.answer_find(sender.clone(), buffer).await?,
+                ProtocolMsg::DhtProvide => self.add_provider(sender.clone(), buffer).await?,
+                ProtocolMsg::GetHeaders => self.share_headers(sender.clone(), buffer).await?,
+                ProtocolMsg::GetBlocks => self.share_blocks(sender.clone(), buffer).await?,
+                ProtocolMsg::GetRange => self.share_range(sender.clone(), buffer).await?,
+                ProtocolMsg::Inv => self.request_inventory(sender.clone(), buffer).await?,
+                ProtocolMsg::GetData => self.share_inventory(sender.clone(), buffer).await?,
+                ProtocolMsg::Summarize => self.compare_digest(sender.clone(), buffer).await?,
+                ProtocolMsg::Request => self.send_requested_chain(sender.clone()).await?,
+                ProtocolMsg::Record => self.add_record(sender.clone(), buffer).await?,
+                ProtocolMsg::BatchTransaction => self.add_batch(sender.clone(), buffer).await?,
+                ProtocolMsg::Role => self.update_role(sender.clone(), buffer).await?,
+                ProtocolMsg::Registry => self.merge_registry(sender.clone(), buffer).await?,
+                ProtocolMsg::Validate => self.answer_validation(sender.clone(), buffer).await?,
+                ProtocolMsg::NodeInfo => self.answer_node_info(sender.clone(), buffer).await?,
+                ProtocolMsg::PeerInfo => self.answer_peer_info(sender.clone(), buffer).await?,
+                ProtocolMsg::Mempool => self.answer_mempool(sender.clone(), buffer).await?,
+                ProtocolMsg::FilterLoad => self.load_peer_filter(sender.clone(), buffer).await?,
+                ProtocolMsg::FilteredBlock => self.accept_filtered_block(sender.clone(), buffer).await?,
+                ProtocolMsg::Checkpoint => self.accept_checkpoint(sender.clone(), buffer).await?,
+                ProtocolMsg::PoolJoin => self.add_worker(sender.clone(), buffer).await?,
+                ProtocolMsg::PoolJob => self.start_job(sender.clone(), buffer).await?,
+                ProtocolMsg::PoolShare => self.accept_share(sender.clone(), buffer).await?,
+                // Answers to requests of this node, read where the requests were sent
+                // from, and wrappers already opened by the listener or above.
+                ProtocolMsg::DhtFound
+                | ProtocolMsg::Headers
+                | ProtocolMsg::Blocks
+                | ProtocolMsg::Response
+                | ProtocolMsg::Welcome
+                | ProtocolMsg::Challenge
+                | ProtocolMsg::Fragment
+                | ProtocolMsg::Encrypted
+                | ProtocolMsg::Batch => GossipPayload::None,
+                // Evictions and pauses of mining, retired: only the operator API takes them,
+                // as the token they carried went over the wire in the clear.
+                ProtocolMsg::Evict | ProtocolMsg::Mining => {
+                    debug!("{} ignoring an operator command from {}", self.id, sender);
+                    GossipPayload::None
+                },
+            };
+
+            match payload {
+                GossipPayload::Chain(chain) => self.check_chain(&sender, chain),
+                GossipPayload::Entry(entry) => self.accept_entry(entry).await,
+                GossipPayload::Neighbour(neighbour) => self.accept_neighbour(neighbour).await,
+                GossipPayload::None => (),
+            }
+            Ok(())
+        }
+
+        /// Hands an entry received from a neighbour to the miner, if this node mines.
+        async fn accept_entry(&mut self, entry: Box<dyn BlockEntry>) {
+            let Some(mut miner) = self.miner.clone() else {
+                return;
+            };
+            if let Some(transaction) = entry.as_transaction() {
+                push_transaction(&mut miner, transaction.clone()).await;
+                self.emit(NodeEvent::TransactionReceived(transaction.clone()));
+            } else if let Some(record) = entry.as_record() {
+                miner.lock().await.push_record(record.clone());
+                self.emit(NodeEvent::RecordReceived(record.clone()));
+            } else if let Some(batch) = entry.as_batch() {
+                miner.lock().await.push_batch(batch.clone());
+                self.emit(NodeEvent::BatchReceived(batch.clone()));
+            }
+        }
+
+        /// Checks `chain` belongs to the network of this node, which its blocks then all do,
+        /// and started at the difficulty of this node's chain, which its blocks are then held
+        /// to. See `verify_headers_from`.
+        fn check_network(&self, chain: &Chain) -> Result<(), BlockCheckError> {
+            if chain.network() != &self.network {
+                return Err(BlockCheckError::WrongNetwork { index: 0, expected: self.network.clone(), got: chain.network().clone() });
+            }
+            let start = self.chain.start_difficulty();
+            if chain.start_difficulty() != start {
+                return Err(BlockCheckError::WrongDifficulty { expected: chain.difficulty + start - chain.start_difficulty(), got: chain.difficulty });
+            }
+            Ok(())
+        }
+
+        /// Checks `chain` starts at the node's genesis: the configured `Genesis`, or else a
+        /// genesis block giving away the same coins as the node's own, so a received chain
+        /// cannot premine coins out of nothing. Only the timestamp may differ, since nodes
+        /// without a `Genesis` each make their genesis block when they start. Once either
+        /// block is pruned, the state roots stand for what they gave away.
+        fn check_genesis(&self, chain: &Chain) -> Result<(), GenesisError> {
+            if let Some(genesis) = &self.genesis {
+                return genesis.verify(chain);
+            }
+            let own = self.chain.get_block(0).expect("chains hold a genesis block");
+            let matches = chain.get_block(0).filter(|first| {
+                let same_content = match first.pruned || own.pruned {
+                    false => first.data == own.data && first.records == own.records && first.batches == own.batches,
+                    true => !own.state_root.is_empty() && first.state_root == own.state_root,
+                };
+                first.hash == own.hash && first.network == own.network && same_content
+            });
+            if matches.is_none() {
+                let got = chain.get_block(0).map(|first| first.hash.clone()).unwrap_or_default();
+                return Err(GenesisError::Mismatch { expected: own.hash.clone(), got });
+            }
+            Ok(())
+        }
+
+        /// Updates the node's chain if the received chain is valid and longer. Only the
+        /// blocks past the ones the `Verifier` verified already are checked.
+        fn check_chain(&mut self, sender: &str, chain: Chain) {
+            if let Err(e) = self.check_genesis(&chain) {
+                debug!("{} rejected chain from {}: {}", self.id, sender, e);
+                self.report(sender, Behaviour::InvalidChain);
+                return;
+            }
+            if let Err(e) = self.check_network(&chain) {
+                debug!("{} rejected chain from {}: {}", self.id, sender, e);
+                self.report(sender, Behaviour::InvalidChain);
+                return;
+            }
+            let verified = match self.verifier.verify(&chain) {
+                Ok(verified) => verified,
+                Err(e) => {
+                    debug!("{} rejected chain from {}: {}", self.id, sender, e);
+                    self.report(sender, Behaviour::InvalidChain);
+                    return;
+                },
+            };
+            if chain.len() > self.chain.len() {
+                if let Some(height) = self.chain.diverges_at(&chain) {
+                    info!("{} dropped {} blocks past the fork at height {}", self.id, self.chain.len() - height, height);
+                }
+                debug!("{} verified {} blocks of the chain from {}", self.id, verified.n_verified(), sender);
+                self.verifier.commit(verified);
+                self.rewind_filters(&chain);
+                self.chain = chain.with_state(self.verifier.state().clone());
+                self.report(sender, Behaviour::UsefulChain);
+                self.metrics.reorg();
+                self.emit(NodeEvent::ChainAdopted { len: self.chain.len() });
+                self.persist_chain();
+            }
+        }
+
+        // -------------------------------
+        // Neighbor Management
+        // -------------------------------
+
+        /// Handles the presentation of this node's ID when contacted by a neighbour.
+        pub async fn present_id(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let payload = match frame::decode(&buffer) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    debug!("Malformed request to enter network -- {}", e);
+                    return Ok(GossipPayload::None);
+                },
+            };
+            let Ok(Greeting { neighbour, ticket, network }) = decode::message::<Greeting>(payload) else {
+                debug!("Malformed neighbour string -- Unable to create neighbour from enter network request");
+                return Ok(GossipPayload::None);
+            };
+            if network != self.network {
+                // Welcomed without peers, so the greeter learns why it is not taken in.
+                debug!("{} refused {} of network {}", self.id, sender, network);
+                let _ = gossip::welcome(self.bind_addr.clone(), self.id, vec![], self.network.clone(), sender).await;
+                return Ok(GossipPayload::None);
+            }
+
+            if !self.admissible(&neighbour) {
+                return Ok(GossipPayload::None);
+            }
+            if self.role == Role::Tracker && self.gatekeeper.enabled() {
+                let admitted = ticket.is_some_and(|ticket| self.gatekeeper.admits(&neighbour.id, &ticket));
+                if !admitted {
+                    let challenge = self.gatekeeper.challenge(&neighbour.id);
+                    let _ = gossip::challenge(self.bind_addr.clone(), &challenge, &sender).await;
+                    return Ok(GossipPayload::None);
+                }
+            }
+            let mut seeds = vec![];
+            if self.role == Role::Tracker {
+                self.registry.announce(neighbour.clone());
+                seeds = self.registry.sample(registry::SEED_PEERS, neighbour.id);
+            }
+            self.make_room(&neighbour);
+            self.insert_neighbour(neighbour, self.config.max_hops);
+
+            // Sending ID back to the sender, with peers to connect to right away
+            let _ = gossip::welcome(self.bind_addr.clone(), self.id, seeds, self.network.clone(), sender).await;
+
+            Ok(GossipPayload::None)
+        }
+
+        /// Removes a neighbour from the list based on the provided sender address.
+        pub async fn remove_neighbour(&mut self, sender: String) -> IOResult<GossipPayload> {
+            self.drop_neighbour(&sender);
+            Ok(GossipPayload::None)
+        }
+
+        /// Checks that a would-be neighbour is not this node itself, and does not claim the
+        /// id of a known neighbour from another address. Conflicting ids are reported with
+        /// a `NodeEvent::IdConflict` and the neighbour known first is kept.
+        fn admissible(&self, neighbour: &Neighbour) -> bool {
+            if neighbour.address == *self.bind_addr || neighbour.address == *self.advertise_addr {
+                debug!("{} ignoring its own address", self.id);
+                return false;
+            }
+            let known = match neighbour.id == self.id {
+                true => Some(self.advertise_addr.to_string()),
+                false => self.neighbours
+                    .get(&neighbour.id)
+                    .filter(|known| known.address != neighbour.address)
+                    .map(|known| known.address.clone()),
+            };
+            if let Some(known) = known {
+                warn!("{} received id {} from {}, already used by {}", self.id, neighbour.id, neighbour.address, known);
+                self.emit(NodeEvent::IdConflict { id: neighbour.id, known, claimed: neighbour.address.clone() });
+                return false;
+            }
+            true
+        }
+
+        /// Adds a neighbour unless one with the same id is already known or the node is full.
+        ///
+        /// # Arguments
+        /// * `neighbour` - The neighbour to add.
+        /// * `ttl` - Times it may still be forwarded to other neighbours. See `PeerLog`.
+        fn insert_neighbour(&mut self, neighbour: Neighbour, ttl: u8) {
+            if let Some(known) = self.neighbours.get_mut(&neighbour.id) {
+                // Only news are passed on, or every copy relayed back would be sent again.
+                let info = known.info();
+                known.set_info(neighbour.info());
+                if known.info() != info {
+                    self.peer_log.record(neighbour, ttl);
+                }
+                return;
+            }
+            if self.config.max_peers.is_some_and(|max_peers| self.neighbours.len() >= max_peers) {
+                let Some(evicted) = self.eviction_candidate(&neighbour, false) else {
+                    debug!("{} is full, ignoring neighbour {}", self.id, neighbour.id);
+                    return;
+                };
+                debug!("{} is full, evicting {} for {}", self.id, evicted, neighbour.id);
+                self.drop_neighbour(&evicted);
+            }
+            self.emit(NodeEvent::NeighbourAdded { id: neighbour.id, address: neighbour.address.clone() });
+            self.routing.insert(Contact { id: neighbour.id, address: neighbour.address.clone() });
+            self.neighbours.insert(neighbour.id, neighbour.clone());
+            self.peer_log.record(neighbour, ttl);
+        }
+
+        /// Picks the neighbour to make room for `newcomer` once the node is full: the lowest
+        /// scored, least recently seen one, never the last neighbour with a given role.
+        /// The newcomer only takes its place if it brings a role the node lacks, or if the
+        /// candidate scored worse than a newcomer does. Neighbours in good standing are
+        /// kept, so a full node does not swap one for every peer it hears of, but not for
+        /// a peer greeting it, or nodes could not enter a network where every node is full.
+        ///
+        /// # Arguments
+        /// * `newcomer` - The neighbour to make room for.
+        /// * `greeted` - Whether the newcomer greeted this node itself.
+        ///
+        /// # Returns
+        /// * `Option<String>` - The address of the neighbour to evict, `None` to turn the newcomer away.
+        fn eviction_candidate(&self, newcomer: &Neighbour, greeted: bool) -> Option<String> {
+            let count = |role: Role| self.neighbours.values().filter(|neighbour| neighbour.role == role).count();
+            let candidate = self.neighbours
+                .values()
+                .filter(|neighbour| count(neighbour.role) > 1)
+                .min_by_key(|neighbour| (neighbour.score, neighbour.last_seen))?;
+            if count(newcomer.role) == 0 || candidate.score < newcomer.score || (greeted && candidate.score == newcomer.score) {
+                return Some(candidate.address.clone());
+            }
+            None
+        }
+
+        /// Evicts a neighbour for `greeter` if the node is full and does not know it yet. See
+        /// `eviction_candidate`.
+        fn make_room(&mut self, greeter: &Neighbour) {
+            let full = self.config.max_peers.is_some_and(|max_peers| self.neighbours.len() >= max_peers);
+            if !full || self.neighbours.contains_key(&greeter.id) {
+                return;
+            }
+            if let Some(evicted) = self.eviction_candidate(greeter, true) {
+                debug!("{} is full, evicting {} for {}, who greeted it", self.id, evicted, greeter.id);
+                self.drop_neighbour(&evicted);
+            }
+        }
+
+        /// Forgets every neighbour listening on `address`.
+        fn drop_neighbour(&mut self, address: &str) {
+            let removed: Vec<Uuid> = self.neighbours
+                .values()
+                .filter(|neighbour| neighbour.address == address)
+                .map(|neighbour| neighbour.id)
+                .collect();
+            for id in removed {
+                self.neighbours.remove(&id);
+                self.routing.remove(&id);
+                self.providers.remove_provider(&id);
+                self.peer_log.forget(&id);
+                self.emit(NodeEvent::NeighbourRemoved { id, address: address.to_string() });
+            }
+            self.filters.remove(address);
+            self.filtering.remove(address);
+            self.decrypt_failures.remove(address);
+            self.retired_sessions.remove(address);
+            if let Some(pool) = self.pool.as_mut() {
+                pool.leave(address);
+            }
+            if matches!(&self.config.pool, Some(PoolConfig::Worker { coordinator, .. }) if coordinator == address) {
+                self.pool_joined = false;
+                if let Some(search) = self.pool_search.take() {
+                    search.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+
+        /// Adds a neighbour to this node's network from the provided buffer.
+        pub async fn add_neighbour(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Ok(payload) = frame::decode(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            debug!("Received neighbour: {}", String::from_utf8_lossy(payload));
+
+            let Ok(relayed) = decode::relayed_neighbour(payload) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let address = &relayed.neighbour.address;
+            if self.is_banned(address) || self.quarantined(address) || !self.admissible(&relayed.neighbour) {
+                return Ok(GossipPayload::None);
+            }
+
+            Ok(GossipPayload::Neighbour(relayed))
+        }
+
+        /// Adds a neighbour learnt from another one, starting a handshake with it if
+        /// it is new to this node. It is forwarded no more than the sender allows, nor
+        /// than `NodeConfig::max_hops`.
+        async fn accept_neighbour(&mut self, relayed: RelayedNeighbour) {
+            let RelayedNeighbour { neighbour, ttl } = relayed;
+            if !self.neighbours.contains_key(&neighbour.id) && self.id < neighbour.id {
+                // Only the side with the lower id initiates, so both do not race each other.
+                self.start_handshake(neighbour.address.clone()).await;
+            }
+            self.insert_neighbour(neighbour, ttl.min(self.config.max_hops));
+        }
+
+        /// Answers a ping from a known neighbour.
+        pub async fn answer_ping(&self, sender: String) -> IOResult<GossipPayload> {
+            if let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == sender) {
+                let _ = gossip::pong(self.bind_addr.clone(), neighbour).await;
+            }
+            Ok(GossipPayload::None)
+        }
+
+        // -------------------------------
+        // Channel Encryption
+        // -------------------------------
+
+        /// Sends a fresh public key to a neighbour and remembers the private half until it
+        /// answers. Does nothing while a handshake started earlier may still be answered,
+        /// since the answer to it would not match the new key. Either side may start a
+        /// handshake, see `handshake` for when both do at once.
+        async fn start_handshake(&mut self, neighbour: String) {
+            if self.handshake_pending(&neighbour) {
+                return;
+            }
+            let handshake = match Handshake::new() {
+                Ok(handshake) => handshake,
+                Err(e) => {
+                    warn!("{} could not start handshake: {}", self.id, e);
+                    return;
+                }
+            };
+            let _ = gossip::send_handshake(self.bind_addr.clone(), neighbour.clone(), handshake.public_key(), true, &self.handshake_info(&handshake)).await;
+            self.pending_handshakes.insert(neighbour, (handshake, now()));
+        }
+
+        /// Completes a key exchange, answering it first if the neighbour initiated it.
+        pub async fn handshake(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if buffer.len() < 2 {
+                return Ok(GossipPayload::None);
+            }
+            let initiated_by_peer = buffer[1] == 1;
+            let key_end = buffer.len().min(2 + PUBLIC_KEY_LEN);
+            let peer_public_key = &buffer[2..key_end];
+            // Peers that do not describe themselves are taken as `mainnet` ones, and refused
+            // for proving no identity.
+            let peer_info = decode::message::<PeerInfo>(&buffer[key_end..]).unwrap_or_default();
+            let network = peer_info.network.clone().unwrap_or_default();
+            if network != self.network {
+                debug!("{} refused to handshake with {} of network {}", self.id, sender, network);
+                self.pending_handshakes.remove(&sender);
+                self.drop_neighbour(&sender);
+                return Ok(GossipPayload::None);
+            }
+            if !self.authentic(&sender, peer_public_key, &peer_info) {
+                self.pending_handshakes.remove(&sender);
+                self.drop_neighbour(&sender);
+                return Ok(GossipPayload::None);
+            }
+            if !self.adopt_prover(&sender, &peer_info) {
+                return Ok(GossipPayload::None);
+            }
+            let handshake = if initiated_by_peer {
+                // Both sides started a handshake at once: the one started by the lower id
+                // goes on, and the other side drops its own to answer it, so both end up
+                // with the same session. A handshake left unanswered yields too.
+                let peer_id = peer_info.identity.as_ref().map(IdentityProof::id).unwrap_or_default();
+                if self.id < peer_id && self.handshake_pending(&sender) {
+                    debug!("{} keeps its own handshake with {}", self.id, sender);
+                    return Ok(GossipPayload::None);
+                }
+                self.pending_handshakes.remove(&sender);
+                match Handshake::new() {
+                    Ok(handshake) => {
+                        let _ = gossip::send_handshake(self.bind_addr.clone(), sender.clone(), handshake.public_key(), false, &self.handshake_info(&handshake)).await;
+                        handshake
+                    },
+                    Err(_) => return Ok(GossipPayload::None),
+                }
+            } else {
+                match self.pending_handshakes.remove(&sender) {
+                    Some((handshake, _)) => handshake,
+                    None => return Ok(GossipPayload::None), // Unsolicited answer.
+                }
+            };
+            match handshake.complete(peer_public_key, !initiated_by_peer) {
+                Ok(session) => {
+                    let session = Arc::new(session);
+                    self.decrypt_failures.remove(&sender);
+                    if let Some(retired) = self.session_with(&sender) {
+                        self.retired_sessions.insert(sender.clone(), retired);
+                    }
+                    self.neighbours
+                        .values_mut()
+                        .filter(|neighbour| neighbour.address == sender)
+                        .for_each(|neighbour| {
+                            neighbour.session = Some(session.clone());
+                            neighbour.set_info(peer_info.clone());
+                        });
+                    debug!("{} established an encrypted channel with {}", self.id, sender);
+                    self.share_checkpoints(&sender).await;
+                },
+                Err(e) => warn!("{} failed handshake with {}: {}", self.id, sender, e),
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// What this node tells about itself along the key of `handshake`, with the proof it
+        /// owns its identity.
+        fn handshake_info(&self, handshake: &Handshake) -> PeerInfo {
+            PeerInfo {
+                identity: Some(self.identity.prove(&handshake.public_key(), &self.network)),
+                ..self.peer_info()
+            }
+        }
+
+        /// Checks the identity a neighbour proves along its handshake key: the proof must
+        /// sign `peer_public_key`, and the id it derives must be the one the neighbour at
+        /// `sender` greeted with, if it did. Neighbours proving no identity are refused.
+        fn authentic(&self, sender: &str, peer_public_key: &[u8], peer_info: &PeerInfo) -> bool {
+            let Some(proof) = &peer_info.identity else {
+                warn!("{} refused {}, proving no identity", self.id, sender);
+                return false;
+            };
+            if !proof.verify(peer_public_key, &self.network) {
+                warn!("{} received a handshake key from {} not signed by its identity", self.id, sender);
+                return false;
+            }
+            match self.neighbours.values().find(|neighbour| neighbour.address == sender) {
+                Some(claimed) if claimed.id == proof.id() => true,
+                Some(claimed) => {
+                    warn!("{} refused {}, claiming id {} but proving {}", self.id, sender, claimed.id, proof.id());
+                    false
+                },
+                None => true,
+            }
+        }
+
+        /// Adds the peer at `sender` under the id it proved, if it is not a neighbour yet.
+        /// Peers learnt from the same relay race each other: the one with the lower id may
+        /// start the handshake before the other heard of it. Identities cost nothing, so
+        /// such peers never evict a neighbour, and trackers only take in the ones that
+        /// registered, paying their admission ticket when greeting. See `present_id`.
+        ///
+        /// # Returns
+        /// * `bool` - Whether `sender` is a neighbour, and the handshake can go on.
+        fn adopt_prover(&mut self, sender: &str, peer_info: &PeerInfo) -> bool {
+            if self.neighbours.values().any(|neighbour| neighbour.address == sender) {
+                return true;
+            }
+            let Some(proof) = &peer_info.identity else {
+                return false;
+            };
+            let mut neighbour = Neighbour::new(proof.id(), sender.to_string(), Role::Node);
+            neighbour.set_info(peer_info.clone());
+            let unregistered = self.role == Role::Tracker && self.gatekeeper.enabled() && !self.registry.contains(&neighbour.id);
+            let full = self.config.max_peers.is_some_and(|max_peers| self.neighbours.len() >= max_peers);
+            if self.is_banned(sender) || !self.admissible(&neighbour) || unregistered || full {
+                debug!("{} refused a handshake from {}", self.id, sender);
+                return false;
+            }
+            self.insert_neighbour(neighbour, self.config.max_hops);
+            self.neighbours.values().any(|neighbour| neighbour.address == sender)
+        }
+
+        /// Whether a handshake this node started with `address` may still be answered: it
+        /// was started less than `HANDSHAKE_RETRY` seconds ago.
+        fn handshake_pending(&self, address: &str) -> bool {
+            self.pending_handshakes.get(address).is_some_and(|(_, started)| now() < started + HANDSHAKE_RETRY)
+        }
+
+        /// Starts a new handshake with a neighbour that seals messages this node cannot
+        /// open: it has no session to open them, as when the answer to an earlier handshake
+        /// was lost, or its session failed to open `MAX_DECRYPT_FAILURES` messages in a row,
+        /// in which case the session is dropped. Waits `HANDSHAKE_RETRY` seconds for a
+        /// pending one to be answered first.
+        async fn restart_handshake(&mut self, sender: &str) {
+            if !self.neighbours.values().any(|neighbour| neighbour.address == sender) {
+                return;
+            }
+            if self.session_with(sender).is_some() {
+                if self.decrypt_failures.get(sender).is_none_or(|failures| *failures < MAX_DECRYPT_FAILURES) {
+                    return;
+                }
+                warn!("{} renegotiates its session with {}, which fails to open its messages", self.id, sender);
+                self.decrypt_failures.remove(sender);
+                // It may still open what the neighbour sealed before it renegotiates too.
+                let retired = self.neighbours
+                    .values_mut()
+                    .filter(|neighbour| neighbour.address == sender)
+                    .find_map(|neighbour| neighbour.session.take());
+                if let Some(retired) = retired {
+                    self.retired_sessions.insert(sender.to_string(), retired);
+                }
+            }
+            self.start_handshake(sender.to_string()).await;
+        }
+
+        /// Returns the session established with the neighbour at `address`, if any.
+        fn session_with(&self, address: &str) -> Option<Arc<Session>> {
+            self.neighbours
+                .values()
+                .find(|neighbour| neighbour.address == address)
+                .and_then(|neighbour| neighbour.session.clone())
+        }
+
+        /// Returns the session to seal the answer to the request being handled with: the
+        /// one that opened it, if it arrived sealed.
+        fn reply_session(&self) -> Option<Arc<Session>> {
+            self.sealed.clone()
+        }
+
+        /// Decrypts a message using the session of the neighbour that sent it, or the one it
+        /// replaced if the neighbour sealed the message before their last handshake, counting
+        /// the messages in a row neither opens. See `restart_handshake`.
+        ///
+        /// # Returns
+        /// * `Option<(Vec<u8>, Arc<Session>)>` - The message and the session that opened it.
+        fn open_message(&mut self, sender: &str, buffer: &[u8]) -> Option<(Vec<u8>, Arc<Session>)> {
+            let Some(session) = self.session_with(sender) else {
+                debug!("{} has no session with {}", self.id, sender);
+                return None;
+            };
+            let opened = match session.open(buffer) {
+                Ok(plain) => {
+                    self.retired_sessions.remove(sender);
+                    Ok((plain, session))
+                },
+                Err(SecureError::Decryption) => match self.retired_sessions.get(sender).cloned() {
+                    Some(retired) => retired.open(buffer).map(|plain| (plain, retired)),
+                    None => Err(SecureError::Decryption),
+                },
+                Err(e) => Err(e),
+            };
+            match opened {
+                Ok(opened) => {
+                    self.decrypt_failures.remove(sender);
+                    Some(opened)
+                },
+                Err(e) => {
+                    if matches!(e, SecureError::Decryption) {
+                        *self.decrypt_failures.entry(sender.to_string()).or_default() += 1;
+                    }
+                    warn!("{} dropped a message from {}: {}", self.id, sender, e);
+                    None
+                },
+            }
+        }
+
+        // -------------------------------
+        // Transaction Handling
+        // -------------------------------
+
+        /// Validates a transaction from the buffer and relays it the first time it is seen.
+        /// Miners also get it back to include in the next block. Its expiry is only checked
+        /// when it is submitted here, not when a neighbour relays it.
+        pub async fn add_transaction(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(transaction) = decode::transaction(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            if self.seen.contains(&transaction.id()) {
+                return Ok(GossipPayload::None);
+            }
+            let report = self.validate_transaction(&transaction);
+            let relayed = self.neighbours.values().any(|neighbour| neighbour.address == sender);
+            if !(report.is_valid() || relayed && report.is_valid_except(Check::Expiry)) {
+                self.report(&sender, Behaviour::InvalidTransaction);
+                return Ok(GossipPayload::None);
+            }
+            let ttl = self.seen.take_announced(&transaction.id()).unwrap_or(self.config.max_hops);
+            self.relay_transaction(transaction.clone(), Some(&sender), ttl).await;
+
+            if self.role != Role::Miner {
+                return Ok(GossipPayload::None);
+            }
+            Ok(GossipPayload::Entry(Box::new(transaction)))
+        }
+
+        /// Runs every check a transaction goes through before being relayed to the miners,
+        /// against the chain and the transactions relayed so far.
+        pub fn validate_transaction(&self, transaction: &Transaction) -> ValidationReport {
+            validation::validate(transaction, &self.chain, self.seen.transactions(), now(), &self.network, &self.config.chain.mint)
+        }
+
+        /// Answers a `ProtocolMsg::Validate` request with the `ValidationReport` of the
+        /// transaction it carries, which is neither kept nor relayed.
+        pub async fn answer_validation(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let transaction = rpc::parse(&buffer).and_then(|(id, payload)| {
+                let transaction = decode::transaction(payload).ok()?;
+                Some((id, transaction))
+            });
+            let Some((id, transaction)) = transaction else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let report = serde_json::to_vec(&self.validate_transaction(&transaction)).unwrap();
+            gossip::respond(self.bind_addr.clone(), &sender, self.reply_session().as_deref(), &id, &report).await?;
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::NodeInfo` request with the `NodeStatus` of this node.
+        pub async fn answer_node_info(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Some((id, _)) = rpc::parse(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let status = serde_json::to_vec(&self.status()).unwrap();
+            gossip::respond(self.bind_addr.clone(), &sender, self.reply_session().as_deref(), &id, &status).await?;
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::PeerInfo` request with the `PeerStatus` of every neighbour.
+        pub async fn answer_peer_info(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Some((id, _)) = rpc::parse(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let peers = serde_json::to_vec(&self.peer_statuses()).unwrap();
+            gossip::respond(self.bind_addr.clone(), &sender, self.reply_session().as_deref(), &id, &peers).await?;
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::Mempool` request with the transactions waiting to be
+        /// mined, or only the one whose id the request carries. Nodes that are not miners
+        /// answer with none.
+        pub async fn answer_mempool(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let request = rpc::parse(&buffer).and_then(|(id, payload)| Some((id, str::from_utf8(payload).ok()?.to_string())));
+            let Some((id, transaction_id)) = request else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let pending = match transaction_id.is_empty() {
+                true => self.list_pending().await.unwrap_or_default(),
+                false => self.get_pending(&transaction_id).await.ok().flatten().into_iter().collect(),
+            };
+            let wire: Vec<String> = pending.into_iter().map(Into::into).collect();
+            gossip::respond(self.bind_addr.clone(), &sender, self.reply_session().as_deref(), &id, &serde_json::to_vec(&wire).unwrap()).await?;
+            Ok(GossipPayload::None)
+        }
+
+        /// Remembers a transaction and, the first time it is seen, announces it to every
+        /// neighbour but the one it came from, if it has forwards left.
+        ///
+        /// # Arguments
+        /// * `transaction` - The transaction to relay.
+        /// * `sender` - The address of the neighbour it came from, `None` if it was submitted locally.
+        /// * `ttl` - Times it may still be forwarded. Neighbours are sent one less.
+        async fn relay_transaction(&mut self, transaction: Transaction, sender: Option<&str>, ttl: u8) {
+            let id = transaction.id();
+            if !self.seen.insert(transaction) {
+                return;
+            }
+            let Some(ttl) = ttl.checked_sub(1) else {
+                return;
+            };
+            for neighbour in self.neighbours.values().filter(|neighbour| Some(neighbour.address.as_str()) != sender) {
+                let _ = gossip::send_inventory(self.bind_addr.clone(), neighbour, vec![id.clone()], ttl).await;
+            }
+        }
+
+        /// Asks the sender of a `ProtocolMsg::Inv` message for the transactions this node has not seen.
+        pub async fn request_inventory(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(inventory) = decode::message::<Inventory>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let unseen = self.seen.unseen(inventory.ids);
+            if unseen.is_empty() {
+                return Ok(GossipPayload::None);
+            }
+            for id in &unseen {
+                self.seen.announce(id.clone(), inventory.ttl.min(self.config.max_hops));
+            }
+            if let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == sender) {
+                let _ = gossip::request_data(self.bind_addr.clone(), neighbour, unseen).await;
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::GetData` message with the requested transactions this node still remembers.
+        pub async fn share_inventory(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
             buffer.remove(0);
-            let str_buffer = str::from_utf8(&buffer)
-                .expect("Malformed request to check chain -- Unable to parse");
+            let Ok(ids) = decode::message::<Vec<String>>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == sender) else {
+                return Ok(GossipPayload::None);
+            };
+            for transaction in ids.iter().filter_map(|id| self.seen.get(id)) {
+                let _ = gossip::send_transaction(self.bind_addr.clone(), neighbour, transaction.clone()).await;
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Submits a transaction, record or batch to the network. The entry is validated
+        /// against this node's chain, queued for mining if this node is a miner, and relayed
+        /// towards miners.
+        ///
+        /// # Arguments
+        /// * `entry` - The entry to be submitted.
+        ///
+        /// # Returns
+        /// * `SubmitReceipt` - Holds the entry's id, to be passed to `entry_status`.
+        pub async fn submit_entry(&mut self, entry: Box<dyn BlockEntry>) -> Result<SubmitReceipt, SubmitError> {
+            let id = entry.id();
+            if self.submitted.contains_key(&id) || self.chain.find_entry(&id).is_some() {
+                return Err(SubmitError::AlreadySubmitted(id));
+            }
+            entry.validate(self.chain.blocks(), &self.network, &self.config.chain)?;
+            if self.miner.is_none() && self.neighbours.is_empty() {
+                return Err(SubmitError::NoMiners);
+            }
 
-            let cleared = Node::sanitize(str_buffer.to_string());
-            let chain: Chain = serde_json::from_str(&cleared)
-                .expect("Malformed chain string -- Unable to create chain from request");
+            let relayed_to = if let Some(transaction) = entry.as_transaction() {
+                if let Some(miner) = self.miner.as_mut() {
+                    push_transaction(miner, transaction.clone()).await;
+                }
+                self.relay_transaction(transaction.clone(), None, self.config.max_hops).await;
+                self.neighbours.len()
+            } else if let Some(record) = entry.as_record() {
+                if let Some(miner) = self.miner.as_ref() {
+                    miner.lock().await.push_record(record.clone());
+                }
+                let mut relayed_to = 0;
+                for miner in self.neighbours.values().filter(|neighbour| neighbour.role == Role::Miner) {
+                    if gossip::send_record(self.bind_addr.clone(), miner, record.clone()).await.is_ok() {
+                        relayed_to += 1;
+                    }
+                }
+                relayed_to
+            } else if let Some(batch) = entry.as_batch() {
+                if !batch.verify_signature(&self.network) {
+                    return Err(SubmitError::InvalidSignature);
+                }
+                if let Some(miner) = self.miner.as_ref() {
+                    miner.lock().await.push_batch(batch.clone());
+                }
+                let mut relayed_to = 0;
+                for miner in self.neighbours.values().filter(|neighbour| neighbour.role == Role::Miner) {
+                    if gossip::send_batch(self.bind_addr.clone(), miner, batch).await.is_ok() {
+                        relayed_to += 1;
+                    }
+                }
+                relayed_to
+            } else {
+                return Err(SubmitError::UnsupportedEntry);
+            };
+
+            let submitted_at = now();
+            self.submitted.insert(id.clone(), submitted_at);
+            Ok(SubmitReceipt { id, relayed_to, submitted_at })
+        }
+
+        /// Forgets the entries submitted through this node that made it into the chain, which
+        /// `entry_status` finds there, or that were submitted longer than `TRANSACTION_TTL` ago.
+        fn prune_submitted(&mut self) {
+            let now = now();
+            let chain = &self.chain;
+            self.submitted.retain(|id, submitted_at| {
+                *submitted_at + validation::TRANSACTION_TTL >= now && chain.find_entry(id).is_none()
+            });
+        }
+
+        /// Tells whether an entry, identified by the id in its `SubmitReceipt`, made it into the chain.
+        pub fn entry_status(&self, id: &str) -> EntryStatus {
+            if let Some(block_index) = self.chain.find_entry(id) {
+                return EntryStatus::Confirmed { block_index };
+            }
+            if self.submitted.contains_key(id) {
+                return EntryStatus::Pending;
+            }
+            EntryStatus::Unknown
+        }
 
-            Ok(Some(Box::new(chain)))
+        /// Adds a record from the buffer, if this node is a miner.
+        pub async fn add_record(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if self.role != Role::Miner {
+                return Ok(GossipPayload::None);
+            }
+            buffer.remove(0);
+            let Ok(record) = decode::record(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            if block::check_record(record.clone(), &self.network, &self.config.chain.records, self.chain.blocks()).is_err() {
+                self.report(&sender, Behaviour::InvalidRecord);
+                return Ok(GossipPayload::None);
+            }
+            Ok(GossipPayload::Entry(Box::new(record)))
         }
 
-        /// Shares the current chain with any requesting neighbour.
-        pub async fn share_chain(&self) -> IOResult<Option<Box<dyn Reply>>> {
-            Ok(None)
+        /// Adds a batch of transfers from the buffer, if this node is a miner and the batch
+        /// is signed for its network and may be added to its chain.
+        pub async fn add_batch(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if self.role != Role::Miner {
+                return Ok(GossipPayload::None);
+            }
+            buffer.remove(0);
+            let Ok(batch) = decode::batch(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let fresh = block::check_sender_nonce(&batch.sender, batch.nonce, &block::last_nonces(self.chain.blocks_from(0)));
+            if !batch.verify_signature(&self.network)
+                || fresh.is_err()
+                || block::check_batch(batch.clone(), self.chain.blocks()).is_err() {
+                self.report(&sender, Behaviour::InvalidTransaction);
+                return Ok(GossipPayload::None);
+            }
+            Ok(GossipPayload::Entry(Box::new(batch)))
         }
 
         // -------------------------------
-        // Utility Methods
+        // Chain Management
         // -------------------------------
 
-        /// Sanitizes a string by only allowing alphanumeric characters and a few special characters.
-        fn sanitize(string: String) -> String {
-            let accepted_chars = " \",;:.-{}[]_=/+";
-            string.chars()
-                .take_while(|c| c.is_alphanumeric() || accepted_chars.contains(*c))
-                .collect()
+        /// Receives a chain from the buffer and returns it.
+        pub async fn get_chain(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Ok(payload) = frame::decode(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let Ok(chain) = decode::chain(payload) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+
+            Ok(GossipPayload::Chain(chain))
+        }
+
+        /// Compares a neighbour's chain digest with our chain and asks for the chain if theirs is longer.
+        pub async fn compare_digest(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(digest) = decode::message::<ChainDigest>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            self.neighbours
+                .values_mut()
+                .filter(|neighbour| neighbour.address == sender)
+                .for_each(|neighbour| neighbour.chain_height = Some(digest.height));
+            let reconnected = self.reconnected.remove(&sender);
+            if digest.height <= self.chain.len() {
+                return Ok(GossipPayload::None);
+            }
+            if reconnected {
+                // The chains may have diverged while apart: fetch only what differs.
+                self.resync.insert(sender);
+                return Ok(GossipPayload::None);
+            }
+            if let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == sender) {
+                let _ = gossip::request_chain(self.bind_addr.clone(), neighbour).await;
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Sends the whole chain to a neighbour that asked for it after seeing our digest,
+        /// unless a bandwidth cap was reached. The neighbour can still sync headers-first.
+        pub async fn send_requested_chain(&mut self, sender: String) -> IOResult<GossipPayload> {
+            if self.bandwidth.exceeded(self.metrics.total_bytes()) {
+                debug!("{} over its bandwidth cap, not pushing the chain to {}", self.id, sender);
+                return Ok(GossipPayload::None);
+            }
+            if self.filters.contains_key(&sender) {
+                return Ok(GossipPayload::None); // It gets filtered blocks instead.
+            }
+            if let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == sender) {
+                let _ = gossip::send_chain(self.bind_addr.clone(), neighbour, self.chain.clone()).await;
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Answers a `ProtocolMsg::PollChain` request with the current chain.
+        pub async fn share_chain(&mut self, sender: String, buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Some((id, _)) = rpc::parse(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            let chain = serde_json::to_vec(&self.chain).unwrap();
+            gossip::respond(self.bind_addr.clone(), &sender, self.reply_session().as_deref(), &id, &chain).await?;
+            Ok(GossipPayload::None)
+        }
+
+        // -------------------------------
+        // Notification Filters
+        // -------------------------------
+
+        /// Loads `filter` on the neighbour listening on `neighbour`, which then relays the
+        /// new blocks of its chain filtered by it, as `NodeEvent::FilteredBlock`s, rather
+        /// than whole chains. An empty filter clears the one loaded.
+        ///
+        /// # Returns
+        /// * `Result<(), FilterError>` - An error if `neighbour` is not a neighbour, the
+        ///   filter holds over `MAX_FILTER_ENTRIES` entries or it could not be sent.
+        pub async fn load_filter(&mut self, neighbour: &str, filter: &NotificationFilter) -> Result<(), FilterError> {
+            if filter.len() > MAX_FILTER_ENTRIES {
+                return Err(FilterError::TooLarge(filter.len()));
+            }
+            let target = self.neighbours
+                .values()
+                .find(|known| known.address == neighbour)
+                .ok_or_else(|| FilterError::NotNeighbour(neighbour.to_string()))?;
+            gossip::load_filter(self.bind_addr.clone(), target, filter).await?;
+            match filter.is_empty() {
+                true => self.filtering.remove(neighbour),
+                false => self.filtering.insert(neighbour.to_string()),
+            };
+            Ok(())
+        }
+
+        /// Loads, or clears, the filter a neighbour sent. Blocks are relayed from the tip
+        /// of the chain at the time it was loaded. Filters from strangers, and past
+        /// `MAX_FILTERS` neighbours, are ignored.
+        async fn load_peer_filter(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if !self.neighbours.values().any(|neighbour| neighbour.address == sender) {
+                return Ok(GossipPayload::None);
+            }
+            buffer.remove(0);
+            let Ok(filter) = decode::filter(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            if filter.is_empty() {
+                self.filters.remove(&sender);
+                return Ok(GossipPayload::None);
+            }
+            if !self.filters.contains_key(&sender) && self.filters.len() >= MAX_FILTERS {
+                debug!("{} already relays filtered blocks to {} neighbours, ignoring {}", self.id, MAX_FILTERS, sender);
+                return Ok(GossipPayload::None);
+            }
+            self.filters.insert(sender, LoadedFilter { filter, next: self.chain.len() });
+            Ok(GossipPayload::None)
+        }
+
+        /// Sends the blocks added to the chain since the last call, filtered, to every
+        /// neighbour that loaded a filter. After a reorg, that is every block past the
+        /// common ancestor; the neighbour can tell from their headers.
+        async fn relay_filtered(&mut self) {
+            let blocks = self.chain.blocks();
+            for (address, loaded) in self.filters.iter_mut() {
+                let start = loaded.next.min(blocks.len());
+                if start == blocks.len() {
+                    continue;
+                }
+                let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == *address) else {
+                    continue;
+                };
+                for block in &blocks[start..] {
+                    let filtered = loaded.filter.filter_block(block);
+                    let _ = gossip::send_filtered_block(self.bind_addr.clone(), neighbour, &filtered).await;
+                }
+                loaded.next = blocks.len();
+            }
+        }
+
+        /// Has `relay_filtered` send the blocks of `chain`, about to replace the chain of the
+        /// node, from the one after their common ancestor, to the neighbours it already sent
+        /// blocks past it.
+        fn rewind_filters(&mut self, chain: &Chain) {
+            if self.filters.is_empty() {
+                return;
+            }
+            let next = self.chain.common_ancestor(chain).map_or(0, |(index, _)| index + 1);
+            for loaded in self.filters.values_mut() {
+                loaded.next = loaded.next.min(next);
+            }
+        }
+
+        /// Publishes a filtered block relayed by a neighbour this node loaded a filter on.
+        async fn accept_filtered_block(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if !self.filtering.contains(&sender) {
+                return Ok(GossipPayload::None);
+            }
+            buffer.remove(0);
+            let block: FilteredBlock = match decode::filtered_block(&buffer) {
+                Ok(block) => block,
+                Err(_) => {
+                    self.report(&sender, Behaviour::MalformedMessage);
+                    return Ok(GossipPayload::None);
+                },
+            };
+            debug!("{} received filtered block {} from {}", self.id, block.header.index, sender);
+            self.emit(NodeEvent::FilteredBlock { from: sender, block });
+            Ok(GossipPayload::None)
+        }
+
+        // -------------------------------
+        // Signed Checkpoints
+        // -------------------------------
+
+        /// Pins a checkpoint signed by a trusted publisher, saves it to the chain store and
+        /// gossips it to every neighbour but `from`, the one it came from, if it was not
+        /// pinned yet. Checkpoints conflicting with one pinned already are refused: the
+        /// first one pinned at an index holds.
+        ///
+        /// If the chain of this node holds another block at the index, it is rolled back to
+        /// the blocks before it, so only chains holding the checkpoint are served and
+        /// adopted from then on. See `roll_back`.
+        ///
+        /// # Returns
+        /// * `bool` - Whether the checkpoint was pinned.
+        async fn pin_checkpoint(&mut self, signed: SignedCheckpoint, from: Option<String>) -> bool {
+            let checkpoint = signed.checkpoint.clone();
+            match self.config.chain.pin(checkpoint.clone()) {
+                Ok(true) => (),
+                Ok(false) => return false,
+                Err(e) => {
+                    warn!("{} refused a signed checkpoint: {}", self.id, e);
+                    return false;
+                },
+            }
+            let _ = self.verifier.pin(checkpoint.clone());
+            if let Err(e) = self.config.chain.verify_checkpoints(self.chain.blocks()) {
+                warn!("{} holds a chain conflicting with a signed checkpoint: {}", self.id, e);
+                self.roll_back(checkpoint.index);
+            }
+            if self.chain_store.is_some() {
+                if let Err(e) = self.save_checkpoint(&signed) {
+                    warn!("{} failed to save a signed checkpoint: {}", self.id, e);
+                }
+            }
+            info!("{} pinned block {} ({})", self.id, checkpoint.index, checkpoint.hash);
+            let neighbours: Vec<Neighbour> = self.neighbours
+                .values()
+                .filter(|neighbour| from.as_ref() != Some(&neighbour.address))
+                .cloned()
+                .collect();
+            for neighbour in &neighbours {
+                let _ = gossip::send_checkpoint(self.bind_addr.clone(), neighbour, &signed).await;
+            }
+            self.share_later(signed);
+            self.emit(NodeEvent::CheckpointPinned { from, checkpoint });
+            true
+        }
+
+        /// Keeps `signed` among the last `SHARED_CHECKPOINTS` checkpoints shared with the
+        /// neighbours this node handshakes with.
+        fn share_later(&mut self, signed: SignedCheckpoint) {
+            self.signed_checkpoints.push_back(signed);
+            if self.signed_checkpoints.len() > SHARED_CHECKPOINTS {
+                self.signed_checkpoints.pop_front();
+            }
+        }
+
+        /// Drops the blocks of the chain from the one at `index` on, and saves what is left.
+        /// The state of the blocks kept is taken back from the verifier if they are recent
+        /// enough, or rebuilt from them otherwise, which a chain holding pruned blocks cannot:
+        /// such a node must then be synchronized from scratch.
+        fn roll_back(&mut self, index: usize) {
+            let kept: Vec<Block> = self.chain.blocks_from(0).take_while(|block| block.index < index).cloned().collect();
+            if kept.is_empty() {
+                warn!("{} holds another genesis block than a signed checkpoint", self.id);
+                return;
+            }
+            let dropped = self.chain.len() - kept.len();
+            let difficulty = self.chain.difficulty_at(index);
+            let state = match self.verifier.roll_back(&kept) {
+                true => self.verifier.state().clone(),
+                false => ChainState::of(&kept),
+            };
+            if !state.is_complete() {
+                warn!("{} cannot rebuild the state of its pruned chain below block {}", self.id, index);
+            }
+            let chain = Chain::from_blocks_unverified(kept, difficulty).with_state(state);
+            self.rewind_filters(&chain);
+            self.chain = chain;
+            info!("{} rolled back {} blocks to block {}", self.id, dropped, self.chain.get_last_block().index);
+            self.metrics.reorg();
+            self.persist_chain();
+        }
+
+        /// Adds `signed` to the signed checkpoints saved to the `CHECKPOINTS_NAMESPACE`
+        /// namespace of the chain store. See `load_checkpoints`.
+        fn save_checkpoint(&mut self, signed: &SignedCheckpoint) -> Result<(), ChainStoreError> {
+            let mut store = persistence::lock(self.chain_store.as_ref().ok_or(ChainStoreError::NoStore)?);
+            let store = store.namespace(CHECKPOINTS_NAMESPACE)?;
+            let mut saved: Vec<SignedCheckpoint> = match store.exists()? {
+                true => store.load()?,
+                false => vec![],
+            };
+            saved.push(signed.clone());
+            store.save(&saved)?;
+            Ok(())
+        }
+
+        /// Pins the signed checkpoints saved with `save_checkpoint` again, the ones of
+        /// publishers this node still trusts, before the chain is loaded.
+        ///
+        /// # Returns
+        /// The number of checkpoints pinned.
+        pub fn load_checkpoints(&mut self) -> Result<usize, ChainStoreError> {
+            let saved: Vec<SignedCheckpoint> = {
+                let mut store = persistence::lock(self.chain_store.as_ref().ok_or(ChainStoreError::NoStore)?);
+                let store = store.namespace(CHECKPOINTS_NAMESPACE)?;
+                if !store.exists()? {
+                    return Ok(0);
+                }
+                store.load()?
+            };
+            let mut pinned = 0;
+            for signed in saved {
+                if !self.config.chain.is_publisher(&signed.publisher) || !signed.verify(&self.network) {
+                    continue;
+                }
+                if let Ok(true) = self.config.chain.pin(signed.checkpoint.clone()) {
+                    let _ = self.verifier.pin(signed.checkpoint.clone());
+                    self.share_later(signed);
+                    pinned += 1;
+                }
+            }
+            Ok(pinned)
+        }
+
+        /// Pins a checkpoint gossiped by a neighbour, if it is signed by one of the
+        /// chain's checkpoint publishers. See `pin_checkpoint`.
+        async fn accept_checkpoint(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            buffer.remove(0);
+            let Ok(signed) = decode::checkpoint(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            if !self.config.chain.is_publisher(&signed.publisher) {
+                debug!("{} ignored a checkpoint from {} signed by an unknown publisher", self.id, sender);
+                return Ok(GossipPayload::None);
+            }
+            if !signed.verify(&self.network) {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            }
+            self.pin_checkpoint(signed, Some(sender)).await;
+            Ok(GossipPayload::None)
+        }
+
+        /// Signs the block `checkpoint_depth` below the tip, if this node is a checkpoint
+        /// publisher and its index is a multiple of `checkpoint_interval`, then pins it
+        /// and gossips it.
+        async fn publish_checkpoint(&mut self) {
+            let Some(publisher) = &self.publisher else {
+                return;
+            };
+            let blocks = self.chain.blocks();
+            let Some(block) = blocks.len().checked_sub(self.config.chain.checkpoint_depth + 1).map(|position| &blocks[position]) else {
+                return;
+            };
+            if block.index == 0 || block.index % self.config.chain.checkpoint_interval != 0 {
+                return;
+            }
+            let checkpoint = Checkpoint { index: block.index, hash: block.hash.clone() };
+            if self.config.chain.checkpoints.contains(&checkpoint) {
+                return;
+            }
+            let signed = publisher.sign_checkpoint(checkpoint, &self.network);
+            self.pin_checkpoint(signed, None).await;
+        }
+
+        /// Sends the signed checkpoints kept to the neighbour listening on `address`, so
+        /// nodes joining the network pin them before synchronizing their chain.
+        async fn share_checkpoints(&self, address: &str) {
+            let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == address) else {
+                return;
+            };
+            for signed in &self.signed_checkpoints {
+                let _ = gossip::send_checkpoint(self.bind_addr.clone(), neighbour, signed).await;
+            }
+        }
+
+        // -------------------------------
+        // Mining Pool
+        // -------------------------------
+
+        /// Hands out new jobs to the workers of the pool this node coordinates, once its
+        /// chain has a new last block to mine on.
+        async fn dispatch_jobs(&mut self) {
+            let Some(pool) = self.pool.as_mut() else {
+                return;
+            };
+            let tip = self.chain.get_last_block();
+            if pool.template().is_some_and(|template| template.hash == tip.hash) {
+                return;
+            }
+            let version = self.config.chain.version_at(self.chain.get_len() + 1);
+            let jobs = pool.assign(tip, self.chain.difficulty, self.network.clone(), version);
+            for (address, job) in jobs {
+                if let Some(worker) = self.neighbours.values().find(|neighbour| neighbour.address == address) {
+                    let _ = gossip::send_job(self.bind_addr.clone(), worker, &job).await;
+                }
+            }
+        }
+
+        /// Adds a neighbour asking for work to the pool this node coordinates, and sends it
+        /// its job. Requests from strangers, and past `MAX_WORKERS` workers, are ignored.
+        async fn add_worker(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Some(pool) = self.pool.as_mut() else {
+                return Ok(GossipPayload::None);
+            };
+            let Some(worker) = self.neighbours.values().find(|neighbour| neighbour.address == sender) else {
+                return Ok(GossipPayload::None);
+            };
+            buffer.remove(0);
+            let Ok(join) = decode::pool_join(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            match pool.join(&sender, join.payout) {
+                Some(job) => {
+                    let _ = gossip::send_job(self.bind_addr.clone(), worker, &job).await;
+                },
+                None => debug!("{} has no job for {}, its pool has {} workers", self.id, sender, pool.len()),
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Credits a share reported by a worker of the pool this node coordinates, and
+        /// completes the block it solves, if any. See `Pool::submit`.
+        async fn accept_share(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            let Some(pool) = self.pool.as_mut() else {
+                return Ok(GossipPayload::None);
+            };
+            buffer.remove(0);
+            let Ok(share) = decode::message::<Share>(&buffer) else {
+                self.report(&sender, Behaviour::MalformedMessage);
+                return Ok(GossipPayload::None);
+            };
+            match pool.submit(&sender, &share) {
+                ShareOutcome::Accepted => debug!("{} accepted a share from {}", self.id, sender),
+                ShareOutcome::Solved { previous, nonce, .. } => self.complete_pool_block(&previous, nonce).await,
+                ShareOutcome::Stale | ShareOutcome::Duplicate => debug!("{} ignored a share from {}", self.id, sender),
+                ShareOutcome::Invalid => self.report(&sender, Behaviour::MalformedMessage),
+            }
+            Ok(GossipPayload::None)
+        }
+
+        /// Adds the block a worker found to the chain, if it still follows the last block,
+        /// then pays the workers whose credit reached a coin, with transactions queued for
+        /// the next block. See `Pool::settle`.
+        async fn complete_pool_block(&mut self, previous: &Block, nonce: u64) {
+            let (Some(miner), Some(pool)) = (self.miner.clone(), self.pool.as_mut()) else {
+                return;
+            };
+            if previous.hash != self.chain.get_last_block().hash {
+                return;
+            }
+            let mut miner = miner.lock().await;
+            miner.set_chain_meta(self.chain.get_len(), self.chain.difficulty, self.chain.shared_blocks());
+            miner.set_chain_state(self.chain.state().clone());
+            let digest = match miner.solved(previous, nonce) {
+                Ok(digest) => digest,
+                Err(e) => {
+                    warn!("{} could not complete the block of its pool: {}", self.id, e);
+                    return;
+                },
+            };
+            if let Err(e) = self.chain.add_block(digest) {
+                warn!("{} refused the block of its pool: {}", self.id, e);
+                return;
+            }
+            info!("{} pool found block {}", self.id, self.chain.get_last_block().index);
+            let coordinator = miner.wallet.get_pub_key();
+            for (payout, coins) in pool.settle() {
+                if miner.wallet.coins.len() < coins {
+                    warn!("{} lacks the coins to pay out {} of its pool's prizes", self.id, coins);
+                    break;
+                }
+                let kept = miner.wallet.coins.len() - coins;
+                let coins: Vec<Token> = miner.wallet.coins.split_off(kept);
+                let nonce = pool.next_payout_nonce(self.chain.next_nonce(&coordinator));
+                let payout = Transaction::new(coordinator.clone(), payout, coins).with_nonce(nonce);
+                let payout = miner.wallet.sign(payout, &self.network);
+                miner.push_transaction(payout);
+            }
+            drop(miner);
+            self.metrics.block_mined();
+            self.emit(NodeEvent::BlockMined { height: self.chain.len() });
+            self.persist_chain();
+        }
+
+        /// Asks the pool coordinator for work, if this node is a worker and the coordinator
+        /// is a neighbour it has not asked yet.
+        async fn join_pool(&mut self) {
+            let Some(PoolConfig::Worker { coordinator, payout }) = &self.config.pool else {
+                return;
+            };
+            if self.pool_joined {
+                return;
+            }
+            let Some(neighbour) = self.neighbours.values().find(|neighbour| neighbour.address == *coordinator) else {
+                return;
+            };
+            let Ok(payout) = general_purpose::STANDARD.decode(payout) else {
+                return;
+            };
+            if gossip::join_pool(self.bind_addr.clone(), neighbour, &PoolJoin { payout }).await.is_ok() {
+                self.pool_joined = true;
+            }
+        }
+
+        /// Starts searching the nonces of a job sent by the pool coordinator, giving up
+        /// the previous one.
+        async fn start_job(&mut self, sender: String, mut buffer: Vec<u8>) -> IOResult<GossipPayload> {
+            if !matches!(&self.config.pool, Some(PoolConfig::Worker { coordinator, .. }) if *coordinator == sender) {
+                return Ok(GossipPayload::None);
+            }
+            let Some(coordinator) = self.neighbours.values().find(|neighbour| neighbour.address == sender).cloned() else {
+                return Ok(GossipPayload::None);
+            };
+            buffer.remove(0);
+            let job = match decode::job(&buffer) {
+                Ok(job) => job,
+                Err(_) => {
+                    self.report(&sender, Behaviour::MalformedMessage);
+                    return Ok(GossipPayload::None);
+                },
+            };
+            if let Some(search) = self.pool_search.take() {
+                search.store(true, Ordering::Relaxed);
+            }
+            debug!("{} searching nonces on block {} for {}", self.id, job.previous.index, sender);
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.pool_search = Some(cancel.clone());
+            tokio::spawn(work(self.bind_addr.clone(), coordinator, job, cancel));
+            Ok(GossipPayload::None)
+        }
+    }
+
+    /// A node dropped while its thread unwinds from a panic, e.g. along with the task
+    /// running its loop, saves what it can before it is gone. See `Node::save_on_panic`.
+    impl Drop for Node {
+        fn drop(&mut self) {
+            if std::thread::panicking() {
+                self.save_on_panic();
+            }
+        }
+    }
+
+    /// Searches the nonces of `job`, `SEARCH_CHUNK` at a time on a blocking thread, and
+    /// reports the shares found to `coordinator`, until the block is solved, the range
+    /// is exhausted or `cancel` is raised.
+    async fn work(address: Arc<str>, coordinator: Neighbour, job: Job, cancel: Arc<AtomicBool>) {
+        let job = Arc::new(job);
+        let mut from = job.start;
+        while from < job.end && !cancel.load(Ordering::Relaxed) {
+            let to = from.saturating_add(SEARCH_CHUNK).min(job.end);
+            let (searched, stop) = (job.clone(), cancel.clone());
+            let Ok((found, solved)) = tokio::task::spawn_blocking(move || searched.search(from, to, &stop)).await else {
+                return;
+            };
+            for nonce in found {
+                let _ = gossip::send_share(address.clone(), &coordinator, &Share { job: job.id, nonce }).await;
+            }
+            if solved {
+                return; // The coordinator hands out a new job once it adds the block.
+            }
+            from = to;
         }
     }
 
     /// Handles mining process if the node is a miner.
-    async fn mine(role: Role, miner: Arc<Mutex<Miner>>, chain: Chain) -> Option<MiningDigest> {
-        let mut inner_miner = miner.lock().await;
-        if role == Role::Miner {
+    /// Mines a block on a blocking thread, so that nodes sharing the runtime keep running,
+    /// until one is found, the node is asked to stop or mining is paused. Under
+    /// `Consensus::PoA`, seals the next block instead, if it is the miner's turn and
+    /// `block_interval` passed. Nothing is mined while `paused` is raised. A panic of the
+    /// mining thread is raised again in the node loop, so the node saves what it can and
+    /// emits `NodeEvent::Crashed` as it unwinds.
+    async fn mine(
+        role: Role,
+        miner: Option<Arc<Mutex<Miner>>>,
+        mut chain: Chain,
+        chain_config: ChainConfig,
+        stop: Arc<AtomicBool>,
+        paused: Arc<AtomicBool>,
+    ) -> Option<MiningDigest> {
+        let miner = miner?;
+        if role != Role::Miner || paused.load(Ordering::Relaxed) {
+            return None;
+        }
+        if chain_config.consensus == Consensus::PoA {
+            return seal(miner, chain, &chain_config).await;
+        }
+        let mining = tokio::task::spawn_blocking(move || {
+            let mut inner_miner = miner.blocking_lock();
             inner_miner.set_chain_meta(
                 chain.get_len(),
                 chain.difficulty,
-                chain.get_blocks(),
+                chain.shared_blocks(),
             );
-            let mining_digest = inner_miner.mine(
+            inner_miner.set_chain_state(chain.state().clone());
+            let mining_digest = inner_miner.mine_until(
                 chain.get_last_block(),
-            ).unwrap(); //TODO: Handle mining abort if the chain gets updated for this index
+                &[&stop, &paused],
+            ).unwrap()?; //TODO: Handle mining abort if the chain gets updated for this index
             info!("Mined block: {}", mining_digest.get_block());
-            let _ = chain.add_block(mining_digest);
+            chain.add_block(mining_digest.clone()).ok()?;
+            Some(mining_digest)
+        });
+        match mining.await {
+            Ok(mining_digest) => mining_digest,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => None,
         }
-        None
     }
 
-   /// Submits a transaction to all miner neighbours.
-    pub async fn submit_transaction(
-        transaction: Transaction, 
-        neighbours: HashMap<Uuid, Neighbour>, 
-        address: Arc<str>
-    ) {
-        let _ = neighbours
-            .iter()
-            .filter(|neighbour| neighbour.1.role == Role::Miner) // Filters only miners
-            .map(|miner| async {
-                gossip::send_transaction(address.clone(), miner.1.address.clone(), transaction.clone()).await
-            })
-            .collect::<Vec<_>>();
+    /// Seals the block following the last one of `chain` with the wallet of `miner`, if
+    /// it is the authority whose turn it is and the last block is `block_interval` old.
+    async fn seal(miner: Arc<Mutex<Miner>>, mut chain: Chain, chain_config: &ChainConfig) -> Option<MiningDigest> {
+        let last_block = chain.get_last_block();
+        if now() < last_block.timestamp + chain_config.block_interval {
+            return None;
+        }
+        let mut inner_miner = miner.lock().await;
+        let turn = chain_config.authority_for(chain.len() + 1)?;
+        if general_purpose::STANDARD.decode(turn).ok()? != inner_miner.wallet.get_pub_key() {
+            return None;
+        }
+        inner_miner.set_chain_meta(chain.get_len(), chain.difficulty, chain.shared_blocks());
+        inner_miner.set_chain_state(chain.state().clone());
+        let digest = inner_miner.seal(&last_block).ok()?;
+        info!("Sealed block: {}", digest.get_block());
+        chain.add_block(digest.clone()).ok()?;
+        Some(digest)
+    }
+
+    /// Blocks downloaded past where a peer's chain diverges from ours. See `Node::adopt_blocks`.
+    struct Download {
+        /// The length of the prefix our chain shares with the peer's.
+        common: usize,
+        /// The difficulty the peer's chain reached.
+        difficulty: usize,
+        blocks: Vec<Block>,
+    }
+
+    /// A resynchronization running in the background, giving back the peer it is with and
+    /// the blocks it fetched, `None` if the peer's chain was not longer.
+    type Resync = JoinHandle<(String, Result<Option<Download>, SyncError>)>;
+
+    /// What a node sends its trackers once every `ANNOUNCE_INTERVAL`.
+    struct Announcements {
+        announcer: Neighbour,
+        trackers: Vec<Neighbour>,
+        /// The registry of a tracker, replicated to the others. Empty for other nodes.
+        registrations: Vec<Registration>,
+    }
+
+    /// Announces this node to its trackers and asks each of them for more peers.
+    async fn announce_to_trackers(address: Arc<str>, announcements: Option<Announcements>) {
+        let Some(Announcements { announcer, trackers, registrations }) = announcements else {
+            return;
+        };
+        for tracker in trackers {
+            let _ = gossip::announce(address.clone(), &announcer, &tracker).await;
+            let _ = gossip::request_peers(address.clone(), &tracker).await;
+            if !registrations.is_empty() {
+                let _ = gossip::send_registry(address.clone(), &tracker, &registrations).await;
+            }
+        }
+    }
+
+    /// Checks a peer's headers describe a chain of the same genesis as ours, sealed as our
+    /// chain config requires, and finds where it diverges from ours.
+    ///
+    /// # Arguments
+    /// * `own_headers` - The headers of our chain.
+    /// * `start` - The difficulty our chain started at.
+    /// * `config` - The rules our chain follows.
+    /// * `response` - The peer's headers.
+    ///
+    /// # Returns
+    /// * `Result<Option<usize>, SyncError>` - The length of the prefix both chains share,
+    ///   `None` if the peer's chain is not longer.
+    fn diverging_headers(own_headers: &[BlockHeader], start: usize, config: &ChainConfig, response: &HeadersResponse) -> Result<Option<usize>, SyncError> {
+        let HeadersResponse { difficulty, headers, .. } = response;
+        chain::verify_headers(headers, start, *difficulty)?;
+        config.verify_headers(headers)?;
+        if headers.first() != own_headers.first() {
+            return Err(SyncError::GenesisMismatch);
+        }
+        if headers.len() <= own_headers.len() {
+            return Ok(None);
+        }
+        Ok(Some(chain::common_prefix(own_headers, headers)))
+    }
+
+    /// Downloads the blocks of `headers` `BLOCKS_PER_REQUEST` at a time, spread over
+    /// `neighbours`, which are asked for a batch each at once. Each batch is asked from
+    /// the next neighbour until one sends the blocks of its headers, at least `MAX_RETRIES`
+    /// times.
+    ///
+    /// # Returns
+    /// * `Result<Vec<Block>, SyncError>` - The blocks of `headers`, in order.
+    async fn download_headers(id: Uuid, address: Arc<str>, neighbours: &[Neighbour], pending: &PendingRequests, headers: &[BlockHeader]) -> Result<Vec<Block>, SyncError> {
+        let batches: Vec<_> = headers.chunks(BLOCKS_PER_REQUEST).collect();
+        let mut downloaded = vec![];
+        for (wave, wanted) in batches.chunks(neighbours.len()).enumerate() {
+            let mut requests = JoinSet::new();
+            for (position, wanted) in wanted.iter().enumerate() {
+                let batch = wave * neighbours.len() + position;
+                let candidates: Vec<Neighbour> = neighbours
+                    .iter()
+                    .cycle()
+                    .skip(batch % neighbours.len())
+                    .take(neighbours.len().max(sync::MAX_RETRIES))
+                    .cloned()
+                    .collect();
+                let (address, pending, wanted) = (address.clone(), pending.clone(), wanted.to_vec());
+                requests.spawn(async move {
+                    let range = wanted[0].index..wanted[wanted.len() - 1].index + 1;
+                    for neighbour in candidates {
+                        match download(address.clone(), &neighbour, &pending, range.clone()).await {
+                            Ok(fetched) if sync::match_headers(&fetched, &wanted) => return Some((position, fetched)),
+                            Ok(_) => debug!("{} sent blocks that do not match their headers", neighbour.address),
+                            Err(e) => debug!("{} could not get blocks from {}: {}", id, neighbour.address, e),
+                        }
+                    }
+                    None
+                });
+            }
+            let mut fetched = vec![vec![]; wanted.len()];
+            while let Some(result) = requests.join_next().await {
+                let (position, blocks) = result.ok().flatten().ok_or(SyncError::Timeout)?;
+                fetched[position] = blocks;
+            }
+            downloaded.extend(fetched.into_iter().flatten());
+        }
+        Ok(downloaded)
+    }
+
+    /// Downloads the blocks of index within `range` from `neighbour`. See `Node::fetch_blocks`.
+    async fn download(address: Arc<str>, neighbour: &Neighbour, pending: &PendingRequests, range: Range<usize>) -> Result<Vec<Block>, SyncError> {
+        let fetched = sync::fetch_range(range, |range| {
+            let address = address.clone();
+            async move { Ok::<_, SyncError>(gossip::get_range(address, neighbour, pending, range).await?) }
+        }).await?;
+        Ok(fetched.blocks)
+    }
+
+    /// Pings every neighbour so unresponsive ones can be detected.
+    async fn ping_neighbours(address: Arc<str>, neighbours: Vec<Neighbour>) {
+        for neighbour in neighbours {
+            let _ = gossip::ping(address.clone(), &neighbour).await;
+        }
     }
 
         /// Updates the chain by polling neighbours for the latest chain.
     /// Listens for and processes incoming transactions.
-    async fn listen_to_transactions(
-        receiver: Arc<Mutex<Receiver>>, 
-        neighbours: HashMap<Uuid, Neighbour>,
-        address: Arc<str>,
-    ) {
+    async fn listen_to_transactions(receiver: Arc<Mutex<Receiver>>) -> Option<Transaction> {
         match receive_transaction(receiver).await {
             Ok(transaction) => {
                 debug!("Transaction being received: {}", transaction);
-                submit_transaction(transaction, neighbours, address).await;
-            },
-            Err(_e) => {
-                // Handle error or log it.
+                Some(transaction)
             },
+            Err(_e) => None, // Handle error or log it.
         }
     }
         /// Handles the gossiping process with random neighbours, based on the provided theme.
     pub async fn gossip(
         address: Arc<str>, 
-        chain: Chain, 
+        digest: ChainDigest, 
         random_neighbours: Vec<Neighbour>, 
-        new_neighbours: Vec<Neighbour>,
-        theme: Theme
+        new_neighbours: HashMap<Uuid, Vec<RelayedNeighbour>>,
+        theme: Theme,
+        interval: Duration,
     ) {
-        gossip::wait_gossip_interval().await;
+        gossip::wait_gossip_interval(interval).await;
         for neighbour in random_neighbours {
             match theme {
                 Theme::Chain => {
-                    if chain.get_len() > 0 {
-                        let _ = gossip::send_chain(
-                            address.clone(),
-                            neighbour.address.clone(),
-                            chain.clone() //TODO: Shouldn't have to clone eveyt time.
-                        ).await;
-                    }
+                    // Only the digest is pushed; neighbours ask for the chain if they need it.
+                    let _ = gossip::summarize(address.clone(), &neighbour, &digest).await;
                 },
                 Theme::NewNeighbours => {
-                    if !new_neighbours.is_empty() {
+                    // Only the neighbours this one was not sent yet, see `PeerLog`.
+                    let updates = new_neighbours.get(&neighbour.id).filter(|updates| !updates.is_empty());
+                    if let Some(updates) = updates {
                         let _ = gossip::send_new_neighbours(
-                            neighbour.id.clone(),
-                            neighbour.address.clone(),
+                            &neighbour,
                             address.clone(),
-                            new_neighbours.clone()
+                            updates.clone()
                         ).await;
                     }
                 },
@@ -0,0 +1,236 @@
+pub mod outbound {
+
+    use crate::clock::clock::clock;
+    use crate::node::{
+        protocol::protocol::ProtocolMsg,
+        ratelimit::ratelimit::{TokenBucket, PEER_RATE_LIMIT},
+        transport::transport::Transport,
+    };
+
+    use std::{
+        collections::{HashMap, VecDeque},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex, OnceLock,
+        },
+        time::Duration,
+    };
+
+    use tokio::{sync::Notify, task::JoinHandle};
+    use tracing::debug;
+
+    /// Messages up to this size (in bytes) are queued and batched; larger ones are sent right away.
+    pub const BATCHABLE_SIZE: usize = 1024;
+    /// Largest `ProtocolMsg::Batch` datagram, in bytes.
+    pub const MAX_BATCH_SIZE: usize = 8 * 1024;
+    /// Time (in milliseconds) queued messages wait for others to share a datagram with.
+    pub const FLUSH_INTERVAL: u64 = 5;
+    /// Messages queued for a single peer before new ones are refused.
+    pub const PEER_QUEUE_CAPACITY: usize = 256;
+    /// Messages per second sent to each peer, on average. Half of what peers accept, so
+    /// messages sent around the outbox, such as chains, do not push it over their limit.
+    pub const PEER_SEND_RATE: u32 = PEER_RATE_LIMIT / 2;
+
+    /// Packs messages into a `ProtocolMsg::Batch` datagram, each prefixed by its length as
+    /// two big-endian bytes.
+    pub fn pack(messages: &[Vec<u8>]) -> Vec<u8> {
+        let mut batch = vec![ProtocolMsg::Batch as u8];
+        for message in messages {
+            batch.extend_from_slice(&(message.len() as u16).to_be_bytes());
+            batch.extend_from_slice(message);
+        }
+        batch
+    }
+
+    /// Splits a `ProtocolMsg::Batch` datagram back into its messages.
+    ///
+    /// # Returns
+    /// * `Option<Vec<Vec<u8>>>` - The messages, `None` if the batch is malformed.
+    pub fn unpack(batch: &[u8]) -> Option<Vec<Vec<u8>>> {
+        let mut messages = vec![];
+        let mut rest = batch.get(1..)?;
+        while !rest.is_empty() {
+            let length = u16::from_be_bytes([*rest.first()?, *rest.get(1)?]) as usize;
+            let message = rest.get(2..2 + length)?;
+            if message.is_empty() {
+                return None;
+            }
+            messages.push(message.to_vec());
+            rest = &rest[2 + length..];
+        }
+        Some(messages)
+    }
+
+    /// Splits a `ProtocolMsg::Batch` into its messages, for sockets read without a
+    /// `Listener`, such as during a sync or a greeting. Any other message is returned alone.
+    pub fn unbatch(message: Vec<u8>) -> Vec<Vec<u8>> {
+        match message.first().map(|&byte| ProtocolMsg::try_from(byte)) {
+            Some(Ok(ProtocolMsg::Batch)) => unpack(&message).unwrap_or_default(),
+            _ => vec![message],
+        }
+    }
+
+    struct PeerQueue {
+        messages: VecDeque<Vec<u8>>,
+        bucket: TokenBucket,
+    }
+
+    type Queues = Arc<Mutex<HashMap<String, PeerQueue>>>;
+
+    /// Per-peer queues of small outgoing messages, drained by a task of their own so
+    /// that gossiping to many neighbours does not wait on every send. Messages queued for
+    /// the same peer within `FLUSH_INTERVAL` share datagrams, and each peer is sent at
+    /// most `peer_rate` messages per second, the rest waiting in its queue.
+    pub struct Outbox {
+        queues: Queues,
+        wake: Arc<Notify>,
+        /// Datagrams taken out of the queues but not sent yet.
+        in_flight: Arc<AtomicUsize>,
+        peer_rate: u32,
+        task: JoinHandle<()>,
+    }
+
+    impl Outbox {
+        /// Starts sending queued messages through `socket`.
+        ///
+        /// # Arguments
+        /// * `socket` - The socket to send from.
+        /// * `peer_rate` - Messages per second sent to each peer.
+        pub fn spawn(socket: Arc<dyn Transport>, peer_rate: u32) -> Self {
+            let queues: Queues = Arc::new(Mutex::new(HashMap::new()));
+            let wake = Arc::new(Notify::new());
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            let task = tokio::spawn(drain(socket, queues.clone(), wake.clone(), in_flight.clone()));
+            Outbox {
+                queues,
+                wake,
+                in_flight,
+                peer_rate,
+                task,
+            }
+        }
+
+        /// Queues a message for `target`.
+        ///
+        /// # Returns
+        /// `false` if the peer's queue is full and the message was dropped.
+        pub fn enqueue(&self, target: &str, message: Vec<u8>) -> bool {
+            let mut queues = self.queues.lock().unwrap();
+            let queue = queues.entry(target.to_string()).or_insert_with(|| PeerQueue {
+                messages: VecDeque::new(),
+                bucket: TokenBucket::new(self.peer_rate, self.peer_rate),
+            });
+            if queue.messages.len() >= PEER_QUEUE_CAPACITY {
+                return false;
+            }
+            queue.messages.push_back(message);
+            self.wake.notify_one();
+            true
+        }
+
+        /// Waits until every queued message was sent. Peers' rate limits still apply, so
+        /// callers that cannot wait long should bound this with a timeout.
+        pub async fn flush(&self) {
+            loop {
+                let queued = self.queues.lock().unwrap().values().any(|queue| !queue.messages.is_empty());
+                if !queued && self.in_flight.load(Ordering::Acquire) == 0 {
+                    return;
+                }
+                clock::sleep(Duration::from_millis(FLUSH_INTERVAL)).await;
+            }
+        }
+    }
+
+    impl Outbox {
+        /// Stops sending, and waits for the sending task to release the socket.
+        pub async fn close(mut self) {
+            self.task.abort();
+            let _ = (&mut self.task).await;
+        }
+    }
+
+    impl Drop for Outbox {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    async fn drain(socket: Arc<dyn Transport>, queues: Queues, wake: Arc<Notify>, in_flight: Arc<AtomicUsize>) {
+        let mut pending = false;
+        loop {
+            if !pending {
+                wake.notified().await;
+            }
+            clock::sleep(Duration::from_millis(FLUSH_INTERVAL)).await;
+            let (datagrams, left) = take_datagrams(&queues, &in_flight);
+            pending = left;
+            for (target, datagram) in datagrams {
+                if let Err(e) = socket.send_to(&datagram, &target).await {
+                    debug!("Failed to send to {}: {}", target, e);
+                }
+                in_flight.fetch_sub(1, Ordering::Release);
+            }
+        }
+    }
+
+    /// Takes whatever the rate limits allow out of every queue, packed into datagrams,
+    /// and counts them in `in_flight` before releasing the queues.
+    ///
+    /// # Returns
+    /// * `(Vec<(String, Vec<u8>)>, bool)` - The datagrams with their target, and whether
+    ///   messages were left queued.
+    fn take_datagrams(queues: &Queues, in_flight: &AtomicUsize) -> (Vec<(String, Vec<u8>)>, bool) {
+        let mut queues = queues.lock().unwrap();
+        let mut datagrams = vec![];
+        for (target, queue) in queues.iter_mut() {
+            let mut batch: Vec<Vec<u8>> = vec![];
+            let mut batch_size = 1;
+            while let Some(message) = queue.messages.front() {
+                if batch_size + 2 + message.len() > MAX_BATCH_SIZE {
+                    datagrams.push((target.clone(), seal(std::mem::take(&mut batch))));
+                    batch_size = 1;
+                }
+                if !queue.bucket.try_take(1.0) {
+                    break;
+                }
+                batch_size += 2 + message.len();
+                batch.push(queue.messages.pop_front().unwrap());
+            }
+            if !batch.is_empty() {
+                datagrams.push((target.clone(), seal(batch)));
+            }
+        }
+        queues.retain(|_, queue| !queue.messages.is_empty() || !queue.bucket.is_full());
+        let left = queues.values().any(|queue| !queue.messages.is_empty());
+        in_flight.fetch_add(datagrams.len(), Ordering::Release);
+        (datagrams, left)
+    }
+
+    /// A lone message is sent as is; only several share a `ProtocolMsg::Batch`.
+    fn seal(mut batch: Vec<Vec<u8>>) -> Vec<u8> {
+        match batch.len() {
+            1 => batch.pop().unwrap(),
+            _ => pack(&batch),
+        }
+    }
+
+    /// Outboxes of the nodes running in this process, by address.
+    static REGISTERED: OnceLock<Mutex<HashMap<String, Arc<Outbox>>>> = OnceLock::new();
+
+    fn registered() -> &'static Mutex<HashMap<String, Arc<Outbox>>> {
+        REGISTERED.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    /// Makes small messages sent from `address` go through `outbox`.
+    pub fn register(address: &str, outbox: Arc<Outbox>) {
+        registered().lock().unwrap().insert(address.to_string(), outbox);
+    }
+
+    pub fn unregister(address: &str) -> Option<Arc<Outbox>> {
+        registered().lock().unwrap().remove(address)
+    }
+
+    pub fn lookup(address: &str) -> Option<Arc<Outbox>> {
+        registered().lock().unwrap().get(address).cloned()
+    }
+}
@@ -0,0 +1,134 @@
+pub mod peer_log {
+
+    use crate::node::{
+        neighbour::neighbour::Neighbour,
+        relay::relay::RelayedNeighbour,
+    };
+
+    use std::collections::{BTreeMap, HashMap};
+
+    use rand::seq::IteratorRandom;
+    use uuid::Uuid;
+
+    /// Rounds of neighbour gossip a peer is sent changes only, before being sent every
+    /// neighbour again in case some changes never reached it.
+    pub const ANTI_ENTROPY_ROUNDS: usize = 16;
+    /// Changes remembered. Peers last synced before the oldest one are sent every neighbour.
+    pub const MAX_CHANGES: usize = 1024;
+    /// Neighbours sent to a peer per round, each in a message of its own, so a node with
+    /// many neighbours does not fill its queue to the peer. See `PeerLog::updates_for`.
+    pub const MAX_UPDATES: usize = 16;
+
+    /// How far a peer was sent the changes to the neighbours of a node.
+    #[derive(Clone, Copy, Debug)]
+    struct Synced {
+        /// Version of the last change sent to it.
+        version: u64,
+        /// Rounds since it was last sent every neighbour.
+        rounds: usize,
+    }
+
+    /// Changes to the neighbours of a node, each numbered by a version, along with the
+    /// version each peer was sent up to, so `ProtocolMsg::Neighbour` gossip only carries the
+    /// neighbours a peer was not told about yet. Every `ANTI_ENTROPY_ROUNDS` rounds, or
+    /// when it fell behind the changes remembered, a peer is sent every neighbour instead.
+    /// Either way, no more than `MAX_UPDATES` a round: changes left over are sent the next
+    /// rounds, and every neighbour comes down to a random sample of them.
+    ///
+    /// Neighbours are only ever sent while they have forwards left, see `RelayedNeighbour`.
+    #[derive(Debug, Default)]
+    pub struct PeerLog {
+        /// Version of the last change.
+        version: u64,
+        /// Neighbours by the version they last changed at, oldest first.
+        changes: BTreeMap<u64, Neighbour>,
+        /// Version each neighbour last changed at.
+        versions: HashMap<Uuid, u64>,
+        /// Version of the last change forgotten to stay within `MAX_CHANGES`.
+        dropped: u64,
+        synced: HashMap<Uuid, Synced>,
+        /// Times each neighbour may still be forwarded.
+        ttl: HashMap<Uuid, u8>,
+    }
+
+    impl PeerLog {
+        pub fn new() -> Self {
+            PeerLog::default()
+        }
+
+        /// Version of the last change.
+        pub fn version(&self) -> u64 {
+            self.version
+        }
+
+        /// Records that `neighbour` was added, or that what it told about itself changed.
+        ///
+        /// # Arguments
+        /// * `neighbour` - The neighbour added or changed.
+        /// * `ttl` - Times it may still be forwarded. The most it was recorded with is kept.
+        pub fn record(&mut self, neighbour: Neighbour, ttl: u8) {
+            let left = self.ttl.entry(neighbour.id).or_insert(ttl);
+            *left = (*left).max(ttl);
+            self.version += 1;
+            if let Some(previous) = self.versions.insert(neighbour.id, self.version) {
+                self.changes.remove(&previous);
+            }
+            self.changes.insert(self.version, neighbour);
+            if self.changes.len() > MAX_CHANGES {
+                if let Some((version, oldest)) = self.changes.pop_first() {
+                    self.versions.remove(&oldest.id);
+                    self.dropped = version;
+                }
+            }
+        }
+
+        /// Forgets the neighbour with id `id`, both as a change to send and as a peer, so
+        /// it is sent every neighbour if it comes back.
+        pub fn forget(&mut self, id: &Uuid) {
+            if let Some(version) = self.versions.remove(id) {
+                self.changes.remove(&version);
+            }
+            self.synced.remove(id);
+            self.ttl.remove(id);
+        }
+
+        /// Picks the neighbours to send `peer` this round, and counts them as sent.
+        ///
+        /// # Arguments
+        /// * `peer` - The neighbour gossiped to.
+        /// * `neighbours` - Every neighbour of the node, sampled on first contact and every
+        ///   `ANTI_ENTROPY_ROUNDS` rounds.
+        ///
+        /// # Returns
+        /// * `Vec<RelayedNeighbour>` - Up to `MAX_UPDATES` neighbours to send, `peer` and
+        ///   those without forwards left aside, each with one forward less. Empty if nothing
+        ///   changed since its last round.
+        pub fn updates_for(&mut self, peer: &Neighbour, neighbours: &[Neighbour]) -> Vec<RelayedNeighbour> {
+            let version = self.version;
+            let updates: Vec<&Neighbour> = match self.synced.get_mut(&peer.id) {
+                Some(synced) if synced.rounds < ANTI_ENTROPY_ROUNDS && synced.version >= self.dropped => {
+                    let changes: Vec<(&u64, &Neighbour)> = self.changes.range(synced.version + 1..).take(MAX_UPDATES).collect();
+                    // Past the last change sent, the rest wait for the next round.
+                    let sent = match changes.len() {
+                        MAX_UPDATES => *changes[MAX_UPDATES - 1].0,
+                        _ => version,
+                    };
+                    *synced = Synced { version: sent, rounds: synced.rounds + 1 };
+                    changes.into_iter().map(|(_, neighbour)| neighbour).collect()
+                },
+                _ => {
+                    self.synced.insert(peer.id, Synced { version, rounds: 0 });
+                    neighbours.iter().collect()
+                },
+            };
+            updates
+                .into_iter()
+                .filter(|neighbour| neighbour.id != peer.id)
+                .filter_map(|neighbour| {
+                    let ttl = self.ttl.get(&neighbour.id).copied().unwrap_or(0).checked_sub(1)?;
+                    Some(RelayedNeighbour { neighbour: neighbour.clone(), ttl })
+                })
+                .choose_multiple(&mut rand::thread_rng(), MAX_UPDATES)
+        }
+    }
+}
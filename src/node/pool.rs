@@ -0,0 +1,290 @@
+pub mod pool {
+
+    use crate::chain::block::block::block::Block;
+    use crate::chain::network::network::NetworkId;
+
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use serde::{Deserialize, Serialize};
+
+    /// Most workers a coordinator hands out work to.
+    pub const MAX_WORKERS: usize = 64;
+    /// Leading zeros a share has fewer of than a block, so workers prove their work
+    /// `16^SHARE_GAP` times as often as they find blocks.
+    pub const SHARE_GAP: usize = 1;
+    /// Credit a worker needs for a payout of one coin. See `Pool::settle`.
+    pub const PAYOUT_UNIT: u64 = 1000;
+    /// Nonces a worker tries between two checks for a newer job.
+    pub const SEARCH_CHUNK: u64 = 1 << 14;
+    /// Nonces in the range of each worker slot. Slots split the nonces evenly.
+    const SLOT_SPAN: u64 = u64::MAX / MAX_WORKERS as u64;
+
+    /// Payload of a `ProtocolMsg::PoolJoin` message: a worker asking a coordinator for work.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct PoolJoin {
+        /// Public key of the wallet the worker's payouts go to.
+        pub payout: Vec<u8>,
+    }
+
+    /// Payload of a `ProtocolMsg::PoolJob` message: the block the next one is mined on, and
+    /// the nonces the worker searches, which no other worker of the pool searches.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Job {
+        pub id: u64,
+        /// The last block of the coordinator's chain. Mined blocks are found by searching a
+        /// nonce giving it a hash that meets the difficulty. See `Chain::add_block`.
+        pub previous: Block,
+        /// Network of the block mined.
+        pub network: NetworkId,
+        /// Version of the block mined.
+        pub version: u32,
+        pub difficulty: usize,
+        /// Leading zeros of the hashes the worker reports as shares.
+        pub share_difficulty: usize,
+        /// First nonce of the worker's range.
+        pub start: u64,
+        /// Nonce past the last one of the worker's range.
+        pub end: u64,
+    }
+
+    impl Job {
+        /// Hash `nonce` gives the block mined on the previous one. See `Block::mining_hash`.
+        pub fn hash(&self, nonce: u64) -> String {
+            self.previous.mining_hash(nonce, &self.network, self.version)
+        }
+
+        /// Tries the nonces from `from` to `to`, within the range of the job, giving up as
+        /// soon as `cancel` is raised or a nonce solves the block.
+        ///
+        /// # Returns
+        /// * `(Vec<u64>, bool)` - The nonces giving a hash that meets `share_difficulty`,
+        ///   and whether the last one also meets `difficulty`.
+        pub fn search(&self, from: u64, to: u64, cancel: &AtomicBool) -> (Vec<u64>, bool) {
+            let share = "0".repeat(self.share_difficulty);
+            let block = "0".repeat(self.difficulty);
+            let mut found = vec![];
+            for nonce in from.max(self.start)..to.min(self.end) {
+                if nonce % 1024 == 0 && cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let hash = self.hash(nonce);
+                if hash.starts_with(&share) {
+                    found.push(nonce);
+                    if hash.starts_with(&block) {
+                        return (found, true);
+                    }
+                }
+            }
+            (found, false)
+        }
+    }
+
+    /// Payload of a `ProtocolMsg::PoolShare` message: a nonce a worker found for a job.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Share {
+        pub job: u64,
+        pub nonce: u64,
+    }
+
+    /// What a coordinator made of a share. See `Pool::submit`.
+    #[derive(Clone, Debug)]
+    pub enum ShareOutcome {
+        /// The share was credited to the worker.
+        Accepted,
+        /// The share also meets the difficulty of the chain: the block it solves was
+        /// found, with the hash given.
+        Solved { previous: Box<Block>, nonce: u64, hash: String },
+        /// The share is for a job replaced since, or the sender is not a worker.
+        Stale,
+        /// The share was already submitted.
+        Duplicate,
+        /// The nonce is out of the worker's range or does not meet the share difficulty.
+        Invalid,
+    }
+
+    /// A worker known to a coordinator.
+    #[derive(Clone, Debug)]
+    struct Worker {
+        payout: Vec<u8>,
+        /// Which `SLOT_SPAN` nonces it searches.
+        slot: usize,
+        /// The job it was last sent.
+        job: Option<Job>,
+    }
+
+    /// State of a coordinator handing out work to the workers of its pool.
+    ///
+    /// Workers search disjoint nonce ranges for a block on the coordinator's last one,
+    /// and report the hashes meeting `SHARE_GAP` fewer zeros as shares. Each block the
+    /// pool finds pays its prize out to the workers, in proportion to the shares they
+    /// reported since the last one. Coins are whole, so credit builds up until it is
+    /// worth one. See `Pool::settle`.
+    #[derive(Debug, Default)]
+    pub struct Pool {
+        /// Workers by address.
+        workers: HashMap<String, Worker>,
+        /// Id of the jobs handed out for the current template.
+        job_id: u64,
+        /// The block the current jobs mine on.
+        template: Option<Block>,
+        difficulty: usize,
+        /// Network and version of the block the current jobs mine.
+        network: NetworkId,
+        version: u32,
+        /// Nonces submitted for the current template.
+        seen: HashSet<u64>,
+        /// Shares reported since the last block found, by payout address.
+        shares: HashMap<Vec<u8>, u64>,
+        /// Credit of each payout address, in `PAYOUT_UNIT`s per coin.
+        credit: HashMap<Vec<u8>, u64>,
+        /// Nonce of the next payout transaction.
+        payout_nonce: u64,
+    }
+
+    impl Pool {
+        pub fn new() -> Self {
+            Pool::default()
+        }
+
+        /// Number of workers.
+        pub fn len(&self) -> usize {
+            self.workers.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.workers.is_empty()
+        }
+
+        /// Adds the worker at `address`, paying out to `payout`, or updates its payout
+        /// address if it joined already.
+        ///
+        /// # Returns
+        /// * `Option<Job>` - Its job for the current template, if there is one, or `None`
+        ///   if there is not or the pool is full.
+        pub fn join(&mut self, address: &str, payout: Vec<u8>) -> Option<Job> {
+            if let Some(worker) = self.workers.get_mut(address) {
+                worker.payout = payout;
+                return worker.job.clone();
+            }
+            let taken: HashSet<usize> = self.workers.values().map(|worker| worker.slot).collect();
+            let slot = (0..MAX_WORKERS).find(|slot| !taken.contains(slot))?;
+            let job = self.template.as_ref().map(|template| self.job_for(template.clone(), slot));
+            self.workers.insert(address.to_string(), Worker { payout, slot, job: job.clone() });
+            job
+        }
+
+        /// Forgets the worker at `address`, freeing its range. Its shares are kept.
+        pub fn leave(&mut self, address: &str) {
+            self.workers.remove(address);
+        }
+
+        /// The block the current jobs mine on.
+        pub fn template(&self) -> Option<&Block> {
+            self.template.as_ref()
+        }
+
+        /// Hands out new jobs, for mining a block of `network` and `version` on `previous`
+        /// at `difficulty`.
+        ///
+        /// # Returns
+        /// * `Vec<(String, Job)>` - The address of every worker, with its job.
+        pub fn assign(&mut self, previous: Block, difficulty: usize, network: NetworkId, version: u32) -> Vec<(String, Job)> {
+            self.job_id += 1;
+            self.seen.clear();
+            self.difficulty = difficulty;
+            self.network = network;
+            self.version = version;
+            self.template = Some(previous.clone());
+            let slots: Vec<(String, usize)> = self.workers.iter().map(|(address, worker)| (address.clone(), worker.slot)).collect();
+            let mut jobs = vec![];
+            for (address, slot) in slots {
+                let job = self.job_for(previous.clone(), slot);
+                if let Some(worker) = self.workers.get_mut(&address) {
+                    worker.job = Some(job.clone());
+                }
+                jobs.push((address, job));
+            }
+            jobs
+        }
+
+        fn job_for(&self, previous: Block, slot: usize) -> Job {
+            let start = slot as u64 * SLOT_SPAN;
+            Job {
+                id: self.job_id,
+                previous,
+                network: self.network.clone(),
+                version: self.version,
+                difficulty: self.difficulty,
+                share_difficulty: self.difficulty.saturating_sub(SHARE_GAP),
+                start,
+                end: start + SLOT_SPAN,
+            }
+        }
+
+        /// Checks a share the worker at `address` reported, and credits it if it holds.
+        pub fn submit(&mut self, address: &str, share: &Share) -> ShareOutcome {
+            let Some(worker) = self.workers.get(address) else {
+                return ShareOutcome::Stale;
+            };
+            let Some(job) = worker.job.as_ref().filter(|job| job.id == share.job) else {
+                return ShareOutcome::Stale;
+            };
+            if share.nonce < job.start || share.nonce >= job.end {
+                return ShareOutcome::Invalid;
+            }
+            let hash = job.hash(share.nonce);
+            if !hash.starts_with(&"0".repeat(job.share_difficulty)) {
+                return ShareOutcome::Invalid;
+            }
+            if !self.seen.insert(share.nonce) {
+                return ShareOutcome::Duplicate;
+            }
+            *self.shares.entry(worker.payout.clone()).or_default() += 1;
+            match hash.starts_with(&"0".repeat(job.difficulty)) {
+                true => ShareOutcome::Solved { previous: Box::new(job.previous.clone()), nonce: share.nonce, hash },
+                false => ShareOutcome::Accepted,
+            }
+        }
+
+        /// Splits the prize of a block the pool found: one coin, worth `PAYOUT_UNIT`
+        /// credit, shared out in proportion to the shares reported since the last block.
+        /// What rounding leaves is kept by the coordinator.
+        ///
+        /// # Returns
+        /// * `Vec<(Vec<u8>, usize)>` - The payout addresses whose credit reached a coin,
+        ///   with the coins to pay them, their credit being lowered by as much.
+        pub fn settle(&mut self) -> Vec<(Vec<u8>, usize)> {
+            let total: u64 = self.shares.values().sum();
+            for (payout, shares) in self.shares.drain() {
+                *self.credit.entry(payout).or_default() += PAYOUT_UNIT * shares / total;
+            }
+            let mut payouts = vec![];
+            for (payout, credit) in self.credit.iter_mut().filter(|(_, credit)| **credit >= PAYOUT_UNIT) {
+                payouts.push((payout.clone(), (*credit / PAYOUT_UNIT) as usize));
+                *credit %= PAYOUT_UNIT;
+            }
+            payouts
+        }
+
+        /// Shares reported by `payout` since the last block found.
+        pub fn shares_of(&self, payout: &[u8]) -> u64 {
+            self.shares.get(payout).copied().unwrap_or(0)
+        }
+
+        /// Credit of `payout`, in `PAYOUT_UNIT`s per coin.
+        pub fn credit_of(&self, payout: &[u8]) -> u64 {
+            self.credit.get(payout).copied().unwrap_or(0)
+        }
+
+        /// Returns the nonce of the next payout transaction, at least `next`, the one the
+        /// chain expects of the coordinator.
+        pub fn next_payout_nonce(&mut self, next: u64) -> u64 {
+            let nonce = self.payout_nonce.max(next);
+            self.payout_nonce = nonce + 1;
+            nonce
+        }
+    }
+}
@@ -1,10 +1,211 @@
 pub mod protocol {
 
-    pub const GREET: u8 = 1;
-    pub const FAREWELL: u8 = 2;
-    pub const NEIGHBOUR: u8 = 3;
-    pub const TRANSACTION: u8 = 4;
-    pub const CHAIN: u8 = 5;
-    pub const POLLCHAIN: u8 = 6;
-}
+    use std::ops::RangeInclusive;
+
+    use thiserror::Error;
+
+    /// Version of the message set below. Bumped when a message is added to `CORE`.
+    pub const VERSION: u8 = 1;
+    /// Protocol bytes of the core messages, which every node understands.
+    pub const CORE: RangeInclusive<u8> = 1..=127;
+    /// Protocol bytes left to extensions. Nodes without an extension report its messages
+    /// as unknown, rather than as malformed.
+    pub const EXTENSIONS: RangeInclusive<u8> = 128..=255;
+
+    /// A protocol byte no message is known by.
+    #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+    #[error("Unknown protocol {0}.")]
+    pub struct UnknownProtocol(pub u8);
+
+    impl UnknownProtocol {
+        /// Whether the byte is within `EXTENSIONS`, so was likely sent by a node running
+        /// an extension this one does not, rather than being garbage.
+        pub fn is_extension(&self) -> bool {
+            EXTENSIONS.contains(&self.0)
+        }
+    }
+
+    /// The message kinds, by the protocol byte opening each message.
+    #[repr(u8)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub enum ProtocolMsg {
+        Greet = 1,
+        Farewell = 2,
+        Neighbour = 3,
+        Transaction = 4,
+        Chain = 5,
+        PollChain = 6,
+        Fragment = 7,
+        Handshake = 8,
+        Encrypted = 9,
+        Ping = 10,
+        Pong = 11,
+        Announce = 12,
+        PeerList = 13,
+        Peers = 14,
+        DhtFind = 15,
+        DhtFound = 16,
+        DhtProvide = 17,
+        GetHeaders = 18,
+        Headers = 19,
+        GetBlocks = 20,
+        Blocks = 21,
+        Inv = 22,
+        GetData = 23,
+        Summarize = 24,
+        Request = 25,
+        Record = 26,
+        Response = 27,
+        Role = 28,
+        Batch = 29,
+        Welcome = 30,
+        Challenge = 31,
+        Registry = 32,
+        Validate = 33,
+        NodeInfo = 34,
+        PeerInfo = 35,
+        Mempool = 36,
+        /// Retired: only the operator API evicts. Kept so no other message takes its byte.
+        Evict = 37,
+        BatchTransaction = 38,
+        /// Retired: only the operator API pauses mining. Kept so no other message takes its byte.
+        Mining = 39,
+        FilterLoad = 40,
+        FilteredBlock = 41,
+        Checkpoint = 42,
+        PoolJoin = 43,
+        PoolJob = 44,
+        PoolShare = 45,
+        GetRange = 46,
+    }
 
+    impl ProtocolMsg {
+        /// Every message kind, by protocol byte.
+        pub const ALL: [ProtocolMsg; 46] = [
+            ProtocolMsg::Greet,
+            ProtocolMsg::Farewell,
+            ProtocolMsg::Neighbour,
+            ProtocolMsg::Transaction,
+            ProtocolMsg::Chain,
+            ProtocolMsg::PollChain,
+            ProtocolMsg::Fragment,
+            ProtocolMsg::Handshake,
+            ProtocolMsg::Encrypted,
+            ProtocolMsg::Ping,
+            ProtocolMsg::Pong,
+            ProtocolMsg::Announce,
+            ProtocolMsg::PeerList,
+            ProtocolMsg::Peers,
+            ProtocolMsg::DhtFind,
+            ProtocolMsg::DhtFound,
+            ProtocolMsg::DhtProvide,
+            ProtocolMsg::GetHeaders,
+            ProtocolMsg::Headers,
+            ProtocolMsg::GetBlocks,
+            ProtocolMsg::Blocks,
+            ProtocolMsg::Inv,
+            ProtocolMsg::GetData,
+            ProtocolMsg::Summarize,
+            ProtocolMsg::Request,
+            ProtocolMsg::Record,
+            ProtocolMsg::Response,
+            ProtocolMsg::Role,
+            ProtocolMsg::Batch,
+            ProtocolMsg::Welcome,
+            ProtocolMsg::Challenge,
+            ProtocolMsg::Registry,
+            ProtocolMsg::Validate,
+            ProtocolMsg::NodeInfo,
+            ProtocolMsg::PeerInfo,
+            ProtocolMsg::Mempool,
+            ProtocolMsg::Evict,
+            ProtocolMsg::BatchTransaction,
+            ProtocolMsg::Mining,
+            ProtocolMsg::FilterLoad,
+            ProtocolMsg::FilteredBlock,
+            ProtocolMsg::Checkpoint,
+            ProtocolMsg::PoolJoin,
+            ProtocolMsg::PoolJob,
+            ProtocolMsg::PoolShare,
+            ProtocolMsg::GetRange,
+        ];
+
+        /// Name of the message kind, as used in logs and metrics.
+        pub fn name(self) -> &'static str {
+            match self {
+                ProtocolMsg::Greet => "greet",
+                ProtocolMsg::Farewell => "farewell",
+                ProtocolMsg::Neighbour => "neighbour",
+                ProtocolMsg::Transaction => "transaction",
+                ProtocolMsg::Chain => "chain",
+                ProtocolMsg::PollChain => "pollchain",
+                ProtocolMsg::Fragment => "fragment",
+                ProtocolMsg::Handshake => "handshake",
+                ProtocolMsg::Encrypted => "encrypted",
+                ProtocolMsg::Ping => "ping",
+                ProtocolMsg::Pong => "pong",
+                ProtocolMsg::Announce => "announce",
+                ProtocolMsg::PeerList => "peerlist",
+                ProtocolMsg::Peers => "peers",
+                ProtocolMsg::DhtFind => "dht_find",
+                ProtocolMsg::DhtFound => "dht_found",
+                ProtocolMsg::DhtProvide => "dht_provide",
+                ProtocolMsg::GetHeaders => "getheaders",
+                ProtocolMsg::Headers => "headers",
+                ProtocolMsg::GetBlocks => "getblocks",
+                ProtocolMsg::Blocks => "blocks",
+                ProtocolMsg::Inv => "inv",
+                ProtocolMsg::GetData => "getdata",
+                ProtocolMsg::Summarize => "summarize",
+                ProtocolMsg::Request => "request",
+                ProtocolMsg::Record => "record",
+                ProtocolMsg::Response => "response",
+                ProtocolMsg::Role => "role",
+                ProtocolMsg::Batch => "batch",
+                ProtocolMsg::Welcome => "welcome",
+                ProtocolMsg::Challenge => "challenge",
+                ProtocolMsg::Registry => "registry",
+                ProtocolMsg::Validate => "validate",
+                ProtocolMsg::NodeInfo => "node_info",
+                ProtocolMsg::PeerInfo => "peer_info",
+                ProtocolMsg::Mempool => "mempool",
+                ProtocolMsg::Evict => "evict",
+                ProtocolMsg::BatchTransaction => "batch_transaction",
+                ProtocolMsg::Mining => "mining",
+                ProtocolMsg::FilterLoad => "filterload",
+                ProtocolMsg::FilteredBlock => "filtered_block",
+                ProtocolMsg::Checkpoint => "checkpoint",
+                ProtocolMsg::PoolJoin => "pool_join",
+                ProtocolMsg::PoolJob => "pool_job",
+                ProtocolMsg::PoolShare => "pool_share",
+                ProtocolMsg::GetRange => "get_range",
+            }
+        }
+    }
+
+    impl TryFrom<u8> for ProtocolMsg {
+        type Error = UnknownProtocol;
+
+        fn try_from(byte: u8) -> Result<Self, Self::Error> {
+            match byte {
+                1..=46 => Ok(ProtocolMsg::ALL[byte as usize - 1]),
+                _ => Err(UnknownProtocol(byte)),
+            }
+        }
+    }
+
+    impl From<ProtocolMsg> for u8 {
+        fn from(protocol: ProtocolMsg) -> u8 {
+            protocol as u8
+        }
+    }
+
+    /// Name of a protocol byte, as used in logs and metrics.
+    pub fn name(protocol: u8) -> &'static str {
+        match ProtocolMsg::try_from(protocol) {
+            Ok(protocol) => protocol.name(),
+            Err(unknown) if unknown.is_extension() => "extension",
+            Err(_) => "unknown",
+        }
+    }
+}
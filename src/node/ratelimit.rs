@@ -0,0 +1,106 @@
+pub mod ratelimit {
+
+    use std::{collections::HashMap, time::Instant};
+
+    /// Messages per second each peer may send, on average.
+    pub const PEER_RATE_LIMIT: u32 = 20;
+    /// Messages per second the node accepts from all peers together, on average.
+    pub const GLOBAL_RATE_LIMIT: u32 = 200;
+    /// Bytes of message covered by one token. Larger messages, such as chains, cost more.
+    pub const BYTES_PER_TOKEN: usize = 8 * 1024;
+    /// Number of per-peer buckets above which idle ones are forgotten.
+    const MAX_TRACKED_PEERS: usize = 1024;
+
+    /// Allows bursts of up to `capacity` tokens, refilled at `rate` tokens per second.
+    pub struct TokenBucket {
+        capacity: f64,
+        rate: f64,
+        tokens: f64,
+        last_refill: Instant,
+    }
+
+    impl TokenBucket {
+        pub fn new(rate: u32, capacity: u32) -> Self {
+            TokenBucket {
+                capacity: capacity as f64,
+                rate: rate as f64,
+                tokens: capacity as f64,
+                last_refill: Instant::now(),
+            }
+        }
+
+        fn refill(&mut self) {
+            let elapsed = self.last_refill.elapsed().as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+            self.last_refill = Instant::now();
+        }
+
+        /// Takes `cost` tokens if there are enough left.
+        pub fn try_take(&mut self, cost: f64) -> bool {
+            self.refill();
+            if self.tokens < cost {
+                return false;
+            }
+            self.tokens -= cost;
+            true
+        }
+
+        pub fn is_full(&mut self) -> bool {
+            self.refill();
+            self.tokens >= self.capacity
+        }
+    }
+
+    /// Outcome of `RateLimiter::check`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum RateDecision {
+        Allowed,
+        /// The sender went over its own budget.
+        PeerLimited,
+        /// The node as a whole is receiving more than it accepts.
+        GlobalLimited,
+    }
+
+    /// Per-peer and global token buckets guarding the listener. Bursts of twice the
+    /// per-second rate are tolerated.
+    pub struct RateLimiter {
+        peer_rate: u32,
+        global: TokenBucket,
+        peers: HashMap<String, TokenBucket>,
+    }
+
+    impl RateLimiter {
+        pub fn new(peer_rate: u32, global_rate: u32) -> Self {
+            RateLimiter {
+                peer_rate,
+                global: TokenBucket::new(global_rate, 2 * global_rate),
+                peers: HashMap::new(),
+            }
+        }
+
+        /// Charges a message of `size` bytes from `sender` against both budgets.
+        pub fn check(&mut self, sender: &str, size: usize) -> RateDecision {
+            let cost = 1.0 + (size / BYTES_PER_TOKEN) as f64;
+            if self.peers.len() > MAX_TRACKED_PEERS {
+                self.peers.retain(|_, bucket| !bucket.is_full());
+            }
+            let peer_rate = self.peer_rate;
+            let bucket = self.peers
+                .entry(sender.to_string())
+                .or_insert_with(|| TokenBucket::new(peer_rate, 2 * peer_rate));
+            if !bucket.try_take(cost) {
+                return RateDecision::PeerLimited;
+            }
+            if !self.global.try_take(cost) {
+                return RateDecision::GlobalLimited;
+            }
+            RateDecision::Allowed
+        }
+    }
+
+    impl Default for RateLimiter {
+        fn default() -> Self {
+            RateLimiter::new(PEER_RATE_LIMIT, GLOBAL_RATE_LIMIT)
+        }
+    }
+}
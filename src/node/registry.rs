@@ -0,0 +1,156 @@
+pub mod registry {
+
+    use crate::node::neighbour::neighbour::{Neighbour, Role};
+    use crate::clock::clock::clock::now;
+
+    use std::collections::HashMap;
+
+    use rand::seq::IteratorRandom;
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    /// Interval (in seconds) at which nodes re-announce themselves to their trackers.
+    pub const ANNOUNCE_INTERVAL: u64 = 30;
+    /// Time (in seconds) after which a peer that stopped announcing is dropped from the registry.
+    pub const REGISTRATION_TTL: u64 = 3 * ANNOUNCE_INTERVAL;
+    /// Maximum number of peers sent in answer to a `ProtocolMsg::PeerList` request.
+    pub const PEERLIST_SIZE: usize = 16;
+    /// Peers handed to a node along with the answer to its greeting.
+    pub const SEED_PEERS: usize = 8;
+    /// How far (in seconds) ahead of this tracker's clock a replicated announce may be
+    /// dated. Later dates are brought back to it, so a tracker with a clock running fast
+    /// cannot keep its registrations from expiring, nor win every conflict.
+    pub const MAX_ANNOUNCE_DRIFT: u64 = 10;
+    /// Namespace of the peer store a tracker's registry is saved to. See `Node::save_peers`.
+    pub const REGISTRY_NAMESPACE: &str = "registry";
+
+    /// A registered peer and when it last announced itself, as replicated between trackers.
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct Registration {
+        pub neighbour: Neighbour,
+        /// Unix timestamp (in seconds) of the peer's last announce, to any tracker.
+        pub last_announce: u64,
+    }
+
+    /// The peers a tracker knows to be active.
+    pub struct Registry {
+        peers: HashMap<Uuid, Registration>,
+    }
+
+    impl Registry {
+        pub fn new() -> Self {
+            Registry {
+                peers: HashMap::new(),
+            }
+        }
+
+        /// Registers a peer, or refreshes its registration if it is already known.
+        pub fn announce(&mut self, neighbour: Neighbour) {
+            self.peers.insert(neighbour.id, Registration {
+                neighbour,
+                last_announce: now(),
+            });
+        }
+
+        /// Updates the role of a registered peer.
+        ///
+        /// # Returns
+        /// `true` if the peer is registered.
+        pub fn set_role(&mut self, id: &Uuid, role: Role) -> bool {
+            match self.peers.get_mut(id) {
+                Some(registration) => {
+                    registration.neighbour.role = role;
+                    true
+                },
+                None => false,
+            }
+        }
+
+        /// Every registration, to replicate to other trackers.
+        pub fn registrations(&self) -> Vec<Registration> {
+            self.peers.values().cloned().collect()
+        }
+
+        /// Merges registrations replicated by another tracker. When both trackers know a
+        /// peer, the most recent announce wins; expired registrations are ignored, and
+        /// announces dated in the future are taken as made `MAX_ANNOUNCE_DRIFT` seconds
+        /// from now at most.
+        ///
+        /// # Arguments
+        /// * `registrations` - The other tracker's registrations.
+        /// * `own_id` - The id of this tracker, which never registers itself.
+        ///
+        /// # Returns
+        /// The number of registrations added or refreshed.
+        pub fn merge(&mut self, registrations: Vec<Registration>, own_id: Uuid) -> usize {
+            let now = now();
+            let deadline = now.saturating_sub(REGISTRATION_TTL);
+            let mut merged = 0;
+            for mut registration in registrations {
+                registration.last_announce = registration.last_announce.min(now + MAX_ANNOUNCE_DRIFT);
+                if registration.neighbour.id == own_id || registration.last_announce < deadline {
+                    continue;
+                }
+                let newer = self.peers
+                    .get(&registration.neighbour.id)
+                    .is_none_or(|known| known.last_announce < registration.last_announce);
+                if newer {
+                    self.peers.insert(registration.neighbour.id, registration);
+                    merged += 1;
+                }
+            }
+            merged
+        }
+
+        /// Drops peers that have not announced themselves within `REGISTRATION_TTL`.
+        ///
+        /// # Returns
+        /// * `Vec<Neighbour>` - The peers dropped.
+        pub fn expire(&mut self) -> Vec<Neighbour> {
+            let deadline = now().saturating_sub(REGISTRATION_TTL);
+            let expired: Vec<Uuid> = self.peers
+                .iter()
+                .filter(|(_, registration)| registration.last_announce < deadline)
+                .map(|(id, _)| *id)
+                .collect();
+            expired
+                .into_iter()
+                .filter_map(|id| self.peers.remove(&id))
+                .map(|registration| registration.neighbour)
+                .collect()
+        }
+
+        /// Picks up to `n` random registered peers, leaving out the one asking.
+        ///
+        /// # Arguments
+        /// * `n` - Maximum number of peers returned.
+        /// * `requester` - The id of the peer the sample is for.
+        pub fn sample(&self, n: usize, requester: Uuid) -> Vec<Neighbour> {
+            let mut rng = rand::thread_rng();
+            self.peers
+                .values()
+                .filter(|registration| registration.neighbour.id != requester)
+                .map(|registration| registration.neighbour.clone())
+                .choose_multiple(&mut rng, n)
+        }
+
+        /// Whether the peer with id `id` is registered.
+        pub fn contains(&self, id: &Uuid) -> bool {
+            self.peers.contains_key(id)
+        }
+
+        pub fn len(&self) -> usize {
+            self.peers.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.peers.is_empty()
+        }
+    }
+
+    impl Default for Registry {
+        fn default() -> Self {
+            Registry::new()
+        }
+    }
+}
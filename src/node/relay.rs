@@ -0,0 +1,180 @@
+pub mod relay {
+
+    use crate::Transaction;
+    use crate::node::neighbour::neighbour::Neighbour;
+    use crate::clock::clock::clock;
+
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::{Deserialize, Serialize};
+
+    /// Transactions remembered by the seen cache. Older ones are forgotten first.
+    pub const SEEN_CACHE_SIZE: usize = 4096;
+    /// Times a transaction or neighbour is forwarded past the node that first relayed it,
+    /// unless `NodeConfig::max_hops` says otherwise.
+    pub const DEFAULT_MAX_HOPS: u8 = 8;
+
+    /// Seconds a transaction announced but not received yet keeps its forwards left.
+    /// Announcements older than that are evicted once `SeenCache::announce` runs out of
+    /// room.
+    pub const ANNOUNCED_EXPIRY: u64 = 60;
+
+    /// Payload of a `ProtocolMsg::Inv` message: transactions announced by id. Nodes
+    /// predating hop limits send the ids alone, which are taken as `DEFAULT_MAX_HOPS`
+    /// forwards left.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(from = "InventoryWire")]
+    pub struct Inventory {
+        pub ids: Vec<String>,
+        /// Times the transactions may still be forwarded by the receiver. Each forward
+        /// announces them with one less, and they are no longer announced at zero.
+        pub ttl: u8,
+    }
+
+    /// The shapes an `Inventory` arrives in.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum InventoryWire {
+        Limited { ids: Vec<String>, ttl: u8 },
+        Legacy(Vec<String>),
+    }
+
+    impl From<InventoryWire> for Inventory {
+        fn from(wire: InventoryWire) -> Self {
+            match wire {
+                InventoryWire::Limited { ids, ttl } => Inventory { ids, ttl },
+                InventoryWire::Legacy(ids) => Inventory { ids, ttl: DEFAULT_MAX_HOPS },
+            }
+        }
+    }
+
+    /// Payload of a `ProtocolMsg::Neighbour` message: a neighbour of the sender. Nodes
+    /// predating hop limits send the neighbour alone, as for `Inventory`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(from = "RelayedNeighbourWire")]
+    pub struct RelayedNeighbour {
+        pub neighbour: Neighbour,
+        /// Times the neighbour may still be forwarded by the receiver, as for `Inventory`.
+        pub ttl: u8,
+    }
+
+    /// The shapes a `RelayedNeighbour` arrives in.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RelayedNeighbourWire {
+        Limited { neighbour: Neighbour, ttl: u8 },
+        Legacy(Neighbour),
+    }
+
+    impl From<RelayedNeighbourWire> for RelayedNeighbour {
+        fn from(wire: RelayedNeighbourWire) -> Self {
+            match wire {
+                RelayedNeighbourWire::Limited { neighbour, ttl } => RelayedNeighbour { neighbour, ttl },
+                RelayedNeighbourWire::Legacy(neighbour) => RelayedNeighbour { neighbour, ttl: DEFAULT_MAX_HOPS },
+            }
+        }
+    }
+
+    /// Transactions this node already relayed, so each one is announced to its
+    /// neighbours only once. The cache is bounded: once full, the least recently
+    /// seen transaction is evicted.
+    pub struct SeenCache {
+        capacity: usize,
+        entries: HashMap<String, (u64, Transaction)>,
+        order: BTreeMap<u64, String>,
+        counter: u64,
+        /// Forwards left to the transactions announced but not received yet, by id, and
+        /// when they were announced.
+        announced: HashMap<String, (u8, u64)>,
+    }
+
+    impl SeenCache {
+        pub fn new(capacity: usize) -> Self {
+            SeenCache {
+                capacity,
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                counter: 0,
+                announced: HashMap::new(),
+            }
+        }
+
+        /// Remembers a transaction, or marks it as recently seen if it is already known.
+        ///
+        /// # Returns
+        /// `true` if the transaction had not been seen before and should be relayed.
+        pub fn insert(&mut self, transaction: Transaction) -> bool {
+            let id = transaction.id();
+            self.counter += 1;
+            if let Some((last_seen, _)) = self.entries.get_mut(&id) {
+                self.order.remove(last_seen);
+                *last_seen = self.counter;
+                self.order.insert(self.counter, id);
+                return false;
+            }
+            self.order.insert(self.counter, id.clone());
+            self.entries.insert(id, (self.counter, transaction));
+            if self.entries.len() > self.capacity {
+                if let Some((_, oldest)) = self.order.pop_first() {
+                    self.entries.remove(&oldest);
+                }
+            }
+            true
+        }
+
+        pub fn contains(&self, id: &str) -> bool {
+            self.entries.contains_key(id)
+        }
+
+        pub fn get(&self, id: &str) -> Option<&Transaction> {
+            self.entries.get(id).map(|(_, transaction)| transaction)
+        }
+
+        /// Every transaction remembered, in no particular order.
+        pub fn transactions(&self) -> impl Iterator<Item = &Transaction> {
+            self.entries.values().map(|(_, transaction)| transaction)
+        }
+
+        /// Filters an announced inventory down to the transactions not seen yet.
+        pub fn unseen(&self, ids: Vec<String>) -> Vec<String> {
+            ids.into_iter().filter(|id| !self.contains(id)).collect()
+        }
+
+        /// Remembers the forwards left to a transaction announced with `ttl`, keeping the
+        /// most announced for it. Once `capacity` transactions wait, the ones announced
+        /// more than `ANNOUNCED_EXPIRY` seconds ago are evicted; if none is, further ones
+        /// are not remembered, and are relayed as if submitted to this node when they
+        /// arrive.
+        pub fn announce(&mut self, id: String, ttl: u8) {
+            let now = clock::now();
+            if self.announced.len() >= self.capacity && !self.announced.contains_key(&id) {
+                self.announced.retain(|_, (_, at)| now.saturating_sub(*at) < ANNOUNCED_EXPIRY);
+                if self.announced.len() >= self.capacity {
+                    return;
+                }
+            }
+            let (left, at) = self.announced.entry(id).or_insert((ttl, now));
+            *left = (*left).max(ttl);
+            *at = now;
+        }
+
+        /// Takes the forwards left to a transaction received after being announced.
+        pub fn take_announced(&mut self, id: &str) -> Option<u8> {
+            self.announced.remove(id).map(|(left, _)| left)
+        }
+
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+    }
+
+    impl Default for SeenCache {
+        fn default() -> Self {
+            SeenCache::new(SEEN_CACHE_SIZE)
+        }
+    }
+}
@@ -1,11 +1,19 @@
 pub mod reply {
 
-    use crate::Transaction;
     use crate::Chain;
+    use crate::chain::block::entry::entry::BlockEntry;
+    use crate::node::relay::relay::RelayedNeighbour;
 
-    pub trait Reply {
-        fn as_transaction(&mut self) -> Option<&mut Transaction>;
-        fn as_chain(&mut self) -> Option<&mut Chain>;
+    /// What a protocol handler hands back to the node loop once a message is processed.
+    pub enum GossipPayload {
+        /// A chain to compare against ours.
+        Chain(Chain),
+        /// A transaction or record for the miner.
+        Entry(Box<dyn BlockEntry>),
+        /// A neighbour introduced by another one.
+        Neighbour(RelayedNeighbour),
+        /// Nothing left to do.
+        None,
     }
 
 }
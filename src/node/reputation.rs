@@ -0,0 +1,61 @@
+pub mod reputation {
+
+    use uuid::Uuid;
+
+    /// Score every neighbour starts with.
+    pub const INITIAL_SCORE: i32 = 0;
+    /// Highest score a neighbour can accumulate, so good behaviour cannot buy unlimited misbehaviour.
+    pub const MAX_SCORE: i32 = 100;
+    /// Neighbours whose score drops to this value or below get banned.
+    pub const BAN_THRESHOLD: i32 = -100;
+    /// How long (in seconds) a ban lasts.
+    pub const BAN_DURATION: u64 = 600;
+
+    /// Something a neighbour did that affects its reputation.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Behaviour {
+        /// Sent a message that could not be parsed.
+        MalformedMessage,
+        /// Sent a chain that fails verification.
+        InvalidChain,
+        /// Sent a transaction spending coins it does not own.
+        InvalidTransaction,
+        /// Sent a record overwriting a key it does not own.
+        InvalidRecord,
+        /// Sent more messages than its rate limit allows.
+        Flooding,
+        /// Sent a valid chain longer than ours.
+        UsefulChain,
+    }
+
+    impl Behaviour {
+        /// Amount added to the neighbour's score.
+        pub fn score_delta(&self) -> i32 {
+            match self {
+                Behaviour::MalformedMessage => -20,
+                Behaviour::InvalidChain => -50,
+                Behaviour::InvalidTransaction => -2,
+                Behaviour::InvalidRecord => -2,
+                Behaviour::Flooding => -5,
+                Behaviour::UsefulChain => 10,
+            }
+        }
+    }
+
+    /// A peer that is temporarily refused by this node.
+    #[derive(Debug, Clone)]
+    pub struct BannedPeer {
+        pub id: Uuid,
+        pub address: String,
+        /// Unix timestamp (in seconds) at which the ban is lifted.
+        pub until: u64,
+    }
+
+    /// Applies a behaviour to a score, keeping it under `MAX_SCORE`.
+    ///
+    /// # Returns
+    /// * `i32` - The new score.
+    pub fn apply(score: i32, behaviour: Behaviour) -> i32 {
+        (score + behaviour.score_delta()).min(MAX_SCORE)
+    }
+}
@@ -0,0 +1,178 @@
+pub mod rpc {
+
+    use crate::clock::clock::clock;
+    use crate::node::gossip::gossip;
+    use crate::node::neighbour::neighbour::Neighbour;
+    use crate::node::protocol::protocol::ProtocolMsg;
+    use crate::node::secure::secure::Session;
+    use crate::node::transport::transport::Transport;
+
+    use std::{
+        collections::HashMap,
+        io::Error as IOError,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use thiserror::Error;
+    use tokio::sync::oneshot;
+    use uuid::Uuid;
+    use tracing::debug;
+
+    /// Time (in milliseconds) to wait for the answer to a request before sending it again.
+    pub const REQUEST_TIMEOUT: u64 = 1000;
+    /// Times a request is sent before giving up on it.
+    pub const REQUEST_RETRIES: usize = 3;
+    /// Length of a request id on the wire.
+    pub const REQUEST_ID_LENGTH: usize = 16;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum RpcError {
+        #[error("Request to {0} timed out.")]
+        #[from(ignore)]
+        Timeout(String),
+        #[error("No listener is running to receive the answer.")]
+        NoListener,
+        #[error(transparent)]
+        IOError(IOError),
+    }
+
+    /// A request waiting for its answer, with the peer asked and the session, if any,
+    /// its answer is sealed with.
+    struct Waiting {
+        answer: oneshot::Sender<Vec<u8>>,
+        peer: String,
+        session: Option<Arc<Session>>,
+    }
+
+    /// Requests waiting for an answer, by id. Answers are `ProtocolMsg::Response` messages
+    /// carrying the id of the request they answer, so they are never confused with
+    /// gossip or with answers to other requests. Peers sharing a session with the node
+    /// seal their answers, opened here with the session of the peer asked.
+    #[derive(Clone, Default)]
+    pub struct PendingRequests {
+        waiting: Arc<Mutex<HashMap<Uuid, Waiting>>>,
+    }
+
+    impl PendingRequests {
+        pub fn new() -> Self {
+            PendingRequests::default()
+        }
+
+        /// Registers a new request.
+        ///
+        /// # Arguments
+        /// * `peer` - The neighbour asked, whose session opens its answer.
+        ///
+        /// # Returns
+        /// * `(Uuid, oneshot::Receiver<Vec<u8>>)` - The request id and where its answer will arrive.
+        pub fn register(&self, peer: &Neighbour) -> (Uuid, oneshot::Receiver<Vec<u8>>) {
+            let id = Uuid::new_v4();
+            let (sender, receiver) = oneshot::channel();
+            let waiting = Waiting {
+                answer: sender,
+                peer: peer.address.clone(),
+                session: peer.session.clone(),
+            };
+            self.waiting.lock().unwrap().insert(id, waiting);
+            (id, receiver)
+        }
+
+        pub fn cancel(&self, id: &Uuid) {
+            self.waiting.lock().unwrap().remove(id);
+        }
+
+        /// Hands a `ProtocolMsg::Response` message to the request it answers.
+        ///
+        /// # Returns
+        /// `true` if a request was waiting for it.
+        pub fn resolve(&self, message: &[u8]) -> bool {
+            let Some((id, payload)) = parse(message) else {
+                return false;
+            };
+            let Some(waiting) = self.waiting.lock().unwrap().remove(&id) else {
+                return false;
+            };
+            waiting.answer.send(payload.to_vec()).is_ok()
+        }
+
+        /// Opens a `ProtocolMsg::Encrypted` message from a peer asked by a pending request.
+        ///
+        /// # Returns
+        /// * `Option<Vec<u8>>` - The plain message if it is a `ProtocolMsg::Response`, `None`
+        ///   if no request waits on `sender` or the message is anything else, left to the node.
+        pub fn open(&self, sender: &str, buffer: &[u8]) -> Option<Vec<u8>> {
+            let sessions: Vec<Arc<Session>> = self.waiting
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|waiting| waiting.peer == sender)
+                .filter_map(|waiting| waiting.session.clone())
+                .collect();
+            let response = |plain: &[u8]| plain.first() == Some(&(ProtocolMsg::Response as u8));
+            sessions
+                .iter()
+                .find_map(|session| session.open_if(buffer, response).ok().flatten())
+        }
+
+        pub fn len(&self) -> usize {
+            self.waiting.lock().unwrap().len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+
+    /// Builds a request: the protocol byte, the request id, then the payload.
+    pub fn encode(protocol: ProtocolMsg, id: &Uuid, payload: &[u8]) -> Vec<u8> {
+        let mut buffer = vec![protocol as u8];
+        buffer.extend_from_slice(id.as_bytes());
+        buffer.extend_from_slice(payload);
+        buffer
+    }
+
+    /// Splits a request or a response into its id and payload.
+    pub fn parse(message: &[u8]) -> Option<(Uuid, &[u8])> {
+        if message.len() < 1 + REQUEST_ID_LENGTH {
+            return None;
+        }
+        let id = Uuid::from_slice(&message[1..1 + REQUEST_ID_LENGTH]).ok()?;
+        Some((id, &message[1 + REQUEST_ID_LENGTH..]))
+    }
+
+    /// Sends a request and waits for its answer, sending it again every `REQUEST_TIMEOUT`
+    /// milliseconds, up to `REQUEST_RETRIES` times. Every attempt carries the same id, so a
+    /// late answer to an earlier attempt is still accepted.
+    ///
+    /// # Arguments
+    /// * `socket` - The socket to send from. Answers come back to the listener on its address.
+    /// * `pending` - The pending requests of that listener.
+    /// * `peer` - The neighbour to ask. The request is sealed if a session was established with it.
+    /// * `build` - Builds the datagram to send from the request id.
+    ///
+    /// # Returns
+    /// * `Vec<u8>` - The payload of the answer.
+    pub async fn request<F>(socket: &dyn Transport, pending: &PendingRequests, peer: &Neighbour, build: F) -> Result<Vec<u8>, RpcError>
+    where
+        F: Fn(&Uuid) -> Vec<u8>,
+    {
+        let (id, mut answer) = pending.register(peer);
+        let request = build(&id);
+        let address = peer.address.as_str();
+        for attempt in 0..REQUEST_RETRIES {
+            // Sealed anew every time, since peers refuse to open a sealed message twice.
+            if let Err(e) = gossip::send_message(socket, &peer.seal(request.clone()), address).await {
+                pending.cancel(&id);
+                return Err(e.into());
+            }
+            match clock::timeout(Duration::from_millis(REQUEST_TIMEOUT), &mut answer).await {
+                Ok(Ok(payload)) => return Ok(payload),
+                Ok(Err(_)) => return Err(RpcError::NoListener),
+                Err(_) => debug!("{} did not answer request {} (attempt {})", address, id, attempt + 1),
+            }
+        }
+        pending.cancel(&id);
+        Err(RpcError::Timeout(address.to_string()))
+    }
+}
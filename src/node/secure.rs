@@ -0,0 +1,233 @@
+pub mod secure {
+
+    use crate::node::protocol::protocol::ProtocolMsg;
+
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    };
+
+    use ring::{
+        aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+        agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, X25519},
+        hkdf::{self, Salt, HKDF_SHA256},
+        rand::SystemRandom,
+    };
+    use thiserror::Error;
+
+    pub const PUBLIC_KEY_LEN: usize = 32;
+    /// Encrypted datagram header: protocol byte followed by the nonce.
+    pub const ENCRYPTED_HEADER_SIZE: usize = 1 + NONCE_LEN;
+    /// Messages a session remembers having opened, counting back from the latest one.
+    /// Older messages are refused, as they cannot be told apart from replayed ones.
+    pub const REPLAY_WINDOW: u64 = 128;
+
+    const INITIATOR_INFO: &[u8] = b"humble_blockchain initiator";
+    const RESPONDER_INFO: &[u8] = b"humble_blockchain responder";
+
+    #[derive(Error, Debug)]
+    pub enum SecureError {
+        #[error("Failed to generate an ephemeral key.")]
+        KeyGeneration,
+        #[error("Key agreement with the peer failed.")]
+        KeyAgreement,
+        #[error("Malformed handshake - expected a {PUBLIC_KEY_LEN} byte public key, got {0} bytes.")]
+        MalformedHandshake(usize),
+        #[error("Malformed encrypted message - {0} bytes is shorter than the header.")]
+        TooShort(usize),
+        #[error("Failed to decrypt message - wrong key or tampered payload.")]
+        Decryption,
+        #[error("Replayed message - counter {0} was already opened or is too old.")]
+        Replayed(u64),
+    }
+
+    /// One side of an unauthenticated Diffie-Hellman handshake (similar to Noise `NN`).
+    ///
+    /// Each handshake uses a fresh X25519 key pair, so every session gets new keys. Peers
+    /// are not authenticated: the handshake protects against eavesdropping, not against an
+    /// active man in the middle.
+    pub struct Handshake {
+        private_key: EphemeralPrivateKey,
+        public_key: [u8; PUBLIC_KEY_LEN],
+    }
+
+    impl Handshake {
+        pub fn new() -> Result<Self, SecureError> {
+            let rng = SystemRandom::new();
+            let private_key = EphemeralPrivateKey::generate(&X25519, &rng)
+                .map_err(|_| SecureError::KeyGeneration)?;
+            let mut public_key = [0u8; PUBLIC_KEY_LEN];
+            public_key.copy_from_slice(
+                private_key.compute_public_key()
+                    .map_err(|_| SecureError::KeyGeneration)?
+                    .as_ref()
+            );
+            Ok(Handshake {
+                private_key,
+                public_key,
+            })
+        }
+
+        pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] {
+            self.public_key
+        }
+
+        /// Derives the session keys from the peer's public key.
+        ///
+        /// # Arguments
+        /// * `peer_public_key` - The ephemeral public key received from the peer.
+        /// * `initiator` - Whether this side sent the first handshake message.
+        ///
+        /// # Returns
+        /// * `Result<Session, SecureError>` - The established session.
+        pub fn complete(self, peer_public_key: &[u8], initiator: bool) -> Result<Session, SecureError> {
+            if peer_public_key.len() != PUBLIC_KEY_LEN {
+                return Err(SecureError::MalformedHandshake(peer_public_key.len()));
+            }
+            // Both sides must feed the same salt, so order the keys initiator first.
+            let salt = if initiator {
+                [self.public_key.as_slice(), peer_public_key].concat()
+            } else {
+                [peer_public_key, self.public_key.as_slice()].concat()
+            };
+            let peer = UnparsedPublicKey::new(&X25519, peer_public_key);
+            let (initiator_key, responder_key) = agreement::agree_ephemeral(self.private_key, &peer, |secret| {
+                let prk = Salt::new(HKDF_SHA256, &salt).extract(secret);
+                (derive_key(&prk, INITIATOR_INFO), derive_key(&prk, RESPONDER_INFO))
+            }).map_err(|_| SecureError::KeyAgreement)?;
+            let (sealing, opening) = if initiator {
+                (initiator_key?, responder_key?)
+            } else {
+                (responder_key?, initiator_key?)
+            };
+            Ok(Session {
+                sealing,
+                opening,
+                counter: AtomicU64::new(0),
+                opened: Mutex::new(ReplayWindow::default()),
+            })
+        }
+    }
+
+    fn derive_key(prk: &hkdf::Prk, info: &'static [u8]) -> Result<LessSafeKey, SecureError> {
+        let info = [info];
+        let okm = prk.expand(&info, &CHACHA20_POLY1305).map_err(|_| SecureError::KeyAgreement)?;
+        Ok(LessSafeKey::new(UnboundKey::from(okm)))
+    }
+
+    /// Counters of the messages opened lately: the highest one, and whether each of the
+    /// `REPLAY_WINDOW` ones below it was opened, as the anti-replay windows of IPsec and
+    /// DTLS do.
+    #[derive(Default)]
+    struct ReplayWindow {
+        highest: Option<u64>,
+        /// Bit `n` is set once the counter `highest - n` was opened.
+        opened: u128,
+    }
+
+    impl ReplayWindow {
+        /// Whether a message of `counter` may still be opened.
+        fn fresh(&self, counter: u64) -> bool {
+            match self.highest {
+                Some(highest) if counter <= highest => {
+                    let age = highest - counter;
+                    age < REPLAY_WINDOW && self.opened & (1 << age) == 0
+                },
+                _ => true,
+            }
+        }
+
+        /// Records that the message of `counter` was opened.
+        fn mark(&mut self, counter: u64) {
+            match self.highest {
+                Some(highest) if counter <= highest => self.opened |= 1 << (highest - counter),
+                highest => {
+                    let shift = highest.map_or(REPLAY_WINDOW, |highest| counter - highest);
+                    self.opened = match shift < REPLAY_WINDOW {
+                        true => self.opened << shift | 1,
+                        false => 1,
+                    };
+                    self.highest = Some(counter);
+                },
+            }
+        }
+    }
+
+    /// Symmetric keys shared with a single peer, one per direction.
+    pub struct Session {
+        sealing: LessSafeKey,
+        opening: LessSafeKey,
+        counter: AtomicU64,
+        /// Counters of the messages opened, so replayed ones are refused.
+        opened: Mutex<ReplayWindow>,
+    }
+
+    impl Session {
+        /// Encrypts a message into a `ProtocolMsg::Encrypted` datagram.
+        ///
+        /// # Arguments
+        /// * `message` - The plain message, starting with its protocol byte.
+        ///
+        /// # Returns
+        /// * `Vec<u8>` - The protocol byte, the nonce and the ciphertext with its tag.
+        pub fn seal(&self, message: &[u8]) -> Vec<u8> {
+            let mut nonce = [0u8; NONCE_LEN];
+            nonce[4..].copy_from_slice(&self.counter.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+
+            let mut ciphertext = message.to_vec();
+            self.sealing
+                .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut ciphertext)
+                .unwrap(); // Only fails for messages larger than ChaCha20 can address.
+
+            let mut buffer = Vec::with_capacity(ENCRYPTED_HEADER_SIZE + ciphertext.len());
+            buffer.push(ProtocolMsg::Encrypted as u8);
+            buffer.extend_from_slice(&nonce);
+            buffer.extend_from_slice(&ciphertext);
+            buffer
+        }
+
+        /// Decrypts a `ProtocolMsg::Encrypted` datagram. Each message opens once: messages
+        /// already opened, or sealed more than `REPLAY_WINDOW` messages before the latest
+        /// one opened, are refused.
+        ///
+        /// # Arguments
+        /// * `buffer` - The received message, including the protocol byte and nonce.
+        ///
+        /// # Returns
+        /// * `Result<Vec<u8>, SecureError>` - The plain message, starting with its protocol byte.
+        pub fn open(&self, buffer: &[u8]) -> Result<Vec<u8>, SecureError> {
+            self.open_if(buffer, |_| true).map(|plain| plain.unwrap())
+        }
+
+        /// Decrypts a `ProtocolMsg::Encrypted` datagram like `open`, but only counts it as
+        /// opened if `take` accepts the plain message, so whoever it is meant for can still
+        /// open it otherwise.
+        ///
+        /// # Returns
+        /// * `Result<Option<Vec<u8>>, SecureError>` - The plain message, `None` if `take`
+        ///   refused it.
+        pub fn open_if(&self, buffer: &[u8], take: impl FnOnce(&[u8]) -> bool) -> Result<Option<Vec<u8>>, SecureError> {
+            if buffer.len() < ENCRYPTED_HEADER_SIZE + aead::MAX_TAG_LEN {
+                return Err(SecureError::TooShort(buffer.len()));
+            }
+            let nonce = Nonce::try_assume_unique_for_key(&buffer[1..ENCRYPTED_HEADER_SIZE])
+                .map_err(|_| SecureError::Decryption)?;
+            let counter = u64::from_be_bytes(buffer[5..ENCRYPTED_HEADER_SIZE].try_into().unwrap()); // Length checked above.
+            let mut opened = self.opened.lock().unwrap();
+            if !opened.fresh(counter) {
+                return Err(SecureError::Replayed(counter));
+            }
+            let mut plaintext = buffer[ENCRYPTED_HEADER_SIZE..].to_vec();
+            let len = self.opening
+                .open_in_place(nonce, Aad::empty(), &mut plaintext)
+                .map_err(|_| SecureError::Decryption)?
+                .len();
+            plaintext.truncate(len);
+            if !take(&plaintext) {
+                return Ok(None);
+            }
+            opened.mark(counter);
+            Ok(Some(plaintext))
+        }
+    }
+}
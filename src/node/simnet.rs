@@ -0,0 +1,198 @@
+pub mod simnet {
+
+    use crate::node::transport::transport::{IOFuture, Network, Transport};
+
+    use std::{
+        collections::HashMap,
+        io::{Error as IOError, ErrorKind, Result as IOResult},
+        net::{IpAddr, SocketAddr},
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use tokio::sync::{mpsc, Mutex as AsyncMutex};
+
+    /// First port handed out to transports bound on port 0.
+    const FIRST_EPHEMERAL_PORT: u16 = 49152;
+
+    type Datagram = (Vec<u8>, SocketAddr);
+
+    /// An in-process network delivering datagrams over channels. Latency, loss and
+    /// partitions are under the test's control, and every random choice comes from a
+    /// seeded generator, so runs with the same seed drop the same datagrams.
+    ///
+    /// Install it with `transport::install` before starting nodes.
+    #[derive(Clone)]
+    pub struct SimNetwork {
+        state: Arc<Mutex<SimState>>,
+    }
+
+    struct SimState {
+        endpoints: HashMap<SocketAddr, mpsc::UnboundedSender<Datagram>>,
+        latency: Duration,
+        jitter: Duration,
+        drop_rate: f64,
+        /// Partition each host belongs to, with all its ports. Hosts not listed are in
+        /// partition 0.
+        partitions: HashMap<IpAddr, usize>,
+        rng: StdRng,
+        next_port: u16,
+        delivered: u64,
+        dropped: u64,
+    }
+
+    impl SimNetwork {
+        pub fn new(seed: u64) -> Self {
+            SimNetwork {
+                state: Arc::new(Mutex::new(SimState {
+                    endpoints: HashMap::new(),
+                    latency: Duration::ZERO,
+                    jitter: Duration::ZERO,
+                    drop_rate: 0.0,
+                    partitions: HashMap::new(),
+                    rng: StdRng::seed_from_u64(seed),
+                    next_port: FIRST_EPHEMERAL_PORT,
+                    delivered: 0,
+                    dropped: 0,
+                })),
+            }
+        }
+
+        /// Delays every datagram by `latency`, plus up to `jitter` more.
+        pub fn set_latency(&self, latency: Duration, jitter: Duration) {
+            let mut state = self.state.lock().unwrap();
+            state.latency = latency;
+            state.jitter = jitter;
+        }
+
+        /// Drops each datagram with probability `drop_rate`, between 0 and 1.
+        pub fn set_drop_rate(&self, drop_rate: f64) {
+            self.state.lock().unwrap().drop_rate = drop_rate.clamp(0.0, 1.0);
+        }
+
+        /// Splits the network: datagrams only flow between hosts of the same group, whatever
+        /// the ports, so the sockets a node binds on port 0 stay on its side. Hosts in no
+        /// group form one more group of their own.
+        ///
+        /// # Arguments
+        /// * `groups` - The addresses of each side of the partition.
+        pub fn partition(&self, groups: &[Vec<String>]) {
+            let mut state = self.state.lock().unwrap();
+            state.partitions = groups
+                .iter()
+                .enumerate()
+                .flat_map(|(i, group)| group.iter().filter_map(move |address| Some((address.parse::<SocketAddr>().ok()?.ip(), i + 1))))
+                .collect();
+        }
+
+        /// Removes any partition.
+        pub fn heal(&self) {
+            self.state.lock().unwrap().partitions.clear();
+        }
+
+        /// Returns the number of datagrams delivered and dropped so far.
+        pub fn stats(&self) -> (u64, u64) {
+            let state = self.state.lock().unwrap();
+            (state.delivered, state.dropped)
+        }
+
+        fn send(&self, from: SocketAddr, buffer: &[u8], target: &str) -> IOResult<usize> {
+            let target: SocketAddr = target
+                .parse()
+                .map_err(|_| IOError::new(ErrorKind::InvalidInput, format!("{} is not a simulated address", target)))?;
+            let mut state = self.state.lock().unwrap();
+            let side = |address: &SocketAddr| *state.partitions.get(&address.ip()).unwrap_or(&0);
+            let same_side = side(&from) == side(&target);
+            let drop_rate = state.drop_rate;
+            let lost = drop_rate > 0.0 && state.rng.gen_bool(drop_rate);
+            let Some(endpoint) = state.endpoints.get(&target).cloned() else {
+                // Like UDP, sending to nobody is not an error.
+                state.dropped += 1;
+                return Ok(buffer.len());
+            };
+            if !same_side || lost {
+                state.dropped += 1;
+                return Ok(buffer.len());
+            }
+            state.delivered += 1;
+            let jitter = match state.jitter.as_millis() as u64 {
+                0 => 0,
+                jitter => state.rng.gen_range(0..=jitter),
+            };
+            let delay = state.latency + Duration::from_millis(jitter);
+            let datagram = (buffer.to_vec(), from);
+            if delay.is_zero() {
+                let _ = endpoint.send(datagram);
+            } else {
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = endpoint.send(datagram);
+                });
+            }
+            Ok(buffer.len())
+        }
+    }
+
+    impl Network for SimNetwork {
+        fn bind(&self, address: &str) -> IOResult<Arc<dyn Transport>> {
+            let mut address: SocketAddr = address
+                .parse()
+                .map_err(|_| IOError::new(ErrorKind::InvalidInput, format!("{} is not a simulated address", address)))?;
+            let mut state = self.state.lock().unwrap();
+            if address.port() == 0 {
+                while state.endpoints.contains_key(&SocketAddr::new(address.ip(), state.next_port)) {
+                    state.next_port = state.next_port.checked_add(1).unwrap_or(FIRST_EPHEMERAL_PORT);
+                }
+                address.set_port(state.next_port);
+            }
+            if state.endpoints.contains_key(&address) {
+                return Err(IOError::new(ErrorKind::AddrInUse, format!("{} is already bound", address)));
+            }
+            let (sender, inbox) = mpsc::unbounded_channel();
+            state.endpoints.insert(address, sender);
+            Ok(Arc::new(SimSocket {
+                address,
+                inbox: AsyncMutex::new(inbox),
+                network: self.clone(),
+            }))
+        }
+    }
+
+    /// A transport bound on a `SimNetwork`. The address is released when it is dropped.
+    pub struct SimSocket {
+        address: SocketAddr,
+        inbox: AsyncMutex<mpsc::UnboundedReceiver<Datagram>>,
+        network: SimNetwork,
+    }
+
+    impl Transport for SimSocket {
+        fn send_to<'a>(&'a self, buffer: &'a [u8], target: &'a str) -> IOFuture<'a, usize> {
+            Box::pin(async move { self.network.send(self.address, buffer, target) })
+        }
+
+        fn recv_from<'a>(&'a self, buffer: &'a mut [u8]) -> IOFuture<'a, (usize, SocketAddr)> {
+            Box::pin(async move {
+                let (datagram, sender) = self.inbox
+                    .lock()
+                    .await
+                    .recv()
+                    .await
+                    .ok_or_else(|| IOError::new(ErrorKind::NotConnected, "simulated network closed"))?;
+                let n_bytes = datagram.len().min(buffer.len());
+                buffer[..n_bytes].copy_from_slice(&datagram[..n_bytes]);
+                Ok((n_bytes, sender))
+            })
+        }
+
+        fn local_addr(&self) -> IOResult<SocketAddr> {
+            Ok(self.address)
+        }
+    }
+
+    impl Drop for SimSocket {
+        fn drop(&mut self) {
+            self.network.state.lock().unwrap().endpoints.remove(&self.address);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+pub mod status {
+
+    use crate::chain::network::network::NetworkId;
+
+    use serde::{Deserialize, Serialize};
+    use uuid::Uuid;
+
+    /// What a node tells about itself. See `Node::status`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct NodeStatus {
+        pub id: Uuid,
+        /// `tracker`, `node` or `miner`.
+        pub role: String,
+        /// Seconds since the node was created.
+        pub uptime: u64,
+        /// Software the node runs, see `USER_AGENT`.
+        pub version: String,
+        /// Network it belongs to. Nodes that do not say are on `mainnet`.
+        #[serde(default)]
+        pub network: NetworkId,
+        /// Hash of the last block of its chain.
+        pub tip_hash: String,
+        /// Number of blocks of its chain.
+        pub height: usize,
+        /// Root of the state of its chain, the same on every node holding the same coins
+        /// and records. See `Chain::state_root`.
+        #[serde(default)]
+        pub state_root: String,
+        /// Whether its mining is paused. See `Node::pause_mining`.
+        #[serde(default)]
+        pub mining_paused: bool,
+    }
+
+    /// What a node knows about one of its neighbours. See `Node::peer_statuses`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PeerStatus {
+        pub id: Uuid,
+        pub address: String,
+        /// `tracker`, `node` or `miner`.
+        pub role: String,
+        /// Unix timestamp (in seconds) of the last message received from the neighbour.
+        pub last_seen: u64,
+        /// Reputation of the neighbour. See `reputation::Behaviour`.
+        pub score: i32,
+        /// Software the neighbour runs, if it said.
+        pub version: Option<String>,
+        /// Bytes received from the neighbour.
+        pub bytes_in: u64,
+        /// Bytes sent to the neighbour.
+        pub bytes_out: u64,
+    }
+}
@@ -0,0 +1,44 @@
+pub mod submit {
+
+    use crate::chain::block::entry::entry::InvalidEntryErr;
+
+    use thiserror::Error;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum SubmitError {
+        #[error(transparent)]
+        InvalidEntryErr(InvalidEntryErr),
+        #[error("Entry {0} was already submitted.")]
+        #[from(ignore)]
+        AlreadySubmitted(String),
+        #[error("This kind of entry cannot be submitted.")]
+        UnsupportedEntry,
+        #[error("The entry is not signed by its sender for this node's network.")]
+        InvalidSignature,
+        #[error("No miner to submit the entry to - this node is not a miner and has no neighbours.")]
+        NoMiners,
+    }
+
+    /// Proof that an entry was accepted by `Node::submit_entry`.
+    #[derive(Debug, Clone)]
+    pub struct SubmitReceipt {
+        /// The entry's id, to be passed to `Node::entry_status`.
+        pub id: String,
+        /// Number of neighbours the entry was sent or announced to.
+        pub relayed_to: usize,
+        /// Unix timestamp (in seconds) of the submission.
+        pub submitted_at: u64,
+    }
+
+    /// Where a submitted entry stands.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum EntryStatus {
+        /// Submitted through this node less than `TRANSACTION_TTL` ago but not in its chain yet.
+        Pending,
+        /// Included in the block with the given index.
+        Confirmed { block_index: usize },
+        /// Neither in its chain nor submitted through this node, or submitted so long ago
+        /// that the node forgot it.
+        Unknown,
+    }
+}
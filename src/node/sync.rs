@@ -0,0 +1,208 @@
+pub mod sync {
+
+    use crate::chain::block::block::block::{Block, BlockHeader};
+    use crate::clock::clock::clock;
+    use crate::chain::chain::chain::BlockCheckError;
+    use crate::node::{
+        decode::decode,
+        fragment::fragment::Reassembler,
+        gossip::gossip::{self, GossipError},
+        outbound::outbound,
+        protocol::protocol::ProtocolMsg,
+        rpc::rpc::RpcError,
+        transport::transport::Transport,
+    };
+
+    use std::{
+        future::Future,
+        io::Error as IOError,
+        ops::Range,
+    };
+
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use thiserror::Error;
+    use uuid::Uuid;
+    use tracing::debug;
+
+    /// Time (in milliseconds) to wait for a peer to answer a sync request.
+    pub const SYNC_TIMEOUT: u64 = 2000;
+    /// Times a request is sent again before the sync gives up on it.
+    pub const MAX_RETRIES: usize = 3;
+    /// Blocks asked for in a single `ProtocolMsg::GetBlocks` request, and most blocks a
+    /// `ProtocolMsg::GetRange` request is answered with.
+    pub const BLOCKS_PER_REQUEST: usize = 16;
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum SyncError {
+        #[error("No peers to synchronize with.")]
+        NoPeers,
+        #[error("Peers stopped answering before the chain was downloaded.")]
+        Timeout,
+        #[error("Received headers do not start at our genesis block.")]
+        GenesisMismatch,
+        #[error("Received chain is invalid: {0}")]
+        InvalidChain(BlockCheckError),
+        #[error("A peer sent blocks other than the ones asked for.")]
+        UnexpectedBlocks,
+        #[error(transparent)]
+        RpcError(RpcError),
+        #[error(transparent)]
+        IOError(IOError),
+    }
+
+    /// Payload of a `ProtocolMsg::GetHeaders` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct HeadersRequest {
+        pub request_id: Uuid,
+    }
+
+    /// Payload of a `ProtocolMsg::Headers` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct HeadersResponse {
+        pub request_id: Uuid,
+        /// The difficulty the sender's chain has reached.
+        pub difficulty: usize,
+        /// Every header of the sender's chain, genesis first.
+        pub headers: Vec<BlockHeader>,
+    }
+
+    /// Payload of a `ProtocolMsg::GetBlocks` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct BlocksRequest {
+        pub request_id: Uuid,
+        pub hashes: Vec<String>,
+    }
+
+    /// Payload of a `ProtocolMsg::Blocks` message.
+    #[derive(Serialize, Deserialize)]
+    pub struct BlocksResponse {
+        pub request_id: Uuid,
+        pub blocks: Vec<Block>,
+    }
+
+    /// Payload of a `ProtocolMsg::GetRange` request: the blocks of index `start` up to
+    /// `end`, excluded.
+    #[derive(Serialize, Deserialize)]
+    pub struct RangeRequest {
+        pub start: usize,
+        pub end: usize,
+    }
+
+    /// Answer to a `RangeRequest`: the first `BLOCKS_PER_REQUEST` blocks of the range the
+    /// sender holds, oldest first, and the difficulty its chain has reached.
+    #[derive(Serialize, Deserialize)]
+    pub struct RangeResponse {
+        pub difficulty: usize,
+        pub blocks: Vec<Block>,
+    }
+
+    /// Asks a peer for the headers of its chain, retrying up to `MAX_RETRIES` times.
+    ///
+    /// # Arguments
+    /// * `socket` - A socket dedicated to this sync, so answers are not mixed with gossip.
+    /// * `peer` - The address of the peer to ask.
+    pub async fn fetch_headers(socket: &dyn Transport, peer: &str) -> Result<HeadersResponse, SyncError> {
+        let mut reassembler = Reassembler::new();
+        for attempt in 0..MAX_RETRIES {
+            let request_id = Uuid::new_v4();
+            send(socket, ProtocolMsg::GetHeaders, &HeadersRequest { request_id }, peer).await?;
+            let deadline = clock::now_millis() + SYNC_TIMEOUT;
+            while let Some(response) = receive::<HeadersResponse>(socket, &mut reassembler, ProtocolMsg::Headers, deadline).await? {
+                if response.request_id == request_id {
+                    return Ok(response);
+                }
+            }
+            debug!("{} did not send headers (attempt {})", peer, attempt + 1);
+        }
+        Err(SyncError::Timeout)
+    }
+
+    /// Downloads the blocks of index within `range`, `BLOCKS_PER_REQUEST` at a time, each
+    /// request starting past the last block received. Stops early once the peer has no
+    /// more blocks in the range.
+    ///
+    /// # Arguments
+    /// * `range` - The indices of the blocks wanted.
+    /// * `request` - Asks the peer for the blocks of a range. See `gossip::get_range`.
+    ///
+    /// # Returns
+    /// * `Result<RangeResponse, E>` - The blocks, oldest first, each pointing to the hash
+    ///   of the one before it, and the difficulty last reported by the peer. An error
+    ///   of `request`, or `SyncError::UnexpectedBlocks` if the peer answered with blocks
+    ///   out of the range or not linked to one another.
+    pub async fn fetch_range<F, Fut, E>(range: Range<usize>, request: F) -> Result<RangeResponse, E>
+    where
+        F: Fn(Range<usize>) -> Fut,
+        Fut: Future<Output = Result<RangeResponse, E>>,
+        E: From<SyncError>,
+    {
+        let mut fetched = RangeResponse { difficulty: 0, blocks: vec![] };
+        let mut start = range.start;
+        while start < range.end {
+            let response = request(start..range.end).await?;
+            let Some(last) = response.blocks.last().map(|block| block.index) else {
+                break;
+            };
+            let mut previous = fetched.blocks.last();
+            for block in &response.blocks {
+                let linked = previous.is_none_or(|previous| previous.index < block.index && block.previous_hash == previous.hash);
+                if block.index < start || block.index >= range.end || !linked {
+                    return Err(SyncError::UnexpectedBlocks.into());
+                }
+                previous = Some(block);
+            }
+            start = last + 1;
+            fetched.difficulty = response.difficulty;
+            fetched.blocks.extend(response.blocks);
+        }
+        Ok(fetched)
+    }
+
+    /// Whether `blocks` are the blocks of `headers`, in the same order.
+    pub fn match_headers(blocks: &[Block], headers: &[BlockHeader]) -> bool {
+        blocks.len() == headers.len() && blocks.iter().zip(headers).all(|(block, header)| block.header() == *header)
+    }
+
+    async fn send<T: Serialize>(socket: &dyn Transport, protocol: ProtocolMsg, payload: &T, peer: &str) -> Result<(), SyncError> {
+        let mut buffer = vec![protocol as u8];
+        buffer.extend_from_slice(serde_json::to_string(payload).unwrap().as_bytes());
+        gossip::send_message(socket, &buffer, peer).await?;
+        Ok(())
+    }
+
+    /// Waits until `deadline`, in milliseconds on the clock, for a message of the given
+    /// protocol, skipping anything else.
+    ///
+    /// # Returns
+    /// * `Option<T>` - The parsed payload, `None` once the deadline passed.
+    async fn receive<T: DeserializeOwned>(
+        socket: &dyn Transport,
+        reassembler: &mut Reassembler,
+        protocol: ProtocolMsg,
+        deadline: u64,
+    ) -> Result<Option<T>, SyncError> {
+        loop {
+            let wait = clock::until(deadline);
+            let messages = match gossip::recv_message(socket, reassembler, wait).await {
+                Ok(Some((buffer, _))) => outbound::unbatch(buffer),
+                Ok(None) => return Ok(None),
+                Err(GossipError::IOError(e)) => return Err(e.into()),
+                Err(e) => {
+                    debug!("Dropped a sync message: {}", e);
+                    if wait.is_zero() {
+                        return Ok(None);
+                    }
+                    continue;
+                },
+            };
+            for buffer in messages {
+                if buffer.first() != Some(&(protocol as u8)) {
+                    continue;
+                }
+                if let Ok(payload) = decode::json::<T>(&buffer[1..], decode::MAX_CHAIN_SIZE) {
+                    return Ok(Some(payload));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+pub mod transport {
+
+    use std::{
+        future::Future,
+        io::Result as IOResult,
+        net::SocketAddr,
+        pin::Pin,
+        sync::{Arc, Mutex, OnceLock},
+    };
+
+    use tokio::net::UdpSocket;
+
+    /// Future returned by `Transport` methods.
+    pub type IOFuture<'a, T> = Pin<Box<dyn Future<Output = IOResult<T>> + Send + 'a>>;
+
+    /// A datagram socket. Nodes only talk to the network through this trait, so the UDP
+    /// sockets they use can be swapped for a simulated network in tests.
+    pub trait Transport: Send + Sync {
+        /// Sends one datagram to `target`.
+        fn send_to<'a>(&'a self, buffer: &'a [u8], target: &'a str) -> IOFuture<'a, usize>;
+
+        /// Waits for the next datagram and copies it into `buffer`.
+        fn recv_from<'a>(&'a self, buffer: &'a mut [u8]) -> IOFuture<'a, (usize, SocketAddr)>;
+
+        fn local_addr(&self) -> IOResult<SocketAddr>;
+    }
+
+    /// Something transports can be bound on instead of the real network.
+    pub trait Network: Send + Sync {
+        /// Binds a transport on `address`. A port of 0 picks a free one.
+        fn bind(&self, address: &str) -> IOResult<Arc<dyn Transport>>;
+    }
+
+    impl Transport for UdpSocket {
+        fn send_to<'a>(&'a self, buffer: &'a [u8], target: &'a str) -> IOFuture<'a, usize> {
+            Box::pin(UdpSocket::send_to(self, buffer, target))
+        }
+
+        fn recv_from<'a>(&'a self, buffer: &'a mut [u8]) -> IOFuture<'a, (usize, SocketAddr)> {
+            Box::pin(UdpSocket::recv_from(self, buffer))
+        }
+
+        fn local_addr(&self) -> IOResult<SocketAddr> {
+            UdpSocket::local_addr(self)
+        }
+    }
+
+    impl<T: Transport + ?Sized> Transport for Arc<T> {
+        fn send_to<'a>(&'a self, buffer: &'a [u8], target: &'a str) -> IOFuture<'a, usize> {
+            (**self).send_to(buffer, target)
+        }
+
+        fn recv_from<'a>(&'a self, buffer: &'a mut [u8]) -> IOFuture<'a, (usize, SocketAddr)> {
+            (**self).recv_from(buffer)
+        }
+
+        fn local_addr(&self) -> IOResult<SocketAddr> {
+            (**self).local_addr()
+        }
+    }
+
+    /// The network transports are bound on, `None` for real UDP sockets.
+    static NETWORK: OnceLock<Mutex<Option<Arc<dyn Network>>>> = OnceLock::new();
+
+    fn network() -> &'static Mutex<Option<Arc<dyn Network>>> {
+        NETWORK.get_or_init(|| Mutex::new(None))
+    }
+
+    /// Makes every transport bound from now on use `network`, or UDP again if `None`.
+    pub fn install(network_: Option<Arc<dyn Network>>) {
+        *network().lock().unwrap() = network_;
+    }
+
+    /// Binds a transport on `address`, on the installed network if there is one.
+    pub async fn bind(address: &str) -> IOResult<Arc<dyn Transport>> {
+        let installed = network().lock().unwrap().clone();
+        match installed {
+            Some(network) => network.bind(address),
+            None => Ok(Arc::new(UdpSocket::bind(address).await?)),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+pub mod verifier {
+
+    use crate::chain::{
+        block::block::block::Block,
+        chain::chain::{self, BlockCheckError, Chain, NonceUndo},
+        config::config::{ChainConfig, Checkpoint},
+        genesis::genesis::{Genesis, GenesisError},
+        state::state::{ChainState, StateUndo},
+    };
+
+    use std::{
+        collections::{HashMap, VecDeque},
+        time::{Duration, Instant},
+    };
+
+    use thiserror::Error;
+    use tokio::sync::oneshot::{self, error::TryRecvError};
+
+    /// Verified blocks whose nonces are kept to undo them, so a fork off any of them is
+    /// verified from the fork on. Deeper forks are verified from the genesis.
+    pub const UNDO_DEPTH: usize = 128;
+    /// Seconds between two audits of the whole chain by default.
+    pub const DEFAULT_AUDIT_INTERVAL: u64 = 3600;
+
+    #[derive(Error, Debug)]
+    pub enum AuditError {
+        #[error("The chain is invalid - {0}")]
+        Chain(BlockCheckError),
+        #[error("The chain breaks the chain rules - {0}")]
+        Rules(BlockCheckError),
+        #[error("The chain belongs to another network - {0}")]
+        Genesis(GenesisError),
+    }
+
+    /// Outcome of an audit of the whole chain. See `Verifier::start_audit`.
+    #[derive(Debug)]
+    pub struct AuditReport {
+        /// Number of blocks audited.
+        pub height: usize,
+        /// Hash of the last block audited.
+        pub tip: String,
+        pub result: Result<(), AuditError>,
+    }
+
+    /// What `Verifier::verify` found, to make the chain the one later verifications build
+    /// on with `Verifier::commit` once adopted.
+    pub struct Verified {
+        /// Blocks shared with the chain verified last.
+        common: usize,
+        /// Hashes of the blocks past them.
+        appended: Vec<String>,
+        nonces: HashMap<Vec<u8>, u64>,
+        undo: VecDeque<NonceUndo>,
+        state: ChainState,
+        state_undo: VecDeque<StateUndo>,
+        /// Whether the chain was verified from the genesis, because it forked off the
+        /// chain verified last deeper than `UNDO_DEPTH`.
+        pub full: bool,
+    }
+
+    impl Verified {
+        /// Number of blocks verified.
+        pub fn n_verified(&self) -> usize {
+            self.appended.len()
+        }
+    }
+
+    /// Schedules the verification of a node's chain. Chains received are only verified
+    /// past the blocks they share with the chain verified last, since running
+    /// `Chain::verify_chain` on every one is too slow on long chains. As the blocks
+    /// verified once are never checked again, the whole chain is audited in the
+    /// background every `audit_interval`, off the runtime serving the node.
+    pub struct Verifier {
+        rules: ChainConfig,
+        /// Hashes of the blocks of the chain verified last, genesis first.
+        verified: Vec<String>,
+        /// Nonce of the last transaction of each sender, in the verified blocks.
+        nonces: HashMap<Vec<u8>, u64>,
+        /// Nonces the last verified blocks replaced, newest last.
+        undo: VecDeque<NonceUndo>,
+        /// State the verified blocks add up to, which the chains adopted take on, see
+        /// `Chain::with_state`.
+        state: ChainState,
+        /// What the last verified blocks replaced in `state`, newest last.
+        state_undo: VecDeque<StateUndo>,
+        audit_interval: Option<Duration>,
+        last_audit: Instant,
+        audit: Option<oneshot::Receiver<AuditReport>>,
+    }
+
+    impl Verifier {
+        /// # Arguments
+        /// * `rules` - The rules chains must follow, on top of `Chain::verify_chain`.
+        /// * `audit_interval` - Seconds between two audits of the whole chain, `None` to
+        ///   never audit it.
+        pub fn new(rules: ChainConfig, audit_interval: Option<u64>) -> Self {
+            Verifier {
+                rules,
+                verified: vec![],
+                nonces: HashMap::new(),
+                undo: VecDeque::new(),
+                state: ChainState::new(),
+                state_undo: VecDeque::new(),
+                audit_interval: audit_interval.map(Duration::from_secs),
+                last_audit: Instant::now(),
+                audit: None,
+            }
+        }
+
+        /// Number of blocks of the chain verified last.
+        pub fn height(&self) -> usize {
+            self.verified.len()
+        }
+
+        /// Returns the state the blocks of the chain verified last add up to.
+        pub fn state(&self) -> &ChainState {
+            &self.state
+        }
+
+        /// Verifies `chain` as `Chain::verify_chain` and `ChainConfig::verify` do, going
+        /// through the blocks past the ones it shares with the chain verified last only,
+        /// whose state it replays them into to check their state roots.
+        ///
+        /// # Returns
+        /// * `Result<Verified, BlockCheckError>` - What was verified, to `commit` if the
+        ///   chain is adopted, or the first `BlockCheckError` found.
+        pub fn verify(&self, chain: &Chain) -> Result<Verified, BlockCheckError> {
+            let shared = self.verified
+                .iter()
+                .zip(chain.blocks_from(0))
+                .take_while(|(verified, block)| **verified == block.hash)
+                .count();
+            let mut nonces = self.nonces.clone();
+            let mut undo = self.undo.clone();
+            let mut state = self.state.clone();
+            let mut state_undo = self.state_undo.clone();
+            let rolled_back = self.verified.len() - shared;
+            let (common, full) = match rolled_back <= undo.len() {
+                true => {
+                    for replaced in undo.drain(undo.len() - rolled_back..).rev() {
+                        undo_nonces(&mut nonces, replaced);
+                    }
+                    for replaced in state_undo.drain(state_undo.len() - rolled_back..).rev() {
+                        state.undo(replaced);
+                    }
+                    (shared, false)
+                },
+                false => {
+                    nonces.clear();
+                    undo.clear();
+                    state = ChainState::new();
+                    state_undo.clear();
+                    (0, true)
+                },
+            };
+
+            // The blocks mined on pruned ones can no longer be checked, so forks off them are refused.
+            if let Some(pruned) = chain.blocks_from(0).skip(common.saturating_sub(1)).find(|block| block.pruned) {
+                return Err(BlockCheckError::Pruned(pruned.index));
+            }
+            chain.verify_from(common)?;
+            self.rules.verify_checkpoints(&chain.blocks()[common..])?;
+            let headers: Vec<_> = chain.blocks_from(0).skip(common).map(|block| block.header()).collect();
+            self.rules.verify_headers(&headers)?;
+            self.rules.verify_records(chain.blocks(), common)?;
+            self.rules.verify_mints(chain.blocks(), common)?;
+            let mut appended = vec![];
+            for block in chain.blocks_from(0).skip(common) {
+                undo.push_back(chain::apply_nonces(&mut nonces, block)?);
+                state_undo.push_back(state.apply(block));
+                // Blocks stored before blocks had state roots commit to none.
+                if !block.state_root.is_empty() && block.state_root != state.root() {
+                    return Err(BlockCheckError::WrongStateRoot { index: block.index, expected: state.root(), got: block.state_root.clone() });
+                }
+                if undo.len() > UNDO_DEPTH {
+                    undo.pop_front();
+                    state_undo.pop_front();
+                }
+                appended.push(block.hash.clone());
+            }
+            Ok(Verified { common, appended, nonces, undo, state, state_undo, full })
+        }
+
+        /// Makes the chain `verified` comes from the one later verifications build on.
+        pub fn commit(&mut self, verified: Verified) {
+            self.verified.truncate(verified.common);
+            self.verified.extend(verified.appended);
+            self.nonces = verified.nonces;
+            self.undo = verified.undo;
+            self.state = verified.state;
+            self.state_undo = verified.state_undo;
+        }
+
+        /// Forgets every chain verified, so the next one is verified from the genesis.
+        pub fn forget(&mut self) {
+            self.verified.clear();
+            self.nonces.clear();
+            self.undo.clear();
+            self.state = ChainState::new();
+            self.state_undo.clear();
+        }
+
+        /// Rolls the chain verified last back to `kept`, the blocks it starts with, so the
+        /// next chains are verified past them only.
+        ///
+        /// # Returns
+        /// * `bool` - Whether `state` is now the one `kept` adds up to. If the chain
+        ///   verified last does not start with `kept`, or forks off it deeper than
+        ///   `UNDO_DEPTH`, every chain verified is forgotten instead, see `forget`.
+        pub fn roll_back(&mut self, kept: &[Block]) -> bool {
+            let shared = self.verified.iter().zip(kept).take_while(|(verified, block)| **verified == block.hash).count();
+            let rolled_back = self.verified.len().saturating_sub(kept.len());
+            if shared < kept.len() || rolled_back > self.undo.len() {
+                self.forget();
+                return false;
+            }
+            for replaced in self.undo.drain(self.undo.len() - rolled_back..).rev() {
+                undo_nonces(&mut self.nonces, replaced);
+            }
+            for replaced in self.state_undo.drain(self.state_undo.len() - rolled_back..).rev() {
+                self.state.undo(replaced);
+            }
+            self.verified.truncate(kept.len());
+            true
+        }
+
+        /// Pins `checkpoint` in the rules chains are verified against. See `ChainConfig::pin`.
+        pub fn pin(&mut self, checkpoint: Checkpoint) -> Result<bool, BlockCheckError> {
+            self.rules.pin(checkpoint)
+        }
+
+        /// Whether an audit is due: auditing is on, none is running and the last one
+        /// started `audit_interval` ago.
+        pub fn audit_due(&self) -> bool {
+            self.audit.is_none() && self.audit_interval.is_some_and(|interval| self.last_audit.elapsed() >= interval)
+        }
+
+        /// Audits `chain` on a blocking thread, checking all of it as a node does the
+        /// chains of its store: `Chain::verify_chain`, the chain rules and, if given, the
+        /// genesis. See `poll_audit`.
+        pub fn start_audit(&mut self, chain: Chain, genesis: Option<Genesis>) {
+            let rules = self.rules.clone();
+            let (sender, receiver) = oneshot::channel();
+            tokio::task::spawn_blocking(move || {
+                let _ = sender.send(audit(&chain, &rules, genesis.as_ref()));
+            });
+            self.audit = Some(receiver);
+            self.last_audit = Instant::now();
+        }
+
+        /// Returns the report of the audit running, once it finished.
+        pub fn poll_audit(&mut self) -> Option<AuditReport> {
+            let receiver = self.audit.as_mut()?;
+            match receiver.try_recv() {
+                Ok(report) => {
+                    self.audit = None;
+                    Some(report)
+                },
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Closed) => {
+                    self.audit = None;
+                    None
+                },
+            }
+        }
+    }
+
+    /// Puts back in `nonces` what a block replaced, see `chain::apply_nonces`.
+    fn undo_nonces(nonces: &mut HashMap<Vec<u8>, u64>, replaced: NonceUndo) {
+        for (sender, previous) in replaced.into_iter().rev() {
+            match previous {
+                Some(nonce) => nonces.insert(sender, nonce),
+                None => nonces.remove(&sender),
+            };
+        }
+    }
+
+    /// Checks the whole of `chain`. See `Verifier::start_audit`.
+    pub fn audit(chain: &Chain, rules: &ChainConfig, genesis: Option<&Genesis>) -> AuditReport {
+        let result = chain.verify_chain().map_err(AuditError::Chain)
+            .and_then(|_| rules.verify(chain).map_err(AuditError::Rules))
+            .and_then(|_| genesis.map_or(Ok(()), |genesis| genesis.verify(chain)).map_err(AuditError::Genesis));
+        AuditReport {
+            height: chain.len(),
+            tip: chain.get_last_block().hash,
+            result,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+pub mod record {
+
+    use crate::chain::network::network::NetworkId;
+    use crate::clock::clock::clock;
+
+    use std::{
+        fmt,
+        num::ParseIntError,
+        string::FromUtf8Error,
+    };
+    use thiserror::Error;
+    use base64::{Engine as _, engine::general_purpose};
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    /// Fields in the wire format of a `Record`.
+    pub const N_RECORD_PARAMS: usize = 5;
+    pub const DEFAULT_MAX_VALUE_SIZE: usize = 4096;
+    pub const DEFAULT_BYTES_PER_COIN: usize = 256;
+
+    #[derive(Error, Debug, derive_more::From, derive_more::Display)]
+    pub enum RecordFromBase64Error {
+        Base64Error(base64::DecodeError),
+        ParseError(ParseIntError),
+        Utf8Error(FromUtf8Error),
+        #[from(ignore)]
+        MissingFields(usize),
+    }
+
+    /// A key-value entry stored on the chain. The first record for a key makes its
+    /// owner the only one allowed to overwrite it. A record with an empty value deletes
+    /// its key.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Record {
+        pub key: String,
+        pub value: String,
+        pub owner: Vec<u8>,
+        pub timestamp: u64,
+        pub signature: Option<Vec<u8>>,
+    }
+
+    impl Record {
+        pub fn new(key: String, value: String, owner: Vec<u8>) -> Self {
+            let now = clock::now();
+            Record {
+                key,
+                value,
+                owner,
+                timestamp: now,
+                signature: None,
+            }
+        }
+
+        /// The bytes the owner signs: the NUL terminated id of the network, key and value,
+        /// each after its length, then owner and timestamp. Lengths and timestamp are
+        /// big-endian, so hosts of either byte order sign the same bytes, and no two
+        /// splits of the same bytes into key and value sign alike.
+        pub fn signed_bytes(&self, network: &NetworkId) -> Vec<u8> {
+            [
+                network.as_str().as_bytes(),
+                &[0],
+                &(self.key.len() as u64).to_be_bytes(),
+                self.key.as_bytes(),
+                &(self.value.len() as u64).to_be_bytes(),
+                self.value.as_bytes(),
+                &self.owner,
+                &self.timestamp.to_be_bytes(),
+            ]
+            .concat()
+        }
+
+        /// Whether the record carries a signature of `signed_bytes` by its owner, for `network`.
+        pub fn verify_signature(&self, network: &NetworkId) -> bool {
+            let Some(signature) = &self.signature else {
+                return false;
+            };
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &self.owner)
+                .verify(&self.signed_bytes(network), signature)
+                .is_ok()
+        }
+
+        /// Whether the record deletes its key rather than writing a value under it.
+        pub fn is_deletion(&self) -> bool {
+            self.value.is_empty()
+        }
+
+        /// Identifies the record by the SHA-256 of its wire format.
+        ///
+        /// # Returns
+        /// * `String` - The hex encoded digest.
+        pub fn id(&self) -> String {
+            let str_record: String = self.clone().into();
+            format!("{:x}", Sha256::digest(str_record.as_bytes()))
+        }
+    }
+
+    /// Limits every chain holds records to, so a record cannot bloat blocks for free.
+    ///
+    /// ```toml
+    /// [chain.records]
+    /// max_value_size = 4096
+    /// bytes_per_coin = 256
+    /// enforced_from = 120000
+    /// ```
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct RecordRules {
+        /// Most bytes the value of a record may take.
+        pub max_value_size: usize,
+        /// Bytes of value each coin its owner burns allows, sending it to the zero wallet
+        /// in the block of the record.
+        pub bytes_per_coin: usize,
+        /// Height from which received chains are held to these rules. Records below it, or
+        /// in any block if unset, are only held to them as miners take them in.
+        pub enforced_from: Option<usize>,
+    }
+
+    impl Default for RecordRules {
+        fn default() -> Self {
+            RecordRules {
+                max_value_size: DEFAULT_MAX_VALUE_SIZE,
+                bytes_per_coin: DEFAULT_BYTES_PER_COIN,
+                enforced_from: None,
+            }
+        }
+    }
+
+    impl RecordRules {
+        /// Coins the owner of `record` must burn in its block for it to be added to a
+        /// chain: one for every full `bytes_per_coin` bytes of its value, so short values
+        /// and deletions are free.
+        pub fn cost(&self, record: &Record) -> usize {
+            record.value.len() / self.bytes_per_coin.max(1)
+        }
+
+        /// Whether received chains are held to these rules at the block of `index`. See
+        /// `enforced_from`.
+        pub fn enforced_at(&self, index: usize) -> bool {
+            self.enforced_from.is_some_and(|from| index >= from)
+        }
+    }
+
+    /// Records travel as `key;value;owner;timestamp;signature;`, every field but the
+    /// timestamp base64 encoded so keys and values may contain the separator.
+    impl TryFrom<String> for Record {
+        type Error = RecordFromBase64Error;
+        fn try_from(string: String) -> Result<Self, Self::Error> {
+            let params: Vec<&str> = string.as_str().split(';').collect();
+            if params.len() < N_RECORD_PARAMS {
+                return Err(RecordFromBase64Error::MissingFields(params.len()));
+            }
+            let signature = general_purpose::STANDARD.decode(params[4]).ok().filter(|sig| !sig.is_empty());
+            Ok(Record {
+                key: String::from_utf8(general_purpose::STANDARD.decode(params[0])?)?,
+                value: String::from_utf8(general_purpose::STANDARD.decode(params[1])?)?,
+                owner: general_purpose::STANDARD.decode(params[2])?,
+                timestamp: params[3].parse::<u64>()?,
+                signature,
+            })
+        }
+    }
+
+    impl From<Record> for String {
+        fn from(record: Record) -> String {
+            let signature = record.signature
+                .as_ref()
+                .map(|sig| general_purpose::STANDARD.encode(sig))
+                .unwrap_or_default();
+            format!("{};{};{};{};{};",
+                general_purpose::STANDARD.encode(&record.key),
+                general_purpose::STANDARD.encode(&record.value),
+                general_purpose::STANDARD.encode(&record.owner),
+                record.timestamp,
+                signature,
+            )
+        }
+    }
+
+    impl fmt::Display for Record {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "timestamp: {}, owner: {:?}, {} = {}",
+                    self.timestamp, self.owner, self.key, self.value)
+        }
+    }
+}
@@ -0,0 +1,43 @@
+pub mod codec {
+
+    use crate::store::store::store::StoreError;
+
+    use base64::{engine::general_purpose, Engine as _};
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+    /// How a `Store` turns values into the text its engine keeps.
+    #[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Codec {
+        /// Readable and understood by block operations, snapshots and the SQLite tables.
+        #[default]
+        Json,
+        /// Base64 encoded bincode, several times smaller than JSON. Block operations,
+        /// write-ahead logs, retention and snapshots cannot look into it and fail with
+        /// `StoreError::OpaqueCodec`, so keep it for stores only used through `Store::save`
+        /// and `Store::load`.
+        Bincode,
+    }
+
+    impl Codec {
+        pub fn encode<T: Serialize>(&self, value: &T) -> Result<String, StoreError> {
+            match self {
+                Codec::Json => serde_json::to_string(value).map_err(|_| StoreError::CodecError),
+                Codec::Bincode => {
+                    let bytes = bincode::serialize(value).map_err(|_| StoreError::CodecError)?;
+                    Ok(general_purpose::STANDARD.encode(bytes))
+                },
+            }
+        }
+
+        pub fn decode<T: DeserializeOwned>(&self, data: &str) -> Result<T, StoreError> {
+            match self {
+                Codec::Json => serde_json::from_str(data).map_err(StoreError::corrupted),
+                Codec::Bincode => {
+                    let bytes = general_purpose::STANDARD.decode(data.trim()).map_err(StoreError::corrupted)?;
+                    bincode::deserialize(&bytes).map_err(StoreError::corrupted)
+                },
+            }
+        }
+    }
+}
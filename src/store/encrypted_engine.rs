@@ -0,0 +1,155 @@
+pub mod encrypted_engine {
+
+    use crate::store::{
+        engine::engine::Engine,
+        store::store::StoreError,
+    };
+
+    use std::{
+        fs::{self, OpenOptions},
+        io::Write,
+        path::Path,
+    };
+    #[cfg(unix)]
+    use std::os::unix::fs::OpenOptionsExt;
+
+    use base64::{engine::general_purpose, Engine as _};
+    use ring::{
+        aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+        rand::{SecureRandom, SystemRandom},
+    };
+    use zeroize::Zeroizing;
+
+    /// Length of the keys stores are encrypted with.
+    pub const KEY_LEN: usize = 32;
+
+    /// Decorator encrypting the payloads of another engine with ChaCha20-Poly1305.
+    ///
+    /// Every payload gets a random nonce, stored along with the ciphertext and its tag as
+    /// base64, so the wrapped engine only ever sees text. A payload altered on disk or
+    /// encrypted with another key fails to load with `StoreError::DecryptionError`.
+    /// Blocks go through the payload, so they are encrypted as well, even if the wrapped
+    /// engine would otherwise store them apart.
+    pub struct EncryptedEngine {
+        inner: Box<dyn Engine>,
+        /// Wiped when the engine is dropped.
+        secret: Zeroizing<[u8; KEY_LEN]>,
+        key: LessSafeKey,
+        rng: SystemRandom,
+    }
+
+    impl EncryptedEngine {
+        /// # Arguments
+        /// * `inner` - The engine the encrypted payloads are stored in.
+        /// * `key` - The secret key. Keep it away from the store.
+        pub fn new(inner: Box<dyn Engine>, key: &[u8; KEY_LEN]) -> Self {
+            EncryptedEngine {
+                inner,
+                secret: Zeroizing::new(*key),
+                key: LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).unwrap()), // Only fails on a key of the wrong length.
+                rng: SystemRandom::new(),
+            }
+        }
+    }
+
+    impl Engine for EncryptedEngine {
+        fn store(&mut self, data: &str) -> Result<(), StoreError> {
+            let mut nonce = [0u8; NONCE_LEN];
+            self.rng.fill(&mut nonce).map_err(|_| StoreError::StorageError)?;
+            let mut sealed = data.as_bytes().to_vec();
+            self.key
+                .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+                .map_err(|_| StoreError::StorageError)?;
+            let mut buffer = nonce.to_vec();
+            buffer.extend_from_slice(&sealed);
+            self.inner.store(&general_purpose::STANDARD.encode(buffer))
+        }
+
+        fn load(&mut self) -> Result<String, StoreError> {
+            let buffer = general_purpose::STANDARD
+                .decode(self.inner.load()?.trim())
+                .map_err(|_| StoreError::DecryptionError)?;
+            if buffer.len() < NONCE_LEN + aead::MAX_TAG_LEN {
+                return Err(StoreError::DecryptionError);
+            }
+            let (nonce, sealed) = buffer.split_at(NONCE_LEN);
+            let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| StoreError::DecryptionError)?;
+            let mut plaintext = sealed.to_vec();
+            let len = self.key
+                .open_in_place(nonce, Aad::empty(), &mut plaintext)
+                .map_err(|_| StoreError::DecryptionError)?
+                .len();
+            plaintext.truncate(len);
+            String::from_utf8(plaintext).map_err(|e| {
+                drop(Zeroizing::new(e.into_bytes()));
+                StoreError::DecryptionError
+            })
+        }
+
+        fn exists(&mut self) -> Result<bool, StoreError> {
+            self.inner.exists()
+        }
+
+        fn delete(&mut self) -> Result<(), StoreError> {
+            self.inner.delete()
+        }
+
+        fn compact(&mut self) -> Result<(), StoreError> {
+            self.inner.compact()
+        }
+
+        fn flush(&mut self) -> Result<(), StoreError> {
+            self.inner.flush()
+        }
+
+        /// Namespaces are encrypted with the same key.
+        fn namespace(&mut self, name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            Ok(Box::new(EncryptedEngine::new(self.inner.namespace(name)?, &self.secret)))
+        }
+    }
+
+    /// Writes a new random key to the file at `path`, in the format `load_key` reads. Only
+    /// the owner of the file can read it, and an existing file is never overwritten.
+    ///
+    /// # Returns
+    /// * `Result<Zeroizing<[u8; KEY_LEN]>, StoreError>` - The key, wiped once dropped, or an
+    ///   error if it cannot be written.
+    pub fn create_key(path: impl AsRef<Path>) -> Result<Zeroizing<[u8; KEY_LEN]>, StoreError> {
+        let path = path.as_ref();
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        SystemRandom::new().fill(key.as_mut()).map_err(|_| StoreError::StorageError)?;
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(path)
+            .map_err(|e| StoreError::io(path, e))?;
+        let encoded = Zeroizing::new(general_purpose::STANDARD.encode(key.as_ref()));
+        file.write_all(encoded.as_bytes()).map_err(|e| StoreError::io(path, e))?;
+        Ok(key)
+    }
+
+    /// Reads a key from the file at `path`, holding the base64 encoding of `KEY_LEN` bytes.
+    ///
+    /// # Returns
+    /// * `Result<Zeroizing<[u8; KEY_LEN]>, StoreError>` - The key, wiped once dropped,
+    ///   `StoreError::OpenError` if the file cannot be read or `StoreError::InvalidKey` if
+    ///   it does not hold a key.
+    pub fn load_key(path: impl AsRef<Path>) -> Result<Zeroizing<[u8; KEY_LEN]>, StoreError> {
+        let path = path.as_ref();
+        let encoded = fs::read_to_string(path)
+            .map(Zeroizing::new)
+            .map_err(|_| StoreError::OpenError(path.to_path_buf()))?;
+        let decoded = general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map(Zeroizing::new)
+            .map_err(|_| StoreError::InvalidKey(path.to_path_buf()))?;
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        if decoded.len() != KEY_LEN {
+            return Err(StoreError::InvalidKey(path.to_path_buf()));
+        }
+        key.copy_from_slice(&decoded);
+        Ok(key)
+    }
+}
@@ -0,0 +1,135 @@
+pub mod engine {
+
+    use crate::{
+        chain::{
+            block::block::block::Block,
+            chain::chain::Chain,
+        },
+        store::store::store::StoreError,
+    };
+
+    use std::ops::Range;
+
+    /// A backend able to persist and restore a single serialized payload.
+    ///
+    /// Implement it to keep a node's state somewhere else than in a file, then hand it to
+    /// `Store::with_engine`. An engine holds at most one payload at a time:
+    /// * `store` replaces the payload, so a `load` right after returns exactly `data`.
+    /// * `load` fails with `StoreError::EmptyFile` while nothing is stored.
+    /// * `exists` tells whether a `load` would find a payload.
+    /// * `delete` drops the payload; deleting when nothing is stored is not an error.
+    ///
+    /// The block methods address the blocks of a stored chain by height, the `index` of a
+    /// block. By default they rewrite the whole payload as a serialized `Chain`; engines able
+    /// to keep blocks apart should override all of them, in which case the blocks may not be
+    /// part of the payload.
+    pub trait Engine: Send + Sync {
+        /// Replaces whatever was stored before with `data`.
+        fn store(&mut self, data: &str) -> Result<(), StoreError>;
+        /// Returns the last payload stored.
+        fn load(&mut self) -> Result<String, StoreError>;
+        /// Whether a payload is stored.
+        fn exists(&mut self) -> Result<bool, StoreError>;
+        /// Drops the stored payload.
+        fn delete(&mut self) -> Result<(), StoreError>;
+
+        /// Opens the namespace `name`: an engine of the same kind, next to this one, whose
+        /// payload and blocks are isolated from this engine's and from other namespaces'.
+        /// Fails with `StoreError::NoNamespaces` unless the engine supports them.
+        fn namespace(&mut self, name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            Err(StoreError::NoNamespaces(name.to_string()))
+        }
+
+        /// Adds `block` on top of the stored blocks. Fails with `StoreError::InvalidBlock`
+        /// if it does not extend them.
+        fn append_block(&mut self, block: &Block) -> Result<(), StoreError> {
+            let (mut blocks, difficulty) = match load_chain(self)? {
+                Some(chain) => (chain.get_blocks(), chain.difficulty),
+                None => (vec![], 1),
+            };
+            blocks.push(block.clone());
+            let chain = Chain::from_blocks(blocks, difficulty).map_err(|_| StoreError::InvalidBlock(block.index))?;
+            store_chain(self, &chain)
+        }
+
+        /// Returns the block at `height`, `None` if no block is stored there.
+        fn load_block(&mut self, height: usize) -> Result<Option<Block>, StoreError> {
+            Ok(self.load_range(height..height + 1)?.pop())
+        }
+
+        /// Returns the blocks stored at heights within `range`, lowest first.
+        fn load_range(&mut self, range: Range<usize>) -> Result<Vec<Block>, StoreError> {
+            let blocks = load_chain(self)?.map(|chain| chain.get_blocks()).unwrap_or_default();
+            Ok(blocks.into_iter().filter(|block| range.contains(&block.index)).collect())
+        }
+
+        /// Height of the highest block stored, `None` if there is none.
+        fn tip_height(&mut self) -> Result<Option<usize>, StoreError> {
+            Ok(load_chain(self)?.map(|chain| chain.get_last_block().index))
+        }
+
+        /// Prunes every stored block but the last `keep_full` ones. See `Chain::prune`.
+        ///
+        /// # Returns
+        /// The number of blocks pruned.
+        fn prune(&mut self, keep_full: usize) -> Result<usize, StoreError> {
+            let Some(mut chain) = load_chain(self)? else {
+                return Ok(0);
+            };
+            let pruned = chain.prune(keep_full);
+            if pruned > 0 {
+                store_chain(self, &chain)?;
+            }
+            Ok(pruned)
+        }
+
+        /// Reclaims the space freed by `prune`, `truncate_to` or `delete`, for engines that
+        /// do not give it back on their own.
+        fn compact(&mut self) -> Result<(), StoreError> {
+            Ok(())
+        }
+
+        /// Makes sure everything stored so far survives the process, for engines that hold
+        /// writes back.
+        fn flush(&mut self) -> Result<(), StoreError> {
+            Ok(())
+        }
+
+        /// Rebuilds what the engine derives from the blocks it stores, such as tables or
+        /// lookups by hash, from the blocks themselves. Meant for recovering from corrupted
+        /// indices, or filling the ones a newer version adds. Engines deriving nothing do
+        /// nothing.
+        ///
+        /// # Returns
+        /// The number of blocks reindexed.
+        fn reindex(&mut self) -> Result<usize, StoreError> {
+            Ok(0)
+        }
+
+        /// Drops every block above `height`, e.g. before storing the blocks of a longer fork.
+        fn truncate_to(&mut self, height: usize) -> Result<(), StoreError> {
+            let Some(chain) = load_chain(self)? else {
+                return Ok(());
+            };
+            let blocks: Vec<Block> = chain.get_blocks().into_iter().filter(|block| block.index <= height).collect();
+            if blocks.is_empty() {
+                return self.delete();
+            }
+            let chain = Chain::from_blocks(blocks, chain.difficulty).map_err(StoreError::corrupted)?;
+            store_chain(self, &chain)
+        }
+    }
+
+    fn load_chain<E: Engine + ?Sized>(engine: &mut E) -> Result<Option<Chain>, StoreError> {
+        match engine.load() {
+            Ok(data) => serde_json::from_str(&data).map(Some).map_err(StoreError::corrupted),
+            Err(StoreError::EmptyFile) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn store_chain<E: Engine + ?Sized>(engine: &mut E, chain: &Chain) -> Result<(), StoreError> {
+        let data = serde_json::to_string(chain).map_err(|_| StoreError::StorageError)?;
+        engine.store(&data)
+    }
+}
@@ -0,0 +1,234 @@
+pub mod file_engine {
+
+    use crate::store::{
+        engine::engine::Engine,
+        store::store::StoreError,
+    };
+
+    use std::{
+        fs::{self, File, OpenOptions, TryLockError},
+        io::{Error as IOError, ErrorKind, Write},
+        path::{Path, PathBuf},
+    };
+
+    /// Default file the chain is persisted to.
+    pub const DEFAULT_FILENAME: &str = "chain.dat";
+    /// Suffix of the file a payload is written to before replacing the stored one.
+    const TEMP_SUFFIX: &str = "tmp";
+    /// Suffix of the previous generation, kept in case the current one is lost.
+    const BACKUP_SUFFIX: &str = "bak";
+    /// Suffix of the file locked by the engine writing to a store.
+    const LOCK_SUFFIX: &str = "lock";
+    /// Prefix of the suffix of namespace files, so no namespace name is taken for the
+    /// temporary, backup or lock file of the store.
+    const NAMESPACE_PREFIX: &str = "ns-";
+    /// First bytes of every file written, followed by the format byte. Files without it
+    /// predate the header and hold the payload as plain text.
+    const MAGIC: &[u8] = b"HBST";
+    /// Format of a payload stored as is.
+    const FORMAT_PLAIN: u8 = 0;
+    /// Format of a zstd compressed payload, the latest one.
+    const FORMAT_ZSTD: u8 = 1;
+
+    /// Engine persisting the payload to a single file.
+    ///
+    /// A payload is written to a temporary file and synced before being renamed over the
+    /// stored one, so a crash mid-write never leaves a truncated file behind. The previous
+    /// payload is kept as a backup and loaded if the current file is missing.
+    ///
+    /// Payloads can be zstd compressed, see `with_compression`. Whether a file is compressed
+    /// is recorded in its header, so files are loaded whatever the current setting.
+    ///
+    /// An engine opened with `open` holds an advisory lock for as long as it lives, so a
+    /// second process cannot write to the same store. Engines opened with `open_read_only`
+    /// take no lock and never write, so a store can be read while a node writes it; since
+    /// payloads are replaced by renames, readers always see a whole payload.
+    pub struct FileEngine {
+        path: PathBuf,
+        compression: Option<i32>,
+        read_only: bool,
+        /// Lock file held while the engine lives, `None` for readers.
+        _lock: Option<File>,
+    }
+
+    impl FileEngine {
+        /// Opens the file at `path`, creating it and its parent directories if they do not exist.
+        ///
+        /// # Arguments
+        /// * `path` - Where the payload is stored. Its backup and temporary files are kept next to it.
+        ///
+        /// # Returns
+        /// * `Result<FileEngine, StoreError>` - The engine, `StoreError::Locked` if another
+        ///   engine writes to the store, or the I/O error met if the file cannot be created
+        ///   or written to.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            let path = path.as_ref().to_path_buf();
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                fs::create_dir_all(parent).map_err(|e| StoreError::io(parent, e))?;
+            }
+            let mut engine = FileEngine {
+                path,
+                compression: None,
+                read_only: false,
+                _lock: None,
+            };
+            let lock_path = engine.sibling(LOCK_SUFFIX);
+            let lock = File::create(&lock_path).map_err(|e| StoreError::io(&lock_path, e))?;
+            match lock.try_lock() {
+                Ok(()) => engine._lock = Some(lock),
+                Err(TryLockError::WouldBlock) => return Err(StoreError::Locked(engine.path)),
+                Err(TryLockError::Error(e)) => return Err(StoreError::io(&lock_path, e)),
+            }
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&engine.path)
+                .map_err(|e| StoreError::io(&engine.path, e))?;
+            Ok(engine)
+        }
+
+        /// Opens the file at `path` for reading only, without creating anything, even while
+        /// another engine writes to it.
+        ///
+        /// # Returns
+        /// * `Result<FileEngine, StoreError>` - The engine, or `StoreError::NotFound` if
+        ///   neither the file nor its backup exist.
+        pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            let engine = FileEngine {
+                path: path.as_ref().to_path_buf(),
+                compression: None,
+                read_only: true,
+                _lock: None,
+            };
+            if !engine.path.exists() && !engine.sibling(BACKUP_SUFFIX).exists() {
+                return Err(StoreError::NotFound(engine.path));
+            }
+            Ok(engine)
+        }
+
+        fn writable(&self) -> Result<(), StoreError> {
+            match self.read_only {
+                true => Err(StoreError::ReadOnly(self.path.clone())),
+                false => Ok(()),
+            }
+        }
+
+        /// Compresses the payloads stored from now on.
+        ///
+        /// # Arguments
+        /// * `level` - The zstd compression level, 0 picking zstd's default. `None` stores
+        ///   payloads uncompressed.
+        pub fn with_compression(mut self, level: Option<i32>) -> Self {
+            self.compression = level;
+            self
+        }
+
+        fn encode(&self, data: &str) -> Result<Vec<u8>, StoreError> {
+            let mut encoded = MAGIC.to_vec();
+            match self.compression {
+                Some(level) => {
+                    encoded.push(FORMAT_ZSTD);
+                    let compressed = zstd::encode_all(data.as_bytes(), level).map_err(|e| StoreError::io(&self.path, e))?;
+                    encoded.extend_from_slice(&compressed);
+                },
+                None => {
+                    encoded.push(FORMAT_PLAIN);
+                    encoded.extend_from_slice(data.as_bytes());
+                },
+            }
+            Ok(encoded)
+        }
+
+        fn decode(bytes: Vec<u8>) -> Result<String, StoreError> {
+            let bytes = match bytes.strip_prefix(MAGIC) {
+                Some([FORMAT_PLAIN, payload @ ..]) => payload.to_vec(),
+                Some([FORMAT_ZSTD, payload @ ..]) => zstd::decode_all(payload).map_err(StoreError::corrupted)?,
+                Some([format, ..]) => {
+                    return Err(StoreError::VersionMismatch { supported: FORMAT_ZSTD as u32, found: *format as u32 });
+                },
+                Some([]) => return Err(StoreError::corrupted(IOError::new(ErrorKind::UnexpectedEof, "header without a format"))),
+                None => bytes,
+            };
+            String::from_utf8(bytes).map_err(StoreError::corrupted)
+        }
+
+        fn sibling(&self, suffix: &str) -> PathBuf {
+            let mut name = self.path.clone().into_os_string();
+            name.push(".");
+            name.push(suffix);
+            PathBuf::from(name)
+        }
+
+        fn read(path: &Path) -> Result<Option<String>, StoreError> {
+            let data = match fs::read(path) {
+                Ok(bytes) => FileEngine::decode(bytes)?,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(StoreError::io(path, e)),
+            };
+            Ok(Some(data).filter(|data| !data.is_empty()))
+        }
+
+        fn remove(path: &Path) -> Result<(), StoreError> {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != ErrorKind::NotFound => Err(StoreError::io(path, e)),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl Engine for FileEngine {
+        fn store(&mut self, data: &str) -> Result<(), StoreError> {
+            self.writable()?;
+            let encoded = self.encode(data)?;
+            let temp = self.sibling(TEMP_SUFFIX);
+            let mut file = File::create(&temp).map_err(|e| StoreError::io(&temp, e))?;
+            file.write_all(&encoded).map_err(|e| StoreError::io(&temp, e))?;
+            file.sync_all().map_err(|e| StoreError::io(&temp, e))?;
+            if self.path.exists() {
+                fs::rename(&self.path, self.sibling(BACKUP_SUFFIX)).map_err(|e| StoreError::io(&self.path, e))?;
+            }
+            fs::rename(&temp, &self.path).map_err(|e| StoreError::io(&self.path, e))
+        }
+
+        fn load(&mut self) -> Result<String, StoreError> {
+            if let Some(data) = FileEngine::read(&self.path)? {
+                return Ok(data);
+            }
+            if self.path.exists() {
+                return Err(StoreError::EmptyFile);
+            }
+            FileEngine::read(&self.sibling(BACKUP_SUFFIX))?.ok_or(StoreError::EmptyFile)
+        }
+
+        fn exists(&mut self) -> Result<bool, StoreError> {
+            match self.load() {
+                Ok(_) => Ok(true),
+                Err(StoreError::EmptyFile) => Ok(false),
+                Err(e) => Err(e),
+            }
+        }
+
+        fn delete(&mut self) -> Result<(), StoreError> {
+            self.writable()?;
+            FileEngine::remove(&self.path)?;
+            FileEngine::remove(&self.sibling(BACKUP_SUFFIX))
+        }
+
+        /// Drops the backup generation, which still holds whatever was pruned from the current one.
+        fn compact(&mut self) -> Result<(), StoreError> {
+            self.writable()?;
+            FileEngine::remove(&self.sibling(BACKUP_SUFFIX))
+        }
+
+        /// Namespaces are kept in files next to this one, named after it and the namespace,
+        /// e.g. `chain.dat.ns-identity`.
+        fn namespace(&mut self, name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            let path = self.sibling(&format!("{}{}", NAMESPACE_PREFIX, name));
+            let engine = match self.read_only {
+                true => FileEngine::open_read_only(path)?,
+                false => FileEngine::open(path)?,
+            };
+            Ok(Box::new(engine.with_compression(self.compression)))
+        }
+    }
+}
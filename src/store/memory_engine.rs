@@ -0,0 +1,44 @@
+pub mod memory_engine {
+
+    use crate::store::{
+        engine::engine::Engine,
+        store::store::StoreError,
+    };
+
+    /// Engine keeping the payload in memory. Nothing survives the process, which makes
+    /// it mostly useful for tests and simulations.
+    #[derive(Default)]
+    pub struct MemoryEngine {
+        data: Option<String>,
+    }
+
+    impl MemoryEngine {
+        pub fn new() -> Self {
+            MemoryEngine::default()
+        }
+    }
+
+    impl Engine for MemoryEngine {
+        fn store(&mut self, data: &str) -> Result<(), StoreError> {
+            self.data = Some(data.to_string());
+            Ok(())
+        }
+
+        fn load(&mut self) -> Result<String, StoreError> {
+            self.data.clone().ok_or(StoreError::EmptyFile)
+        }
+
+        fn exists(&mut self) -> Result<bool, StoreError> {
+            Ok(self.data.is_some())
+        }
+
+        fn delete(&mut self) -> Result<(), StoreError> {
+            self.data = None;
+            Ok(())
+        }
+
+        fn namespace(&mut self, _name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            Ok(Box::new(MemoryEngine::new()))
+        }
+    }
+}
@@ -0,0 +1,165 @@
+pub mod persistence {
+
+    use crate::{
+        chain::{chain::chain::Chain, state::state::STATE_NAMESPACE},
+        clock::clock::clock,
+        store::store::store::{Store, StoreError},
+    };
+
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, MutexGuard, PoisonError,
+        },
+        time::Duration,
+    };
+
+    use tokio::{sync::watch, task::JoinHandle};
+    use tracing::{debug, warn};
+
+    /// Seconds between two saves by default.
+    pub const PERSIST_INTERVAL: u64 = 30;
+
+    /// A store shared by a node and the `PersistenceService` saving its chain.
+    pub type SharedStore = Arc<Mutex<Store>>;
+
+    /// Locks a shared store, taking it over from a thread that panicked while holding it,
+    /// as a node saves what it can on its way down.
+    pub fn lock(store: &SharedStore) -> MutexGuard<'_, Store> {
+        store.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Saves `chain` to `store`, appending the blocks the store lacks if it holds a prefix
+    /// of the chain and saving the whole chain otherwise, along with a snapshot of its
+    /// state to the `STATE_NAMESPACE` namespace if the store prunes the chain, which can
+    /// then no longer rebuild it.
+    pub fn save_chain(store: &mut Store, chain: &Chain) -> Result<(), StoreError> {
+        let tip = store.tip_height().ok().flatten().and_then(|height| store.load_block(height).ok().flatten());
+        let appended = match tip.filter(|tip| chain.get_block(tip.index).is_some_and(|block| block.hash == tip.hash)) {
+            Some(tip) => chain.blocks_from(tip.index + 1).try_for_each(|block| store.append_block(block)),
+            None => Err(StoreError::EmptyFile),
+        };
+        if let Err(e) = appended {
+            debug!("Could not append the missing blocks, saving the whole chain: {}", e);
+            store.save(chain)?;
+        }
+        if store.retention().is_some() && chain.state().is_complete() {
+            store.namespace(STATE_NAMESPACE)?.save(&chain.snapshot())?;
+        }
+        Ok(())
+    }
+
+    /// Counters describing the saves of a chain, shared with whoever wants to export them.
+    #[derive(Default)]
+    pub struct PersistenceStats {
+        saves: AtomicU64,
+        failures: AtomicU64,
+        last_save: AtomicU64,
+        failing: AtomicBool,
+    }
+
+    impl PersistenceStats {
+        /// Counts the outcome of a save.
+        pub fn record(&self, saved: &Result<(), StoreError>) {
+            self.failing.store(saved.is_err(), Ordering::Relaxed);
+            match saved {
+                Ok(()) => {
+                    self.saves.fetch_add(1, Ordering::Relaxed);
+                    self.last_save.store(clock::now(), Ordering::Relaxed);
+                },
+                Err(_) => {
+                    self.failures.fetch_add(1, Ordering::Relaxed);
+                },
+            }
+        }
+
+        /// Successful saves so far.
+        pub fn saves(&self) -> u64 {
+            self.saves.load(Ordering::Relaxed)
+        }
+
+        /// Failed saves so far.
+        pub fn failures(&self) -> u64 {
+            self.failures.load(Ordering::Relaxed)
+        }
+
+        /// Unix timestamp (in seconds) of the last successful save, `None` before the first one.
+        pub fn last_save(&self) -> Option<u64> {
+            Some(self.last_save.load(Ordering::Relaxed)).filter(|secs| *secs > 0)
+        }
+
+        /// Whether the last save failed.
+        pub fn is_failing(&self) -> bool {
+            self.failing.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Saves the chain published on a `watch` channel, at most once per interval.
+    ///
+    /// Updates arriving while the service waits are folded into a single save of the latest
+    /// chain. Dropping the sender shuts the service down, after it saved any update still
+    /// pending.
+    pub struct PersistenceService {
+        store: SharedStore,
+        updates: watch::Receiver<Chain>,
+        interval: Duration,
+        stats: Arc<PersistenceStats>,
+    }
+
+    impl PersistenceService {
+        /// # Arguments
+        /// * `store` - Where the chain is saved.
+        /// * `updates` - The channel the chain is published on. The value it holds when the
+        ///   service starts is taken as already saved.
+        /// * `interval` - Shortest time between two saves.
+        /// * `stats` - Where the saves are counted.
+        pub fn new(store: SharedStore, mut updates: watch::Receiver<Chain>, interval: Duration, stats: Arc<PersistenceStats>) -> Self {
+            updates.mark_unchanged();
+            PersistenceService {
+                store,
+                updates,
+                interval,
+                stats,
+            }
+        }
+
+        /// Runs the service on its own task.
+        ///
+        /// # Returns
+        /// * `JoinHandle<()>` - Resolves once the sender is dropped and the last update saved.
+        pub fn spawn(self) -> JoinHandle<()> {
+            tokio::spawn(self.run())
+        }
+
+        /// Saves updates until the sender is dropped.
+        pub async fn run(mut self) {
+            let mut next_save = clock::now_millis();
+            while self.updates.changed().await.is_ok() {
+                let closed = tokio::select! {
+                    _ = clock::sleep(clock::until(next_save)) => false,
+                    _ = wait_closed(&mut self.updates) => true,
+                };
+                self.save();
+                next_save = clock::now_millis() + self.interval.as_millis() as u64;
+                if closed {
+                    break;
+                }
+            }
+            debug!("Persistence service stopped after {} saves.", self.stats.saves());
+        }
+
+        fn save(&mut self) {
+            let chain = self.updates.borrow_and_update().clone();
+            let saved = save_chain(&mut lock(&self.store), &chain);
+            if let Err(e) = &saved {
+                warn!("Failed to save the chain: {}", e);
+            }
+            self.stats.record(&saved);
+        }
+    }
+
+    /// Returns once the sender is dropped, letting updates pile up in the meantime.
+    async fn wait_closed(updates: &mut watch::Receiver<Chain>) {
+        while updates.changed().await.is_ok() {}
+    }
+}
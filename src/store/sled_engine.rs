@@ -0,0 +1,206 @@
+pub mod sled_engine {
+
+    use crate::{
+        chain::block::block::block::Block,
+        store::{
+            engine::engine::Engine,
+            store::store::StoreError,
+        },
+    };
+
+    use std::{
+        ops::Range,
+        path::Path,
+    };
+
+    /// Key the `Engine` payload is kept under, in the default tree or the namespace's tree.
+    const PAYLOAD_KEY: &[u8] = b"payload";
+    /// Tree mapping the big endian height of a block to the block.
+    const BLOCKS_TREE: &str = "blocks";
+    /// Tree mapping the hash of a block to its big endian height.
+    const HASHES_TREE: &str = "hashes";
+
+    /// Engine backed by an embedded sled database.
+    ///
+    /// Blocks are kept apart from the `Engine` payload, one by one under their height and
+    /// hash, so a single block or a range of blocks is loaded without reading the whole chain.
+    /// Namespaces get their own trees in the same database.
+    pub struct SledEngine {
+        db: sled::Db,
+        payload: sled::Tree,
+        blocks: sled::Tree,
+        hashes: sled::Tree,
+    }
+
+    impl SledEngine {
+        /// Opens the database at `path`, creating it if it does not exist.
+        ///
+        /// # Returns
+        /// * `Result<SledEngine, StoreError>` - The engine, or `StoreError::OpenError` if the
+        ///   database cannot be opened, e.g. because another process holds it.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            let open_error = || StoreError::OpenError(path.as_ref().to_path_buf());
+            let db = sled::open(path.as_ref()).map_err(|_| open_error())?;
+            let payload = sled::Tree::clone(&db);
+            let blocks = db.open_tree(BLOCKS_TREE).map_err(|_| open_error())?;
+            let hashes = db.open_tree(HASHES_TREE).map_err(|_| open_error())?;
+            Ok(SledEngine {
+                db,
+                payload,
+                blocks,
+                hashes,
+            })
+        }
+
+        /// Returns the block with hash `hash`, `None` if no such block is stored.
+        pub fn load_block_by_hash(&self, hash: &str) -> Result<Option<Block>, StoreError> {
+            match self.hashes.get(hash.as_bytes()).map_err(|_| StoreError::LoadError)? {
+                Some(height) => {
+                    let height = height.as_ref().try_into().map_err(|_| StoreError::LoadError)?;
+                    self.block_at(u64::from_be_bytes(height))
+                },
+                None => Ok(None),
+            }
+        }
+
+        fn block_at(&self, height: u64) -> Result<Option<Block>, StoreError> {
+            self.blocks
+                .get(height.to_be_bytes())
+                .map_err(|_| StoreError::LoadError)?
+                .map(|data| serde_json::from_slice(&data).map_err(|_| StoreError::LoadError))
+                .transpose()
+        }
+
+        fn tip(&self) -> Result<Option<Block>, StoreError> {
+            self.blocks
+                .last()
+                .map_err(|_| StoreError::LoadError)?
+                .map(|(_, data)| serde_json::from_slice(&data).map_err(|_| StoreError::LoadError))
+                .transpose()
+        }
+
+        /// Number of blocks stored.
+        pub fn block_count(&self) -> usize {
+            self.blocks.len()
+        }
+    }
+
+    impl Engine for SledEngine {
+        fn store(&mut self, data: &str) -> Result<(), StoreError> {
+            self.payload.insert(PAYLOAD_KEY, data.as_bytes()).map_err(|_| StoreError::StorageError)?;
+            self.db.flush().map_err(|_| StoreError::StorageError)?;
+            Ok(())
+        }
+
+        fn load(&mut self) -> Result<String, StoreError> {
+            let data = self.payload.get(PAYLOAD_KEY).map_err(|_| StoreError::LoadError)?;
+            match data {
+                Some(data) if !data.is_empty() => {
+                    String::from_utf8(data.to_vec()).map_err(|_| StoreError::LoadError)
+                },
+                _ => Err(StoreError::EmptyFile),
+            }
+        }
+
+        fn exists(&mut self) -> Result<bool, StoreError> {
+            self.payload.contains_key(PAYLOAD_KEY).map_err(|_| StoreError::LoadError)
+        }
+
+        /// Drops the payload along with every stored block.
+        fn delete(&mut self) -> Result<(), StoreError> {
+            self.payload.remove(PAYLOAD_KEY).map_err(|_| StoreError::StorageError)?;
+            self.blocks.clear().map_err(|_| StoreError::StorageError)?;
+            self.hashes.clear().map_err(|_| StoreError::StorageError)?;
+            self.db.flush().map_err(|_| StoreError::StorageError)?;
+            Ok(())
+        }
+
+        fn namespace(&mut self, name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            let tree = |suffix: &str| {
+                self.db.open_tree(format!("{}/{}", name, suffix)).map_err(|_| StoreError::StorageError)
+            };
+            Ok(Box::new(SledEngine {
+                db: self.db.clone(),
+                payload: tree("payload")?,
+                blocks: tree(BLOCKS_TREE)?,
+                hashes: tree(HASHES_TREE)?,
+            }))
+        }
+
+        fn append_block(&mut self, block: &Block) -> Result<(), StoreError> {
+            if let Some(tip) = self.tip()? {
+                if block.index <= tip.index || block.previous_hash != tip.hash {
+                    return Err(StoreError::InvalidBlock(block.index));
+                }
+            }
+            let height = (block.index as u64).to_be_bytes();
+            let data = serde_json::to_vec(block).map_err(|_| StoreError::StorageError)?;
+            self.blocks.insert(height, data).map_err(|_| StoreError::StorageError)?;
+            self.hashes.insert(block.hash.as_bytes(), &height).map_err(|_| StoreError::StorageError)?;
+            self.db.flush().map_err(|_| StoreError::StorageError)?;
+            Ok(())
+        }
+
+        fn load_block(&mut self, height: usize) -> Result<Option<Block>, StoreError> {
+            self.block_at(height as u64)
+        }
+
+        fn load_range(&mut self, range: Range<usize>) -> Result<Vec<Block>, StoreError> {
+            let range = (range.start as u64).to_be_bytes()..(range.end as u64).to_be_bytes();
+            self.blocks
+                .range(range)
+                .values()
+                .map(|data| {
+                    let data = data.map_err(|_| StoreError::LoadError)?;
+                    serde_json::from_slice(&data).map_err(|_| StoreError::LoadError)
+                })
+                .collect()
+        }
+
+        fn tip_height(&mut self) -> Result<Option<usize>, StoreError> {
+            Ok(self.tip()?.map(|block| block.index))
+        }
+
+        fn prune(&mut self, keep_full: usize) -> Result<usize, StoreError> {
+            let end = self.blocks.len().saturating_sub(keep_full.max(1));
+            let mut pruned = 0;
+            for entry in self.blocks.iter().take(end) {
+                let (key, data) = entry.map_err(|_| StoreError::LoadError)?;
+                let mut block: Block = serde_json::from_slice(&data).map_err(StoreError::corrupted)?;
+                if block.prune() {
+                    let data = serde_json::to_vec(&block).map_err(|_| StoreError::StorageError)?;
+                    self.blocks.insert(key, data).map_err(|_| StoreError::StorageError)?;
+                    pruned += 1;
+                }
+            }
+            self.db.flush().map_err(|_| StoreError::StorageError)?;
+            Ok(pruned)
+        }
+
+        /// Rebuilds the lookup of blocks by hash from the stored blocks.
+        fn reindex(&mut self) -> Result<usize, StoreError> {
+            self.hashes.clear().map_err(|_| StoreError::StorageError)?;
+            let mut reindexed = 0;
+            for entry in self.blocks.iter() {
+                let (height, data) = entry.map_err(|_| StoreError::LoadError)?;
+                let block: Block = serde_json::from_slice(&data).map_err(StoreError::corrupted)?;
+                self.hashes.insert(block.hash.as_bytes(), height).map_err(|_| StoreError::StorageError)?;
+                reindexed += 1;
+            }
+            self.db.flush().map_err(|_| StoreError::StorageError)?;
+            Ok(reindexed)
+        }
+
+        fn truncate_to(&mut self, height: usize) -> Result<(), StoreError> {
+            let above = (height as u64 + 1).to_be_bytes();
+            for entry in self.blocks.range(above..) {
+                let (key, data) = entry.map_err(|_| StoreError::StorageError)?;
+                let block: Block = serde_json::from_slice(&data).map_err(|_| StoreError::StorageError)?;
+                self.hashes.remove(block.hash.as_bytes()).map_err(|_| StoreError::StorageError)?;
+                self.blocks.remove(key).map_err(|_| StoreError::StorageError)?;
+            }
+            self.db.flush().map_err(|_| StoreError::StorageError)?;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,168 @@
+pub mod snapshot {
+
+    use crate::{
+        chain::chain::chain::{BlockCheckError, Chain},
+        store::store::store::StoreError,
+    };
+
+    use std::{
+        fs,
+        io::Error as IOError,
+        path::Path,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use thiserror::Error;
+
+    /// Version of the snapshots written by `Snapshot::new`. Bump it along with a migration
+    /// in `MIGRATIONS` whenever the format changes.
+    pub const SNAPSHOT_VERSION: u32 = 1;
+
+    /// Upgrades a snapshot from one version to the next.
+    type Migration = fn(Value) -> Result<Value, SnapshotError>;
+
+    /// Migrations by the version they upgrade from. Importing a snapshot runs every migration
+    /// from its version up to `SNAPSHOT_VERSION`.
+    const MIGRATIONS: &[(u32, Migration)] = &[
+        (0, from_bare_payload),
+    ];
+
+    #[derive(Error, Debug, derive_more::From)]
+    pub enum SnapshotError {
+        #[error("Snapshot version {0} is newer than the supported version {SNAPSHOT_VERSION}.")]
+        #[from(ignore)]
+        UnsupportedVersion(u32),
+        #[error("No migration from snapshot version {0}.")]
+        #[from(ignore)]
+        MissingMigration(u32),
+        #[error("Snapshot describes a chain of height {expected:?} starting at {expected_genesis:?}, but holds one of height {got:?} starting at {got_genesis:?}.")]
+        #[from(ignore)]
+        Mismatch {
+            expected: Option<usize>,
+            expected_genesis: Option<String>,
+            got: Option<usize>,
+            got_genesis: Option<String>,
+        },
+        #[error("Snapshot holds an invalid chain: {0}")]
+        InvalidChain(BlockCheckError),
+        #[error(transparent)]
+        StoreError(StoreError),
+        #[error(transparent)]
+        IOError(IOError),
+        #[error(transparent)]
+        SerdeError(serde_json::Error),
+    }
+
+    /// A store's payload along with what is needed to check it on import.
+    ///
+    /// `genesis_hash` and `height` are only set when the payload is a chain.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct Snapshot {
+        pub version: u32,
+        pub genesis_hash: Option<String>,
+        pub height: Option<usize>,
+        /// When the snapshot was taken, in seconds since the UNIX epoch.
+        pub created: u64,
+        pub payload: String,
+    }
+
+    impl Snapshot {
+        /// Describes `payload` in a snapshot of the current version.
+        pub fn new(payload: String) -> Self {
+            let (genesis_hash, height) = describe(&payload);
+            Snapshot {
+                version: SNAPSHOT_VERSION,
+                genesis_hash,
+                height,
+                created: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+                payload,
+            }
+        }
+
+        /// Writes the snapshot to the file at `path`.
+        pub fn write(&self, path: &Path) -> Result<(), SnapshotError> {
+            fs::write(path, serde_json::to_vec_pretty(self)?)?;
+            Ok(())
+        }
+
+        /// Reads the snapshot in the file at `path`, migrating it to the current version and
+        /// checking the payload matches its description.
+        pub fn read(path: &Path) -> Result<Self, SnapshotError> {
+            let value: Value = serde_json::from_slice(&fs::read(path)?)?;
+            let snapshot: Snapshot = serde_json::from_value(migrate(value)?)?;
+            snapshot.check()?;
+            Ok(snapshot)
+        }
+
+        fn check(&self) -> Result<(), SnapshotError> {
+            if self.genesis_hash.is_some() || self.height.is_some() {
+                let chain: Chain = serde_json::from_str(&self.payload)?;
+                chain.verify_chain()?;
+            }
+            let (got_genesis, got) = describe(&self.payload);
+            if got_genesis != self.genesis_hash || got != self.height {
+                return Err(SnapshotError::Mismatch {
+                    expected: self.height,
+                    expected_genesis: self.genesis_hash.clone(),
+                    got,
+                    got_genesis,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    /// Genesis hash and height of `payload`, if it is a chain.
+    fn describe(payload: &str) -> (Option<String>, Option<usize>) {
+        match serde_json::from_str::<Chain>(payload) {
+            Ok(chain) => {
+                let genesis = chain.blocks().first().map(|block| block.hash.clone());
+                (genesis, Some(chain.get_last_block().index))
+            },
+            Err(_) => (None, None),
+        }
+    }
+
+    /// Runs the migrations needed to bring `value` to `SNAPSHOT_VERSION`. A value without a
+    /// version is taken for version 0.
+    fn migrate(mut value: Value) -> Result<Value, SnapshotError> {
+        let mut version = snapshot_version(&value);
+        if version > SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        while version < SNAPSHOT_VERSION {
+            let (_, migration) = MIGRATIONS
+                .iter()
+                .find(|(from, _)| *from == version)
+                .ok_or(SnapshotError::MissingMigration(version))?;
+            value = migration(value)?;
+            version = snapshot_version(&value);
+        }
+        Ok(value)
+    }
+
+    fn snapshot_version(value: &Value) -> u32 {
+        value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+    }
+
+    /// Version 0 is a payload saved as is, e.g. a chain file written before snapshots existed.
+    fn from_bare_payload(value: Value) -> Result<Value, SnapshotError> {
+        let payload = match value {
+            Value::String(payload) => payload,
+            value => serde_json::to_string(&value)?,
+        };
+        let (genesis_hash, height) = describe(&payload);
+        Ok(json!({
+            "version": 1,
+            "genesis_hash": genesis_hash,
+            "height": height,
+            "created": 0,
+            "payload": payload,
+        }))
+    }
+}
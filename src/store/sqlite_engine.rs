@@ -0,0 +1,358 @@
+pub mod sqlite_engine {
+
+    use crate::{
+        chain::{block::block::block::Block, chain::chain::Chain},
+        record::record::record::Record,
+        transaction::transaction::transaction::Transaction,
+        store::{
+            engine::engine::Engine,
+            store::store::StoreError,
+        },
+    };
+
+    use std::{
+        path::{Path, PathBuf},
+        sync::Mutex,
+    };
+
+    use rusqlite::{params, Connection, OptionalExtension, Transaction as SqlTransaction};
+
+    const SCHEMA: &str = "
+        CREATE TABLE IF NOT EXISTS payload (
+            id INTEGER PRIMARY KEY CHECK (id = 0),
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS blocks (
+            height INTEGER PRIMARY KEY,
+            hash TEXT NOT NULL UNIQUE,
+            previous_hash TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            nonce INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS transactions (
+            id TEXT NOT NULL,
+            height INTEGER NOT NULL REFERENCES blocks(height) ON DELETE CASCADE,
+            sender BLOB NOT NULL,
+            receiver BLOB NOT NULL,
+            timestamp INTEGER NOT NULL,
+            wire TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS transactions_sender ON transactions(sender);
+        CREATE INDEX IF NOT EXISTS transactions_receiver ON transactions(receiver);
+        CREATE TABLE IF NOT EXISTS records (
+            id TEXT NOT NULL,
+            height INTEGER NOT NULL REFERENCES blocks(height) ON DELETE CASCADE,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            owner BLOB NOT NULL,
+            timestamp INTEGER NOT NULL,
+            signature BLOB
+        );
+        CREATE INDEX IF NOT EXISTS records_key ON records(key);
+    ";
+
+    /// Engine backed by an SQLite database.
+    ///
+    /// The payload is kept as is, so the engine can back any store. When the payload is a
+    /// chain, its blocks, transactions and records are also written to their own tables,
+    /// which can be queried directly or through `transactions_by_key`, `records_by_prefix` and
+    /// their paginated counterparts.
+    pub struct SqliteEngine {
+        path: PathBuf,
+        connection: Mutex<Connection>,
+    }
+
+    impl SqliteEngine {
+        /// Opens the database at `path`, creating it and its tables if they do not exist.
+        ///
+        /// # Returns
+        /// * `Result<SqliteEngine, StoreError>` - The engine, or `StoreError::OpenError` if the
+        ///   database cannot be opened.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            let open_error = || StoreError::OpenError(path.as_ref().to_path_buf());
+            let connection = Connection::open(path.as_ref()).map_err(|_| open_error())?;
+            connection.execute_batch("PRAGMA foreign_keys = ON;").map_err(|_| open_error())?;
+            connection.execute_batch(SCHEMA).map_err(|_| open_error())?;
+            Ok(SqliteEngine {
+                path: path.as_ref().to_path_buf(),
+                connection: Mutex::new(connection),
+            })
+        }
+
+        /// Returns the transactions sent or received by the public key `key`, oldest block first.
+        pub fn transactions_by_key(&self, key: &[u8]) -> Result<Vec<Transaction>, StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection
+                .prepare("SELECT wire FROM transactions WHERE sender = ?1 OR receiver = ?1 ORDER BY height, rowid")
+                .map_err(|_| StoreError::LoadError)?;
+            let wires = statement
+                .query_map(params![key], |row| row.get::<_, String>(0))
+                .map_err(|_| StoreError::LoadError)?;
+            wires
+                .map(|wire| {
+                    let wire = wire.map_err(|_| StoreError::LoadError)?;
+                    Transaction::try_from(wire).map_err(|_| StoreError::LoadError)
+                })
+                .collect()
+        }
+
+        /// Returns the records whose key starts with `prefix`, oldest block first.
+        pub fn records_by_prefix(&self, prefix: &str) -> Result<Vec<Record>, StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection
+                .prepare("SELECT key, value, owner, timestamp, signature FROM records \
+                          WHERE substr(key, 1, length(?1)) = ?1 ORDER BY height, rowid")
+                .map_err(|_| StoreError::LoadError)?;
+            let records = statement
+                .query_map(params![prefix], |row| {
+                    Ok(Record {
+                        key: row.get(0)?,
+                        value: row.get(1)?,
+                        owner: row.get(2)?,
+                        timestamp: row.get(3)?,
+                        signature: row.get(4)?,
+                    })
+                })
+                .map_err(|_| StoreError::LoadError)?;
+            records.map(|record| record.map_err(|_| StoreError::LoadError)).collect()
+        }
+
+        /// Returns a page of the transactions sent or received by the public key `key`,
+        /// newest block first, each with the height of its block.
+        ///
+        /// # Returns
+        /// * `Result<(Vec<(Transaction, usize)>, usize), StoreError>` - The page and the number
+        ///   of transactions across every page.
+        pub fn transactions_page(&self, key: &[u8], offset: usize, limit: usize) -> Result<(Vec<(Transaction, usize)>, usize), StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let total: i64 = connection
+                .query_row("SELECT COUNT(*) FROM transactions WHERE sender = ?1 OR receiver = ?1", params![key], |row| row.get(0))
+                .map_err(|_| StoreError::LoadError)?;
+            let mut statement = connection
+                .prepare("SELECT wire, height FROM transactions WHERE sender = ?1 OR receiver = ?1 \
+                          ORDER BY height DESC, rowid DESC LIMIT ?2 OFFSET ?3")
+                .map_err(|_| StoreError::LoadError)?;
+            let rows = statement
+                .query_map(params![key, limit as i64, offset as i64], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                })
+                .map_err(|_| StoreError::LoadError)?;
+            let transactions = rows
+                .map(|row| {
+                    let (wire, height) = row.map_err(|_| StoreError::LoadError)?;
+                    let transaction = Transaction::try_from(wire).map_err(|_| StoreError::LoadError)?;
+                    Ok((transaction, height as usize))
+                })
+                .collect::<Result<_, StoreError>>()?;
+            Ok((transactions, total as usize))
+        }
+
+        /// Returns a page of the records written under `key`, newest block first, each with
+        /// the height of its block.
+        ///
+        /// # Returns
+        /// * `Result<(Vec<(Record, usize)>, usize), StoreError>` - The page and the number of
+        ///   records across every page.
+        pub fn records_page(&self, key: &str, offset: usize, limit: usize) -> Result<(Vec<(Record, usize)>, usize), StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let total: i64 = connection
+                .query_row("SELECT COUNT(*) FROM records WHERE key = ?1", params![key], |row| row.get(0))
+                .map_err(|_| StoreError::LoadError)?;
+            let mut statement = connection
+                .prepare("SELECT key, value, owner, timestamp, signature, height FROM records WHERE key = ?1 \
+                          ORDER BY height DESC, rowid DESC LIMIT ?2 OFFSET ?3")
+                .map_err(|_| StoreError::LoadError)?;
+            let records = statement
+                .query_map(params![key, limit as i64, offset as i64], |row| {
+                    let record = Record {
+                        key: row.get(0)?,
+                        value: row.get(1)?,
+                        owner: row.get(2)?,
+                        timestamp: row.get(3)?,
+                        signature: row.get(4)?,
+                    };
+                    Ok((record, row.get::<_, i64>(5)? as usize))
+                })
+                .map_err(|_| StoreError::LoadError)?
+                .map(|record| record.map_err(|_| StoreError::LoadError))
+                .collect::<Result<_, StoreError>>()?;
+            Ok((records, total as usize))
+        }
+
+        /// Returns up to `limit` keys, in lexicographic order, whose last record is not a
+        /// deletion, starting with `prefix` and past `cursor`.
+        pub fn live_keys(&self, prefix: &str, limit: usize, cursor: Option<&str>) -> Result<Vec<String>, StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection
+                .prepare("SELECT key FROM records AS last \
+                          WHERE substr(key, 1, length(?1)) = ?1 AND (?2 IS NULL OR key > ?2) AND value != '' \
+                          AND rowid = (SELECT rowid FROM records WHERE key = last.key ORDER BY height DESC, rowid DESC LIMIT 1) \
+                          ORDER BY key LIMIT ?3")
+                .map_err(|_| StoreError::LoadError)?;
+            let keys = statement
+                .query_map(params![prefix, cursor, limit as i64], |row| row.get(0))
+                .map_err(|_| StoreError::LoadError)?
+                .map(|key| key.map_err(|_| StoreError::LoadError))
+                .collect::<Result<_, StoreError>>()?;
+            Ok(keys)
+        }
+
+        /// Returns the height of the block whose hash is `hash`, `None` if no block has it.
+        pub fn block_height(&self, hash: &str) -> Result<Option<usize>, StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let height: Option<i64> = connection
+                .query_row("SELECT height FROM blocks WHERE hash = ?1", params![hash], |row| row.get(0))
+                .optional()
+                .map_err(|_| StoreError::LoadError)?;
+            Ok(height.map(|height| height as usize))
+        }
+
+        /// Returns the number of blocks in the blocks table.
+        pub fn block_count(&self) -> Result<usize, StoreError> {
+            let connection = self.connection.lock().unwrap();
+            let count: i64 = connection
+                .query_row("SELECT COUNT(*) FROM blocks", [], |row| row.get(0))
+                .map_err(|_| StoreError::LoadError)?;
+            Ok(count as usize)
+        }
+
+        /// Writes `blocks`, with their transactions and records, to their tables in place of
+        /// the blocks from the height of the first one on. The payload is left as it is.
+        ///
+        /// # Arguments
+        /// * `blocks` - Consecutive blocks, oldest first.
+        pub fn index_blocks(&mut self, blocks: &[Block]) -> Result<(), StoreError> {
+            let Some(first) = blocks.first() else {
+                return Ok(());
+            };
+            let connection = self.connection.get_mut().unwrap();
+            let write = |connection: &mut Connection| -> rusqlite::Result<()> {
+                let tx = connection.transaction()?;
+                tx.execute("DELETE FROM blocks WHERE height >= ?1", params![first.index as i64])?;
+                for block in blocks {
+                    SqliteEngine::insert_block(&tx, block)?;
+                }
+                tx.commit()
+            };
+            write(connection).map_err(|_| StoreError::StorageError)
+        }
+
+        /// Returns the hash of the highest block, `None` if no chain was stored.
+        pub fn tip_hash(&self) -> Result<Option<String>, StoreError> {
+            let connection = self.connection.lock().unwrap();
+            connection
+                .query_row("SELECT hash FROM blocks ORDER BY height DESC LIMIT 1", [], |row| row.get(0))
+                .optional()
+                .map_err(|_| StoreError::LoadError)
+        }
+
+        fn write(connection: &mut Connection, data: &str) -> rusqlite::Result<()> {
+            let tx = connection.transaction()?;
+            tx.execute("INSERT OR REPLACE INTO payload (id, data) VALUES (0, ?1)", params![data])?;
+            SqliteEngine::index(&tx, data)?;
+            tx.commit()
+        }
+
+        /// Writes the blocks, transactions and records of `data` to their tables, in place
+        /// of the ones there, if it is a chain.
+        fn index(tx: &SqlTransaction, data: &str) -> rusqlite::Result<()> {
+            if let Ok(chain) = serde_json::from_str::<Chain>(data) {
+                tx.execute("DELETE FROM blocks", [])?;
+                for block in chain.blocks() {
+                    SqliteEngine::insert_block(tx, block)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Writes a block and its transactions and records to their tables.
+        fn insert_block(tx: &SqlTransaction, block: &Block) -> rusqlite::Result<()> {
+            tx.execute(
+                "INSERT INTO blocks (height, hash, previous_hash, timestamp, nonce) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![block.index as i64, block.hash, block.previous_hash, block.timestamp, block.nonce as i64],
+            )?;
+            for transaction in block.get_transactions() {
+                let id = transaction.id();
+                let wire: String = transaction.clone().into();
+                tx.execute(
+                    "INSERT INTO transactions (id, height, sender, receiver, timestamp, wire) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![id, block.index as i64, transaction.sender, transaction.receiver, transaction.timestamp, wire],
+                )?;
+            }
+            for record in &block.records {
+                tx.execute(
+                    "INSERT INTO records (id, height, key, value, owner, timestamp, signature) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![record.id(), block.index as i64, record.key, record.value, record.owner, record.timestamp, record.signature],
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    impl Engine for SqliteEngine {
+        fn store(&mut self, data: &str) -> Result<(), StoreError> {
+            let connection = self.connection.get_mut().unwrap();
+            SqliteEngine::write(connection, data).map_err(|_| StoreError::StorageError)
+        }
+
+        fn load(&mut self) -> Result<String, StoreError> {
+            let connection = self.connection.get_mut().unwrap();
+            let data: Option<String> = connection
+                .query_row("SELECT data FROM payload WHERE id = 0", [], |row| row.get(0))
+                .optional()
+                .map_err(|_| StoreError::LoadError)?;
+            data.filter(|data| !data.is_empty()).ok_or(StoreError::EmptyFile)
+        }
+
+        fn exists(&mut self) -> Result<bool, StoreError> {
+            match self.load() {
+                Ok(_) => Ok(true),
+                Err(StoreError::EmptyFile) => Ok(false),
+                Err(e) => Err(e),
+            }
+        }
+
+        fn compact(&mut self) -> Result<(), StoreError> {
+            let connection = self.connection.get_mut().unwrap();
+            connection.execute_batch("VACUUM;").map_err(|_| StoreError::StorageError)
+        }
+
+        /// Namespaces are kept in databases next to this one, named after it and the namespace,
+        /// e.g. `chain.db.ns-identity`, so no namespace takes the write-ahead log of the store.
+        fn namespace(&mut self, name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            let mut path = self.path.clone().into_os_string();
+            path.push(".ns-");
+            path.push(name);
+            Ok(Box::new(SqliteEngine::open(path)?))
+        }
+
+        /// Recreates the blocks, transactions and records tables, dropping whatever they held,
+        /// and fills them from the payload.
+        fn reindex(&mut self) -> Result<usize, StoreError> {
+            let connection = self.connection.get_mut().unwrap();
+            let reindex = |connection: &mut Connection| -> rusqlite::Result<i64> {
+                let tx = connection.transaction()?;
+                tx.execute_batch("DROP TABLE IF EXISTS records; DROP TABLE IF EXISTS transactions; DROP TABLE IF EXISTS blocks;")?;
+                tx.execute_batch(SCHEMA)?;
+                let data: Option<String> = tx
+                    .query_row("SELECT data FROM payload WHERE id = 0", [], |row| row.get(0))
+                    .optional()?;
+                if let Some(data) = data {
+                    SqliteEngine::index(&tx, &data)?;
+                }
+                let reindexed = tx.query_row("SELECT COUNT(*) FROM blocks", [], |row| row.get(0))?;
+                tx.commit()?;
+                Ok(reindexed)
+            };
+            reindex(connection).map(|reindexed| reindexed as usize).map_err(|_| StoreError::StorageError)
+        }
+
+        /// Drops the payload along with every block, transaction and record.
+        fn delete(&mut self) -> Result<(), StoreError> {
+            let connection = self.connection.get_mut().unwrap();
+            connection
+                .execute_batch("DELETE FROM payload; DELETE FROM blocks;")
+                .map_err(|_| StoreError::StorageError)
+        }
+    }
+}
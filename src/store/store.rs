@@ -0,0 +1,341 @@
+pub mod store {
+
+    pub use crate::store::{
+        engine::engine::Engine,
+        file_engine::file_engine::FileEngine,
+        memory_engine::memory_engine::MemoryEngine,
+        encrypted_engine::encrypted_engine::{EncryptedEngine, KEY_LEN},
+        wal::wal::WalEngine,
+        snapshot::snapshot::{Snapshot, SnapshotError},
+        codec::codec::Codec,
+    };
+    #[cfg(feature = "sled")]
+    pub use crate::store::sled_engine::sled_engine::SledEngine;
+    #[cfg(feature = "sqlite")]
+    pub use crate::store::sqlite_engine::sqlite_engine::SqliteEngine;
+
+    use crate::chain::block::block::block::Block;
+
+    use std::{
+        collections::HashMap,
+        error::Error as StdError,
+        io::{Error as IOError, ErrorKind},
+        ops::Range,
+        path::{Path, PathBuf},
+    };
+
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use thiserror::Error;
+
+    #[derive(Error, Debug)]
+    pub enum StoreError {
+        #[error("Failed to store data.")]
+        StorageError,
+        #[error("Failed to load data.")]
+        LoadError,
+        #[error("Nothing has been stored yet.")]
+        EmptyFile,
+        #[error("Failed to open the store at {}.", .0.display())]
+        OpenError(PathBuf),
+        #[error("{} does not exist.", .0.display())]
+        NotFound(PathBuf),
+        #[error("Permission denied on {}: {source}", path.display())]
+        PermissionDenied { path: PathBuf, source: IOError },
+        #[error("No space left to write {}: {source}", path.display())]
+        Full { path: PathBuf, source: IOError },
+        #[error("Stored data is corrupted: {0}")]
+        Corrupted(#[source] Box<dyn StdError + Send + Sync>),
+        #[error("Stored data has format version {found}, while {supported} is the latest supported.")]
+        VersionMismatch { supported: u32, found: u32 },
+        #[error("{} is locked by another writer.", .0.display())]
+        Locked(PathBuf),
+        #[error("{} was opened read-only.", .0.display())]
+        ReadOnly(PathBuf),
+        #[error("I/O error on {}: {source}", path.display())]
+        Io { path: PathBuf, source: IOError },
+        #[error("Block {0} does not extend the stored blocks.")]
+        InvalidBlock(usize),
+        #[error("Invalid key in {} - expected {KEY_LEN} base64 encoded bytes.", .0.display())]
+        InvalidKey(PathBuf),
+        #[error("Failed to decrypt data - wrong key or tampered store.")]
+        DecryptionError,
+        #[error("Failed to encode a value to store.")]
+        CodecError,
+        #[error("Invalid namespace {0:?} - expected letters, digits, '-' or '_'.")]
+        InvalidNamespace(String),
+        #[error("Cannot open namespace {0:?} - the store's engine does not support namespaces.")]
+        NoNamespaces(String),
+        #[error("Block operations cannot read values saved with the {0:?} codec.")]
+        OpaqueCodec(Codec),
+    }
+
+    impl StoreError {
+        /// Classifies an I/O error met while accessing `path`.
+        pub fn io(path: &Path, error: IOError) -> Self {
+            let path = path.to_path_buf();
+            match error.kind() {
+                ErrorKind::NotFound => StoreError::NotFound(path),
+                ErrorKind::PermissionDenied => StoreError::PermissionDenied { path, source: error },
+                ErrorKind::StorageFull | ErrorKind::QuotaExceeded => StoreError::Full { path, source: error },
+                _ => StoreError::Io { path, source: error },
+            }
+        }
+
+        /// Wraps the error met while decoding stored data.
+        pub fn corrupted(error: impl StdError + Send + Sync + 'static) -> Self {
+            StoreError::Corrupted(Box::new(error))
+        }
+
+        /// Whether retrying the operation later may succeed, e.g. once disk space is freed.
+        /// Other errors need someone to step in: fixing permissions, restoring a backup, or
+        /// upgrading to read a newer format.
+        pub fn is_transient(&self) -> bool {
+            matches!(self, StoreError::StorageError | StoreError::LoadError | StoreError::Full { .. } | StoreError::Io { .. })
+        }
+    }
+
+    /// How much of a chain a store keeps whole.
+    #[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+    pub struct Retention {
+        /// Blocks at the top of the chain kept with their transactions and records. Older
+        /// blocks are pruned down to their headers. See `Chain::prune`.
+        pub keep_full: usize,
+        /// Blocks appended or chains saved between two prunings.
+        pub interval: usize,
+    }
+
+    /// Persists values through a storage engine, serializing them with a `Codec`.
+    ///
+    /// Besides its own payload, a store manages named namespaces ("chain", "peers", ...),
+    /// each with a payload and blocks of its own, kept by the same kind of engine.
+    pub struct Store {
+        engine: Box<dyn Engine>,
+        codec: Codec,
+        namespaces: HashMap<String, Store>,
+        retention: Option<Retention>,
+        /// Writes since the last pruning.
+        writes: usize,
+    }
+
+    impl Store {
+        /// Creates a `Store` backed by the file at `path`, creating its directory if needed.
+        /// A relative path is relative to the current working directory.
+        pub fn new(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            Ok(Store::with_engine(Box::new(FileEngine::open(path)?)))
+        }
+
+        /// Creates a `Store` reading the file at `path` without ever writing to it, so it can
+        /// be read while a node writes it.
+        pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+            Ok(Store::with_engine(Box::new(FileEngine::open_read_only(path)?)))
+        }
+
+        /// Creates a `Store` backed by any engine, including ones implemented outside this crate.
+        pub fn with_engine(engine: Box<dyn Engine>) -> Self {
+            Store {
+                engine,
+                codec: Codec::default(),
+                namespaces: HashMap::new(),
+                retention: None,
+                writes: 0,
+            }
+        }
+
+        /// Creates a `Store` kept in memory, which writes nothing to disk.
+        pub fn in_memory() -> Self {
+            Store::with_engine(Box::new(MemoryEngine::new()))
+        }
+
+        /// Encrypts everything stored from now on with `key`. See `EncryptedEngine`.
+        pub fn encrypted(self, key: &[u8; KEY_LEN]) -> Self {
+            Store {
+                engine: Box::new(EncryptedEngine::new(self.engine, key)),
+                namespaces: HashMap::new(),
+                ..self
+            }
+        }
+
+        /// Logs appended blocks to a write-ahead log at `path` and merges them into this store
+        /// every `checkpoint_interval` blocks. See `WalEngine`.
+        pub fn with_wal(self, path: impl AsRef<Path>, checkpoint_interval: usize) -> Result<Self, StoreError> {
+            self.readable_blocks()?;
+            Ok(Store {
+                engine: Box::new(WalEngine::open(self.engine, path, checkpoint_interval)?),
+                namespaces: HashMap::new(),
+                ..self
+            })
+        }
+
+        /// Serializes the values saved from now on with `codec`. Only `Codec::Json` leaves the
+        /// chain readable to block operations, write-ahead logs, retention and snapshots.
+        pub fn with_codec(mut self, codec: Codec) -> Self {
+            self.codec = codec;
+            self
+        }
+
+        /// Prunes the chain held by the store following `retention`, as it gets written.
+        pub fn with_retention(mut self, retention: Retention) -> Self {
+            self.retention = Some(retention);
+            self
+        }
+
+        /// Returns how much of the chain the store keeps whole, `None` if it keeps all of it.
+        pub fn retention(&self) -> Option<Retention> {
+            self.retention
+        }
+
+        /// Prunes the chain held by the store following its retention and reclaims the space
+        /// freed. Does nothing without a retention.
+        ///
+        /// # Returns
+        /// The number of blocks pruned.
+        pub fn enforce_retention(&mut self) -> Result<usize, StoreError> {
+            self.writes = 0;
+            let Some(retention) = self.retention else {
+                return Ok(0);
+            };
+            self.readable_blocks()?;
+            let pruned = self.engine.prune(retention.keep_full)?;
+            if pruned > 0 {
+                self.engine.compact()?;
+            }
+            Ok(pruned)
+        }
+
+        /// Fails unless the store's codec leaves the chain it holds readable to the engine,
+        /// which reads and writes blocks as JSON.
+        fn readable_blocks(&self) -> Result<(), StoreError> {
+            match self.codec {
+                Codec::Json => Ok(()),
+                codec => Err(StoreError::OpaqueCodec(codec)),
+            }
+        }
+
+        /// Counts a write, enforcing the retention once every `Retention::interval` writes.
+        fn wrote(&mut self) -> Result<(), StoreError> {
+            self.writes += 1;
+            if self.retention.is_some_and(|retention| self.writes >= retention.interval) {
+                self.enforce_retention()?;
+            }
+            Ok(())
+        }
+
+        /// Returns the namespace `name`, opening it the first time. It uses the store's codec.
+        ///
+        /// # Arguments
+        /// * `name` - Letters, digits, '-' and '_' only, since engines may use it in file names.
+        pub fn namespace(&mut self, name: &str) -> Result<&mut Store, StoreError> {
+            let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(StoreError::InvalidNamespace(name.to_string()));
+            }
+            if !self.namespaces.contains_key(name) {
+                let engine = self.engine.namespace(name)?;
+                let store = Store::with_engine(engine).with_codec(self.codec);
+                self.namespaces.insert(name.to_string(), store);
+            }
+            Ok(self.namespaces.get_mut(name).unwrap())
+        }
+
+        /// Replaces the stored payload with `value`, serialized with the store's codec.
+        pub fn save<T: Serialize>(&mut self, value: &T) -> Result<(), StoreError> {
+            let data = self.codec.encode(value)?;
+            self.engine.store(&data)?;
+            self.wrote()
+        }
+
+        /// Returns the last value saved, deserialized with the store's codec.
+        pub fn load<T: DeserializeOwned>(&mut self) -> Result<T, StoreError> {
+            let data = self.engine.load()?;
+            self.codec.decode(&data)
+        }
+
+        /// Replaces the stored payload with `data`, as is.
+        pub fn store_raw(&mut self, data: &str) -> Result<(), StoreError> {
+            self.engine.store(data)
+        }
+
+        /// Returns the last stored payload, as is.
+        pub fn load_raw(&mut self) -> Result<String, StoreError> {
+            self.engine.load()
+        }
+
+        /// Whether a payload is stored.
+        pub fn exists(&mut self) -> Result<bool, StoreError> {
+            self.engine.exists()
+        }
+
+        /// Drops the stored payload.
+        pub fn delete(&mut self) -> Result<(), StoreError> {
+            self.engine.delete()
+        }
+
+        /// Flushes the engine and every namespace opened, e.g. before the process exits.
+        pub fn flush(&mut self) -> Result<(), StoreError> {
+            self.engine.flush()?;
+            for namespace in self.namespaces.values_mut() {
+                namespace.flush()?;
+            }
+            Ok(())
+        }
+
+        /// Writes the stored payload to the file at `path`, as a versioned snapshot recording
+        /// the genesis hash and height of the chain it holds.
+        pub fn export_snapshot(&mut self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+            self.readable_blocks()?;
+            Snapshot::new(self.load_raw()?).write(path.as_ref())
+        }
+
+        /// Replaces the stored payload with the one in the snapshot at `path`. Snapshots of
+        /// older versions are migrated, and the chain they hold is verified.
+        ///
+        /// # Returns
+        /// * `Result<Snapshot, SnapshotError>` - The imported snapshot.
+        pub fn import_snapshot(&mut self, path: impl AsRef<Path>) -> Result<Snapshot, SnapshotError> {
+            self.readable_blocks()?;
+            let snapshot = Snapshot::read(path.as_ref())?;
+            self.store_raw(&snapshot.payload)?;
+            Ok(snapshot)
+        }
+
+        /// Adds `block` on top of the stored blocks.
+        pub fn append_block(&mut self, block: &Block) -> Result<(), StoreError> {
+            self.readable_blocks()?;
+            self.engine.append_block(block)?;
+            self.wrote()
+        }
+
+        /// Returns the block at `height`, `None` if no block is stored there.
+        pub fn load_block(&mut self, height: usize) -> Result<Option<Block>, StoreError> {
+            self.readable_blocks()?;
+            self.engine.load_block(height)
+        }
+
+        /// Returns the blocks stored at heights within `range`, lowest first.
+        pub fn load_range(&mut self, range: Range<usize>) -> Result<Vec<Block>, StoreError> {
+            self.readable_blocks()?;
+            self.engine.load_range(range)
+        }
+
+        /// Height of the highest block stored, `None` if there is none.
+        pub fn tip_height(&mut self) -> Result<Option<usize>, StoreError> {
+            self.readable_blocks()?;
+            self.engine.tip_height()
+        }
+
+        /// Rebuilds what the engine derives from the stored blocks. See `Engine::reindex`.
+        ///
+        /// # Returns
+        /// The number of blocks reindexed.
+        pub fn reindex(&mut self) -> Result<usize, StoreError> {
+            self.readable_blocks()?;
+            self.engine.reindex()
+        }
+
+        /// Drops every block above `height`.
+        pub fn truncate_to(&mut self, height: usize) -> Result<(), StoreError> {
+            self.readable_blocks()?;
+            self.engine.truncate_to(height)
+        }
+    }
+}
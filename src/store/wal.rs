@@ -0,0 +1,210 @@
+pub mod wal {
+
+    use crate::{
+        chain::{
+            block::block::block::Block,
+            chain::chain::Chain,
+        },
+        store::{
+            engine::engine::Engine,
+            store::store::StoreError,
+        },
+    };
+
+    use std::{
+        fs::{self, File, OpenOptions},
+        io::{ErrorKind, Write},
+        path::{Path, PathBuf},
+    };
+
+    /// Blocks appended between two checkpoints by default.
+    pub const DEFAULT_CHECKPOINT_INTERVAL: usize = 64;
+
+    /// Decorator logging appended blocks to a write-ahead log instead of rewriting the
+    /// whole chain held by another engine.
+    ///
+    /// Each block is written to the log as a line of JSON and synced before `append_block`
+    /// returns. Every `checkpoint_interval` blocks, the logged blocks are merged into the
+    /// snapshot kept by the wrapped engine and the log is emptied. A log left over by a
+    /// crash is replayed when the engine is opened; a last line torn by the crash is dropped.
+    pub struct WalEngine {
+        inner: Box<dyn Engine>,
+        path: PathBuf,
+        log: File,
+        pending: Vec<Block>,
+        /// The highest block, logged or in the snapshot.
+        tip: Option<Block>,
+        checkpoint_interval: usize,
+    }
+
+    impl WalEngine {
+        /// Opens the log at `path`, creating it if it does not exist, and checkpoints the
+        /// blocks it holds.
+        ///
+        /// # Arguments
+        /// * `inner` - The engine holding the snapshot.
+        /// * `path` - Where the log is kept.
+        /// * `checkpoint_interval` - Blocks logged before they are merged into the snapshot.
+        ///
+        /// # Returns
+        /// * `Result<WalEngine, StoreError>` - The engine, or an error if the log cannot be
+        ///   opened or replayed.
+        pub fn open(inner: Box<dyn Engine>, path: impl AsRef<Path>, checkpoint_interval: usize) -> Result<Self, StoreError> {
+            let path = path.as_ref();
+            let pending = replay(path)?;
+            let log = OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)
+                .map_err(|e| StoreError::io(path, e))?;
+            let mut engine = WalEngine {
+                inner,
+                path: path.to_path_buf(),
+                log,
+                pending,
+                tip: None,
+                checkpoint_interval: checkpoint_interval.max(1),
+            };
+            // Also empties a log holding nothing but a torn line, which would hide the blocks appended after it.
+            engine.checkpoint()?;
+            engine.clear_log()?;
+            engine.tip = engine.inner_tip()?;
+            Ok(engine)
+        }
+
+        /// Merges the logged blocks into the snapshot and empties the log.
+        pub fn checkpoint(&mut self) -> Result<(), StoreError> {
+            if self.pending.is_empty() {
+                return Ok(());
+            }
+            let data = self.merged()?;
+            self.inner.store(&data)?;
+            self.clear_log()
+        }
+
+        /// The snapshot with the logged blocks on top of it, serialized.
+        fn merged(&mut self) -> Result<String, StoreError> {
+            let (mut blocks, difficulty) = match self.inner.load() {
+                Ok(data) => {
+                    let chain: Chain = serde_json::from_str(&data).map_err(StoreError::corrupted)?;
+                    (chain.get_blocks(), chain.difficulty)
+                },
+                Err(StoreError::EmptyFile) => (vec![], 1),
+                Err(e) => return Err(e),
+            };
+            let tip = blocks.last().map(|block| block.index);
+            blocks.extend(self.pending.iter().filter(|block| tip.is_none_or(|tip| block.index > tip)).cloned());
+            let chain = Chain::from_blocks(blocks, difficulty).map_err(StoreError::corrupted)?;
+            serde_json::to_string(&chain).map_err(|_| StoreError::LoadError)
+        }
+
+        fn inner_tip(&mut self) -> Result<Option<Block>, StoreError> {
+            match self.inner.tip_height()? {
+                Some(height) => self.inner.load_block(height),
+                None => Ok(None),
+            }
+        }
+
+        fn clear_log(&mut self) -> Result<(), StoreError> {
+            self.log.set_len(0).map_err(|e| StoreError::io(&self.path, e))?;
+            self.log.sync_all().map_err(|e| StoreError::io(&self.path, e))?;
+            self.pending.clear();
+            Ok(())
+        }
+    }
+
+    impl Engine for WalEngine {
+        /// Replaces the snapshot, which supersedes the blocks logged so far.
+        fn store(&mut self, data: &str) -> Result<(), StoreError> {
+            self.inner.store(data)?;
+            self.clear_log()?;
+            self.tip = self.inner_tip()?;
+            Ok(())
+        }
+
+        fn load(&mut self) -> Result<String, StoreError> {
+            if self.pending.is_empty() {
+                return self.inner.load();
+            }
+            self.merged()
+        }
+
+        fn exists(&mut self) -> Result<bool, StoreError> {
+            Ok(!self.pending.is_empty() || self.inner.exists()?)
+        }
+
+        fn delete(&mut self) -> Result<(), StoreError> {
+            self.inner.delete()?;
+            self.clear_log()?;
+            self.tip = None;
+            Ok(())
+        }
+
+        fn prune(&mut self, keep_full: usize) -> Result<usize, StoreError> {
+            self.checkpoint()?;
+            self.inner.prune(keep_full)
+        }
+
+        fn compact(&mut self) -> Result<(), StoreError> {
+            self.checkpoint()?;
+            self.inner.compact()
+        }
+
+        /// Checkpoints, so the snapshot alone holds the chain.
+        fn flush(&mut self) -> Result<(), StoreError> {
+            self.checkpoint()?;
+            self.inner.flush()
+        }
+
+        /// Namespaces are opened on the wrapped engine, so their blocks are not logged.
+        fn namespace(&mut self, name: &str) -> Result<Box<dyn Engine>, StoreError> {
+            self.inner.namespace(name)
+        }
+
+        fn append_block(&mut self, block: &Block) -> Result<(), StoreError> {
+            if self.tip.as_ref().is_some_and(|tip| block.index <= tip.index || block.previous_hash != tip.hash) {
+                return Err(StoreError::InvalidBlock(block.index));
+            }
+            let mut line = serde_json::to_vec(block).map_err(|_| StoreError::StorageError)?;
+            line.push(b'\n');
+            self.log.write_all(&line).map_err(|e| StoreError::io(&self.path, e))?;
+            self.log.sync_data().map_err(|e| StoreError::io(&self.path, e))?;
+            self.pending.push(block.clone());
+            self.tip = Some(block.clone());
+            if self.pending.len() >= self.checkpoint_interval {
+                self.checkpoint()?;
+            }
+            Ok(())
+        }
+
+        /// Checkpoints first, so the logged blocks get reindexed too.
+        fn reindex(&mut self) -> Result<usize, StoreError> {
+            self.checkpoint()?;
+            self.inner.reindex()
+        }
+
+        fn truncate_to(&mut self, height: usize) -> Result<(), StoreError> {
+            self.checkpoint()?;
+            self.inner.truncate_to(height)?;
+            self.tip = self.inner_tip()?;
+            Ok(())
+        }
+
+        fn tip_height(&mut self) -> Result<Option<usize>, StoreError> {
+            Ok(self.tip.as_ref().map(|tip| tip.index))
+        }
+    }
+
+    /// Reads the blocks logged at `path`, stopping at the first line that cannot be parsed.
+    fn replay(path: &Path) -> Result<Vec<Block>, StoreError> {
+        let data = match fs::read(path) {
+            Ok(data) => String::from_utf8_lossy(&data).into_owned(),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(StoreError::io(path, e)),
+        };
+        Ok(data
+            .lines()
+            .map_while(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
@@ -0,0 +1,103 @@
+pub mod test_batch {
+
+    use crate::{
+        Chain,
+        Wallet,
+        bench::bench::bench,
+        chain::{
+            block::block::block,
+            chain::chain::BlockCheckError,
+            network::network::NetworkId,
+        },
+        miner::miner::miner::MiningDigest,
+        transaction::batch::batch::{BatchTransaction, InvalidBatchErr, Transfer},
+    };
+
+    use tracing::info;
+
+    /// Test function to check batches of transfers are mined as a single entry, move their
+    /// coins like transactions would, and are checked by miners and `Chain::verify_chain`.
+    ///
+    /// A miner mines three prizes, then pays two of them out to two wallets in one batch.
+    /// The batch is replayed, sends a coin twice and is signed for another network, which
+    /// miners and chains must refuse. The miner's wallet then sends its last coins to
+    /// alice in two transactions, each with the next nonce of the chain.
+    pub fn test_batch() {
+        let network = NetworkId::default();
+        let mut chain = Chain::new();
+        let mut miner = bench::miner_for(&chain);
+        for _ in 0..3 {
+            bench::add_next(&mut chain, &mut miner);
+        }
+        let sender = miner.wallet.get_pub_key();
+        let coins = chain.coins_of(&sender);
+        assert_eq!(coins.len(), 3);
+
+        let (alice, bob) = (Wallet::new(), Wallet::new());
+        let batch = BatchTransaction::new(sender.clone(), vec![
+            Transfer { receiver: alice.get_pub_key(), coins: vec![coins[0]] },
+            Transfer { receiver: bob.get_pub_key(), coins: vec![coins[1]] },
+        ]).with_nonce(chain.next_nonce(&sender));
+        let batch = miner.wallet.sign_batch(batch, &network);
+        assert!(batch.verify_signature(&network));
+        assert!(!batch.verify_signature(&NetworkId::testnet()));
+        block::check_batch(batch.clone(), chain.blocks()).expect("the sender owns the coins");
+
+        miner.push_batch(batch.clone());
+        bench::add_next(&mut chain, &mut miner);
+        assert_eq!(chain.get_last_block().batches, vec![batch.clone()]);
+        chain.verify_chain().expect("chains with batches verify");
+        assert_eq!(chain.coins_of(&alice.get_pub_key()), vec![coins[0]]);
+        assert_eq!(chain.coins_of(&bob.get_pub_key()), vec![coins[1]]);
+        assert!(chain.find_entry(&batch.id()).is_some());
+        assert_eq!(chain.next_nonce(&sender), batch.nonce + 1);
+        info!("A batch of {} transfers was mined as one entry", batch.transfers.len());
+
+        // Miners drop replays, and chains holding one fail verification.
+        miner.push_batch(batch.clone());
+        let digest = bench::mine_next(&chain, &mut miner);
+        assert!(digest.get_block().batches.is_empty());
+        let mut replayed = digest.get_block();
+        replayed.batches.push(batch.clone());
+        let mut tampered = chain.clone();
+        tampered.add_block(MiningDigest::new(replayed, digest.get_nonce()))
+            .expect_err("replayed batches are stale");
+        info!("A replayed batch was refused");
+
+        // Coins sent twice, or sent by someone else, are refused too.
+        let twice = BatchTransaction::new(sender.clone(), vec![
+            Transfer { receiver: alice.get_pub_key(), coins: vec![coins[2]] },
+            Transfer { receiver: bob.get_pub_key(), coins: vec![coins[2]] },
+        ]).with_nonce(chain.next_nonce(&sender));
+        assert!(matches!(twice.check(), Err(InvalidBatchErr::RepeatedCoin(_))));
+        let mut repeated = digest.get_block();
+        repeated.batches.push(miner.wallet.sign_batch(twice, &network));
+        let mut tampered = chain.clone();
+        let result = tampered.add_block(MiningDigest::new(repeated, digest.get_nonce()));
+        assert!(matches!(result, Err(BlockCheckError::InvalidBatch { .. })));
+        let stolen = BatchTransaction::new(alice.get_pub_key(), vec![
+            Transfer { receiver: alice.get_pub_key(), coins: vec![coins[2]] },
+        ]);
+        assert!(matches!(block::check_batch(stolen, chain.blocks()), Err(InvalidBatchErr::Transfer(_))));
+        assert!(matches!(BatchTransaction::new(sender.clone(), vec![]).check(), Err(InvalidBatchErr::NoTransfers)));
+
+        // A wallet keeps transferring as long as each transaction takes the next nonce.
+        bench::add_next(&mut chain, &mut miner);
+        for coin in chain.coins_of(&sender) {
+            miner.wallet.add_coin(coin);
+        }
+        for _ in 0..2 {
+            let nonce = chain.next_nonce(&sender);
+            let transfer = miner.wallet.submit_transaction(alice.get_pub_key(), 1, nonce, &network)
+                .expect("the wallet holds the coins");
+            miner.push_transaction(transfer.clone());
+            bench::add_next(&mut chain, &mut miner);
+            assert_eq!(chain.find_entry(&transfer.id()), Some(chain.get_last_block().index));
+            assert_eq!(chain.next_nonce(&sender), nonce + 1);
+        }
+        chain.verify_chain().expect("chains with successive transfers verify");
+        assert_eq!(chain.coins_of(&alice.get_pub_key()).len(), 3);
+        info!("A wallet made two transfers in a row");
+        info!("Batch test passed");
+    }
+}
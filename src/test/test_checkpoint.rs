@@ -0,0 +1,141 @@
+pub mod test_checkpoint {
+
+    use crate::{
+        Wallet,
+        bench::bench::bench,
+        chain::{
+            chain::chain::BlockCheckError,
+            config::config::{ChainConfig, Checkpoint},
+            network::network::NetworkId,
+        },
+        node::{
+            event::event::NodeEvent,
+            config::config::StoreEngine,
+            neighbour::neighbour::Role,
+            node::node::Node,
+        },
+    };
+
+    use std::{fs, time::Duration};
+
+    use base64::{Engine as _, engine::general_purpose};
+    use tokio::sync::broadcast;
+    use tracing::info;
+
+    /// Waits for `events` to tell a checkpoint was pinned.
+    async fn pinned(events: &mut broadcast::Receiver<NodeEvent>) -> (Option<String>, Checkpoint) {
+        tokio::time::timeout(Duration::from_secs(60), async {
+            loop {
+                match events.recv().await {
+                    Ok(NodeEvent::CheckpointPinned { from, checkpoint }) => break (from, checkpoint),
+                    Ok(_) => continue,
+                    Err(e) => panic!("events stopped: {}", e),
+                }
+            }
+        }).await.expect("a signed checkpoint is pinned")
+    }
+
+    /// Test function to check signed checkpoints only verify for their publisher and
+    /// network, that pinning one refuses chains holding another block at its index, and
+    /// that a checkpoint signed by a publishing miner is pinned by the nodes trusting it,
+    /// including ones joining afterwards, and kept pinned across restarts.
+    pub async fn test_checkpoint() {
+        // Mined before the nodes run, which it would hold up.
+        let stale = bench::synthetic_chain(40);
+        let publisher = Wallet::new();
+        let network = NetworkId::default();
+        let chain = bench::synthetic_chain(1);
+        let tip = chain.get_last_block();
+        let checkpoint = Checkpoint { index: tip.index, hash: tip.hash.clone() };
+        let signed = publisher.sign_checkpoint(checkpoint.clone(), &network);
+        assert!(signed.verify(&network));
+        assert!(!signed.verify(&NetworkId::testnet()));
+        let mut forged = signed.clone();
+        forged.checkpoint.hash = "0".repeat(64);
+        assert!(!forged.verify(&network));
+
+        let mut rules = ChainConfig {
+            checkpoint_publishers: vec![general_purpose::STANDARD.encode(publisher.get_pub_key())],
+            ..ChainConfig::default()
+        };
+        assert!(rules.is_publisher(&publisher.get_pub_key()));
+        assert!(!rules.is_publisher(&Wallet::new().get_pub_key()));
+        assert!(rules.pin(checkpoint.clone()).expect("nothing is pinned yet"));
+        assert!(!rules.pin(checkpoint.clone()).expect("pinning twice is fine"));
+        assert!(matches!(rules.pin(forged.checkpoint.clone()), Err(BlockCheckError::CheckpointMismatch { .. })));
+        rules.verify(&chain).expect("the chain holds the checkpoint");
+        let other = bench::synthetic_chain(1);
+        assert!(matches!(rules.verify(&other), Err(BlockCheckError::CheckpointMismatch { .. })));
+        info!("Pinned block {} and refused a chain branching off it", checkpoint.index);
+
+        // A publishing miner signs its first block, and its neighbours pin it.
+        let dir = std::env::temp_dir().join(format!("humble_checkpoint_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("the test directory is created");
+        let key = dir.join("checkpoint.key");
+        fs::write(&key, publisher.to_base64().as_bytes()).expect("the checkpoint key is written");
+        let publishers = vec![general_purpose::STANDARD.encode(publisher.get_pub_key())];
+        let mut miner = Node::builder()
+            .with_role(Role::Miner)
+            .with_address("127.0.0.1:8111")
+            .with_checkpoint_publishers(publishers.clone())
+            .with_checkpoint_key(&key, 1, 0)
+            .build()
+            .expect("the publisher's key is one of the publishers");
+        let mut published = miner.subscribe();
+        let stop_miner = miner.stop_handle();
+        tokio::spawn(async move {
+            let _ = miner.node_loop().await;
+        });
+        let store = StoreEngine::File { path: dir.join("chain.dat"), compression: None };
+        let follower = || Node::builder()
+            .with_address("127.0.0.1:8113")
+            .with_trackers(vec!["127.0.0.1:8111".to_owned()])
+            .with_checkpoint_publishers(publishers.clone())
+            .with_chain_store(store.clone())
+            .build()
+            .expect("nodes build");
+        let mut node = follower();
+        let mut events = node.subscribe();
+        node.enter_network().await.expect("the node greets the miner");
+        let stop_node = node.stop_handle();
+        let running = tokio::spawn(async move {
+            let _ = node.node_loop().await;
+        });
+        let (from, own) = pinned(&mut published).await;
+        assert_eq!(from, None);
+        let (from, received) = pinned(&mut events).await;
+        assert_eq!(from.as_deref(), Some("127.0.0.1:8111"));
+        assert_eq!(received, own);
+        info!("Pinned block {} signed by the miner", received.index);
+
+        // A node joining afterwards is sent the checkpoints signed so far.
+        let mut late = Node::builder()
+            .with_address("127.0.0.1:8115")
+            .with_trackers(vec!["127.0.0.1:8111".to_owned()])
+            .with_checkpoint_publishers(publishers.clone())
+            .build()
+            .expect("nodes build");
+        let mut late_events = late.subscribe();
+        late.enter_network().await.expect("the late node greets the miner");
+        let stop_late = late.stop_handle();
+        tokio::spawn(async move {
+            let _ = late.node_loop().await;
+        });
+        // The miner keeps signing its new blocks, which may reach it first.
+        while pinned(&mut late_events).await.1 != own {}
+        info!("The late node pinned block {}", own.index);
+        stop_miner.stop();
+        stop_late.stop();
+
+        // Restarted, the node pins the checkpoints it saved before loading its chain, and
+        // refuses a longer chain branching off them.
+        stop_node.stop();
+        running.await.expect("the node stops");
+        let mut node = follower();
+        node.start_from(stale.clone());
+        assert!(node.watch_chain().borrow().len() < stale.len());
+        info!("Kept the checkpoints pinned across a restart");
+        fs::remove_dir_all(&dir).expect("the test directory is removed");
+        info!("Checkpoint test passed");
+    }
+}
@@ -0,0 +1,85 @@
+pub mod test_clock {
+
+    use crate::{
+        bench::bench::bench,
+        chain::chain::chain::{self, BlockCheckError, Chain, INTERVAL},
+        clock::clock::clock::{self, Elapsed, MockClock},
+        node::{
+            fragment::fragment::{self, FragmentError, Reassembler, REASSEMBLY_TIMEOUT},
+            gossip::gossip::{self, MAX_DATAGRAM_SIZE},
+            rpc::rpc::REQUEST_TIMEOUT,
+        },
+    };
+
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use tracing::info;
+
+    /// Test function to check time-dependent logic on a `MockClock`.
+    ///
+    /// This function installs a mock clock, mines blocks `INTERVAL` seconds apart and then
+    /// in quick succession, checking the difficulty only rises for the latter and chains
+    /// cannot claim a lower one, sleeps a gossip interval and times out a request, checking
+    /// both move the clock without waiting for real, and expires a partial message. The system clock is back once the test returns or panics.
+    pub async fn test_clock() {
+        let mock = MockClock::new(1_700_000_000);
+        let _clock = clock::install_scoped(Arc::new(mock.clone()));
+
+        // Blocks mined after the interval leave the difficulty unchanged
+        let mut chain = Chain::new();
+        let difficulty = chain.difficulty;
+        mock.advance(Duration::from_secs(INTERVAL));
+        bench::extend(&mut chain, 1);
+        assert_eq!(chain.difficulty, difficulty);
+        info!("Block mined after {} seconds, difficulty {}", INTERVAL, chain.difficulty);
+
+        // Blocks mined within the interval raise it. Miners date them by the mock clock,
+        // unlike `bench::mine_next`.
+        mock.advance(Duration::from_secs(1));
+        let mut miner = bench::miner_for(&chain);
+        let digest = miner.mine(chain.get_last_block()).expect("the chain meta is set");
+        chain.add_block(digest).expect("mined blocks extend the chain");
+        assert_eq!(chain.difficulty, difficulty + 1);
+        info!("Block mined after 1 second, difficulty {}", chain.difficulty);
+
+        // The rise is replayed from the genesis when verifying the chain, so it cannot be
+        // claimed away to hold blocks to a lower difficulty.
+        chain.verify_chain().expect("the chain reached the difficulty it claims");
+        let headers = chain.headers();
+        assert!(chain::verify_headers(&headers, difficulty, difficulty + 1).is_ok());
+        assert!(matches!(chain::verify_headers(&headers, difficulty, difficulty), Err(BlockCheckError::WrongDifficulty { .. })));
+        let mut lowered = chain.clone();
+        lowered.difficulty = difficulty;
+        assert_eq!(lowered.start_difficulty(), 1);
+        assert!(matches!(lowered.verify_chain(), Err(BlockCheckError::WrongDifficulty { .. })));
+
+        // Sleeping moves the mock clock right away
+        let before = clock::now();
+        let started = Instant::now();
+        gossip::wait_gossip_interval(Duration::from_secs(3600)).await;
+        assert_eq!(clock::now(), before + 3600);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Timeouts elapse on the mock clock too
+        let before = clock::now_millis();
+        let started = Instant::now();
+        let answer = clock::timeout(Duration::from_millis(REQUEST_TIMEOUT), std::future::pending::<()>()).await;
+        assert_eq!(answer, Err(Elapsed));
+        assert_eq!(clock::now_millis(), before + REQUEST_TIMEOUT);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Partial messages expire on the mock clock
+        let mut reassembler = Reassembler::new();
+        let fragments = fragment::fragment(&vec![7u8; MAX_DATAGRAM_SIZE + 1]).expect("messages fragment");
+        let sender = "127.0.0.1:9001".parse().unwrap();
+        assert!(reassembler.push(sender, &fragments[0]).expect("fragments are well formed").is_none());
+        mock.advance(Duration::from_secs(REASSEMBLY_TIMEOUT));
+        assert!(reassembler.expire().is_empty());
+        mock.advance(Duration::from_secs(1));
+        assert!(matches!(reassembler.expire().as_slice(), [FragmentError::Incomplete { received: 1, total: 2, .. }]));
+        info!("Clock test passed");
+    }
+}
@@ -2,7 +2,7 @@ pub mod test_core {
 
     use crate::{
         miner::miner::miner::Miner,
-        chain::chain::chain::Chain,
+        chain::{chain::chain::Chain, network::network::NetworkId},
         wallet::wallet::wallet::Wallet,
         transaction::transaction::transaction::Transaction,
     };
@@ -48,7 +48,7 @@ pub mod test_core {
         // Create a transaction from miner1 to wallet1 using one token
         let one_token = miner1.wallet.get_coins().pop().expect("No coins available");
         let t1 = Transaction::new(miner1.wallet.get_pub_key(), wallet1.get_pub_key(), vec![one_token]);
-        let signed_t1 = miner1.wallet.sign(t1);
+        let signed_t1 = miner1.wallet.sign(t1, &NetworkId::default());
 
         // Update miner1 with the latest chain metadata and mine a block with the transaction
         miner1.set_chain_meta(my_chain.get_len(), my_chain.difficulty, my_chain.get_blocks());
@@ -0,0 +1,86 @@
+pub mod test_crash {
+
+    use crate::{
+        Wallet,
+        chain::network::network::NetworkId,
+        node::{
+            config::config::StoreEngine,
+            event::event::NodeEvent,
+            neighbour::neighbour::Role,
+            node::node::Node,
+            submit::submit::EntryStatus,
+        },
+        record::record::record::Record,
+    };
+
+    use std::{fs, time::Duration};
+
+    use tracing::info;
+
+    /// Test function to check a miner whose task panics saves its chain and mempool and
+    /// reports it crashed, that the entries it had yet to mine are mined once it is
+    /// started again on the same chain store, and that the chain holding them is saved.
+    pub async fn test_crash() {
+        let dir = std::env::temp_dir().join(format!("humble_crash_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("the test directory is created");
+        let store = StoreEngine::File { path: dir.join("chain.dat"), compression: None };
+        let miner = || Node::builder()
+            .with_role(Role::Miner)
+            .with_address("127.0.0.1:8131")
+            .with_chain_store(store.clone())
+            .build()
+            .expect("miners build");
+
+        let mut node = miner();
+        let mut events = node.subscribe();
+        let wallet = Wallet::new();
+        let record = wallet.sign_record(Record::new("crash".to_string(), "survived".to_string(), wallet.get_pub_key()), &NetworkId::default());
+        let record_id = record.id();
+        node.submit_entry(Box::new(record)).await.expect("miners take records");
+        let task = tokio::spawn(async move {
+            let _node = node;
+            panic!("the task running the node panics");
+        });
+        assert!(task.await.expect_err("the task panicked").is_panic());
+        let crashed = loop {
+            match events.try_recv() {
+                Ok(NodeEvent::Crashed { height, pending, saved }) => break (height, pending, saved),
+                Ok(_) => continue,
+                Err(e) => panic!("no crash was reported: {}", e),
+            }
+        };
+        assert_eq!((crashed.1, crashed.2), (1, true));
+        info!("The node saved its chain of {} blocks and {} entry when it crashed", crashed.0, crashed.1);
+
+        // Started again, the miner mines the record it had queued.
+        let mut node = miner();
+        let mut chain_updates = node.watch_chain();
+        let stop = node.stop_handle();
+        let running = tokio::spawn(async move {
+            let _ = node.node_loop().await;
+            node
+        });
+        let height = tokio::time::timeout(Duration::from_secs(60), async {
+            loop {
+                chain_updates.changed().await.expect("the node runs");
+                let chain = chain_updates.borrow_and_update().clone();
+                if let Some((_, height)) = chain.find_record("crash") {
+                    break height;
+                }
+            }
+        }).await.expect("the saved record is mined");
+        info!("The record saved when the node crashed was mined in block {}", height);
+        stop.stop();
+
+        // The chain is saved in the background, and once more as the loop stops.
+        let node = running.await.expect("the node stops");
+        let metrics = node.metrics();
+        assert!(metrics.chain_saves > 0 && metrics.last_chain_save > 0);
+        assert_eq!(metrics.chain_save_failures, 0);
+        drop(node);
+        assert!(matches!(miner().entry_status(&record_id), EntryStatus::Confirmed { .. }));
+        info!("The chain was saved {} times", metrics.chain_saves);
+        fs::remove_dir_all(&dir).expect("the test directory is removed");
+        info!("Crash test passed");
+    }
+}
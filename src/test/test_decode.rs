@@ -0,0 +1,327 @@
+pub mod test_decode {
+
+    use crate::{
+        Transaction,
+        Wallet,
+        bench::bench::bench,
+        node::{
+            decode::decode::{self, DecodeError},
+            filter::filter::{NotificationFilter, MAX_FILTER_ENTRIES},
+            frame::frame,
+            fragment::fragment::{self, FragmentError, Reassembler, FRAGMENT_PAYLOAD_SIZE, MAX_PARTIAL_MESSAGES},
+            gossip::gossip::MAX_DATAGRAM_SIZE,
+            protocol::protocol::ProtocolMsg,
+            secure::secure::{Handshake, SecureError, REPLAY_WINDOW},
+        },
+        record::record::record::Record,
+        token::token::token::{Token, TOKEN_LEN},
+        chain::network::network::NetworkId,
+        chain::config::config::Checkpoint,
+        transaction::batch::batch::{BatchTransaction, Transfer},
+    };
+
+    use std::net::SocketAddr;
+
+    use proptest::{
+        prelude::*,
+        collection::vec,
+        sample::{select, Index},
+        test_runner::{Config, RngAlgorithm, TestError, TestRng, TestRunner},
+    };
+    use uuid::Uuid;
+    use tracing::info;
+
+    /// An edit to valid input, as a faulty or hostile peer might send it.
+    #[derive(Clone, Debug)]
+    enum Mutation {
+        /// Overwrites the byte at an index.
+        Overwrite(Index, u8),
+        /// Drops the bytes from an index on.
+        Truncate(Index),
+        /// Appends a byte.
+        Push(u8),
+    }
+
+    /// Any one `Mutation`, at any index of the input it is applied to.
+    fn mutation() -> impl Strategy<Value = Mutation> {
+        prop_oneof![
+            (any::<Index>(), any::<u8>()).prop_map(|(index, byte)| Mutation::Overwrite(index, byte)),
+            any::<Index>().prop_map(Mutation::Truncate),
+            any::<u8>().prop_map(Mutation::Push),
+        ]
+    }
+
+    /// Returns `bytes` with `mutations` applied in order.
+    fn mutate(mut bytes: Vec<u8>, mutations: &[Mutation]) -> Vec<u8> {
+        for mutation in mutations {
+            match mutation {
+                Mutation::Overwrite(index, byte) if !bytes.is_empty() => {
+                    let i = index.index(bytes.len());
+                    bytes[i] = *byte;
+                },
+                Mutation::Overwrite(..) => (),
+                Mutation::Truncate(index) => bytes.truncate(index.index(bytes.len() + 1)),
+                Mutation::Push(byte) => bytes.push(*byte),
+            }
+        }
+        bytes
+    }
+
+    /// Fragment datagrams with a header, valid or not, from one of a few messages, so some
+    /// of them belong together.
+    fn fragment_datagram() -> impl Strategy<Value = Vec<u8>> {
+        (0..4u8, 0..8u16, 0..8u16, vec(any::<u8>(), 0..64)).prop_map(|(id, index, total, payload)| {
+            let mut datagram = vec![ProtocolMsg::Fragment as u8];
+            datagram.extend_from_slice(Uuid::from_u128(id as u128).as_bytes());
+            datagram.extend_from_slice(&index.to_be_bytes());
+            datagram.extend_from_slice(&total.to_be_bytes());
+            datagram.extend_from_slice(&payload);
+            datagram
+        })
+    }
+
+    /// Runs every decoder on `bytes`. Any outcome is fine but a panic.
+    fn decode_all(bytes: &[u8]) {
+        let _ = decode::transaction(bytes);
+        let _ = decode::record(bytes);
+        let _ = decode::neighbour(bytes);
+        let _ = decode::relayed_neighbour(bytes);
+        let _ = decode::neighbours(bytes);
+        let _ = decode::chain(bytes);
+        let _ = decode::batch(bytes);
+        let _ = decode::filter(bytes);
+        let _ = decode::filtered_block(bytes);
+        let _ = decode::checkpoint(bytes);
+        let _ = decode::pool_join(bytes);
+        let _ = decode::job(bytes);
+        let _ = decode::range(bytes);
+    }
+
+    /// Checks `property` holds for `cases` inputs drawn from `strategy`, seeded by `seed`.
+    /// A failure is shrunk to the smallest input still failing, which the panic shows.
+    fn check<S: Strategy>(name: &str, cases: usize, seed: u64, strategy: S, property: impl Fn(S::Value) -> Result<(), TestCaseError>)
+    where
+        S::Value: std::fmt::Debug,
+    {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&seed.to_le_bytes());
+        let config = Config { failure_persistence: None, ..Config::with_cases(cases as u32) };
+        let mut runner = TestRunner::new_with_rng(config, TestRng::from_seed(RngAlgorithm::ChaCha, &key));
+        match runner.run(&strategy, property) {
+            Ok(()) => info!("{}: {} cases passed", name, cases),
+            Err(TestError::Fail(reason, input)) => panic!("{} failed with seed {}: {}; minimal input: {:?}", name, seed, reason, input),
+            Err(TestError::Abort(reason)) => panic!("{} aborted with seed {}: {}", name, seed, reason),
+        }
+    }
+
+    /// Test function to check the decoders of network input round-trip what nodes send
+    /// and never panic on anything else.
+    ///
+    /// This function checks, with proptest strategies, that random signed transactions
+    /// and records come back unchanged from their wire format, that the message decoders
+    /// do not panic on random bytes or mutations of valid input, that frames are refused
+    /// once padded or cut short, that fragmented messages reassemble in any order, that
+    /// random fragment datagrams are handled, and that tampered sealed messages do not
+    /// open. Failures are shrunk and replay from `seed`. It also checks oversized input,
+    /// missing fields, unknown roles and chains with malformed blocks are rejected, that
+    /// fragments of too many or too large messages are not all held, and that replayed
+    /// sealed messages are refused.
+    ///
+    /// # Arguments
+    /// * `cases` - The number of random cases of each property.
+    /// * `seed` - Seeds the random cases, so failures can be replayed.
+    pub fn test_decode(cases: usize, seed: u64) {
+        let wallet = Wallet::new();
+        let receiver = Wallet::new().get_pub_key();
+        let network = NetworkId::default();
+
+        // Round trips
+        check("transaction round trip", cases, seed, (any::<[u8; TOKEN_LEN]>(), any::<u64>()), |(coin, nonce)| {
+            let transaction = Transaction::new(wallet.get_pub_key(), receiver.clone(), vec![Token::new(coin)])
+                .with_nonce(nonce);
+            let transaction = wallet.sign(transaction, &network);
+            let wire: String = transaction.clone().into();
+            let decoded = decode::transaction(wire.as_bytes()).expect("signed transactions decode");
+            prop_assert_eq!(decoded.id(), transaction.id());
+            prop_assert!(decoded.verify_signature(&network));
+            prop_assert!(!decoded.verify_signature(&NetworkId::testnet()));
+            Ok(())
+        });
+        // Any characters, separators included.
+        check("record round trip", cases, seed, ("(?s).{0,64}", "(?s).{0,512}"), |(key, value)| {
+            let record = wallet.sign_record(Record::new(key, value, wallet.get_pub_key()), &network);
+            let wire: String = record.clone().into();
+            let decoded = decode::record(wire.as_bytes()).expect("signed records decode");
+            prop_assert!(decoded.verify_signature(&network));
+            prop_assert!(!decoded.verify_signature(&NetworkId::testnet()));
+            prop_assert_eq!(decoded, record);
+            Ok(())
+        });
+
+        let mut wires = vec![];
+        let transaction = wallet.sign(Transaction::new(wallet.get_pub_key(), receiver.clone(), vec![Token::new([1; TOKEN_LEN])]), &network);
+        let wire: String = transaction.into();
+        wires.push(wire.into_bytes());
+        let record = wallet.sign_record(Record::new(String::from("key"), String::from("value"), wallet.get_pub_key()), &network);
+        let wire: String = record.into();
+        wires.push(wire.into_bytes());
+        let transfers = (0..4u8).map(|i| Transfer { receiver: receiver.clone(), coins: vec![Token::new([i; TOKEN_LEN])] }).collect();
+        let batch = wallet.sign_batch(BatchTransaction::new(wallet.get_pub_key(), transfers), &network);
+        let json = serde_json::to_vec(&batch).expect("batches serialize");
+        assert_eq!(decode::batch(&json).expect("signed batches decode"), batch);
+        wires.push(json);
+        let chain = bench::synthetic_chain(3);
+        let json = serde_json::to_vec(&chain).expect("chains serialize");
+        assert_eq!(decode::chain(&json).expect("chains decode").len(), chain.len());
+        wires.push(json);
+        let filter = NotificationFilter::new().with_address(wallet.get_pub_key()).with_key("key");
+        let json = serde_json::to_vec(&filter).expect("filters serialize");
+        assert_eq!(decode::filter(&json).expect("filters decode"), filter);
+        wires.push(json);
+        let filtered = filter.filter_block(&chain.get_last_block());
+        let json = serde_json::to_vec(&filtered).expect("filtered blocks serialize");
+        assert_eq!(decode::filtered_block(&json).expect("filtered blocks decode"), filtered);
+        wires.push(json);
+        let tip = chain.get_last_block();
+        let signed = wallet.sign_checkpoint(Checkpoint { index: tip.index, hash: tip.hash }, &network);
+        let json = serde_json::to_vec(&signed).expect("checkpoints serialize");
+        assert_eq!(decode::checkpoint(&json).expect("signed checkpoints decode"), signed);
+        wires.push(json);
+
+        // Garbage and mutated input
+        check("garbage messages", cases, seed, vec(any::<u8>(), 0..256), |garbage| {
+            decode_all(&garbage);
+            Ok(())
+        });
+        check("mutated messages", cases, seed, (select(wires), vec(mutation(), 1..4)), |(wire, mutations)| {
+            decode_all(&mutate(wire, &mutations));
+            Ok(())
+        });
+
+        // Frames give back their payload, and nothing once padded or cut short.
+        check("frames", cases, seed, (vec(any::<u8>(), 0..256), vec(any::<u8>(), 1..8), any::<Index>()), |(payload, padding, cut)| {
+            let framed = frame::encode(ProtocolMsg::Chain, &payload);
+            prop_assert_eq!(frame::decode(&framed).expect("frames decode"), payload.as_slice());
+            prop_assert!(frame::decode(&[framed.as_slice(), &padding].concat()).is_err());
+            prop_assert!(frame::decode(&framed[..cut.index(framed.len())]).is_err());
+            Ok(())
+        });
+
+        // Messages past a datagram reassemble from their fragments in any order; the
+        // filling does not repeat across fragments, so misplaced ones would show.
+        let fragmented = (MAX_DATAGRAM_SIZE + 1..=3 * FRAGMENT_PAYLOAD_SIZE, any::<u8>()).prop_flat_map(|(len, fill)| {
+            let order: Vec<usize> = (0..len.div_ceil(FRAGMENT_PAYLOAD_SIZE)).collect();
+            (Just(len), Just(fill), Just(order).prop_shuffle())
+        });
+        check("fragment reassembly", cases, seed, fragmented, |(len, fill, order)| {
+            let message: Vec<u8> = (0..len).map(|i| (i % 251) as u8 ^ fill).collect();
+            let fragments = fragment::fragment(&message).expect("messages fragment");
+            prop_assert_eq!(fragments.len(), order.len());
+            let mut reassembler = Reassembler::new();
+            let sender: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+            let (last, first) = order.split_last().unwrap();
+            for &i in first {
+                prop_assert!(reassembler.push(sender, &fragments[i]).expect("fragments are well formed").is_none());
+            }
+            let reassembled = reassembler.push(sender, &fragments[*last]).expect("fragments are well formed");
+            prop_assert!(reassembled == Some(message), "fragments reassembled into another message");
+            Ok(())
+        });
+        let datagrams = vec(prop_oneof![fragment_datagram(), vec(any::<u8>(), 0..32)], 1..32);
+        check("garbage fragments", cases, seed, (datagrams, vec(any::<bool>(), 32)), |(datagrams, senders)| {
+            let mut reassembler = Reassembler::new();
+            let peers: [SocketAddr; 2] = ["127.0.0.1:9001".parse().unwrap(), "127.0.0.1:9002".parse().unwrap()];
+            for (datagram, from_peer) in datagrams.iter().zip(senders) {
+                let _ = reassembler.push(peers[from_peer as usize], datagram);
+            }
+            let _ = reassembler.expire();
+            Ok(())
+        });
+
+        // Sealed messages open unless changed past their protocol byte, which is not sealed.
+        let (initiator, responder) = (Handshake::new().unwrap(), Handshake::new().unwrap());
+        let initiator_key = initiator.public_key();
+        let sealing = initiator.complete(&responder.public_key(), true).expect("handshakes complete");
+        let opening = responder.complete(&initiator_key, false).expect("handshakes complete");
+        check("tampered sealed messages", cases, seed, (vec(any::<u8>(), 0..256), vec(mutation(), 1..4)), |(message, mutations)| {
+            let sealed = sealing.seal(&message);
+            let tampered = mutate(sealed.clone(), &mutations);
+            let intact = tampered.get(1..) == sealed.get(1..);
+            match opening.open(&tampered) {
+                Ok(plain) => prop_assert!(intact && plain == message, "a tampered message opened"),
+                Err(e) => prop_assert!(!intact, "an intact message did not open: {}", e),
+            }
+            Ok(())
+        });
+        check("garbage sealed messages", cases, seed, vec(any::<u8>(), 0..256), |garbage| {
+            prop_assert!(opening.open(&garbage).is_err());
+            Ok(())
+        });
+
+        // Input the decoders must reject
+        let oversized = vec![b'A'; decode::MAX_ENTRY_SIZE + 1];
+        assert!(matches!(decode::transaction(&oversized), Err(DecodeError::TooLong { .. })));
+        assert!(matches!(decode::record(&oversized), Err(DecodeError::TooLong { .. })));
+        assert!(matches!(decode::transaction(b"a;b;"), Err(DecodeError::Transaction(_))));
+        assert!(matches!(decode::record(b"a;b;"), Err(DecodeError::Record(_))));
+        let long_key = Record::new("k".repeat(decode::MAX_RECORD_KEY_SIZE + 1), String::from("value"), wallet.get_pub_key());
+        let wire: String = long_key.into();
+        assert!(matches!(decode::record(wire.as_bytes()), Err(DecodeError::FieldTooLong { field: "key", .. })));
+        let unknown_role = format!(r#"{{"id":"{}","address":"127.0.0.1:9000","role":99}}"#, Uuid::new_v4());
+        assert!(matches!(decode::neighbour(unknown_role.as_bytes()), Err(DecodeError::Json(_))));
+        let crowded = (0..=MAX_FILTER_ENTRIES).fold(NotificationFilter::new(), |filter, i| filter.with_key(i.to_string()));
+        let json = serde_json::to_vec(&crowded).expect("filters serialize");
+        assert!(matches!(decode::filter(&json), Err(DecodeError::FieldTooLong { field: "entries", .. })));
+        let unhashed = wallet.sign_checkpoint(Checkpoint { index: 5, hash: "not a hash".to_string() }, &network);
+        let json = serde_json::to_vec(&unhashed).expect("checkpoints serialize");
+        assert!(matches!(decode::checkpoint(&json), Err(DecodeError::MalformedCheckpoint(5))));
+
+        let mut malformed = serde_json::to_value(&chain).expect("chains serialize");
+        malformed["blocks"][1]["data"] = serde_json::Value::from("not;a;valid;transaction;at;all;");
+        let json = serde_json::to_vec(&malformed).expect("chains serialize");
+        assert!(matches!(decode::chain(&json), Err(DecodeError::MalformedBlock(_))));
+
+        // Partial messages past the limit push the sender's oldest out, reported as
+        // incomplete, and leave other senders' messages alone.
+        let mut reassembler = Reassembler::new();
+        let flooder: SocketAddr = "127.0.0.1:9001".parse().unwrap();
+        let peer: SocketAddr = "127.0.0.1:9002".parse().unwrap();
+        let messages: Vec<Vec<Vec<u8>>> = (0..=MAX_PARTIAL_MESSAGES + 1)
+            .map(|_| fragment::fragment(&vec![7u8; MAX_DATAGRAM_SIZE + 1]).expect("messages fragment"))
+            .collect();
+        let (peer_message, flood) = messages.split_last().unwrap();
+        assert!(reassembler.push(peer, &peer_message[0]).expect("fragments are well formed").is_none());
+        for fragments in flood {
+            assert!(reassembler.push(flooder, &fragments[0]).expect("fragments are well formed").is_none());
+        }
+        let dropped = reassembler.expire();
+        assert!(matches!(dropped.as_slice(), [FragmentError::Incomplete { received: 1, total: 2, .. }]));
+        assert!(reassembler.push(flooder, &flood[0][1]).expect("fragments are well formed").is_none());
+        let last = reassembler.push(flooder, &flood[MAX_PARTIAL_MESSAGES][1]).expect("fragments are well formed");
+        assert_eq!(last.map(|message| message.len()), Some(MAX_DATAGRAM_SIZE + 1));
+        assert!(reassembler.push(flooder, &peer_message[1]).expect("fragments are well formed").is_none());
+        let kept = reassembler.push(peer, &peer_message[1]).expect("fragments are well formed");
+        assert_eq!(kept.map(|message| message.len()), Some(MAX_DATAGRAM_SIZE + 1));
+        let mut huge = flood[0][0].clone();
+        let total = (decode::MAX_CHAIN_SIZE / FRAGMENT_PAYLOAD_SIZE + 2) as u16;
+        huge[19..21].copy_from_slice(&total.to_be_bytes());
+        assert!(matches!(reassembler.push(flooder, &huge), Err(FragmentError::Oversized { .. })));
+
+        // Sealed messages open once, and not at all once too old.
+        let (initiator, responder) = (Handshake::new().unwrap(), Handshake::new().unwrap());
+        let initiator_key = initiator.public_key();
+        let sealing = initiator.complete(&responder.public_key(), true).expect("handshakes complete");
+        let opening = responder.complete(&initiator_key, false).expect("handshakes complete");
+        let sealed: Vec<Vec<u8>> = (0..=REPLAY_WINDOW).map(|i| sealing.seal(&[i as u8])).collect();
+        assert_eq!(opening.open(&sealed[1]).expect("sealed messages open"), [1]);
+        assert!(matches!(opening.open(&sealed[1]), Err(SecureError::Replayed(1))));
+        assert!(opening.open(&sealed[0]).is_ok());
+        assert!(opening.open(&sealed[REPLAY_WINDOW as usize]).is_ok());
+        assert!(matches!(opening.open(&sealed[0]), Err(SecureError::Replayed(0))));
+        let mut tampered = sealed[2].clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(opening.open(&tampered), Err(SecureError::Decryption)));
+        assert!(opening.open(&sealed[2]).is_ok());
+        info!("Decode test passed");
+    }
+}
@@ -0,0 +1,82 @@
+pub mod test_filter {
+
+    use crate::{
+        Chain,
+        Wallet,
+        bench::bench::bench,
+        node::{
+            event::event::NodeEvent,
+            filter::filter::{NotificationFilter, MAX_FILTER_ENTRIES},
+            neighbour::neighbour::Role,
+            node::node::{FilterError, Node},
+            receiver::receiver::Receiver,
+        },
+        record::record::record::Record,
+    };
+
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+    use tracing::info;
+
+    /// Test function to check notification filters pick the entries of a block a light
+    /// node asked for, and that a node which loaded a filter on a miner hears about the
+    /// blocks it mines as filtered blocks.
+    pub async fn test_filter() {
+        let mut chain = Chain::new();
+        let mut miner = bench::miner_for(&chain);
+        let owner = Wallet::new();
+        let record = owner.sign_record(Record::new("app/greeting".to_string(), "hello".to_string(), owner.get_pub_key()), chain.network());
+        miner.push_record(record.clone());
+        bench::add_next(&mut chain, &mut miner);
+        let block = chain.get_last_block().clone();
+
+        let prizes = NotificationFilter::new().with_address(miner.wallet.get_pub_key());
+        let filtered = prizes.filter_block(&block);
+        assert_eq!(filtered.header, block.header());
+        assert_eq!(filtered.get_transactions().len(), 1);
+        assert!(filtered.records.is_empty());
+        let keys = NotificationFilter::new().with_key("app/greeting");
+        assert_eq!(keys.filter_block(&block).records, vec![record.clone()]);
+        let owned = NotificationFilter::new().with_address(owner.get_pub_key());
+        assert_eq!(owned.filter_block(&block).records, vec![record]);
+        assert!(NotificationFilter::new().with_key("app/other").filter_block(&block).is_empty());
+        info!("Filters picked the entries they match only");
+
+        // A miner relays the blocks it mines, filtered, to a node that loaded a filter.
+        let (_tx1, rx1) = mpsc::channel::<String>(1024);
+        let mut miner = Node::new(Role::Miner, "127.0.0.1:8101".to_owned(), None, Receiver::new(rx1));
+        let stop_miner = miner.stop_handle();
+        tokio::spawn(async move {
+            let _ = miner.node_loop().await;
+        });
+        let (_tx2, rx2) = mpsc::channel::<String>(1024);
+        let mut light = Node::new(Role::Node, "127.0.0.1:8103".to_owned(), Some(vec!["127.0.0.1:8101".to_owned()]), Receiver::new(rx2));
+        let mut events = light.subscribe();
+        light.enter_network().await.expect("the light node greets the miner");
+        let too_large = (0..=MAX_FILTER_ENTRIES).fold(NotificationFilter::new(), |filter, i| filter.with_key(i.to_string()));
+        assert!(matches!(light.load_filter("127.0.0.1:8101", &too_large).await, Err(FilterError::TooLarge(_))));
+        assert!(matches!(light.load_filter("127.0.0.1:9", &prizes).await, Err(FilterError::NotNeighbour(_))));
+        light.load_filter("127.0.0.1:8101", &keys).await.expect("the miner is a neighbour");
+        let stop_light = light.stop_handle();
+        tokio::spawn(async move {
+            let _ = light.node_loop().await;
+        });
+
+        let received = tokio::time::timeout(Duration::from_secs(60), async {
+            loop {
+                match events.recv().await {
+                    Ok(NodeEvent::FilteredBlock { from, block }) => break (from, block),
+                    Ok(_) => continue,
+                    Err(e) => panic!("events stopped: {}", e),
+                }
+            }
+        }).await.expect("the miner relays a filtered block");
+        assert_eq!(received.0, "127.0.0.1:8101");
+        assert!(received.1.is_empty());
+        info!("Received filtered block {} from the miner", received.1.header.index);
+        stop_miner.stop();
+        stop_light.stop();
+        info!("Filter test passed");
+    }
+}
@@ -0,0 +1,50 @@
+pub mod test_fork {
+
+    use crate::{
+        bench::bench::bench,
+        chain::{chain::chain::Chain, genesis::genesis::Genesis},
+    };
+
+    use tracing::info;
+
+    /// Test function to find where crafted forks of a chain part ways.
+    ///
+    /// This function mines a common trunk, forks it twice, and checks `Chain::common_ancestor`
+    /// and `Chain::diverges_at` agree on the fork point, whichever chain asks. It also
+    /// checks a chain extending another does not diverge from it, and two chains with
+    /// different genesis blocks share no ancestor.
+    pub fn test_fork() {
+        let trunk = bench::synthetic_chain(3);
+        let tip = trunk.get_last_block();
+        info!("Trunk mined up to block {}", tip.index);
+
+        // Two forks of the trunk, of different lengths
+        let (mut short, mut long) = (trunk.clone(), trunk.clone());
+        bench::extend(&mut short, 1);
+        bench::extend(&mut long, 4);
+        assert_eq!(short.common_ancestor(&long), Some((tip.index, tip.hash.clone())));
+        assert_eq!(long.common_ancestor(&short), Some((tip.index, tip.hash.clone())));
+        assert_eq!(short.diverges_at(&long), Some(trunk.len()));
+        assert_eq!(long.diverges_at(&short), Some(trunk.len()));
+        info!("Forks of {} and {} blocks diverge at height {}", short.len(), long.len(), trunk.len());
+
+        // A chain extending another holds all of it
+        assert_eq!(long.common_ancestor(&trunk), Some((tip.index, tip.hash.clone())));
+        assert_eq!(trunk.diverges_at(&long), None);
+        assert_eq!(long.diverges_at(&long), None);
+
+        // A fork right after the genesis block
+        let genesis = trunk.get_block(0).expect("every chain starts with a genesis block");
+        let mut early = Chain::from_genesis(genesis.clone(), trunk.difficulty);
+        bench::extend(&mut early, 2);
+        assert_eq!(early.common_ancestor(&long), Some((0, genesis.hash.clone())));
+        assert_eq!(early.diverges_at(&long), Some(1));
+
+        // Chains of different networks
+        let mut other = Genesis::new("other", tip.timestamp, trunk.difficulty).chain();
+        bench::extend(&mut other, 3);
+        assert_eq!(other.common_ancestor(&trunk), None);
+        assert_eq!(other.diverges_at(&trunk), Some(0));
+        info!("Fork test passed");
+    }
+}
@@ -10,6 +10,8 @@ pub mod test_gossip {
             node::node::Node,
             receiver::receiver::Receiver,
         },
+        token::token::token::Token,
+        chain::network::network::NetworkId,
     };
 
     use std::{
@@ -30,7 +32,7 @@ pub mod test_gossip {
     /// # Returns
     /// * `Transaction` - The signed transaction.
     fn make_up_transaction() -> Transaction {
-        let some_token = "0".repeat(64); // A made-up coin token represented as a string of 64 zeros
+        let some_token = Token::default(); // A made-up coin token of 32 zero bytes
         let wallet1 = Wallet::new();
         let wallet2 = Wallet::new();
         
@@ -40,7 +42,7 @@ pub mod test_gossip {
             vec![some_token], 
         );
 
-        wallet1.sign(transaction)
+        wallet1.sign(transaction, &NetworkId::default())
     }
 
     /// Repeatedly sends mock transactions to a given channel.
@@ -105,12 +107,12 @@ pub mod test_gossip {
 
         // Spawn the Tracker node's event loop
         tokio::spawn(async move {
-            clone1.lock().await.node_loop().await;
+            let _ = clone1.lock().await.node_loop().await;
         });
 
         // Spawn the second node and start its event loop
         tokio::spawn(async move {
-            node2.enter_and_node_loop().await;
+            let _ = node2.enter_and_node_loop().await;
         });
 
         // Allow some time for the nodes to initialize
@@ -127,7 +129,7 @@ pub mod test_gossip {
 
         // Spawn the Miner node's event loop
         tokio::spawn(async move {
-            node3.enter_and_node_loop().await;
+            let _ = node3.enter_and_node_loop().await;
         });
 
         // Give some time for the miner node to be added to the network
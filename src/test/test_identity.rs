@@ -0,0 +1,75 @@
+pub mod test_identity {
+
+    use crate::{
+        chain::network::network::NetworkId,
+        clock::clock::clock::now,
+        node::{
+            config::config::StoreEngine,
+            identity::identity::{self, Identity},
+            neighbour::neighbour::{Neighbour, Role},
+            node::node::Node,
+            registry::registry::{Registration, REGISTRATION_TTL, REGISTRY_NAMESPACE},
+        },
+        store::store::store::Store,
+    };
+
+    use std::fs;
+
+    use tracing::info;
+    use uuid::Uuid;
+
+    /// Test function to check a node saving its address book keeps its id across restarts,
+    /// that a tracker keeps the registrations of its registry that have not expired, and
+    /// that handshake keys are only proven by the identity that signed them, for the
+    /// network they were signed for.
+    pub fn test_identity() {
+        let dir = std::env::temp_dir().join(format!("humble_identity_{}", std::process::id()));
+        let path = dir.join("peers.json");
+        let open = || Node::builder()
+            .with_store(StoreEngine::File { path: path.clone(), compression: None })
+            .build()
+            .expect("nodes with a file store build");
+        let id = open().status().id;
+        assert_eq!(open().status().id, id);
+        assert_ne!(Node::builder().build().expect("nodes build").status().id, id);
+        let mut store = Store::new(&path).expect("the peer store opens");
+        assert_eq!(Identity::load_or_create(&mut store).expect("the identity loads").id(), id);
+        info!("A restarted node kept its id {}", id);
+
+        let registration = |address: &str, last_announce| Registration {
+            neighbour: Neighbour::new(Uuid::new_v4(), address.to_string(), Role::Node),
+            last_announce,
+        };
+        let saved = vec![registration("127.0.0.1:9101", now()), registration("127.0.0.1:9102", now() - REGISTRATION_TTL - 1)];
+        store.namespace(REGISTRY_NAMESPACE).and_then(|store| store.save(&saved)).expect("the registry is saved");
+        drop(store);
+        let mut tracker = Node::builder()
+            .with_role(Role::Tracker)
+            .with_store(StoreEngine::File { path: path.clone(), compression: None })
+            .build()
+            .expect("trackers with a file store build");
+        tracker.load_peers().expect("the address book loads");
+        tracker.save_peers().expect("the address book is saved");
+        drop(tracker);
+        let mut store = Store::new(&path).expect("the peer store opens");
+        let kept: Vec<Registration> = store.namespace(REGISTRY_NAMESPACE).and_then(|store| store.load()).expect("the registry loads");
+        let addresses: Vec<&str> = kept.iter().map(|registration| registration.neighbour.address.as_str()).collect();
+        assert_eq!(addresses, vec!["127.0.0.1:9101"]);
+        info!("A restarted tracker kept the registration that had not expired");
+
+        let identity = Identity::new();
+        let key = [7u8; 32];
+        let network = NetworkId::default();
+        let proof = identity.prove(&key, &network);
+        assert_eq!(proof.id(), identity.id());
+        assert_eq!(identity::id_of(&identity.public_key()), identity.id());
+        assert!(proof.verify(&key, &network));
+        assert!(!proof.verify(&[8u8; 32], &network));
+        assert!(!proof.verify(&key, &NetworkId::testnet()));
+        let mut forged = proof.clone();
+        forged.public_key = Identity::new().public_key();
+        assert!(!forged.verify(&key, &network));
+        fs::remove_dir_all(&dir).expect("the test directory is removed");
+        info!("Identity test passed");
+    }
+}
@@ -0,0 +1,65 @@
+pub mod test_keys {
+
+    use crate::{
+        Chain,
+        Wallet,
+        bench::bench::bench,
+        chain::{chain::chain::KeyPage, network::network::NetworkId},
+        explorer::{explorer::explorer::Index, memory_index::memory_index::MemoryIndex},
+        record::record::record::Record,
+    };
+
+    use tracing::info;
+
+    /// Lists every key of `index` starting with `prefix`, `limit` at a time, checking each
+    /// page matches the one `chain` lists.
+    fn list_all(chain: &Chain, index: &dyn Index, prefix: &str, limit: usize) -> Vec<String> {
+        let mut keys = vec![];
+        let mut cursor = None;
+        loop {
+            let page = chain.list_keys(prefix, limit, cursor.as_deref());
+            assert_eq!(index.keys(prefix, limit, cursor.as_deref()).expect("memory indices answer"), page);
+            assert!(page.keys.len() <= limit);
+            keys.extend(page.keys);
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => return keys,
+            }
+        }
+    }
+
+    /// Test function to check `Chain::list_keys` pages through the keys records are
+    /// written under, in order and without deleted ones, and that the explorer's index
+    /// lists the same.
+    pub fn test_keys() {
+        let owner = Wallet::new();
+        let write = |key: &str, value: &str| owner.sign_record(Record::new(key.to_string(), value.to_string(), owner.get_pub_key()), &NetworkId::default());
+        let mut chain = Chain::new();
+        let mut miner = bench::miner_for(&chain);
+        for i in 0..7 {
+            miner.push_record(write(&format!("app/user-{}", i), "alice"));
+        }
+        miner.push_record(write("other/config", "on"));
+        bench::add_next(&mut chain, &mut miner);
+        miner.push_record(write("app/user-3", ""));
+        miner.push_record(write("app/user-5", "bob"));
+        bench::add_next(&mut chain, &mut miner);
+
+        let mut index = MemoryIndex::new();
+        index.update(&chain).expect("memory indices update");
+        let keys = list_all(&chain, &index, "app/", 2);
+        assert_eq!(keys, ["app/user-0", "app/user-1", "app/user-2", "app/user-4", "app/user-5", "app/user-6"]);
+        assert_eq!(list_all(&chain, &index, "", 3).len(), keys.len() + 1);
+        assert!(list_all(&chain, &index, "none/", 5).is_empty());
+        let page = chain.list_keys("app/", 10, Some("app/user-4"));
+        assert_eq!(page, KeyPage { keys: vec!["app/user-5".to_string(), "app/user-6".to_string()], next: None });
+        info!("Listed {} live keys, 2 at a time", keys.len());
+
+        // Writing a deleted key again lists it again.
+        miner.push_record(write("app/user-3", "carol"));
+        bench::add_next(&mut chain, &mut miner);
+        index.update(&chain).expect("memory indices update");
+        assert_eq!(list_all(&chain, &index, "app/", 4).len(), keys.len() + 1);
+        info!("Keys test passed");
+    }
+}
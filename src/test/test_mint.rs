@@ -0,0 +1,94 @@
+pub mod test_mint {
+
+    use crate::{
+        Chain,
+        Transaction,
+        Wallet,
+        bench::bench,
+        chain::{
+            chain::chain::BlockCheckError,
+            config::config::{ChainConfig, MintPolicy},
+            genesis::genesis::{Genesis, GenesisError},
+        },
+        miner::miner::miner::ZERO_WALLET_PK,
+        token::token::token::Token,
+    };
+
+    use base64::{Engine as _, engine::general_purpose};
+    use tracing::info;
+
+    fn rules(mint: MintPolicy) -> ChainConfig {
+        ChainConfig { mint, ..ChainConfig::default() }
+    }
+
+    /// Test function to check coins are only created the way the mint policy of the chain
+    /// allows: given away by the genesis block, paid to miners and sent by mint
+    /// authorities, whose transactions miners take while they refuse anyone else's, and
+    /// chains holding mints of anyone else or forged in an authority's name are refused.
+    pub fn test_mint() {
+        let holder = Wallet::new();
+        let address = general_purpose::STANDARD.encode(holder.get_pub_key());
+        let genesis = Genesis::new("mintnet", 0, 1).allocate(address.clone(), 2);
+        let twice = genesis.clone().allocate(address.clone(), 1);
+        assert!(matches!(twice.validate(), Err(GenesisError::DuplicateAllocation(_))));
+        let genesis = genesis.chain();
+        rules(MintPolicy::default()).verify(&genesis).expect("premines are allowed by default");
+        let no_premine = MintPolicy { premine: false, ..MintPolicy::default() };
+        assert!(matches!(rules(no_premine).verify(&genesis), Err(BlockCheckError::InvalidMint { index: 0, .. })));
+        info!("The genesis block only gives coins away if the policy allows it");
+
+        // Miners are paid the coin of their block only, and not at all without coinbase.
+        let mut chain = bench::bench::synthetic_chain(2);
+        rules(MintPolicy::default()).verify(&chain).expect("mined chains pay their miners");
+        let no_coinbase = MintPolicy { coinbase: false, ..MintPolicy::default() };
+        assert!(matches!(rules(no_coinbase.clone()).verify(&chain), Err(BlockCheckError::InvalidMint { index: 2, .. })));
+        let mut blocks = chain.get_blocks();
+        let last = blocks.last_mut().expect("chains hold blocks");
+        let forged: String = Transaction::new(ZERO_WALLET_PK.to_vec(), holder.get_pub_key(), vec![Token::default()]).into();
+        last.data.push_str(&forged);
+        let forged = Chain::from_blocks_unverified(blocks, chain.difficulty);
+        assert!(matches!(rules(MintPolicy::default()).verify(&forged), Err(BlockCheckError::InvalidMint { index: 3, .. })));
+        let mut miner = bench::bench::miner_for(&chain);
+        miner.set_mint_policy(no_coinbase);
+        let digest = bench::bench::mine_next(&chain, &mut miner);
+        assert!(digest.get_block().get_transactions().is_empty());
+        info!("Miners are only paid the coin of their block, if the policy allows it");
+
+        // Mint authorities create coins, anyone else is refused.
+        let authority = Wallet::new();
+        let mint = MintPolicy {
+            authorities: vec![general_purpose::STANDARD.encode(authority.get_pub_key())],
+            ..MintPolicy::default()
+        };
+        let network = chain.network().clone();
+        let minted = authority.mint(holder.get_pub_key(), 0, &network);
+        assert!(MintPolicy::default().check_transaction(minted.clone(), chain.blocks()).is_err());
+        assert!(mint.check_transaction(holder.mint(authority.get_pub_key(), 0, &network), chain.blocks()).is_err());
+        let mut miner = bench::bench::miner_for(&chain);
+        miner.set_mint_policy(mint.clone());
+        miner.push_transaction(minted.clone());
+        bench::bench::add_next(&mut chain, &mut miner);
+        assert_eq!(chain.coins_of(&holder.get_pub_key()), minted.coins);
+        rules(mint.clone()).verify(&chain).expect("mints of authorities follow the policy");
+        assert!(mint.check_transaction(minted, chain.blocks()).is_err());
+
+        // Blocks mined under another policy are checked against the one of the chain.
+        let mut lenient = bench::bench::miner_for(&chain);
+        lenient.set_mint_policy(MintPolicy {
+            authorities: vec![general_purpose::STANDARD.encode(holder.get_pub_key())],
+            ..MintPolicy::default()
+        });
+        lenient.push_transaction(holder.mint(holder.get_pub_key(), 0, &network));
+        let mut minting = chain.clone();
+        bench::bench::add_next(&mut minting, &mut lenient);
+        assert!(matches!(rules(mint.clone()).verify(&minting), Err(BlockCheckError::InvalidMint { .. })));
+        let impostor = Transaction::new(authority.get_pub_key(), holder.get_pub_key(), vec![Token::default()]).with_nonce(1);
+        let mut forging = bench::bench::miner_for(&chain);
+        forging.set_mint_policy(mint.clone());
+        forging.push_transaction(holder.sign(impostor, &network));
+        let mut forged = chain.clone();
+        bench::bench::add_next(&mut forged, &mut forging);
+        assert!(matches!(rules(mint).verify(&forged), Err(BlockCheckError::InvalidSignature { .. })));
+        info!("Mint test passed");
+    }
+}
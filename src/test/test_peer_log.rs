@@ -0,0 +1,118 @@
+pub mod test_peer_log {
+
+    use crate::{
+        clock::clock::clock::{self, MockClock},
+        node::{
+            neighbour::neighbour::{Neighbour, Role},
+            peer_log::peer_log::{PeerLog, ANTI_ENTROPY_ROUNDS, MAX_CHANGES, MAX_UPDATES},
+            relay::relay::{Inventory, RelayedNeighbour, SeenCache, ANNOUNCED_EXPIRY, DEFAULT_MAX_HOPS},
+        },
+    };
+
+    use std::{sync::Arc, time::Duration};
+
+    use tracing::info;
+    use uuid::Uuid;
+
+    fn neighbour(port: u16) -> Neighbour {
+        Neighbour::new(Uuid::new_v4(), format!("127.0.0.1:{}", port), Role::Node)
+    }
+
+    fn ports(neighbours: &[RelayedNeighbour]) -> Vec<String> {
+        let mut addresses: Vec<String> = neighbours.iter().map(|relayed| relayed.neighbour.address.clone()).collect();
+        addresses.sort();
+        addresses
+    }
+
+    /// Test function to check neighbour gossip only carries the neighbours a peer was not
+    /// sent yet, and falls back to every neighbour on first contact, every
+    /// `ANTI_ENTROPY_ROUNDS` rounds and once a peer fell behind the changes remembered,
+    /// never more than `MAX_UPDATES` a round. Neighbours are sent with one forward less,
+    /// and no longer once none are left. Relayed neighbours and inventories of nodes
+    /// predating hop limits are still taken, and stale announcements make room for new ones.
+    pub fn test_peer_log() {
+        let mut log = PeerLog::new();
+        let peer = neighbour(9000);
+        let (first, second) = (neighbour(9001), neighbour(9002));
+        let mut neighbours = vec![peer.clone(), first.clone(), second.clone()];
+        for neighbour in &neighbours {
+            log.record(neighbour.clone(), DEFAULT_MAX_HOPS);
+        }
+
+        // First contact: every neighbour but the peer itself.
+        let updates = log.updates_for(&peer, &neighbours);
+        assert!(updates.iter().all(|relayed| relayed.ttl == DEFAULT_MAX_HOPS - 1));
+        assert_eq!(ports(&updates), vec![first.address.clone(), second.address.clone()]);
+        assert!(log.updates_for(&peer, &neighbours).is_empty());
+        let third = neighbour(9003);
+        log.record(third.clone(), 2);
+        neighbours.push(third.clone());
+        let updates = log.updates_for(&peer, &neighbours);
+        assert_eq!(ports(&updates), vec![third.address.clone()]);
+        assert_eq!(updates[0].ttl, 1);
+        log.record(first.clone(), 0);
+        assert_eq!(ports(&log.updates_for(&peer, &neighbours)), vec![first.address.clone()]);
+        info!("Only new and changed neighbours were sent");
+
+        // Neighbours without forwards left are kept but not sent.
+        let last = neighbour(9004);
+        log.record(last.clone(), 0);
+        neighbours.push(last);
+        assert!(log.updates_for(&peer, &neighbours).is_empty());
+
+        // Anti-entropy: every neighbour again once in a while.
+        let mut rounds = 0;
+        while log.updates_for(&peer, &neighbours).is_empty() {
+            rounds += 1;
+        }
+        assert!(rounds < ANTI_ENTROPY_ROUNDS);
+        assert!(log.updates_for(&peer, &neighbours).is_empty());
+
+        // Forgotten peers start over, and so do peers behind the changes remembered.
+        log.forget(&peer.id);
+        assert_eq!(log.updates_for(&peer, &neighbours).len(), neighbours.len() - 2);
+        for port in 0..=MAX_CHANGES as u16 {
+            log.record(neighbour(10000 + port), DEFAULT_MAX_HOPS);
+        }
+        assert_eq!(log.updates_for(&peer, &neighbours).len(), neighbours.len() - 2);
+        assert!(log.version() > MAX_CHANGES as u64);
+        info!("Peers behind the changes remembered were sent every neighbour");
+
+        // No more than `MAX_UPDATES` neighbours a round: changes left over come next round.
+        let many: Vec<Neighbour> = (0..2 * MAX_UPDATES as u16).map(|port| neighbour(20000 + port)).collect();
+        for neighbour in &many {
+            log.record(neighbour.clone(), DEFAULT_MAX_HOPS);
+        }
+        assert_eq!(log.updates_for(&peer, &many).len(), MAX_UPDATES);
+        assert_eq!(log.updates_for(&peer, &many).len(), MAX_UPDATES);
+        assert!(log.updates_for(&peer, &many).is_empty());
+        log.forget(&peer.id);
+        assert_eq!(log.updates_for(&peer, &many).len(), MAX_UPDATES);
+        info!("Peers were sent a bounded number of neighbours a round");
+
+        let legacy = serde_json::to_string(&peer).expect("neighbours serialize");
+        let relayed: RelayedNeighbour = serde_json::from_str(&legacy).expect("bare neighbours are taken");
+        assert_eq!((relayed.neighbour.address, relayed.ttl), (peer.address.clone(), DEFAULT_MAX_HOPS));
+        let relayed = RelayedNeighbour { neighbour: peer.clone(), ttl: 3 };
+        let relayed: RelayedNeighbour = serde_json::from_str(&serde_json::to_string(&relayed).unwrap()).expect("relayed neighbours round-trip");
+        assert_eq!(relayed.ttl, 3);
+        let inventory: Inventory = serde_json::from_str(r#"["a","b"]"#).expect("bare ids are taken");
+        assert_eq!(inventory, Inventory { ids: vec!["a".to_string(), "b".to_string()], ttl: DEFAULT_MAX_HOPS });
+        let inventory: Inventory = serde_json::from_str(r#"{"ids":["a"],"ttl":2}"#).expect("inventories are taken");
+        assert_eq!(inventory.ttl, 2);
+        info!("Took the messages of nodes predating hop limits");
+
+        let mock = MockClock::new(1_700_000_000);
+        let _clock = clock::install_scoped(Arc::new(mock.clone()));
+        let mut seen = SeenCache::new(2);
+        seen.announce("a".to_string(), 1);
+        seen.announce("b".to_string(), 1);
+        seen.announce("c".to_string(), 1);
+        assert_eq!(seen.take_announced("c"), None);
+        mock.advance(Duration::from_secs(ANNOUNCED_EXPIRY));
+        seen.announce("b".to_string(), 2);
+        seen.announce("c".to_string(), 1);
+        assert_eq!((seen.take_announced("a"), seen.take_announced("b"), seen.take_announced("c")), (None, Some(2), Some(1)));
+        info!("Peer log test passed");
+    }
+}
@@ -0,0 +1,118 @@
+pub mod test_pool {
+
+    use crate::{
+        Chain,
+        Wallet,
+        bench::bench::bench,
+        chain::block::block::block::BLOCK_VERSION,
+        node::{
+            config::config::PoolConfig,
+            neighbour::neighbour::Role,
+            node::node::Node,
+            pool::pool::{Job, Pool, Share, ShareOutcome, PAYOUT_UNIT},
+        },
+    };
+
+    use std::{sync::atomic::AtomicBool, time::Duration};
+
+    use base64::{Engine as _, engine::general_purpose};
+    use tracing::info;
+
+    /// Test function to check a pool hands out disjoint nonce ranges, credits the shares
+    /// of its workers and splits the prizes of the blocks they find between them, and
+    /// that a coordinator pays a worker that found blocks for it.
+    pub async fn test_pool() {
+        let mut chain = Chain::new();
+        let mut pool = Pool::new();
+        assert!(pool.join("127.0.0.1:9001", vec![1]).is_none());
+        pool.join("127.0.0.1:9002", vec![2]);
+        let jobs = pool.assign(chain.get_last_block(), chain.difficulty, chain.network().clone(), BLOCK_VERSION);
+        assert_eq!(jobs.len(), 2);
+        let (first, second) = (&jobs[0].1, &jobs[1].1);
+        assert!(first.end <= second.start || second.end <= first.start);
+        let late = pool.join("127.0.0.1:9003", vec![3]).expect("there is a template to mine on");
+        assert!(jobs.iter().all(|(_, job)| late.end <= job.start || job.end <= late.start));
+
+        // Shares are checked against the worker's job.
+        let (address, job) = &jobs[0];
+        let never = AtomicBool::new(false);
+        let (found, solved) = job.search(job.start, job.end, &never);
+        assert!(solved);
+        let nonce = *found.last().expect("the block was solved");
+        assert!(matches!(pool.submit("127.0.0.1:9004", &Share { job: job.id, nonce }), ShareOutcome::Stale));
+        assert!(matches!(pool.submit(address, &Share { job: job.id + 1, nonce }), ShareOutcome::Stale));
+        assert!(matches!(pool.submit(address, &Share { job: job.id, nonce: jobs[1].1.start }), ShareOutcome::Invalid));
+        let ShareOutcome::Solved { previous, nonce, .. } = pool.submit(address, &Share { job: job.id, nonce }) else {
+            panic!("the share solves the block");
+        };
+        assert!(matches!(pool.submit(address, &Share { job: job.id, nonce }), ShareOutcome::Duplicate));
+        let mut miner = bench::miner_for(&chain);
+        let digest = miner.solved(&previous, nonce).expect("the chain meta is set");
+        chain.add_block(digest).expect("blocks solved by workers extend the chain");
+        info!("A worker solved block {}", chain.get_last_block().index);
+
+        // Prizes are split by shares, paid out once worth a coin.
+        let mut shares = Pool::new();
+        shares.join("127.0.0.1:9001", vec![1]);
+        shares.join("127.0.0.1:9002", vec![2]);
+        let jobs = shares.assign(chain.get_last_block(), 0, chain.network().clone(), BLOCK_VERSION);
+        let credit = |pool: &mut Pool, address: &str, job: &Job, n: u64| {
+            for nonce in job.start..job.start + n {
+                pool.submit(address, &Share { job: job.id, nonce });
+            }
+        };
+        credit(&mut shares, &jobs[0].0, &jobs[0].1, 3);
+        credit(&mut shares, &jobs[1].0, &jobs[1].1, 1);
+        let payout = |address: &str| if address == "127.0.0.1:9001" { vec![1] } else { vec![2] };
+        assert_eq!(shares.shares_of(&payout(&jobs[0].0)), 3);
+        assert!(shares.settle().is_empty());
+        assert_eq!(shares.credit_of(&payout(&jobs[0].0)), PAYOUT_UNIT * 3 / 4);
+        assert_eq!(shares.credit_of(&payout(&jobs[1].0)), PAYOUT_UNIT / 4);
+        let jobs = shares.assign(chain.get_last_block(), 0, chain.network().clone(), BLOCK_VERSION);
+        credit(&mut shares, &jobs[0].0, &jobs[0].1, 1);
+        assert_eq!(shares.settle(), vec![(payout(&jobs[0].0), 1)]);
+        info!("Prizes were split by shares");
+
+        // A worker finds blocks for a coordinator, which pays it.
+        let worker_wallet = Wallet::new();
+        let mut coordinator = Node::builder()
+            .with_role(Role::Miner)
+            .with_address("127.0.0.1:8121")
+            .with_pool(PoolConfig::Coordinator)
+            .build()
+            .expect("miners coordinate pools");
+        let mut chain_updates = coordinator.watch_chain();
+        let stop_coordinator = coordinator.stop_handle();
+        tokio::spawn(async move {
+            let _ = coordinator.node_loop().await;
+        });
+        let mut worker = Node::builder()
+            .with_address("127.0.0.1:8123")
+            .with_trackers(vec!["127.0.0.1:8121".to_owned()])
+            .with_pool(PoolConfig::Worker {
+                coordinator: "127.0.0.1:8121".to_owned(),
+                payout: general_purpose::STANDARD.encode(worker_wallet.get_pub_key()),
+            })
+            .build()
+            .expect("nodes work for pools");
+        worker.enter_network().await.expect("the worker greets the coordinator");
+        let stop_worker = worker.stop_handle();
+        tokio::spawn(async move {
+            let _ = worker.node_loop().await;
+        });
+        let paid = tokio::time::timeout(Duration::from_secs(120), async {
+            loop {
+                chain_updates.changed().await.expect("the coordinator runs");
+                let chain = chain_updates.borrow_and_update().clone();
+                let paid = chain.coins_of(&worker_wallet.get_pub_key());
+                if !paid.is_empty() {
+                    break (chain.len(), paid.len());
+                }
+            }
+        }).await.expect("the coordinator pays its worker");
+        info!("The worker was paid {} coins by block {}", paid.1, paid.0);
+        stop_coordinator.stop();
+        stop_worker.stop();
+        info!("Pool test passed");
+    }
+}
@@ -0,0 +1,63 @@
+pub mod test_protocol {
+
+    use crate::node::{
+        event::event::NodeEvent,
+        gossip::gossip,
+        neighbour::neighbour::Role,
+        node::node::Node,
+        protocol::protocol::{self, ProtocolMsg, UnknownProtocol},
+        transport::transport,
+    };
+
+    use std::time::Duration;
+
+    use tracing::info;
+
+    /// Test function to check protocol bytes map to their message kind and back, that
+    /// unknown bytes are told apart from extensions, and that a node reports messages of
+    /// an unknown protocol with a `NodeEvent::UnknownProtocol` rather than dropping them
+    /// silently. Once the node shut down, its address can be bound again.
+    pub async fn test_protocol() {
+        for (i, message) in ProtocolMsg::ALL.iter().enumerate() {
+            assert_eq!(*message as u8 as usize, i + 1);
+            assert_eq!(ProtocolMsg::try_from(*message as u8), Ok(*message));
+            assert_eq!(protocol::name(*message as u8), message.name());
+        }
+        assert_eq!(ProtocolMsg::try_from(0), Err(UnknownProtocol(0)));
+        assert!(!UnknownProtocol(ProtocolMsg::ALL.len() as u8 + 1).is_extension());
+        assert!(UnknownProtocol(*protocol::EXTENSIONS.start()).is_extension());
+        assert_eq!(protocol::name(*protocol::EXTENSIONS.end()), "extension");
+        info!("Every protocol byte maps to its message kind");
+
+        let mut node = Node::builder()
+            .with_role(Role::Node)
+            .with_address("127.0.0.1:8141")
+            .build()
+            .expect("nodes build");
+        let mut events = node.subscribe();
+        let stop = node.stop_handle();
+        let looping = tokio::spawn(async move {
+            let _ = node.node_loop().await;
+            node
+        });
+        let socket = transport::bind("127.0.0.1:8142").await.expect("the socket binds");
+        let unknown = tokio::time::timeout(Duration::from_secs(10), async {
+            for message in [vec![ProtocolMsg::ALL.len() as u8 + 1], vec![*protocol::EXTENSIONS.start(), 1, 2, 3]] {
+                gossip::send_message(&*socket, &message, "127.0.0.1:8141").await.expect("the message is sent");
+                let extension = loop {
+                    if let Ok(NodeEvent::UnknownProtocol { from, protocol, extension }) = events.recv().await {
+                        assert_eq!((from.as_str(), protocol), ("127.0.0.1:8142", message[0]));
+                        break extension;
+                    }
+                };
+                assert_eq!(extension, protocol::EXTENSIONS.contains(&message[0]));
+            }
+        }).await;
+        assert!(unknown.is_ok(), "no unknown protocol was reported");
+        stop.stop();
+        let mut node = looping.await.expect("the node loop returns");
+        node.shutdown().await.expect("the node shuts down");
+        transport::bind("127.0.0.1:8141").await.expect("the node closed its socket");
+        info!("Protocol test passed");
+    }
+}
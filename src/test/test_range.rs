@@ -0,0 +1,80 @@
+pub mod test_range {
+
+    use crate::{
+        Chain,
+        app::client::client,
+        bench::bench::bench,
+        chain::block::block::block::Block,
+        node::{
+            neighbour::neighbour::Role,
+            node::node::Node,
+            sync::sync::BLOCKS_PER_REQUEST,
+        },
+    };
+
+    use tracing::info;
+
+    fn hashes(blocks: &[Block]) -> Vec<String> {
+        blocks.iter().map(|block| block.hash.clone()).collect()
+    }
+
+    /// Test function to check blocks are downloaded by range, over more requests than one
+    /// answer holds, by nodes and clients alike, that ranges past the end of the chain of
+    /// the peer stop where it does, and that headers-first sync downloads blocks so.
+    pub async fn test_range() {
+        let chain = bench::synthetic_chain(3 * BLOCKS_PER_REQUEST);
+        let genesis = Chain::from_blocks(chain.blocks()[..1].to_vec(), chain.difficulty).expect("the genesis block is a chain");
+        // Every node is trusted, so the tracker hands out no admission puzzles, and nodes
+        // gossip every second without slowing down, so requests are read promptly.
+        let builder = |address: &str, role: Role| Node::builder()
+            .with_role(role)
+            .with_address(address)
+            .with_admission_difficulty(0)
+            .with_gossip_interval(1)
+            .with_max_gossip_interval(1)
+            .with_chain(genesis.clone());
+        let mut server = builder("127.0.0.1:8151", Role::Node).with_chain(chain.clone()).build().expect("nodes build");
+        let mut stops = vec![server.stop_handle()];
+        tokio::spawn(async move {
+            let _ = server.node_loop().await;
+        });
+
+        let (start, end) = (chain.blocks()[2].index, chain.blocks()[2 * BLOCKS_PER_REQUEST].index);
+        let expected: Vec<Block> = chain.blocks_from(start).take_while(|block| block.index < end).cloned().collect();
+        let mut node = builder("127.0.0.1:8152", Role::Node).build().expect("nodes build");
+        let fetched = node.fetch_blocks("127.0.0.1:8151", start..end).await.expect("the server answers");
+        assert!(expected.len() > BLOCKS_PER_REQUEST);
+        assert_eq!(hashes(&fetched), hashes(&expected));
+        info!("Fetched blocks {} to {} in batches of {}", start, end - 1, BLOCKS_PER_REQUEST);
+
+        let tip = chain.get_last_block().index;
+        let fetched = client::fetch_blocks("127.0.0.1:8151", tip - 1..tip + 10).await.expect("the server answers");
+        assert_eq!(hashes(&fetched.blocks), hashes(&chain.blocks()[chain.len() - 2..]));
+        assert_eq!(fetched.difficulty, chain.difficulty);
+        let past = node.fetch_blocks("127.0.0.1:8151", tip + 1..tip + 10).await.expect("the server answers");
+        assert!(past.is_empty());
+        info!("Ranges stop where the chain does");
+
+        // A node joining through a tracker downloads the chain of the node it meets.
+        let trackers = vec!["127.0.0.1:8153".to_string()];
+        let mut tracker = builder("127.0.0.1:8153", Role::Tracker).build().expect("trackers build");
+        let mut seed = builder("127.0.0.1:8155", Role::Node).with_chain(chain.clone()).with_trackers(trackers.clone()).build().expect("nodes build");
+        stops.extend([tracker.stop_handle(), seed.stop_handle()]);
+        tokio::spawn(async move {
+            let _ = tracker.node_loop().await;
+        });
+        tokio::spawn(async move {
+            let _ = seed.enter_and_node_loop().await;
+        });
+        client::node_info("127.0.0.1:8155").await.expect("the seed enters the network");
+        let mut joiner = builder("127.0.0.1:8154", Role::Node).with_trackers(trackers).build().expect("nodes build");
+        joiner.enter_network().await.expect("the node enters the network");
+        let synchronized = joiner.sync_chain().await.expect("the node synchronizes");
+        assert_eq!(synchronized, chain.len() - 1);
+        assert_eq!(joiner.status().tip_hash, chain.get_last_block().hash);
+        for stop in stops {
+            stop.stop();
+        }
+        info!("Range test passed");
+    }
+}
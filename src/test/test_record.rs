@@ -0,0 +1,202 @@
+pub mod test_record {
+
+    use crate::{
+        Chain,
+        Wallet,
+        bench::bench::bench,
+        chain::{
+            block::block::block::{self, InvalidRecordErr},
+            chain::chain::BlockCheckError,
+            config::config::ChainConfig,
+            network::network::NetworkId,
+        },
+        miner::miner::miner::ZERO_WALLET_PK,
+        record::record::record::{Record, RecordRules},
+        transaction::transaction::transaction::Transaction,
+        node::{
+            neighbour::neighbour::{Neighbour, Role},
+            gossip::gossip,
+            node::node::Node,
+            receiver::receiver::Receiver,
+        },
+    };
+
+    use std::{
+        collections::HashMap,
+        time::Duration,
+        sync::Arc,
+    };
+
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use tracing::info;
+
+    /// Creates a signed mock record owned by `wallet`.
+    ///
+    /// # Arguments
+    /// * `wallet` - The wallet owning the record.
+    /// * `i` - Used to make up the key and value.
+    ///
+    /// # Returns
+    /// * `Record` - The signed record.
+    fn make_up_record(wallet: &Wallet, i: u32) -> Record {
+        let record = Record::new(
+            format!("key-{}", i),
+            format!("value;{}", i), // Values may contain the wire separator.
+            wallet.get_pub_key(),
+        );
+        wallet.sign_record(record, &NetworkId::default())
+    }
+
+    /// Repeatedly sends mock records straight to a miner.
+    ///
+    /// # Arguments
+    /// * `address` - The address records are sent from.
+    /// * `miner` - The miner to send the records to.
+    /// * `iterations` - Number of records to send.
+    async fn send_record_loop(address: Arc<str>, miner: Neighbour, iterations: u32) {
+        let wallet = Wallet::new();
+        for i in 0..iterations {
+            let record = make_up_record(&wallet, i);
+            info!("sending record: {}", record);
+            if let Err(e) = gossip::send_record(address.clone(), &miner, record).await {
+                info!("Error sending record: {}", e);
+            }
+
+            // Sleep for 500 milliseconds between sends
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Test function to simulate record gossip between nodes.
+    ///
+    /// This function spawns a tracker and a miner, then sends mock records to the miner
+    /// over `ProtocolMsg::Record`, the way a remote node would.
+    pub async fn test_record_gossip() {
+        info!("Starting record gossip test");
+
+        // Create the tracker
+        let (_tx1, rx1) = mpsc::channel::<String>(1024);
+        let mut tracker = Node::new(
+            Role::Tracker,
+            "127.0.0.1:8091".to_owned(),
+            None,
+            Receiver::new(rx1),
+        );
+
+        // Spawn the tracker's event loop
+        tokio::spawn(async move {
+            let _ = tracker.node_loop().await;
+        });
+
+        // Create the miner, which enters the network through the tracker
+        let (_tx2, rx2) = mpsc::channel::<String>(1024);
+        let mut miner = Node::new(
+            Role::Miner,
+            "127.0.0.1:8093".to_owned(),
+            Some(vec!["127.0.0.1:8091".to_owned()]),
+            Receiver::new(rx2),
+        );
+        let mut events = miner.subscribe();
+
+        tokio::spawn(async move {
+            let _ = miner.enter_and_node_loop().await;
+        });
+
+        // Give some time for the miner to be added to the network
+        tokio::time::sleep(Duration::from_secs(3)).await;
+
+        tokio::spawn(async move {
+            while let Ok(event) = events.recv().await {
+                if let crate::node::event::event::NodeEvent::RecordReceived(record) = event {
+                    info!("miner accepted record: {}", record);
+                }
+            }
+        });
+
+        let miner_neighbour = Neighbour::new(Uuid::nil(), "127.0.0.1:8093".to_owned(), Role::Miner);
+        send_record_loop("127.0.0.1:8094".into(), miner_neighbour, 100).await;
+
+        // Keep the nodes alive long enough to mine the records
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    }
+
+    /// Test function to check records are held to their size limit and cost, by the
+    /// checks of submissions, by the miner and by chain validation.
+    pub fn test_record_rules() {
+        let rules = RecordRules { max_value_size: 64, bytes_per_coin: 16, enforced_from: None };
+        let mut chain = Chain::new();
+        let mut miner = bench::miner_for(&chain);
+        bench::add_next(&mut chain, &mut miner);
+        let owner = miner.wallet.get_pub_key();
+        let network = chain.network().clone();
+        let record = |wallet: &Wallet, key: &str, size: usize| wallet.sign_record(Record::new(key.to_string(), "v".repeat(size), owner.clone()), &network);
+        assert_eq!(rules.cost(&record(&miner.wallet, "free", 15)), 0);
+        assert_eq!(rules.cost(&record(&miner.wallet, "paid", 32)), 2);
+
+        block::check_record(record(&miner.wallet, "free", 15), &network, &rules, chain.blocks()).expect("short values are free");
+        block::check_record(record(&miner.wallet, "paid", 32), &network, &rules, chain.blocks()).expect("costs are paid in the block");
+        assert!(matches!(
+            block::check_record(record(&miner.wallet, "large", 65), &network, &rules, chain.blocks()),
+            Err(InvalidRecordErr::ValueTooLarge { size: 65, limit: 64 })
+        ));
+        let stranger = Wallet::new();
+        let mut impostor = record(&miner.wallet, "free", 15);
+        impostor.signature = stranger.sign_record(impostor.clone(), &network).signature;
+        assert!(matches!(
+            block::check_record(impostor, &network, &rules, chain.blocks()),
+            Err(InvalidRecordErr::InvalidSignature)
+        ));
+        assert!(matches!(
+            block::check_record(record(&miner.wallet, "free", 15), &NetworkId::testnet(), &rules, chain.blocks()),
+            Err(InvalidRecordErr::InvalidSignature)
+        ));
+        let mut burned = HashMap::from([(owner.clone(), 1)]);
+        assert!(matches!(
+            block::pay_record(&record(&miner.wallet, "paid", 32), &rules, &mut burned),
+            Err(InvalidRecordErr::Unpaid { cost: 2, paid: 1 })
+        ));
+        block::pay_record(&record(&miner.wallet, "paid", 16), &rules, &mut burned).expect("one coin was burned");
+        assert!(matches!(
+            block::pay_record(&record(&miner.wallet, "again", 16), &rules, &mut burned),
+            Err(InvalidRecordErr::Unpaid { cost: 1, paid: 0 })
+        ));
+        info!("Records were checked against their size limit and cost");
+
+        // Miners drop the records over the size limit, and hold back the ones their owner
+        // did not burn enough for in the block. The owner holds the prize of the block it
+        // mined: one coin.
+        miner.set_record_rules(rules);
+        let coin = chain.coins_of(&owner)[0];
+        let burn = Transaction::new(owner.clone(), ZERO_WALLET_PK.to_vec(), vec![coin]).with_nonce(chain.next_nonce(&owner));
+        miner.push_transaction(miner.wallet.sign(burn, chain.network()));
+        miner.push_record(record(&miner.wallet, "paid", 32));
+        miner.push_record(record(&miner.wallet, "large", 65));
+        miner.push_record(record(&miner.wallet, "kept", 16));
+        bench::add_next(&mut chain, &mut miner);
+        let keys: Vec<String> = chain.get_last_block().records.iter().map(|record| record.key.clone()).collect();
+        assert_eq!(keys, vec!["kept".to_string()]);
+        let waiting: Vec<String> = miner.check_records().into_iter().map(|record| record.key).collect();
+        assert_eq!(waiting, vec!["paid".to_string()]);
+        assert!(chain.coins_of(&owner).iter().all(|held| *held != coin));
+
+        // Chains holding records breaking the rules are refused from the height they are
+        // enforced from.
+        let mut lenient = bench::miner_for(&chain);
+        lenient.push_record(record(&miner.wallet, "unpaid", 48));
+        bench::add_next(&mut chain, &mut lenient);
+        let index = chain.get_last_block().index;
+        ChainConfig::default().verify(&chain).expect("the default rules let 48 bytes through for free");
+        let unset = ChainConfig { records: rules, ..ChainConfig::default() };
+        unset.verify(&chain).expect("chains are not held to rules enforced from no height");
+        let later = RecordRules { enforced_from: Some(index + 1), ..rules };
+        ChainConfig { records: later, ..ChainConfig::default() }.verify(&chain).expect("the unpaid record predates the rules");
+        let strict = ChainConfig { records: RecordRules { enforced_from: Some(0), ..rules }, ..ChainConfig::default() };
+        assert!(matches!(
+            strict.verify(&chain),
+            Err(BlockCheckError::InvalidRecord { error: InvalidRecordErr::Unpaid { cost: 3, paid: 0 }, .. })
+        ));
+        info!("Record rules test passed");
+    }
+}
@@ -0,0 +1,154 @@
+pub mod test_sim {
+
+    use crate::{
+        bench::bench::bench,
+        chain::chain::chain::Chain,
+        node::{
+            metrics::metrics::Metrics,
+            neighbour::neighbour::Role,
+            node::node::Node,
+            simnet::simnet::SimNetwork,
+            transport::transport,
+        },
+    };
+
+    use std::{
+        sync::Arc,
+        time::Duration,
+    };
+
+    use tracing::info;
+
+    const TRACKER_ADDRESS: &str = "10.0.0.1:8000";
+    /// Blocks mined on the chain one node brings to the network.
+    const LONGER_CHAIN: usize = 10;
+    /// Time (in seconds) the nodes get to find neighbours, or to agree on a chain.
+    const CONVERGENCE_TIMEOUT: u64 = 60;
+
+    /// Address of the `i`-th simulated node.
+    fn node_address(i: usize) -> String {
+        format!("10.0.{}.{}:8000", 1 + i / 250, 2 + i % 250)
+    }
+
+    /// Logs how many neighbours the nodes have, and how many have none.
+    fn report(label: &str, metrics: &[Arc<Metrics>]) {
+        let peers: Vec<u64> = metrics.iter().map(|metrics| metrics.snapshot().peers).collect();
+        let isolated = peers.iter().filter(|peers| **peers == 0).count();
+        let average = peers.iter().sum::<u64>() as f64 / peers.len().max(1) as f64;
+        info!("{}: {:.1} neighbours on average, {} isolated nodes", label, average, isolated);
+    }
+
+    /// Heights of the chains of the nodes.
+    fn heights(metrics: &[Arc<Metrics>]) -> Vec<u64> {
+        metrics.iter().map(|metrics| metrics.snapshot().chain_height).collect()
+    }
+
+    /// Waits up to `CONVERGENCE_TIMEOUT` seconds for `condition` to hold.
+    async fn settle(condition: impl Fn() -> bool) -> bool {
+        for _ in 0..CONVERGENCE_TIMEOUT {
+            if condition() {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        condition()
+    }
+
+    /// Test function to run many nodes on a simulated network, without real sockets.
+    ///
+    /// This function installs a `SimNetwork`, starts a tracker and `n_nodes` nodes that
+    /// enter the network through it, then partitions the network in two and has a node
+    /// holding a longer chain join one side. It checks every node found neighbours and
+    /// got datagrams, that the longer chain only spreads through its side, and that every
+    /// node adopts it once the network heals. The same seed drops the same datagrams on
+    /// every run.
+    ///
+    /// # Arguments
+    /// * `n_nodes` - Number of regular nodes to start.
+    /// * `seed` - Seed of the network's latency, loss and port choices.
+    pub async fn test_sim_gossip(n_nodes: usize, seed: u64) {
+        info!("Starting simulated gossip test with {} nodes", n_nodes);
+
+        let network = SimNetwork::new(seed);
+        network.set_latency(Duration::from_millis(5), Duration::from_millis(20));
+        network.set_drop_rate(0.01);
+        transport::install(Some(Arc::new(network.clone())));
+
+        // Every node is trusted, so the tracker hands out no admission puzzles, and nodes
+        // gossip every second without slowing down, so greetings are answered promptly.
+        // They all start from the genesis block of the longer chain.
+        let longer = bench::synthetic_chain(LONGER_CHAIN);
+        let genesis = Chain::from_blocks(longer.blocks()[..1].to_vec(), longer.difficulty).expect("the genesis block is a chain");
+        let builder = |address: String, role: Role| Node::builder()
+            .with_role(role)
+            .with_address(address)
+            .with_admission_difficulty(0)
+            .with_gossip_interval(1)
+            .with_max_gossip_interval(1)
+            .with_trackers(vec![TRACKER_ADDRESS.to_owned()])
+            .with_chain(genesis.clone());
+
+        // Create the tracker
+        let mut tracker = builder(TRACKER_ADDRESS.to_owned(), Role::Tracker).build().expect("trackers build");
+        let mut stops = vec![tracker.stop_handle()];
+        tokio::spawn(async move {
+            let _ = tracker.node_loop().await;
+        });
+
+        // Create the nodes, which enter the network through the tracker
+        let mut metrics = vec![];
+        for i in 0..n_nodes {
+            let mut node = builder(node_address(i), Role::Node).build().expect("nodes build");
+            metrics.push(node.metrics_handle());
+            stops.push(node.stop_handle());
+            tokio::spawn(async move {
+                let _ = node.enter_and_node_loop().await;
+            });
+        }
+
+        let found = settle(|| metrics.iter().all(|metrics| {
+            let snapshot = metrics.snapshot();
+            snapshot.peers > 0 && snapshot.bytes_in > 0
+        })).await;
+        report("After bootstrap", &metrics);
+        assert!(found, "every node finds neighbours and is delivered datagrams");
+
+        // Split the nodes in two halves, the tracker on the left one, where a node holding
+        // a longer chain joins
+        let mut left: Vec<String> = (0..n_nodes).step_by(2).map(node_address).collect();
+        let right: Vec<String> = (1..n_nodes).step_by(2).map(node_address).collect();
+        left.extend([TRACKER_ADDRESS.to_owned(), node_address(n_nodes)]);
+        network.partition(&[left, right]);
+        let mut holder = builder(node_address(n_nodes), Role::Node).with_chain(longer.clone()).build().expect("nodes build");
+        metrics.push(holder.metrics_handle());
+        stops.push(holder.stop_handle());
+        tokio::spawn(async move {
+            let _ = holder.enter_and_node_loop().await;
+        });
+
+        let length = longer.len() as u64;
+        let spread = settle(|| heights(&metrics).iter().step_by(2).all(|height| *height == length)).await;
+        report("During partition", &metrics);
+        info!("Heights during partition: {:?}", heights(&metrics));
+        assert!(spread, "the longer chain spreads through its side");
+        assert!(
+            heights(&metrics).iter().skip(1).step_by(2).all(|height| *height < length),
+            "the longer chain does not cross the partition",
+        );
+
+        // Once healed, every node adopts the longer chain
+        network.heal();
+        let converged = settle(|| heights(&metrics).iter().all(|height| *height == length)).await;
+        report("After healing", &metrics);
+        info!("Heights after healing: {:?}", heights(&metrics));
+        assert!(converged, "every node adopts the longer chain");
+
+        let (delivered, dropped) = network.stats();
+        info!("Simulated network delivered {} datagrams and dropped {}", delivered, dropped);
+        assert!(delivered > dropped, "the network delivers most datagrams");
+        for stop in stops {
+            stop.stop();
+        }
+        transport::install(None);
+    }
+}
@@ -0,0 +1,91 @@
+pub mod test_state {
+
+    use crate::{
+        Chain,
+        bench::bench::bench,
+        chain::{
+            chain::chain::BlockCheckError,
+            config::config::ChainConfig,
+            state::state::{self, ChainState},
+        },
+        miner::miner::miner::MiningDigest,
+        record::record::record::Record,
+    };
+
+    use tracing::info;
+
+    /// Test function to check every block commits to the state of the chain once it is
+    /// applied, that blocks committing to another state are refused, that proofs of the
+    /// owner of a coin and of the last record under a key hold under the root, that
+    /// chains reloaded pruned are refused until their state is restored from a snapshot,
+    /// and that blocks committing to no state are refused from the height the chain
+    /// requires roots.
+    pub fn test_state() {
+        let mut chain = Chain::new();
+        let mut miner = bench::miner_for(&chain);
+        for _ in 0..2 {
+            bench::add_next(&mut chain, &mut miner);
+            assert_eq!(chain.get_last_block().state_root, chain.state_root());
+        }
+        let early = chain.snapshot();
+        let owner = miner.wallet.get_pub_key();
+        miner.push_record(miner.wallet.sign_record(Record::new("name".to_string(), "humble".to_string(), owner.clone()), chain.network()));
+        let digest = bench::mine_next(&chain, &mut miner);
+        let mut forged = digest.get_block();
+        forged.state_root = ChainState::new().root();
+        let refused = chain.add_block(MiningDigest::new(forged, digest.get_nonce()));
+        assert!(matches!(refused, Err(BlockCheckError::WrongStateRoot { index: 4, .. })));
+        chain.add_block(digest).expect("the block commits to the state it leads to");
+        assert_eq!(ChainState::of(chain.blocks()).root(), chain.state_root());
+        info!("Every block commits to the state it leads to");
+
+        // Proofs against the root of the last block.
+        let root = chain.get_last_block().state_root;
+        let coin = chain.coins_of(&owner)[0];
+        let proof = chain.state().prove(&state::coin_key(&coin));
+        assert!(proof.verify(&root));
+        let mut stolen = proof.clone();
+        stolen.value = Some("00".repeat(64));
+        assert!(!stolen.verify(&root));
+        let (record, _) = chain.find_record("name").expect("the record was mined");
+        let proof = chain.state().prove(&state::record_key("name"));
+        assert_eq!(proof.value, Some(record.id()));
+        assert!(proof.verify(&root));
+        let absent = chain.state().prove(&state::record_key("missing"));
+        assert!(absent.value.is_none() && absent.verify(&root));
+        info!("Proofs hold under the state root");
+
+        // Pruning keeps the state, reloading a pruned chain cannot rebuild it.
+        chain.prune(1);
+        assert_eq!(chain.state_root(), root);
+        let json = serde_json::to_string(&chain).expect("chains serialize");
+        let mut reloaded: Chain = serde_json::from_str(&json).expect("chains deserialize");
+        assert!(chain.state().is_complete() && !reloaded.state().is_complete());
+        assert!(matches!(reloaded.verify_state(), Err(BlockCheckError::WrongStateRoot { .. })));
+        assert!(ChainConfig::default().verify(&reloaded).is_err());
+        let next = bench::mine_next(&chain, &mut miner);
+        assert!(matches!(reloaded.clone().add_block(next.clone()), Err(BlockCheckError::WrongStateRoot { .. })));
+        info!("Chains reloaded pruned are refused without their state");
+
+        // Snapshots restore the state, once they match the root of their block.
+        let mut forged = chain.snapshot();
+        forged.leaves.pop();
+        assert!(matches!(reloaded.restore_state(forged), Err(BlockCheckError::WrongStateRoot { .. })));
+        let mut replayed = reloaded.clone();
+        replayed.restore_state(early).expect("the blocks past the snapshot are replayed");
+        assert_eq!(replayed.state_root(), root);
+        reloaded.restore_state(chain.snapshot()).expect("the snapshot matches the root of the last block");
+        assert_eq!(reloaded.state_root(), root);
+        ChainConfig::default().verify(&reloaded).expect("the state is restored");
+        reloaded.add_block(next).expect("the roots of new blocks are checked again");
+        info!("Snapshots restore the state of pruned chains");
+
+        // Blocks committing to no state are refused from the height the chain requires roots.
+        let mut headers = chain.headers();
+        headers.last_mut().expect("chains hold blocks").state_root.clear();
+        ChainConfig::default().verify_headers(&headers).expect("roots are optional by default");
+        let required = ChainConfig { state_roots_from: Some(1), ..ChainConfig::default() };
+        assert!(matches!(required.verify_headers(&headers), Err(BlockCheckError::MissingStateRoot(4))));
+        info!("State test passed");
+    }
+}
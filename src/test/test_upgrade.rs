@@ -0,0 +1,46 @@
+pub mod test_upgrade {
+
+    use crate::{
+        Chain,
+        bench::bench::bench,
+        chain::{
+            block::block::block::{Upgrade, BLOCK_VERSION},
+            chain::chain::BlockCheckError,
+            config::config::ChainConfig,
+            network::network::NetworkId,
+        },
+        miner::miner::miner::MiningDigest,
+    };
+
+    use tracing::info;
+
+    /// Test function to check blocks only extend chains of the network they were mined
+    /// for, even once relabelled, and that the version of the block format follows the
+    /// upgrades of the chain, chains of another version being refused.
+    pub fn test_upgrade() {
+        let mut testnet = Chain::for_network(&NetworkId::testnet());
+        let mut mainnet = Chain::new();
+        let mut miner = bench::miner_for(&testnet);
+        let digest = bench::mine_next(&testnet, &mut miner);
+        assert!(matches!(mainnet.add_block(digest.clone()), Err(BlockCheckError::WrongNetwork { index: 2, .. })));
+        let mut relabelled = digest.get_block();
+        relabelled.network = NetworkId::mainnet();
+        assert!(mainnet.add_block(MiningDigest::new(relabelled, digest.get_nonce())).is_err());
+        testnet.add_block(digest).expect("mined blocks extend the chain of their network");
+        assert_eq!(testnet.get_last_block().network, NetworkId::testnet());
+        info!("A block mined for testnet only extends testnet chains");
+
+        // The version of the blocks changes at the height of the upgrade.
+        let config = ChainConfig { upgrades: vec![Upgrade { height: 3, version: BLOCK_VERSION + 1 }], ..ChainConfig::default() };
+        let mut miner = bench::miner_for(&mainnet);
+        miner.set_upgrades(config.upgrades.clone());
+        for _ in 0..3 {
+            bench::add_next(&mut mainnet, &mut miner);
+        }
+        let versions: Vec<u32> = mainnet.blocks().iter().skip(1).map(|block| block.version).collect();
+        assert_eq!(versions, vec![BLOCK_VERSION, BLOCK_VERSION + 1, BLOCK_VERSION + 1]);
+        config.verify(&mainnet).expect("the chain follows its upgrades");
+        assert!(matches!(ChainConfig::default().verify(&mainnet), Err(BlockCheckError::WrongVersion { index: 3, .. })));
+        info!("Upgrade test passed");
+    }
+}
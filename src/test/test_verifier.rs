@@ -0,0 +1,141 @@
+pub mod test_verifier {
+
+    use crate::{
+        Chain,
+        bench::bench::bench,
+        chain::{chain::chain::{self, BlockCheckError}, config::config::{ChainConfig, Consensus}},
+        node::verifier::verifier::{self, Verifier},
+    };
+
+    use std::time::Duration;
+
+    use base64::{Engine as _, engine::general_purpose};
+    use tracing::info;
+
+    /// Returns `chain` with `field` of the block at `index` overwritten by `value`.
+    fn tamper(chain: &Chain, index: usize, field: &str, value: String) -> Chain {
+        let mut json = serde_json::to_value(chain).expect("chains serialize");
+        json["blocks"][index][field] = serde_json::Value::from(value);
+        serde_json::from_value(json).expect("tampered chains deserialize")
+    }
+
+    /// Test function to check a node's `Verifier` only goes through new blocks, rolls
+    /// back forks, rejects tampered chains and checks the state roots of chains sharing
+    /// pruned blocks with the one verified last, and that audits catch chains broken below
+    /// what was verified.
+    pub async fn test_verifier() {
+        let mut verifier = Verifier::new(ChainConfig::default(), None);
+        let chain = bench::synthetic_chain(5);
+        let verified = verifier.verify(&chain).expect("mined chains verify");
+        assert_eq!(verified.n_verified(), chain.len());
+        verifier.commit(verified);
+        info!("Verified a chain of {} blocks from the genesis", chain.len());
+
+        // Extensions are verified past the chain verified last only.
+        let mut longer = chain.clone();
+        bench::extend(&mut longer, 3);
+        let verified = verifier.verify(&longer).expect("extensions verify");
+        assert_eq!(verified.n_verified(), 3);
+        assert!(!verified.full);
+        verifier.commit(verified);
+
+        // Forks are rolled back to where they branch off.
+        let mut fork = chain.clone();
+        bench::extend(&mut fork, 4);
+        let verified = verifier.verify(&fork).expect("forks verify");
+        assert_eq!(verified.n_verified(), 4);
+        assert!(!verified.full);
+        verifier.commit(verified);
+        assert_eq!(verifier.height(), fork.len());
+        info!("Verified an extension and a fork past their common blocks only");
+
+        // Rolled back to the blocks it shares with another chain, the verifier holds their
+        // state, and verifies the other chain past them only.
+        assert!(verifier.roll_back(chain.blocks()));
+        assert_eq!(verifier.height(), chain.len());
+        assert_eq!(verifier.state().root(), chain.state_root());
+        let verified = verifier.verify(&longer).expect("the rolled back blocks are forgotten");
+        assert_eq!(verified.n_verified(), 3);
+        assert!(!verifier.roll_back(&longer.blocks()[..chain.len() + 1]));
+        assert_eq!(verifier.height(), 0);
+        verifier.commit(verifier.verify(&fork).expect("forks verify"));
+        info!("Rolled the verified chain back");
+
+        // Tampered blocks are rejected, leaving the verified chain as it was.
+        let mut extended = fork.clone();
+        bench::extend(&mut extended, 2);
+        let tampered = tamper(&extended, fork.len() + 1, "previous_hash", "f".repeat(64));
+        assert!(verifier.verify(&tampered).is_err());
+        assert_eq!(verifier.height(), fork.len());
+        // So are blocks whose hash is not the one their nonce gives, whatever it starts with.
+        let mut next = fork.clone();
+        bench::extend(&mut next, 1);
+        let forged = tamper(&next, fork.len(), "hash", "0".repeat(64));
+        assert!(matches!(verifier.verify(&forged), Err(BlockCheckError::WrongHash { .. })));
+        let forged = tamper(&extended, fork.len(), "data", String::new());
+        assert!(matches!(verifier.verify(&forged), Err(BlockCheckError::WrongHash { .. })));
+        let mut pruned = extended.clone();
+        pruned.prune(1);
+        assert!(matches!(verifier.verify(&pruned), Err(BlockCheckError::Pruned(_))));
+        // And chains claiming another difficulty than their blocks raised it to.
+        let mut lowered = next.clone();
+        lowered.difficulty = 0;
+        assert!(matches!(verifier.verify(&lowered), Err(BlockCheckError::WrongDifficulty { .. })));
+        let headers = next.headers();
+        assert!(chain::verify_headers(&headers, next.start_difficulty(), next.difficulty).is_ok());
+        assert!(chain::verify_headers(&headers, next.start_difficulty(), next.difficulty + 1).is_err());
+        // Blocks are replayed into the state of the chain verified last, to check their roots.
+        let forged = tamper(&next, fork.len(), "state_root", "0".repeat(64));
+        assert!(matches!(verifier.verify(&forged), Err(BlockCheckError::WrongStateRoot { .. })));
+        let mut ours = fork.clone();
+        ours.prune(2);
+        bench::extend(&mut ours, 2);
+        let json = serde_json::to_string(&ours).expect("chains serialize");
+        let received: Chain = serde_json::from_str(&json).expect("chains deserialize");
+        assert!(!received.state().is_complete());
+        let mut follower = Verifier::new(ChainConfig::default(), None);
+        follower.commit(follower.verify(&fork).expect("forks verify"));
+        follower.commit(follower.verify(&received).expect("the pruned blocks were verified already"));
+        let adopted = received.with_state(follower.state().clone());
+        assert_eq!(adopted.state_root(), adopted.get_last_block().state_root);
+        ChainConfig::default().verify(&adopted).expect("adopted chains take the state they were replayed into");
+
+        // Sealed blocks are held to what they hold, not only to the hash their seal signs.
+        let mut sealed = Chain::new();
+        let mut authority = bench::miner_for(&sealed);
+        for _ in 0..2 {
+            authority.set_chain_meta(sealed.get_len(), sealed.difficulty, sealed.shared_blocks());
+            let digest = authority.seal(&sealed.get_last_block()).expect("the chain meta was just set");
+            sealed.add_block(digest).expect("sealed blocks extend the chain");
+        }
+        let rules = ChainConfig {
+            consensus: Consensus::PoA,
+            authorities: vec![general_purpose::STANDARD.encode(authority.wallet.get_pub_key())],
+            ..ChainConfig::default()
+        };
+        Verifier::new(rules.clone(), None).verify(&sealed).expect("sealed chains verify");
+        let forged = tamper(&sealed, 2, "data", "forged".to_string());
+        assert!(matches!(Verifier::new(rules, None).verify(&forged), Err(BlockCheckError::WrongHash { .. })));
+        let verified = verifier.verify(&next).expect("extensions verify");
+        assert_eq!(verified.n_verified(), 1);
+
+        // Blocks below the ones verified are never checked again, but audits catch them.
+        let broken = tamper(&fork, 2, "previous_hash", "f".repeat(64));
+        assert!(verifier.verify(&broken).is_ok());
+        assert!(verifier::audit(&fork, &ChainConfig::default(), None).result.is_ok());
+        let mut auditor = Verifier::new(ChainConfig::default(), Some(1));
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert!(auditor.audit_due());
+        auditor.start_audit(broken, None);
+        assert!(!auditor.audit_due());
+        let report = loop {
+            if let Some(report) = auditor.poll_audit() {
+                break report;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert!(report.result.is_err());
+        assert_eq!(report.height, fork.len());
+        info!("Verifier test passed");
+    }
+}
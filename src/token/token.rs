@@ -0,0 +1,129 @@
+pub mod token {
+
+    use std::{fmt, str::FromStr};
+
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use sha2::{digest::Output, Sha256};
+    use thiserror::Error;
+
+    /// Length of a token, in bytes.
+    pub const TOKEN_LEN: usize = 32;
+    /// Length of the hex encoding of a token, the form it takes on the wire and in
+    /// signed payloads.
+    pub const TOKEN_HEX_LEN: usize = 2 * TOKEN_LEN;
+
+    #[derive(Error, Debug, PartialEq)]
+    pub enum TokenError {
+        #[error("A token is {TOKEN_HEX_LEN} hex digits long, got {0} characters.")]
+        WrongLength(usize),
+        #[error("A token is made of hex digits, got `{0}`.")]
+        NotHex(char),
+    }
+
+    /// A coin, named after the SHA-256 digest it was minted from: the hash of the block
+    /// that paid it as a mining prize, or the allocation of a genesis file.
+    ///
+    /// Tokens are kept as their raw 32 bytes, and shown, sent and signed as 64 lowercase
+    /// hex digits, the format coins always had, so existing chains and signatures stay valid.
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct Token([u8; TOKEN_LEN]);
+
+    impl Token {
+        pub fn new(bytes: [u8; TOKEN_LEN]) -> Self {
+            Token(bytes)
+        }
+
+        pub fn as_bytes(&self) -> &[u8; TOKEN_LEN] {
+            &self.0
+        }
+
+        /// Parses a token in the legacy format, a string of 64 hex digits, in either case.
+        /// Only the lowercase form is ever written.
+        ///
+        /// # Returns
+        /// * `Result<Token, TokenError>` - The token, or why `legacy` is not one.
+        pub fn parse_legacy(legacy: &str) -> Result<Self, TokenError> {
+            if legacy.len() != TOKEN_HEX_LEN {
+                return Err(TokenError::WrongLength(legacy.chars().count()));
+            }
+            let mut bytes = [0u8; TOKEN_LEN];
+            let digits = legacy.as_bytes();
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = (hex_digit(digits[2 * i])? << 4) | hex_digit(digits[2 * i + 1])?;
+            }
+            Ok(Token(bytes))
+        }
+
+        /// Parses the tokens written one after the other, as the coins of a transaction
+        /// are on the wire.
+        pub fn parse_joined(joined: &str) -> Result<Vec<Self>, TokenError> {
+            if !joined.len().is_multiple_of(TOKEN_HEX_LEN) || !joined.is_ascii() {
+                return Err(TokenError::WrongLength(joined.chars().count()));
+            }
+            joined
+                .as_bytes()
+                .chunks(TOKEN_HEX_LEN)
+                .map(|chunk| Token::parse_legacy(std::str::from_utf8(chunk).expect("ASCII was checked")))
+                .collect()
+        }
+    }
+
+    /// Value of the hex digit `digit`.
+    fn hex_digit(digit: u8) -> Result<u8, TokenError> {
+        (digit as char)
+            .to_digit(16)
+            .map(|value| value as u8)
+            .ok_or(TokenError::NotHex(digit as char))
+    }
+
+    impl From<[u8; TOKEN_LEN]> for Token {
+        fn from(bytes: [u8; TOKEN_LEN]) -> Self {
+            Token(bytes)
+        }
+    }
+
+    impl From<Output<Sha256>> for Token {
+        fn from(digest: Output<Sha256>) -> Self {
+            Token(digest.into())
+        }
+    }
+
+    impl From<Token> for [u8; TOKEN_LEN] {
+        fn from(token: Token) -> Self {
+            token.0
+        }
+    }
+
+    impl FromStr for Token {
+        type Err = TokenError;
+        fn from_str(string: &str) -> Result<Self, Self::Err> {
+            Token::parse_legacy(string)
+        }
+    }
+
+    impl fmt::Display for Token {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+        }
+    }
+
+    impl fmt::Debug for Token {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Token({})", self)
+        }
+    }
+
+    /// Tokens are serialized as their hex string, like coins were.
+    impl Serialize for Token {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Token {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let string = String::deserialize(deserializer)?;
+            Token::parse_legacy(&string).map_err(de::Error::custom)
+        }
+    }
+}
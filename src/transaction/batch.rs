@@ -0,0 +1,159 @@
+pub mod batch {
+
+    use crate::{
+        chain::{block::block::block::InvalidTransactionErr, network::network::NetworkId},
+        clock::clock::clock,
+        token::token::token::Token,
+        transaction::transaction::transaction::Transaction,
+    };
+
+    use std::collections::HashSet;
+
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+    use thiserror::Error;
+
+    /// Most transfers a batch carries.
+    pub const MAX_TRANSFERS: usize = 64;
+    /// Starts the bytes a batch signs, after the network id, so they never match the ones
+    /// of a transaction: those go on with the sender's key, which starts with 4.
+    const BATCH_TAG: &[u8] = b"batch\0";
+
+    #[derive(Error, Debug)]
+    pub enum InvalidBatchErr {
+        #[error("A batch needs at least one transfer.")]
+        NoTransfers,
+        #[error("A batch carries at most {MAX_TRANSFERS} transfers, got {0}.")]
+        TooManyTransfers(usize),
+        #[error("Transfer {0} of the batch sends no coin.")]
+        NoCoins(usize),
+        #[error("The batch sends the coin {0} more than once.")]
+        RepeatedCoin(Token),
+        #[error(transparent)]
+        Transfer(InvalidTransactionErr),
+    }
+
+    /// Coins a batch sends to one receiver.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Transfer {
+        pub receiver: Vec<u8>,
+        pub coins: Vec<Token>,
+    }
+
+    /// Transfers from one sender to many receivers, signed once and carried by a block as
+    /// a single entry, e.g. an exchange or a faucet paying out. The batch takes a single
+    /// nonce, which must exceed the one of its sender's last transaction like the nonce of
+    /// a `Transaction` does.
+    ///
+    /// Once in a block, every transfer counts as a transaction of the sender to its
+    /// receiver, dated and nonced like the batch. See `transactions`.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct BatchTransaction {
+        pub sender: Vec<u8>,
+        pub timestamp: u64,
+        pub nonce: u64,
+        pub transfers: Vec<Transfer>,
+        pub signature: Option<Vec<u8>>,
+    }
+
+    impl BatchTransaction {
+        pub fn new(sender: Vec<u8>, transfers: Vec<Transfer>) -> Self {
+            BatchTransaction {
+                sender,
+                timestamp: clock::now(),
+                nonce: 0,
+                transfers,
+                signature: None,
+            }
+        }
+
+        /// Sets the nonce of an unsigned batch. See `Chain::next_nonce`.
+        pub fn with_nonce(mut self, nonce: u64) -> Self {
+            self.nonce = nonce;
+            self
+        }
+
+        /// The bytes the sender signs: the NUL terminated id of the network the batch is
+        /// meant for, `BATCH_TAG`, then sender, timestamp, nonce and every transfer. Keys
+        /// and coin lists are prefixed by their length, so no two batches sign the same bytes.
+        /// Lengths, timestamp and nonce are big-endian, so hosts of either byte order sign
+        /// the same bytes.
+        pub fn signed_bytes(&self, network: &NetworkId) -> Vec<u8> {
+            let mut bytes = [
+                network.as_str().as_bytes(),
+                &[0],
+                BATCH_TAG,
+                &(self.sender.len() as u64).to_be_bytes(),
+                self.sender.as_slice(),
+                &self.timestamp.to_be_bytes(),
+                &self.nonce.to_be_bytes(),
+            ].concat();
+            for transfer in &self.transfers {
+                bytes.extend_from_slice(&(transfer.receiver.len() as u64).to_be_bytes());
+                bytes.extend_from_slice(&transfer.receiver);
+                bytes.extend_from_slice(&(transfer.coins.len() as u64).to_be_bytes());
+                for coin in &transfer.coins {
+                    bytes.extend_from_slice(coin.as_bytes());
+                }
+            }
+            bytes
+        }
+
+        /// Whether the batch carries a signature of `signed_bytes` by its sender, for
+        /// `network`. Batches signed for another network fail.
+        pub fn verify_signature(&self, network: &NetworkId) -> bool {
+            let Some(signature) = &self.signature else {
+                return false;
+            };
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &self.sender)
+                .verify(&self.signed_bytes(network), signature)
+                .is_ok()
+        }
+
+        /// Identifies the batch by the SHA-256 of its wire format, JSON.
+        ///
+        /// # Returns
+        /// * `String` - The hex encoded digest.
+        pub fn id(&self) -> String {
+            let json = serde_json::to_vec(self).expect("batches serialize");
+            format!("{:x}", Sha256::digest(json))
+        }
+
+        /// Checks the batch is well formed: it carries between 1 and `MAX_TRANSFERS`
+        /// transfers, each sending at least one coin, and no coin twice.
+        pub fn check(&self) -> Result<(), InvalidBatchErr> {
+            match self.transfers.len() {
+                0 => return Err(InvalidBatchErr::NoTransfers),
+                n if n > MAX_TRANSFERS => return Err(InvalidBatchErr::TooManyTransfers(n)),
+                _ => (),
+            }
+            let mut coins = HashSet::new();
+            for (i, transfer) in self.transfers.iter().enumerate() {
+                if transfer.coins.is_empty() {
+                    return Err(InvalidBatchErr::NoCoins(i));
+                }
+                if let Some(coin) = transfer.coins.iter().find(|coin| !coins.insert(**coin)) {
+                    return Err(InvalidBatchErr::RepeatedCoin(*coin));
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns every transfer as an unsigned transaction of the sender, dated and
+        /// nonced like the batch, which is how the chain tracks the coins it moves.
+        pub fn transactions(&self) -> Vec<Transaction> {
+            self.transfers
+                .iter()
+                .map(|transfer| Transaction {
+                    sender: self.sender.clone(),
+                    receiver: transfer.receiver.clone(),
+                    timestamp: self.timestamp,
+                    coins: transfer.coins.clone(),
+                    nonce: self.nonce,
+                    signature: None,
+                })
+                .collect()
+        }
+    }
+}
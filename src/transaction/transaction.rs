@@ -1,22 +1,28 @@
 pub mod transaction {
-    
-    use crate::node::reply::reply::Reply;
-    use crate::Chain;
+
+    use crate::{
+        chain::{block::block::block::N_TRANSACTION_PARAMS, network::network::NetworkId},
+        clock::clock::clock,
+        token::token::token::{Token, TokenError},
+    };
 
     use std::{
         fmt,
         num::ParseIntError,
-        time::{SystemTime, 
-            UNIX_EPOCH},
     };
     use thiserror::Error;
     use base64::{Engine as _, engine::general_purpose};
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+    use sha2::{Digest, Sha256};
 
 
     #[derive(Error, Debug, derive_more::From, derive_more::Display)]    
     pub enum TransactionFromBase64Error {
         Base64Error(base64::DecodeError),
         ParseError(ParseIntError),
+        TokenError(TokenError),
+        #[from(ignore)]
+        MissingFields(usize),
     }
 
     #[derive(Clone)]
@@ -24,36 +30,88 @@ pub mod transaction {
         pub sender: Vec<u8>,
         pub receiver: Vec<u8>,
         pub timestamp: u64,
-        pub coins: Vec<String>,
+        pub coins: Vec<Token>,
+        /// Counter of the sender's transactions. It must exceed the nonce of every earlier
+        /// transaction of the sender in the chain, so a signed transaction cannot be replayed.
+        pub nonce: u64,
         pub signature: Option<Vec<u8>>,
     }
 
     impl Transaction {
-        pub fn new(sender: Vec<u8>, receiver: Vec<u8>, coins: Vec<String>) -> Self {
-            let now = SystemTime::now()
-                         .duration_since(UNIX_EPOCH)
-                         .unwrap()
-                         .as_secs();
+        pub fn new(sender: Vec<u8>, receiver: Vec<u8>, coins: Vec<Token>) -> Self {
+            let now = clock::now();
             Transaction {
                 sender,
                 receiver,
                 timestamp: now,
                 coins,
+                nonce: 0,
                 signature: None,
             }
         }
+
+        /// Sets the nonce of an unsigned transaction. See `Chain::next_nonce`.
+        pub fn with_nonce(mut self, nonce: u64) -> Self {
+            self.nonce = nonce;
+            self
+        }
+
+        /// The bytes the sender signs: the id of the network the transaction is meant
+        /// for, then sender, receiver, timestamp, nonce and coins, the latter hex encoded.
+        /// Timestamp and nonce are big-endian, so hosts of either byte order sign the same
+        /// bytes.
+        /// The id is NUL terminated, which it never holds, so no two networks sign the
+        /// same bytes.
+        pub fn signed_bytes(&self, network: &NetworkId) -> Vec<u8> {
+            let mut bytes = [
+                network.as_str().as_bytes(),
+                &[0],
+                self.sender.as_slice(),
+                self.receiver.as_slice(),
+                &self.timestamp.to_be_bytes(),
+                &self.nonce.to_be_bytes(),
+            ].concat();
+            for coin in &self.coins {
+                bytes.extend_from_slice(coin.to_string().as_bytes());
+            }
+            bytes
+        }
+
+        /// Whether the transaction carries a signature of `signed_bytes` by its sender, for
+        /// `network`. Transactions signed for another network fail.
+        pub fn verify_signature(&self, network: &NetworkId) -> bool {
+            let Some(signature) = &self.signature else {
+                return false;
+            };
+            UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &self.sender)
+                .verify(&self.signed_bytes(network), signature)
+                .is_ok()
+        }
+
+        /// Identifies the transaction by the SHA-256 of its wire format.
+        ///
+        /// # Returns
+        /// * `String` - The hex encoded digest.
+        pub fn id(&self) -> String {
+            let str_transaction: String = self.clone().into();
+            format!("{:x}", Sha256::digest(str_transaction.as_bytes()))
+        }
     }
 
     impl TryFrom<String> for Transaction {
         type Error = TransactionFromBase64Error;
         fn try_from(string: String) -> Result<Self, Self::Error> {
             let params: Vec<&str> = string.as_str().split(';').collect();
-            let signature = general_purpose::STANDARD.decode(params[4]).ok();
+            if params.len() < N_TRANSACTION_PARAMS {
+                return Err(TransactionFromBase64Error::MissingFields(params.len()));
+            }
+            let signature = general_purpose::STANDARD.decode(params[5]).ok();
             Ok(Transaction {
                 sender: general_purpose::STANDARD.decode(params[0])?, 
                 receiver: general_purpose::STANDARD.decode(params[1])?,
-                coins: vec![params[2].to_string().clone()],
+                coins: Token::parse_joined(params[2])?,
                 timestamp: params[3].parse::<u64>()?,
+                nonce: params[4].parse::<u64>()?,
                 signature,
             })
         }
@@ -61,7 +119,7 @@ pub mod transaction {
 
     impl Into<String> for Transaction {
         fn into(self) -> String {
-            let joined_coins = self.coins.join("");
+            let joined_coins: String = self.coins.iter().map(Token::to_string).collect();
             let signature = match &self.signature {
                 Some(sig) => general_purpose::STANDARD.encode(&self
                     .signature
@@ -71,11 +129,12 @@ pub mod transaction {
                 ).to_string(),
                 None => "".to_string(),
             };
-            format!("{};{};{};{};{};", 
+            format!("{};{};{};{};{};{};", 
                 general_purpose::STANDARD.encode(&self.sender).to_string(), 
                 general_purpose::STANDARD.encode(&self.receiver).to_string(),
                 joined_coins,
                 self.timestamp.to_string(),
+                self.nonce,
                 signature,
             )
         }
@@ -84,17 +143,7 @@ pub mod transaction {
     impl fmt::Display for Transaction {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
             write!(f, "timestamp: {}, sender: {:?}, receiver: {:?}, coins: {}", 
-                    self.timestamp, self.sender, self.receiver, self.coins.join(" "))
-        }
-    }
-
-    impl Reply for Transaction {
-        fn as_transaction(&mut self) -> Option<&mut Transaction> {
-            Some(self)
-        }
-
-        fn as_chain(&mut self) -> Option<&mut Chain> {
-            None
+                    self.timestamp, self.sender, self.receiver, self.coins.iter().map(Token::to_string).collect::<Vec<_>>().join(" "))
         }
     }
 
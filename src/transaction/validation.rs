@@ -0,0 +1,139 @@
+pub mod validation {
+
+    use crate::{
+        chain::{block::block::block, chain::chain::Chain, config::config::MintPolicy, network::network::NetworkId},
+        transaction::transaction::transaction::Transaction,
+    };
+
+    use std::fmt;
+
+    use serde::{Deserialize, Serialize};
+
+    /// Seconds a transaction stays valid after its timestamp. Older ones are dropped.
+    pub const TRANSACTION_TTL: u64 = 3600;
+    /// Seconds a transaction may be dated ahead of the clock of the node checking it.
+    pub const MAX_CLOCK_DRIFT: u64 = 300;
+
+    /// A check a transaction goes through before a node relays it to the miners.
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Check {
+        /// The sender signed it, for the network of the node checking it.
+        Signature,
+        /// The sender owns every coin it spends, or is a mint authority creating them.
+        Ownership,
+        /// No other transaction waiting to be mined spends the same coins.
+        DoubleSpend,
+        /// It is neither older than `TRANSACTION_TTL` nor dated in the future. Only the node
+        /// a transaction is submitted to checks it, as relays may run on other clocks.
+        Expiry,
+        /// Its nonce exceeds the one of the sender's last transaction, so it is no replay.
+        Nonce,
+    }
+
+    impl fmt::Display for Check {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Check::Signature => write!(f, "signature"),
+                Check::Ownership => write!(f, "ownership"),
+                Check::DoubleSpend => write!(f, "double spend"),
+                Check::Expiry => write!(f, "expiry"),
+                Check::Nonce => write!(f, "nonce"),
+            }
+        }
+    }
+
+    /// How a transaction fared on a `Check`.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct CheckOutcome {
+        pub check: Check,
+        /// Why the check failed, `None` if it passed.
+        pub error: Option<String>,
+    }
+
+    /// Outcome of every check of a transaction, so wallets can tell users exactly what
+    /// is wrong with it.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ValidationReport {
+        /// Id of the transaction. See `Transaction::id`.
+        pub id: String,
+        pub checks: Vec<CheckOutcome>,
+    }
+
+    impl ValidationReport {
+        /// Whether the transaction passed every check.
+        pub fn is_valid(&self) -> bool {
+            self.checks.iter().all(|outcome| outcome.error.is_none())
+        }
+
+        /// Whether the transaction passed every check but `check`.
+        pub fn is_valid_except(&self, check: Check) -> bool {
+            self.failures().all(|(failed, _)| failed == check)
+        }
+
+        /// The checks the transaction failed, with why.
+        pub fn failures(&self) -> impl Iterator<Item = (Check, &str)> {
+            self.checks
+                .iter()
+                .filter_map(|outcome| outcome.error.as_deref().map(|error| (outcome.check, error)))
+        }
+    }
+
+    /// Runs every `Check` on `transaction`, without stopping at the first failure.
+    ///
+    /// # Arguments
+    /// * `transaction` - The transaction to check.
+    /// * `chain` - The chain the coins are looked up in.
+    /// * `pending` - Transactions waiting to be mined. The ones already in `chain` are
+    ///   ignored.
+    /// * `now` - The current time, in seconds since the UNIX epoch.
+    /// * `network` - The network the transaction must be signed for.
+    /// * `mint` - Who may create coins on the chain.
+    pub fn validate<'a>(
+        transaction: &Transaction,
+        chain: &Chain,
+        pending: impl IntoIterator<Item = &'a Transaction>,
+        now: u64,
+        network: &NetworkId,
+        mint: &MintPolicy,
+    ) -> ValidationReport {
+        let id = transaction.id();
+        let signature = match transaction.signature {
+            None => Some("The transaction is not signed.".to_string()),
+            Some(_) if !transaction.verify_signature(network) => {
+                Some(format!("The signature does not match the sender, or is not meant for {}.", network))
+            },
+            Some(_) => None,
+        };
+        let ownership = mint.check_transaction(transaction.clone(), chain.blocks())
+            .err()
+            .map(|e| e.to_string());
+        let conflict = pending
+            .into_iter()
+            .filter(|other| other.coins.iter().any(|coin| transaction.coins.contains(coin)))
+            .map(Transaction::id)
+            .find(|other| *other != id && chain.find_entry(other).is_none());
+        let double_spend = conflict.map(|other| format!("Transaction {} already spends the same coins.", other));
+        let expiry = if transaction.timestamp + TRANSACTION_TTL < now {
+            Some(format!("The transaction expired {} seconds ago.", now - transaction.timestamp - TRANSACTION_TTL))
+        } else if transaction.timestamp > now + MAX_CLOCK_DRIFT {
+            Some(format!("The transaction is dated {} seconds in the future.", transaction.timestamp - now))
+        } else {
+            None
+        };
+        let nonce = block::check_nonce(transaction, &block::last_nonces(chain.blocks_from(0)))
+            .err()
+            .map(|e| e.to_string());
+        let checks = [
+            (Check::Signature, signature),
+            (Check::Ownership, ownership),
+            (Check::DoubleSpend, double_spend),
+            (Check::Expiry, expiry),
+            (Check::Nonce, nonce),
+        ];
+        ValidationReport {
+            id,
+            checks: checks.into_iter().map(|(check, error)| CheckOutcome { check, error }).collect(),
+        }
+    }
+}
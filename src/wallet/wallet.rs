@@ -1,54 +1,111 @@
 pub mod wallet {
 
     use crate::transaction::transaction::transaction::Transaction;
+    use crate::transaction::batch::batch::BatchTransaction;
+    use crate::record::record::record::Record;
+    use crate::chain::block::block::block::{Block, Seal};
+    use crate::token::token::token::Token;
+    use crate::chain::network::network::NetworkId;
+    use crate::chain::config::config::{Checkpoint, SignedCheckpoint};
  
     use ring::rand::{SystemRandom};
+    use ring::error::KeyRejected;
     use ring::signature::{KeyPair, EcdsaKeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
     use std::fmt;
+    use base64::{Engine as _, engine::general_purpose};
+    use sha2::{Digest, Sha256};
+    use zeroize::Zeroizing;
 
     pub struct Wallet {
         //pub key_pair: Ed25519KeyPair,
         pub key_pair: EcdsaKeyPair,
-        pub coins: Vec<String>,
+        pub coins: Vec<Token>,
         rng: SystemRandom,
+        /// The PKCS#8 document the key pair was read from, to save the wallet. Wiped
+        /// when the wallet is dropped.
+        pkcs8: Zeroizing<Vec<u8>>,
     }
 
+    #[derive(Debug)]
     pub enum TransactionErr {
         InsuficientBalance,
     }
 
-    fn generate_key_pair() -> (EcdsaKeyPair, SystemRandom) {
+    fn generate_key_pair() -> (EcdsaKeyPair, SystemRandom, Zeroizing<Vec<u8>>) {
         let rng = SystemRandom::new();
         let pkcs8_bytes = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
         let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8_bytes.as_ref(), &rng)
         .unwrap();  
-        (key_pair, rng)
+        (key_pair, rng, Zeroizing::new(pkcs8_bytes.as_ref().to_vec()))
     }
 
 
+    impl Default for Wallet {
+        fn default() -> Self {
+            Wallet::new()
+        }
+    }
+
     impl Wallet {
         pub fn new() -> Self{
-            let (key_pair, rng) = generate_key_pair();
+            let (key_pair, rng, pkcs8) = generate_key_pair();
             Wallet {
                 coins: vec![],
                 key_pair,
                 rng,
+                pkcs8,
             }
         }
 
+        /// Restores a wallet from the PKCS#8 document of its key pair. See `Wallet::pkcs8`.
+        ///
+        /// # Returns
+        /// * `Result<Wallet, KeyRejected>` - The wallet, without coins, or an error if
+        ///   `pkcs8` does not hold an ECDSA P-256 key pair.
+        pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, KeyRejected> {
+            let rng = SystemRandom::new();
+            let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8, &rng)?;
+            Ok(Wallet {
+                coins: vec![],
+                key_pair,
+                rng,
+                pkcs8: Zeroizing::new(pkcs8.to_vec()),
+            })
+        }
+
+        /// Restores a wallet from the base64 encoding of its PKCS#8 document, as
+        /// `Wallet::to_base64` writes it. The decoded document is wiped once read.
+        ///
+        /// # Returns
+        /// * `Option<Wallet>` - The wallet, without coins, or `None` if `encoded` does not
+        ///   hold a key pair.
+        pub fn from_base64(encoded: &str) -> Option<Self> {
+            let pkcs8 = Zeroizing::new(general_purpose::STANDARD.decode(encoded.trim()).ok()?);
+            Wallet::from_pkcs8(&pkcs8).ok()
+        }
+
+        /// The PKCS#8 document of the wallet's key pair, which is all it takes to restore it.
+        pub fn pkcs8(&self) -> &[u8] {
+            &self.pkcs8
+        }
+
+        /// The base64 encoding of `Wallet::pkcs8`, the format wallet key files hold. Wiped
+        /// once dropped.
+        pub fn to_base64(&self) -> Zeroizing<String> {
+            Zeroizing::new(general_purpose::STANDARD.encode(self.pkcs8()))
+        }
+
 
         pub fn get_pub_key(&self) -> Vec<u8> {
             self.key_pair.public_key().as_ref().to_vec().clone() 
         }
 
-        pub fn add_coin(&mut self, coin: String) {
+        pub fn add_coin(&mut self, coin: Token) {
             self.coins.push(coin);
         }
 
-        pub fn get_coins(&mut self) -> Vec<String> {
-            self.coins.iter().map(|coin| {
-                                coin.clone()
-                            }).collect()
+        pub fn get_coins(&mut self) -> Vec<Token> {
+            self.coins.clone()
          }
 
         fn check_balance(&self, amount: usize) -> Result<(), TransactionErr> {
@@ -58,29 +115,70 @@ pub mod wallet {
             Ok(())
         }
 
-        pub fn sign(&self, mut transaction: Transaction) -> Transaction {
-            let arr_sender: &[u8] = &transaction.sender.clone();
-            let arr_receiver: &[u8] = &transaction.receiver.clone();
-            let members = [arr_sender,
-                arr_receiver, 
-                &transaction.timestamp.to_ne_bytes()];
-            let mut vec: Vec<u8> = members.concat();
-            let coins: Vec<Vec<u8>> = transaction.coins
-                .iter()
-                .map(|coin| { coin.as_bytes().to_vec() })
-                .collect();
-            for mut i in coins {
-                vec.append(&mut i);
-            }
-            let bytes = &vec; 
-            transaction.signature = Some(self.key_pair.sign(&self.rng, bytes).unwrap().as_ref().to_vec());
+        /// Signs `transaction` for `network`. Nodes of any other network reject it.
+        pub fn sign(&self, mut transaction: Transaction, network: &NetworkId) -> Transaction {
+            let bytes = transaction.signed_bytes(network);
+            transaction.signature = Some(self.key_pair.sign(&self.rng, &bytes).unwrap().as_ref().to_vec());
             transaction
         }
-            
-        pub fn submit_transaction(&mut self, receiver: Vec<u8>, amount: usize) 
+
+        /// Signs `batch` for `network`, once for all its transfers. Nodes of any other
+        /// network reject it.
+        pub fn sign_batch(&self, mut batch: BatchTransaction, network: &NetworkId) -> BatchTransaction {
+            let bytes = batch.signed_bytes(network);
+            batch.signature = Some(self.key_pair.sign(&self.rng, &bytes).unwrap().as_ref().to_vec());
+            batch
+        }
+
+        /// Signs `record` for `network`. Nodes of any other network reject it.
+        pub fn sign_record(&self, mut record: Record, network: &NetworkId) -> Record {
+            let bytes = record.signed_bytes(network);
+            record.signature = Some(self.key_pair.sign(&self.rng, &bytes).unwrap().as_ref().to_vec());
+            record
+        }
+
+        /// Seals `block` as an authority of a proof-of-authority chain: its hash becomes
+        /// `Block::content_hash`, signed with this wallet's key.
+        pub fn seal(&self, mut block: Block) -> Block {
+            block.hash = block.content_hash();
+            let signature = self.key_pair.sign(&self.rng, block.hash.as_bytes()).unwrap().as_ref().to_vec();
+            block.seal = Some(Seal { authority: self.get_pub_key(), signature });
+            block
+        }
+
+        /// Signs `checkpoint` for `network`, as one of the chain's checkpoint publishers.
+        pub fn sign_checkpoint(&self, checkpoint: Checkpoint, network: &NetworkId) -> SignedCheckpoint {
+            let bytes = SignedCheckpoint::signed_bytes(&checkpoint, network);
+            let signature = self.key_pair.sign(&self.rng, &bytes).unwrap().as_ref().to_vec();
+            SignedCheckpoint { checkpoint, publisher: self.get_pub_key(), signature }
+        }
+
+        /// Creates a coin and sends it to `receiver`, as a mint authority of the chain (see
+        /// `MintPolicy::authorities`). The coin is named after the network, this wallet and
+        /// `nonce`, which must exceed the nonce of its last transaction, so every mint
+        /// creates another coin.
+        pub fn mint(&self, receiver: Vec<u8>, nonce: u64, network: &NetworkId) -> Transaction {
+            let address = general_purpose::STANDARD.encode(self.get_pub_key());
+            let coin = Token::from(Sha256::digest(format!("{}:{}:{}", network, address, nonce)));
+            self.sign(Transaction::new(self.get_pub_key(), receiver, vec![coin]).with_nonce(nonce), network)
+        }
+
+        /// Sends `amount` of this wallet's coins to `receiver`.
+        ///
+        /// # Arguments
+        /// * `receiver` - Public key of the wallet receiving the coins.
+        /// * `amount` - Number of coins sent.
+        /// * `nonce` - Must exceed the nonce of this wallet's last transaction on the chain
+        ///   (see `Chain::next_nonce`), or miners drop the transaction as a replay.
+        /// * `network` - The network the transaction is signed for.
+        ///
+        /// # Returns
+        /// * `Result<Transaction, TransactionErr>` - The signed transaction, or
+        ///   `InsuficientBalance` if the wallet holds fewer than `amount` coins.
+        pub fn submit_transaction(&mut self, receiver: Vec<u8>, amount: usize, nonce: u64, network: &NetworkId)
                     -> Result<Transaction, TransactionErr> {
             self.check_balance(amount)?;
-            let coins: Vec<String> = (0..amount).map(|_| {
+            let coins: Vec<Token> = (0..amount).map(|_| {
                 self.coins.pop().unwrap()
             }).collect();
                                    
@@ -88,13 +186,13 @@ pub mod wallet {
                 self.key_pair.public_key().as_ref().to_vec(), 
                 receiver, 
                 coins,
-            )))
+            ).with_nonce(nonce), network))
         }
     }
 
     impl fmt::Display for Wallet {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { 
-            let joint_coins = self.coins.join(",\n");
+            let joint_coins = self.coins.iter().map(Token::to_string).collect::<Vec<_>>().join(",\n");
             write!(f, "{{\n{}}}", joint_coins)
         }
     }